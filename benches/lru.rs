@@ -0,0 +1,64 @@
+//! Throughput of `ConcurrentLru` against one `LruCache` behind a `Mutex`, with
+//! several threads mixing lookups and inserts. Run with `cargo +nightly bench`.
+#![feature(test)]
+
+extern crate test;
+
+use std::{hint::black_box, thread};
+
+use my_std::{
+    cache::{ConcurrentLru, LruCache},
+    sync::Mutex,
+};
+use test::Bencher;
+
+const THREADS: u64 = 4;
+const OPS_PER_THREAD: u64 = 10_000;
+const CAPACITY: usize = 1_024;
+// Twice the capacity, so about half the lookups miss and insert.
+const KEYS: u64 = 2_048;
+
+// A cheap per-thread pseudo-random key sequence.
+fn keys(seed: u64) -> impl Iterator<Item = u64> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..OPS_PER_THREAD).map(move |_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % KEYS
+    })
+}
+
+fn run(op: impl Fn(u64) + Sync) {
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            let op = &op;
+            s.spawn(move || keys(t + 1).for_each(op));
+        }
+    });
+}
+
+#[bench]
+fn concurrent_lru(b: &mut Bencher) {
+    let cache = ConcurrentLru::new(CAPACITY);
+    b.iter(|| {
+        run(|key| {
+            if cache.get(&key, |v| black_box(*v)).is_none() {
+                cache.put(key, key);
+            }
+        })
+    });
+}
+
+#[bench]
+fn mutex_lru(b: &mut Bencher) {
+    let cache = Mutex::new(LruCache::new(CAPACITY));
+    b.iter(|| {
+        run(|key| {
+            let mut cache = cache.lock().unwrap();
+            if cache.get(&key).map(|v| black_box(*v)).is_none() {
+                cache.put(key, key);
+            }
+        })
+    });
+}
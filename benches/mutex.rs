@@ -0,0 +1,44 @@
+//! Contended locking: threads taking turns incrementing one counter, with the
+//! crate's `Mutex` against `std::sync::Mutex` and a pure `SpinLock`. Run with
+//! `cargo +nightly bench`.
+#![feature(test)]
+
+extern crate test;
+
+use std::{sync, thread};
+
+use my_std::sync::{Mutex, SpinLock};
+use test::Bencher;
+
+const THREADS: usize = 4;
+const INCREMENTS: usize = 10_000;
+
+fn contend(increment: impl Fn() + Sync) {
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| {
+                for _ in 0..INCREMENTS {
+                    increment();
+                }
+            });
+        }
+    });
+}
+
+#[bench]
+fn mutex(b: &mut Bencher) {
+    let counter = Mutex::new(0);
+    b.iter(|| contend(|| *counter.lock().unwrap() += 1));
+}
+
+#[bench]
+fn std_mutex(b: &mut Bencher) {
+    let counter = sync::Mutex::new(0);
+    b.iter(|| contend(|| *counter.lock().unwrap() += 1));
+}
+
+#[bench]
+fn spin_lock(b: &mut Bencher) {
+    let counter = SpinLock::new(0);
+    b.iter(|| contend(|| *counter.lock() += 1));
+}
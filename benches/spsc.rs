@@ -0,0 +1,61 @@
+//! Throughput of `spsc::channel` against `channel::bounded`, moving values
+//! from one thread to another. Run with `cargo +nightly bench`.
+#![feature(test)]
+
+extern crate test;
+
+use std::{
+    hint::black_box,
+    sync::mpsc::{TryRecvError, TrySendError},
+    thread,
+};
+
+use my_std::{channel, spsc};
+use test::Bencher;
+
+const MESSAGES: u64 = 100_000;
+const CAPACITY: usize = 1_024;
+
+#[bench]
+fn spsc_ring(b: &mut Bencher) {
+    b.iter(|| {
+        let (mut tx, mut rx) = spsc::channel(CAPACITY);
+        thread::scope(|s| {
+            s.spawn(move || {
+                for i in 0..MESSAGES {
+                    let mut value = i;
+                    while let Err(TrySendError::Full(v)) = tx.push(value) {
+                        value = v;
+                        thread::yield_now();
+                    }
+                }
+            });
+            loop {
+                match rx.pop() {
+                    Ok(value) => {
+                        black_box(value);
+                    }
+                    Err(TryRecvError::Empty) => thread::yield_now(),
+                    Err(TryRecvError::Disconnected) => break,
+                }
+            }
+        });
+    });
+}
+
+#[bench]
+fn bounded_channel(b: &mut Bencher) {
+    b.iter(|| {
+        let (tx, rx) = channel::bounded(CAPACITY);
+        thread::scope(|s| {
+            s.spawn(move || {
+                for i in 0..MESSAGES {
+                    tx.send(i).unwrap();
+                }
+            });
+            for value in rx {
+                black_box(value);
+            }
+        });
+    });
+}
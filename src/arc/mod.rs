@@ -0,0 +1,725 @@
+use ::alloc::alloc::{self, Layout};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::{PhantomData, Unsize},
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ops::{CoerceUnsized, Deref},
+    ptr::{self, NonNull},
+};
+
+use crate::boxed::MyBox;
+use crate::compat::{
+    self,
+    atomic::{self, AtomicUsize, Ordering::*},
+};
+
+// Counts above this abort the process, long before they could overflow: every
+// thread would have to leak clones concurrently to get from here to `usize::MAX`.
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+
+// Value of the weak count while `MyArc::is_unique` checks both counts.
+const LOCKED: usize = usize::MAX;
+
+// The shared allocation. All strong pointers together hold one weak reference,
+// so the allocation is freed when the weak count drops to zero, which happens
+// after the value has been dropped.
+//
+// `repr(C)` gives `ArcInner<MaybeUninit<T>>` the layout of `ArcInner<T>`, which
+// `MyArc::new_cyclic` relies on.
+#[repr(C)]
+struct ArcInner<T: ?Sized> {
+    strong: AtomicUsize,
+    weak: AtomicUsize,
+    value: T,
+}
+
+/// A thread-safe reference-counted pointer.
+///
+/// Like [`MyRc`](crate::rc::MyRc), but the counts are atomic, so clones can be
+/// sent to and dropped on other threads. Dropping a pointer decrements the
+/// count with `Release` ordering, and the thread dropping the last one runs an
+/// `Acquire` fence before dropping the value: every use of the value through
+/// other pointers happens before it is dropped.
+///
+/// A [`Weak`] pointer, made with [`MyArc::downgrade`], keeps the allocation but
+/// not the value alive, and can be upgraded back as long as a strong pointer
+/// exists.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::arc::MyArc;
+///
+/// let shared = MyArc::new(vec![1, 2, 3]);
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let shared = MyArc::clone(&shared);
+///         thread::spawn(move || shared.iter().sum::<i32>())
+///     })
+///     .collect();
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), 6);
+/// }
+/// assert_eq!(MyArc::strong_count(&shared), 1);
+///
+/// let weak = MyArc::downgrade(&shared);
+/// assert_eq!(weak.upgrade().as_deref(), Some(&vec![1, 2, 3]));
+/// drop(shared);
+/// assert!(weak.upgrade().is_none());
+/// ```
+pub struct MyArc<T: ?Sized> {
+    ptr: NonNull<ArcInner<T>>,
+    _marker: PhantomData<ArcInner<T>>,
+}
+
+unsafe impl<T: ?Sized + Send + Sync> Send for MyArc<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for MyArc<T> {}
+
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<MyArc<U>> for MyArc<T> {}
+
+impl<T> MyArc<T> {
+    /// Move `value` into a new shared allocation.
+    pub fn new(value: T) -> Self {
+        let inner = MyBox::new(ArcInner {
+            strong: AtomicUsize::new(1),
+            weak: AtomicUsize::new(1),
+            value,
+        });
+        unsafe { Self::from_inner(MyBox::into_raw(inner)) }
+    }
+
+    /// Build a value that holds a [`Weak`] pointer to itself.
+    ///
+    /// `f` receives the weak pointer before the value exists, so upgrading it
+    /// inside `f` returns `None`; it works as soon as `new_cyclic` returns.
+    pub fn new_cyclic<F>(f: F) -> Self
+    where
+        F: FnOnce(&Weak<T>) -> T,
+    {
+        // No strong pointer yet, and the weak reference of the strong pointers
+        // is the one handed to `f`.
+        let uninit = MyBox::new(ArcInner {
+            strong: AtomicUsize::new(0),
+            weak: AtomicUsize::new(1),
+            value: MaybeUninit::<T>::uninit(),
+        });
+        let ptr = MyBox::into_raw(uninit).cast::<ArcInner<T>>();
+        let weak = Weak {
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+        };
+        // If `f` panics, dropping `weak` frees the allocation.
+        let value = f(&weak);
+        unsafe {
+            ptr::write(&mut (*ptr).value, value);
+            // `Release` publishes the value to threads upgrading clones of `weak`.
+            (*ptr).strong.store(1, Release);
+        }
+        mem::forget(weak);
+        unsafe { Self::from_inner(ptr) }
+    }
+
+    /// Return the value if `this` is its only strong pointer, or `this` itself
+    /// otherwise. Weak pointers can no longer be upgraded afterwards.
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if this
+            .inner()
+            .strong
+            .compare_exchange(1, 0, Relaxed, Relaxed)
+            .is_err()
+        {
+            return Err(this);
+        }
+        // Pairs with the `Release` decrements of the other strong pointers.
+        atomic::fence(Acquire);
+        let this = ManuallyDrop::new(this);
+        unsafe {
+            let value = ptr::read(&this.inner().value);
+            // Give up the weak reference held by the strong pointers.
+            drop(Weak { ptr: this.ptr });
+            Ok(value)
+        }
+    }
+
+    /// Return the value if `this` is its only strong pointer, or a clone of it
+    /// otherwise.
+    pub fn unwrap_or_clone(this: Self) -> T
+    where
+        T: Clone,
+    {
+        Self::try_unwrap(this).unwrap_or_else(|this| (*this).clone())
+    }
+
+    /// Turn `this` into a pointer to the value, keeping its strong reference.
+    /// [`from_raw`](MyArc::from_raw) turns it back.
+    pub fn into_raw(this: Self) -> *const T {
+        let this = ManuallyDrop::new(this);
+        &this.inner().value
+    }
+
+    /// Take back the strong reference given up by [`into_raw`](MyArc::into_raw).
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from `into_raw`, and each pointer be turned back once.
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        let inner = ptr.byte_sub(mem::offset_of!(ArcInner<T>, value));
+        Self::from_inner(inner.cast::<ArcInner<T>>().cast_mut())
+    }
+}
+
+impl<T: ?Sized> MyArc<T> {
+    unsafe fn from_inner(ptr: *mut ArcInner<T>) -> Self {
+        MyArc {
+            ptr: NonNull::new_unchecked(ptr),
+            _marker: PhantomData,
+        }
+    }
+
+    fn inner(&self) -> &ArcInner<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// A pointer to the value.
+    pub fn as_ptr(this: &Self) -> *const T {
+        &this.inner().value
+    }
+
+    /// Number of strong pointers to the value. Other threads may change it
+    /// right after it is read.
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().strong.load(Relaxed)
+    }
+
+    /// Number of [`Weak`] pointers to the value. Other threads may change it
+    /// right after it is read.
+    pub fn weak_count(this: &Self) -> usize {
+        let weak = this.inner().weak.load(Relaxed);
+        let strong = this.inner().strong.load(Relaxed);
+        if weak == LOCKED {
+            // `is_unique` only locks the count while it is 1.
+            0
+        } else {
+            weak - usize::from(strong > 0)
+        }
+    }
+
+    /// Return `true` if both pointers point to the same allocation.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        ptr::addr_eq(this.ptr.as_ptr(), other.ptr.as_ptr())
+    }
+
+    /// Make a [`Weak`] pointer to the value.
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        let weak = &this.inner().weak;
+        let mut current = weak.load(Relaxed);
+        loop {
+            if current == LOCKED {
+                compat::spin_loop();
+                current = weak.load(Relaxed);
+                continue;
+            }
+            if current > MAX_REFCOUNT {
+                compat::abort();
+            }
+            // `Acquire` pairs with the `Release` unlock in `is_unique`.
+            match weak.compare_exchange_weak(current, current + 1, Acquire, Relaxed) {
+                Ok(_) => return Weak { ptr: this.ptr },
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    // Return `true` if no other strong or weak pointer exists.
+    fn is_unique(&mut self) -> bool {
+        // Lock the weak count so no other thread can downgrade a strong pointer
+        // while the strong count is read: with both counts at 1 no other pointer
+        // exists, and none can be created from `self` since it is borrowed.
+        let inner = self.inner();
+        if inner
+            .weak
+            .compare_exchange(1, LOCKED, Acquire, Relaxed)
+            .is_err()
+        {
+            return false;
+        }
+        // `Acquire` pairs with the `Release` decrements of dropped strong
+        // pointers, so their writes happen before our access.
+        let unique = inner.strong.load(Acquire) == 1;
+        inner.weak.store(1, Release);
+        unique
+    }
+
+    /// A mutable reference to the value if `this` is its only pointer, weak
+    /// pointers included.
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if this.is_unique() {
+            Some(unsafe { &mut this.ptr.as_mut().value })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Clone> MyArc<T> {
+    /// A mutable reference to the value, cloning it into a new allocation
+    /// first if any other pointer shares it (clone-on-write).
+    pub fn make_mut(this: &mut Self) -> &mut T {
+        if !this.is_unique() {
+            *this = MyArc::new((**this).clone());
+        }
+        unsafe { &mut this.ptr.as_mut().value }
+    }
+}
+
+impl<T: ?Sized> Clone for MyArc<T> {
+    fn clone(&self) -> Self {
+        // A new pointer can only be made from an existing one, which keeps the
+        // value alive, so no ordering with other threads is needed.
+        if self.inner().strong.fetch_add(1, Relaxed) > MAX_REFCOUNT {
+            compat::abort();
+        }
+        MyArc {
+            ptr: self.ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+unsafe impl<#[may_dangle] T: ?Sized> Drop for MyArc<T> {
+    fn drop(&mut self) {
+        // `Release` makes our uses of the value happen before the decrement.
+        if self.inner().strong.fetch_sub(1, Release) != 1 {
+            return;
+        }
+        // The `Acquire` fence pairs with the `Release` decrements of every other
+        // pointer, so their uses of the value happen before it is dropped.
+        atomic::fence(Acquire);
+        unsafe {
+            ptr::drop_in_place(&mut (*self.ptr.as_ptr()).value);
+            // Give up the weak reference held by the strong pointers.
+            drop(Weak { ptr: self.ptr });
+        }
+    }
+}
+
+/// A pointer to a [`MyArc`] allocation that doesn't keep the value alive.
+///
+/// The allocation itself stays around until the last `Weak` is dropped, so
+/// [`Weak::upgrade`] can always tell whether the value is still alive.
+pub struct Weak<T: ?Sized> {
+    // Dangling for weak pointers made by `Weak::new`, which have no allocation.
+    ptr: NonNull<ArcInner<T>>,
+}
+
+unsafe impl<T: ?Sized + Send + Sync> Send for Weak<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for Weak<T> {}
+
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Weak<U>> for Weak<T> {}
+
+impl<T> Weak<T> {
+    /// Make a weak pointer that never upgrades, without allocating.
+    pub const fn new() -> Self {
+        Weak {
+            ptr: NonNull::without_provenance(core::num::NonZeroUsize::MAX),
+        }
+    }
+}
+
+impl<T> Default for Weak<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized> Weak<T> {
+    fn inner(&self) -> Option<&ArcInner<T>> {
+        if self.ptr.as_ptr().addr() == usize::MAX {
+            None
+        } else {
+            Some(unsafe { self.ptr.as_ref() })
+        }
+    }
+
+    /// Make a strong pointer to the value, or return `None` if it has already
+    /// been dropped.
+    pub fn upgrade(&self) -> Option<MyArc<T>> {
+        let strong = &self.inner()?.strong;
+        let mut current = strong.load(Relaxed);
+        loop {
+            // Once the count has reached zero the value is being dropped, and
+            // must not be brought back.
+            if current == 0 {
+                return None;
+            }
+            if current > MAX_REFCOUNT {
+                compat::abort();
+            }
+            match strong.compare_exchange_weak(current, current + 1, Acquire, Relaxed) {
+                Ok(_) => {
+                    return Some(MyArc {
+                        ptr: self.ptr,
+                        _marker: PhantomData,
+                    })
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Number of strong pointers to the value.
+    pub fn strong_count(&self) -> usize {
+        self.inner().map_or(0, |inner| inner.strong.load(Relaxed))
+    }
+
+    /// Return `true` if both pointers point to the same allocation, or were
+    /// both made by [`Weak::new`].
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        ptr::addr_eq(self.ptr.as_ptr(), other.ptr.as_ptr())
+    }
+}
+
+impl<T: ?Sized> Clone for Weak<T> {
+    fn clone(&self) -> Self {
+        if let Some(inner) = self.inner() {
+            // The weak count can't be locked here: `is_unique` only locks it
+            // while it is 1, which means no `Weak` exists.
+            if inner.weak.fetch_add(1, Relaxed) > MAX_REFCOUNT {
+                compat::abort();
+            }
+        }
+        Weak { ptr: self.ptr }
+    }
+}
+
+impl<T: ?Sized> Drop for Weak<T> {
+    fn drop(&mut self) {
+        let Some(inner) = self.inner() else {
+            return;
+        };
+        if inner.weak.fetch_sub(1, Release) != 1 {
+            return;
+        }
+        atomic::fence(Acquire);
+        // The value is already gone, only the memory is left to free. It was
+        // allocated by `MyBox::new` with the global allocator.
+        unsafe {
+            let layout = Layout::for_value_raw(self.ptr.as_ptr());
+            alloc::dealloc(self.ptr.as_ptr().cast(), layout);
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Weak<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(Weak)")
+    }
+}
+
+impl<T: ?Sized> Deref for MyArc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for MyArc<T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for MyArc<T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: Default> Default for MyArc<T> {
+    fn default() -> Self {
+        MyArc::new(T::default())
+    }
+}
+
+impl<T> From<T> for MyArc<T> {
+    fn from(value: T) -> Self {
+        MyArc::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MyArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for MyArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> fmt::Pointer for MyArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&&**self, f)
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for MyArc<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for MyArc<T> {}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for MyArc<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: ?Sized + Ord> Ord for MyArc<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: ?Sized + Hash> Hash for MyArc<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::alloc::vec::Vec;
+
+    use super::*;
+    use std::thread;
+
+    struct Noisy<'a>(&'a AtomicUsize);
+
+    impl Drop for Noisy<'_> {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, SeqCst);
+        }
+    }
+
+    #[test]
+    fn counts() {
+        let drops = AtomicUsize::new(0);
+        let a = MyArc::new(Noisy(&drops));
+        let b = a.clone();
+        let weak = MyArc::downgrade(&a);
+        let weak2 = weak.clone();
+        assert_eq!(MyArc::strong_count(&a), 2);
+        assert_eq!(MyArc::weak_count(&a), 2);
+        drop(a);
+        assert!(weak.upgrade().is_some());
+        drop(b);
+        assert_eq!(drops.load(SeqCst), 1);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(weak2.strong_count(), 0);
+        assert!(Weak::<u8>::new().upgrade().is_none());
+    }
+
+    #[test]
+    fn unique_access() {
+        let mut a = MyArc::new(1);
+        *MyArc::get_mut(&mut a).unwrap() += 1;
+        let weak = MyArc::downgrade(&a);
+        assert_eq!(MyArc::get_mut(&mut a), None);
+        *MyArc::make_mut(&mut a) += 1;
+        // `make_mut` moved `a` to a new allocation, leaving `weak` dangling.
+        assert_eq!((*a, weak.upgrade()), (3, None));
+
+        let b = a.clone();
+        let a = MyArc::try_unwrap(a).unwrap_err();
+        drop(b);
+        assert_eq!(MyArc::try_unwrap(a), Ok(3));
+    }
+
+    #[test]
+    fn new_cyclic() {
+        struct Worker {
+            me: Weak<Worker>,
+            id: u32,
+        }
+
+        let worker = MyArc::new_cyclic(|me| {
+            assert!(me.upgrade().is_none());
+            Worker {
+                me: me.clone(),
+                id: 7,
+            }
+        });
+        let me = worker.me.clone();
+        let id = thread::spawn(move || me.upgrade().map(|w| w.id))
+            .join()
+            .unwrap();
+        assert_eq!(id, Some(7));
+        assert_eq!(MyArc::weak_count(&worker), 1);
+    }
+
+    #[test]
+    fn unsized_targets() {
+        let slice: MyArc<[u32]> = MyArc::new([1, 2, 3]);
+        let weak = MyArc::downgrade(&slice);
+        assert_eq!(weak.upgrade().unwrap().len(), 3);
+        drop(slice);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn concurrent_clones_drop_once() {
+        let drops = AtomicUsize::new(0);
+        thread::scope(|s| {
+            let shared = MyArc::new(Noisy(&drops));
+            for _ in 0..8 {
+                let shared = shared.clone();
+                s.spawn(move || {
+                    let mut local = Vec::new();
+                    for _ in 0..1000 {
+                        local.push(shared.clone());
+                    }
+                    let weak = MyArc::downgrade(&shared);
+                    assert!(weak.upgrade().is_some());
+                });
+            }
+        });
+        assert_eq!(drops.load(SeqCst), 1);
+    }
+
+    #[test]
+    fn upgrade_races_with_last_drop() {
+        for _ in 0..200 {
+            let drops = AtomicUsize::new(0);
+            let shared = MyArc::new(Noisy(&drops));
+            let weak = MyArc::downgrade(&shared);
+            thread::scope(|s| {
+                s.spawn(move || drop(shared));
+                s.spawn(|| {
+                    // Either the upgrade wins and the value lives on until the
+                    // upgraded pointer is dropped, or it sees the value gone.
+                    if let Some(strong) = weak.upgrade() {
+                        assert_eq!(drops.load(SeqCst), 0);
+                        drop(strong);
+                    }
+                });
+            });
+            assert_eq!(drops.load(SeqCst), 1);
+        }
+    }
+}
+
+// Run with `RUSTFLAGS="--cfg loom" cargo test --release --lib arc::loom_tests`.
+// Loom runs each model under every interleaving of its threads, and reports a
+// data race on `Tracked::data` if the value is dropped while another thread
+// may still be reading it.
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use loom::{cell::UnsafeCell, sync::Arc, thread};
+
+    use super::*;
+
+    struct Tracked {
+        data: UnsafeCell<usize>,
+        drops: Arc<AtomicUsize>,
+    }
+
+    // Threads only read `data` while they hold a pointer, and `drop` is the
+    // only writer.
+    unsafe impl Sync for Tracked {}
+
+    impl Tracked {
+        fn new(drops: &Arc<AtomicUsize>) -> Self {
+            Tracked {
+                data: UnsafeCell::new(1),
+                drops: drops.clone(),
+            }
+        }
+
+        fn read(&self) -> usize {
+            self.data.with(|data| unsafe { *data })
+        }
+    }
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.data.with_mut(|data| unsafe { *data = 0 });
+            self.drops.fetch_add(1, Relaxed);
+        }
+    }
+
+    #[test]
+    fn clones_dropped_on_two_threads() {
+        loom::model(|| {
+            let drops = Arc::new(AtomicUsize::new(0));
+            let shared = MyArc::new(Tracked::new(&drops));
+            let other = shared.clone();
+            let handle = thread::spawn(move || {
+                assert_eq!(other.read(), 1);
+            });
+            assert_eq!(shared.read(), 1);
+            drop(shared);
+            handle.join().unwrap();
+            assert_eq!(drops.load(Relaxed), 1);
+        });
+    }
+
+    #[test]
+    fn upgrade_races_with_last_drop() {
+        loom::model(|| {
+            let drops = Arc::new(AtomicUsize::new(0));
+            let shared = MyArc::new(Tracked::new(&drops));
+            let weak = MyArc::downgrade(&shared);
+            let handle = thread::spawn(move || drop(shared));
+            if let Some(strong) = weak.upgrade() {
+                assert_eq!(strong.read(), 1);
+            }
+            handle.join().unwrap();
+            assert!(weak.upgrade().is_none());
+            assert_eq!(drops.load(Relaxed), 1);
+        });
+    }
+
+    #[test]
+    fn weak_and_strong_dropped_on_two_threads() {
+        loom::model(|| {
+            let drops = Arc::new(AtomicUsize::new(0));
+            let shared = MyArc::new(Tracked::new(&drops));
+            let weak = MyArc::downgrade(&shared);
+            let handle = thread::spawn(move || drop(weak));
+            drop(shared);
+            handle.join().unwrap();
+            assert_eq!(drops.load(Relaxed), 1);
+        });
+    }
+
+    #[test]
+    fn get_mut_after_other_thread_drops() {
+        loom::model(|| {
+            let drops = Arc::new(AtomicUsize::new(0));
+            let mut shared = MyArc::new(Tracked::new(&drops));
+            let other = shared.clone();
+            let weak = MyArc::downgrade(&shared);
+            let handle = thread::spawn(move || {
+                assert_eq!(other.read(), 1);
+                drop(other);
+                drop(weak);
+            });
+            // Whether or not the other thread is done, a successful `get_mut`
+            // must see its reads finished.
+            if let Some(tracked) = MyArc::get_mut(&mut shared) {
+                tracked.data.with_mut(|data| unsafe { *data = 2 });
+            }
+            handle.join().unwrap();
+            assert!(MyArc::get_mut(&mut shared).is_some());
+        });
+    }
+}
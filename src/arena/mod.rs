@@ -0,0 +1,214 @@
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::{cmp, mem, slice};
+
+// Size in bytes of the first chunk, later chunks double in size.
+const INITIAL_CHUNK_BYTES: usize = 4096;
+
+struct ChunkList<T> {
+    // The chunk new values are pushed into. It is never grown past its capacity,
+    // so values already in it never move.
+    current: Vec<T>,
+    rest: Vec<Vec<T>>,
+}
+
+impl<T> ChunkList<T> {
+    // Retire the current chunk and start a new one with room for at least `additional` values.
+    fn reserve(&mut self, additional: usize) {
+        let double = self
+            .current
+            .capacity()
+            .checked_mul(2)
+            .expect("capacity overflow");
+        let capacity = cmp::max(double, additional);
+        let chunk = mem::replace(&mut self.current, Vec::with_capacity(capacity));
+        self.rest.push(chunk);
+    }
+}
+
+/// A typed arena.
+/// It allocates values of a single type in large chunks and hands out references
+/// that live as long as the arena itself. Values are never freed individually:
+/// they are all dropped together when the arena is dropped.
+///
+/// Allocating from an arena is a bounds check and a write most of the time, which
+/// makes it a good fit for node-based structures that are built up and then
+/// thrown away as a whole.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::arena::Arena;
+///
+/// let arena = Arena::new();
+/// let a = arena.alloc(1);
+/// let b = arena.alloc(2);
+/// *a += 10;
+/// assert_eq!(*a + *b, 13);
+///
+/// let slice = arena.alloc_extend(0..3);
+/// assert_eq!(slice, &[0, 1, 2]);
+/// assert_eq!(arena.len(), 5);
+/// ```
+pub struct Arena<T> {
+    chunks: RefCell<ChunkList<T>>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Arena<T> {
+    /// Create a new, empty arena.
+    pub fn new() -> Self {
+        let size = cmp::max(1, mem::size_of::<T>());
+        Self::with_capacity(cmp::max(1, INITIAL_CHUNK_BYTES / size))
+    }
+
+    /// Create a new arena whose first chunk holds `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Arena {
+            chunks: RefCell::new(ChunkList {
+                current: Vec::with_capacity(cmp::max(1, capacity)),
+                rest: Vec::new(),
+            }),
+        }
+    }
+
+    /// Move a value into the arena and return a mutable reference to it.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        if chunks.current.len() == chunks.current.capacity() {
+            chunks.reserve(1);
+        }
+        chunks.current.push(value);
+        let value = chunks.current.last_mut().unwrap() as *mut T;
+        // SAFETY: the current chunk is never grown past its capacity, so the value
+        // won't move until the arena is dropped, and every call returns a
+        // reference to a different value.
+        unsafe { &mut *value }
+    }
+
+    /// Move every value of an iterator into the arena and return them as a
+    /// contiguous mutable slice.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_extend<I>(&self, iter: I) -> &mut [T]
+    where
+        I: IntoIterator<Item = T>,
+    {
+        // Collect first: the iterator could allocate from this arena itself.
+        let values: Vec<T> = iter.into_iter().collect();
+        let len = values.len();
+        let mut chunks = self.chunks.borrow_mut();
+        if chunks.current.capacity() - chunks.current.len() < len {
+            chunks.reserve(len);
+        }
+        let start = chunks.current.len();
+        chunks.current.extend(values);
+        let ptr = unsafe { chunks.current.as_mut_ptr().add(start) };
+        // SAFETY: same as in `alloc`, the values sit in the current chunk which
+        // never reallocates.
+        unsafe { slice::from_raw_parts_mut(ptr, len) }
+    }
+
+    /// Number of values allocated in the arena.
+    pub fn len(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        chunks.current.len() + chunks.rest.iter().map(Vec::len).sum::<usize>()
+    }
+
+    /// Return `true` if nothing has been allocated in the arena.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Consume the arena and return all of its values in allocation order.
+    pub fn into_vec(self) -> Vec<T> {
+        let chunks = self.chunks.into_inner();
+        let mut values = Vec::with_capacity(
+            chunks.current.len() + chunks.rest.iter().map(Vec::len).sum::<usize>(),
+        );
+        for chunk in chunks.rest {
+            values.extend(chunk);
+        }
+        values.extend(chunks.current);
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn alloc() {
+        let arena = Arena::with_capacity(2);
+        let refs: Vec<&mut i32> = (0..100).map(|i| arena.alloc(i)).collect();
+        // References stay valid while new chunks are added.
+        for (i, r) in refs.into_iter().enumerate() {
+            assert_eq!(*r, i as i32);
+        }
+        assert_eq!(arena.len(), 100);
+    }
+
+    #[test]
+    fn alloc_extend() {
+        let arena = Arena::with_capacity(4);
+        let a = arena.alloc(1);
+        let slice = arena.alloc_extend(vec![2, 3, 4, 5, 6]);
+        slice[0] = 20;
+        assert_eq!(slice, &[20, 3, 4, 5, 6]);
+        assert_eq!(*a, 1);
+        assert!(arena.alloc_extend(Vec::new()).is_empty());
+        assert_eq!(arena.into_vec(), vec![1, 20, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn drops_all_values() {
+        struct Counted<'a>(&'a Cell<usize>);
+
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        {
+            let arena = Arena::with_capacity(3);
+            for _ in 0..10 {
+                arena.alloc(Counted(&drops));
+            }
+            arena.alloc_extend((0..5).map(|_| Counted(&drops)));
+            assert_eq!(drops.get(), 0);
+        }
+        assert_eq!(drops.get(), 15);
+    }
+
+    #[test]
+    fn linked_nodes() {
+        // Nodes can point at each other through arena references.
+        struct Node<'a> {
+            value: i32,
+            next: Cell<Option<&'a Node<'a>>>,
+        }
+
+        let arena = Arena::new();
+        let a = arena.alloc(Node {
+            value: 1,
+            next: Cell::new(None),
+        });
+        let b = arena.alloc(Node {
+            value: 2,
+            next: Cell::new(Some(a)),
+        });
+        a.next.set(Some(b));
+        assert_eq!(a.next.get().unwrap().next.get().unwrap().value, 1);
+    }
+}
@@ -0,0 +1,446 @@
+use core::{fmt, mem::MaybeUninit, ops, ptr, slice};
+
+/// A fixed-capacity vector backed entirely by an inline `[MaybeUninit<T>; N]`.
+///
+/// It never allocates, so it can be used in `no_std` code, interrupt handlers and
+/// other places where the allocator is unavailable. Pushing to a full vector hands
+/// the element back instead of growing.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::array_vec::ArrayVec;
+///
+/// let mut vec: ArrayVec<i32, 2> = ArrayVec::new();
+/// assert_eq!(vec.try_push(1), Ok(()));
+/// assert_eq!(vec.try_push(2), Ok(()));
+/// assert_eq!(vec.try_push(3), Err(3));
+/// assert!(vec.is_full());
+/// assert_eq!(&*vec, &[1, 2]);
+/// ```
+pub struct ArrayVec<T, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayVec<T, N> {
+    /// Maximum number of elements the vector can hold.
+    pub const CAPACITY: usize = N;
+
+    /// Constructs a new, empty `ArrayVec`.
+    pub const fn new() -> Self {
+        ArrayVec {
+            data: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the vector.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector contains no elements.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements the vector can hold, always `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns `true` if the vector is full.
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Returns the number of elements that can still be pushed.
+    pub const fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+
+    /// Returns a raw pointer to the vector's buffer.
+    pub const fn as_ptr(&self) -> *const T {
+        self.data.as_ptr() as *const T
+    }
+
+    /// Returns an unsafe mutable pointer to the vector's buffer.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.data.as_mut_ptr() as *mut T
+    }
+
+    /// Appends an element to the back of the vector.
+    /// Returns the element back in `Err` if the vector is full.
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.data[self.len].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Appends an element to the back of the vector.
+    /// Panics if the vector is full.
+    pub fn push(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            panic!("ArrayVec is full (capacity is {N})");
+        }
+    }
+
+    /// Removes the last element from the vector and returns it, or [`None`] if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        unsafe { Some(self.data[self.len].assume_init_read()) }
+    }
+
+    /// Inserts an element at position `index`, shifting all elements after it to the right.
+    /// Returns the element back in `Err` if the vector is full.
+    /// Panics if `index > len`.
+    pub fn try_insert(&mut self, index: usize, element: T) -> Result<(), T> {
+        let len = self.len;
+        if index > len {
+            panic!("insertion index (is {index}) should be <= len (is {len})");
+        }
+        if self.is_full() {
+            return Err(element);
+        }
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(1), len - index);
+            ptr::write(p, element);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Inserts an element at position `index`, shifting all elements after it to the right.
+    /// Panics if `index > len` or if the vector is full.
+    pub fn insert(&mut self, index: usize, element: T) {
+        if self.try_insert(index, element).is_err() {
+            panic!("ArrayVec is full (capacity is {N})");
+        }
+    }
+
+    /// Removes and returns the element at position `index`,
+    /// shifting all elements after it to the left.
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        if index >= len {
+            panic!("removal index (is {index}) should be < len (is {len})");
+        }
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            let ret = ptr::read(p);
+            ptr::copy(p.add(1), p, len - index - 1);
+            self.len -= 1;
+            ret
+        }
+    }
+
+    /// Removes and returns the element at position `index`, replacing it with the
+    /// last element. This does not preserve ordering, but is O(1).
+    /// Panics if `index >= len`.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        if index >= len {
+            panic!("swap_remove index (is {index}) should be < len (is {len})");
+        }
+        self.swap(index, len - 1);
+        self.pop().unwrap()
+    }
+
+    /// Shortens the vector, keeping the first `len` elements and dropping the rest.
+    /// Has no effect if `len` is greater than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail =
+            ptr::slice_from_raw_parts_mut(unsafe { self.as_mut_ptr().add(len) }, self.len - len);
+        // Update the length first so a panicking destructor can't cause a double drop.
+        self.len = len;
+        unsafe {
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<T, const N: usize> Default for ArrayVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayVec<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> ops::Deref for ArrayVec<T, N> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+}
+
+impl<T, const N: usize> ops::DerefMut for ArrayVec<T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayVec<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for ArrayVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for ArrayVec<T, N> {}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut ArrayVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for ArrayVec<T, N> {
+    /// Panics if the iterator yields more elements than the remaining capacity.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for ArrayVec<T, N> {
+    /// Panics if the iterator yields more than `N` elements.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = ArrayVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+/// An iterator that moves the elements out of an [`ArrayVec`].
+pub struct IntoIter<T, const N: usize> {
+    vec: ArrayVec<T, N>,
+    index: usize,
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.index == self.vec.len {
+            return None;
+        }
+        self.index += 1;
+        unsafe { Some(self.vec.data[self.index - 1].assume_init_read()) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.vec.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        let (start, len) = (self.index, self.vec.len);
+        // Drop the elements that were not yielded and keep `ArrayVec`'s destructor
+        // away from the ones that were.
+        self.vec.len = 0;
+        unsafe {
+            let tail = ptr::slice_from_raw_parts_mut(self.vec.as_mut_ptr().add(start), len - start);
+            ptr::drop_in_place(tail);
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            vec: self,
+            index: 0,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, const N: usize> serde::Serialize for ArrayVec<T, N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+/// Fails on a sequence of more than `N` elements, instead of panicking like
+/// `collect`.
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, const N: usize> serde::Deserialize<'de> for ArrayVec<T, N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_bounded_seq(deserializer, N)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>, const N: usize> arbitrary::Arbitrary<'a> for ArrayVec<T, N> {
+    /// Stops at `N` elements rather than panicking.
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.take(N).collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.take(N).collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary + 'static, const N: usize> proptest::arbitrary::Arbitrary
+    for ArrayVec<T, N>
+{
+    type Parameters = T::Parameters;
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    /// Holds up to `N` elements.
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect((proptest::collection::SizeRange::from(0..=N), args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn push_pop() {
+        let mut v: ArrayVec<i32, 4> = ArrayVec::new();
+        v.push(1);
+        v.push(2);
+        v.push(3);
+        assert_eq!(v.pop(), Some(3));
+        assert_eq!(v.pop(), Some(2));
+        assert_eq!(v.pop(), Some(1));
+        assert_eq!(v.pop(), None);
+    }
+
+    #[test]
+    fn try_push_full() {
+        let mut v: ArrayVec<String, 1> = ArrayVec::new();
+        assert_eq!(v.try_push("foo".to_string()), Ok(()));
+        assert_eq!(v.try_push("bar".to_string()), Err("bar".to_string()));
+        assert_eq!(v.remaining_capacity(), 0);
+        assert_eq!(v.try_insert(0, "baz".to_string()), Err("baz".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "ArrayVec is full (capacity is 2)")]
+    fn push_full() {
+        let mut v: ArrayVec<i32, 2> = ArrayVec::new();
+        v.extend([1, 2, 3]);
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut v: ArrayVec<i32, 8> = [1, 2, 4].into_iter().collect();
+        v.insert(2, 3);
+        v.insert(0, 0);
+        assert_eq!(&*v, &[0, 1, 2, 3, 4]);
+        assert_eq!(v.remove(4), 4);
+        assert_eq!(v.swap_remove(0), 0);
+        assert_eq!(&*v, &[3, 1, 2]);
+    }
+
+    #[test]
+    fn drops_elements() {
+        let rc = Rc::new(());
+        {
+            let mut v: ArrayVec<Rc<()>, 4> = ArrayVec::new();
+            v.push(rc.clone());
+            v.push(rc.clone());
+            v.push(rc.clone());
+            v.truncate(1);
+            assert_eq!(Rc::strong_count(&rc), 2);
+            v.push(rc.clone());
+        }
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn into_iter() {
+        let rc = Rc::new(());
+        let v: ArrayVec<_, 3> = [rc.clone(), rc.clone(), rc.clone()].into_iter().collect();
+        let mut iter = v.into_iter();
+        assert!(iter.next().is_some());
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        drop(iter);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let values: ArrayVec<i32, 3> = [1, 2, 3].into_iter().collect();
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let back: ArrayVec<i32, 3> = serde_json::from_str(&json).unwrap();
+        assert!(back.iter().eq(values.iter()));
+        assert!(serde_json::from_str::<ArrayVec<i32, 2>>(&json).is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_stops_at_capacity() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes = [1, 5, 1, 6, 1, 7, 1, 8, 0];
+        let vec = ArrayVec::<u8, 3>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert_eq!(vec[..], [5, 6, 7]);
+        let vec = ArrayVec::<u8, 8>::arbitrary_take_rest(Unstructured::new(&bytes)).unwrap();
+        assert_eq!(vec[..], [5, 6, 7, 8]);
+    }
+}
@@ -0,0 +1,388 @@
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    mem::{self, align_of, size_of},
+    ptr,
+    sync::atomic::{self, AtomicU16, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering::*},
+};
+
+use crate::sync::Backoff;
+
+/// A cell holding a `Copy` value that threads can load, store and swap
+/// atomically, whatever its type.
+///
+/// When `T` has the size of a native atomic integer and at least its
+/// alignment, every operation is a single atomic instruction on its bytes.
+/// Other types go through one of a fixed set of global sequence locks, picked
+/// from the cell's address: loads read optimistically and retry if a writer
+/// got in the way, so they never block each other. The cell is always exactly
+/// as large as `T`.
+///
+/// [`is_lock_free`](AtomicCell::is_lock_free) tells which of the two a type
+/// gets. Every operation is sequentially consistent.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::atomic::AtomicCell;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// struct Point {
+///     x: u64,
+///     y: u64,
+/// }
+///
+/// let cell = AtomicCell::new(Point { x: 0, y: 0 });
+/// assert!(!AtomicCell::<Point>::is_lock_free());
+/// thread::scope(|s| {
+///     s.spawn(|| cell.store(Point { x: 1, y: 1 }));
+///     // A load never sees half of a store.
+///     let p = cell.load();
+///     assert_eq!(p.x, p.y);
+/// });
+/// assert_eq!(cell.swap(Point { x: 2, y: 2 }), Point { x: 1, y: 1 });
+///
+/// let n = AtomicCell::new(5u32);
+/// assert!(AtomicCell::<u32>::is_lock_free());
+/// assert_eq!(n.compare_exchange(5, 6), Ok(5));
+/// assert_eq!(n.compare_exchange(5, 7), Err(6));
+/// ```
+#[repr(transparent)]
+pub struct AtomicCell<T> {
+    value: UnsafeCell<T>,
+}
+
+// The value is only ever copied in and out whole, like through a `Mutex`.
+unsafe impl<T: Send> Send for AtomicCell<T> {}
+unsafe impl<T: Send> Sync for AtomicCell<T> {}
+
+// Can a `T` be handled as an `A`?
+const fn fits<T, A>() -> bool {
+    size_of::<T>() == size_of::<A>() && align_of::<T>() >= align_of::<A>()
+}
+
+// Evaluates `$native` with `$a` bound to the cell at `$ptr` as a native atomic,
+// whose integer type is `$int`, if `$t` fits one, or `$fallback` otherwise. The
+// sizes are constants, so only one branch is left after compilation.
+macro_rules! atomic {
+    ($t:ty, $ptr:expr, $a:ident, $int:ident, $native:expr, $fallback:expr) => {
+        loop {
+            atomic!(@try $t, $ptr, $a, $int, AtomicU8, u8, $native);
+            atomic!(@try $t, $ptr, $a, $int, AtomicU16, u16, $native);
+            atomic!(@try $t, $ptr, $a, $int, AtomicU32, u32, $native);
+            atomic!(@try $t, $ptr, $a, $int, AtomicU64, u64, $native);
+            break $fallback;
+        }
+    };
+    (@try $t:ty, $ptr:expr, $a:ident, $int:ident, $atomic:ty, $prim:ty, $native:expr) => {
+        if fits::<$t, $atomic>() {
+            #[allow(dead_code)]
+            type $int = $prim;
+            let $a = unsafe { &*($ptr as *const $atomic) };
+            break $native;
+        }
+    };
+}
+
+impl<T> AtomicCell<T> {
+    /// Create a cell holding `value`.
+    pub const fn new(value: T) -> Self {
+        AtomicCell {
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Return `true` if operations on an `AtomicCell<T>` are native atomic
+    /// instructions rather than going through a lock.
+    pub const fn is_lock_free() -> bool {
+        fits::<T, AtomicU8>()
+            || fits::<T, AtomicU16>()
+            || fits::<T, AtomicU32>()
+            || fits::<T, AtomicU64>()
+    }
+
+    /// A raw pointer to the value.
+    pub fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+
+    /// A mutable reference to the value. No other thread can be using the
+    /// cell, so this needs no synchronization.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Consume the cell and return the value.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    fn lock(&self) -> &'static SeqLock {
+        &LOCKS[self.as_ptr().addr() % LOCKS.len()]
+    }
+}
+
+impl<T: Copy> AtomicCell<T> {
+    /// Load the value.
+    pub fn load(&self) -> T {
+        atomic!(
+            T,
+            self.as_ptr(),
+            a,
+            Int,
+            unsafe { mem::transmute_copy::<Int, T>(&a.load(SeqCst)) },
+            {
+                let lock = self.lock();
+                if let Some(stamp) = lock.optimistic_read() {
+                    // May race with a writer, in which case the copy is thrown
+                    // away: `T` is `Copy`, so a torn copy is never used.
+                    let value = unsafe { ptr::read_volatile(self.as_ptr()) };
+                    if lock.validate_read(stamp) {
+                        return value;
+                    }
+                }
+                let _guard = lock.write();
+                unsafe { ptr::read(self.as_ptr()) }
+            }
+        )
+    }
+
+    /// Store `value`.
+    pub fn store(&self, value: T) {
+        atomic!(
+            T,
+            self.as_ptr(),
+            a,
+            Int,
+            a.store(unsafe { mem::transmute_copy::<T, Int>(&value) }, SeqCst),
+            {
+                let _guard = self.lock().write();
+                unsafe { ptr::write(self.as_ptr(), value) };
+            }
+        )
+    }
+
+    /// Store `value` and return the previous value.
+    pub fn swap(&self, value: T) -> T {
+        atomic!(
+            T,
+            self.as_ptr(),
+            a,
+            Int,
+            unsafe {
+                let old = a.swap(mem::transmute_copy::<T, Int>(&value), SeqCst);
+                mem::transmute_copy::<Int, T>(&old)
+            },
+            {
+                let _guard = self.lock().write();
+                unsafe { ptr::replace(self.as_ptr(), value) }
+            }
+        )
+    }
+}
+
+impl<T: Copy + Eq> AtomicCell<T> {
+    /// Store `new` if the value equals `current`. Return the previous value,
+    /// as `Ok` if `new` was stored and `Err` otherwise.
+    pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T> {
+        atomic!(
+            T,
+            self.as_ptr(),
+            a,
+            Int,
+            unsafe {
+                let mut expected = mem::transmute_copy::<T, Int>(&current);
+                let new = mem::transmute_copy::<T, Int>(&new);
+                loop {
+                    match a.compare_exchange(expected, new, SeqCst, SeqCst) {
+                        Ok(old) => break Ok(mem::transmute_copy::<Int, T>(&old)),
+                        Err(actual) => {
+                            // Equal values may still differ in their bytes, in
+                            // which case retry with the bytes found.
+                            let actual_value = mem::transmute_copy::<Int, T>(&actual);
+                            if actual_value != current {
+                                break Err(actual_value);
+                            }
+                            expected = actual;
+                        }
+                    }
+                }
+            },
+            {
+                let _guard = self.lock().write();
+                let old = unsafe { ptr::read(self.as_ptr()) };
+                if old == current {
+                    unsafe { ptr::write(self.as_ptr(), new) };
+                    Ok(old)
+                } else {
+                    Err(old)
+                }
+            }
+        )
+    }
+}
+
+impl<T: Default> Default for AtomicCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for AtomicCell<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for AtomicCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AtomicCell").field(&self.load()).finish()
+    }
+}
+
+// A sequence lock: the stamp is odd while a writer holds it, and grows by two
+// with every write, so a reader can tell whether a write overlapped its read.
+struct SeqLock {
+    stamp: AtomicUsize,
+}
+
+// Shared by all the cells that don't fit a native atomic. A prime number of
+// them, so cells laid out at regular strides spread over all of them.
+static LOCKS: [SeqLock; 67] = [const { SeqLock::new() }; 67];
+
+impl SeqLock {
+    const fn new() -> Self {
+        SeqLock {
+            stamp: AtomicUsize::new(0),
+        }
+    }
+
+    // The stamp to validate a read against, if no writer holds the lock.
+    fn optimistic_read(&self) -> Option<usize> {
+        let stamp = self.stamp.load(Acquire);
+        (stamp & 1 == 0).then_some(stamp)
+    }
+
+    // Return `true` if no write happened since `optimistic_read` gave `stamp`.
+    fn validate_read(&self, stamp: usize) -> bool {
+        // Keeps the read of the value before the second read of the stamp.
+        atomic::fence(Acquire);
+        self.stamp.load(Relaxed) == stamp
+    }
+
+    fn write(&self) -> SeqLockWriteGuard<'_> {
+        let mut backoff = Backoff::new();
+        loop {
+            let stamp = self.stamp.load(Relaxed);
+            if stamp & 1 == 0
+                && self
+                    .stamp
+                    .compare_exchange_weak(stamp, stamp | 1, Acquire, Relaxed)
+                    .is_ok()
+            {
+                // Keeps the odd stamp visible before any write to the value.
+                atomic::fence(Release);
+                return SeqLockWriteGuard { lock: self, stamp };
+            }
+            backoff.spin();
+        }
+    }
+}
+
+struct SeqLockWriteGuard<'a> {
+    lock: &'a SeqLock,
+    stamp: usize,
+}
+
+impl Drop for SeqLockWriteGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.stamp.store(self.stamp.wrapping_add(2), Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn native_types() {
+        assert!(AtomicCell::<u8>::is_lock_free());
+        assert!(AtomicCell::<f64>::is_lock_free());
+        assert!(AtomicCell::<Option<std::num::NonZeroU32>>::is_lock_free());
+        // Too loosely aligned for an `AtomicU32`.
+        assert!(!AtomicCell::<[u8; 4]>::is_lock_free());
+
+        let cell = AtomicCell::new('a');
+        cell.store('b');
+        assert_eq!(cell.swap('c'), 'b');
+        assert_eq!(cell.compare_exchange('c', 'd'), Ok('c'));
+        assert_eq!(cell.compare_exchange('c', 'e'), Err('d'));
+        assert_eq!(cell.into_inner(), 'd');
+
+        let float = AtomicCell::new(1.5f64);
+        float.store(float.load() * 2.0);
+        assert_eq!(float.load(), 3.0);
+    }
+
+    #[test]
+    fn locked_types() {
+        let cell = AtomicCell::new([1u8, 2, 3]);
+        assert_eq!(cell.swap([4, 5, 6]), [1, 2, 3]);
+        assert_eq!(cell.compare_exchange([4, 5, 6], [7, 8, 9]), Ok([4, 5, 6]));
+        assert_eq!(cell.compare_exchange([4, 5, 6], [0; 3]), Err([7, 8, 9]));
+        assert_eq!(format!("{cell:?}"), "AtomicCell([7, 8, 9])");
+        let mut cell = cell;
+        cell.get_mut()[0] = 0;
+        assert_eq!(cell.load(), [0, 8, 9]);
+
+        let unit = AtomicCell::new(());
+        assert_eq!(unit.swap(()), ());
+    }
+
+    #[test]
+    fn no_torn_reads() {
+        let cell = AtomicCell::new([0u64; 4]);
+        thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=10_000 {
+                    cell.store([i; 4]);
+                }
+            });
+            for _ in 0..2 {
+                s.spawn(|| {
+                    for _ in 0..10_000 {
+                        let [a, b, c, d] = cell.load();
+                        assert!(a == b && b == c && c == d);
+                    }
+                });
+            }
+        });
+        assert_eq!(cell.load(), [10_000; 4]);
+    }
+
+    #[test]
+    fn concurrent_compare_exchange() {
+        let native = AtomicCell::new(0u32);
+        let locked = AtomicCell::new((0u32, 0u8));
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1_000 {
+                        let mut n = native.load();
+                        while let Err(actual) = native.compare_exchange(n, n + 1) {
+                            n = actual;
+                        }
+                        let mut pair = locked.load();
+                        while let Err(actual) = locked.compare_exchange(pair, (pair.0 + 1, 0)) {
+                            pair = actual;
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(native.load(), 4_000);
+        assert_eq!(locked.load(), (4_000, 0));
+    }
+}
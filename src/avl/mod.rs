@@ -0,0 +1,734 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::borrow::Borrow;
+use core::cmp::{self, Ordering};
+use core::fmt;
+use core::ops::{Bound, RangeBounds};
+use core::ptr;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+/// Node of an AVL tree.
+struct Node<K, V> {
+    key: K,
+    value: V,
+    height: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V) -> Self {
+        Node {
+            key,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn update_height(&mut self) {
+        self.height = cmp::max(height(&self.left), height(&self.right)) + 1;
+    }
+
+    fn balance_factor(&self) -> isize {
+        height(&self.left) as isize - height(&self.right) as isize
+    }
+}
+
+fn height<K, V>(node: &Link<K, V>) -> usize {
+    node.as_ref().map_or(0, |node| node.height)
+}
+
+/// An ordered map based on an AVL tree.
+/// It is a self-balancing binary search tree in which the heights of the two child
+/// subtrees of any node differ by at most one, so lookups, insertions and removals
+/// are all O(log n) in the worst case.
+///
+/// In debug builds every node a mutation rebalances re-checks its balance factor,
+/// its height and the order of its keys, which makes it easy to experiment with
+/// the rebalancing code.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::avl::AvlMap;
+///
+/// let mut map = AvlMap::new();
+/// map.insert(3, "c");
+/// map.insert(1, "a");
+/// map.insert(2, "b");
+/// assert_eq!(map.get(&2), Some(&"b"));
+/// assert_eq!(map.remove(&1), Some("a"));
+/// let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+/// assert_eq!(keys, vec![2, 3]);
+/// ```
+pub struct AvlMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K, V> Default for AvlMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> AvlMap<K, V> {
+    /// Create a new, empty map.
+    pub const fn new() -> Self {
+        AvlMap { root: None, len: 0 }
+    }
+
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Iterate over the entries in ascending key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            remaining: self.len,
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    /// Iterate over the entries in ascending key order, with mutable
+    /// references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut iter = IterMut {
+            stack: Vec::new(),
+            remaining: self.len,
+        };
+        iter.push_left(self.root.as_deref_mut());
+        iter
+    }
+}
+
+impl<K, V> AvlMap<K, V>
+where
+    K: Ord,
+{
+    /// Insert a key-value pair into the map.
+    /// Returns the old value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut old = None;
+        self.root = Some(Self::insert_node(self.root.take(), key, value, &mut old));
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    fn insert_node(node: Link<K, V>, key: K, value: V, old: &mut Option<V>) -> Box<Node<K, V>> {
+        let mut node = match node {
+            Some(node) => node,
+            None => return Box::new(Node::new(key, value)),
+        };
+        match key.cmp(&node.key) {
+            Ordering::Less => {
+                node.left = Some(Self::insert_node(node.left.take(), key, value, old))
+            }
+            Ordering::Greater => {
+                node.right = Some(Self::insert_node(node.right.take(), key, value, old))
+            }
+            Ordering::Equal => {
+                *old = Some(core::mem::replace(&mut node.value, value));
+                return node;
+            }
+        }
+        Self::rebalance(node)
+    }
+
+    /// Get a reference to the value of a key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => current = node.left.as_deref(),
+                Ordering::Greater => current = node.right.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+            }
+        }
+        None
+    }
+
+    /// Get a mutable reference to the value of a key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => current = node.left.as_deref_mut(),
+                Ordering::Greater => current = node.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+            }
+        }
+        None
+    }
+
+    /// Return `true` if the map contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key from the map.
+    /// Returns the value of the key if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut removed = None;
+        self.root = Self::remove_node(self.root.take(), key, &mut removed);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed.map(|(_, value)| value)
+    }
+
+    fn remove_node<Q>(node: Link<K, V>, key: &Q, removed: &mut Option<(K, V)>) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = node?;
+        match key.cmp(node.key.borrow()) {
+            Ordering::Less => node.left = Self::remove_node(node.left.take(), key, removed),
+            Ordering::Greater => node.right = Self::remove_node(node.right.take(), key, removed),
+            Ordering::Equal => {
+                let Node {
+                    key,
+                    value,
+                    left,
+                    right,
+                    ..
+                } = *node;
+                *removed = Some((key, value));
+                return match (left, right) {
+                    (None, None) => None,
+                    (Some(child), None) | (None, Some(child)) => Some(child),
+                    // Node with two children: replace it with the minimum of the right subtree.
+                    (Some(left), Some(right)) => {
+                        let (right, mut min) = Self::remove_min(right);
+                        min.left = Some(left);
+                        min.right = right;
+                        Some(Self::rebalance(min))
+                    }
+                };
+            }
+        }
+        Some(Self::rebalance(node))
+    }
+
+    // Detach the minimum node of a subtree, returning the rest of the subtree and the node.
+    fn remove_min(mut node: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+        match node.left.take() {
+            Some(left) => {
+                let (left, min) = Self::remove_min(left);
+                node.left = left;
+                (Some(Self::rebalance(node)), min)
+            }
+            None => (node.right.take(), node),
+        }
+    }
+
+    /// Get the entry with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Get the entry with the largest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Iterate over the entries whose keys fall in `range`, in ascending key order.
+    /// Panics if the start of the range is greater than its end.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+            (start, end)
+        {
+            assert!(s <= e, "range start is greater than range end");
+        }
+        let mut iter = Range {
+            front: Vec::new(),
+            back: Vec::new(),
+        };
+        // Each stack keeps the nodes on the path to its end of the range that
+        // are still to be visited from that end.
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            let key = current.key.borrow();
+            node = match start {
+                Bound::Included(s) if key < s => current.right.as_deref(),
+                Bound::Excluded(s) if key <= s => current.right.as_deref(),
+                _ => {
+                    iter.front.push(current);
+                    current.left.as_deref()
+                }
+            };
+        }
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            let key = current.key.borrow();
+            node = match end {
+                Bound::Included(e) if key > e => current.left.as_deref(),
+                Bound::Excluded(e) if key >= e => current.left.as_deref(),
+                _ => {
+                    iter.back.push(current);
+                    current.right.as_deref()
+                }
+            };
+        }
+        // The ends cross when no key falls in the range.
+        match (iter.front.last(), iter.back.last()) {
+            (Some(first), Some(last)) if first.key <= last.key => {}
+            _ => {
+                iter.front.clear();
+                iter.back.clear();
+            }
+        }
+        iter
+    }
+
+    fn rebalance(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        node.update_height();
+        let balance = node.balance_factor();
+        let node = if balance > 1 {
+            // Left-right case: turn it into a left-left case first.
+            if node.left.as_ref().unwrap().balance_factor() < 0 {
+                node.left = Some(Self::rotate_left(node.left.take().unwrap()));
+            }
+            Self::rotate_right(node)
+        } else if balance < -1 {
+            // Right-left case: turn it into a right-right case first.
+            if node.right.as_ref().unwrap().balance_factor() > 0 {
+                node.right = Some(Self::rotate_right(node.right.take().unwrap()));
+            }
+            Self::rotate_left(node)
+        } else {
+            node
+        };
+        Self::check_rebalanced(&node);
+        node
+    }
+
+    // Check a node that was just rebalanced, and the children a rotation may
+    // have moved under it, in debug builds.
+    fn check_rebalanced(node: &Node<K, V>) {
+        for n in [Some(node), node.left.as_deref(), node.right.as_deref()]
+            .into_iter()
+            .flatten()
+        {
+            debug_assert!(n.balance_factor().abs() <= 1, "balance factor out of range");
+            debug_assert_eq!(
+                n.height,
+                cmp::max(height(&n.left), height(&n.right)) + 1,
+                "stale height"
+            );
+        }
+        debug_assert!(
+            node.left.as_ref().is_none_or(|left| left.key < node.key)
+                && node.right.as_ref().is_none_or(|right| node.key < right.key),
+            "keys out of order"
+        );
+    }
+
+    fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut right = node
+            .right
+            .take()
+            .expect("rotate left requires a right child");
+        node.right = right.left.take();
+        node.update_height();
+        right.left = Some(node);
+        right.update_height();
+        right
+    }
+
+    fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut left = node
+            .left
+            .take()
+            .expect("rotate right requires a left child");
+        node.left = left.right.take();
+        node.update_height();
+        left.right = Some(node);
+        left.update_height();
+        left
+    }
+
+    // Verify the AVL invariants of the whole tree.
+    #[cfg(test)]
+    fn check_invariants(&self) {
+        fn check<K: Ord, V>(node: &Link<K, V>) -> usize {
+            let node = match node {
+                Some(node) => node,
+                None => return 0,
+            };
+            let left = check(&node.left);
+            let right = check(&node.right);
+            assert!(left.abs_diff(right) <= 1, "balance factor out of range");
+            assert_eq!(node.height, cmp::max(left, right) + 1, "stale height");
+            node.height
+        }
+        check(&self.root);
+        // The in-order walk is ascending only if every key is between the
+        // keys of its ancestors, not just ordered against its children.
+        let keys: Vec<&K> = self.iter().map(|(k, _)| k).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]), "keys out of order");
+        assert_eq!(keys.len(), self.len, "stale length");
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for AvlMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the entries of an [`AvlMap`] in ascending key order.
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a AvlMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over a range of entries of an [`AvlMap`] in ascending key order.
+pub struct Range<'a, K, V> {
+    // Like the stack of `Iter`, for the front and back of the range. The range
+    // is used up once both ends reach the same node.
+    front: Vec<&'a Node<K, V>>,
+    back: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front.pop()?;
+        if ptr::eq(node, *self.back.last().unwrap()) {
+            self.front.clear();
+            self.back.clear();
+        } else {
+            let mut next = node.right.as_deref();
+            while let Some(current) = next {
+                self.front.push(current);
+                next = current.left.as_deref();
+            }
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back.pop()?;
+        if ptr::eq(node, *self.front.last().unwrap()) {
+            self.front.clear();
+            self.back.clear();
+        } else {
+            let mut next = node.left.as_deref();
+            while let Some(current) = next {
+                self.back.push(current);
+                next = current.right.as_deref();
+            }
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+// A node whose left subtree is done, split into its entry and the right
+// subtree still to visit.
+type Pending<'a, K, V> = (&'a K, &'a mut V, Option<&'a mut Node<K, V>>);
+
+/// Mutable iterator over the entries of an [`AvlMap`] in ascending key order.
+pub struct IterMut<'a, K, V> {
+    stack: Vec<Pending<'a, K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> IterMut<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a mut Node<K, V>>) {
+        while let Some(current) = node {
+            let Node {
+                key,
+                value,
+                left,
+                right,
+                ..
+            } = current;
+            self.stack.push((key, value, right.as_deref_mut()));
+            node = left.as_deref_mut();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value, right) = self.stack.pop()?;
+        self.push_left(right);
+        self.remaining -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut AvlMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for AvlMap<K, V>
+where
+    K: Ord,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for AvlMap<K, V>
+where
+    K: Ord,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = AvlMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for AvlMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for AvlMap<K, V>
+where
+    K: serde::Deserialize<'de> + Ord,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for AvlMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for AvlMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Ord,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &(K, V), b: &(K, V)| a.0.cmp(&b.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn insert() {
+        let mut map = AvlMap::new();
+        assert_eq!(map.insert("foo", 42), None);
+        assert_eq!(map.insert("foo", 43), Some(42));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "keys out of order")]
+    fn check_invariants_whole_tree() {
+        // 7 is greater than its parent 3 but sits in the left subtree of 5.
+        let mut three = Box::new(Node::new(3, ()));
+        three.right = Some(Box::new(Node::new(7, ())));
+        three.update_height();
+        let mut five = Box::new(Node::new(5, ()));
+        five.left = Some(three);
+        five.right = Some(Box::new(Node::new(8, ())));
+        five.update_height();
+        let map = AvlMap {
+            root: Some(five),
+            len: 4,
+        };
+        map.check_invariants();
+    }
+
+    #[test]
+    fn get() {
+        let mut map = AvlMap::new();
+        map.insert("foo", 42);
+        assert_eq!(map.get("foo"), Some(&42));
+        assert_eq!(map.get("bar"), None);
+        *map.get_mut("foo").unwrap() += 1;
+        assert_eq!(map.get("foo"), Some(&43));
+        assert!(map.contains_key("foo"));
+    }
+
+    #[test]
+    fn remove() {
+        let mut map: AvlMap<_, _> = (0..100).map(|i| (i, i * 10)).collect();
+        for i in (0..100).step_by(3) {
+            assert_eq!(map.remove(&i), Some(i * 10));
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 66);
+        for i in 0..100 {
+            assert_eq!(map.contains_key(&i), i % 3 != 0);
+        }
+        map.check_invariants();
+    }
+
+    #[test]
+    fn iter() {
+        let map: AvlMap<_, _> = [5, 3, 7, 2, 4, 6, 8].into_iter().map(|k| (k, ())).collect();
+        let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec![2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(map.first_key_value(), Some((&2, &())));
+        assert_eq!(map.last_key_value(), Some((&8, &())));
+    }
+
+    #[test]
+    fn balanced() {
+        let mut map = AvlMap::new();
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+        // An AVL tree with n nodes has a height below 1.44 * log2(n + 2).
+        assert!(height(&map.root) <= 14);
+        map.check_invariants();
+        for i in 0..990 {
+            map.remove(&i);
+        }
+        assert!(height(&map.root) <= 5);
+        map.check_invariants();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: AvlMap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"1":10,"2":20}"#);
+        let back: AvlMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            back.iter().collect::<Vec<_>>(),
+            map.iter().collect::<Vec<_>>()
+        );
+    }
+}
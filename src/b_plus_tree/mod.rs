@@ -0,0 +1,1465 @@
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+use core::borrow::Borrow;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::{Bound, RangeBounds};
+use core::{fmt, mem};
+
+// Minimum degree of the tree, every node holds at most `2 * B - 1` keys and
+// every node but the root at least `B - 1`.
+const B: usize = 6;
+const MAX_KEYS: usize = 2 * B - 1;
+const MIN_KEYS: usize = B - 1;
+
+/// Node of a B+ tree.
+/// Nodes live in an arena and refer to each other by index, which lets the
+/// leaves form a linked list without shared ownership.
+enum Node<K, V> {
+    /// `keys[i]` is the smallest key reachable through `children[i + 1]`.
+    Internal { keys: Vec<K>, children: Vec<usize> },
+    /// Leaves hold all entries and link to their neighbours in key order.
+    Leaf {
+        keys: Vec<K>,
+        values: Vec<V>,
+        prev: Option<usize>,
+        next: Option<usize>,
+    },
+}
+
+/// An ordered map based on a B+ tree.
+/// Unlike a plain B-tree, values only live in the leaves and every leaf links to
+/// its neighbours, so a range scan descends the tree once and then walks the leaf
+/// chain, in either direction, without going back up. This makes it a better fit for scan-heavy
+/// workloads.
+///
+/// Removal keeps every node but the root at least half full, refilling an
+/// underfull node from a sibling or merging the two, so lookups stay O(log n)
+/// in the number of entries. The arena slots of merged nodes are reused.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::b_plus_tree::BPlusTree;
+///
+/// let mut tree = BPlusTree::new();
+/// for i in 0..100 {
+///     tree.insert(i, i * 10);
+/// }
+/// assert_eq!(tree.get(&42), Some(&420));
+///
+/// let keys: Vec<_> = tree.range(10..15).map(|(k, _)| *k).collect();
+/// assert_eq!(keys, vec![10, 11, 12, 13, 14]);
+/// ```
+pub struct BPlusTree<K, V> {
+    nodes: Vec<Node<K, V>>,
+    root: usize,
+    first_leaf: usize,
+    len: usize,
+    // Arena slots of merged nodes, filled again before `nodes` grows.
+    free: Vec<usize>,
+}
+
+impl<K, V> Default for BPlusTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> BPlusTree<K, V> {
+    /// Create a new, empty tree.
+    pub fn new() -> Self {
+        BPlusTree {
+            nodes: vec![Node::Leaf {
+                keys: Vec::new(),
+                values: Vec::new(),
+                prev: None,
+                next: None,
+            }],
+            root: 0,
+            first_leaf: 0,
+            len: 0,
+            free: Vec::new(),
+        }
+    }
+
+    /// Number of entries in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all entries from the tree.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    // Keys and values of the leaf at `index`.
+    fn leaf(&self, index: usize) -> (&Vec<K>, &Vec<V>) {
+        match &self.nodes[index] {
+            Node::Leaf { keys, values, .. } => (keys, values),
+            Node::Internal { .. } => unreachable!(),
+        }
+    }
+
+    fn leaf_mut(&mut self, index: usize) -> (&mut Vec<K>, &mut Vec<V>) {
+        match &mut self.nodes[index] {
+            Node::Leaf { keys, values, .. } => (keys, values),
+            Node::Internal { .. } => unreachable!(),
+        }
+    }
+
+    // Keys and children of the internal node at `index`.
+    fn internal(&self, index: usize) -> (&Vec<K>, &Vec<usize>) {
+        match &self.nodes[index] {
+            Node::Internal { keys, children } => (keys, children),
+            Node::Leaf { .. } => unreachable!(),
+        }
+    }
+
+    fn internal_mut(&mut self, index: usize) -> (&mut Vec<K>, &mut Vec<usize>) {
+        match &mut self.nodes[index] {
+            Node::Internal { keys, children } => (keys, children),
+            Node::Leaf { .. } => unreachable!(),
+        }
+    }
+
+    fn key_count(&self, index: usize) -> usize {
+        match &self.nodes[index] {
+            Node::Internal { keys, .. } | Node::Leaf { keys, .. } => keys.len(),
+        }
+    }
+
+    // Smallest key in the subtree at `index`, which must not be empty.
+    fn first_key(&self, mut index: usize) -> &K {
+        while let Node::Internal { children, .. } = &self.nodes[index] {
+            index = children[0];
+        }
+        &self.leaf(index).0[0]
+    }
+
+    // Store `node` in a free arena slot, or a new one, and return its index.
+    fn alloc(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    // Move the node at `index` out of the arena and free its slot.
+    fn take_node(&mut self, index: usize) -> Node<K, V> {
+        self.free.push(index);
+        mem::replace(
+            &mut self.nodes[index],
+            Node::Leaf {
+                keys: Vec::new(),
+                values: Vec::new(),
+                prev: None,
+                next: None,
+            },
+        )
+    }
+
+    // The rightmost leaf and the number of entries in it.
+    fn end(&self) -> (usize, usize) {
+        let mut index = self.root;
+        while let Node::Internal { children, .. } = &self.nodes[index] {
+            index = *children.last().unwrap();
+        }
+        (index, self.leaf(index).0.len())
+    }
+
+    /// Iterate over the entries in ascending key order.
+    pub fn iter(&self) -> Range<'_, K, V> {
+        Range {
+            tree: self,
+            front: (self.first_leaf, 0),
+            back: self.end(),
+        }
+    }
+
+    /// Iterate over the entries in ascending key order, with mutable
+    /// references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let back = self.end();
+        IterMut {
+            nodes: self.nodes.as_mut_ptr(),
+            front: (self.first_leaf, 0),
+            back,
+            remaining: self.len,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> BPlusTree<K, V>
+where
+    K: Ord + Clone,
+{
+    /// Insert a key-value pair into the tree.
+    /// Returns the old value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut old = None;
+        if let Some((separator, right)) = self.insert_node(self.root, key, value, &mut old) {
+            // The root was split, grow the tree by one level.
+            self.root = self.alloc(Node::Internal {
+                keys: vec![separator],
+                children: vec![self.root, right],
+            });
+        }
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    // Insert into the subtree at `index`. If the node overflows it is split and
+    // the separator and index of the new right sibling are returned.
+    fn insert_node(
+        &mut self,
+        index: usize,
+        key: K,
+        value: V,
+        old: &mut Option<V>,
+    ) -> Option<(K, usize)> {
+        let new_node = match &mut self.nodes[index] {
+            Node::Leaf {
+                keys, values, next, ..
+            } => {
+                match keys.binary_search(&key) {
+                    Ok(i) => {
+                        *old = Some(mem::replace(&mut values[i], value));
+                        return None;
+                    }
+                    Err(i) => {
+                        keys.insert(i, key);
+                        values.insert(i, value);
+                    }
+                }
+                if keys.len() <= MAX_KEYS {
+                    return None;
+                }
+                let mid = keys.len() / 2;
+                Node::Leaf {
+                    keys: keys.split_off(mid),
+                    values: values.split_off(mid),
+                    prev: Some(index),
+                    next: next.take(),
+                }
+            }
+            Node::Internal { keys, children } => {
+                let i = keys.partition_point(|k| k <= &key);
+                let child = children[i];
+                let (separator, right) = self.insert_node(child, key, value, old)?;
+                let Node::Internal { keys, children } = &mut self.nodes[index] else {
+                    unreachable!("node kind never changes");
+                };
+                keys.insert(i, separator);
+                children.insert(i + 1, right);
+                if keys.len() <= MAX_KEYS {
+                    return None;
+                }
+                let mid = keys.len() / 2;
+                let right_keys = keys.split_off(mid + 1);
+                let right_children = children.split_off(mid + 1);
+                let separator = keys.pop().unwrap();
+                let right = self.alloc(Node::Internal {
+                    keys: right_keys,
+                    children: right_children,
+                });
+                return Some((separator, right));
+            }
+        };
+        // Split leaf: link the new leaf right after the old one.
+        let separator = match &new_node {
+            Node::Leaf { keys, .. } => keys[0].clone(),
+            Node::Internal { .. } => unreachable!(),
+        };
+        let new_index = self.alloc(new_node);
+        if let Node::Leaf { next, .. } = &mut self.nodes[index] {
+            *next = Some(new_index);
+        }
+        if let Node::Leaf {
+            next: Some(after), ..
+        } = self.nodes[new_index]
+        {
+            if let Node::Leaf { prev, .. } = &mut self.nodes[after] {
+                *prev = Some(new_index);
+            }
+        }
+        Some((separator, new_index))
+    }
+
+    // Find the leaf that would hold `key`.
+    fn find_leaf<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut index = self.root;
+        while let Node::Internal { keys, children } = &self.nodes[index] {
+            index = children[keys.partition_point(|k| k.borrow() <= key)];
+        }
+        index
+    }
+
+    /// Get a reference to the value of a key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match &self.nodes[self.find_leaf(key)] {
+            Node::Leaf { keys, values, .. } => keys
+                .binary_search_by(|k| k.borrow().cmp(key))
+                .ok()
+                .map(|i| &values[i]),
+            Node::Internal { .. } => unreachable!(),
+        }
+    }
+
+    /// Get a mutable reference to the value of a key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let leaf = self.find_leaf(key);
+        match &mut self.nodes[leaf] {
+            Node::Leaf { keys, values, .. } => keys
+                .binary_search_by(|k| k.borrow().cmp(key))
+                .ok()
+                .map(|i| &mut values[i]),
+            Node::Internal { .. } => unreachable!(),
+        }
+    }
+
+    /// Return `true` if the tree contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key from the tree.
+    /// Returns the value of the key if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    /// Remove a key from the tree.
+    /// Returns the stored key and its value if it was present.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let entry = self.remove_node(self.root, key)?;
+        self.len -= 1;
+        if let Node::Internal { keys, children } = &self.nodes[self.root] {
+            if keys.is_empty() {
+                // The root is down to one child, shrink the tree by one level.
+                let child = children[0];
+                self.take_node(self.root);
+                self.root = child;
+            }
+        }
+        Some(entry)
+    }
+
+    // Remove from the subtree at `index`. A child left underfull is refilled or
+    // merged here, the node itself is left for its parent to fix.
+    fn remove_node<Q>(&mut self, index: usize, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (i, child) = match &mut self.nodes[index] {
+            Node::Leaf { keys, values, .. } => {
+                let i = keys.binary_search_by(|k| k.borrow().cmp(key)).ok()?;
+                return Some((keys.remove(i), values.remove(i)));
+            }
+            Node::Internal { keys, children } => {
+                let i = keys.partition_point(|k| k.borrow() <= key);
+                (i, children[i])
+            }
+        };
+        let entry = self.remove_node(child, key)?;
+        if i > 0 && self.internal(index).0[i - 1].borrow() == key {
+            // The smallest key under the child is gone, the next one separates it.
+            let separator = self.first_key(child).clone();
+            self.internal_mut(index).0[i - 1] = separator;
+        }
+        if self.key_count(child) < MIN_KEYS {
+            let children = self.internal(index).1;
+            let spare = |sibling: Option<&usize>| {
+                sibling.is_some_and(|&sibling| self.key_count(sibling) > MIN_KEYS)
+            };
+            if i > 0 && spare(children.get(i - 1)) {
+                self.rotate_right(index, i - 1);
+            } else if spare(children.get(i + 1)) {
+                self.rotate_left(index, i);
+            } else {
+                // Merge with the left sibling if there is one, else the right.
+                self.merge(index, i.saturating_sub(1));
+            }
+        }
+        Some(entry)
+    }
+
+    // Move the first entry of child `i + 1` of `parent` to the end of child `i`.
+    fn rotate_left(&mut self, parent: usize, i: usize) {
+        let children = self.internal(parent).1;
+        let (left, right) = (children[i], children[i + 1]);
+        match &mut self.nodes[right] {
+            Node::Leaf { keys, values, .. } => {
+                let (key, value) = (keys.remove(0), values.remove(0));
+                let separator = keys[0].clone();
+                self.internal_mut(parent).0[i] = separator;
+                let (keys, values) = self.leaf_mut(left);
+                keys.push(key);
+                values.push(value);
+            }
+            Node::Internal { keys, children } => {
+                let (key, child) = (keys.remove(0), children.remove(0));
+                let separator = mem::replace(&mut self.internal_mut(parent).0[i], key);
+                let (keys, children) = self.internal_mut(left);
+                keys.push(separator);
+                children.push(child);
+            }
+        }
+    }
+
+    // Move the last entry of child `i` of `parent` to the front of child `i + 1`.
+    fn rotate_right(&mut self, parent: usize, i: usize) {
+        let children = self.internal(parent).1;
+        let (left, right) = (children[i], children[i + 1]);
+        match &mut self.nodes[left] {
+            Node::Leaf { keys, values, .. } => {
+                let (key, value) = (keys.pop().unwrap(), values.pop().unwrap());
+                self.internal_mut(parent).0[i] = key.clone();
+                let (keys, values) = self.leaf_mut(right);
+                keys.insert(0, key);
+                values.insert(0, value);
+            }
+            Node::Internal { keys, children } => {
+                let (key, child) = (keys.pop().unwrap(), children.pop().unwrap());
+                let separator = mem::replace(&mut self.internal_mut(parent).0[i], key);
+                let (keys, children) = self.internal_mut(right);
+                keys.insert(0, separator);
+                children.insert(0, child);
+            }
+        }
+    }
+
+    // Merge child `i + 1` of `parent` into child `i`, dropping their separator.
+    fn merge(&mut self, parent: usize, i: usize) {
+        let (keys, children) = self.internal_mut(parent);
+        let (separator, right) = (keys.remove(i), children.remove(i + 1));
+        let left = children[i];
+        match self.take_node(right) {
+            Node::Leaf {
+                keys, values, next, ..
+            } => {
+                // Unlink the right leaf from the chain.
+                if let Some(after) = next {
+                    if let Node::Leaf { prev, .. } = &mut self.nodes[after] {
+                        *prev = Some(left);
+                    }
+                }
+                let Node::Leaf {
+                    keys: left_keys,
+                    values: left_values,
+                    next: left_next,
+                    ..
+                } = &mut self.nodes[left]
+                else {
+                    unreachable!("siblings are at the same depth");
+                };
+                left_keys.extend(keys);
+                left_values.extend(values);
+                *left_next = next;
+            }
+            Node::Internal { keys, children } => {
+                let (left_keys, left_children) = self.internal_mut(left);
+                left_keys.push(separator);
+                left_keys.extend(keys);
+                left_children.extend(children);
+            }
+        }
+    }
+
+    /// Get the entry of a key, to read, insert or remove its value after
+    /// descending the tree once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::b_plus_tree::BPlusTree;
+    ///
+    /// let mut index: BPlusTree<&str, Vec<u32>> = BPlusTree::new();
+    /// for (word, line) in [("fn", 1), ("let", 2), ("fn", 3)] {
+    ///     index.entry(word).or_default().push(line);
+    /// }
+    /// assert_eq!(index.get("fn"), Some(&vec![1, 3]));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let leaf = self.find_leaf(&key);
+        match self.leaf(leaf).0.binary_search(&key) {
+            Ok(index) => Entry::Occupied(OccupiedEntry {
+                tree: self,
+                leaf,
+                index,
+            }),
+            Err(index) => Entry::Vacant(VacantEntry {
+                tree: self,
+                key,
+                leaf,
+                index,
+            }),
+        }
+    }
+
+    /// Iterate over the entries whose keys fall in `range`, in ascending key order.
+    /// The tree is descended once for each end of the range, the rest of the scan
+    /// follows the leaf links. Panics if the start of the range is greater than
+    /// its end.
+    pub fn range<R>(&self, range: R) -> Range<'_, K, V>
+    where
+        R: RangeBounds<K>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+            (start, end)
+        {
+            assert!(s <= e, "range start is greater than range end");
+        }
+        // Both ends are found as the position of the first entry past a bound.
+        let first_after = |key: &K, before: &dyn Fn(&K) -> bool| {
+            let leaf = self.find_leaf(key);
+            (leaf, self.leaf(leaf).0.partition_point(|k| before(k)))
+        };
+        let front = match start {
+            Bound::Included(s) => first_after(s, &|k| k < s),
+            Bound::Excluded(s) => first_after(s, &|k| k <= s),
+            Bound::Unbounded => (self.first_leaf, 0),
+        };
+        let back = match end {
+            Bound::Included(e) => first_after(e, &|k| k <= e),
+            Bound::Excluded(e) => first_after(e, &|k| k < e),
+            Bound::Unbounded => self.end(),
+        };
+        Range {
+            tree: self,
+            front,
+            back,
+        }
+    }
+
+    /// Get the entry with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    /// Get the entry with the largest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.iter().next_back()
+    }
+
+    /// Keep only the entries for which `f` returns `true`, visiting them in
+    /// ascending key order.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.extract_if(|k, v| !f(k, v)).for_each(drop);
+    }
+
+    /// Return an iterator that removes and yields, in ascending key order,
+    /// every entry for which `filter` returns `true`.
+    ///
+    /// Each entry is removed from the tree as it is yielded, so the entries
+    /// the iterator didn't get to are kept, whether it is dropped or leaked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::b_plus_tree::BPlusTree;
+    ///
+    /// let mut tree: BPlusTree<_, _> = (0..100).map(|i| (i, i)).collect();
+    /// let evens = tree.extract_if(|k, _| k % 2 == 0);
+    /// assert_eq!(evens.take(3).collect::<Vec<_>>(), [(0, 0), (2, 2), (4, 4)]);
+    /// assert_eq!(tree.len(), 97);
+    /// ```
+    pub fn extract_if<F>(&mut self, filter: F) -> ExtractIf<'_, K, V, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf {
+            leaf: Some(self.first_leaf),
+            tree: self,
+            pos: 0,
+            filter,
+        }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for BPlusTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// An entry of a [`BPlusTree`], returned by [`BPlusTree::entry`].
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: Ord + Clone, V> Entry<'a, K, V> {
+    /// The key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    /// Get the value, inserting `default` first if the key is missing.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Get the value, inserting the result of `default` first if the key is missing.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// Like [`Entry::or_insert_with`], but `default` is passed the key.
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let value = default(&e.key);
+                e.insert(value)
+            }
+        }
+    }
+
+    /// Get the value, inserting the default value first if the key is missing.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Apply `f` to the value if the key is present.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+/// An entry of a [`BPlusTree`] whose key is present.
+pub struct OccupiedEntry<'a, K, V> {
+    tree: &'a mut BPlusTree<K, V>,
+    leaf: usize,
+    // Position of the entry in its leaf.
+    index: usize,
+}
+
+impl<'a, K: Ord + Clone, V> OccupiedEntry<'a, K, V> {
+    /// The key of the entry.
+    pub fn key(&self) -> &K {
+        &self.tree.leaf(self.leaf).0[self.index]
+    }
+
+    /// Get a reference to the value.
+    pub fn get(&self) -> &V {
+        &self.tree.leaf(self.leaf).1[self.index]
+    }
+
+    /// Get a mutable reference to the value.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.tree.leaf_mut(self.leaf).1[self.index]
+    }
+
+    /// Turn the entry into a mutable reference to the value, borrowed from the tree.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.tree.leaf_mut(self.leaf).1[self.index]
+    }
+
+    /// Replace the value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the entry from the tree, returning the key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        // The leaf may underflow, so go through the tree to rebalance it.
+        let key = self.key().clone();
+        self.tree.remove_entry(&key).unwrap()
+    }
+
+    /// Remove the entry from the tree, returning the value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+/// An entry of a [`BPlusTree`] whose key is missing.
+pub struct VacantEntry<'a, K, V> {
+    tree: &'a mut BPlusTree<K, V>,
+    key: K,
+    leaf: usize,
+    // Position the key goes at in its leaf.
+    index: usize,
+}
+
+impl<'a, K: Ord + Clone, V> VacantEntry<'a, K, V> {
+    /// The key of the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Take back ownership of the key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Insert the key with `value`, returning a mutable reference to the value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let VacantEntry {
+            tree,
+            key,
+            leaf,
+            index,
+        } = self;
+        if tree.leaf(leaf).0.len() < MAX_KEYS {
+            tree.len += 1;
+            let (keys, values) = tree.leaf_mut(leaf);
+            keys.insert(index, key);
+            values.insert(index, value);
+            return &mut values[index];
+        }
+        // The leaf is split, so look the value up again once the tree settled.
+        let probe = key.clone();
+        tree.insert(key, value);
+        tree.get_mut(&probe).unwrap()
+    }
+}
+
+/// An iterator removing the entries of a [`BPlusTree`] that match a filter,
+/// returned by [`BPlusTree::extract_if`].
+pub struct ExtractIf<'a, K, V, F> {
+    tree: &'a mut BPlusTree<K, V>,
+    // Next entry to look at.
+    leaf: Option<usize>,
+    pos: usize,
+    filter: F,
+}
+
+impl<K, V, F> Iterator for ExtractIf<'_, K, V, F>
+where
+    K: Ord + Clone,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        while let Some(leaf) = self.leaf {
+            let Node::Leaf {
+                keys, values, next, ..
+            } = &mut self.tree.nodes[leaf]
+            else {
+                unreachable!("only leaves are linked");
+            };
+            while self.pos < keys.len() {
+                if (self.filter)(&keys[self.pos], &mut values[self.pos]) {
+                    let key = keys[self.pos].clone();
+                    let entry = self.tree.remove_entry(&key).unwrap();
+                    // Rebalancing may have moved the entries after it, so find
+                    // the next one again.
+                    let leaf = self.tree.find_leaf(&key);
+                    self.leaf = Some(leaf);
+                    self.pos = self.tree.leaf(leaf).0.partition_point(|k| k < &key);
+                    return Some(entry);
+                }
+                self.pos += 1;
+            }
+            self.leaf = *next;
+            self.pos = 0;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.tree.len))
+    }
+}
+
+/// Iterator over a range of entries of a [`BPlusTree`] in ascending key order.
+pub struct Range<'a, K, V> {
+    tree: &'a BPlusTree<K, V>,
+    // Leaf and position of the next entry from the front, and of the entry
+    // after the next one from the back. The range is used up once they meet.
+    front: (usize, usize),
+    back: (usize, usize),
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (leaf, pos) = self.front;
+            if leaf == self.back.0 && pos >= self.back.1 {
+                return None;
+            }
+            let Node::Leaf {
+                keys, values, next, ..
+            } = &self.tree.nodes[leaf]
+            else {
+                unreachable!("only leaves are linked");
+            };
+            if pos < keys.len() {
+                self.front.1 += 1;
+                return Some((&keys[pos], &values[pos]));
+            }
+            // The back is further down the chain, so there is a next leaf.
+            self.front = (next.unwrap(), 0);
+        }
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let (leaf, end) = self.back;
+            if leaf == self.front.0 && end <= self.front.1 {
+                return None;
+            }
+            let Node::Leaf {
+                keys, values, prev, ..
+            } = &self.tree.nodes[leaf]
+            else {
+                unreachable!("only leaves are linked");
+            };
+            if end > 0 {
+                self.back.1 -= 1;
+                return Some((&keys[end - 1], &values[end - 1]));
+            }
+            let prev = prev.unwrap();
+            self.back = (prev, self.tree.leaf(prev).0.len());
+        }
+    }
+}
+
+impl<K, V> FusedIterator for Range<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a BPlusTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Range<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Mutable iterator over the entries of a [`BPlusTree`] in ascending key order.
+pub struct IterMut<'a, K, V> {
+    // The tree's nodes, borrowed mutably for `'a`.
+    nodes: *mut Node<K, V>,
+    // Positions as in `Range`.
+    front: (usize, usize),
+    back: (usize, usize),
+    remaining: usize,
+    marker: PhantomData<&'a mut Node<K, V>>,
+}
+
+// A leaf as seen by `IterMut`: its entries and its neighbours.
+struct RawLeaf<K, V> {
+    keys: *const K,
+    values: *mut V,
+    len: usize,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+impl<'a, K, V> IterMut<'a, K, V> {
+    // The leaf at `index`. Entries are handed out through raw pointers into its
+    // vectors, so references already returned from the leaf stay valid.
+    fn leaf(&self, index: usize) -> RawLeaf<K, V> {
+        // SAFETY: the nodes are borrowed for `'a` and the vectors' buffers are
+        // never touched through this reference.
+        match unsafe { &mut *self.nodes.add(index) } {
+            Node::Leaf {
+                keys,
+                values,
+                prev,
+                next,
+            } => RawLeaf {
+                keys: keys.as_ptr(),
+                values: values.as_mut_ptr(),
+                len: keys.len(),
+                prev: *prev,
+                next: *next,
+            },
+            Node::Internal { .. } => unreachable!("only leaves are linked"),
+        }
+    }
+
+    // The entry at `pos` in `leaf`.
+    fn entry(leaf: RawLeaf<K, V>, pos: usize) -> (&'a K, &'a mut V) {
+        // SAFETY: every entry is visited at most once, so this is the only
+        // mutable reference to the value.
+        unsafe { (&*leaf.keys.add(pos), &mut *leaf.values.add(pos)) }
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            let (index, pos) = self.front;
+            let leaf = self.leaf(index);
+            if pos < leaf.len {
+                self.front.1 += 1;
+                self.remaining -= 1;
+                return Some(Self::entry(leaf, pos));
+            }
+            self.front = (leaf.next.unwrap(), 0);
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            let (index, end) = self.back;
+            let leaf = self.leaf(index);
+            if end > 0 {
+                self.back.1 -= 1;
+                self.remaining -= 1;
+                return Some(Self::entry(leaf, end - 1));
+            }
+            let prev = leaf.prev.unwrap();
+            self.back = (prev, self.leaf(prev).len);
+        }
+        None
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a mut BPlusTree<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for BPlusTree<K, V>
+where
+    K: Ord + Clone,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        if self.is_empty() {
+            *self = iter.into_iter().collect();
+            return;
+        }
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for BPlusTree<K, V>
+where
+    K: Ord + Clone,
+{
+    /// Sort the entries and build the tree bottom-up, one level at a time.
+    /// When a key repeats, the last value wins, as with repeated inserts.
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut entries: Vec<_> = iter.into_iter().collect();
+        // Stable, so equal keys stay in the order they came in.
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.dedup_by(|later, earlier| {
+            let duplicate = later.0 == earlier.0;
+            if duplicate {
+                mem::swap(&mut later.1, &mut earlier.1);
+            }
+            duplicate
+        });
+        BPlusTree::from_sorted(entries)
+    }
+}
+
+impl<K: Clone, V> From<BTreeMap<K, V>> for BPlusTree<K, V> {
+    /// Build the tree bottom-up from the map's entries, which are already sorted.
+    fn from(map: BTreeMap<K, V>) -> Self {
+        BPlusTree::from_sorted(map.into_iter().collect())
+    }
+}
+
+impl<K: Ord, V> From<BPlusTree<K, V>> for BTreeMap<K, V> {
+    /// Move the entries out of the leaves, in key order.
+    fn from(tree: BPlusTree<K, V>) -> Self {
+        let mut nodes = tree.nodes;
+        let mut entries = Vec::with_capacity(tree.len);
+        let mut leaf = Some(tree.first_leaf);
+        while let Some(index) = leaf {
+            let Node::Leaf {
+                keys, values, next, ..
+            } = &mut nodes[index]
+            else {
+                unreachable!()
+            };
+            entries.extend(mem::take(keys).into_iter().zip(mem::take(values)));
+            leaf = *next;
+        }
+        entries.into_iter().collect()
+    }
+}
+
+impl<K: Clone, V> BPlusTree<K, V> {
+    // Build a tree from entries sorted by key, without duplicates. Nodes are
+    // filled as evenly as possible, with up to `MAX_KEYS` keys each.
+    fn from_sorted(entries: Vec<(K, V)>) -> Self {
+        if entries.is_empty() {
+            return BPlusTree::new();
+        }
+        let len = entries.len();
+        let mut nodes = Vec::new();
+        // Index and smallest key of every node on the level last built.
+        let mut level = Vec::new();
+        let mut entries = entries.into_iter();
+        for size in even_chunks(len, MAX_KEYS) {
+            let (keys, values): (Vec<_>, Vec<_>) = entries.by_ref().take(size).unzip();
+            let index = nodes.len();
+            if let Some(Node::Leaf { next, .. }) = nodes.last_mut() {
+                *next = Some(index);
+            }
+            level.push((index, keys[0].clone()));
+            nodes.push(Node::Leaf {
+                keys,
+                values,
+                prev: index.checked_sub(1),
+                next: None,
+            });
+        }
+        while level.len() > 1 {
+            let mut parents = Vec::new();
+            let mut children = level.into_iter();
+            for size in even_chunks(children.len(), MAX_KEYS + 1) {
+                let (first, smallest) = children.next().unwrap();
+                let (keys, rest): (Vec<_>, Vec<_>) = children
+                    .by_ref()
+                    .take(size - 1)
+                    .map(|(i, k)| (k, i))
+                    .unzip();
+                parents.push((nodes.len(), smallest));
+                nodes.push(Node::Internal {
+                    keys,
+                    children: [first].into_iter().chain(rest).collect(),
+                });
+            }
+            level = parents;
+        }
+        BPlusTree {
+            root: level[0].0,
+            nodes,
+            first_leaf: 0,
+            len,
+            free: Vec::new(),
+        }
+    }
+}
+
+// Sizes of the fewest chunks of at most `max` that `n` items split into, as
+// close to each other as possible.
+fn even_chunks(n: usize, max: usize) -> impl ExactSizeIterator<Item = usize> {
+    let count = n.div_ceil(max);
+    (0..count).map(move |i| n / count + usize::from(i < n % count))
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for BPlusTree<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for BPlusTree<K, V>
+where
+    K: serde::Deserialize<'de> + Ord + Clone,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for BPlusTree<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord + Clone,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for BPlusTree<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Ord + Clone,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys<'a>(iter: impl Iterator<Item = (&'a i32, &'a i32)>) -> Vec<i32> {
+        iter.map(|(k, _)| *k).collect()
+    }
+
+    // Check that all leaves are at the same depth, every node but the root is
+    // at least half full, separators are the smallest key to their right, the
+    // leaf chain runs through every leaf in order and no arena slot is lost.
+    fn check(tree: &BPlusTree<i32, i32>) {
+        // Returns the height of the subtree at `index`.
+        fn walk(
+            tree: &BPlusTree<i32, i32>,
+            index: usize,
+            leaves: &mut Vec<usize>,
+            nodes: &mut usize,
+        ) -> usize {
+            *nodes += 1;
+            let min = if index == tree.root { 0 } else { MIN_KEYS };
+            assert!((min..=MAX_KEYS).contains(&tree.key_count(index)));
+            match &tree.nodes[index] {
+                Node::Leaf { .. } => {
+                    leaves.push(index);
+                    0
+                }
+                Node::Internal { keys, children } => {
+                    assert!(!keys.is_empty());
+                    assert_eq!(children.len(), keys.len() + 1);
+                    for (key, &child) in keys.iter().zip(&children[1..]) {
+                        assert_eq!(key, tree.first_key(child));
+                    }
+                    let heights: Vec<_> = children
+                        .iter()
+                        .map(|&child| walk(tree, child, leaves, nodes))
+                        .collect();
+                    assert!(heights.iter().all(|&h| h == heights[0]));
+                    heights[0] + 1
+                }
+            }
+        }
+        let (mut leaves, mut nodes) = (Vec::new(), 0);
+        walk(tree, tree.root, &mut leaves, &mut nodes);
+        assert_eq!(nodes + tree.free.len(), tree.nodes.len());
+
+        let (mut chain, mut prev, mut leaf) = (Vec::new(), None, Some(tree.first_leaf));
+        while let Some(index) = leaf {
+            let Node::Leaf { prev: p, next, .. } = &tree.nodes[index] else {
+                panic!("internal node in the leaf chain");
+            };
+            assert_eq!(*p, prev);
+            chain.push(index);
+            (prev, leaf) = (leaf, *next);
+        }
+        assert_eq!(chain, leaves);
+        assert_eq!(tree.iter().count(), tree.len());
+        assert!(keys(tree.iter()).windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn insert() {
+        let mut tree = BPlusTree::new();
+        assert_eq!(tree.insert("foo", 42), None);
+        assert_eq!(tree.insert("foo", 43), Some(42));
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree.get("foo"), Some(&43));
+    }
+
+    #[test]
+    fn insert_many() {
+        let tree: BPlusTree<_, _> = (0..1000).map(|i| ((i * 7919) % 1000, i)).collect();
+        assert_eq!(tree.len(), 1000);
+        assert_eq!(keys(tree.iter()), (0..1000).collect::<Vec<_>>());
+        for i in 0..1000 {
+            assert!(tree.contains_key(&i));
+        }
+        assert_eq!(tree.first_key_value().map(|(k, _)| *k), Some(0));
+        assert_eq!(tree.last_key_value().map(|(k, _)| *k), Some(999));
+    }
+
+    #[test]
+    fn reverse() {
+        let mut tree: BPlusTree<_, _> = (0..300).map(|i| (i, i)).collect();
+        // Remove runs of keys spanning several leaves, the rightmost among them.
+        for i in (100..150).chain(280..300) {
+            tree.remove(&i);
+        }
+        check(&tree);
+        let expected: Vec<_> = (0..100).chain(150..280).collect();
+        assert_eq!(
+            keys(tree.iter().rev()),
+            expected.iter().rev().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(tree.last_key_value(), Some((&279, &279)));
+        assert_eq!(
+            keys(tree.range(90..=160).rev()),
+            (150..=160).rev().chain((90..100).rev()).collect::<Vec<_>>()
+        );
+
+        for (k, v) in tree.iter_mut().rev().take(10) {
+            *v = -k;
+        }
+        let mut iter = tree.iter_mut();
+        assert_eq!(iter.len(), expected.len());
+        assert_eq!(iter.next_back(), Some((&279, &mut -279)));
+        assert_eq!(iter.next(), Some((&0, &mut 0)));
+        assert_eq!(iter.len(), expected.len() - 2);
+        assert_eq!(iter.count(), expected.len() - 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn inverted_range() {
+        let tree: BPlusTree<i32, ()> = BPlusTree::new();
+        let (start, end) = (5, 1);
+        tree.range(start..end);
+    }
+
+    #[test]
+    fn bulk_load() {
+        for n in [0, 1, MAX_KEYS, MAX_KEYS + 1, 144, 145, 1000] {
+            let tree: BPlusTree<_, _> = (0..n as i32).rev().map(|i| (i, i)).collect();
+            assert_eq!(tree.len(), n);
+            assert_eq!(keys(tree.iter()), (0..n as i32).collect::<Vec<_>>());
+            assert!((0..n as i32).all(|i| tree.get(&i) == Some(&i)));
+        }
+
+        // The last value of a repeated key wins.
+        let mut tree: BPlusTree<_, _> = [(1, 'a'), (2, 'b'), (1, 'c'), (1, 'd')]
+            .into_iter()
+            .collect();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.get(&1), Some(&'d'));
+        tree.extend([(0, 'e'), (2, 'f')]);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.get(&2), Some(&'f'));
+
+        // A bulk-loaded tree keeps taking inserts and removals.
+        let mut tree: BPlusTree<_, _> = (0..500).map(|i| (i * 2, i)).collect();
+        for i in 0..500 {
+            tree.insert(i * 2 + 1, i);
+        }
+        for i in (0..1000).step_by(3) {
+            tree.remove(&i);
+        }
+        let expected: Vec<_> = (0..1000).filter(|i| i % 3 != 0).collect();
+        assert_eq!(keys(tree.iter()), expected);
+        assert_eq!(keys(tree.range(10..20)), [10, 11, 13, 14, 16, 17, 19]);
+    }
+
+    #[test]
+    fn range() {
+        let tree: BPlusTree<_, _> = (0..500).map(|i| (i * 2, i)).collect();
+        assert_eq!(keys(tree.range(10..20)), vec![10, 12, 14, 16, 18]);
+        assert_eq!(keys(tree.range(11..=20)), vec![12, 14, 16, 18, 20]);
+        assert_eq!(keys(tree.range(995..)), vec![996, 998]);
+        assert_eq!(keys(tree.range(..4)), vec![0, 2]);
+        assert_eq!(
+            keys(tree.range((Bound::Excluded(10), Bound::Excluded(16)))),
+            vec![12, 14]
+        );
+        assert_eq!(tree.range(1000..).count(), 0);
+        assert_eq!(tree.range(100..700).count(), 300);
+    }
+
+    #[test]
+    fn remove() {
+        let mut tree: BPlusTree<_, _> = (0..300).map(|i| (i, i * 10)).collect();
+        for i in (0..300).step_by(3) {
+            assert_eq!(tree.remove(&i), Some(i * 10));
+        }
+        assert_eq!(tree.remove(&0), None);
+        assert_eq!(tree.len(), 200);
+        let expected: Vec<_> = (0..300).filter(|i| i % 3 != 0).collect();
+        assert_eq!(keys(tree.iter()), expected);
+        check(&tree);
+
+        // Remove enough to merge leaves and scan across the merged ones.
+        for i in 100..250 {
+            tree.remove(&i);
+        }
+        check(&tree);
+        assert_eq!(
+            keys(tree.range(95..255)),
+            vec![95, 97, 98, 250, 251, 253, 254]
+        );
+        for i in 250..300 {
+            tree.remove(&i);
+        }
+        assert_eq!(tree.last_key_value().map(|(k, _)| *k), Some(98));
+        tree.insert(120, 0);
+        assert_eq!(tree.get(&120), Some(&0));
+        check(&tree);
+
+        for i in 0..300 {
+            tree.remove(&i);
+        }
+        assert!(tree.is_empty());
+        check(&tree);
+        assert!(matches!(tree.nodes[tree.root], Node::Leaf { .. }));
+    }
+
+    #[test]
+    fn churn() {
+        // Slide a window of 100 keys along. Merged nodes give their slots back,
+        // so the arena stays the size of a tree with about 100 entries.
+        let mut tree = BPlusTree::new();
+        for i in 0..20_000 {
+            tree.insert(i, i);
+            if i >= 100 {
+                assert_eq!(tree.remove(&(i - 100)), Some(i - 100));
+            }
+        }
+        check(&tree);
+        assert_eq!(tree.len(), 100);
+        assert!(tree.nodes.len() <= 25, "{} nodes", tree.nodes.len());
+
+        // Refill and drain in scattered order, through every way of removing.
+        for round in 0..10 {
+            tree.extend((0..1000).map(|i| ((i * 7919) % 1000, i)));
+            tree.retain(|k, _| k % 3 != round % 3);
+            for i in (0..1000).step_by(7) {
+                if let Entry::Occupied(e) = tree.entry((i * 389) % 1000) {
+                    e.remove();
+                }
+            }
+            check(&tree);
+            for i in 0..1000 {
+                tree.remove(&((i * 271) % 1000));
+            }
+            check(&tree);
+        }
+        assert!(tree.is_empty());
+        assert!(tree.nodes.len() <= 150, "{} nodes", tree.nodes.len());
+    }
+
+    #[test]
+    fn entry() {
+        let mut tree = BPlusTree::new();
+        // Enough keys to fill leaves and insert through splits.
+        for i in (0..200).rev() {
+            *tree.entry(i % 50).or_insert(0) += 1;
+        }
+        assert_eq!(tree.len(), 50);
+        assert!(tree.iter().all(|(_, n)| *n == 4));
+        for i in 0..300 {
+            assert_eq!(
+                *tree.entry(i).or_insert_with_key(|k| *k),
+                if i < 50 { 4 } else { i }
+            );
+        }
+        assert_eq!(keys(tree.iter()), (0..300).collect::<Vec<_>>());
+
+        tree.entry(7).and_modify(|v| *v = -1).or_default();
+        let Entry::Occupied(mut e) = tree.entry(7) else {
+            unreachable!()
+        };
+        assert_eq!((e.key(), e.get()), (&7, &-1));
+        assert_eq!(e.insert(70), -1);
+        assert_eq!(e.remove_entry(), (7, 70));
+        let Entry::Vacant(e) = tree.entry(7) else {
+            unreachable!()
+        };
+        assert_eq!(e.into_key(), 7);
+        assert_eq!(tree.len(), 299);
+        assert_eq!(tree.get(&7), None);
+    }
+
+    #[test]
+    fn btree_map() {
+        let map: BTreeMap<_, _> = (0..500).map(|i| (i, -i)).collect();
+        let tree = BPlusTree::from(map.clone());
+        assert_eq!(tree.len(), 500);
+        assert_eq!(tree.get(&250), Some(&-250));
+        assert_eq!(BTreeMap::from(tree), map);
+        let mut tree = BPlusTree::from(map);
+        tree.insert(500, -500);
+        assert!(BTreeMap::from(tree)
+            .into_iter()
+            .eq((0..=500).map(|i| (i, -i))));
+        assert!(BTreeMap::from(BPlusTree::<i32, i32>::new()).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: BPlusTree<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"1":10,"2":20}"#);
+        let back: BPlusTree<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            back.iter().collect::<Vec<_>>(),
+            map.iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::Hash;
+
+use crate::hashmap::{self, HashMap};
+
+/// The pairs a [`BiMap::insert`] displaced to keep the map one-to-one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Overwritten<L, R> {
+    /// Neither value was in the map.
+    Neither,
+    /// The left value was paired with another right value, returned here.
+    Left(L, R),
+    /// The right value was paired with another left value, returned here.
+    Right(L, R),
+    /// The exact same pair was already in the map.
+    Pair(L, R),
+    /// Both values were in the map in two different pairs: the pair of the left
+    /// value first, then the pair of the right value.
+    Both((L, R), (L, R)),
+}
+
+impl<L, R> Overwritten<L, R> {
+    /// Return `true` if a pair was displaced.
+    pub fn did_overwrite(&self) -> bool {
+        !matches!(self, Overwritten::Neither)
+    }
+}
+
+/// A one-to-one map between left and right values.
+///
+/// Each left value has at most one right value and the other way around, and
+/// both sides can be looked up in O(1) through a hash map per direction. Both
+/// maps own a copy of each value, so values must be `Clone`: cheap keys such as
+/// ids, `Rc<str>` or interned symbols fit best.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::bi_map::{BiMap, Overwritten};
+///
+/// let mut ids = BiMap::new();
+/// ids.insert(1, "alice");
+/// ids.insert(2, "bob");
+/// assert_eq!(ids.get_by_left(&1), Some(&"alice"));
+/// assert_eq!(ids.get_by_right(&"bob"), Some(&2));
+///
+/// // Giving "bob" to id 1 displaces both existing pairs.
+/// assert_eq!(ids.insert(1, "bob"), Overwritten::Both((1, "alice"), (2, "bob")));
+/// assert_eq!(ids.len(), 1);
+/// ```
+#[derive(Clone)]
+pub struct BiMap<L, R> {
+    left_to_right: HashMap<L, R>,
+    right_to_left: HashMap<R, L>,
+}
+
+impl<L, R> Default for BiMap<L, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L, R> BiMap<L, R> {
+    /// Create a new, empty map.
+    pub fn new() -> Self {
+        BiMap {
+            left_to_right: HashMap::new(),
+            right_to_left: HashMap::new(),
+        }
+    }
+
+    /// Number of pairs in the map.
+    pub fn len(&self) -> usize {
+        self.left_to_right.len()
+    }
+
+    /// Return `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.left_to_right.is_empty()
+    }
+
+    /// Remove every pair.
+    pub fn clear(&mut self) {
+        self.left_to_right.clear();
+        self.right_to_left.clear();
+    }
+
+    /// Iterate over the pairs, in no particular order.
+    pub fn iter(&self) -> Iter<'_, L, R> {
+        Iter {
+            inner: self.left_to_right.iter(),
+        }
+    }
+
+    /// Iterate over the left values, in no particular order.
+    pub fn left_values(&self) -> impl Iterator<Item = &L> {
+        self.left_to_right.iter().map(|(left, _)| left)
+    }
+
+    /// Iterate over the right values, in no particular order.
+    pub fn right_values(&self) -> impl Iterator<Item = &R> {
+        self.right_to_left.iter().map(|(right, _)| right)
+    }
+}
+
+impl<L, R> BiMap<L, R>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+{
+    /// The right value paired with `left`.
+    pub fn get_by_left<Q>(&self, left: &Q) -> Option<&R>
+    where
+        L: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.left_to_right.get(left)
+    }
+
+    /// The left value paired with `right`.
+    pub fn get_by_right<Q>(&self, right: &Q) -> Option<&L>
+    where
+        R: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.right_to_left.get(right)
+    }
+
+    /// Return `true` if `left` is in the map.
+    pub fn contains_left<Q>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.left_to_right.contains_key(left)
+    }
+
+    /// Return `true` if `right` is in the map.
+    pub fn contains_right<Q>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.right_to_left.contains_key(right)
+    }
+
+    /// Remove the pair holding `left` and return it.
+    pub fn remove_by_left<Q>(&mut self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (left, right) = self.left_to_right.remove_entry(left)?;
+        self.right_to_left.remove(&right);
+        Some((left, right))
+    }
+
+    /// Remove the pair holding `right` and return it.
+    pub fn remove_by_right<Q>(&mut self, right: &Q) -> Option<(L, R)>
+    where
+        R: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (right, left) = self.right_to_left.remove_entry(right)?;
+        self.left_to_right.remove(&left);
+        Some((left, right))
+    }
+
+    /// Pair `left` with `right`, removing the pairs either of them was in before.
+    /// Returns the displaced pairs.
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<L, R> {
+        if self.get_by_left(&left) == Some(&right) {
+            let (old_left, old_right) = self.remove_by_left(&left).unwrap();
+            self.insert_unchecked(left, right);
+            return Overwritten::Pair(old_left, old_right);
+        }
+        let by_left = self.remove_by_left(&left);
+        let by_right = self.remove_by_right(&right);
+        self.insert_unchecked(left, right);
+        match (by_left, by_right) {
+            (None, None) => Overwritten::Neither,
+            (Some((l, r)), None) => Overwritten::Left(l, r),
+            (None, Some((l, r))) => Overwritten::Right(l, r),
+            (Some(by_left), Some(by_right)) => Overwritten::Both(by_left, by_right),
+        }
+    }
+
+    /// Pair `left` with `right` only if neither is in the map yet.
+    /// Otherwise the pair is handed back.
+    pub fn insert_no_overwrite(&mut self, left: L, right: R) -> Result<(), (L, R)> {
+        if self.contains_left(&left) || self.contains_right(&right) {
+            return Err((left, right));
+        }
+        self.insert_unchecked(left, right);
+        Ok(())
+    }
+
+    fn insert_unchecked(&mut self, left: L, right: R) {
+        self.left_to_right.insert(left.clone(), right.clone());
+        self.right_to_left.insert(right, left);
+    }
+}
+
+impl<L: fmt::Debug, R: fmt::Debug> fmt::Debug for BiMap<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<L: Eq + Hash, R: PartialEq> PartialEq for BiMap<L, R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.left_to_right == other.left_to_right
+    }
+}
+
+impl<L: Eq + Hash, R: Eq> Eq for BiMap<L, R> {}
+
+impl<L, R> Extend<(L, R)> for BiMap<L, R>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+{
+    fn extend<I: IntoIterator<Item = (L, R)>>(&mut self, iter: I) {
+        for (left, right) in iter {
+            self.insert(left, right);
+        }
+    }
+}
+
+impl<L, R> FromIterator<(L, R)> for BiMap<L, R>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = (L, R)>>(iter: I) -> Self {
+        let mut map = BiMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+/// Iterator over the pairs of a [`BiMap`].
+pub struct Iter<'a, L, R> {
+    inner: hashmap::Iter<'a, L, R>,
+}
+
+impl<'a, L, R> Iterator for Iter<'a, L, R> {
+    type Item = (&'a L, &'a R);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<L, R> ExactSizeIterator for Iter<'_, L, R> {}
+
+impl<'a, L, R> IntoIterator for &'a BiMap<L, R> {
+    type Item = (&'a L, &'a R);
+    type IntoIter = Iter<'a, L, R>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<L: serde::Serialize, R: serde::Serialize> serde::Serialize for BiMap<L, R> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, L, R> serde::Deserialize<'de> for BiMap<L, R>
+where
+    L: serde::Deserialize<'de> + Eq + Hash + Clone,
+    R: serde::Deserialize<'de> + Eq + Hash + Clone,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, L, R> arbitrary::Arbitrary<'a> for BiMap<L, R>
+where
+    L: arbitrary::Arbitrary<'a> + Eq + Hash + Clone,
+    R: arbitrary::Arbitrary<'a> + Eq + Hash + Clone,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(L, R)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(L, R)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<L, R> proptest::arbitrary::Arbitrary for BiMap<L, R>
+where
+    L: proptest::arbitrary::Arbitrary + 'static + Eq + Hash + Clone,
+    R: proptest::arbitrary::Arbitrary + 'static + Eq + Hash + Clone,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(L, R) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use super::*;
+
+    #[test]
+    fn insert_reports_displaced_pairs() {
+        let mut map = BiMap::new();
+        assert_eq!(map.insert(1, 'a'), Overwritten::Neither);
+        assert_eq!(map.insert(1, 'a'), Overwritten::Pair(1, 'a'));
+        assert_eq!(map.insert(1, 'b'), Overwritten::Left(1, 'a'));
+        assert_eq!(map.insert(2, 'b'), Overwritten::Right(1, 'b'));
+        map.insert(3, 'c');
+        assert_eq!(map.insert(2, 'c'), Overwritten::Both((2, 'b'), (3, 'c')));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get_by_left(&2), Some(&'c'));
+        assert_eq!(map.get_by_right(&'c'), Some(&2));
+        assert!(!map.contains_right(&'b'));
+        assert!(!map.insert(4, 'd').did_overwrite());
+    }
+
+    #[test]
+    fn insert_no_overwrite() {
+        let mut map = BiMap::new();
+        assert_eq!(map.insert_no_overwrite("x", 1), Ok(()));
+        assert_eq!(map.insert_no_overwrite("x", 2), Err(("x", 2)));
+        assert_eq!(map.insert_no_overwrite("y", 1), Err(("y", 1)));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn remove_keeps_both_sides_in_sync() {
+        let mut map: BiMap<u32, String> = (0..10).map(|i| (i, i.to_string())).collect();
+        assert_eq!(map.remove_by_left(&3), Some((3, "3".to_string())));
+        assert_eq!(map.remove_by_right("4"), Some((4, "4".to_string())));
+        assert_eq!(map.remove_by_right("4"), None);
+        assert_eq!(map.len(), 8);
+        assert_eq!(map.right_values().count(), 8);
+        for (left, right) in &map {
+            assert_eq!(map.get_by_right(right), Some(left));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: BiMap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        let back: BiMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.len(), 2);
+        assert_eq!(back.get_by_right(&10), Some(&1));
+        assert_eq!(back.get_by_left(&2), Some(&20));
+    }
+}
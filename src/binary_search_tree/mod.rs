@@ -1,8 +1,10 @@
 #![allow(clippy::assigning_clones)]
 
-use std::cell::RefCell;
-use std::collections::VecDeque;
-use std::rc::Rc;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
 
 /// Node of a binary tree.
 #[derive(Eq, PartialEq, Debug)]
@@ -374,6 +376,39 @@ impl<T> BinarySearchTree<T> {
     }
 }
 
+impl<T> Extend<T> for BinarySearchTree<T>
+where
+    T: PartialOrd + Clone,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for BinarySearchTree<T>
+where
+    T: PartialOrd + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = BinarySearchTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BinarySearchTree<T> {
+    /// Write the values in order, as a set.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut set = f.debug_set();
+        self.inorder(|value| {
+            set.entry(value);
+        });
+        set.finish()
+    }
+}
+
 /// Iterator for the binary search tree.
 pub struct BinaryTreeIterator<T> {
     current: Option<Rc<RefCell<Node<T>>>>,
@@ -415,8 +450,73 @@ where
     }
 }
 
+/// A tree serializes its values in preorder, so inserting them back in that
+/// order rebuilds the same shape.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for BinarySearchTree<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut len = 0;
+        self.preorder(|_| len += 1);
+        let mut seq = serializer.serialize_seq(Some(len))?;
+        let mut result = Ok(());
+        self.preorder(|value| {
+            if result.is_ok() {
+                result = seq.serialize_element(value);
+            }
+        });
+        result?;
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for BinarySearchTree<T>
+where
+    T: serde::Deserialize<'de> + PartialOrd + Clone,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for BinarySearchTree<T>
+where
+    T: arbitrary::Arbitrary<'a> + PartialOrd + Clone,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for BinarySearchTree<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static + PartialOrd + Clone,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &T, b: &T| {
+            a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal)
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::vec;
+
     use super::*;
 
     #[test]
@@ -434,6 +534,14 @@ mod tests {
         assert_eq!(res, vec![2, 3, 4, 5, 6, 7, 8]);
     }
 
+    #[test]
+    fn collect_and_extend() {
+        let mut tree: BinarySearchTree<_> = [5, 3, 7].into_iter().collect();
+        tree.extend([2, 8, 4]);
+        assert_eq!(tree.min(), Some(2));
+        assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![2, 3, 4, 5, 7, 8]);
+    }
+
     #[test]
     fn search() {
         let mut tree = BinarySearchTree::new();
@@ -629,4 +737,35 @@ mod tests {
         assert_eq!(tree.find_successor(20), Some(22));
         assert_eq!(tree.find_successor(22), None);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let tree: BinarySearchTree<i32> = [2, 1, 3, 4].into_iter().collect();
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(json, "[2,1,3,4]");
+        let back: BinarySearchTree<i32> = serde_json::from_str(&json).unwrap();
+        let mut levels = Vec::new();
+        back.level_order(|value| levels.push(*value));
+        assert_eq!(levels, [2, 1, 3, 4]);
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn proptest_builds_paths() {
+        use proptest::{arbitrary::any, strategy::Strategy, test_runner::TestRunner};
+
+        // Sorted inserts leave every left child empty, so the preorder is the
+        // inorder.
+        let strategy = any::<BinarySearchTree<u32>>();
+        let mut runner = TestRunner::deterministic();
+        let path = (0..64).any(|_| {
+            let tree = strategy.new_tree(&mut runner).unwrap().current();
+            let (mut inorder, mut preorder) = (Vec::new(), Vec::new());
+            tree.inorder(|&value| inorder.push(value));
+            tree.preorder(|&value| preorder.push(value));
+            inorder.len() >= 8 && inorder == preorder
+        });
+        assert!(path);
+    }
 }
@@ -0,0 +1,506 @@
+use alloc::{vec, vec::Vec};
+use core::{fmt, iter, slice};
+
+use crate::codec::{self, Decode, DecodeError, Encode};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A set of small integers stored as one bit each.
+/// The set covers the integers below a fixed capacity, packed into 64-bit words,
+/// so membership tests and updates are a shift and a mask. The iterators scan a
+/// word at a time and jump from one set bit to the next with `trailing_zeros`,
+/// without allocating.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::bit_set::BitSet;
+///
+/// let mut primes = BitSet::new(20);
+/// for p in [2, 3, 5, 7, 11, 13, 17, 19] {
+///     primes.insert(p);
+/// }
+/// let mut odd = BitSet::new(20);
+/// for i in (1..20).step_by(2) {
+///     odd.insert(i);
+/// }
+/// assert!(primes.contains(7));
+/// assert_eq!(primes.len(), 8);
+/// assert_eq!(primes.intersection(&odd).collect::<Vec<_>>(), [3, 5, 7, 11, 13, 17, 19]);
+/// assert_eq!(primes.zeros().take(4).collect::<Vec<_>>(), [0, 1, 4, 6]);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize))]
+pub struct BitSet {
+    // Bit `i % 64` of `words[i / 64]` is set if `i` is in the set. Bits at or
+    // past `capacity` are always clear.
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl BitSet {
+    /// Create an empty set that can hold the integers below `capacity`.
+    pub fn new(capacity: usize) -> Self {
+        BitSet {
+            words: vec![0; capacity.div_ceil(WORD_BITS)],
+            capacity,
+        }
+    }
+
+    /// The integers the set can hold are those below its capacity.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of integers in the set. O(capacity / 64).
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Return `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Remove every integer from the set.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    fn check(&self, value: usize) {
+        let capacity = self.capacity;
+        if value >= capacity {
+            panic!("value (is {value}) should be < capacity (is {capacity})");
+        }
+    }
+
+    /// Add an integer. Returns `false` if it was already present.
+    /// Panics if the value is not below the capacity.
+    pub fn insert(&mut self, value: usize) -> bool {
+        self.check(value);
+        let (word, mask) = (&mut self.words[value / WORD_BITS], 1 << (value % WORD_BITS));
+        let added = *word & mask == 0;
+        *word |= mask;
+        added
+    }
+
+    /// Remove an integer. Returns `true` if it was present.
+    pub fn remove(&mut self, value: usize) -> bool {
+        if value >= self.capacity {
+            return false;
+        }
+        let (word, mask) = (&mut self.words[value / WORD_BITS], 1 << (value % WORD_BITS));
+        let removed = *word & mask != 0;
+        *word &= !mask;
+        removed
+    }
+
+    /// Return `true` if the set contains the integer.
+    pub fn contains(&self, value: usize) -> bool {
+        value < self.capacity && self.words[value / WORD_BITS] & (1 << (value % WORD_BITS)) != 0
+    }
+
+    /// Iterate over the integers in the set, in ascending order.
+    pub fn ones(&self) -> Ones<'_> {
+        Ones {
+            bits: Bits::new(self.words.iter().copied(), self.capacity),
+        }
+    }
+
+    /// Iterate over the integers below the capacity that are not in the set,
+    /// in ascending order.
+    pub fn zeros(&self) -> Zeros<'_> {
+        Zeros {
+            bits: Bits::new(self.words.iter().map((|w: &u64| !w) as _), self.capacity),
+        }
+    }
+
+    /// Iterate over the integers in both `self` and `other`, in ascending order.
+    pub fn intersection<'a>(&'a self, other: &'a BitSet) -> Intersection<'a> {
+        let words = iter::zip(&self.words, &other.words).map((|(a, b)| a & b) as _);
+        Intersection {
+            bits: Bits::new(words, self.capacity.min(other.capacity)),
+        }
+    }
+}
+
+impl BitSet {
+    /// Encode the set in the [`codec`] format: a version byte,
+    /// the capacity and then the bits, eight to a byte.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        codec::to_bytes(self)
+    }
+
+    /// Decode a set written by [`BitSet::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        codec::from_bytes(bytes)
+    }
+}
+
+impl Encode for BitSet {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.capacity.encode(out);
+        let bytes = self.words.iter().flat_map(|w| w.to_le_bytes());
+        out.extend(bytes.take(self.capacity.div_ceil(8)));
+    }
+}
+
+impl Decode for BitSet {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let capacity = usize::decode(input)?;
+        // Read the bits before allocating, so a corrupt capacity fails early.
+        let bytes = codec::take(input, capacity.div_ceil(8))?;
+        let mut set = BitSet::new(capacity);
+        for (word, chunk) in iter::zip(&mut set.words, bytes.chunks(8)) {
+            let mut le = [0; 8];
+            le[..chunk.len()].copy_from_slice(chunk);
+            *word = u64::from_le_bytes(le);
+        }
+        let past_capacity = match set.words.last() {
+            Some(last) if capacity % WORD_BITS != 0 => last >> (capacity % WORD_BITS),
+            _ => 0,
+        };
+        if past_capacity != 0 {
+            return Err(DecodeError::Invalid("bit set"));
+        }
+        Ok(set)
+    }
+}
+
+impl fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.ones()).finish()
+    }
+}
+
+// The set bits below `end` of a sequence of words, one per step.
+#[derive(Clone)]
+struct Bits<W> {
+    words: W,
+    // The current word with the bits already visited cleared.
+    word: u64,
+    // Index of the first bit past the current word.
+    base: usize,
+    end: usize,
+}
+
+impl<W: Iterator<Item = u64>> Bits<W> {
+    fn new(words: W, end: usize) -> Self {
+        Bits {
+            words,
+            word: 0,
+            base: 0,
+            end,
+        }
+    }
+
+    fn next(&mut self) -> Option<usize> {
+        while self.word == 0 {
+            self.word = self.words.next()?;
+            self.base += WORD_BITS;
+        }
+        let index = self.base - WORD_BITS + self.word.trailing_zeros() as usize;
+        // Clear the lowest set bit.
+        self.word &= self.word - 1;
+        if index >= self.end {
+            self.word = 0;
+            return None;
+        }
+        Some(index)
+    }
+}
+
+// Words computed from the words of one or two sets.
+type WordMap<T> = iter::Map<T, fn(<T as Iterator>::Item) -> u64>;
+
+/// Iterator over the integers in a [`BitSet`], returned by [`BitSet::ones`].
+#[derive(Clone)]
+pub struct Ones<'a> {
+    bits: Bits<iter::Copied<slice::Iter<'a, u64>>>,
+}
+
+impl Iterator for Ones<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.bits.next()
+    }
+}
+
+/// Iterator over the integers missing from a [`BitSet`], returned by
+/// [`BitSet::zeros`].
+pub struct Zeros<'a> {
+    bits: Bits<WordMap<slice::Iter<'a, u64>>>,
+}
+
+impl Iterator for Zeros<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.bits.next()
+    }
+}
+
+/// Iterator over the integers in two [`BitSet`]s, returned by
+/// [`BitSet::intersection`].
+pub struct Intersection<'a> {
+    bits: Bits<WordMap<iter::Zip<slice::Iter<'a, u64>, slice::Iter<'a, u64>>>>,
+}
+
+impl Iterator for Intersection<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.bits.next()
+    }
+}
+
+/// A set serializes as its capacity and its values in ascending order.
+#[cfg(feature = "serde")]
+impl serde::Serialize for BitSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("BitSet", 2)?;
+        state.serialize_field("capacity", &self.capacity)?;
+        state.serialize_field("values", &self.ones().collect::<Vec<_>>())?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BitSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "BitSet")]
+        struct Repr {
+            capacity: usize,
+            values: Vec<usize>,
+        }
+
+        let Repr { capacity, values } = Repr::deserialize(deserializer)?;
+        let mut set = BitSet::new(capacity);
+        for value in values {
+            if value >= capacity {
+                return Err(serde::de::Error::custom(format_args!(
+                    "bit set value {value} out of its capacity {capacity}"
+                )));
+            }
+            set.insert(value);
+        }
+        Ok(set)
+    }
+}
+
+/// The archive of a [`BitSet`] answers membership queries in place, without
+/// deserializing.
+#[cfg(feature = "rkyv")]
+impl ArchivedBitSet {
+    /// The integers the set can hold are those below its capacity.
+    pub fn capacity(&self) -> usize {
+        self.capacity.to_native() as usize
+    }
+
+    /// Number of integers in the set. O(capacity / 64).
+    pub fn len(&self) -> usize {
+        self.ones().count()
+    }
+
+    /// Return `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.ones().next().is_none()
+    }
+
+    /// Return `true` if the set contains the integer.
+    pub fn contains(&self, value: usize) -> bool {
+        value < self.capacity()
+            && self
+                .words
+                .get(value / WORD_BITS)
+                .is_some_and(|word| word.to_native() & (1 << (value % WORD_BITS)) != 0)
+    }
+
+    /// Iterate over the integers in the set, in ascending order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut bits = Bits::new(
+            self.words.iter().map(|word| word.to_native()),
+            self.capacity(),
+        );
+        iter::from_fn(move || bits.next())
+    }
+}
+
+/// Deserializing only keeps the bits below the capacity, so a set read back
+/// from a corrupt archive still keeps the ones past it clear.
+#[cfg(feature = "rkyv")]
+impl<D: rkyv::rancor::Fallible + ?Sized> rkyv::Deserialize<BitSet, D> for ArchivedBitSet {
+    fn deserialize(&self, _: &mut D) -> Result<BitSet, D::Error> {
+        let mut set = BitSet::new(self.capacity());
+        for value in self.ones() {
+            set.insert(value);
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for BitSet {
+    /// Values are reduced modulo the capacity, so they always fit.
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut set = BitSet::new(u.arbitrary::<u16>()?.into());
+        if set.capacity > 0 {
+            for value in u.arbitrary_iter::<usize>()? {
+                set.insert(value? % set.capacity);
+            }
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for BitSet {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    /// Values are reduced modulo the capacity, which is at most 256.
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        let values = proptest::collection::vec(proptest::arbitrary::any::<usize>(), 0..64);
+        (0..=256usize, values)
+            .prop_map(|(capacity, values)| {
+                let mut set = BitSet::new(capacity);
+                if capacity > 0 {
+                    for value in values {
+                        set.insert(value % capacity);
+                    }
+                }
+                set
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+
+    fn set(capacity: usize, values: impl IntoIterator<Item = usize>) -> BitSet {
+        let mut set = BitSet::new(capacity);
+        for value in values {
+            set.insert(value);
+        }
+        set
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut s = BitSet::new(130);
+        assert!(s.is_empty());
+        assert!(s.insert(0));
+        assert!(s.insert(64));
+        assert!(s.insert(129));
+        assert!(!s.insert(64));
+        assert_eq!(s.len(), 3);
+        assert!(s.contains(129));
+        assert!(!s.contains(130));
+        assert!(s.remove(64));
+        assert!(!s.remove(64));
+        assert!(!s.remove(1000));
+        assert_eq!(format!("{s:?}"), "{0, 129}");
+        s.clear();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "value (is 10) should be < capacity (is 10)")]
+    fn insert_out_of_range() {
+        BitSet::new(10).insert(10);
+    }
+
+    #[test]
+    fn ones_and_zeros() {
+        for capacity in [0, 1, 63, 64, 65, 200] {
+            let multiples = set(capacity, (0..capacity).step_by(3));
+            let ones: Vec<_> = multiples.ones().collect();
+            assert_eq!(ones, (0..capacity).step_by(3).collect::<Vec<_>>());
+            let zeros: Vec<_> = multiples.zeros().collect();
+            assert_eq!(
+                zeros,
+                (0..capacity).filter(|i| i % 3 != 0).collect::<Vec<_>>()
+            );
+        }
+        let full = set(128, 0..128);
+        assert_eq!(full.ones().count(), 128);
+        assert_eq!(full.zeros().next(), None);
+    }
+
+    #[test]
+    fn intersection() {
+        let evens = set(300, (0..300).step_by(2));
+        let threes = set(100, (0..100).step_by(3));
+        let both: Vec<_> = evens.intersection(&threes).collect();
+        assert_eq!(both, (0..100).step_by(6).collect::<Vec<_>>());
+        assert_eq!(threes.intersection(&evens).collect::<Vec<_>>(), both);
+    }
+
+    #[test]
+    fn bytes() {
+        for capacity in [0, 5, 64, 127] {
+            let s = set(capacity, (0..capacity).step_by(3));
+            let bytes = s.to_bytes();
+            assert_eq!(bytes.len(), 2 + capacity.div_ceil(8));
+            assert_eq!(BitSet::from_bytes(&bytes), Ok(s));
+        }
+        assert_eq!(set(10, [1, 9]).to_bytes(), [codec::VERSION, 10, 0b10, 0b10]);
+        assert_eq!(
+            BitSet::from_bytes(&[codec::VERSION, 10, 0, 0b100]),
+            Err(DecodeError::Invalid("bit set"))
+        );
+        assert_eq!(
+            BitSet::from_bytes(&[codec::VERSION, 0xff, 0xff, 0xff, 0x7f]),
+            Err(DecodeError::UnexpectedEnd)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let bits = set(70, [3, 64, 69]);
+        let json = serde_json::to_string(&bits).unwrap();
+        assert_eq!(json, r#"{"capacity":70,"values":[3,64,69]}"#);
+        assert_eq!(serde_json::from_str::<BitSet>(&json).unwrap(), bits);
+        assert!(serde_json::from_str::<BitSet>(r#"{"capacity":70,"values":[70]}"#).is_err());
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn rkyv_round_trip() {
+        use rkyv::rancor::Error;
+
+        let bits = set(130, [0, 64, 129]);
+        let bytes = rkyv::to_bytes::<Error>(&bits).unwrap();
+        let archived = rkyv::access::<ArchivedBitSet, Error>(&bytes).unwrap();
+        assert_eq!(archived.capacity(), 130);
+        assert_eq!(archived.len(), 3);
+        assert!(archived.contains(64));
+        assert!(!archived.contains(65));
+        assert!(!archived.contains(1000));
+        assert_eq!(archived.ones().collect::<Vec<_>>(), [0, 64, 129]);
+        assert_eq!(rkyv::deserialize::<BitSet, Error>(archived).unwrap(), bits);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_values_fit() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes: [u8; 256] = core::array::from_fn(|i| (i * 37) as u8);
+        let set = BitSet::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert!(set.ones().all(|value| value < set.capacity()));
+        let set = BitSet::arbitrary(&mut Unstructured::new(&[])).unwrap();
+        assert_eq!(set.capacity(), 0);
+    }
+}
@@ -0,0 +1,294 @@
+use ::alloc::alloc::{self, Layout};
+use core::{
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::{PhantomData, Unsize},
+    mem::ManuallyDrop,
+    ops::{CoerceUnsized, Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+
+/// An owned pointer to a heap allocation.
+///
+/// Like `Box`, a `MyBox` owns exactly one value on the heap, frees it when
+/// dropped, and can be unsized: a `MyBox<[T; N]>` coerces to `MyBox<[T]>` and a
+/// `MyBox<T>` to `MyBox<dyn Trait>` for any trait `T` implements. Zero-sized
+/// values never allocate.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::Display;
+/// use my_std::boxed::MyBox;
+///
+/// let mut b = MyBox::new(5);
+/// *b += 1;
+/// assert_eq!(MyBox::into_inner(b), 6);
+///
+/// let shapes: Vec<MyBox<dyn Display>> = vec![MyBox::new(1), MyBox::new("two")];
+/// let shown: Vec<_> = shapes.iter().map(|s| s.to_string()).collect();
+/// assert_eq!(shown, vec!["1", "two"]);
+///
+/// let slice: MyBox<[u8]> = MyBox::new([1, 2, 3]);
+/// assert_eq!(slice.len(), 3);
+/// ```
+pub struct MyBox<T: ?Sized> {
+    ptr: NonNull<T>,
+    // The box owns a `T`, which matters for the drop checker.
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for MyBox<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for MyBox<T> {}
+
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<MyBox<U>> for MyBox<T> {}
+
+impl<T> MyBox<T> {
+    /// Move `value` to the heap.
+    pub fn new(value: T) -> Self {
+        let layout = Layout::new::<T>();
+        let ptr = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            let raw = unsafe { alloc::alloc(layout) } as *mut T;
+            match NonNull::new(raw) {
+                Some(ptr) => ptr,
+                None => alloc::handle_alloc_error(layout),
+            }
+        };
+        unsafe { ptr.as_ptr().write(value) };
+        MyBox {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Move the value back out of the box, freeing the allocation.
+    pub fn into_inner(boxed: Self) -> T {
+        let boxed = ManuallyDrop::new(boxed);
+        unsafe {
+            let value = boxed.ptr.as_ptr().read();
+            free(boxed.ptr.cast(), Layout::new::<T>());
+            value
+        }
+    }
+}
+
+impl<T: ?Sized> MyBox<T> {
+    /// Consume the box and return its raw pointer. The caller becomes
+    /// responsible for the value and its allocation, and should hand the
+    /// pointer back to [`MyBox::from_raw`] to free them.
+    pub fn into_raw(boxed: Self) -> *mut T {
+        ManuallyDrop::new(boxed).ptr.as_ptr()
+    }
+
+    /// Rebuild a box from a pointer returned by [`MyBox::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `raw` must come from [`MyBox::into_raw`] and must not be used again
+    /// afterwards.
+    pub unsafe fn from_raw(raw: *mut T) -> Self {
+        MyBox {
+            ptr: NonNull::new_unchecked(raw),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Consume the box without ever freeing it, and return a reference to the
+    /// value that lives for the rest of the program.
+    pub fn leak<'a>(boxed: Self) -> &'a mut T
+    where
+        T: 'a,
+    {
+        unsafe { &mut *Self::into_raw(boxed) }
+    }
+}
+
+// Free an allocation made by `MyBox::new`. Zero-sized values were never
+// allocated.
+unsafe fn free(ptr: NonNull<u8>, layout: Layout) {
+    if layout.size() != 0 {
+        alloc::dealloc(ptr.as_ptr(), layout);
+    }
+}
+
+unsafe impl<#[may_dangle] T: ?Sized> Drop for MyBox<T> {
+    fn drop(&mut self) {
+        unsafe {
+            // The layout has to be read before the value is dropped.
+            let layout = Layout::for_value(self.ptr.as_ref());
+            ptr::drop_in_place(self.ptr.as_ptr());
+            free(self.ptr.cast(), layout);
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for MyBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MyBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for MyBox<T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> AsMut<T> for MyBox<T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for MyBox<T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> BorrowMut<T> for MyBox<T> {
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: Default> Default for MyBox<T> {
+    fn default() -> Self {
+        MyBox::new(T::default())
+    }
+}
+
+impl<T: Clone> Clone for MyBox<T> {
+    fn clone(&self) -> Self {
+        MyBox::new((**self).clone())
+    }
+}
+
+impl<T> From<T> for MyBox<T> {
+    fn from(value: T) -> Self {
+        MyBox::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MyBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for MyBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> fmt::Pointer for MyBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.ptr, f)
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for MyBox<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for MyBox<T> {}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for MyBox<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: ?Sized + Ord> Ord for MyBox<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: ?Sized + Hash> Hash for MyBox<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::alloc::{format, string::String};
+
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct Noisy(Rc<Cell<usize>>);
+
+    impl Drop for Noisy {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn drops_exactly_once() {
+        let drops = Rc::new(Cell::new(0));
+        drop(MyBox::new(Noisy(drops.clone())));
+        assert_eq!(drops.get(), 1);
+
+        let inner = MyBox::into_inner(MyBox::new(Noisy(drops.clone())));
+        assert_eq!(drops.get(), 1);
+        drop(inner);
+        assert_eq!(drops.get(), 2);
+
+        let raw = MyBox::into_raw(MyBox::new(Noisy(drops.clone())));
+        assert_eq!(drops.get(), 2);
+        drop(unsafe { MyBox::from_raw(raw) });
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn unsized_targets() {
+        let drops = Rc::new(Cell::new(0));
+        let slice: MyBox<[Noisy]> = MyBox::new([Noisy(drops.clone()), Noisy(drops.clone())]);
+        assert_eq!(slice.len(), 2);
+        drop(slice);
+        assert_eq!(drops.get(), 2);
+
+        let any: MyBox<dyn std::any::Any> = MyBox::new(7u64);
+        assert_eq!(any.downcast_ref::<u64>(), Some(&7));
+    }
+
+    #[test]
+    fn zero_sized() {
+        let mut unit = MyBox::new(());
+        *unit = ();
+        let empty: MyBox<[u32]> = MyBox::new([]);
+        assert!(empty.is_empty());
+        assert_eq!(MyBox::into_inner(unit), ());
+    }
+
+    #[test]
+    fn traits_forward_to_value() {
+        let a = MyBox::new(String::from("a"));
+        let b = a.clone();
+        assert_eq!(a, b);
+        assert!(a < MyBox::new(String::from("b")));
+        assert_eq!(format!("{a:?} {b}"), "\"a\" a");
+        let leaked: &'static mut u32 = MyBox::leak(MyBox::new(3));
+        *leaked += 1;
+        assert_eq!(*leaked, 4);
+    }
+}
@@ -0,0 +1,353 @@
+use ::alloc::alloc::{self, AllocError, Allocator, Layout};
+use ::alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::ptr::{self, NonNull};
+use core::{cmp, slice, str};
+
+// Size in bytes of the first chunk, later chunks double in size.
+const INITIAL_CHUNK_SIZE: usize = 4096;
+// Alignment of every chunk, so small alignments never need padding at the start.
+const CHUNK_ALIGN: usize = 16;
+
+struct Chunk {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+/// A byte-level bump allocator.
+/// Values of any type are carved out of large chunks by moving an offset forward,
+/// so allocating is almost free. Nothing is freed individually: [`Bump::reset`]
+/// releases everything at once while keeping the chunks for reuse, and
+/// [`Bump::scope`] releases everything allocated inside a closure.
+///
+/// Destructors of allocated values are never run, so it is meant for plain data
+/// such as the temporaries of parsers and builders.
+///
+/// `&Bump` is also an [`Allocator`], so collections with an allocator parameter
+/// can keep their buffers in it. They drop their elements as usual, but the
+/// memory they free is only reclaimed on reset.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::bump::Bump;
+///
+/// let mut bump = Bump::new();
+/// let x = bump.alloc(1u8);
+/// let y = bump.alloc(2u64);
+/// assert_eq!(*x as u64 + *y, 3);
+///
+/// let name = bump.alloc_str("bump");
+/// assert_eq!(name, "bump");
+///
+/// bump.reset();
+/// assert_eq!(bump.allocated_bytes(), 0);
+/// ```
+pub struct Bump {
+    chunks: RefCell<Vec<Chunk>>,
+    // Index of the chunk allocations are served from.
+    current: Cell<usize>,
+    // Offset of the first free byte in the current chunk.
+    offset: Cell<usize>,
+}
+
+/// A position in a [`Bump`] allocator that it can be rewound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    chunk: usize,
+    offset: usize,
+}
+
+impl Default for Bump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bump {
+    /// Create a new bump allocator. No memory is allocated until the first allocation.
+    pub const fn new() -> Self {
+        Bump {
+            chunks: RefCell::new(Vec::new()),
+            current: Cell::new(0),
+            offset: Cell::new(0),
+        }
+    }
+
+    /// Move a value into the allocator and return a mutable reference to it.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        let ptr = self.alloc_layout(Layout::new::<T>()).cast::<T>();
+        unsafe {
+            ptr::write(ptr.as_ptr(), value);
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    /// Copy a slice into the allocator.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, src: &[T]) -> &mut [T] {
+        let layout = Layout::array::<T>(src.len()).expect("capacity overflow");
+        let ptr = self.alloc_layout(layout).cast::<T>();
+        unsafe {
+            ptr::copy_nonoverlapping(src.as_ptr(), ptr.as_ptr(), src.len());
+            slice::from_raw_parts_mut(ptr.as_ptr(), src.len())
+        }
+    }
+
+    /// Copy a string slice into the allocator.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_str(&self, src: &str) -> &mut str {
+        let bytes = self.alloc_slice_copy(src.as_bytes());
+        // SAFETY: the bytes were copied from a valid `str`.
+        unsafe { str::from_utf8_unchecked_mut(bytes) }
+    }
+
+    /// Allocate a block of memory for the given layout.
+    /// The memory is uninitialized and stays valid until the allocator is reset.
+    pub fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        if layout.size() == 0 {
+            // Zero-sized allocations only need a well aligned address.
+            return unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(layout.align())) };
+        }
+        let mut chunks = self.chunks.borrow_mut();
+        loop {
+            if let Some(chunk) = chunks.get(self.current.get()) {
+                let base = chunk.ptr.as_ptr() as usize;
+                let start = (base + self.offset.get()).next_multiple_of(layout.align()) - base;
+                if let Some(end) = start.checked_add(layout.size()) {
+                    if end <= chunk.layout.size() {
+                        self.offset.set(end);
+                        return unsafe { NonNull::new_unchecked(chunk.ptr.as_ptr().add(start)) };
+                    }
+                }
+                // Move on to the next chunk. It may be left over from before a reset.
+                if self.current.get() + 1 < chunks.len() {
+                    self.current.set(self.current.get() + 1);
+                    self.offset.set(0);
+                    continue;
+                }
+            }
+            let last = chunks
+                .last()
+                .map_or(INITIAL_CHUNK_SIZE / 2, |c| c.layout.size());
+            let size = cmp::max(
+                last.checked_mul(2).expect("capacity overflow"),
+                layout.size() + layout.align(),
+            );
+            let chunk_layout = Layout::from_size_align(size, cmp::max(CHUNK_ALIGN, layout.align()))
+                .expect("capacity overflow");
+            let ptr = unsafe { alloc::alloc(chunk_layout) };
+            let ptr = match NonNull::new(ptr) {
+                Some(ptr) => ptr,
+                None => alloc::handle_alloc_error(chunk_layout),
+            };
+            chunks.push(Chunk {
+                ptr,
+                layout: chunk_layout,
+            });
+            self.current.set(chunks.len() - 1);
+            self.offset.set(0);
+        }
+    }
+
+    /// Release every allocation at once.
+    /// The chunks are kept and reused by later allocations.
+    pub fn reset(&mut self) {
+        self.current.set(0);
+        self.offset.set(0);
+    }
+
+    /// Return the current position of the allocator.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            chunk: self.current.get(),
+            offset: self.offset.get(),
+        }
+    }
+
+    /// Release every allocation made after `checkpoint` was taken.
+    ///
+    /// # Safety
+    ///
+    /// No reference to memory allocated after the checkpoint may be used afterwards,
+    /// and the checkpoint must come from this allocator and not predate a
+    /// [`Bump::reset`] or an earlier rewind to an older checkpoint.
+    pub unsafe fn rewind(&self, checkpoint: Checkpoint) {
+        self.current.set(checkpoint.chunk);
+        self.offset.set(checkpoint.offset);
+    }
+
+    /// Run `f` and release everything it allocated once it returns.
+    /// The borrow checker makes sure none of those allocations escape the closure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::bump::Bump;
+    ///
+    /// let mut bump = Bump::new();
+    /// bump.alloc(1u32);
+    /// let used = bump.allocated_bytes();
+    /// let len = bump.scope(|bump| {
+    ///     let tmp = bump.alloc_slice_copy(&[0u8; 100]);
+    ///     tmp.len()
+    /// });
+    /// assert_eq!(len, 100);
+    /// assert_eq!(bump.allocated_bytes(), used);
+    /// ```
+    pub fn scope<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&Bump) -> R,
+    {
+        let checkpoint = self.checkpoint();
+        let res = f(self);
+        // SAFETY: `R` cannot borrow from the `&Bump` given to `f`, so nothing
+        // allocated inside the scope is reachable any more.
+        unsafe { self.rewind(checkpoint) };
+        res
+    }
+
+    /// Number of bytes handed out since the last reset, including alignment padding
+    /// and the unused tails of earlier chunks.
+    pub fn allocated_bytes(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        let current = self.current.get();
+        chunks[..current.min(chunks.len())]
+            .iter()
+            .map(|chunk| chunk.layout.size())
+            .sum::<usize>()
+            + self.offset.get()
+    }
+}
+
+// SAFETY: a block stays valid until `reset`, `scope` or `rewind` releases it. The
+// first two need `&mut Bump`, so they can't run while a collection holds the
+// allocator, and `rewind` leaves it to its caller not to use the memory after.
+unsafe impl Allocator for &Bump {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.alloc_layout(layout);
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // Blocks are only released all at once.
+    }
+}
+
+impl Drop for Bump {
+    fn drop(&mut self) {
+        for chunk in self.chunks.get_mut().drain(..) {
+            unsafe { alloc::dealloc(chunk.ptr.as_ptr(), chunk.layout) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::alloc::string::{String, ToString};
+
+    use super::*;
+
+    #[test]
+    fn alloc() {
+        let bump = Bump::new();
+        let a = bump.alloc(1u8);
+        let b = bump.alloc(2u64);
+        let c = bump.alloc([3u16; 3]);
+        *a += 1;
+        assert_eq!(*a, 2);
+        assert_eq!(*b, 2);
+        assert_eq!(*c, [3, 3, 3]);
+    }
+
+    #[test]
+    fn alignment() {
+        #[repr(align(64))]
+        struct Aligned(u8);
+
+        let bump = Bump::new();
+        for i in 0..100u8 {
+            bump.alloc(i);
+            let aligned = bump.alloc(Aligned(i));
+            assert_eq!(aligned as *const Aligned as usize % 64, 0);
+            assert_eq!(aligned.0, i);
+            let wide = bump.alloc(i as u128);
+            assert_eq!(
+                wide as *const u128 as usize % std::mem::align_of::<u128>(),
+                0
+            );
+        }
+        let zst = bump.alloc(());
+        assert_eq!(*zst, ());
+    }
+
+    #[test]
+    fn large_allocations() {
+        let bump = Bump::new();
+        let big = bump.alloc_slice_copy(&[7u8; 100_000]);
+        assert_eq!(big.len(), 100_000);
+        assert!(big.iter().all(|b| *b == 7));
+        let small = bump.alloc(1u32);
+        assert_eq!(*small, 1);
+    }
+
+    #[test]
+    fn reset_reuses_chunks() {
+        let mut bump = Bump::new();
+        for i in 0..10_000u64 {
+            bump.alloc(i);
+        }
+        let chunks = bump.chunks.borrow().len();
+        bump.reset();
+        assert_eq!(bump.allocated_bytes(), 0);
+        for i in 0..10_000u64 {
+            assert_eq!(*bump.alloc(i), i);
+        }
+        assert_eq!(bump.chunks.borrow().len(), chunks);
+    }
+
+    #[test]
+    fn scope() {
+        let mut bump = Bump::new();
+        let before = bump.alloc_str("kept").len();
+        let checkpoint = bump.checkpoint();
+        let total = bump.scope(|bump| {
+            let mut total = 0;
+            for i in 0..1000u32 {
+                total += *bump.alloc(i);
+            }
+            total
+        });
+        assert_eq!(total, 499_500);
+        assert_eq!(before, 4);
+        assert_eq!(bump.checkpoint(), checkpoint);
+    }
+
+    #[test]
+    fn collections_in_bump() {
+        use crate::hashmap::HashMap;
+        use crate::linked_list::LinkedList;
+        use crate::my_vec::MyVec;
+
+        let mut bump = Bump::new();
+        bump.scope(|bump| {
+            let mut vec = MyVec::new_in(bump);
+            vec.extend((0..1000).map(|i| i.to_string()));
+            let mut map = HashMap::new_in(bump);
+            map.extend(vec.iter().map(|s| (s.as_str(), s.len())));
+            let mut list = LinkedList::new_in(bump);
+            list.extend(0..10);
+            assert!(bump.allocated_bytes() > 1000 * size_of::<String>());
+
+            let copy = vec.clone();
+            assert_eq!(copy.allocator().allocated_bytes(), bump.allocated_bytes());
+            assert_eq!(copy[999], "999");
+            assert_eq!(map.get("42"), Some(&2));
+            assert_eq!(map.drain().count(), 1000);
+            assert_eq!(list.pop(), Some(9));
+            assert_eq!(list.clone().len(), 9);
+        });
+        assert_eq!(bump.allocated_bytes(), 0);
+    }
+}
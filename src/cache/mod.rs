@@ -0,0 +1,610 @@
+#[cfg(feature = "serde")]
+use alloc::vec::Vec;
+use core::{borrow::Borrow, fmt, hash::Hash};
+#[cfg(feature = "std")]
+use std::{hash::Hasher, sync::PoisonError, thread};
+
+#[cfg(feature = "std")]
+use crate::{
+    compat::DefaultHasher,
+    sync::{Mutex, MutexGuard},
+};
+use crate::{
+    hashmap::HashMap,
+    slot_map::{Key, SlotMap},
+};
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    // Toward the most recently used entry.
+    newer: Option<Key>,
+    // Toward the least recently used entry.
+    older: Option<Key>,
+}
+
+/// A map holding at most `capacity` entries, evicting the least recently used
+/// one to make room.
+///
+/// Entries live in a [`SlotMap`], linked from the most to the least recently
+/// used, and a [`HashMap`] finds an entry's slot from its key. Looking up,
+/// inserting and evicting all take constant time.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::cache::LruCache;
+///
+/// let mut cache = LruCache::new(2);
+/// cache.put("a", 1);
+/// cache.put("b", 2);
+/// // Using "a" makes "b" the least recently used.
+/// assert_eq!(cache.get("a"), Some(&1));
+/// cache.put("c", 3);
+/// assert_eq!(cache.get("b"), None);
+/// assert_eq!(cache.len(), 2);
+/// ```
+pub struct LruCache<K, V> {
+    index: HashMap<K, Key>,
+    entries: SlotMap<Entry<K, V>>,
+    newest: Option<Key>,
+    oldest: Option<Key>,
+    capacity: usize,
+}
+
+impl<K, V> LruCache<K, V> {
+    /// Create an empty cache holding at most `capacity` entries.
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "an LruCache needs room for at least one entry"
+        );
+        LruCache {
+            index: HashMap::new(),
+            entries: SlotMap::new(),
+            newest: None,
+            oldest: None,
+            capacity,
+        }
+    }
+
+    /// Most entries the cache holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return `true` if the cache holds no entry.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the entries, from the most to the least recently used.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            entries: &self.entries,
+            next: self.newest,
+            remaining: self.len(),
+        }
+    }
+
+    fn unlink(&mut self, slot: Key) {
+        let Entry { newer, older, .. } = self.entries[slot];
+        match newer {
+            Some(newer) => self.entries[newer].older = older,
+            None => self.newest = older,
+        }
+        match older {
+            Some(older) => self.entries[older].newer = newer,
+            None => self.oldest = newer,
+        }
+    }
+
+    fn push_newest(&mut self, slot: Key) {
+        let entry = &mut self.entries[slot];
+        entry.newer = None;
+        entry.older = self.newest;
+        match self.newest {
+            Some(newest) => self.entries[newest].newer = Some(slot),
+            None => self.oldest = Some(slot),
+        }
+        self.newest = Some(slot);
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// The value for `key`, marking it as the most recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// The value for `key`, mutably, marking it as the most recently used.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = *self.index.get(key)?;
+        if self.newest != Some(slot) {
+            self.unlink(slot);
+            self.push_newest(slot);
+        }
+        Some(&mut self.entries[slot].value)
+    }
+
+    /// The value for `key`, leaving the order of use alone.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = *self.index.get(key)?;
+        Some(&self.entries[slot].value)
+    }
+
+    /// Return `true` if the cache holds `key`, leaving the order of use alone.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.contains_key(key)
+    }
+
+    /// Insert `value` as the most recently used, and return the value it
+    /// replaced. If the cache was full, the least recently used entry is
+    /// evicted.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(core::mem::replace(old, value));
+        }
+        if self.len() == self.capacity {
+            self.pop_lru();
+        }
+        let slot = self.entries.insert(Entry {
+            key: key.clone(),
+            value,
+            newer: None,
+            older: None,
+        });
+        self.push_newest(slot);
+        self.index.insert(key, slot);
+        None
+    }
+
+    /// Remove the entry for `key` and return its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.index.remove(key)?;
+        self.unlink(slot);
+        self.entries.remove(slot).map(|entry| entry.value)
+    }
+
+    /// Remove and return the least recently used entry.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let slot = self.oldest?;
+        self.unlink(slot);
+        let entry = self.entries.remove(slot)?;
+        self.index.remove(&entry.key);
+        Some((entry.key, entry.value))
+    }
+
+    /// Remove every entry.
+    pub fn clear(&mut self) {
+        self.index = HashMap::new();
+        self.entries.clear();
+        self.newest = None;
+        self.oldest = None;
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the entries of an [`LruCache`], from the most to the least
+/// recently used.
+pub struct Iter<'a, K, V> {
+    entries: &'a SlotMap<Entry<K, V>>,
+    next: Option<Key>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = &self.entries[self.next?];
+        self.next = entry.older;
+        self.remaining -= 1;
+        Some((&entry.key, &entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a LruCache<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An [`LruCache`] shared between threads, split into independently locked
+/// shards.
+///
+/// Each key belongs to one shard, picked from its hash, and every operation
+/// locks only that shard, so threads using keys in different shards don't wait
+/// for each other. Each shard evicts its own least recently used entry when it
+/// is full, which approximates a single LRU order: an entry may be evicted
+/// while older ones survive in other shards.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::cache::ConcurrentLru;
+///
+/// let cache = ConcurrentLru::new(10_000);
+/// thread::scope(|s| {
+///     for t in 0..4 {
+///         let cache = &cache;
+///         s.spawn(move || {
+///             for i in 0..100 {
+///                 cache.put(t * 100 + i, i * i);
+///             }
+///         });
+///     }
+/// });
+/// assert_eq!(cache.get(&305, |v| *v), Some(25));
+/// assert_eq!(cache.len(), 400);
+/// ```
+#[cfg(feature = "std")]
+pub struct ConcurrentLru<K, V> {
+    shards: Box<[Mutex<LruCache<K, V>>]>,
+}
+
+#[cfg(feature = "std")]
+impl<K, V> ConcurrentLru<K, V> {
+    /// Create an empty cache holding about `capacity` entries, with a few
+    /// shards per available CPU. Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_shards(capacity, cpus * 4)
+    }
+
+    /// Create an empty cache holding about `capacity` entries, with up to
+    /// `shards` shards. Panics if either is zero.
+    ///
+    /// The capacity is split evenly between the shards, rounding up, and
+    /// there are never more shards than entries.
+    pub fn with_shards(capacity: usize, shards: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "a ConcurrentLru needs room for at least one entry"
+        );
+        assert!(shards > 0, "a ConcurrentLru needs at least one shard");
+        let shards = shards.min(capacity);
+        let per_shard = capacity.div_ceil(shards);
+        ConcurrentLru {
+            shards: (0..shards)
+                .map(|_| Mutex::new(LruCache::new(per_shard)))
+                .collect(),
+        }
+    }
+
+    /// Number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Most entries the cache holds, summed over the shards.
+    pub fn capacity(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).capacity()).sum()
+    }
+
+    /// Number of entries. Since shards are counted one at a time, entries
+    /// added or removed meanwhile may or may not be counted.
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).len()).sum()
+    }
+
+    /// Return `true` if no shard holds an entry.
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| lock(shard).is_empty())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Hash + Eq + Clone, V> ConcurrentLru<K, V> {
+    fn shard<Q: Hash + ?Sized>(&self, key: &Q) -> MutexGuard<'_, LruCache<K, V>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        // High bits, since the shard's own map picks buckets with the low ones.
+        let index = (hasher.finish() >> 32) as usize % self.shards.len();
+        lock(&self.shards[index])
+    }
+
+    /// Call `f` on the value for `key`, marking it as the most recently used,
+    /// and return what it returns.
+    ///
+    /// `f` runs with the shard locked, so it should be short and must not use
+    /// the cache.
+    pub fn get<Q, R, F>(&self, key: &Q, f: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&V) -> R,
+    {
+        self.shard(key).get(key).map(f)
+    }
+
+    /// Return `true` if the cache holds `key`, leaving the order of use alone.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shard(key).contains_key(key)
+    }
+
+    /// Insert `value` as the most recently used in its shard, and return the
+    /// value it replaced. If the shard was full, its least recently used entry
+    /// is evicted.
+    pub fn put(&self, key: K, value: V) -> Option<V> {
+        self.shard(&key).put(key, value)
+    }
+
+    /// Remove the entry for `key` and return its value.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shard(key).remove(key)
+    }
+
+    /// Remove every entry.
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            lock(shard).clear();
+        }
+    }
+}
+
+// An `LruCache` is never left broken by a panic: the only user code that runs
+// with a shard locked is `get`'s closure, which can't change it.
+#[cfg(feature = "std")]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(feature = "std")]
+impl<K, V> fmt::Debug for ConcurrentLru<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConcurrentLru")
+            .field("shards", &self.shard_count())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A cache serializes as its capacity and its entries, from the least to the
+/// most recently used, so putting them back in that order restores the
+/// recency.
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for LruCache<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.reverse();
+        let mut state = serializer.serialize_struct("LruCache", 2)?;
+        state.serialize_field("capacity", &self.capacity)?;
+        state.serialize_field("entries", &entries)?;
+        state.end()
+    }
+}
+
+/// Entries past the capacity evict the earlier ones, as with [`LruCache::put`].
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for LruCache<K, V>
+where
+    K: serde::Deserialize<'de> + Hash + Eq + Clone,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "LruCache")]
+        struct Repr<K, V> {
+            capacity: usize,
+            entries: Vec<(K, V)>,
+        }
+
+        let Repr { capacity, entries } = Repr::deserialize(deserializer)?;
+        if capacity == 0 {
+            return Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(0),
+                &"a capacity of at least one entry",
+            ));
+        }
+        let mut cache = LruCache::new(capacity);
+        for (key, value) in entries {
+            cache.put(key, value);
+        }
+        Ok(cache)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for LruCache<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Hash + Eq + Clone,
+    V: arbitrary::Arbitrary<'a>,
+{
+    /// The capacity is at most 64, so that longer inputs also evict.
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut cache = LruCache::new(u.int_in_range(1..=64)?);
+        for entry in u.arbitrary_iter::<(K, V)>()? {
+            let (key, value) = entry?;
+            cache.put(key, value);
+        }
+        Ok(cache)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for LruCache<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + Hash + Eq + Clone + 'static,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    /// The capacity is at most 64, so that longer runs of puts also evict.
+    fn arbitrary_with((size, args): Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        let entries =
+            proptest::collection::vec(proptest::arbitrary::any_with::<(K, V)>(args), size);
+        (1..=64usize, entries)
+            .prop_map(|(capacity, entries)| {
+                let mut cache = LruCache::new(capacity);
+                for (key, value) in entries {
+                    cache.put(key, value);
+                }
+                cache
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec::Vec};
+
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = LruCache::new(3);
+        for (k, v) in [("a", 1), ("b", 2), ("c", 3)] {
+            assert_eq!(cache.put(k, v), None);
+        }
+        assert_eq!(cache.get("a"), Some(&1));
+        assert_eq!(cache.peek("b"), Some(&2));
+        assert_eq!(cache.put("c", 30), Some(3));
+        cache.put("d", 4);
+        // "b" was only peeked at, so it went first.
+        assert!(!cache.contains_key("b"));
+        let order: Vec<_> = cache.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(order, [("d", 4), ("c", 30), ("a", 1)]);
+
+        *cache.get_mut("a").unwrap() += 10;
+        assert_eq!(cache.pop_lru(), Some(("c", 30)));
+        assert_eq!(cache.remove("d"), Some(4));
+        assert_eq!(cache.remove("d"), None);
+        assert_eq!(format!("{cache:?}"), r#"{"a": 11}"#);
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.pop_lru(), None);
+        cache.put("e", 5);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn capacity_of_one() {
+        let mut cache = LruCache::new(1);
+        cache.put(1, "one");
+        cache.put(2, "two");
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2), Some(&"two"));
+        assert_eq!(cache.capacity(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn concurrent_shards_stay_bounded() {
+        let cache = ConcurrentLru::with_shards(64, 8);
+        assert_eq!(cache.capacity(), 64);
+        thread::scope(|s| {
+            for t in 0..4u32 {
+                let cache = &cache;
+                s.spawn(move || {
+                    for i in 0..1_000 {
+                        let key = (t * 1_000 + i) % 200;
+                        if cache.get(&key, |v| assert_eq!(*v, key * 2)).is_none() {
+                            cache.put(key, key * 2);
+                        }
+                    }
+                });
+            }
+        });
+        assert!(cache.len() <= 64);
+        assert!(!cache.is_empty());
+        cache.clear();
+        assert_eq!(cache.len(), 0);
+
+        // Never more shards than entries.
+        let small: ConcurrentLru<u8, u8> = ConcurrentLru::with_shards(3, 16);
+        assert_eq!(small.shard_count(), 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut cache = LruCache::new(3);
+        cache.put(1, 'a');
+        cache.put(2, 'b');
+        cache.get(&1);
+        let json = serde_json::to_string(&cache).unwrap();
+        assert_eq!(json, r#"{"capacity":3,"entries":[[2,"b"],[1,"a"]]}"#);
+        let mut back: LruCache<i32, char> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.capacity(), 3);
+        assert_eq!(back.pop_lru(), Some((2, 'b')));
+
+        let json = r#"{"capacity":0,"entries":[]}"#;
+        assert!(serde_json::from_str::<LruCache<i32, char>>(json).is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_evicts() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        // Capacity 2, then puts of 1, 2 and 3.
+        let bytes = [1, 1, 1, 10, 1, 2, 20, 1, 3, 30, 0];
+        let cache = LruCache::<u8, u8>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert_eq!(cache.capacity(), 2);
+        assert_eq!(cache.iter().collect::<Vec<_>>(), [(&3, &30), (&2, &20)]);
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn proptest_within_capacity(cache in proptest::arbitrary::any::<LruCache<u8, u8>>()) {
+            proptest::prop_assert!(cache.len() <= cache.capacity());
+        }
+    }
+}
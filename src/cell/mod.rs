@@ -0,0 +1,648 @@
+use core::{
+    cell::UnsafeCell,
+    cmp::Ordering,
+    error::Error,
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+
+#[cfg(debug_assertions)]
+use core::panic::Location;
+
+// Borrow state: positive for the number of shared borrows, -1 while mutably
+// borrowed, 0 otherwise.
+type BorrowFlag = isize;
+const UNUSED: BorrowFlag = 0;
+const WRITING: BorrowFlag = -1;
+
+/// A mutable memory location for values that are cheap to copy or move.
+///
+/// A `MyCell` never hands out references to its value: values only go in and
+/// out by copy or by move, so it can be mutated through a shared reference
+/// without any runtime bookkeeping. This makes it the right tool for counters
+/// and flags; reach for [`MyRefCell`] when the value has to be borrowed.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::cell::MyCell;
+///
+/// struct Node {
+///     visits: MyCell<u32>,
+/// }
+///
+/// let node = Node { visits: MyCell::new(0) };
+/// let shared = &node;
+/// shared.visits.set(1);
+/// shared.visits.update(|n| n + 1);
+/// assert_eq!(node.visits.get(), 2);
+/// assert_eq!(node.visits.replace(10), 2);
+/// ```
+#[repr(transparent)]
+pub struct MyCell<T: ?Sized> {
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for MyCell<T> {}
+
+impl<T> MyCell<T> {
+    /// Create a new cell holding `value`.
+    pub const fn new(value: T) -> Self {
+        MyCell {
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Replace the value.
+    pub fn set(&self, value: T) {
+        // Drop the old value only after the cell is back in a consistent state,
+        // since its `Drop` could reach the cell again.
+        drop(self.replace(value));
+    }
+
+    /// Replace the value, returning the old one.
+    pub fn replace(&self, value: T) -> T {
+        // No reference to the value can exist, so writing through the pointer
+        // is fine.
+        unsafe { mem::replace(&mut *self.value.get(), value) }
+    }
+
+    /// Swap the values of two cells.
+    pub fn swap(&self, other: &Self) {
+        if ptr::eq(self, other) {
+            return;
+        }
+        unsafe { ptr::swap(self.value.get(), other.value.get()) }
+    }
+
+    /// Take the value, leaving `Default::default()` in its place.
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        self.replace(T::default())
+    }
+
+    /// Consume the cell and return the value.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Copy> MyCell<T> {
+    /// A copy of the value.
+    pub fn get(&self) -> T {
+        unsafe { *self.value.get() }
+    }
+
+    /// Replace the value with `f` applied to it.
+    pub fn update<F: FnOnce(T) -> T>(&self, f: F) {
+        self.set(f(self.get()));
+    }
+}
+
+impl<T: ?Sized> MyCell<T> {
+    /// A mutable reference to the value. `&mut self` already proves that no
+    /// one else can reach the cell.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// A raw pointer to the value.
+    pub fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for MyCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MyCell")
+            .field("value", &self.get())
+            .finish()
+    }
+}
+
+impl<T: Default> Default for MyCell<T> {
+    fn default() -> Self {
+        MyCell::new(T::default())
+    }
+}
+
+impl<T: Copy> Clone for MyCell<T> {
+    fn clone(&self) -> Self {
+        MyCell::new(self.get())
+    }
+}
+
+impl<T> From<T> for MyCell<T> {
+    fn from(value: T) -> Self {
+        MyCell::new(value)
+    }
+}
+
+impl<T: Copy + PartialEq> PartialEq for MyCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: Copy + Eq> Eq for MyCell<T> {}
+
+impl<T: Copy + PartialOrd> PartialOrd for MyCell<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.get().partial_cmp(&other.get())
+    }
+}
+
+impl<T: Copy + Ord> Ord for MyCell<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.get().cmp(&other.get())
+    }
+}
+
+/// A mutable memory location with borrows checked at runtime.
+///
+/// Like `RefCell`, [`MyRefCell::borrow`] and [`MyRefCell::borrow_mut`] hand out
+/// guards and panic if the borrow would conflict with an outstanding one. In
+/// debug builds the cell also records where the borrow was taken, so the panic
+/// points at the code holding it and not only at the code that tripped over
+/// it. Shared borrows only record the one that took the cell from unborrowed,
+/// so with several of them the reported one may already have been dropped.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::cell::MyRefCell;
+///
+/// let cell = MyRefCell::new(vec![1, 2]);
+/// cell.borrow_mut().push(3);
+/// assert_eq!(*cell.borrow(), vec![1, 2, 3]);
+///
+/// let reader = cell.borrow();
+/// let err = cell.try_borrow_mut().unwrap_err();
+/// // In debug builds the message goes on to name the line that took `reader`.
+/// assert!(err.to_string().starts_with("already borrowed"));
+/// drop(reader);
+/// assert!(cell.try_borrow_mut().is_ok());
+/// ```
+pub struct MyRefCell<T: ?Sized> {
+    borrow: MyCell<BorrowFlag>,
+    // Where the borrow that last took the cell from unborrowed was taken.
+    #[cfg(debug_assertions)]
+    borrowed_at: MyCell<Option<&'static Location<'static>>>,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for MyRefCell<T> {}
+
+/// Error returned by [`MyRefCell::try_borrow`] while the cell is mutably
+/// borrowed.
+pub struct BorrowError {
+    #[cfg(debug_assertions)]
+    location: &'static Location<'static>,
+}
+
+/// Error returned by [`MyRefCell::try_borrow_mut`] while the cell is borrowed.
+pub struct BorrowMutError {
+    #[cfg(debug_assertions)]
+    location: &'static Location<'static>,
+}
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("already mutably borrowed")?;
+        #[cfg(debug_assertions)]
+        write!(f, " at {}", self.location)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for BorrowMutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("already borrowed")?;
+        #[cfg(debug_assertions)]
+        write!(f, " at {}", self.location)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("BorrowError");
+        #[cfg(debug_assertions)]
+        d.field("location", &self.location);
+        d.finish()
+    }
+}
+
+impl fmt::Debug for BorrowMutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("BorrowMutError");
+        #[cfg(debug_assertions)]
+        d.field("location", &self.location);
+        d.finish()
+    }
+}
+
+impl Error for BorrowError {}
+impl Error for BorrowMutError {}
+
+impl<T> MyRefCell<T> {
+    /// Create a new cell holding `value`.
+    pub const fn new(value: T) -> Self {
+        MyRefCell {
+            borrow: MyCell::new(UNUSED),
+            #[cfg(debug_assertions)]
+            borrowed_at: MyCell::new(None),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consume the cell and return the value.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Replace the value, returning the old one.
+    /// Panics if the cell is borrowed.
+    #[track_caller]
+    pub fn replace(&self, value: T) -> T {
+        mem::replace(&mut *self.borrow_mut(), value)
+    }
+
+    /// Replace the value with one computed from it, returning the old one.
+    /// Panics if the cell is borrowed.
+    #[track_caller]
+    pub fn replace_with<F: FnOnce(&mut T) -> T>(&self, f: F) -> T {
+        let mut guard = self.borrow_mut();
+        let value = f(&mut guard);
+        mem::replace(&mut *guard, value)
+    }
+
+    /// Swap the values of two cells.
+    /// Panics if either cell is borrowed.
+    #[track_caller]
+    pub fn swap(&self, other: &Self) {
+        mem::swap(&mut *self.borrow_mut(), &mut *other.borrow_mut());
+    }
+
+    /// Take the value, leaving `Default::default()` in its place.
+    /// Panics if the cell is borrowed.
+    #[track_caller]
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        self.replace(T::default())
+    }
+}
+
+impl<T: ?Sized> MyRefCell<T> {
+    /// Borrow the value. Any number of shared borrows can be held at once.
+    /// Panics if the value is mutably borrowed.
+    #[track_caller]
+    pub fn borrow(&self) -> Ref<'_, T> {
+        match self.try_borrow() {
+            Ok(guard) => guard,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Borrow the value, or return an error if it is mutably borrowed.
+    #[track_caller]
+    pub fn try_borrow(&self) -> Result<Ref<'_, T>, BorrowError> {
+        let flag = self.borrow.get();
+        if flag == WRITING {
+            return Err(BorrowError {
+                #[cfg(debug_assertions)]
+                location: self.borrowed_at.get().unwrap(),
+            });
+        }
+        assert!(flag < BorrowFlag::MAX, "too many immutable borrows");
+        self.borrow.set(flag + 1);
+        #[cfg(debug_assertions)]
+        if flag == UNUSED {
+            self.borrowed_at.set(Some(Location::caller()));
+        }
+        Ok(Ref {
+            value: unsafe { NonNull::new_unchecked(self.value.get()) },
+            borrow: BorrowRef {
+                borrow: &self.borrow,
+            },
+        })
+    }
+
+    /// Mutably borrow the value. Only one mutable borrow, and no shared one,
+    /// can be held at once.
+    /// Panics if the value is borrowed.
+    #[track_caller]
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        match self.try_borrow_mut() {
+            Ok(guard) => guard,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Mutably borrow the value, or return an error if it is borrowed.
+    #[track_caller]
+    pub fn try_borrow_mut(&self) -> Result<RefMut<'_, T>, BorrowMutError> {
+        if self.borrow.get() != UNUSED {
+            return Err(BorrowMutError {
+                #[cfg(debug_assertions)]
+                location: self.borrowed_at.get().unwrap(),
+            });
+        }
+        self.borrow.set(WRITING);
+        #[cfg(debug_assertions)]
+        self.borrowed_at.set(Some(Location::caller()));
+        Ok(RefMut {
+            value: unsafe { NonNull::new_unchecked(self.value.get()) },
+            borrow: BorrowRefMut {
+                borrow: &self.borrow,
+            },
+            _marker: PhantomData,
+        })
+    }
+
+    /// A mutable reference to the value. No runtime check is needed, as
+    /// `&mut self` already proves that no borrow is outstanding.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// A raw pointer to the value.
+    pub fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+}
+
+// Releases a shared borrow when dropped.
+struct BorrowRef<'b> {
+    borrow: &'b MyCell<BorrowFlag>,
+}
+
+impl Drop for BorrowRef<'_> {
+    fn drop(&mut self) {
+        self.borrow.set(self.borrow.get() - 1);
+    }
+}
+
+impl Clone for BorrowRef<'_> {
+    fn clone(&self) -> Self {
+        let flag = self.borrow.get();
+        assert!(flag < BorrowFlag::MAX, "too many immutable borrows");
+        self.borrow.set(flag + 1);
+        BorrowRef {
+            borrow: self.borrow,
+        }
+    }
+}
+
+// Releases a mutable borrow when dropped.
+struct BorrowRefMut<'b> {
+    borrow: &'b MyCell<BorrowFlag>,
+}
+
+impl Drop for BorrowRefMut<'_> {
+    fn drop(&mut self) {
+        self.borrow.set(UNUSED);
+    }
+}
+
+/// A shared borrow of the value of a [`MyRefCell`].
+pub struct Ref<'b, T: ?Sized + 'b> {
+    // A pointer rather than a reference, so `Ref::map` can point into the value.
+    value: NonNull<T>,
+    borrow: BorrowRef<'b>,
+}
+
+impl<'b, T: ?Sized> Ref<'b, T> {
+    /// Make another shared borrow of the same value.
+    #[allow(clippy::should_implement_trait)]
+    pub fn clone(orig: &Self) -> Self {
+        Ref {
+            value: orig.value,
+            borrow: orig.borrow.clone(),
+        }
+    }
+
+    /// Turn the borrow into a borrow of a part of the value.
+    pub fn map<U: ?Sized, F: FnOnce(&T) -> &U>(orig: Self, f: F) -> Ref<'b, U> {
+        Ref {
+            value: NonNull::from(f(&*orig)),
+            borrow: orig.borrow,
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Ref<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for Ref<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+/// A mutable borrow of the value of a [`MyRefCell`].
+pub struct RefMut<'b, T: ?Sized + 'b> {
+    value: NonNull<T>,
+    borrow: BorrowRefMut<'b>,
+    // `RefMut` acts like a `&'b mut T`, which is invariant in `T`.
+    _marker: PhantomData<&'b mut T>,
+}
+
+impl<'b, T: ?Sized> RefMut<'b, T> {
+    /// Turn the borrow into a borrow of a part of the value.
+    pub fn map<U: ?Sized, F: FnOnce(&mut T) -> &mut U>(mut orig: Self, f: F) -> RefMut<'b, U> {
+        RefMut {
+            value: NonNull::from(f(&mut *orig)),
+            borrow: orig.borrow,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for RefMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RefMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RefMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MyRefCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("MyRefCell");
+        match self.try_borrow() {
+            Ok(value) => d.field("value", &value),
+            Err(_) => d.field("value", &format_args!("<borrowed>")),
+        };
+        d.finish()
+    }
+}
+
+impl<T: Default> Default for MyRefCell<T> {
+    fn default() -> Self {
+        MyRefCell::new(T::default())
+    }
+}
+
+impl<T: Clone> Clone for MyRefCell<T> {
+    /// Panics if the value is mutably borrowed.
+    #[track_caller]
+    fn clone(&self) -> Self {
+        MyRefCell::new(self.borrow().clone())
+    }
+}
+
+impl<T> From<T> for MyRefCell<T> {
+    fn from(value: T) -> Self {
+        MyRefCell::new(value)
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for MyRefCell<T> {
+    /// Panics if either value is mutably borrowed.
+    fn eq(&self, other: &Self) -> bool {
+        *self.borrow() == *other.borrow()
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for MyRefCell<T> {}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for MyRefCell<T> {
+    /// Panics if either value is mutably borrowed.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.borrow().partial_cmp(&*other.borrow())
+    }
+}
+
+impl<T: ?Sized + Ord> Ord for MyRefCell<T> {
+    /// Panics if either value is mutably borrowed.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.borrow().cmp(&*other.borrow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String, vec, vec::Vec};
+
+    use super::*;
+
+    #[test]
+    fn cell_values_go_in_and_out() {
+        let a = MyCell::new(1);
+        let b = MyCell::new(2);
+        a.swap(&b);
+        a.swap(&a);
+        assert_eq!((a.get(), b.get()), (2, 1));
+        a.update(|n| n * 10);
+        assert_eq!(a.take(), 20);
+        assert_eq!(a, MyCell::new(0));
+
+        let mut names = MyCell::new(vec!["x"]);
+        names.get_mut().push("y");
+        assert_eq!(names.replace(Vec::new()), vec!["x", "y"]);
+        assert!(names.into_inner().is_empty());
+    }
+
+    #[test]
+    fn borrow_rules() {
+        let cell = MyRefCell::new(5);
+        let a = cell.borrow();
+        let b = Ref::clone(&a);
+        assert!(cell.try_borrow().is_ok());
+        assert!(cell.try_borrow_mut().is_err());
+        drop(a);
+        assert!(cell.try_borrow_mut().is_err());
+        drop(b);
+
+        let mut w = cell.borrow_mut();
+        *w += 1;
+        assert!(cell.try_borrow().is_err());
+        assert_eq!(format!("{cell:?}"), "MyRefCell { value: <borrowed> }");
+        drop(w);
+        assert_eq!(format!("{cell:?}"), "MyRefCell { value: 6 }");
+        assert_eq!(cell.replace(7), 6);
+        assert_eq!(cell.take(), 7);
+    }
+
+    #[test]
+    fn map_keeps_the_borrow() {
+        let cell = MyRefCell::new((1, String::from("a")));
+        let name = Ref::map(cell.borrow(), |pair| pair.1.as_str());
+        assert_eq!(&*name, "a");
+        assert!(cell.try_borrow_mut().is_err());
+        drop(name);
+
+        let mut num = RefMut::map(cell.borrow_mut(), |pair| &mut pair.0);
+        *num += 1;
+        assert!(cell.try_borrow().is_err());
+        drop(num);
+        assert_eq!(cell.borrow().0, 2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn errors_report_the_conflicting_borrow() {
+        use alloc::string::ToString;
+
+        let cell = MyRefCell::new(());
+        let line = line!() + 1;
+        let first = cell.borrow();
+        let _second = cell.borrow();
+        let err = cell.try_borrow_mut().unwrap_err();
+        assert_eq!(err.location.file(), file!());
+        assert_eq!(err.location.line(), line);
+        drop(first);
+        drop(_second);
+
+        let writer = cell.borrow_mut();
+        let err = cell.try_borrow().unwrap_err();
+        assert_eq!(err.location.line(), line!() - 2);
+        assert!(err.to_string().starts_with("already mutably borrowed at "));
+        drop(writer);
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn conflicting_borrow_mut_panics() {
+        let cell = MyRefCell::new(0);
+        let _reader = cell.borrow();
+        cell.borrow_mut();
+    }
+}
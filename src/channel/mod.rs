@@ -0,0 +1,511 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    arc::MyArc,
+    sync::{SpinLock, WaitQueue},
+};
+
+struct State<T> {
+    // Never grows past the capacity, so pushing doesn't allocate under the
+    // lock.
+    queue: VecDeque<T>,
+    senders: usize,
+    receivers: usize,
+}
+
+struct Channel<T> {
+    // Only held to push, pop or count, so spinning beats parking.
+    state: SpinLock<State<T>>,
+    capacity: usize,
+    // Receivers waiting for a message, and senders waiting for room.
+    not_empty: WaitQueue,
+    not_full: WaitQueue,
+}
+
+/// Create a channel that holds up to `capacity` messages, returning its two
+/// ends.
+///
+/// Both ends can be cloned and shared between threads: every message is
+/// received exactly once, by whichever receiver gets to it first, in the order
+/// it was sent. A sender blocks while the channel is full, and a receiver while
+/// it is empty. Once every receiver is gone, sending fails and hands the
+/// message back. Once every sender is gone, receivers drain what is left, then
+/// fail instead of blocking.
+///
+/// Panics if `capacity` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::channel;
+///
+/// let (tx, rx) = channel::bounded(2);
+/// thread::scope(|s| {
+///     for id in 0..3 {
+///         let tx = tx.clone();
+///         s.spawn(move || tx.send(id).unwrap());
+///     }
+///     drop(tx);
+///     let mut got: Vec<i32> = rx.iter().collect();
+///     got.sort();
+///     assert_eq!(got, [0, 1, 2]);
+/// });
+/// ```
+pub fn bounded<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "capacity must be positive");
+    let chan = MyArc::new(Channel {
+        state: SpinLock::new(State {
+            queue: VecDeque::with_capacity(capacity),
+            senders: 1,
+            receivers: 1,
+        }),
+        capacity,
+        not_empty: WaitQueue::new(),
+        not_full: WaitQueue::new(),
+    });
+    (Sender { chan: chan.clone() }, Receiver { chan })
+}
+
+/// The sending end of a [`bounded`] channel.
+pub struct Sender<T> {
+    chan: MyArc<Channel<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send `value`, blocking while the channel is full.
+    ///
+    /// Fails, handing `value` back, if every receiver is gone.
+    pub fn send(&self, mut value: T) -> Result<(), SendError<T>> {
+        loop {
+            match self.try_send(value) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(value)) => return Err(SendError(value)),
+                Err(TrySendError::Full(v)) => value = v,
+            }
+            let chan = &*self.chan;
+            // Checked under the queue lock, so a receiver that makes room after
+            // it is sure to wake us.
+            chan.not_full.wait_if(|| {
+                let state = chan.state.lock();
+                state.queue.len() == chan.capacity && state.receivers > 0
+            });
+        }
+    }
+
+    /// Send `value` if there is room, without blocking.
+    ///
+    /// Fails, handing `value` back, if the channel is full or every receiver is
+    /// gone.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.chan.state.lock();
+        if state.receivers == 0 {
+            return Err(TrySendError::Disconnected(value));
+        }
+        if state.queue.len() == self.chan.capacity {
+            return Err(TrySendError::Full(value));
+        }
+        state.queue.push_back(value);
+        drop(state);
+        self.chan.not_empty.wake_one();
+        Ok(())
+    }
+
+    /// Return `true` if [`send`](Sender::send) would return without blocking,
+    /// because there is room or every receiver is gone.
+    pub fn ready(&self) -> bool {
+        let state = self.chan.state.lock();
+        state.queue.len() < self.chan.capacity || state.receivers == 0
+    }
+
+    /// Number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.chan.state.lock().queue.len()
+    }
+
+    /// Return `true` if the channel holds no messages.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of messages the channel can hold.
+    pub fn capacity(&self) -> usize {
+        self.chan.capacity
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.chan.state.lock().senders += 1;
+        Sender {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.chan.state.lock();
+        state.senders -= 1;
+        let last = state.senders == 0;
+        drop(state);
+        if last {
+            // Blocked receivers must see the channel is disconnected.
+            self.chan.not_empty.wake_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving end of a [`bounded`] channel.
+pub struct Receiver<T> {
+    chan: MyArc<Channel<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive the oldest message, blocking while the channel is empty.
+    ///
+    /// Fails if the channel is empty and every sender is gone.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        loop {
+            match self.try_recv() {
+                Ok(value) => return Ok(value),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {}
+            }
+            let chan = &*self.chan;
+            chan.not_empty.wait_if(|| {
+                let state = chan.state.lock();
+                state.queue.is_empty() && state.senders > 0
+            });
+        }
+    }
+
+    /// Receive the oldest message, if there is one, without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.chan.state.lock();
+        match state.queue.pop_front() {
+            Some(value) => {
+                drop(state);
+                self.chan.not_full.wake_one();
+                Ok(value)
+            }
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Receive the oldest message, blocking for at most `timeout` while the
+    /// channel is empty.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let Some(deadline) = Instant::now().checked_add(timeout) else {
+            // Too far away to tell from forever.
+            return self.recv().map_err(|_| RecvTimeoutError::Disconnected);
+        };
+        loop {
+            match self.try_recv() {
+                Ok(value) => return Ok(value),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+            if Instant::now() >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            let chan = &*self.chan;
+            chan.not_empty.wait_if_until(deadline, || {
+                let state = chan.state.lock();
+                state.queue.is_empty() && state.senders > 0
+            });
+        }
+    }
+
+    /// Return `true` if [`recv`](Receiver::recv) would return without
+    /// blocking, because there is a message or every sender is gone.
+    ///
+    /// Polling several receivers with it is a poor man's `select`.
+    pub fn ready(&self) -> bool {
+        let state = self.chan.state.lock();
+        !state.queue.is_empty() || state.senders == 0
+    }
+
+    /// Number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.chan.state.lock().queue.len()
+    }
+
+    /// Return `true` if the channel holds no messages.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of messages the channel can hold.
+    pub fn capacity(&self) -> usize {
+        self.chan.capacity
+    }
+
+    /// An iterator receiving messages until every sender is gone.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { rx: self }
+    }
+
+    /// An iterator receiving the messages already in the channel.
+    pub fn try_iter(&self) -> TryIter<'_, T> {
+        TryIter { rx: self }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.chan.state.lock().receivers += 1;
+        Receiver {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.chan.state.lock();
+        state.receivers -= 1;
+        let last = state.receivers == 0;
+        drop(state);
+        if last {
+            // Blocked senders must see the channel is disconnected.
+            self.chan.not_full.wake_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+/// An iterator over the messages of a [`Receiver`], blocking for each one
+/// until every sender is gone.
+pub struct Iter<'a, T> {
+    rx: &'a Receiver<T>,
+}
+
+impl<T> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+/// An iterator over the messages already in the channel of a [`Receiver`].
+pub struct TryIter<'a, T> {
+    rx: &'a Receiver<T>,
+}
+
+impl<T> Iterator for TryIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// An owning iterator over the messages of a [`Receiver`], blocking for each
+/// one until every sender is gone.
+pub struct IntoIter<T> {
+    rx: Receiver<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { rx: self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn try_apis_and_disconnection() {
+        let (tx, rx) = bounded(2);
+        assert!(tx.ready() && !rx.ready());
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        tx.try_send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+        assert!(!tx.ready() && rx.ready());
+        assert_eq!((rx.len(), rx.capacity()), (2, 2));
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [1, 2]);
+
+        tx.send(4).unwrap();
+        drop(tx);
+        assert!(rx.ready());
+        assert_eq!(rx.recv(), Ok(4));
+        assert_eq!(rx.recv(), Err(RecvError));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+
+        let (tx, rx) = bounded(1);
+        drop(rx);
+        assert_eq!(tx.send("lost"), Err(SendError("lost")));
+    }
+
+    #[test]
+    fn recv_timeout() {
+        let (tx, rx) = bounded::<u8>(1);
+        let start = Instant::now();
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(20)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(10));
+                tx.send(7).unwrap();
+            });
+            assert_eq!(rx.recv_timeout(Duration::from_secs(10)), Ok(7));
+        });
+        drop(tx);
+        assert_eq!(
+            rx.recv_timeout(Duration::MAX),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn blocked_ends_are_woken() {
+        let (tx, rx) = bounded(1);
+        tx.send(0).unwrap();
+        thread::scope(|s| {
+            // Blocks until the receiver makes room, then until it is dropped.
+            let sender = s.spawn(move || {
+                tx.send(1).unwrap();
+                tx.send(2).unwrap();
+                tx.send(3)
+            });
+            assert_eq!(rx.recv(), Ok(0));
+            assert_eq!(rx.recv(), Ok(1));
+            thread::sleep(Duration::from_millis(10));
+            drop(rx);
+            assert_eq!(sender.join().unwrap(), Err(SendError(3)));
+        });
+    }
+
+    #[test]
+    fn many_producers_and_consumers() {
+        let (tx, rx) = bounded(4);
+        let total: u64 = thread::scope(|s| {
+            for p in 0..4 {
+                let tx = tx.clone();
+                s.spawn(move || {
+                    for i in 0..1_000 {
+                        tx.send(p * 1_000 + i).unwrap();
+                    }
+                });
+            }
+            drop(tx);
+            let consumers: Vec<_> = (0..4)
+                .map(|_| {
+                    let rx = rx.clone();
+                    s.spawn(move || rx.into_iter().sum::<u64>())
+                })
+                .collect();
+            drop(rx);
+            consumers.into_iter().map(|c| c.join().unwrap()).sum()
+        });
+        assert_eq!(total, (0..4_000).sum::<u64>());
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use loom::{cell::UnsafeCell, thread};
+
+    use super::*;
+
+    // A message whose reads loom checks against its write, so a send that
+    // doesn't publish it to the receiver is caught.
+    struct Message(UnsafeCell<usize>);
+
+    impl Message {
+        fn new(n: usize) -> Self {
+            let message = Message(UnsafeCell::new(0));
+            message.0.with_mut(|p| unsafe { *p = n });
+            message
+        }
+
+        fn get(&self) -> usize {
+            self.0.with(|p| unsafe { *p })
+        }
+    }
+
+    #[test]
+    fn send_wakes_blocked_receiver() {
+        loom::model(|| {
+            let (tx, rx) = bounded(1);
+            let handle = thread::spawn(move || tx.send(Message::new(1)).unwrap());
+            assert_eq!(rx.recv().unwrap().get(), 1);
+            handle.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn recv_wakes_blocked_sender() {
+        // Every preemption multiplies the schedules of the two spin locks,
+        // and three are enough to block the sender at each point.
+        let mut model = loom::model::Builder::new();
+        model.preemption_bound = Some(3);
+        model.check(|| {
+            let (tx, rx) = bounded(1);
+            let handle = thread::spawn(move || {
+                tx.send(1).unwrap();
+                tx.send(2).unwrap();
+            });
+            assert_eq!(rx.recv(), Ok(1));
+            assert_eq!(rx.recv(), Ok(2));
+            handle.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn last_sender_dropped_wakes_receiver() {
+        loom::model(|| {
+            let (tx, rx) = bounded::<usize>(1);
+            let handle = thread::spawn(move || drop(tx));
+            assert_eq!(rx.recv(), Err(RecvError));
+            handle.join().unwrap();
+        });
+    }
+}
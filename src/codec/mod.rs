@@ -0,0 +1,426 @@
+use alloc::{string::String, vec::Vec};
+use core::{error, fmt, ops::Range, str};
+
+/// Version of the format written by [`to_bytes`], stored in its first byte.
+pub const VERSION: u8 = 1;
+
+/// A value that can be written in the crate's compact binary format.
+///
+/// Unsigned integers are written as LEB128 varints, seven bits to a byte, and
+/// signed integers are zigzag-mapped first so that small magnitudes of either
+/// sign stay short. `u8`, `i8` and `bool` take one byte and floats their
+/// little-endian bits. Sequences and strings are a varint length followed by
+/// their elements.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::codec::{self, Encode};
+///
+/// let mut out = Vec::new();
+/// 300u32.encode(&mut out);
+/// (-2i64).encode(&mut out);
+/// assert_eq!(out, [0xac, 0x02, 0x03]);
+/// assert_eq!(codec::from_bytes::<Vec<u32>>(&codec::to_bytes(&[1u32, 500])), Ok(vec![1, 500]));
+/// ```
+pub trait Encode {
+    /// Append the encoding of `self` to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+}
+
+/// A value that can be read back from the crate's compact binary format.
+pub trait Decode: Sized {
+    /// Read a value from the start of `input` and advance it past the bytes
+    /// read.
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError>;
+}
+
+/// The error returned when bytes are not a valid encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of a value.
+    UnexpectedEnd,
+    /// The input was written by a version of the format this one can't read.
+    UnsupportedVersion(u8),
+    /// A varint is too large for the integer type being read.
+    Overflow,
+    /// The bytes don't make a valid value of the type being read.
+    Invalid(&'static str),
+    /// Bytes were left over after the value was read.
+    TrailingBytes,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => f.write_str("unexpected end of input"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "unsupported format version {v}"),
+            DecodeError::Overflow => f.write_str("integer out of range"),
+            DecodeError::Invalid(what) => write!(f, "invalid {what}"),
+            DecodeError::TrailingBytes => f.write_str("trailing bytes after value"),
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+/// Encode `value` after a byte holding the format [`VERSION`].
+pub fn to_bytes<T: Encode + ?Sized>(value: &T) -> Vec<u8> {
+    let mut out = alloc::vec![VERSION];
+    value.encode(&mut out);
+    out
+}
+
+/// Decode a value written by [`to_bytes`]. Fails if the bytes were written
+/// by another version of the format or go on past the value.
+pub fn from_bytes<T: Decode>(mut bytes: &[u8]) -> Result<T, DecodeError> {
+    match u8::decode(&mut bytes)? {
+        VERSION => {}
+        version => return Err(DecodeError::UnsupportedVersion(version)),
+    }
+    let value = T::decode(&mut bytes)?;
+    if !bytes.is_empty() {
+        return Err(DecodeError::TrailingBytes);
+    }
+    Ok(value)
+}
+
+/// Split the first `n` bytes off `input`.
+pub(crate) fn take<'a>(input: &mut &'a [u8], n: usize) -> Result<&'a [u8], DecodeError> {
+    let (head, rest) = input
+        .split_at_checked(n)
+        .ok_or(DecodeError::UnexpectedEnd)?;
+    *input = rest;
+    Ok(head)
+}
+
+fn write_varint(mut value: u128, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<u128, DecodeError> {
+    let mut value = 0;
+    for shift in (0..u128::BITS).step_by(7) {
+        let byte = u8::decode(input)?;
+        let bits = u128::from(byte & 0x7f);
+        // The last byte of a `u128` only has room for two bits.
+        if bits << shift >> shift != bits {
+            return Err(DecodeError::Overflow);
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DecodeError::Overflow)
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty)*) => {$(
+        impl Encode for $t {
+            fn encode(&self, out: &mut Vec<u8>) {
+                write_varint(*self as u128, out);
+            }
+        }
+
+        impl Decode for $t {
+            fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+                <$t>::try_from(read_varint(input)?).map_err(|_| DecodeError::Overflow)
+            }
+        }
+    )*};
+}
+
+impl_unsigned!(u16 u32 u64 u128 usize);
+
+macro_rules! impl_signed {
+    ($($t:ty => $u:ty)*) => {$(
+        impl Encode for $t {
+            fn encode(&self, out: &mut Vec<u8>) {
+                // Zigzag: 0, -1, 1, -2, ... become 0, 1, 2, 3, ...
+                let zigzag = (*self << 1) ^ (*self >> (<$t>::BITS - 1));
+                (zigzag as $u).encode(out);
+            }
+        }
+
+        impl Decode for $t {
+            fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+                let zigzag = <$u>::decode(input)?;
+                Ok((zigzag >> 1) as $t ^ -((zigzag & 1) as $t))
+            }
+        }
+    )*};
+}
+
+impl_signed!(i16 => u16 i32 => u32 i64 => u64 i128 => u128 isize => usize);
+
+impl Encode for u8 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+}
+
+impl Decode for u8 {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let (&byte, rest) = input.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+        *input = rest;
+        Ok(byte)
+    }
+}
+
+impl Encode for i8 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+}
+
+impl Decode for i8 {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        u8::decode(input).map(|b| b as i8)
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+}
+
+impl Decode for bool {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        match u8::decode(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DecodeError::Invalid("bool")),
+        }
+    }
+}
+
+impl Encode for char {
+    fn encode(&self, out: &mut Vec<u8>) {
+        u32::from(*self).encode(out);
+    }
+}
+
+impl Decode for char {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        char::from_u32(u32::decode(input)?).ok_or(DecodeError::Invalid("char"))
+    }
+}
+
+macro_rules! impl_float {
+    ($($t:ty)*) => {$(
+        impl Encode for $t {
+            fn encode(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+        }
+
+        impl Decode for $t {
+            fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+                let bytes = take(input, size_of::<$t>())?;
+                Ok(<$t>::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    )*};
+}
+
+impl_float!(f32 f64);
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (**self).encode(out);
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for item in self {
+            item.encode(out);
+        }
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_slice().encode(out);
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_slice().encode(out);
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let len = usize::decode(input)?;
+        // Every element takes at least one byte, so a corrupt length can't
+        // make this allocate more than the input's size.
+        let mut vec = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            vec.push(T::decode(input)?);
+        }
+        Ok(vec)
+    }
+}
+
+impl Encode for str {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_bytes().encode(out);
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_str().encode(out);
+    }
+}
+
+impl Decode for String {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let len = usize::decode(input)?;
+        let bytes = take(input, len)?;
+        let s = str::from_utf8(bytes).map_err(|_| DecodeError::Invalid("UTF-8 string"))?;
+        Ok(s.into())
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.is_some().encode(out);
+        if let Some(value) = self {
+            value.encode(out);
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        match bool::decode(input)? {
+            true => T::decode(input).map(Some),
+            false => Ok(None),
+        }
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+        self.1.encode(out);
+    }
+}
+
+impl<A: Decode, B: Decode> Decode for (A, B) {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok((A::decode(input)?, B::decode(input)?))
+    }
+}
+
+impl<T: Encode> Encode for Range<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.start.encode(out);
+        self.end.encode(out);
+    }
+}
+
+impl<T: Decode> Decode for Range<T> {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(T::decode(input)?..T::decode(input)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    fn round_trip<T: Encode + Decode + PartialEq + fmt::Debug>(value: T) -> usize {
+        let bytes = to_bytes(&value);
+        assert_eq!(from_bytes::<T>(&bytes), Ok(value));
+        bytes.len() - 1
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(round_trip(0u64), 1);
+        assert_eq!(round_trip(127u64), 1);
+        assert_eq!(round_trip(128u64), 2);
+        assert_eq!(round_trip(u64::MAX), 10);
+        assert_eq!(round_trip(u128::MAX), 19);
+        assert_eq!(round_trip(usize::MAX), 10);
+        assert_eq!(round_trip(-1i32), 1);
+        assert_eq!(round_trip(63i32), 1);
+        assert_eq!(round_trip(-65i32), 2);
+        assert_eq!(round_trip(i64::MIN), 10);
+        assert_eq!(round_trip(i128::MAX), 19);
+        assert_eq!(round_trip(-128i8), 1);
+        assert_eq!(round_trip(255u8), 1);
+    }
+
+    #[test]
+    fn other_values() {
+        round_trip(true);
+        round_trip('é');
+        round_trip(-1.5f64);
+        round_trip(f32::INFINITY);
+        round_trip("héllo".to_string());
+        round_trip(vec![Some((1u8, -2i16)), None]);
+        round_trip(3u32..10);
+        assert_eq!(round_trip(Vec::<u64>::new()), 1);
+        assert_eq!(to_bytes("hi"), [VERSION, 2, b'h', b'i']);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(from_bytes::<u8>(&[]), Err(DecodeError::UnexpectedEnd));
+        assert_eq!(
+            from_bytes::<u8>(&[9, 0]),
+            Err(DecodeError::UnsupportedVersion(9))
+        );
+        assert_eq!(
+            from_bytes::<u8>(&[VERSION, 1, 2]),
+            Err(DecodeError::TrailingBytes)
+        );
+        assert_eq!(
+            from_bytes::<u16>(&[VERSION, 0x80]),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            from_bytes::<u16>(&[VERSION, 0xff, 0xff, 0x04]),
+            Err(DecodeError::Overflow)
+        );
+        let mut long = vec![VERSION];
+        long.extend([0x80; 20]);
+        assert_eq!(from_bytes::<u128>(&long), Err(DecodeError::Overflow));
+        let mut max = to_bytes(&u128::MAX);
+        max[19] = 0x07;
+        assert_eq!(from_bytes::<u128>(&max), Err(DecodeError::Overflow));
+        assert_eq!(
+            from_bytes::<bool>(&[VERSION, 2]),
+            Err(DecodeError::Invalid("bool"))
+        );
+        assert_eq!(
+            from_bytes::<String>(&[VERSION, 1, 0xff]),
+            Err(DecodeError::Invalid("UTF-8 string"))
+        );
+        // A huge length fails on the missing elements without allocating them.
+        let mut huge = vec![VERSION];
+        usize::MAX.encode(&mut huge);
+        assert_eq!(
+            from_bytes::<Vec<u8>>(&huge),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            DecodeError::UnsupportedVersion(2).to_string(),
+            "unsupported format version 2"
+        );
+    }
+}
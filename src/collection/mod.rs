@@ -0,0 +1,795 @@
+use core::hash::Hash;
+
+use crate::array_vec::ArrayVec;
+use crate::avl::AvlMap;
+use crate::b_plus_tree::BPlusTree;
+use crate::bi_map::BiMap;
+use crate::hashmap::HashMap;
+use crate::index_map::IndexMap;
+use crate::linked_list::LinkedList;
+use crate::my_vec::MyVec;
+use crate::range_set::RangeSet;
+use crate::red_black::RedBlackMap;
+use crate::ring::CircularBuffer;
+use crate::skip_list::{SkipMap, SkipSet};
+use crate::small_vec::SmallVec;
+use crate::tiny_map::TinyMap;
+use crate::treap::Treap;
+use crate::vec_map::VecMap;
+
+/// A container that knows how many elements it holds and can drop them all.
+///
+/// Implemented by every owning collection in the crate, so code that only
+/// needs to size or empty a container can take any of them.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::collection::Collection;
+/// use my_std::linked_list::LinkedList;
+/// use my_std::skip_list::SkipSet;
+///
+/// fn reset(c: &mut impl Collection) -> usize {
+///     let len = c.len();
+///     c.clear();
+///     len
+/// }
+///
+/// let mut list = LinkedList::new();
+/// list.push(1);
+/// list.push(2);
+/// assert_eq!(reset(&mut list), 2);
+/// assert!(list.is_empty());
+///
+/// let mut set: SkipSet<_> = ["a", "b", "c"].into_iter().collect();
+/// assert_eq!(reset(&mut set), 3);
+/// ```
+pub trait Collection {
+    /// Number of elements in the collection.
+    fn len(&self) -> usize;
+
+    /// Return `true` if the collection holds no element.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove every element.
+    fn clear(&mut self);
+}
+
+/// A collection whose elements are addressed by their position.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::collection::Sequence;
+/// use my_std::my_vec::MyVec;
+/// use my_std::small_vec::SmallVec;
+///
+/// fn rotate_left<S: Sequence>(s: &mut S) {
+///     if !s.is_empty() {
+///         let first = s.remove(0);
+///         s.insert(s.len(), first);
+///     }
+/// }
+///
+/// let mut v = MyVec::new();
+/// for x in [1, 2, 3] {
+///     v.push(x);
+/// }
+/// rotate_left(&mut v);
+/// assert_eq!(*v, [2, 3, 1]);
+///
+/// let mut s: SmallVec<_, 2> = [1, 2, 3].into_iter().collect();
+/// rotate_left(&mut s);
+/// assert_eq!(Sequence::get(&s, 2), Some(&1));
+/// ```
+pub trait Sequence: Collection {
+    /// The type of the elements.
+    type Item;
+
+    /// The element at `index`, or `None` if it is out of bounds.
+    fn get(&self, index: usize) -> Option<&Self::Item>;
+
+    /// Insert `value` at `index`, shifting every later element back.
+    ///
+    /// Panics if `index > len`.
+    fn insert(&mut self, index: usize, value: Self::Item);
+
+    /// Remove and return the element at `index`, shifting every later
+    /// element forward.
+    ///
+    /// Panics if `index >= len`.
+    fn remove(&mut self, index: usize) -> Self::Item;
+}
+
+/// A collection of values looked up by key.
+///
+/// The lookup methods take the key type itself rather than anything it
+/// borrows as, since the maps of the crate disagree on what a borrowed key
+/// must implement.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::avl::AvlMap;
+/// use my_std::collection::Map;
+/// use my_std::hashmap::HashMap;
+///
+/// fn count_words<'a, M: Map<Key = &'a str, Value = usize>>(map: &mut M, text: &'a str) {
+///     for word in text.split_whitespace() {
+///         *map.entry(word).or_default() += 1;
+///     }
+/// }
+///
+/// let text = "the cat and the hat";
+/// let mut tree = AvlMap::new();
+/// count_words(&mut tree, text);
+/// let mut hash = HashMap::new();
+/// count_words(&mut hash, text);
+/// assert_eq!(Map::get(&tree, &"the"), Some(&2));
+/// assert_eq!(Map::get(&hash, &"the"), Some(&2));
+/// assert_eq!(Map::get(&hash, &"dog"), None);
+/// ```
+pub trait Map: Collection {
+    /// The type of the keys.
+    type Key;
+    /// The type of the values.
+    type Value;
+
+    /// The value for `key`.
+    fn get(&self, key: &Self::Key) -> Option<&Self::Value>;
+
+    /// The value for `key`, mutably.
+    fn get_mut(&mut self, key: &Self::Key) -> Option<&mut Self::Value>;
+
+    /// Map `key` to `value`, returning the value it replaced, if any.
+    fn insert(&mut self, key: Self::Key, value: Self::Value) -> Option<Self::Value>;
+
+    /// Remove `key`, returning its value if it was present.
+    fn remove(&mut self, key: &Self::Key) -> Option<Self::Value>;
+
+    /// Return `true` if the map holds `key`.
+    fn contains_key(&self, key: &Self::Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The entry for `key`, to read or update it in place.
+    fn entry(&mut self, key: Self::Key) -> Entry<'_, Self> {
+        Entry { map: self, key }
+    }
+}
+
+/// A key of a [`Map`], which may or may not be present, returned by
+/// [`Map::entry`].
+///
+/// Built on the lookups every map provides, so each method searches the map
+/// again, and inserting a missing key clones it to find its value afterwards.
+pub struct Entry<'a, M: Map + ?Sized> {
+    map: &'a mut M,
+    key: M::Key,
+}
+
+impl<'a, M: Map + ?Sized> Entry<'a, M> {
+    /// The key of the entry.
+    pub fn key(&self) -> &M::Key {
+        &self.key
+    }
+
+    /// Apply `f` to the value if the key is present.
+    pub fn and_modify<F: FnOnce(&mut M::Value)>(self, f: F) -> Self {
+        if let Some(value) = self.map.get_mut(&self.key) {
+            f(value);
+        }
+        self
+    }
+
+    /// The value of the key, after inserting `default` if it was missing.
+    pub fn or_insert(self, default: M::Value) -> &'a mut M::Value
+    where
+        M::Key: Clone,
+    {
+        self.or_insert_with(|| default)
+    }
+
+    /// The value of the key, after inserting the result of `default` if it
+    /// was missing.
+    pub fn or_insert_with<F: FnOnce() -> M::Value>(self, default: F) -> &'a mut M::Value
+    where
+        M::Key: Clone,
+    {
+        if !self.map.contains_key(&self.key) {
+            self.map.insert(self.key.clone(), default());
+        }
+        self.map.get_mut(&self.key).unwrap()
+    }
+
+    /// The value of the key, after inserting the default value if it was
+    /// missing.
+    pub fn or_default(self) -> &'a mut M::Value
+    where
+        M::Key: Clone,
+        M::Value: Default,
+    {
+        self.or_insert_with(Default::default)
+    }
+}
+
+// Forwards `Collection` to the inherent methods of the same name.
+macro_rules! collection {
+    ([$($params:tt)*] $ty:ty) => {
+        impl<$($params)*> Collection for $ty {
+            fn len(&self) -> usize {
+                <$ty>::len(self)
+            }
+
+            fn is_empty(&self) -> bool {
+                <$ty>::is_empty(self)
+            }
+
+            fn clear(&mut self) {
+                <$ty>::clear(self)
+            }
+        }
+    };
+}
+
+// Implements `Collection` and `Sequence` for a vector that derefs to a slice.
+macro_rules! sequence {
+    ([$($params:tt)*] $ty:ty) => {
+        impl<$($params)*> Collection for $ty {
+            fn len(&self) -> usize {
+                <[T]>::len(self)
+            }
+
+            fn clear(&mut self) {
+                <$ty>::clear(self)
+            }
+        }
+
+        impl<$($params)*> Sequence for $ty {
+            type Item = T;
+
+            fn get(&self, index: usize) -> Option<&T> {
+                <[T]>::get(self, index)
+            }
+
+            fn insert(&mut self, index: usize, value: T) {
+                <$ty>::insert(self, index, value)
+            }
+
+            fn remove(&mut self, index: usize) -> T {
+                <$ty>::remove(self, index)
+            }
+        }
+    };
+}
+
+// Implements `Collection` and `Map` by forwarding to the inherent methods,
+// `$remove` being the one that removes a key.
+macro_rules! map {
+    ([$($params:tt)*] $ty:ty, $remove:ident) => {
+        collection!([$($params)*] $ty);
+
+        impl<$($params)*> Map for $ty {
+            type Key = K;
+            type Value = V;
+
+            fn get(&self, key: &K) -> Option<&V> {
+                <$ty>::get(self, key)
+            }
+
+            fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+                <$ty>::get_mut(self, key)
+            }
+
+            fn insert(&mut self, key: K, value: V) -> Option<V> {
+                <$ty>::insert(self, key, value)
+            }
+
+            fn remove(&mut self, key: &K) -> Option<V> {
+                <$ty>::$remove(self, key)
+            }
+
+            fn contains_key(&self, key: &K) -> bool {
+                <$ty>::contains_key(self, key)
+            }
+        }
+    };
+}
+
+// `get` can't lend out an element of a `LinkedList` from behind its
+// `RefCell`, and a `CircularBuffer` overwrites rather than shifts, so both are
+// only collections.
+collection!([T: Clone] LinkedList<T>);
+collection!([T, const N: usize] CircularBuffer<T, N>);
+collection!([T] SkipSet<T>);
+collection!([T] RangeSet<T>);
+collection!([L, R] BiMap<L, R>);
+
+sequence!([T] MyVec<T>);
+sequence!([T, const N: usize] SmallVec<T, N>);
+sequence!([T, const N: usize] ArrayVec<T, N>);
+
+map!([K: Hash + Eq, V] HashMap<K, V>, remove);
+map!([K: Hash + Eq, V] IndexMap<K, V>, shift_remove);
+map!([K: Eq, V] TinyMap<K, V>, remove);
+map!([K: Ord, V] VecMap<K, V>, remove);
+map!([K: Ord, V] AvlMap<K, V>, remove);
+map!([K: Ord, V] RedBlackMap<K, V>, remove);
+map!([K: Ord, V] Treap<K, V>, remove);
+map!([K: Ord + Clone, V] BPlusTree<K, V>, remove);
+map!([K: Ord, V] SkipMap<K, V>, remove);
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use std::ops::{Bound, RangeBounds};
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::leftist_heap::LeftistHeap;
+    use crate::splay::SplayMap;
+
+    fn check_sequence<S: Sequence<Item = i32>>(mut s: S) {
+        for (i, x) in [3, 1, 2].into_iter().enumerate() {
+            s.insert(i, x);
+        }
+        s.insert(1, 0);
+        assert_eq!(s.len(), 4);
+        assert_eq!(
+            (0..5).map(|i| s.get(i).copied()).collect::<Vec<_>>(),
+            [Some(3), Some(0), Some(1), Some(2), None]
+        );
+        assert_eq!(s.remove(0), 3);
+        assert_eq!(s.get(0), Some(&0));
+        s.clear();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn sequences() {
+        check_sequence(MyVec::new());
+        check_sequence(SmallVec::<_, 2>::new());
+        check_sequence(ArrayVec::<_, 4>::new());
+    }
+
+    fn check_map<M>()
+    where
+        M: Map<Key = u32, Value = String> + Default,
+        M: FromIterator<(u32, String)> + Extend<(u32, String)>,
+    {
+        // Later duplicates win, whether collected or extended.
+        let mut m: M = (0..60)
+            .chain(100..110)
+            .map(|k| (k % 50, k.to_string()))
+            .collect();
+        assert_eq!(m.len(), 50);
+        assert_eq!(m.get(&5).map(String::as_str), Some("105"));
+        assert_eq!(m.get(&15).map(String::as_str), Some("15"));
+        m.extend((40..60).map(|k| (k, format!("x{k}"))));
+        assert_eq!(m.len(), 60);
+        assert_eq!(m.get(&45).map(String::as_str), Some("x45"));
+        assert_eq!(m.get(&20).map(String::as_str), Some("20"));
+
+        let mut m = M::default();
+        for k in (0..100).rev() {
+            assert_eq!(m.insert(k, k.to_string()), None);
+        }
+        assert_eq!(m.insert(7, "seven".to_string()).as_deref(), Some("7"));
+        assert_eq!(m.len(), 100);
+        assert_eq!(m.get(&7).map(String::as_str), Some("seven"));
+        m.get_mut(&8).unwrap().push('!');
+        assert_eq!(m.get(&8).map(String::as_str), Some("8!"));
+
+        assert_eq!(m.remove(&50).as_deref(), Some("50"));
+        assert_eq!(m.remove(&50), None);
+        assert!(!m.contains_key(&50));
+
+        m.entry(50).or_insert_with(|| "new".to_string()).push('?');
+        m.entry(51).and_modify(|v| v.clear()).or_default();
+        assert_eq!(m.get(&50).map(String::as_str), Some("new?"));
+        assert_eq!(m.get(&51).map(String::as_str), Some(""));
+        assert_eq!(*m.entry(200).key(), 200);
+        assert_eq!(m.len(), 100);
+
+        m.clear();
+        assert!(m.is_empty());
+        assert_eq!(m.get(&1), None);
+    }
+
+    #[test]
+    fn maps() {
+        check_map::<HashMap<_, _>>();
+        check_map::<IndexMap<_, _>>();
+        check_map::<TinyMap<_, _>>();
+        check_map::<VecMap<_, _>>();
+        check_map::<AvlMap<_, _>>();
+        check_map::<RedBlackMap<_, _>>();
+        check_map::<Treap<_, _>>();
+        check_map::<BPlusTree<_, _>>();
+        check_map::<SkipMap<_, _>>();
+    }
+
+    // `for` loops over `&M` and `&mut M` see every entry once, in the same order.
+    fn check_map_loops<M>(ordered: bool)
+    where
+        M: FromIterator<(u32, u32)>,
+        for<'a> &'a M: IntoIterator<Item = (&'a u32, &'a u32)>,
+        for<'a> &'a mut M: IntoIterator<Item = (&'a u32, &'a mut u32)>,
+    {
+        let mut m: M = (0..100).map(|k| (k * 37 % 100, 0)).collect();
+        let mut order = Vec::new();
+        for (&k, v) in &mut m {
+            *v = k * 2;
+            order.push(k);
+        }
+        let mut seen = Vec::new();
+        for (&k, &v) in &m {
+            assert_eq!(v, k * 2);
+            seen.push(k);
+        }
+        assert_eq!(seen, order);
+        if ordered {
+            assert_eq!(seen, (0..100).collect::<Vec<_>>());
+        } else {
+            seen.sort_unstable();
+            assert_eq!(seen, (0..100).collect::<Vec<_>>());
+        }
+    }
+
+    fn check_seq_loops<S>()
+    where
+        S: FromIterator<u32>,
+        for<'a> &'a S: IntoIterator<Item = &'a u32>,
+        for<'a> &'a mut S: IntoIterator<Item = &'a mut u32>,
+    {
+        let mut s: S = (0..20).collect();
+        for x in &mut s {
+            *x *= 3;
+        }
+        let all: Vec<_> = (&s).into_iter().copied().collect();
+        assert_eq!(all, (0..20).map(|x| x * 3).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn reference_loops() {
+        check_map_loops::<HashMap<_, _>>(false);
+        check_map_loops::<IndexMap<_, _>>(false);
+        check_map_loops::<TinyMap<_, _>>(false);
+        check_map_loops::<VecMap<_, _>>(true);
+        check_map_loops::<AvlMap<_, _>>(true);
+        check_map_loops::<RedBlackMap<_, _>>(true);
+        check_map_loops::<Treap<_, _>>(true);
+        check_map_loops::<SplayMap<_, _>>(true);
+        check_map_loops::<BPlusTree<_, _>>(true);
+        check_map_loops::<SkipMap<_, _>>(true);
+
+        check_seq_loops::<MyVec<_>>();
+        check_seq_loops::<SmallVec<_, 4>>();
+        check_seq_loops::<ArrayVec<_, 20>>();
+        check_seq_loops::<CircularBuffer<_, 20>>();
+        // Wrapped around the end of the buffer.
+        let mut ring: CircularBuffer<_, 8> = (0..13).collect();
+        ring.iter_mut().for_each(|x| *x += 100);
+        assert_eq!(
+            ring.iter().copied().collect::<Vec<_>>(),
+            (105..113).collect::<Vec<_>>()
+        );
+        assert_eq!(ring.iter_mut().next_back(), Some(&mut 112));
+    }
+
+    #[test]
+    fn collections() {
+        fn drain(c: &mut impl Collection) -> usize {
+            let len = c.len();
+            c.clear();
+            assert!(c.is_empty());
+            len
+        }
+
+        let mut list = LinkedList::new();
+        (0..1000).for_each(|x| list.push(x));
+        assert_eq!(drain(&mut list), 1000);
+        let mut ring: CircularBuffer<_, 3> = (0..5).collect();
+        assert_eq!(drain(&mut ring), 3);
+        let mut set: SkipSet<_> = (0..10).collect();
+        assert_eq!(drain(&mut set), 10);
+        let mut ranges: RangeSet<_> = [0..2, 5..8].into_iter().collect();
+        assert_eq!(drain(&mut ranges), 2);
+        let mut bimap: BiMap<_, _> = [(1, 'a'), (2, 'b')].into_iter().collect();
+        assert_eq!(drain(&mut bimap), 2);
+    }
+
+    // An element that shares a token with all the others, so the token's count
+    // tells how many elements are alive.
+    #[derive(Debug)]
+    struct Tracked {
+        id: u32,
+        _token: Rc<()>,
+    }
+
+    impl PartialEq for Tracked {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+
+    impl Eq for Tracked {}
+
+    impl PartialOrd for Tracked {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Tracked {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.id.cmp(&other.id)
+        }
+    }
+
+    // A collection of `Tracked` elements, seen through its `retain` and
+    // `extract_if`.
+    trait Filterable {
+        // Whether `retain` and `extract_if` keep the iteration order of the
+        // elements they don't remove.
+        const KEEPS_ORDER: bool = true;
+
+        fn build(elems: Vec<Tracked>) -> Self;
+        fn ids(&self) -> Vec<u32>;
+        fn retain_ids(&mut self, f: &mut dyn FnMut(u32) -> bool);
+        // Extract the first `n` matching elements, then drop the iterator.
+        fn extract_ids(&mut self, f: &mut dyn FnMut(u32) -> bool, n: usize) -> Vec<Tracked>;
+    }
+
+    macro_rules! filterable_seq {
+        ($($ty:ty => $build:expr $(, keeps_order: $keeps:expr)?;)*) => {$(
+            impl Filterable for $ty {
+                $(const KEEPS_ORDER: bool = $keeps;)?
+
+                fn build(elems: Vec<Tracked>) -> Self {
+                    $build(elems)
+                }
+
+                fn ids(&self) -> Vec<u32> {
+                    self.iter().map(|x| x.id).collect()
+                }
+
+                fn retain_ids(&mut self, f: &mut dyn FnMut(u32) -> bool) {
+                    self.retain(|x| f(x.id));
+                }
+
+                fn extract_ids(
+                    &mut self,
+                    f: &mut dyn FnMut(u32) -> bool,
+                    n: usize,
+                ) -> Vec<Tracked> {
+                    self.extract_if(|x| f(x.id)).take(n).collect()
+                }
+            }
+        )*};
+    }
+
+    macro_rules! filterable_map {
+        ($($ty:ty),* $(,)?) => {$(
+            impl Filterable for $ty {
+                fn build(elems: Vec<Tracked>) -> Self {
+                    elems.into_iter().map(|x| (x.id, x)).collect()
+                }
+
+                fn ids(&self) -> Vec<u32> {
+                    self.into_iter().map(|(&k, _)| k).collect()
+                }
+
+                fn retain_ids(&mut self, f: &mut dyn FnMut(u32) -> bool) {
+                    self.retain(|&k, _| f(k));
+                }
+
+                fn extract_ids(
+                    &mut self,
+                    f: &mut dyn FnMut(u32) -> bool,
+                    n: usize,
+                ) -> Vec<Tracked> {
+                    self.extract_if(|&k, _| f(k)).take(n).map(|(_, v)| v).collect()
+                }
+            }
+        )*};
+    }
+
+    filterable_seq! {
+        MyVec<Tracked> => FromIterator::from_iter;
+        CircularBuffer<Tracked, 64> => FromIterator::from_iter;
+        SkipSet<Tracked> => FromIterator::from_iter;
+        LeftistHeap<Tracked> => FromIterator::from_iter, keeps_order: false;
+    }
+    filterable_map!(
+        HashMap<u32, Tracked>,
+        IndexMap<u32, Tracked>,
+        TinyMap<u32, Tracked>,
+        VecMap<u32, Tracked>,
+        BPlusTree<u32, Tracked>,
+        SkipMap<u32, Tracked>,
+    );
+
+    // Both operations see every element once, in iteration order, and move the
+    // removed ones out without leaking or dropping anything twice.
+    fn check_filterable<C: Filterable>() {
+        fn sorted(mut ids: Vec<u32>) -> Vec<u32> {
+            ids.sort_unstable();
+            ids
+        }
+
+        let token = Rc::new(());
+        let live = || Rc::strong_count(&token) - 1;
+        // Scrambled, so insertion order differs from key order.
+        let elems = (0..50)
+            .map(|i| Tracked {
+                id: i * 7 % 50,
+                _token: token.clone(),
+            })
+            .collect();
+        let mut c = C::build(elems);
+        assert_eq!(live(), 50);
+
+        let order = c.ids();
+        assert_eq!(sorted(order.clone()), (0..50).collect::<Vec<_>>());
+        let mut seen = Vec::new();
+        c.retain_ids(&mut |id| {
+            seen.push(id);
+            id % 3 != 0
+        });
+        assert_eq!(seen, order);
+        let kept: Vec<_> = order.into_iter().filter(|id| id % 3 != 0).collect();
+        if C::KEEPS_ORDER {
+            assert_eq!(c.ids(), kept);
+        } else {
+            assert_eq!(sorted(c.ids()), sorted(kept.clone()));
+        }
+        assert_eq!(live(), kept.len());
+
+        let order = c.ids();
+        seen.clear();
+        let extracted = c.extract_ids(
+            &mut |id| {
+                seen.push(id);
+                id % 2 == 0
+            },
+            5,
+        );
+        let out: Vec<_> = extracted.iter().map(|x| x.id).collect();
+        let even: Vec<_> = order.iter().copied().filter(|id| id % 2 == 0).collect();
+        assert_eq!(out, even[..5]);
+        // Visiting stopped at the fifth match, and the rest was left alone.
+        let last = order.iter().position(|&id| id == out[4]).unwrap();
+        assert_eq!(seen, order[..=last]);
+        let rest: Vec<_> = order.into_iter().filter(|id| !out.contains(id)).collect();
+        if C::KEEPS_ORDER {
+            assert_eq!(c.ids(), rest);
+        } else {
+            assert_eq!(sorted(c.ids()), sorted(rest.clone()));
+        }
+        assert_eq!(live(), rest.len() + 5);
+        drop(extracted);
+        assert_eq!(live(), rest.len());
+
+        drop(c);
+        assert_eq!(live(), 0);
+    }
+
+    trait Ranged {
+        fn range_keys(
+            &self,
+            range: (Bound<u32>, Bound<u32>),
+        ) -> Box<dyn DoubleEndedIterator<Item = u32> + '_>;
+    }
+
+    macro_rules! ranged {
+        ($($ty:ty),* $(,)?) => {
+            $(impl Ranged for $ty {
+                fn range_keys(
+                    &self,
+                    range: (Bound<u32>, Bound<u32>),
+                ) -> Box<dyn DoubleEndedIterator<Item = u32> + '_> {
+                    Box::new(self.range(range).map(|(k, _)| *k))
+                }
+            })*
+        };
+    }
+
+    ranged!(
+        VecMap<u32, u32>,
+        AvlMap<u32, u32>,
+        RedBlackMap<u32, u32>,
+        Treap<u32, u32>,
+        SplayMap<u32, u32>,
+        BPlusTree<u32, u32>,
+        SkipMap<u32, u32>,
+    );
+
+    // Every range is scanned front to back, back to front, and from both ends
+    // at once, which must meet without skipping or repeating an entry.
+    fn check_ranges<M>()
+    where
+        M: Ranged + FromIterator<(u32, u32)>,
+    {
+        let keys: Vec<u32> = (0..400)
+            .step_by(2)
+            .filter(|k| !(100..=180).contains(k))
+            .collect();
+        let m: M = keys.iter().rev().map(|&k| (k, k)).collect();
+        let points = [0, 1, 57, 100, 140, 181, 182, 398, 399, 450];
+        let bounds: Vec<_> = points
+            .iter()
+            .flat_map(|&p| [Bound::Included(p), Bound::Excluded(p)])
+            .chain([Bound::Unbounded])
+            .collect();
+        for &lo in &bounds {
+            for &hi in &bounds {
+                if let (
+                    Bound::Included(s) | Bound::Excluded(s),
+                    Bound::Included(e) | Bound::Excluded(e),
+                ) = (lo, hi)
+                {
+                    if s > e {
+                        continue;
+                    }
+                }
+                let range = (lo, hi);
+                let expected: Vec<_> = keys.iter().copied().filter(|k| range.contains(k)).collect();
+                assert_eq!(
+                    m.range_keys(range).collect::<Vec<_>>(),
+                    expected,
+                    "{range:?}"
+                );
+                let mut reversed: Vec<_> = m.range_keys(range).rev().collect();
+                reversed.reverse();
+                assert_eq!(reversed, expected, "{range:?}");
+
+                let mut iter = m.range_keys(range);
+                let (mut front, mut back) = (Vec::new(), Vec::new());
+                while let Some(k) = iter.next() {
+                    front.push(k);
+                    let Some(k) = iter.next_back() else {
+                        break;
+                    };
+                    back.push(k);
+                }
+                assert_eq!((iter.next(), iter.next_back()), (None, None));
+                front.extend(back.into_iter().rev());
+                assert_eq!(front, expected, "{range:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn double_ended_ranges() {
+        check_ranges::<VecMap<_, _>>();
+        check_ranges::<AvlMap<_, _>>();
+        check_ranges::<RedBlackMap<_, _>>();
+        check_ranges::<Treap<_, _>>();
+        check_ranges::<SplayMap<_, _>>();
+        check_ranges::<BPlusTree<_, _>>();
+        check_ranges::<SkipMap<_, _>>();
+    }
+
+    #[test]
+    fn retain_and_extract_if() {
+        check_filterable::<MyVec<_>>();
+        check_filterable::<CircularBuffer<_, 64>>();
+        check_filterable::<SkipSet<_>>();
+        check_filterable::<LeftistHeap<_>>();
+        check_filterable::<HashMap<_, _>>();
+        check_filterable::<IndexMap<_, _>>();
+        check_filterable::<TinyMap<_, _>>();
+        check_filterable::<VecMap<_, _>>();
+        check_filterable::<BPlusTree<_, _>>();
+        check_filterable::<SkipMap<_, _>>();
+    }
+}
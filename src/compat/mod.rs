@@ -0,0 +1,148 @@
+//! The few pieces of `std` the `no_std` build needs a stand-in for, and the
+//! atomics that `cfg(loom)` swaps for loom's model-checked ones.
+
+#[cfg(not(feature = "std"))]
+use core::hash::{Hash, Hasher};
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+/// Unkeyed hasher for hash tables.
+#[cfg(feature = "std")]
+pub(crate) use std::hash::DefaultHasher;
+
+/// Unkeyed hasher for hash tables, SipHash like `std`'s `DefaultHasher`.
+#[cfg(not(feature = "std"))]
+#[allow(deprecated)]
+#[derive(Debug, Clone)]
+pub(crate) struct DefaultHasher(core::hash::SipHasher);
+
+#[cfg(not(feature = "std"))]
+impl DefaultHasher {
+    #[allow(deprecated)]
+    pub(crate) fn new() -> Self {
+        DefaultHasher(core::hash::SipHasher::new())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Hasher for DefaultHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+/// A seed for a pseudo-random generator, different on every call.
+#[cfg(feature = "std")]
+pub(crate) fn random_seed() -> u64 {
+    use std::hash::{BuildHasher, RandomState};
+
+    // `RandomState` is randomly keyed per process, so hashing anything with it
+    // gives a random number.
+    RandomState::new().hash_one(0u64)
+}
+
+/// A seed for a pseudo-random generator, different on every call.
+#[cfg(not(feature = "std"))]
+pub(crate) fn random_seed() -> u64 {
+    // Without a source of entropy, count calls so seeds at least differ.
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    let mut hasher = DefaultHasher::new();
+    CALLS.fetch_add(1, Relaxed).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Abort the process, for errors that leave no safe way to unwind.
+#[cfg(feature = "std")]
+pub(crate) fn abort() -> ! {
+    std::process::abort()
+}
+
+/// Abort the process, for errors that leave no safe way to unwind.
+#[cfg(not(feature = "std"))]
+pub(crate) fn abort() -> ! {
+    core::intrinsics::abort()
+}
+
+/// Let other threads run while waiting for one of them.
+#[cfg(feature = "std")]
+pub(crate) fn yield_now() {
+    std::thread::yield_now()
+}
+
+/// Let other threads run while waiting for one of them.
+#[cfg(not(feature = "std"))]
+pub(crate) fn yield_now() {
+    // There is no scheduler to hand over to, so just spin.
+    core::hint::spin_loop()
+}
+
+/// Atomics of the types whose interleavings the loom tests explore.
+pub(crate) mod atomic {
+    #[cfg(not(loom))]
+    pub(crate) use core::sync::atomic::{fence, AtomicUsize, Ordering};
+    #[cfg(loom)]
+    pub(crate) use loom::sync::atomic::{fence, AtomicUsize, Ordering};
+
+    #[cfg(all(feature = "std", not(loom)))]
+    pub(crate) use core::sync::atomic::{AtomicBool, AtomicIsize, AtomicPtr, AtomicU8};
+    #[cfg(all(feature = "std", loom))]
+    pub(crate) use loom::sync::atomic::{AtomicBool, AtomicIsize, AtomicPtr, AtomicU8};
+
+    // Only the futex that parks threads itself needs it, and Linux has the
+    // kernel's.
+    #[cfg(all(feature = "std", not(loom)))]
+    #[allow(unused_imports)]
+    pub(crate) use core::sync::atomic::AtomicU32;
+    #[cfg(all(feature = "std", loom))]
+    pub(crate) use loom::sync::atomic::AtomicU32;
+}
+
+/// Parking, for the threads loom runs inside its model.
+#[cfg(feature = "std")]
+pub(crate) mod thread {
+    #[cfg(not(loom))]
+    pub(crate) use std::thread::{current, park, park_timeout, Thread};
+
+    #[cfg(loom)]
+    pub(crate) use loom::thread::{current, park, Thread};
+
+    /// Loom has no clock, so a timed park waits like an untimed one.
+    #[cfg(loom)]
+    pub(crate) fn park_timeout(_: std::time::Duration) {
+        park()
+    }
+}
+
+/// Signal a spin-wait, letting loom switch threads in its model.
+#[cfg(not(loom))]
+pub(crate) fn spin_loop() {
+    core::hint::spin_loop()
+}
+
+/// Signal a spin-wait, letting loom switch threads in its model.
+#[cfg(loom)]
+pub(crate) fn spin_loop() {
+    loom::hint::spin_loop()
+}
+
+/// Declare a `const fn`, except under loom, whose atomics can only be created
+/// at run time.
+#[cfg(feature = "std")]
+macro_rules! const_unless_loom {
+    ($(#[$attr:meta])* $vis:vis fn $($rest:tt)*) => {
+        $(#[$attr])*
+        #[cfg(not(loom))]
+        $vis const fn $($rest)*
+
+        $(#[$attr])*
+        #[cfg(loom)]
+        $vis fn $($rest)*
+    };
+}
+
+#[cfg(feature = "std")]
+pub(crate) use const_unless_loom;
@@ -0,0 +1,435 @@
+//! Epoch-based memory reclamation.
+//!
+//! A lock-free structure can't free a node as soon as it unlinks it: other
+//! threads may have loaded a pointer to it just before and still be reading
+//! it. With this module, a thread [`pin`]s itself before loading any pointer
+//! from the structure and keeps the returned [`Guard`] for as long as it uses
+//! them. Unlinked nodes are handed to [`Guard::defer_destroy`], which frees
+//! them only once every thread that was pinned at that point has unpinned.
+//!
+//! # How it works
+//!
+//! A global epoch counter only moves forward. Pinning records the current
+//! epoch in a per-thread entry, and deferred work is tagged with the epoch it
+//! was deferred in. The epoch advances from `e` to `e + 1` only when every
+//! pinned thread has recorded `e`, so once it reaches `e + 2`, no thread pinned
+//! before the work was deferred is still pinned, and the work can run.
+//!
+//! Each thread keeps its deferred work in a bag of its own and runs what became
+//! due every so often, or when asked to with [`Guard::collect`]. A thread that
+//! exits hands its bag over to a shared list, run by whichever thread collects
+//! next. A thread that stays pinned holds back reclamation for every structure
+//! using this module, so guards should be short-lived.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::ptr;
+//! use std::sync::atomic::{AtomicPtr, Ordering};
+//! use my_std::boxed::MyBox;
+//! use my_std::concurrent::epoch;
+//!
+//! let shared = AtomicPtr::new(MyBox::into_raw(MyBox::new(1)));
+//!
+//! let guard = epoch::pin();
+//! // Readers pinned now may still see the old value.
+//! let old = shared.swap(MyBox::into_raw(MyBox::new(2)), Ordering::AcqRel);
+//! unsafe {
+//!     assert_eq!(*old, 1);
+//!     guard.defer_destroy(old);
+//! }
+//! drop(guard);
+//!
+//! let guard = epoch::pin();
+//! let last = shared.swap(ptr::null_mut(), Ordering::AcqRel);
+//! unsafe { guard.defer_destroy(last) };
+//! ```
+
+use std::{
+    cell::{Cell, UnsafeCell},
+    fmt,
+    marker::PhantomData,
+    mem, ptr,
+    sync::atomic::{self, AtomicBool, AtomicPtr, AtomicUsize, Ordering::*},
+};
+
+use crate::{boxed::MyBox, sync::SpinLock};
+
+// Run what became due every this many pins.
+const PINS_PER_COLLECT: usize = 128;
+// Run what became due once a bag holds this many entries.
+const BAG_CAPACITY: usize = 64;
+
+static EPOCH: AtomicUsize = AtomicUsize::new(0);
+// Every entry ever created. Entries are never freed, only reused.
+static PARTICIPANTS: AtomicPtr<Local> = AtomicPtr::new(ptr::null_mut());
+// Bags of threads that exited.
+#[cfg(not(loom))]
+static ORPHANS: SpinLock<Vec<Deferred>> = SpinLock::new(Vec::new());
+// Loom's lock is created at run time, once per modeled execution.
+#[cfg(loom)]
+loom::lazy_static! {
+    static ref ORPHANS: SpinLock<Vec<Deferred>> = SpinLock::new(Vec::new());
+}
+
+struct Deferred {
+    epoch: usize,
+    f: Box<dyn FnOnce()>,
+}
+
+// `Guard::defer_unchecked` makes the caller promise that the work may run on
+// any thread.
+unsafe impl Send for Deferred {}
+
+// The entry of one thread.
+struct Local {
+    // The epoch shifted left by one, with the low bit set while pinned.
+    state: AtomicUsize,
+    // Taken by a live thread.
+    in_use: AtomicBool,
+    // Never changes once the entry is published.
+    next: *mut Local,
+    // The rest is only touched by the thread holding the entry.
+    guards: Cell<usize>,
+    pins: Cell<usize>,
+    // The thread exited while a guard was alive, so the last guard releases
+    // the entry.
+    detached: Cell<bool>,
+    bag: UnsafeCell<Vec<Deferred>>,
+}
+
+// Other threads only read `state`, `in_use` and `next`.
+unsafe impl Sync for Local {}
+
+impl Local {
+    // Take an unused entry, or publish a new one.
+    fn acquire() -> &'static Local {
+        let mut entry = PARTICIPANTS.load(Acquire);
+        while !entry.is_null() {
+            let local = unsafe { &*entry };
+            if local
+                .in_use
+                .compare_exchange(false, true, Acquire, Relaxed)
+                .is_ok()
+            {
+                local.detached.set(false);
+                return local;
+            }
+            entry = local.next;
+        }
+        let entry = MyBox::into_raw(MyBox::new(Local {
+            state: AtomicUsize::new(0),
+            in_use: AtomicBool::new(true),
+            next: ptr::null_mut(),
+            guards: Cell::new(0),
+            pins: Cell::new(0),
+            detached: Cell::new(false),
+            bag: UnsafeCell::new(Vec::new()),
+        }));
+        let mut head = PARTICIPANTS.load(Relaxed);
+        loop {
+            // Not shared until the exchange succeeds.
+            unsafe { (*entry).next = head };
+            match PARTICIPANTS.compare_exchange_weak(head, entry, Release, Relaxed) {
+                Ok(_) => return unsafe { &*entry },
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    // Hand the bag over and let another thread take the entry.
+    fn release(&self) {
+        let bag = mem::take(unsafe { &mut *self.bag.get() });
+        if !bag.is_empty() {
+            ORPHANS.lock().extend(bag);
+        }
+        self.in_use.store(false, Release);
+    }
+
+    fn pin(&'static self) -> Guard {
+        let guards = self.guards.get();
+        self.guards.set(guards + 1);
+        if guards == 0 {
+            let epoch = EPOCH.load(Relaxed);
+            self.state.store(epoch << 1 | 1, Relaxed);
+            // Make the pin visible before we load any pointer, and pairs with
+            // the fence in `try_advance`.
+            atomic::fence(SeqCst);
+            let pins = self.pins.get().wrapping_add(1);
+            self.pins.set(pins);
+            if pins.is_multiple_of(PINS_PER_COLLECT) {
+                self.collect();
+            }
+        }
+        Guard {
+            local: self,
+            _not_send: PhantomData,
+        }
+    }
+
+    fn unpin(&self) {
+        let guards = self.guards.get() - 1;
+        self.guards.set(guards);
+        if guards == 0 {
+            // `Release` so our reads happen before the epoch moves on.
+            self.state.store(0, Release);
+            if self.detached.get() {
+                self.release();
+            }
+        }
+    }
+
+    fn defer(&self, f: Box<dyn FnOnce()>) {
+        // The global epoch is at most one past ours while we are pinned, so
+        // this is never older than the epoch the caller unlinked in.
+        let epoch = (self.state.load(Relaxed) >> 1).wrapping_add(1);
+        let bag = unsafe { &mut *self.bag.get() };
+        bag.push(Deferred { epoch, f });
+        if bag.len() >= BAG_CAPACITY {
+            self.collect();
+        }
+    }
+
+    // Advance the epoch if we can, then run the work that became due.
+    fn collect(&self) {
+        let epoch = try_advance();
+        // Our bag is in the order the work was deferred, so the due entries
+        // come first. Take them out before running them, since they may defer
+        // more work.
+        let due: Vec<Deferred> = {
+            let bag = unsafe { &mut *self.bag.get() };
+            let n = bag.partition_point(|d| is_due(d.epoch, epoch));
+            bag.drain(..n).collect()
+        };
+        let orphans: Vec<Deferred> = match ORPHANS.try_lock() {
+            Some(mut orphans) if !orphans.is_empty() => {
+                let (due, waiting) = mem::take(&mut *orphans)
+                    .into_iter()
+                    .partition(|d| is_due(d.epoch, epoch));
+                *orphans = waiting;
+                due
+            }
+            _ => Vec::new(),
+        };
+        for deferred in due.into_iter().chain(orphans) {
+            (deferred.f)();
+        }
+    }
+}
+
+fn is_due(deferred: usize, epoch: usize) -> bool {
+    // Signed, since work may be tagged one epoch ahead of the global one.
+    epoch.wrapping_sub(deferred) as isize >= 2
+}
+
+// Move the epoch on if every pinned thread has seen the current one, and
+// return the epoch.
+fn try_advance() -> usize {
+    let epoch = EPOCH.load(Relaxed);
+    // Pairs with the fence in `Local::pin`: a thread whose pin we miss pinned
+    // after this point, so it sees the pointers as they are now.
+    atomic::fence(SeqCst);
+    let mut entry = PARTICIPANTS.load(Acquire);
+    while !entry.is_null() {
+        let local = unsafe { &*entry };
+        let state = local.state.load(Relaxed);
+        if state & 1 == 1 && state >> 1 != epoch {
+            return epoch;
+        }
+        entry = local.next;
+    }
+    atomic::fence(Acquire);
+    match EPOCH.compare_exchange(epoch, epoch.wrapping_add(1), Release, Relaxed) {
+        Ok(_) => epoch.wrapping_add(1),
+        Err(actual) => actual,
+    }
+}
+
+// Holds the thread's entry, and gives it back when the thread exits.
+struct Handle {
+    local: &'static Local,
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        if self.local.guards.get() == 0 {
+            self.local.release();
+        } else {
+            // A guard outlives the thread-local storage.
+            self.local.detached.set(true);
+        }
+    }
+}
+
+thread_local! {
+    static HANDLE: Handle = Handle { local: Local::acquire() };
+}
+
+/// Pin the current thread, returning a guard that keeps it pinned until
+/// dropped.
+///
+/// While the thread is pinned, nothing deferred with
+/// [`Guard::defer_destroy`] or the other `defer` methods after it pinned is
+/// freed, by any thread. Pinning again while pinned is cheap and just adds a
+/// guard.
+pub fn pin() -> Guard {
+    HANDLE
+        .try_with(|handle| handle.local.pin())
+        .unwrap_or_else(|_| {
+            // The thread is exiting: borrow an entry for this guard only.
+            let local = Local::acquire();
+            local.detached.set(true);
+            local.pin()
+        })
+}
+
+/// Return `true` if the current thread is pinned.
+pub fn is_pinned() -> bool {
+    HANDLE
+        .try_with(|handle| handle.local.guards.get() > 0)
+        .unwrap_or(false)
+}
+
+/// Keeps the current thread pinned, as returned by [`pin`].
+///
+/// Pointers loaded from a structure using this module stay valid as long as
+/// the guard that was alive when they were loaded.
+pub struct Guard {
+    local: &'static Local,
+    // The pin belongs to the thread.
+    _not_send: PhantomData<*mut ()>,
+}
+
+impl Guard {
+    /// Run `f` once no thread pinned now is still pinned.
+    pub fn defer<F: FnOnce() + Send + 'static>(&self, f: F) {
+        self.local.defer(Box::new(f));
+    }
+
+    /// Like [`defer`](Guard::defer), for work that isn't `Send` or
+    /// `'static`.
+    ///
+    /// # Safety
+    ///
+    /// `f` may run on any thread, at any later point, and must be fine with
+    /// that.
+    pub unsafe fn defer_unchecked<F: FnOnce()>(&self, f: F) {
+        let f: Box<dyn FnOnce() + '_> = Box::new(f);
+        // The caller vouches for `f` outliving its borrows.
+        let f: Box<dyn FnOnce()> = mem::transmute(f);
+        self.local.defer(f);
+    }
+
+    /// Free `ptr` once no thread pinned now is still pinned.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from [`MyBox::into_raw`] and be unreachable for threads
+    /// that pin from now on. Its value is dropped on any thread, at any later
+    /// point, so it must be fine with that.
+    pub unsafe fn defer_destroy<T>(&self, ptr: *mut T) {
+        self.defer_unchecked(move || drop(MyBox::from_raw(ptr)));
+    }
+
+    /// Try to advance the epoch, then run the work deferred by this thread
+    /// that became due.
+    pub fn collect(&self) {
+        self.local.collect();
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.local.unpin();
+    }
+}
+
+impl fmt::Debug for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Guard").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::Barrier;
+    use std::thread;
+
+    // Collect until `done`, giving other tests' pins time to end.
+    fn collect_until(done: impl Fn() -> bool) {
+        for _ in 0..10_000 {
+            if done() {
+                return;
+            }
+            pin().collect();
+            thread::yield_now();
+        }
+        panic!("deferred work never ran");
+    }
+
+    #[test]
+    fn nested_pins() {
+        assert!(!is_pinned());
+        let outer = pin();
+        let inner = pin();
+        assert!(is_pinned());
+        drop(outer);
+        assert!(is_pinned());
+        drop(inner);
+        assert!(!is_pinned());
+    }
+
+    #[test]
+    fn deferred_waits_for_own_guard() {
+        static RAN: AtomicBool = AtomicBool::new(false);
+        let guard = pin();
+        guard.defer(|| RAN.store(true, SeqCst));
+        for _ in 0..100 {
+            guard.collect();
+        }
+        assert!(!RAN.load(SeqCst));
+        drop(guard);
+        collect_until(|| RAN.load(SeqCst));
+    }
+
+    #[test]
+    fn deferred_waits_for_pinned_threads() {
+        static RAN: AtomicBool = AtomicBool::new(false);
+        let pinned = Barrier::new(2);
+        let unpin = Barrier::new(2);
+        thread::scope(|s| {
+            s.spawn(|| {
+                let _guard = pin();
+                pinned.wait();
+                unpin.wait();
+            });
+            pinned.wait();
+            pin().defer(|| RAN.store(true, SeqCst));
+            for _ in 0..100 {
+                pin().collect();
+            }
+            assert!(!RAN.load(SeqCst));
+            unpin.wait();
+        });
+        collect_until(|| RAN.load(SeqCst));
+    }
+
+    #[test]
+    fn exited_threads_hand_over_their_work() {
+        static RAN: AtomicUsize = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    let guard = pin();
+                    for _ in 0..10 {
+                        guard.defer(|| {
+                            RAN.fetch_add(1, SeqCst);
+                        });
+                    }
+                });
+            }
+        });
+        collect_until(|| RAN.load(SeqCst) == 80);
+    }
+}
@@ -0,0 +1,318 @@
+//! Hazard-pointer memory reclamation.
+//!
+//! A thread announces each node it is about to read by storing its address in
+//! a [`HazardPointer`], a slot on a global list that every thread can see.
+//! Unlinked nodes are handed to [`retire`], which frees a node only once no
+//! slot holds its address.
+//!
+//! Unlike [`epoch`](super::epoch), a thread reading for a long time only keeps
+//! alive the few nodes it protects, not everything retired meanwhile, so the
+//! garbage waiting to be freed stays bounded by the number of slots. In
+//! exchange, every protected load costs a fence and a second load, and
+//! retiring scans every slot.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::sync::atomic::{AtomicPtr, Ordering};
+//! use my_std::boxed::MyBox;
+//! use my_std::concurrent::hazard::{self, HazardPointer};
+//!
+//! let shared = AtomicPtr::new(MyBox::into_raw(MyBox::new(1)));
+//!
+//! let mut hazard = HazardPointer::new();
+//! let value = hazard.protect(&shared);
+//! let old = shared.swap(MyBox::into_raw(MyBox::new(2)), Ordering::AcqRel);
+//! // Protected, so not freed yet.
+//! unsafe { hazard::retire(old) };
+//! assert_eq!(unsafe { *value }, 1);
+//! drop(hazard);
+//!
+//! unsafe { hazard::retire(shared.into_inner()) };
+//! hazard::collect();
+//! ```
+
+use std::{cell::RefCell, fmt, mem, ptr};
+
+use crate::{
+    boxed::MyBox,
+    compat::atomic::{self, AtomicBool, AtomicPtr, Ordering::*},
+    sync::SpinLock,
+};
+
+// Scan the slots once a thread has retired this many nodes.
+const RETIRED_CAPACITY: usize = 64;
+
+// Every slot ever created. Slots are never freed, only reused.
+#[cfg(not(loom))]
+static SLOTS: AtomicPtr<Slot> = AtomicPtr::new(ptr::null_mut());
+// Nodes retired by threads that exited while they were still protected.
+#[cfg(not(loom))]
+static ORPHANS: SpinLock<Vec<Retired>> = SpinLock::new(Vec::new());
+
+// Loom's atomics are created at run time, once per modeled execution.
+#[cfg(loom)]
+loom::lazy_static! {
+    static ref SLOTS: AtomicPtr<Slot> = AtomicPtr::new(ptr::null_mut());
+    static ref ORPHANS: SpinLock<Vec<Retired>> = SpinLock::new(Vec::new());
+}
+
+struct Slot {
+    // The protected address, or null.
+    hazard: AtomicPtr<u8>,
+    // Taken by a live `HazardPointer`.
+    in_use: AtomicBool,
+    // Never changes once the slot is published.
+    next: *mut Slot,
+}
+
+struct Retired {
+    ptr: *mut u8,
+    free: unsafe fn(*mut u8),
+}
+
+// `retire` makes the caller promise that the node may be freed on any thread.
+unsafe impl Send for Retired {}
+
+impl Retired {
+    unsafe fn free<T>(ptr: *mut u8) {
+        drop(MyBox::from_raw(ptr.cast::<T>()));
+    }
+}
+
+// The nodes retired by one thread, handed over when it exits.
+struct RetiredList(RefCell<Vec<Retired>>);
+
+impl Drop for RetiredList {
+    fn drop(&mut self) {
+        let left = scan(mem::take(self.0.get_mut()));
+        if !left.is_empty() {
+            ORPHANS.lock().extend(left);
+        }
+    }
+}
+
+thread_local! {
+    static RETIRED: RetiredList = const { RetiredList(RefCell::new(Vec::new())) };
+}
+
+/// A slot announcing one node the current thread is reading.
+///
+/// While a hazard pointer protects an address, nodes at that address passed to
+/// [`retire`] aren't freed, by any thread.
+pub struct HazardPointer {
+    slot: &'static Slot,
+}
+
+// A slot only holds an address, and any thread may clear it.
+unsafe impl Send for HazardPointer {}
+unsafe impl Sync for HazardPointer {}
+
+impl HazardPointer {
+    /// Take a free slot, or add one to the global list.
+    pub fn new() -> Self {
+        let mut slot = SLOTS.load(Acquire);
+        while !slot.is_null() {
+            let s = unsafe { &*slot };
+            if s.in_use
+                .compare_exchange(false, true, Acquire, Relaxed)
+                .is_ok()
+            {
+                return HazardPointer { slot: s };
+            }
+            slot = s.next;
+        }
+        let slot = MyBox::into_raw(MyBox::new(Slot {
+            hazard: AtomicPtr::new(ptr::null_mut()),
+            in_use: AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = SLOTS.load(Relaxed);
+        loop {
+            // Not shared until the exchange succeeds.
+            unsafe { (*slot).next = head };
+            match SLOTS.compare_exchange_weak(head, slot, Release, Relaxed) {
+                Ok(_) => {
+                    return HazardPointer {
+                        slot: unsafe { &*slot },
+                    }
+                }
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    /// Load `src` and protect the node it points to, until this hazard pointer
+    /// protects another one, is reset or dropped.
+    ///
+    /// The returned pointer may be dereferenced while it is protected,
+    /// provided nodes unlinked from `src` are only freed through [`retire`].
+    pub fn protect<T>(&mut self, src: &AtomicPtr<T>) -> *mut T {
+        let mut ptr = src.load(Relaxed);
+        loop {
+            self.slot.hazard.store(ptr.cast(), Relaxed);
+            // Pairs with the fence in `scan`: either the scan sees our slot, or
+            // we see the node unlinked below.
+            atomic::fence(SeqCst);
+            let again = src.load(Acquire);
+            if again == ptr {
+                return ptr;
+            }
+            ptr = again;
+        }
+    }
+
+    /// Stop protecting anything.
+    pub fn reset(&mut self) {
+        self.slot.hazard.store(ptr::null_mut(), Release);
+    }
+}
+
+impl Drop for HazardPointer {
+    fn drop(&mut self) {
+        self.reset();
+        self.slot.in_use.store(false, Release);
+    }
+}
+
+impl Default for HazardPointer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for HazardPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HazardPointer")
+            .field(&self.slot.hazard.load(Relaxed))
+            .finish()
+    }
+}
+
+/// Free `ptr` once no hazard pointer protects it.
+///
+/// # Safety
+///
+/// `ptr` must come from [`MyBox::into_raw`] and be unreachable for loads that
+/// start from now on. Its value is dropped on any thread, at any later point,
+/// so it must be fine with that.
+pub unsafe fn retire<T>(ptr: *mut T) {
+    let mut retired = Some(Retired {
+        ptr: ptr.cast(),
+        free: Retired::free::<T>,
+    });
+    let full = RETIRED
+        .try_with(|list| {
+            let mut list = list.0.borrow_mut();
+            list.extend(retired.take());
+            list.len() >= RETIRED_CAPACITY
+        })
+        .unwrap_or(false);
+    if let Some(retired) = retired {
+        // The thread is exiting: leave the node to the others.
+        ORPHANS.lock().push(retired);
+    }
+    if full {
+        collect();
+    }
+}
+
+/// Free the nodes retired by the current thread, and by exited threads, that
+/// no hazard pointer protects anymore.
+pub fn collect() {
+    let mut retired = RETIRED
+        .try_with(|list| mem::take(&mut *list.0.borrow_mut()))
+        .unwrap_or_default();
+    if let Some(mut orphans) = ORPHANS.try_lock() {
+        retired.append(&mut orphans);
+    }
+    let mut left = Some(scan(retired));
+    // Freeing may have retired more nodes meanwhile, so add to the list.
+    let _ = RETIRED.try_with(|list| list.0.borrow_mut().extend(left.take().unwrap()));
+    if let Some(left) = left.filter(|left| !left.is_empty()) {
+        ORPHANS.lock().extend(left);
+    }
+}
+
+// Free the nodes no slot protects, and return the others.
+fn scan(retired: Vec<Retired>) -> Vec<Retired> {
+    if retired.is_empty() {
+        return retired;
+    }
+    // Pairs with the fence in `HazardPointer::protect`.
+    atomic::fence(SeqCst);
+    let mut hazards = Vec::new();
+    let mut slot = SLOTS.load(Acquire);
+    while !slot.is_null() {
+        let s = unsafe { &*slot };
+        // `Acquire` pairs with `reset`, so the reads made through a slot are
+        // over before we free what it protected.
+        let hazard = s.hazard.load(Acquire);
+        if !hazard.is_null() {
+            hazards.push(hazard);
+        }
+        slot = s.next;
+    }
+    hazards.sort_unstable();
+    let (protected, free): (Vec<_>, Vec<_>) = retired
+        .into_iter()
+        .partition(|r| hazards.binary_search(&r.ptr).is_ok());
+    for r in free {
+        unsafe { (r.free)(r.ptr) };
+    }
+    protected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    struct Counted(&'static AtomicUsize);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, SeqCst);
+        }
+    }
+
+    #[test]
+    fn protected_nodes_survive_collect() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let shared = AtomicPtr::new(MyBox::into_raw(MyBox::new(Counted(&DROPS))));
+        let mut hazard = HazardPointer::new();
+        hazard.protect(&shared);
+        unsafe { retire(shared.swap(ptr::null_mut(), SeqCst)) };
+        collect();
+        assert_eq!(DROPS.load(SeqCst), 0);
+        // Another hazard pointer gets another slot.
+        let mut other = HazardPointer::new();
+        assert!(other.protect(&shared).is_null());
+        hazard.reset();
+        collect();
+        assert_eq!(DROPS.load(SeqCst), 1);
+    }
+
+    #[test]
+    fn exiting_threads_hand_over_protected_nodes() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let shared = AtomicPtr::new(MyBox::into_raw(MyBox::new(Counted(&DROPS))));
+        let mut hazard = HazardPointer::new();
+        hazard.protect(&shared);
+        thread::scope(|s| {
+            s.spawn(|| unsafe { retire(shared.swap(ptr::null_mut(), SeqCst)) });
+        });
+        assert_eq!(DROPS.load(SeqCst), 0);
+        drop(hazard);
+        // Another test may hold the orphan list for a moment.
+        for _ in 0..1_000 {
+            collect();
+            if DROPS.load(SeqCst) == 1 {
+                return;
+            }
+            thread::yield_now();
+        }
+        panic!("orphaned node never freed");
+    }
+}
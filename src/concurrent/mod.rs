@@ -0,0 +1,1440 @@
+pub mod epoch;
+pub mod hazard;
+
+use std::{
+    borrow::Borrow,
+    cell::{Cell, UnsafeCell},
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    marker::PhantomData,
+    mem::{ManuallyDrop, MaybeUninit},
+    ptr,
+    sync::PoisonError,
+    thread,
+};
+
+use crate::{
+    arc::MyArc,
+    boxed::MyBox,
+    compat::{
+        atomic::{self, AtomicIsize, AtomicPtr, Ordering::*},
+        const_unless_loom,
+    },
+    hashmap::{Entry, HashMap},
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// A hash map shared between threads, split into independently locked shards.
+///
+/// Each key belongs to one shard, picked from its hash, and every operation
+/// locks only that shard. Threads working on keys in different shards never
+/// wait for each other, so with enough shards a busy cache scales with the
+/// number of threads instead of queueing on one lock.
+///
+/// Closures passed to [`get`](ShardedMap::get) and the other accessors run
+/// with the shard locked: they should be short and must not touch the same map
+/// again, which may deadlock.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::concurrent::ShardedMap;
+///
+/// let hits = ShardedMap::new();
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| {
+///             for page in ["/", "/about", "/"] {
+///                 hits.entry(page, |e| *e.or_insert(0) += 1);
+///             }
+///         });
+///     }
+/// });
+/// assert_eq!(hits.get("/", |n| *n), Some(8));
+/// assert_eq!(hits.len(), 2);
+/// ```
+pub struct ShardedMap<K, V> {
+    shards: Box<[RwLock<HashMap<K, V>>]>,
+}
+
+impl<K, V> ShardedMap<K, V> {
+    /// Create an empty map with a few shards per available CPU.
+    pub fn new() -> Self {
+        let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_shards(cpus * 4)
+    }
+
+    /// Create an empty map with `shards` shards.
+    /// Panics if `shards` is zero.
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards > 0, "a ShardedMap needs at least one shard");
+        ShardedMap {
+            shards: (0..shards).map(|_| RwLock::new(HashMap::new())).collect(),
+        }
+    }
+
+    /// Number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Lock shard `index` for reading, to look at all of its entries at once.
+    /// Panics if `index >= shard_count`.
+    pub fn read_shard(&self, index: usize) -> RwLockReadGuard<'_, HashMap<K, V>> {
+        read(&self.shards[index])
+    }
+
+    /// Call `f` on every entry, one shard at a time.
+    ///
+    /// Each shard is locked only while its own entries are visited, so other
+    /// threads may change the ones already or not yet visited meanwhile.
+    pub fn for_each<F: FnMut(&K, &V)>(&self, mut f: F) {
+        for shard in self.shards.iter() {
+            for (key, value) in &*read(shard) {
+                f(key, value);
+            }
+        }
+    }
+
+    /// Remove every entry.
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            *write(shard) = HashMap::new();
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> ShardedMap<K, V> {
+    fn shard<Q: Hash + ?Sized>(&self, key: &Q) -> &RwLock<HashMap<K, V>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        // The maps pick buckets with the low bits of the same hash, so use the
+        // high ones here. Otherwise all the keys of a shard would crowd into
+        // the same few buckets.
+        let index = (hasher.finish() >> 32) as usize % self.shards.len();
+        &self.shards[index]
+    }
+
+    /// Number of entries. Since shards are counted one at a time, entries
+    /// added or removed meanwhile may or may not be counted.
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| read(shard).len()).sum()
+    }
+
+    /// Return `true` if no shard holds an entry.
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| read(shard).is_empty())
+    }
+
+    /// Insert a key-value pair, returning the value the key had, if any.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        write(self.shard(&key)).insert(key, value)
+    }
+
+    /// Call `f` on the value of `key`, if there is one, and return its result.
+    pub fn get<Q, R, F>(&self, key: &Q, f: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&V) -> R,
+    {
+        read(self.shard(key)).get(key).map(f)
+    }
+
+    /// Call `f` on a mutable reference to the value of `key`, if there is one,
+    /// and return its result.
+    pub fn get_mut<Q, R, F>(&self, key: &Q, f: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&mut V) -> R,
+    {
+        write(self.shard(key)).get_mut(key).map(f)
+    }
+
+    /// Return `true` if the map has a value for `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        read(self.shard(key)).contains_key(key)
+    }
+
+    /// Remove `key`, returning its value if it had one.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        write(self.shard(key)).remove(key)
+    }
+
+    /// Call `f` on the entry of `key`, with its shard locked, and return its
+    /// result. Reading and updating the value happen as one step, so no other
+    /// thread can change it in between.
+    pub fn entry<R, F>(&self, key: K, f: F) -> R
+    where
+        F: FnOnce(Entry<'_, K, V>) -> R,
+    {
+        f(write(self.shard(&key)).entry(key))
+    }
+}
+
+// The shards are never left broken by a panic: a panicking closure can only
+// have changed a value, which it was allowed to do, so poisoning is ignored.
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<K, V> Default for ShardedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V> Extend<(K, V)> for ShardedMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for ShardedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = ShardedMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for ShardedMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        self.for_each(|key, value| {
+            map.entry(key, value);
+        });
+        map.finish()
+    }
+}
+
+/// How a lock-free structure frees the nodes it unlinks, while other threads
+/// may still be reading them.
+///
+/// [`Stack`] and [`Queue`] take it as a type parameter: [`Epoch`], the
+/// default, is cheap on every operation but lets garbage pile up while any
+/// thread stays pinned; [`Hazard`] costs a fence per load and keeps the garbage
+/// bounded even under long reader sections.
+///
+/// # Safety
+///
+/// A pointer returned by [`protect`](Reclaim::protect) must stay valid, even if
+/// it is unlinked and passed to [`retire`](Reclaim::retire), until the guard is
+/// dropped or protects another pointer in the same slot.
+pub unsafe trait Reclaim {
+    /// Protects the nodes loaded through it.
+    type Guard;
+
+    /// Start an operation on the structure.
+    fn guard(&self) -> Self::Guard;
+
+    /// Load `src`, protecting the node it points to in `slot`, 0 or 1: a guard
+    /// protects up to two nodes at once.
+    fn protect<T>(guard: &mut Self::Guard, slot: usize, src: &AtomicPtr<T>) -> *mut T;
+
+    /// Free `ptr` once no guard protects it anymore.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`epoch::Guard::defer_destroy`].
+    unsafe fn retire<T>(guard: &Self::Guard, ptr: *mut T);
+}
+
+/// Reclaims nodes with [`epoch`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Epoch;
+
+// Nothing deferred while the guard is alive is freed before it is dropped.
+unsafe impl Reclaim for Epoch {
+    type Guard = epoch::Guard;
+
+    fn guard(&self) -> epoch::Guard {
+        epoch::pin()
+    }
+
+    fn protect<T>(_: &mut epoch::Guard, _: usize, src: &AtomicPtr<T>) -> *mut T {
+        src.load(Acquire)
+    }
+
+    unsafe fn retire<T>(guard: &epoch::Guard, ptr: *mut T) {
+        guard.defer_destroy(ptr);
+    }
+}
+
+/// Reclaims nodes with [`hazard`] pointers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Hazard;
+
+unsafe impl Reclaim for Hazard {
+    type Guard = [hazard::HazardPointer; 2];
+
+    fn guard(&self) -> Self::Guard {
+        [hazard::HazardPointer::new(), hazard::HazardPointer::new()]
+    }
+
+    fn protect<T>(guard: &mut Self::Guard, slot: usize, src: &AtomicPtr<T>) -> *mut T {
+        guard[slot].protect(src)
+    }
+
+    unsafe fn retire<T>(_: &Self::Guard, ptr: *mut T) {
+        hazard::retire(ptr);
+    }
+}
+
+struct Node<T> {
+    // Moved out by the `pop` that unlinks the node.
+    value: ManuallyDrop<T>,
+    // Never changes once the node is pushed.
+    next: *mut Node<T>,
+}
+
+/// A lock-free stack, known as a Treiber stack.
+///
+/// The stack is a linked list, and both [`push`](Stack::push) and
+/// [`pop`](Stack::pop) swap its head with a single compare-and-swap, retrying
+/// if another thread changed the head in between. No thread ever waits for
+/// another, so a thread that stalls mid-operation never blocks the rest.
+///
+/// # Memory reclamation
+///
+/// A thread in `pop` reads the head node's `next` before its compare-and-swap,
+/// so a popped node can't be freed while any other `pop` might still read it.
+/// `pop` protects the head through `R`, by default [`Epoch`], and retires the
+/// node it unlinked through it too, which frees it once no `pop` can still be
+/// reading it. [`Hazard`] does the same with hazard pointers.
+///
+/// The same rule takes care of the ABA problem: a `pop` could be fooled by the
+/// head being popped and a new node pushed at the same address between its read
+/// and its compare-and-swap. That needs the old node to have been freed, which
+/// can't happen while the `pop` still protects it.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::concurrent::{Hazard, Stack};
+///
+/// let stack = Stack::new();
+/// thread::scope(|s| {
+///     for i in 0..4 {
+///         let stack = &stack;
+///         s.spawn(move || stack.push(i));
+///     }
+/// });
+/// let mut popped: Vec<_> = std::iter::from_fn(|| stack.pop()).collect();
+/// popped.sort();
+/// assert_eq!(popped, [0, 1, 2, 3]);
+///
+/// let stack = Stack::with_reclaim(Hazard);
+/// stack.push("hazard");
+/// assert_eq!(stack.pop(), Some("hazard"));
+/// ```
+pub struct Stack<T, R: Reclaim = Epoch> {
+    head: AtomicPtr<Node<T>>,
+    reclaim: R,
+    _marker: PhantomData<T>,
+}
+
+// Values are moved in on one thread and out on another, never shared.
+unsafe impl<T: Send, R: Reclaim + Send> Send for Stack<T, R> {}
+unsafe impl<T: Send, R: Reclaim + Sync> Sync for Stack<T, R> {}
+
+impl<T> Stack<T> {
+    const_unless_loom! {
+        /// Create an empty stack, reclaiming nodes with [`Epoch`].
+        pub fn new() -> Self {
+            Self::with_reclaim(Epoch)
+        }
+    }
+}
+
+impl<T, R: Reclaim> Stack<T, R> {
+    const_unless_loom! {
+        /// Create an empty stack, reclaiming nodes with `reclaim`.
+        pub fn with_reclaim(reclaim: R) -> Self {
+            Stack {
+                head: AtomicPtr::new(ptr::null_mut()),
+                reclaim,
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    /// Push `value` on top of the stack.
+    pub fn push(&self, value: T) {
+        let node = MyBox::into_raw(MyBox::new(Node {
+            value: ManuallyDrop::new(value),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.head.load(Relaxed);
+        loop {
+            // The node isn't shared until the exchange succeeds.
+            unsafe { (*node).next = head };
+            // `Release` publishes the node to the `pop` that takes it.
+            match self
+                .head
+                .compare_exchange_weak(head, node, Release, Relaxed)
+            {
+                Ok(_) => return,
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    /// Pop the value on top of the stack, if there is one.
+    pub fn pop(&self) -> Option<T> {
+        let mut guard = self.reclaim.guard();
+        loop {
+            let head = R::protect(&mut guard, 0, &self.head);
+            if head.is_null() {
+                return None;
+            }
+            // `head` is protected, so still allocated even if another thread
+            // popped it.
+            let next = unsafe { (*head).next };
+            if self
+                .head
+                .compare_exchange_weak(head, next, Acquire, Relaxed)
+                .is_ok()
+            {
+                // Unlinking the node made it ours.
+                let value = unsafe { ManuallyDrop::take(&mut (*head).value) };
+                // `ManuallyDrop` keeps the value from being dropped again.
+                unsafe { R::retire(&guard, head) };
+                return Some(value);
+            }
+        }
+    }
+
+    /// Return `true` if the stack is empty. By the time it returns, other
+    /// threads may have changed that.
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Acquire).is_null()
+    }
+}
+
+impl<T, R: Reclaim> Drop for Stack<T, R> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T, R: Reclaim + Default> Default for Stack<T, R> {
+    fn default() -> Self {
+        Self::with_reclaim(R::default())
+    }
+}
+
+impl<T, R: Reclaim> Extend<T> for Stack<T, R> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, R: Reclaim + Default> FromIterator<T> for Stack<T, R> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut stack = Stack::default();
+        stack.extend(iter);
+        stack
+    }
+}
+
+impl<T, R: Reclaim> fmt::Debug for Stack<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stack").finish_non_exhaustive()
+    }
+}
+
+struct QueueNode<T> {
+    // Uninitialized in the dummy node at the head, and moved out by the
+    // `dequeue` that makes the node the new dummy.
+    value: MaybeUninit<T>,
+    next: AtomicPtr<QueueNode<T>>,
+}
+
+impl<T> QueueNode<T> {
+    fn alloc(value: MaybeUninit<T>) -> *mut Self {
+        MyBox::into_raw(MyBox::new(QueueNode {
+            value,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+/// A lock-free first-in first-out queue, after Michael and Scott.
+///
+/// The queue is a linked list that starts with a dummy node, with atomic
+/// pointers to both ends. [`enqueue`](Queue::enqueue) links a node after the
+/// last one, and [`dequeue`](Queue::dequeue) makes the first real node the new
+/// dummy, each with a compare-and-swap. The tail pointer may lag one node
+/// behind while an `enqueue` is in progress; any thread that notices moves it
+/// on instead of waiting, so no thread ever blocks another.
+///
+/// Values sent by one thread come out in the order it sent them. Nodes are
+/// freed through `R`, like [`Stack`]'s.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::concurrent::Queue;
+///
+/// let queue = Queue::new();
+/// thread::scope(|s| {
+///     s.spawn(|| {
+///         for i in 0..3 {
+///             queue.enqueue(i);
+///         }
+///     });
+/// });
+/// assert_eq!(queue.dequeue(), Some(0));
+/// assert_eq!(queue.dequeue(), Some(1));
+/// ```
+pub struct Queue<T, R: Reclaim = Epoch> {
+    // The dummy node.
+    head: AtomicPtr<QueueNode<T>>,
+    // The last node, or the one before it.
+    tail: AtomicPtr<QueueNode<T>>,
+    reclaim: R,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send, R: Reclaim + Send> Send for Queue<T, R> {}
+unsafe impl<T: Send, R: Reclaim + Sync> Sync for Queue<T, R> {}
+
+impl<T> Queue<T> {
+    /// Create an empty queue, reclaiming nodes with [`Epoch`].
+    pub fn new() -> Self {
+        Self::with_reclaim(Epoch)
+    }
+}
+
+impl<T, R: Reclaim> Queue<T, R> {
+    /// Create an empty queue, reclaiming nodes with `reclaim`.
+    pub fn with_reclaim(reclaim: R) -> Self {
+        let dummy = QueueNode::alloc(MaybeUninit::uninit());
+        Queue {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            reclaim,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Add `value` at the back of the queue.
+    pub fn enqueue(&self, value: T) {
+        let node = QueueNode::alloc(MaybeUninit::new(value));
+        let mut guard = self.reclaim.guard();
+        loop {
+            let tail = R::protect(&mut guard, 0, &self.tail);
+            let next = unsafe { (*tail).next.load(Acquire) };
+            if !next.is_null() {
+                // The tail lags behind: move it on and retry.
+                let _ = self.tail.compare_exchange(tail, next, Release, Relaxed);
+                continue;
+            }
+            // `Release` publishes the node to the `dequeue` that takes it.
+            let linked = unsafe { &(*tail).next }
+                .compare_exchange(ptr::null_mut(), node, Release, Relaxed)
+                .is_ok();
+            if linked {
+                // May fail if another thread already moved it for us.
+                let _ = self.tail.compare_exchange(tail, node, Release, Relaxed);
+                return;
+            }
+        }
+    }
+
+    /// Remove the value at the front of the queue, if there is one.
+    pub fn dequeue(&self) -> Option<T> {
+        let mut guard = self.reclaim.guard();
+        loop {
+            let head = R::protect(&mut guard, 0, &self.head);
+            let tail = self.tail.load(Acquire);
+            let next = R::protect(&mut guard, 1, unsafe { &(*head).next });
+            // If `head` is still the head, `next` wasn't dequeued before we
+            // protected it.
+            if self.head.load(Acquire) != head {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+            if head == tail {
+                // Never retire a node the tail still points to.
+                let _ = self.tail.compare_exchange(tail, next, Release, Relaxed);
+                continue;
+            }
+            if self
+                .head
+                .compare_exchange(head, next, Acquire, Relaxed)
+                .is_ok()
+            {
+                // `next` is the new dummy, and its value ours to move out:
+                // other threads only ever touch its `next`.
+                let value = unsafe { (*next).value.assume_init_read() };
+                unsafe { R::retire(&guard, head) };
+                return Some(value);
+            }
+        }
+    }
+
+    /// Return `true` if the queue is empty. By the time it returns, other
+    /// threads may have changed that.
+    pub fn is_empty(&self) -> bool {
+        let mut guard = self.reclaim.guard();
+        let head = R::protect(&mut guard, 0, &self.head);
+        unsafe { (*head).next.load(Acquire) }.is_null()
+    }
+}
+
+impl<T, R: Reclaim> Drop for Queue<T, R> {
+    fn drop(&mut self) {
+        while self.dequeue().is_some() {}
+        drop(unsafe { MyBox::from_raw(self.head.load(Relaxed)) });
+    }
+}
+
+impl<T, R: Reclaim + Default> Default for Queue<T, R> {
+    fn default() -> Self {
+        Self::with_reclaim(R::default())
+    }
+}
+
+impl<T, R: Reclaim> Extend<T> for Queue<T, R> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.enqueue(value);
+        }
+    }
+}
+
+impl<T, R: Reclaim + Default> FromIterator<T> for Queue<T, R> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = Queue::default();
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<T, R: Reclaim> fmt::Debug for Queue<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Queue").finish_non_exhaustive()
+    }
+}
+
+// A circular array of slots, indexed modulo its power-of-two capacity.
+struct Buffer<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+}
+
+impl<T> Buffer<T> {
+    fn alloc(capacity: usize) -> *mut Self {
+        let slots = (0..capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect();
+        MyBox::into_raw(MyBox::new(Buffer { slots }))
+    }
+
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn slot(&self, index: isize) -> *mut MaybeUninit<T> {
+        self.slots[index as usize & (self.slots.len() - 1)].get()
+    }
+
+    unsafe fn write(&self, index: isize, value: MaybeUninit<T>) {
+        self.slot(index).write(value);
+    }
+
+    // A stealer may read a slot while the owner overwrites it, in which case
+    // its compare-and-swap then fails and the torn copy is never used as a
+    // `T`. The read is volatile so it stays a plain copy of the bytes.
+    unsafe fn read(&self, index: isize) -> MaybeUninit<T> {
+        self.slot(index).read_volatile()
+    }
+}
+
+struct Deque<T> {
+    // Stealers take from the top, the owner pushes and pops at the bottom.
+    top: AtomicIsize,
+    bottom: AtomicIsize,
+    buffer: AtomicPtr<Buffer<T>>,
+    // Buffers replaced by bigger ones, which a stealer may still be reading.
+    // Only the owner touches the list, and they are freed with the deque.
+    retired: UnsafeCell<Vec<*mut Buffer<T>>>,
+}
+
+impl<T> Drop for Deque<T> {
+    fn drop(&mut self) {
+        let (top, bottom) = (self.top.load(Relaxed), self.bottom.load(Relaxed));
+        let buffer = self.buffer.load(Relaxed);
+        unsafe {
+            for i in top..bottom {
+                (*buffer).read(i).assume_init_drop();
+            }
+            drop(MyBox::from_raw(buffer));
+            for &old in self.retired.get_mut().iter() {
+                drop(MyBox::from_raw(old));
+            }
+        }
+    }
+}
+
+/// The result of [`Stealer::steal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Steal<T> {
+    /// The deque was empty.
+    Empty,
+    /// A value was stolen.
+    Success(T),
+    /// Another thread took the value first; trying again may succeed.
+    Retry,
+}
+
+impl<T> Steal<T> {
+    /// The stolen value, if any.
+    pub fn success(self) -> Option<T> {
+        match self {
+            Steal::Success(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A work-stealing deque, after Chase and Lev.
+///
+/// The deque belongs to one thread, which pushes and pops tasks at the bottom
+/// like a stack, so it keeps working on the freshest, cache-hot ones. Idle
+/// threads hold [`Stealer`]s and take the oldest tasks from the top. Only the
+/// owner writes the bottom index and stealers race for the top with a
+/// compare-and-swap, so the owner's operations are nearly free until the
+/// deque is down to its last value.
+///
+/// The buffer grows as needed. Old buffers are kept until the deque and all of
+/// its stealers are gone, since a stealer may still be reading one.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::concurrent::WorkStealingDeque;
+///
+/// let deque = WorkStealingDeque::new();
+/// for task in 0..100 {
+///     deque.push(task);
+/// }
+/// let stealer = deque.stealer();
+/// let stolen = thread::scope(|s| {
+///     let thief = s.spawn(|| {
+///         std::iter::from_fn(|| stealer.steal().success()).count()
+///     });
+///     let mine = std::iter::from_fn(|| deque.pop()).count();
+///     mine + thief.join().unwrap()
+/// });
+/// assert_eq!(stolen, 100);
+/// ```
+pub struct WorkStealingDeque<T> {
+    inner: MyArc<Deque<T>>,
+    // Only one thread may be the owner at a time, though it can hand the deque
+    // over to another.
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+unsafe impl<T: Send> Send for WorkStealingDeque<T> {}
+
+impl<T> WorkStealingDeque<T> {
+    const MIN_CAPACITY: usize = 16;
+
+    /// Create an empty deque.
+    pub fn new() -> Self {
+        WorkStealingDeque {
+            inner: MyArc::new(Deque {
+                top: AtomicIsize::new(0),
+                bottom: AtomicIsize::new(0),
+                buffer: AtomicPtr::new(Buffer::alloc(Self::MIN_CAPACITY)),
+                retired: UnsafeCell::new(Vec::new()),
+            }),
+            _not_sync: PhantomData,
+        }
+    }
+
+    /// A handle for other threads to steal values with.
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer {
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Push `value` at the bottom.
+    pub fn push(&self, value: T) {
+        let deque = &*self.inner;
+        let bottom = deque.bottom.load(Relaxed);
+        let top = deque.top.load(Acquire);
+        let mut buffer = deque.buffer.load(Relaxed);
+        if bottom - top >= unsafe { &*buffer }.capacity() as isize {
+            buffer = unsafe { self.grow(buffer, top, bottom) };
+        }
+        unsafe { (*buffer).write(bottom, MaybeUninit::new(value)) };
+        // Publish the value before the index that makes it visible.
+        atomic::fence(Release);
+        deque.bottom.store(bottom + 1, Relaxed);
+    }
+
+    // Move the values in `top..bottom` to a buffer twice as big.
+    unsafe fn grow(&self, old: *mut Buffer<T>, top: isize, bottom: isize) -> *mut Buffer<T> {
+        let deque = &*self.inner;
+        let new = Buffer::alloc((&*old).capacity() * 2);
+        for i in top..bottom {
+            (*new).write(i, (*old).read(i));
+        }
+        deque.buffer.store(new, Release);
+        (*deque.retired.get()).push(old);
+        new
+    }
+
+    /// Pop the value at the bottom, the one pushed last, if there is one.
+    pub fn pop(&self) -> Option<T> {
+        let deque = &*self.inner;
+        let bottom = deque.bottom.load(Relaxed) - 1;
+        let buffer = deque.buffer.load(Relaxed);
+        // Claim the slot first, then check no stealer got there before. The
+        // fence orders the two against the stealers' read of `bottom`.
+        deque.bottom.store(bottom, Relaxed);
+        atomic::fence(SeqCst);
+        let top = deque.top.load(Relaxed);
+        if top > bottom {
+            // Empty.
+            deque.bottom.store(bottom + 1, Relaxed);
+            return None;
+        }
+        let value = unsafe { (*buffer).read(bottom) };
+        if top < bottom {
+            // Not the last value, so no stealer can be after it.
+            return Some(unsafe { value.assume_init() });
+        }
+        // The last value: race the stealers for it.
+        let won = deque
+            .top
+            .compare_exchange(top, top + 1, SeqCst, Relaxed)
+            .is_ok();
+        deque.bottom.store(bottom + 1, Relaxed);
+        won.then(|| unsafe { value.assume_init() })
+    }
+
+    /// Number of values in the deque.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Return `true` if the deque is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Deque<T> {
+    fn len(&self) -> usize {
+        let bottom = self.bottom.load(Relaxed);
+        let top = self.top.load(Relaxed);
+        (bottom - top).max(0) as usize
+    }
+}
+
+impl<T> Default for WorkStealingDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for WorkStealingDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkStealingDeque")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A handle that takes values from the top of a [`WorkStealingDeque`], from
+/// any thread.
+pub struct Stealer<T> {
+    inner: MyArc<Deque<T>>,
+}
+
+unsafe impl<T: Send> Send for Stealer<T> {}
+unsafe impl<T: Send> Sync for Stealer<T> {}
+
+impl<T> Stealer<T> {
+    /// Take the value at the top, the oldest one, if there is one.
+    pub fn steal(&self) -> Steal<T> {
+        let deque = &*self.inner;
+        let top = deque.top.load(Acquire);
+        atomic::fence(SeqCst);
+        let bottom = deque.bottom.load(Acquire);
+        if top >= bottom {
+            return Steal::Empty;
+        }
+        // `Acquire` pairs with the `Release` store of a grown buffer.
+        let buffer = deque.buffer.load(Acquire);
+        let value = unsafe { (*buffer).read(top) };
+        match deque.top.compare_exchange(top, top + 1, SeqCst, Relaxed) {
+            Ok(_) => Steal::Success(unsafe { value.assume_init() }),
+            Err(_) => Steal::Retry,
+        }
+    }
+
+    /// Number of values in the deque.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Return `true` if the deque is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Clone for Stealer<T> {
+    fn clone(&self) -> Self {
+        Stealer {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Stealer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stealer")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for ShardedMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Hash + Eq,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T, R> arbitrary::Arbitrary<'a> for Stack<T, R>
+where
+    T: arbitrary::Arbitrary<'a>,
+    R: Reclaim + Default,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T, R> arbitrary::Arbitrary<'a> for Queue<T, R>
+where
+    T: arbitrary::Arbitrary<'a>,
+    R: Reclaim + Default,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for ShardedMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Hash + Eq,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T, R> proptest::arbitrary::Arbitrary for Stack<T, R>
+where
+    T: proptest::arbitrary::Arbitrary + 'static,
+    R: Reclaim + Default + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T, R> proptest::arbitrary::Arbitrary for Queue<T, R>
+where
+    T: proptest::arbitrary::Arbitrary + 'static,
+    R: Reclaim + Default + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn single_threaded() {
+        let map = ShardedMap::with_shards(3);
+        assert_eq!(map.insert("a", 1), None);
+        assert_eq!(map.insert("a", 2), Some(1));
+        map.insert("b", 3);
+        assert_eq!(map.get("a", |v| v * 10), Some(20));
+        assert_eq!(map.get_mut("b", |v| std::mem::replace(v, 4)), Some(3));
+        assert!(map.contains_key("b"));
+        assert_eq!(map.entry("c", |e| *e.or_default()), 0);
+        assert_eq!(map.len(), 3);
+
+        let mut pairs = Vec::new();
+        map.for_each(|k, v| pairs.push((*k, *v)));
+        pairs.sort();
+        assert_eq!(pairs, [("a", 2), ("b", 4), ("c", 0)]);
+        let per_shard: usize = (0..map.shard_count())
+            .map(|i| map.read_shard(i).len())
+            .sum();
+        assert_eq!(per_shard, 3);
+
+        assert_eq!(map.remove("a"), Some(2));
+        assert_eq!(map.get("a", |v| *v), None);
+        map.clear();
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn keys_spread_over_shards() {
+        let map: ShardedMap<u32, ()> = (0..1_000).map(|i| (i, ())).collect();
+        let sizes: Vec<usize> = (0..map.shard_count())
+            .map(|i| map.read_shard(i).len())
+            .collect();
+        assert_eq!(sizes.iter().sum::<usize>(), 1_000);
+        if sizes.len() > 1 {
+            assert!(sizes.iter().all(|&n| n < 1_000));
+        }
+    }
+
+    #[test]
+    fn concurrent_upserts() {
+        let map = ShardedMap::with_shards(8);
+        thread::scope(|s| {
+            for t in 0..8u64 {
+                let map = &map;
+                s.spawn(move || {
+                    for i in 0..1_000u64 {
+                        map.entry(i % 100, |e| *e.or_insert(0) += 1);
+                        if i.is_multiple_of(10) {
+                            map.insert(1_000 + t * 1_000 + i, i);
+                        }
+                    }
+                });
+            }
+        });
+        for key in 0..100 {
+            assert_eq!(map.get(&key, |n| *n), Some(80));
+        }
+        assert_eq!(map.len(), 100 + 8 * 100);
+    }
+
+    #[test]
+    fn stack_is_lifo() {
+        let stack: Stack<_> = (0..3).collect();
+        assert!(!stack.is_empty());
+        stack.push(3);
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        drop(stack);
+
+        let stack = Stack::<String>::new();
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+        // Values left on the stack are dropped with it.
+        stack.push("left".to_string());
+    }
+
+    #[test]
+    fn stack_concurrent_push_pop() {
+        let stack = Stack::new();
+        let popped = AtomicUsize::new(0);
+        let sum = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for t in 0..4 {
+                let stack = &stack;
+                s.spawn(move || {
+                    for i in 0..2_000 {
+                        stack.push(t * 2_000 + i);
+                    }
+                });
+            }
+            for _ in 0..4 {
+                s.spawn(|| {
+                    while popped.load(SeqCst) < 8_000 {
+                        if let Some(v) = stack.pop() {
+                            sum.fetch_add(v, SeqCst);
+                            popped.fetch_add(1, SeqCst);
+                        }
+                    }
+                });
+            }
+        });
+        assert!(stack.is_empty());
+        assert_eq!(sum.into_inner(), (0..8_000).sum::<usize>());
+    }
+
+    #[test]
+    fn queue_is_fifo() {
+        let queue: Queue<_> = (0..3).map(|i| i.to_string()).collect();
+        queue.enqueue("3".to_string());
+        assert_eq!(queue.dequeue().as_deref(), Some("0"));
+        assert_eq!(queue.dequeue().as_deref(), Some("1"));
+        // The rest is dropped with the queue.
+        drop(queue);
+
+        let queue = Queue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.dequeue(), None::<u8>);
+        queue.enqueue(1);
+        assert!(!queue.is_empty());
+    }
+
+    #[test]
+    fn queue_keeps_each_producers_order() {
+        const PER_PRODUCER: usize = 5_000;
+        let queue = Queue::new();
+        let received = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for p in 0..4 {
+                let queue = &queue;
+                s.spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.enqueue((p, i));
+                    }
+                });
+            }
+            for _ in 0..4 {
+                s.spawn(|| {
+                    // Each consumer must see every producer's values in order.
+                    let mut last = [None; 4];
+                    while received.load(SeqCst) < 4 * PER_PRODUCER {
+                        if let Some((p, i)) = queue.dequeue() {
+                            assert!(last[p] < Some(i));
+                            last[p] = Some(i);
+                            received.fetch_add(1, SeqCst);
+                        }
+                    }
+                });
+            }
+        });
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn stack_and_queue_under_thread_churn() {
+        churn::<Epoch>();
+        churn::<Hazard>();
+    }
+
+    fn churn<R: Reclaim + Default + Sync>() {
+        let stack = Stack::<_, R>::default();
+        let queue = Queue::<_, R>::default();
+        let taken = AtomicUsize::new(0);
+        // Short-lived threads, so epoch entries and bags change hands.
+        for round in 0..20 {
+            thread::scope(|s| {
+                for t in 0..4 {
+                    let (stack, queue, taken) = (&stack, &queue, &taken);
+                    s.spawn(move || {
+                        for i in 0..200 {
+                            let value = format!("{round}-{t}-{i}");
+                            stack.push(value.clone());
+                            queue.enqueue(value);
+                            for popped in [stack.pop(), queue.dequeue()].into_iter().flatten() {
+                                // A freed node would show up as garbage here.
+                                assert_eq!(popped.split('-').count(), 3);
+                                taken.fetch_add(1, SeqCst);
+                            }
+                        }
+                    });
+                }
+            });
+        }
+        assert_eq!(taken.into_inner(), 2 * 20 * 4 * 200);
+        assert!(stack.is_empty() && queue.is_empty());
+    }
+
+    #[test]
+    fn deque_owner_is_lifo_stealers_fifo() {
+        let deque = WorkStealingDeque::new();
+        let stealer = deque.stealer();
+        assert_eq!(stealer.steal(), Steal::Empty);
+        // Enough to grow the buffer a few times.
+        for i in 0..100 {
+            deque.push(i.to_string());
+        }
+        assert_eq!(deque.len(), 100);
+        assert_eq!(deque.pop().as_deref(), Some("99"));
+        assert_eq!(stealer.steal().success().as_deref(), Some("0"));
+        assert_eq!(stealer.clone().len(), 98);
+        // The rest is dropped with the last handle.
+        drop(deque);
+        assert_eq!(stealer.steal().success().as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn deque_every_value_taken_once() {
+        const VALUES: usize = 20_000;
+        let deque = WorkStealingDeque::new();
+        let taken = AtomicUsize::new(0);
+        let sum = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..3 {
+                let stealer = deque.stealer();
+                let (taken, sum) = (&taken, &sum);
+                s.spawn(move || {
+                    while taken.load(SeqCst) < VALUES {
+                        if let Steal::Success(v) = stealer.steal() {
+                            sum.fetch_add(v, SeqCst);
+                            taken.fetch_add(1, SeqCst);
+                        }
+                    }
+                });
+            }
+            for i in 0..VALUES {
+                deque.push(i);
+                // Pop some back, racing the stealers for the last values.
+                if i.is_multiple_of(3) {
+                    if let Some(v) = deque.pop() {
+                        sum.fetch_add(v, SeqCst);
+                        taken.fetch_add(1, SeqCst);
+                    }
+                }
+            }
+            while let Some(v) = deque.pop() {
+                sum.fetch_add(v, SeqCst);
+                taken.fetch_add(1, SeqCst);
+            }
+        });
+        assert_eq!(sum.into_inner(), (0..VALUES).sum::<usize>());
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use loom::{cell::UnsafeCell, thread};
+
+    use super::*;
+    use crate::sync::SpinLock;
+
+    // A value whose reads loom checks against its write, so a push that
+    // doesn't publish it to the pop is caught.
+    struct Value(UnsafeCell<usize>);
+
+    impl Value {
+        fn new(n: usize) -> Self {
+            let value = Value(UnsafeCell::new(0));
+            value.0.with_mut(|p| unsafe { *p = n });
+            value
+        }
+
+        fn get(&self) -> usize {
+            self.0.with(|p| unsafe { *p })
+        }
+    }
+
+    struct Retired {
+        ptr: *mut u8,
+        free: unsafe fn(*mut u8),
+    }
+
+    unsafe impl Send for Retired {}
+
+    impl Retired {
+        unsafe fn free<T>(ptr: *mut u8) {
+            drop(MyBox::from_raw(ptr.cast::<T>()));
+        }
+    }
+
+    // Frees nothing before the structure is dropped. Epochs and hazard pointers
+    // keep global state that outlives a modeled execution, so the model
+    // explores the structure's own atomics only.
+    #[derive(Default)]
+    struct FreeOnDrop(MyArc<SpinLock<Vec<Retired>>>);
+
+    unsafe impl Reclaim for FreeOnDrop {
+        type Guard = MyArc<SpinLock<Vec<Retired>>>;
+
+        fn guard(&self) -> Self::Guard {
+            self.0.clone()
+        }
+
+        fn protect<T>(_: &mut Self::Guard, _: usize, src: &AtomicPtr<T>) -> *mut T {
+            src.load(Acquire)
+        }
+
+        unsafe fn retire<T>(guard: &Self::Guard, ptr: *mut T) {
+            guard.lock().push(Retired {
+                ptr: ptr.cast(),
+                free: Retired::free::<T>,
+            });
+        }
+    }
+
+    impl Drop for FreeOnDrop {
+        fn drop(&mut self) {
+            for r in self.0.lock().drain(..) {
+                unsafe { (r.free)(r.ptr) };
+            }
+        }
+    }
+
+    #[test]
+    fn stack_push_publishes_the_value() {
+        loom::model(|| {
+            let stack = MyArc::new(Stack::with_reclaim(FreeOnDrop::default()));
+            let pusher = stack.clone();
+            let handle = thread::spawn(move || pusher.push(Value::new(1)));
+            let value = loop {
+                match stack.pop() {
+                    Some(value) => break value,
+                    None => thread::yield_now(),
+                }
+            };
+            assert_eq!(value.get(), 1);
+            handle.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn stack_pops_take_distinct_values() {
+        loom::model(|| {
+            let stack = MyArc::new(Stack::with_reclaim(FreeOnDrop::default()));
+            stack.push(1);
+            stack.push(2);
+            let popper = stack.clone();
+            let handle = thread::spawn(move || popper.pop());
+            let mine = stack.pop();
+            let mut popped = [mine.unwrap(), handle.join().unwrap().unwrap()];
+            popped.sort();
+            assert_eq!(popped, [1, 2]);
+            assert!(stack.is_empty());
+        });
+    }
+
+    #[test]
+    fn stack_push_races_with_pop() {
+        loom::model(|| {
+            let stack = MyArc::new(Stack::with_reclaim(FreeOnDrop::default()));
+            stack.push(1);
+            let pusher = stack.clone();
+            let handle = thread::spawn(move || pusher.push(2));
+            let first = stack.pop().unwrap();
+            handle.join().unwrap();
+            let second = stack.pop().unwrap();
+            assert!(matches!((first, second), (1, 2) | (2, 1)));
+            assert_eq!(stack.pop(), None);
+        });
+    }
+
+    #[test]
+    fn queue_enqueue_publishes_the_value() {
+        loom::model(|| {
+            let queue = MyArc::new(Queue::with_reclaim(FreeOnDrop::default()));
+            let producer = queue.clone();
+            let handle = thread::spawn(move || producer.enqueue(Value::new(1)));
+            let value = loop {
+                match queue.dequeue() {
+                    Some(value) => break value,
+                    None => thread::yield_now(),
+                }
+            };
+            assert_eq!(value.get(), 1);
+            handle.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn queue_keeps_one_producer_in_order() {
+        loom::model(|| {
+            let queue = MyArc::new(Queue::with_reclaim(FreeOnDrop::default()));
+            let producer = queue.clone();
+            let handle = thread::spawn(move || {
+                producer.enqueue(1);
+                producer.enqueue(2);
+            });
+            let mut got: Vec<_> = queue.dequeue().into_iter().collect();
+            handle.join().unwrap();
+            got.extend(std::iter::from_fn(|| queue.dequeue()));
+            assert_eq!(got, [1, 2]);
+        });
+    }
+
+    #[test]
+    fn queue_dequeues_take_distinct_values() {
+        loom::model(|| {
+            let queue = MyArc::new(Queue::with_reclaim(FreeOnDrop::default()));
+            queue.enqueue(1);
+            queue.enqueue(2);
+            let consumer = queue.clone();
+            let handle = thread::spawn(move || consumer.dequeue());
+            let first = queue.dequeue().unwrap();
+            let second = handle.join().unwrap().unwrap();
+            assert!(matches!((first, second), (1, 2) | (2, 1)));
+            assert!(queue.is_empty());
+        });
+    }
+}
@@ -0,0 +1,398 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::Hash;
+use core::ops::{BitAnd, BitOr, Sub};
+
+use crate::hashmap::{self, HashMap};
+
+/// A multiset that counts how many times each element was added, like Python's `Counter`.
+/// Elements whose count drops to zero are removed.
+///
+/// Counters can be combined with [`Counter::sum`], [`Counter::difference`],
+/// [`Counter::union`] and [`Counter::intersection`]. The last three are also
+/// available as the `-`, `|` and `&` operators on references.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::counter::Counter;
+///
+/// let words: Counter<_> = "a b a c a b".split(' ').collect();
+/// assert_eq!(words.count("a"), 3);
+/// assert_eq!(words.count("z"), 0);
+/// assert_eq!(words.most_common(2), vec![(&"a", 3), (&"b", 2)]);
+///
+/// let other: Counter<_> = ["a", "c", "c"].into_iter().collect();
+/// assert_eq!((&words & &other).count("c"), 1);
+/// assert_eq!((&words | &other).count("c"), 2);
+/// assert_eq!(words.sum(&other).total(), 9);
+/// ```
+pub struct Counter<T> {
+    counts: HashMap<T, usize>,
+    // Sum of all counts.
+    total: usize,
+}
+
+impl<T> Default for Counter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Counter<T> {
+    /// Create a new, empty counter.
+    pub fn new() -> Self {
+        Counter {
+            counts: HashMap::new(),
+            total: 0,
+        }
+    }
+
+    /// Sum of the counts of every element.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Return `true` if no element has been counted.
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Iterate over the distinct elements and their counts, in no particular order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.counts.iter(),
+        }
+    }
+}
+
+impl<T> Counter<T>
+where
+    T: Hash + Eq,
+{
+    /// Number of distinct elements.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Count one occurrence of an element.
+    pub fn add(&mut self, item: T) {
+        self.add_n(item, 1);
+    }
+
+    /// Count `n` occurrences of an element.
+    pub fn add_n(&mut self, item: T, n: usize) {
+        if n == 0 {
+            return;
+        }
+        *self.counts.entry(item).or_insert(0) += n;
+        self.total += n;
+    }
+
+    /// Remove one occurrence of an element.
+    /// Returns `false` if the element wasn't counted.
+    pub fn remove<Q>(&mut self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_n(item, 1) == 1
+    }
+
+    /// Remove up to `n` occurrences of an element.
+    /// Returns the number of occurrences actually removed.
+    pub fn remove_n<Q>(&mut self, item: &Q, n: usize) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(count) = self.counts.get_mut(item) else {
+            return 0;
+        };
+        let removed = n.min(*count);
+        *count -= removed;
+        if *count == 0 {
+            self.counts.remove(item);
+        }
+        self.total -= removed;
+        removed
+    }
+
+    /// Number of occurrences of an element, zero if it was never counted.
+    pub fn count<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    /// Return the `n` elements with the highest counts, most common first.
+    /// Elements with equal counts are returned in no particular order.
+    pub fn most_common(&self, n: usize) -> Vec<(&T, usize)> {
+        let mut items: Vec<_> = self.iter().collect();
+        items.sort_by_key(|&(_, n)| core::cmp::Reverse(n));
+        items.truncate(n);
+        items
+    }
+
+    // Build a counter from every element of `self` or `other`, with the count
+    // computed by `f(self_count, other_count)`.
+    fn combine<F>(&self, other: &Self, f: F) -> Self
+    where
+        T: Clone,
+        F: Fn(usize, usize) -> usize,
+    {
+        let mut res = Counter::new();
+        for (item, n) in self.iter() {
+            res.add_n(item.clone(), f(n, other.count(item)));
+        }
+        for (item, n) in other.iter() {
+            if !self.counts.contains_key(item) {
+                res.add_n(item.clone(), f(0, n));
+            }
+        }
+        res
+    }
+
+    /// Add the counts of both counters.
+    pub fn sum(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        self.combine(other, |a, b| a + b)
+    }
+
+    /// Subtract the counts of `other`, dropping elements whose count would not be positive.
+    pub fn difference(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        self.combine(other, usize::saturating_sub)
+    }
+
+    /// Keep the maximum of both counts.
+    pub fn union(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        self.combine(other, usize::max)
+    }
+
+    /// Keep the minimum of both counts.
+    pub fn intersection(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        self.combine(other, usize::min)
+    }
+}
+
+impl<T: Hash + Eq + Clone> Sub for &Counter<T> {
+    type Output = Counter<T>;
+
+    fn sub(self, other: Self) -> Counter<T> {
+        self.difference(other)
+    }
+}
+
+impl<T: Hash + Eq + Clone> BitOr for &Counter<T> {
+    type Output = Counter<T>;
+
+    fn bitor(self, other: Self) -> Counter<T> {
+        self.union(other)
+    }
+}
+
+impl<T: Hash + Eq + Clone> BitAnd for &Counter<T> {
+    type Output = Counter<T>;
+
+    fn bitand(self, other: Self) -> Counter<T> {
+        self.intersection(other)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Counter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the distinct elements of a [`Counter`] and their counts, in no
+/// particular order.
+pub struct Iter<'a, T> {
+    inner: hashmap::Iter<'a, T, usize>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(item, &n)| (item, n))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Counter<T> {
+    type Item = (&'a T, usize);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for Counter<T>
+where
+    T: Hash + Eq,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut counter = Counter::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<T> Extend<T> for Counter<T>
+where
+    T: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Counter<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+/// A counter deserializes from a map of the elements to their counts.
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for Counter<T>
+where
+    T: serde::Deserialize<'de> + Hash + Eq,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let counts: Vec<(T, usize)> = crate::serde_impl::deserialize_map(deserializer)?;
+        let mut counter = Counter::new();
+        for (item, n) in counts {
+            counter.add_n(item, n);
+        }
+        Ok(counter)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for Counter<T>
+where
+    T: arbitrary::Arbitrary<'a> + Hash + Eq,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for Counter<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static + Hash + Eq,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_remove() {
+        let mut c = Counter::new();
+        c.add("foo");
+        c.add_n("foo", 2);
+        c.add("bar");
+        c.add_n("baz", 0);
+        assert_eq!(c.count("foo"), 3);
+        assert_eq!(c.len(), 2);
+        assert_eq!(c.total(), 4);
+        assert!(c.remove("bar"));
+        assert!(!c.remove("bar"));
+        assert_eq!(c.remove_n("foo", 5), 3);
+        assert!(c.is_empty());
+        assert_eq!(c.len(), 0);
+    }
+
+    #[test]
+    fn most_common() {
+        let c: Counter<_> = "mississippi".chars().collect();
+        let common = c.most_common(4);
+        assert_eq!(common[0].1, 4);
+        assert_eq!(common[1].1, 4);
+        assert_eq!(common[2], (&'p', 2));
+        assert_eq!(common[3], (&'m', 1));
+        assert_eq!(c.most_common(10).len(), 4);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a: Counter<_> = [1, 1, 1, 2].into_iter().collect();
+        let b: Counter<_> = [1, 2, 2, 3].into_iter().collect();
+
+        let sum = a.sum(&b);
+        assert_eq!((sum.count(&1), sum.count(&2), sum.count(&3)), (4, 3, 1));
+
+        let diff = &a - &b;
+        assert_eq!((diff.count(&1), diff.count(&2), diff.count(&3)), (2, 0, 0));
+        assert_eq!(diff.len(), 1);
+
+        let union = &a | &b;
+        assert_eq!(
+            (union.count(&1), union.count(&2), union.count(&3)),
+            (3, 2, 1)
+        );
+
+        let inter = &a & &b;
+        assert_eq!(
+            (inter.count(&1), inter.count(&2), inter.count(&3)),
+            (1, 1, 0)
+        );
+        assert_eq!(inter.total(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let counter: Counter<_> = ["a", "b", "a"].into_iter().collect();
+        let json = serde_json::to_string(&counter).unwrap();
+        let back: Counter<&str> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.count("a"), 2);
+        assert_eq!(back.count("b"), 1);
+        assert_eq!(back.total(), 3);
+
+        let back: Counter<&str> = serde_json::from_str(r#"{"a":0,"b":1}"#).unwrap();
+        assert_eq!(back.len(), 1);
+    }
+}
@@ -0,0 +1,436 @@
+use core::{fmt, ops::Deref};
+
+use crate::{linked_list::LinkedList, my_vec::MyVec, rc::MyRc};
+
+/// A [`MyVec`] shared between snapshots and copied on the first write.
+///
+/// Cloning a `CowVec` only bumps a reference count, so taking a snapshot before
+/// an edit is O(1). Mutating methods copy the vector first if any other
+/// snapshot still shares it; later writes to the now unshared copy are as cheap
+/// as on a plain vector.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::cow::CowVec;
+///
+/// let mut doc: CowVec<_> = ["a", "b"].into_iter().collect();
+/// let saved = doc.clone();
+/// assert!(doc.is_shared());
+///
+/// doc.push("c"); // Copies the vector, `saved` keeps the old one.
+/// doc.push("d"); // No copy this time.
+/// assert_eq!(&*doc, &["a", "b", "c", "d"]);
+/// assert_eq!(&*saved, &["a", "b"]);
+/// ```
+pub struct CowVec<T> {
+    vec: MyRc<MyVec<T>>,
+}
+
+impl<T> CowVec<T> {
+    /// Create a new, empty vector.
+    pub fn new() -> Self {
+        CowVec {
+            vec: MyRc::new(MyVec::new()),
+        }
+    }
+
+    /// Return `true` if another snapshot shares the vector, so the next write
+    /// copies it.
+    pub fn is_shared(&self) -> bool {
+        MyRc::strong_count(&self.vec) > 1
+    }
+
+    /// Return `true` if both snapshots share the same vector.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        MyRc::ptr_eq(&self.vec, &other.vec)
+    }
+}
+
+impl<T: Clone> CowVec<T> {
+    /// A mutable reference to the vector, copying it first if it is shared.
+    pub fn to_mut(&mut self) -> &mut MyVec<T> {
+        MyRc::make_mut(&mut self.vec)
+    }
+
+    /// Return the vector, copying it if it is shared.
+    pub fn into_inner(self) -> MyVec<T> {
+        MyRc::unwrap_or_clone(self.vec)
+    }
+
+    /// Append an element.
+    pub fn push(&mut self, value: T) {
+        self.to_mut().push(value);
+    }
+
+    /// Remove and return the last element.
+    pub fn pop(&mut self) -> Option<T> {
+        // Popping from an empty snapshot shouldn't copy it.
+        if self.is_empty() {
+            return None;
+        }
+        self.to_mut().pop()
+    }
+
+    /// Insert an element at `index`, shifting the later ones.
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.to_mut().insert(index, value);
+    }
+
+    /// Remove and return the element at `index`, shifting the later ones.
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        self.to_mut().remove(index)
+    }
+
+    /// Remove every element. A shared vector is left to the other snapshots
+    /// instead of being copied.
+    pub fn clear(&mut self) {
+        if self.is_shared() {
+            *self = CowVec::new();
+        } else {
+            while self.to_mut().pop().is_some() {}
+        }
+    }
+}
+
+impl<T> Deref for CowVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.vec
+    }
+}
+
+impl<T> Clone for CowVec<T> {
+    /// Take a snapshot, without copying the vector.
+    fn clone(&self) -> Self {
+        CowVec {
+            vec: self.vec.clone(),
+        }
+    }
+}
+
+impl<T> Default for CowVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<MyVec<T>> for CowVec<T> {
+    fn from(vec: MyVec<T>) -> Self {
+        CowVec {
+            vec: MyRc::new(vec),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CowVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for CowVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for CowVec<T> {}
+
+impl<T: Clone> Extend<T> for CowVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let vec = self.to_mut();
+        for value in iter {
+            vec.push(value);
+        }
+    }
+}
+
+impl<T: Clone> FromIterator<T> for CowVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = CowVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+/// A [`LinkedList`] shared between snapshots and copied on the first write.
+///
+/// Like [`CowVec`], cloning only bumps a reference count and mutating methods
+/// copy the list first if another snapshot still shares it.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::cow::CowList;
+///
+/// let mut list = CowList::new();
+/// list.push(1);
+/// list.push(2);
+/// let saved = list.clone();
+/// assert_eq!(list.pop(), Some(2));
+/// assert_eq!((list.len(), saved.len()), (1, 2));
+/// ```
+pub struct CowList<T: Clone> {
+    list: MyRc<LinkedList<T>>,
+}
+
+impl<T: Clone> CowList<T> {
+    /// Create a new, empty list.
+    pub fn new() -> Self {
+        CowList {
+            list: MyRc::new(LinkedList::new()),
+        }
+    }
+
+    /// Length of the list.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Return `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Return `true` if another snapshot shares the list, so the next write
+    /// copies it.
+    pub fn is_shared(&self) -> bool {
+        MyRc::strong_count(&self.list) > 1
+    }
+
+    /// Return `true` if both snapshots share the same list.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        MyRc::ptr_eq(&self.list, &other.list)
+    }
+
+    /// The shared list.
+    pub fn as_list(&self) -> &LinkedList<T> {
+        &self.list
+    }
+
+    /// A mutable reference to the list, copying it first if it is shared.
+    pub fn to_mut(&mut self) -> &mut LinkedList<T> {
+        MyRc::make_mut(&mut self.list)
+    }
+
+    /// Return the list, copying it if it is shared.
+    pub fn into_inner(self) -> LinkedList<T> {
+        MyRc::unwrap_or_clone(self.list)
+    }
+
+    /// Add an element to the end of the list.
+    pub fn push(&mut self, elem: T) {
+        self.to_mut().push(elem);
+    }
+
+    /// Remove an element from the end of the list.
+    pub fn pop(&mut self) -> Option<T> {
+        // Popping from an empty snapshot shouldn't copy it.
+        if self.is_empty() {
+            return None;
+        }
+        self.to_mut().pop()
+    }
+
+    /// Insert an element after the one at the given index, like
+    /// [`LinkedList::insert`].
+    /// Panics if the index is out of bounds.
+    pub fn insert(&mut self, index: usize, elem: T) {
+        self.to_mut().insert(index, elem);
+    }
+
+    /// Remove the element at the given index.
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        self.to_mut().remove(index)
+    }
+}
+
+impl<T: Clone> Clone for CowList<T> {
+    /// Take a snapshot, without copying the list.
+    fn clone(&self) -> Self {
+        CowList {
+            list: self.list.clone(),
+        }
+    }
+}
+
+impl<T: Clone> Default for CowList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> From<LinkedList<T>> for CowList<T> {
+    fn from(list: LinkedList<T>) -> Self {
+        CowList {
+            list: MyRc::new(list),
+        }
+    }
+}
+
+impl<T: Clone + fmt::Debug> fmt::Debug for CowList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.list, f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for CowVec<T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for CowVec<T>
+where
+    T: serde::Deserialize<'de> + Clone,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Clone + serde::Serialize> serde::Serialize for CowList<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_list().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Clone + serde::Deserialize<'de>> serde::Deserialize<'de> for CowList<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        LinkedList::deserialize(deserializer).map(CowList::from)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for CowVec<T>
+where
+    T: arbitrary::Arbitrary<'a> + Clone,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a> + Clone> arbitrary::Arbitrary<'a> for CowList<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        LinkedList::arbitrary(u).map(CowList::from)
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        LinkedList::arbitrary_take_rest(u).map(CowList::from)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for CowVec<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static + Clone,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary + Clone + 'static> proptest::arbitrary::Arbitrary
+    for CowList<T>
+{
+    type Parameters = <LinkedList<T> as proptest::arbitrary::Arbitrary>::Parameters;
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::arbitrary::any_with::<LinkedList<T>>(args)
+            .prop_map(CowList::from)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use super::*;
+
+    #[test]
+    fn vec_copies_only_when_shared() {
+        let mut vec: CowVec<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+        vec.push("c".to_string());
+
+        let snapshot = vec.clone();
+        assert!(vec.ptr_eq(&snapshot));
+        assert_eq!(vec.remove(0), "a");
+        assert!(!vec.ptr_eq(&snapshot));
+        assert!(!vec.is_shared());
+        assert_eq!(&*snapshot, &["a", "b", "c"]);
+        assert_eq!(&*vec, &["b", "c"]);
+
+        let other = vec.clone();
+        vec.clear();
+        assert!(vec.is_empty());
+        assert_eq!(other.len(), 2);
+        assert_eq!(&*other.into_inner(), &["b", "c"]);
+    }
+
+    #[test]
+    fn list_snapshots_are_independent() {
+        let mut list = CowList::new();
+        for i in 0..5 {
+            list.push(i);
+        }
+        let snapshot = list.clone();
+        list.insert(0, 10);
+        list.push(11);
+        assert_eq!(list.remove(1), 10);
+        assert_eq!(list.len(), 6);
+        assert_eq!(snapshot.len(), 5);
+
+        let mut restored = snapshot.into_inner();
+        assert_eq!(restored.pop(), Some(4));
+        assert_eq!(list.pop(), Some(11));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let values: CowVec<i32> = [1, 2, 3].into_iter().collect();
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let back: CowVec<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(*back, *values);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn list_serde_round_trip() {
+        let list = CowList::from([1, 2, 3].into_iter().collect::<LinkedList<_>>());
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let mut back: CowList<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.pop(), Some(3));
+    }
+}
@@ -0,0 +1,323 @@
+use alloc::{vec, vec::Vec};
+use core::ops::{Add, Range, Sub};
+
+/// Fenwick tree, also known as a binary indexed tree.
+/// It maintains prefix sums of a sequence in a single array of the same length:
+/// both adding to an element and computing a prefix sum take O(log n).
+/// It is a lighter alternative to the segment tree when only sums are needed.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::fenwick_tree::FenwickTree;
+///
+/// let mut tree = FenwickTree::new(5);
+/// tree.add(0, 3);
+/// tree.add(2, 4);
+/// tree.add(4, 1);
+/// assert_eq!(tree.prefix_sum(3), 7);
+/// assert_eq!(tree.range_sum(2..5), 5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FenwickTree<T> {
+    // `tree[i - 1]` holds the sum of the `i & i.wrapping_neg()` elements ending at `i - 1`.
+    tree: Vec<T>,
+}
+
+impl<T> FenwickTree<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    /// Create a Fenwick tree of `len` elements, all set to `T::default()`.
+    pub fn new(len: usize) -> Self {
+        FenwickTree {
+            tree: vec![T::default(); len],
+        }
+    }
+
+    /// Build a Fenwick tree from `data` in O(n).
+    pub fn from_slice(data: &[T]) -> Self {
+        Self::from_vec(data.to_vec())
+    }
+
+    fn from_vec(mut tree: Vec<T>) -> Self {
+        for i in 1..=tree.len() {
+            let parent = i + lowbit(i);
+            if parent <= tree.len() {
+                tree[parent - 1] = tree[parent - 1] + tree[i - 1];
+            }
+        }
+        FenwickTree { tree }
+    }
+
+    /// Number of elements in the tree.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Return `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Append an element. O(log n).
+    pub fn push(&mut self, value: T) {
+        // The new node sums the element and the `lowbit(i) - 1` ones before it.
+        let i = self.len() + 1;
+        let before = self.range_sum(i - lowbit(i)..i - 1);
+        self.tree.push(before + value);
+    }
+
+    /// Add `delta` to the element at `index`.
+    /// Panics if the index is out of bounds.
+    pub fn add(&mut self, index: usize, delta: T) {
+        let len = self.len();
+        if index >= len {
+            panic!("index (is {index}) should be < len (is {len})");
+        }
+        let mut i = index + 1;
+        while i <= len {
+            self.tree[i - 1] = self.tree[i - 1] + delta;
+            i += lowbit(i);
+        }
+    }
+
+    /// Sum of the first `len` elements, i.e. of `0..len`.
+    /// Panics if `len` is greater than the length of the tree.
+    pub fn prefix_sum(&self, len: usize) -> T {
+        if len > self.len() {
+            panic!(
+                "prefix length (is {len}) should be <= len (is {tree_len})",
+                tree_len = self.len()
+            );
+        }
+        let mut sum = T::default();
+        let mut i = len;
+        while i > 0 {
+            sum = sum + self.tree[i - 1];
+            i -= lowbit(i);
+        }
+        sum
+    }
+
+    /// Sum of the elements in `range`.
+    /// Panics if the range is out of bounds.
+    pub fn range_sum(&self, range: Range<usize>) -> T {
+        if range.start > range.end {
+            panic!(
+                "range start (is {start}) should be <= range end (is {end})",
+                start = range.start,
+                end = range.end
+            );
+        }
+        self.prefix_sum(range.end) - self.prefix_sum(range.start)
+    }
+
+    /// Get the element at `index`.
+    /// Panics if the index is out of bounds.
+    pub fn get(&self, index: usize) -> T {
+        self.range_sum(index..index + 1)
+    }
+}
+
+impl<T> FenwickTree<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + PartialOrd,
+{
+    /// Find the index of the `k`-th unit (0-based) when element `i` is read as the
+    /// number of occurrences of `i`, i.e. the smallest index whose inclusive prefix
+    /// sum is greater than `k`.
+    /// Returns `None` if the total sum is not greater than `k`.
+    ///
+    /// All elements must be non-negative for the search to be meaningful.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::fenwick_tree::FenwickTree;
+    ///
+    /// // The multiset {1, 1, 3}.
+    /// let tree = FenwickTree::from_slice(&[0, 2, 0, 1]);
+    /// assert_eq!(tree.kth(0), Some(1));
+    /// assert_eq!(tree.kth(1), Some(1));
+    /// assert_eq!(tree.kth(2), Some(3));
+    /// assert_eq!(tree.kth(3), None);
+    /// ```
+    pub fn kth(&self, k: T) -> Option<usize> {
+        let len = self.len();
+        let mut pos = 0;
+        let mut remaining = k;
+        let mut step = if len == 0 {
+            0
+        } else {
+            1 << (usize::BITS - 1 - len.leading_zeros())
+        };
+        // Descend from the highest power of two, skipping every block whose sum
+        // does not exceed the remaining rank.
+        while step > 0 {
+            let next = pos + step;
+            if next <= len && self.tree[next - 1] <= remaining {
+                pos = next;
+                remaining = remaining - self.tree[next - 1];
+            }
+            step /= 2;
+        }
+        if pos < len {
+            Some(pos)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Extend<T> for FenwickTree<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for FenwickTree<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        FenwickTree::from_vec(iter.into_iter().collect())
+    }
+}
+
+// Value of the lowest set bit of `i`.
+fn lowbit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+/// A tree serializes as the sequence of its elements, not of its partial sums.
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for FenwickTree<T>
+where
+    T: serde::Serialize + Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq((0..self.len()).map(|i| self.get(i)))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for FenwickTree<T>
+where
+    T: serde::Deserialize<'de> + Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self::from_vec)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for FenwickTree<T>
+where
+    T: arbitrary::Arbitrary<'a> + Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for FenwickTree<T>
+where
+    T: proptest::arbitrary::Arbitrary
+        + 'static
+        + Copy
+        + Default
+        + Add<Output = T>
+        + Sub<Output = T>,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_sum() {
+        let data = [5, 3, 8, 6, 1, 4, 2];
+        let tree = FenwickTree::from_slice(&data);
+        for len in 0..=data.len() {
+            assert_eq!(tree.prefix_sum(len), data[..len].iter().sum::<i32>());
+        }
+    }
+
+    #[test]
+    fn collect_and_extend() {
+        let data = [5, 3, 8, 6, 1, 4, 2, 7, 9];
+        let mut tree: FenwickTree<_> = data[..3].iter().copied().collect();
+        tree.extend(data[3..].iter().copied());
+        assert_eq!(tree.len(), data.len());
+        for len in 0..=data.len() {
+            assert_eq!(tree.prefix_sum(len), data[..len].iter().sum::<i32>());
+        }
+    }
+
+    #[test]
+    fn add() {
+        let mut tree = FenwickTree::new(8);
+        let mut naive = [0i64; 8];
+        for (i, delta) in [(0, 5), (7, 2), (3, -4), (3, 10), (5, 1)] {
+            tree.add(i, delta);
+            naive[i] += delta;
+        }
+        for l in 0..8 {
+            for r in l..=8 {
+                assert_eq!(tree.range_sum(l..r), naive[l..r].iter().sum::<i64>());
+            }
+        }
+        assert_eq!(tree.get(3), 6);
+    }
+
+    #[test]
+    fn kth() {
+        let mut tree = FenwickTree::new(10);
+        for value in [7, 2, 2, 9, 0, 7] {
+            tree.add(value, 1);
+        }
+        let res: Vec<_> = (0..6).map(|k| tree.kth(k).unwrap()).collect();
+        assert_eq!(res, vec![0, 2, 2, 7, 7, 9]);
+        assert_eq!(tree.kth(6), None);
+        assert_eq!(FenwickTree::<u32>::new(0).kth(0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "index (is 3) should be < len (is 3)")]
+    fn add_out_of_bounds() {
+        let mut tree = FenwickTree::new(3);
+        tree.add(3, 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let tree = FenwickTree::from_slice(&[3, 0, 4, 1]);
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(json, "[3,0,4,1]");
+        let back: FenwickTree<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.prefix_sum(3), 7);
+        assert_eq!(back.range_sum(2..4), 5);
+    }
+}
@@ -0,0 +1,277 @@
+use alloc::{string::String, vec::Vec};
+use core::{fmt, slice};
+
+use crate::{my_vec::MyVec, string::MyString};
+
+/// A vector's buffer described as a C struct, for handing across an FFI
+/// boundary.
+///
+/// A `CVec` is just the pointer, length and capacity of a [`MyVec`] in a
+/// `#[repr(C)]` struct, so C code can read and write the elements in place.
+/// [`CVec::into_raw`] gives up the vector without copying its elements and
+/// [`CVec::from_raw`] takes it back, so a buffer built in Rust can be passed
+/// to C and returned to be freed by the allocator that made it. A `CVec` does
+/// not own the buffer: dropping one leaks it.
+///
+/// The struct [`CHeader`] writes for a `CVec` matches this layout.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::{ffi::CVec, my_vec::MyVec};
+///
+/// extern "C" fn double(v: CVec<u32>) {
+///     for x in unsafe { v.as_mut_slice() } {
+///         *x *= 2;
+///     }
+/// }
+///
+/// let mut v = MyVec::new();
+/// v.push(1);
+/// v.push(2);
+/// let raw = CVec::into_raw(v);
+/// double(raw);
+/// let v = unsafe { CVec::from_raw(raw) };
+/// assert_eq!(*v, [2, 4]);
+/// ```
+#[repr(C)]
+#[derive(Debug)]
+pub struct CVec<T> {
+    /// Start of the buffer. Never null, but dangling if `cap` is zero or `T`
+    /// is zero-sized.
+    pub ptr: *mut T,
+    /// Number of initialized elements at the start of the buffer.
+    pub len: usize,
+    /// Number of elements the buffer has room for.
+    pub cap: usize,
+}
+
+/// A byte buffer described as a C struct, as made from a [`MyString`] or a
+/// vector of bytes.
+pub type CBytes = CVec<u8>;
+
+// Derived `Clone` and `Copy` would need `T: Copy`, but only the pointer is
+// copied.
+impl<T> Clone for CVec<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for CVec<T> {}
+
+impl<T> CVec<T> {
+    /// Give up ownership of `vec`'s buffer, returning a description of it.
+    /// The buffer is not freed until it is handed back to [`CVec::from_raw`].
+    pub fn into_raw(vec: MyVec<T>) -> Self {
+        let (ptr, len, cap, _) = vec.into_raw_parts_with_alloc();
+        CVec { ptr, len, cap }
+    }
+
+    /// Take back ownership of a buffer given up by [`CVec::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `raw` must come from [`CVec::into_raw`], and the buffer must not have
+    /// been taken back already. Foreign code may have changed the elements
+    /// and lowered `len`, but the first `len` elements must be initialized
+    /// and `ptr` and `cap` must be unchanged.
+    pub unsafe fn from_raw(raw: Self) -> MyVec<T> {
+        // SAFETY: the caller guarantees these are the parts of a vector
+        // allocated with `Global`.
+        unsafe { MyVec::from_raw_parts_in(raw.ptr, raw.len, raw.cap, alloc::alloc::Global) }
+    }
+
+    /// Number of initialized elements in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the buffer has no initialized elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// View the initialized elements of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must not have been taken back by [`CVec::from_raw`], the
+    /// first `len` elements must be initialized, and they must not be written
+    /// to while the slice is alive.
+    pub unsafe fn as_slice<'a>(&self) -> &'a [T] {
+        // SAFETY: `ptr` is non-null and aligned even when dangling, and the
+        // caller guarantees the rest.
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// View the initialized elements of the buffer mutably.
+    ///
+    /// # Safety
+    ///
+    /// As for [`CVec::as_slice`], and the elements must not be accessed
+    /// through any other pointer while the slice is alive.
+    pub unsafe fn as_mut_slice<'a>(&self) -> &'a mut [T] {
+        // SAFETY: as for `as_slice`.
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<T> From<MyVec<T>> for CVec<T> {
+    fn from(vec: MyVec<T>) -> Self {
+        CVec::into_raw(vec)
+    }
+}
+
+impl<T> From<Vec<T>> for CVec<T> {
+    fn from(vec: Vec<T>) -> Self {
+        CVec::into_raw(MyVec::from(vec))
+    }
+}
+
+impl From<MyString> for CBytes {
+    fn from(s: MyString) -> Self {
+        CVec::into_raw(s.into_bytes())
+    }
+}
+
+impl From<String> for CBytes {
+    fn from(s: String) -> Self {
+        CVec::from(s.into_bytes())
+    }
+}
+
+/// A C header declaring the structs that [`CVec`]s are passed as.
+///
+/// C has no generics, so each element type a `CVec` is used with needs its
+/// own struct; add one with [`CHeader::vec`] for each. The header is written
+/// with `Display`, for example from a build script to a file the C side
+/// includes.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::ffi::CHeader;
+///
+/// let header = CHeader::new("MY_STD_H")
+///     .bytes("Bytes")
+///     .vec("Points", "struct point")
+///     .to_string();
+/// assert!(header.contains("typedef struct Bytes {\n    uint8_t *ptr;"));
+/// assert!(header.contains("    struct point *ptr;\n    size_t len;\n    size_t cap;\n} Points;"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CHeader {
+    guard: String,
+    // Name of each struct and the C type of its elements.
+    vecs: Vec<(String, String)>,
+}
+
+impl CHeader {
+    /// Start a header with no structs, guarded against double inclusion by
+    /// the macro `guard`.
+    pub fn new(guard: &str) -> Self {
+        CHeader {
+            guard: guard.into(),
+            vecs: Vec::new(),
+        }
+    }
+
+    /// Declare a struct `name` for a [`CVec`] of elements with the C type
+    /// `element`.
+    pub fn vec(mut self, name: &str, element: &str) -> Self {
+        self.vecs.push((name.into(), element.into()));
+        self
+    }
+
+    /// Declare a struct `name` for [`CBytes`].
+    pub fn bytes(self, name: &str) -> Self {
+        self.vec(name, "uint8_t")
+    }
+}
+
+impl fmt::Display for CHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let guard = &self.guard;
+        writeln!(f, "#ifndef {guard}")?;
+        writeln!(f, "#define {guard}")?;
+        writeln!(f)?;
+        writeln!(f, "#include <stddef.h>")?;
+        writeln!(f, "#include <stdint.h>")?;
+        for (name, element) in &self.vecs {
+            writeln!(f)?;
+            writeln!(f, "typedef struct {name} {{")?;
+            writeln!(f, "    {element} *ptr;")?;
+            writeln!(f, "    size_t len;")?;
+            writeln!(f, "    size_t cap;")?;
+            writeln!(f, "}} {name};")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "#endif /* {guard} */")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+    use core::mem::{align_of, offset_of, size_of};
+
+    use super::*;
+
+    #[test]
+    fn layout() {
+        assert_eq!(offset_of!(CVec<u64>, ptr), 0);
+        assert_eq!(offset_of!(CVec<u64>, len), size_of::<usize>());
+        assert_eq!(offset_of!(CVec<u64>, cap), 2 * size_of::<usize>());
+        assert_eq!(size_of::<CBytes>(), 3 * size_of::<usize>());
+        assert_eq!(align_of::<CBytes>(), align_of::<usize>());
+    }
+
+    #[test]
+    fn round_trip() {
+        let raw = CVec::from(vec![1, 2, 3]);
+        assert_eq!(raw.len(), 3);
+        assert!(raw.cap >= 3);
+        unsafe {
+            raw.as_mut_slice()[1] = 20;
+            assert_eq!(raw.as_slice(), [1, 20, 3]);
+        }
+        // Foreign code may shrink the vector.
+        let raw = CVec { len: 1, ..raw };
+        let mut v = unsafe { CVec::from_raw(raw) };
+        v.push(4);
+        assert_eq!(*v, [1, 4]);
+
+        let empty = CVec::into_raw(MyVec::<String>::new());
+        assert!(empty.is_empty() && !empty.ptr.is_null());
+        assert!(unsafe { empty.as_slice() }.is_empty());
+        assert!(unsafe { CVec::from_raw(empty) }.is_empty());
+
+        let bytes = CBytes::from(MyString::from("hi"));
+        assert_eq!(unsafe { bytes.as_slice() }, b"hi");
+        let bytes = unsafe { CVec::from_raw(bytes) };
+        assert_eq!(MyString::from_utf8(bytes).unwrap(), "hi");
+    }
+
+    #[test]
+    fn header() {
+        let header = CHeader::new("BUFFERS_H")
+            .bytes("Bytes")
+            .vec("Floats", "double")
+            .to_string();
+        assert_eq!(
+            header,
+            "#ifndef BUFFERS_H\n\
+             #define BUFFERS_H\n\
+             \n\
+             #include <stddef.h>\n\
+             #include <stdint.h>\n\
+             \n\
+             typedef struct Bytes {\n    uint8_t *ptr;\n    size_t len;\n    size_t cap;\n} Bytes;\n\
+             \n\
+             typedef struct Floats {\n    double *ptr;\n    size_t len;\n    size_t cap;\n} Floats;\n\
+             \n\
+             #endif /* BUFFERS_H */\n"
+        );
+    }
+}
@@ -0,0 +1,642 @@
+use alloc::{vec, vec::Vec};
+use core::{fmt, iter, ops, slice};
+
+const OFFSETS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const OFFSETS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A two-dimensional grid stored as a row-major `Vec`.
+/// Cells are addressed by `(x, y)`, where `x` is the column and `y` the row.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::grid::Grid;
+///
+/// let mut grid = Grid::new(3, 2, 0);
+/// grid[(1, 1)] = 5;
+/// assert_eq!(grid.get(1, 1), Some(&5));
+/// assert_eq!(grid.get(3, 0), None);
+/// assert_eq!(grid.row(1), &[0, 5, 0]);
+///
+/// let neighbors: Vec<_> = grid.neighbors4(0, 0).collect();
+/// assert_eq!(neighbors, vec![(1, 0), (0, 1)]);
+///
+/// let transposed = grid.transpose();
+/// assert_eq!((transposed.width(), transposed.height()), (2, 3));
+/// assert_eq!(transposed[(1, 1)], 5);
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    data: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Create a grid of the given size with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        let len = width.checked_mul(height).expect("capacity overflow");
+        Grid {
+            width,
+            height,
+            data: vec![value; len],
+        }
+    }
+
+    /// Create a grid from its cells in row-major order.
+    /// Panics if `data.len() != width * height`.
+    pub fn from_vec(width: usize, height: usize, data: Vec<T>) -> Self {
+        let len = data.len();
+        assert_eq!(
+            Some(len),
+            width.checked_mul(height),
+            "data length (is {len}) should be width * height (is {width} * {height})"
+        );
+        Grid {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Create a grid by calling `f(x, y)` for every cell.
+    pub fn from_fn<F>(width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(usize, usize) -> T,
+    {
+        let mut data = Vec::with_capacity(width.checked_mul(height).expect("capacity overflow"));
+        for y in 0..height {
+            for x in 0..width {
+                data.push(f(x, y));
+            }
+        }
+        Grid {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return `true` if the grid has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Return `true` if `(x, y)` lies inside the grid.
+    pub fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// Get a reference to the cell at `(x, y)`.
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if self.in_bounds(x, y) {
+            Some(&self.data[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Get a mutable reference to the cell at `(x, y)`.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if self.in_bounds(x, y) {
+            Some(&mut self.data[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// The cells of row `y`.
+    /// Panics if `y >= height`.
+    pub fn row(&self, y: usize) -> &[T] {
+        let height = self.height;
+        assert!(
+            y < height,
+            "row index (is {y}) should be < height (is {height})"
+        );
+        &self.data[y * self.width..(y + 1) * self.width]
+    }
+
+    /// The cells of row `y`, mutably.
+    /// Panics if `y >= height`.
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        let height = self.height;
+        assert!(
+            y < height,
+            "row index (is {y}) should be < height (is {height})"
+        );
+        &mut self.data[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Iterate over the cells of column `x`, from top to bottom.
+    /// Panics if `x >= width`.
+    pub fn column(&self, x: usize) -> Column<'_, T> {
+        let width = self.width;
+        assert!(
+            x < width,
+            "column index (is {x}) should be < width (is {width})"
+        );
+        Column {
+            inner: self.data[x..].iter().step_by(width),
+        }
+    }
+
+    /// Iterate over the rows of the grid.
+    pub fn rows(&self) -> Rows<'_, T> {
+        Rows {
+            // `chunks` panics on a zero chunk size, an empty slice yields nothing anyway.
+            inner: self.data.chunks(self.width.max(1)),
+        }
+    }
+
+    /// Iterate over the columns of the grid.
+    pub fn columns(&self) -> impl Iterator<Item = Column<'_, T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// Iterate over every cell with its position, in row-major order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.data.iter().enumerate(),
+            width: self.width,
+        }
+    }
+
+    /// Iterate over every cell with its position, in row-major order, with
+    /// mutable references to the cells.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            inner: self.data.iter_mut().enumerate(),
+            width: self.width,
+        }
+    }
+
+    /// Iterate over the positions of the up to 4 cells sharing an edge with `(x, y)`.
+    pub fn neighbors4(&self, x: usize, y: usize) -> Neighbors {
+        Neighbors::new(x, y, self.width, self.height, &OFFSETS_4)
+    }
+
+    /// Iterate over the positions of the up to 8 cells sharing an edge or a corner with `(x, y)`.
+    pub fn neighbors8(&self, x: usize, y: usize) -> Neighbors {
+        Neighbors::new(x, y, self.width, self.height, &OFFSETS_8)
+    }
+
+    /// Return a new grid with rows and columns swapped.
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.height, self.width, |x, y| self[(y, x)].clone())
+    }
+
+    /// Borrow the `width` x `height` rectangle whose top-left corner is `(x, y)`.
+    /// Panics if the rectangle doesn't fit in the grid.
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> GridView<'_, T> {
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "view ({x}, {y}) {width}x{height} is out of bounds of a {}x{} grid",
+            self.width,
+            self.height
+        );
+        GridView {
+            grid: self,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Consume the grid and return its cells in row-major order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+}
+
+impl<T> ops::Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        let (width, height) = (self.width, self.height);
+        self.get(x, y).unwrap_or_else(|| {
+            panic!("position ({x}, {y}) is out of bounds of a {width}x{height} grid")
+        })
+    }
+}
+
+impl<T> ops::IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(x, y).unwrap_or_else(|| {
+            panic!("position ({x}, {y}) is out of bounds of a {width}x{height} grid")
+        })
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.rows()).finish()
+    }
+}
+
+/// Iterator over the cells of a [`Grid`] with their positions, in row-major
+/// order.
+pub struct Iter<'a, T> {
+    inner: iter::Enumerate<slice::Iter<'a, T>>,
+    width: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (i, cell) = self.inner.next()?;
+        Some(((i % self.width, i / self.width), cell))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Mutable iterator over the cells of a [`Grid`] with their positions, in
+/// row-major order.
+pub struct IterMut<'a, T> {
+    inner: iter::Enumerate<slice::IterMut<'a, T>>,
+    width: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = ((usize, usize), &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (i, cell) = self.inner.next()?;
+        Some(((i % self.width, i / self.width), cell))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Grid<T> {
+    type Item = ((usize, usize), &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Grid<T> {
+    type Item = ((usize, usize), &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Iterator over the rows of a [`Grid`].
+pub struct Rows<'a, T> {
+    inner: slice::Chunks<'a, T>,
+}
+
+impl<'a, T> Iterator for Rows<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Iterator over the cells of a column of a [`Grid`].
+pub struct Column<'a, T> {
+    inner: iter::StepBy<slice::Iter<'a, T>>,
+}
+
+impl<'a, T> Iterator for Column<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Iterator over the in-bounds neighbor positions of a cell.
+pub struct Neighbors {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    offsets: slice::Iter<'static, (isize, isize)>,
+}
+
+impl Neighbors {
+    fn new(
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        offsets: &'static [(isize, isize)],
+    ) -> Self {
+        Neighbors {
+            x,
+            y,
+            width,
+            height,
+            offsets: offsets.iter(),
+        }
+    }
+}
+
+impl Iterator for Neighbors {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for &(dx, dy) in self.offsets.by_ref() {
+            let (Some(x), Some(y)) = (self.x.checked_add_signed(dx), self.y.checked_add_signed(dy))
+            else {
+                continue;
+            };
+            if x < self.width && y < self.height {
+                return Some((x, y));
+            }
+        }
+        None
+    }
+}
+
+/// A borrowed rectangular region of a [`Grid`].
+/// Positions are relative to the top-left corner of the view.
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a, T> GridView<'a, T> {
+    /// Number of columns in the view.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of rows in the view.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get a reference to the cell at `(x, y)` of the view.
+    pub fn get(&self, x: usize, y: usize) -> Option<&'a T> {
+        if x < self.width && y < self.height {
+            self.grid.get(self.x + x, self.y + y)
+        } else {
+            None
+        }
+    }
+
+    /// The cells of row `y` of the view.
+    /// Panics if `y >= height`.
+    pub fn row(&self, y: usize) -> &'a [T] {
+        let height = self.height;
+        assert!(
+            y < height,
+            "row index (is {y}) should be < height (is {height})"
+        );
+        &self.grid.row(self.y + y)[self.x..self.x + self.width]
+    }
+
+    /// Iterate over the rows of the view.
+    pub fn rows(&self) -> impl Iterator<Item = &'a [T]> + '_ {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    /// Copy the view into a new grid.
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.width, self.height, |x, y| {
+            self.get(x, y).unwrap().clone()
+        })
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for GridView<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.rows()).finish()
+    }
+}
+
+/// A grid serializes as its size and its cells in row-major order.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Grid<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Grid", 3)?;
+        state.serialize_field("width", &self.width)?;
+        state.serialize_field("height", &self.height)?;
+        state.serialize_field("cells", &self.data)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Grid<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "Grid")]
+        struct Repr<T> {
+            width: usize,
+            height: usize,
+            cells: Vec<T>,
+        }
+
+        let Repr {
+            width,
+            height,
+            cells,
+        } = Repr::deserialize(deserializer)?;
+        if Some(cells.len()) != width.checked_mul(height) {
+            return Err(serde::de::Error::custom(format_args!(
+                "{} cells for a {width} by {height} grid",
+                cells.len()
+            )));
+        }
+        Ok(Grid::from_vec(width, height, cells))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Grid<T> {
+    /// Each side is at most 32 cells.
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let width = u.int_in_range(0..=32)?;
+        let height = u.int_in_range(0..=32)?;
+        let cells = iter::repeat_with(|| u.arbitrary())
+            .take(width * height)
+            .collect::<arbitrary::Result<_>>()?;
+        Ok(Grid::from_vec(width, height, cells))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for Grid<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static,
+    T::Parameters: Clone,
+{
+    type Parameters = T::Parameters;
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    /// Each side is at most 16 cells.
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        (0..=16usize, 0..=16usize)
+            .prop_flat_map(move |(width, height)| {
+                let cells = proptest::arbitrary::any_with::<T>(args.clone());
+                proptest::collection::vec(cells, width * height)
+                    .prop_map(move |cells| Grid::from_vec(width, height, cells))
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get() {
+        let mut grid = Grid::from_vec(3, 2, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(grid.get(0, 0), Some(&1));
+        assert_eq!(grid.get(2, 1), Some(&6));
+        assert_eq!(grid.get(3, 1), None);
+        assert_eq!(grid.get(0, 2), None);
+        *grid.get_mut(1, 0).unwrap() = 20;
+        assert_eq!(grid[(1, 0)], 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "data length (is 5) should be width * height (is 3 * 2)")]
+    fn from_vec_wrong_len() {
+        Grid::from_vec(3, 2, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = Grid::from_fn(3, 2, |x, y| y * 3 + x);
+        let rows: Vec<_> = grid.rows().collect();
+        assert_eq!(rows, vec![&[0, 1, 2][..], &[3, 4, 5][..]]);
+        let columns: Vec<Vec<_>> = grid.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(columns, vec![vec![0, 3], vec![1, 4], vec![2, 5]]);
+        assert_eq!(grid.iter().nth(4), Some(((1, 1), &4)));
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = Grid::new(3, 3, ());
+        let mut n: Vec<_> = grid.neighbors4(1, 1).collect();
+        n.sort();
+        assert_eq!(n, vec![(0, 1), (1, 0), (1, 2), (2, 1)]);
+        assert_eq!(grid.neighbors8(1, 1).count(), 8);
+        assert_eq!(grid.neighbors8(0, 0).count(), 3);
+        assert_eq!(grid.neighbors4(2, 2).count(), 2);
+    }
+
+    #[test]
+    fn transpose() {
+        let grid = Grid::from_vec(3, 2, vec![1, 2, 3, 4, 5, 6]);
+        let t = grid.transpose();
+        assert_eq!(t, Grid::from_vec(2, 3, vec![1, 4, 2, 5, 3, 6]));
+        assert_eq!(t.transpose(), grid);
+    }
+
+    #[test]
+    fn view() {
+        let grid = Grid::from_fn(4, 4, |x, y| y * 4 + x);
+        let view = grid.view(1, 1, 2, 3);
+        assert_eq!(view.get(0, 0), Some(&5));
+        assert_eq!(view.get(2, 0), None);
+        assert_eq!(view.row(2), &[13, 14]);
+        assert_eq!(
+            view.to_grid(),
+            Grid::from_vec(2, 3, vec![5, 6, 9, 10, 13, 14])
+        );
+    }
+
+    #[test]
+    fn game_of_life_blinker() {
+        let mut grid = Grid::new(5, 5, false);
+        for x in 1..4 {
+            grid[(x, 2)] = true;
+        }
+        let next = Grid::from_fn(5, 5, |x, y| {
+            let alive = grid
+                .neighbors8(x, y)
+                .filter(|&(nx, ny)| grid[(nx, ny)])
+                .count();
+            matches!((grid[(x, y)], alive), (true, 2) | (_, 3))
+        });
+        for y in 1..4 {
+            assert!(next[(2, y)]);
+        }
+        assert!(!next[(1, 2)]);
+        assert!(!next[(3, 2)]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let grid = Grid::from_vec(3, 2, vec![1, 2, 3, 4, 5, 6]);
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(json, r#"{"width":3,"height":2,"cells":[1,2,3,4,5,6]}"#);
+        assert_eq!(serde_json::from_str::<Grid<i32>>(&json).unwrap(), grid);
+        let json = r#"{"width":3,"height":2,"cells":[1,2,3]}"#;
+        assert!(serde_json::from_str::<Grid<i32>>(json).is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes = [3, 2, 1, 2, 3, 4, 5, 6];
+        let grid = Grid::<u8>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(2, 1), Some(&6));
+    }
+}
@@ -0,0 +1,627 @@
+use alloc::sync::Arc;
+use alloc::{vec, vec::Vec};
+use core::borrow::Borrow;
+use core::hash::{Hash, Hasher};
+use core::{fmt, slice};
+
+use crate::compat::DefaultHasher;
+
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+fn hash<Q: Hash + ?Sized>(key: &Q) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+enum Node<K, V> {
+    // `bitmap` has a bit set for every occupied slot, `children` holds the
+    // occupied slots in order.
+    Branch {
+        bitmap: u32,
+        children: Vec<Arc<Node<K, V>>>,
+    },
+    Leaf {
+        hash: u64,
+        key: K,
+        value: V,
+    },
+    // Entries whose keys have exactly the same hash.
+    Collision {
+        hash: u64,
+        entries: Vec<(K, V)>,
+    },
+}
+
+impl<K, V> Node<K, V> {
+    // Hash of a leaf or collision node.
+    fn hash(&self) -> u64 {
+        match self {
+            Node::Leaf { hash, .. } | Node::Collision { hash, .. } => *hash,
+            Node::Branch { .. } => unreachable!("branches have no single hash"),
+        }
+    }
+
+    fn is_branch(&self) -> bool {
+        matches!(self, Node::Branch { .. })
+    }
+}
+
+// Slot of `hash` in a branch at depth `shift` and the bit for that slot.
+fn slot(hash: u64, shift: u32) -> (usize, u32) {
+    let index = ((hash >> shift) & MASK) as u32;
+    (index as usize, 1 << index)
+}
+
+// Position of the child for `bit` in a branch's children.
+fn position(bitmap: u32, bit: u32) -> usize {
+    (bitmap & (bit - 1)).count_ones() as usize
+}
+
+// Build the smallest subtree at depth `shift` that holds two nodes with different hashes.
+fn join<K, V>(shift: u32, a: Arc<Node<K, V>>, b: Arc<Node<K, V>>) -> Arc<Node<K, V>> {
+    let (ia, bit_a) = slot(a.hash(), shift);
+    let (ib, bit_b) = slot(b.hash(), shift);
+    let (bitmap, children) = if ia == ib {
+        (bit_a, vec![join(shift + BITS, a, b)])
+    } else if ia < ib {
+        (bit_a | bit_b, vec![a, b])
+    } else {
+        (bit_a | bit_b, vec![b, a])
+    };
+    Arc::new(Node::Branch { bitmap, children })
+}
+
+/// An immutable hash map implemented as a hash array mapped trie (HAMT).
+///
+/// Each level of the trie consumes 5 bits of a key's hash and stores only its
+/// occupied slots, found through a 32-bit bitmap. [`HamtMap::insert`] and
+/// [`HamtMap::remove`] return a new map that shares every node except the
+/// O(log n) path to the changed key, so old versions stay valid and cloning is
+/// O(1). Nodes are reference counted with [`Arc`], so maps can be shared across
+/// threads without locks.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::hamt::HamtMap;
+///
+/// let v1 = HamtMap::new().insert("a", 1).insert("b", 2);
+/// let v2 = v1.insert("a", 10).remove("b");
+///
+/// assert_eq!(v1.get("a"), Some(&1));
+/// assert_eq!(v1.len(), 2);
+/// assert_eq!(v2.get("a"), Some(&10));
+/// assert_eq!(v2.get("b"), None);
+/// ```
+pub struct HamtMap<K, V> {
+    root: Option<Arc<Node<K, V>>>,
+    len: usize,
+}
+
+impl<K, V> Clone for HamtMap<K, V> {
+    fn clone(&self) -> Self {
+        HamtMap {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V> Default for HamtMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> HamtMap<K, V> {
+    /// Create a new, empty map.
+    pub const fn new() -> Self {
+        HamtMap { root: None, len: 0 }
+    }
+
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over the entries of the map, in no particular order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            stack: vec![self.root.as_slice().iter()],
+            collision: [].iter(),
+            remaining: self.len,
+        }
+    }
+}
+
+impl<K, V> HamtMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Get a reference to the value of a key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = hash(key);
+        let mut node = self.root.as_ref()?;
+        let mut shift = 0;
+        loop {
+            match &**node {
+                Node::Branch { bitmap, children } => {
+                    let (_, bit) = slot(hash, shift);
+                    if bitmap & bit == 0 {
+                        return None;
+                    }
+                    node = &children[position(*bitmap, bit)];
+                    shift += BITS;
+                }
+                Node::Leaf {
+                    hash: h,
+                    key: k,
+                    value,
+                } => {
+                    return (*h == hash && k.borrow() == key).then_some(value);
+                }
+                Node::Collision { hash: h, entries } => {
+                    if *h != hash {
+                        return None;
+                    }
+                    return entries
+                        .iter()
+                        .find(|(k, _)| k.borrow() == key)
+                        .map(|(_, v)| v);
+                }
+            }
+        }
+    }
+
+    /// Return `true` if the map contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+}
+
+impl<K, V> HamtMap<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Return a new map with `key` set to `value`.
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let hash = hash(&key);
+        let (root, added) = match &self.root {
+            None => (Arc::new(Node::Leaf { hash, key, value }), true),
+            Some(root) => Self::insert_node(root, 0, hash, key, value),
+        };
+        HamtMap {
+            root: Some(root),
+            len: self.len + added as usize,
+        }
+    }
+
+    // Return the new version of `node` and whether a new key was added.
+    fn insert_node(
+        node: &Arc<Node<K, V>>,
+        shift: u32,
+        hash: u64,
+        key: K,
+        value: V,
+    ) -> (Arc<Node<K, V>>, bool) {
+        match &**node {
+            Node::Branch { bitmap, children } => {
+                let (_, bit) = slot(hash, shift);
+                let pos = position(*bitmap, bit);
+                let mut children = children.clone();
+                let added = if bitmap & bit == 0 {
+                    children.insert(pos, Arc::new(Node::Leaf { hash, key, value }));
+                    true
+                } else {
+                    let (child, added) =
+                        Self::insert_node(&children[pos], shift + BITS, hash, key, value);
+                    children[pos] = child;
+                    added
+                };
+                let bitmap = bitmap | bit;
+                (Arc::new(Node::Branch { bitmap, children }), added)
+            }
+            Node::Leaf {
+                hash: h, key: k, ..
+            } if *h == hash && *k == key => (Arc::new(Node::Leaf { hash, key, value }), false),
+            Node::Leaf {
+                hash: h,
+                key: k,
+                value: v,
+            } if *h == hash => {
+                let entries = vec![(k.clone(), v.clone()), (key, value)];
+                (Arc::new(Node::Collision { hash, entries }), true)
+            }
+            Node::Collision { hash: h, entries } if *h == hash => {
+                let mut entries = entries.clone();
+                let added = match entries.iter_mut().find(|(k, _)| *k == key) {
+                    Some(entry) => {
+                        entry.1 = value;
+                        false
+                    }
+                    None => {
+                        entries.push((key, value));
+                        true
+                    }
+                };
+                (Arc::new(Node::Collision { hash, entries }), added)
+            }
+            // A leaf or collision node with a different hash: split them apart.
+            _ => {
+                let leaf = Arc::new(Node::Leaf { hash, key, value });
+                (join(shift, Arc::clone(node), leaf), true)
+            }
+        }
+    }
+
+    /// Return a new map without `key`.
+    /// If the key is absent, the new map shares everything with this one.
+    pub fn remove<Q>(&self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(root) = &self.root else {
+            return self.clone();
+        };
+        match Self::remove_node(root, 0, hash(key), key) {
+            None => self.clone(),
+            Some(root) => HamtMap {
+                root,
+                len: self.len - 1,
+            },
+        }
+    }
+
+    // Return `None` if the key is absent, otherwise the new version of `node`,
+    // which is `None` if it became empty.
+    fn remove_node<Q>(
+        node: &Arc<Node<K, V>>,
+        shift: u32,
+        hash: u64,
+        key: &Q,
+    ) -> Option<Option<Arc<Node<K, V>>>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match &**node {
+            Node::Branch { bitmap, children } => {
+                let (_, bit) = slot(hash, shift);
+                if bitmap & bit == 0 {
+                    return None;
+                }
+                let pos = position(*bitmap, bit);
+                let child = Self::remove_node(&children[pos], shift + BITS, hash, key)?;
+                let mut children = children.clone();
+                let mut bitmap = *bitmap;
+                match child {
+                    Some(child) => children[pos] = child,
+                    None => {
+                        children.remove(pos);
+                        bitmap &= !bit;
+                    }
+                }
+                // A branch left with a single leaf or collision node is replaced by
+                // that node, so the trie stays as shallow as possible.
+                match children.as_slice() {
+                    [] => Some(None),
+                    [only] if !only.is_branch() => Some(Some(Arc::clone(only))),
+                    _ => Some(Some(Arc::new(Node::Branch { bitmap, children }))),
+                }
+            }
+            Node::Leaf {
+                hash: h, key: k, ..
+            } => (*h == hash && k.borrow() == key).then_some(None),
+            Node::Collision { hash: h, entries } => {
+                if *h != hash {
+                    return None;
+                }
+                let pos = entries.iter().position(|(k, _)| k.borrow() == key)?;
+                let mut entries = entries.clone();
+                entries.remove(pos);
+                if let [(k, v)] = entries.as_slice() {
+                    return Some(Some(Arc::new(Node::Leaf {
+                        hash,
+                        key: k.clone(),
+                        value: v.clone(),
+                    })));
+                }
+                Some(Some(Arc::new(Node::Collision { hash, entries })))
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for HamtMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> PartialEq for HamtMap<K, V>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K, V> Eq for HamtMap<K, V>
+where
+    K: Hash + Eq,
+    V: Eq,
+{
+}
+
+/// Iterator over the entries of a [`HamtMap`].
+pub struct Iter<'a, K, V> {
+    stack: Vec<slice::Iter<'a, Arc<Node<K, V>>>>,
+    collision: slice::Iter<'a, (K, V)>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((k, v)) = self.collision.next() {
+                self.remaining -= 1;
+                return Some((k, v));
+            }
+            let Some(node) = self.stack.last_mut()?.next() else {
+                self.stack.pop();
+                continue;
+            };
+            match &**node {
+                Node::Branch { children, .. } => self.stack.push(children.iter()),
+                Node::Leaf { key, value, .. } => {
+                    self.remaining -= 1;
+                    return Some((key, value));
+                }
+                Node::Collision { entries, .. } => self.collision = entries.iter(),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a HamtMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for HamtMap<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        for (k, v) in iter {
+            *self = self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for HamtMap<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = HamtMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for HamtMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for HamtMap<K, V>
+where
+    K: serde::Deserialize<'de> + Hash + Eq + Clone,
+    V: serde::Deserialize<'de> + Clone,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for HamtMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Hash + Eq + Clone,
+    V: arbitrary::Arbitrary<'a> + Clone,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for HamtMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Hash + Eq + Clone,
+    V: proptest::arbitrary::Arbitrary + 'static + Clone,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    // A key whose hash only depends on `group`, to force collisions.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Colliding {
+        group: u8,
+        id: u32,
+    }
+
+    impl Hash for Colliding {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.group.hash(state);
+        }
+    }
+
+    #[test]
+    fn insert() {
+        let mut map = HamtMap::new();
+        for i in 0..10_000 {
+            map = map.insert(i, i * 2);
+        }
+        assert_eq!(map.len(), 10_000);
+        for i in 0..10_000 {
+            assert_eq!(map.get(&i), Some(&(i * 2)));
+        }
+        assert_eq!(map.get(&10_000), None);
+        let map = map.insert(5, 0);
+        assert_eq!(map.len(), 10_000);
+        assert_eq!(map.get(&5), Some(&0));
+    }
+
+    #[test]
+    fn versions() {
+        let v1: HamtMap<_, _> = (0..100).map(|i| (i, i)).collect();
+        let v2 = v1.insert(0, -1).insert(100, 100);
+        let v3 = v2.remove(&50);
+        assert_eq!(v1.get(&0), Some(&0));
+        assert_eq!(v1.get(&100), None);
+        assert_eq!(v2.get(&0), Some(&-1));
+        assert_eq!(v2.len(), 101);
+        assert_eq!(v3.len(), 100);
+        assert_eq!(v2.get(&50), Some(&50));
+        assert_eq!(v3.get(&50), None);
+        assert_eq!(v1, v1.clone());
+        assert_ne!(v1, v2);
+    }
+
+    #[test]
+    fn remove() {
+        let mut map: HamtMap<_, _> = (0..1000).map(|i| (i, i)).collect();
+        for i in (0..1000).step_by(2) {
+            map = map.remove(&i);
+        }
+        assert_eq!(map.len(), 500);
+        assert_eq!(map.remove(&0).len(), 500);
+        for i in 0..1000 {
+            assert_eq!(map.contains_key(&i), i % 2 == 1);
+        }
+        for i in (1..1000).step_by(2) {
+            map = map.remove(&i);
+        }
+        assert!(map.is_empty());
+        assert!(map.root.is_none());
+    }
+
+    #[test]
+    fn collisions() {
+        let key = |group, id| Colliding { group, id };
+        let mut map = HamtMap::new();
+        for id in 0..5 {
+            map = map.insert(key(0, id), id);
+        }
+        map = map.insert(key(1, 0), 100);
+        map = map.insert(key(0, 2), 20);
+        assert_eq!(map.len(), 6);
+        assert_eq!(map.get(&key(0, 2)), Some(&20));
+        assert_eq!(map.get(&key(0, 7)), None);
+        for id in 0..4 {
+            map = map.remove(&key(0, id));
+        }
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&key(0, 4)), Some(&4));
+        assert_eq!(map.get(&key(1, 0)), Some(&100));
+        assert_eq!(map.iter().count(), 2);
+    }
+
+    #[test]
+    fn iter() {
+        let map: HamtMap<_, _> = (0..500).map(|i| (i, i * 3)).collect();
+        let iter = map.iter();
+        assert_eq!(iter.size_hint(), (500, Some(500)));
+        let mut pairs: Vec<_> = iter.map(|(k, v)| (*k, *v)).collect();
+        pairs.sort();
+        assert_eq!(pairs, (0..500).map(|i| (i, i * 3)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn share_across_threads() {
+        let map: HamtMap<_, _> = (0..100).map(|i| (i, i)).collect();
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let map = map.clone();
+                thread::spawn(move || {
+                    let map = map.insert(1000 + t, t);
+                    (0..100).all(|i| map.get(&i) == Some(&i)) && map.len() == 101
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+        assert_eq!(map.len(), 100);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: HamtMap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        let back: HamtMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        let mut entries: Vec<_> = back.iter().collect();
+        entries.sort();
+        assert_eq!(entries, [(&1, &10), (&2, &20)]);
+    }
+}
@@ -1,11 +1,24 @@
-use std::borrow::Borrow;
-use std::hash::{DefaultHasher, Hash, Hasher};
-use std::mem;
+use alloc::alloc::{Allocator, Global};
+use alloc::collections::{TryReserveError, TryReserveErrorKind};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{Hash, Hasher};
+use core::iter::{Flatten, FusedIterator};
+use core::marker::PhantomData;
+use core::str::FromStr;
+use core::{fmt, mem, slice};
+
+use crate::compat::DefaultHasher;
+use crate::parse::{self, Format, Parse, ParseError, Parser};
+use crate::raw_vec::handle_error;
 
 const INITIAL_BUCKETS: usize = 1;
 
-pub struct HashMap<K, V> {
-    buckets: Vec<Vec<(K, V)>>,
+// The buckets are allocated with `A`, through the outer `Vec`'s copy of it.
+#[derive(Clone)]
+pub struct HashMap<K, V, A: Allocator = Global> {
+    buckets: Vec<Vec<(K, V), A>, A>,
     items: usize,
 }
 impl<K, V> Default for HashMap<K, V> {
@@ -16,24 +29,290 @@ impl<K, V> Default for HashMap<K, V> {
 
 impl<K, V> HashMap<K, V> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    // Room for `capacity` entries before the first resize.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
+}
+
+impl<K, V, A: Allocator + Clone> HashMap<K, V, A> {
+    pub fn new_in(alloc: A) -> Self {
         Self {
-            buckets: Vec::new(),
+            buckets: Vec::new_in(alloc),
             items: 0,
         }
     }
+
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        match Self::try_with_capacity_in(capacity, alloc) {
+            Ok(map) => map,
+            Err(err) => handle_error(err),
+        }
+    }
+
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+        let mut map = Self::new_in(alloc.clone());
+        if capacity > 0 {
+            let size = bucket_count(capacity, 0)?;
+            map.buckets.try_reserve_exact(size)?;
+            map.buckets
+                .extend((0..size).map(|_| Vec::new_in(alloc.clone())));
+        }
+        Ok(map)
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.buckets.allocator()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+        self.items = 0;
+    }
+
+    // Entries come in bucket order. It is arbitrary and changes whenever the
+    // map grows, but it is the same for every iteration in between.
+    pub fn iter(&self) -> Iter<'_, K, V, A> {
+        Iter {
+            inner: self.buckets.iter().flatten(),
+            remaining: self.items,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V, A> {
+        IterMut {
+            inner: self.buckets.iter_mut().flatten(),
+            remaining: self.items,
+        }
+    }
+
+    // The map is emptied right away, so it is empty even if the iterator is leaked.
+    pub fn drain(&mut self) -> Drain<'_, K, V, A> {
+        let empty = Vec::new_in(self.allocator().clone());
+        Drain {
+            remaining: mem::take(&mut self.items),
+            inner: mem::replace(&mut self.buckets, empty).into_iter().flatten(),
+            marker: PhantomData,
+        }
+    }
+
+    // Each entry is removed as it is yielded. The ones not reached when the
+    // iterator is dropped stay.
+    pub fn drain_filter<F>(&mut self, filter: F) -> DrainFilter<'_, K, V, F, A>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        DrainFilter {
+            map: self,
+            bucket_index: 0,
+            index: 0,
+            filter,
+        }
+    }
+
+    pub fn extract_if<F>(&mut self, filter: F) -> DrainFilter<'_, K, V, F, A>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.drain_filter(filter)
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        for bucket in &mut self.buckets {
+            bucket.retain_mut(|(k, v)| f(k, v));
+        }
+        self.items = self.buckets.iter().map(Vec::len).sum();
+    }
+}
+
+pub struct Iter<'a, K, V, A: Allocator = Global> {
+    inner: Flatten<slice::Iter<'a, Vec<(K, V), A>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V, A: Allocator> Iterator for Iter<'a, K, V, A> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, v) = self.inner.next()?;
+        self.remaining -= 1;
+        Some((k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, A: Allocator> ExactSizeIterator for Iter<'_, K, V, A> {}
+
+impl<K, V, A: Allocator> FusedIterator for Iter<'_, K, V, A> {}
+
+pub struct IterMut<'a, K, V, A: Allocator = Global> {
+    inner: Flatten<slice::IterMut<'a, Vec<(K, V), A>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V, A: Allocator> Iterator for IterMut<'a, K, V, A> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, v) = self.inner.next()?;
+        self.remaining -= 1;
+        Some((k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
-pub struct OccupiedEntry<'a, K, V> {
-    element: &'a mut (K, V),
+impl<K, V, A: Allocator> ExactSizeIterator for IterMut<'_, K, V, A> {}
+
+impl<K, V, A: Allocator> FusedIterator for IterMut<'_, K, V, A> {}
+
+// The buckets of a map, moved out of it.
+type IntoBuckets<K, V, A> = vec::IntoIter<Vec<(K, V), A>, A>;
+
+pub struct Drain<'a, K, V, A: Allocator = Global> {
+    inner: Flatten<IntoBuckets<K, V, A>>,
+    remaining: usize,
+    marker: PhantomData<&'a mut HashMap<K, V, A>>,
 }
 
-pub struct VacantEntry<'a, K, V> {
+impl<K, V, A: Allocator> Iterator for Drain<'_, K, V, A> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.inner.next()?;
+        self.remaining -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, A: Allocator> ExactSizeIterator for Drain<'_, K, V, A> {}
+
+impl<K, V, A: Allocator> FusedIterator for Drain<'_, K, V, A> {}
+
+pub struct DrainFilter<'a, K, V, F, A: Allocator = Global> {
+    map: &'a mut HashMap<K, V, A>,
+    // Next entry to look at.
+    bucket_index: usize,
+    index: usize,
+    filter: F,
+}
+
+impl<K, V, F, A> Iterator for DrainFilter<'_, K, V, F, A>
+where
+    F: FnMut(&K, &mut V) -> bool,
+    A: Allocator,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(bucket) = self.map.buckets.get_mut(self.bucket_index) {
+            while let Some((k, v)) = bucket.get_mut(self.index) {
+                if (self.filter)(k, v) {
+                    self.map.items -= 1;
+                    // Not `swap_remove`, so entries are looked at in the order
+                    // the map iterates in.
+                    return Some(bucket.remove(self.index));
+                }
+                self.index += 1;
+            }
+            self.bucket_index += 1;
+            self.index = 0;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.map.items))
+    }
+}
+
+pub struct OccupiedEntry<'a, K, V, A: Allocator = Global> {
+    map: &'a mut HashMap<K, V, A>,
+    bucket_index: usize,
+    index: usize,
+}
+
+impl<'a, K, V, A: Allocator> OccupiedEntry<'a, K, V, A> {
+    fn element(&self) -> &(K, V) {
+        &self.map.buckets[self.bucket_index][self.index]
+    }
+
+    fn element_mut(&mut self) -> &mut (K, V) {
+        &mut self.map.buckets[self.bucket_index][self.index]
+    }
+
+    pub fn key(&self) -> &K {
+        &self.element().0
+    }
+
+    pub fn get(&self) -> &V {
+        &self.element().1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.element_mut().1
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.buckets[self.bucket_index][self.index].1
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.items -= 1;
+        self.map.buckets[self.bucket_index].swap_remove(self.index)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+pub struct VacantEntry<'a, K, V, A: Allocator = Global> {
     key: K,
-    map: &'a mut HashMap<K, V>,
+    map: &'a mut HashMap<K, V, A>,
     bucket_index: usize,
 }
 
-impl<'a, K, V> VacantEntry<'a, K, V> {
+impl<'a, K, V, A: Allocator> VacantEntry<'a, K, V, A> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
     pub fn insert(self, value: V) -> &'a mut V {
         self.map.buckets[self.bucket_index].push((self.key, value));
         self.map.items += 1;
@@ -41,15 +320,22 @@ impl<'a, K, V> VacantEntry<'a, K, V> {
     }
 }
 
-pub enum Entry<'a, K, V> {
-    Occupied(OccupiedEntry<'a, K, V>),
-    Vacant(VacantEntry<'a, K, V>),
+pub enum Entry<'a, K, V, A: Allocator = Global> {
+    Occupied(OccupiedEntry<'a, K, V, A>),
+    Vacant(VacantEntry<'a, K, V, A>),
 }
 
-impl<'a, K, V> Entry<'a, K, V> {
+impl<'a, K, V, A: Allocator> Entry<'a, K, V, A> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
     pub fn or_insert(self, value: V) -> &'a mut V {
         match self {
-            Entry::Occupied(e) => &mut e.element.1,
+            Entry::Occupied(e) => e.into_mut(),
             Entry::Vacant(e) => e.insert(value),
         }
     }
@@ -59,34 +345,56 @@ impl<'a, K, V> Entry<'a, K, V> {
         F: FnOnce() -> V,
     {
         match self {
-            Entry::Occupied(e) => &mut e.element.1,
+            Entry::Occupied(e) => e.into_mut(),
             Entry::Vacant(e) => e.insert(maker()),
         }
     }
 
+    pub fn or_insert_with_key<F>(self, maker: F) -> &'a mut V
+    where
+        F: FnOnce(&K) -> V,
+    {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let value = maker(&e.key);
+                e.insert(value)
+            }
+        }
+    }
+
     pub fn or_default(self) -> &'a mut V
     where
         V: Default,
     {
         match self {
-            Entry::Occupied(e) => &mut e.element.1,
+            Entry::Occupied(e) => e.into_mut(),
             Entry::Vacant(e) => e.insert(V::default()),
         }
     }
+
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
 }
 
-impl<K, V> HashMap<K, V>
+impl<K, V, A> HashMap<K, V, A>
 where
     K: Hash + Eq,
+    A: Allocator + Clone,
 {
     fn key<Q>(&self, key: &Q) -> usize
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        (hasher.finish() % self.buckets.len() as u64) as usize
+        bucket_index(key, self.buckets.len())
     }
 
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
@@ -97,7 +405,7 @@ where
         let bucket = &mut self.buckets[bucket_index];
         for (k, v) in &mut bucket.iter_mut() {
             if k == &key {
-                return Some(std::mem::replace(v, value));
+                return Some(core::mem::replace(v, value));
             }
         }
         self.items += 1;
@@ -110,6 +418,9 @@ where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
+        if self.buckets.is_empty() {
+            return None;
+        }
         let bucket_index = self.key(key);
         let bucket = &self.buckets[bucket_index];
         for (k, v) in bucket {
@@ -120,30 +431,51 @@ where
         None
     }
 
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let bucket_index = self.key(key);
+        let bucket = &mut self.buckets[bucket_index];
+        for (k, v) in bucket {
+            if (*k).borrow() == key {
+                return Some(v);
+            }
+        }
+        None
+    }
+
     pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
         let bucket_index = self.key(key);
         let bucket = &mut self.buckets[bucket_index];
         if let Some(index) = bucket.iter().position(|(k, _)| k.borrow() == key) {
             self.items -= 1;
             // swap_remove is O(1) because the order of the elements in the bucket doesn't matter.
-            Some(bucket.swap_remove(index).1)
+            Some(bucket.swap_remove(index))
         } else {
             None
         }
     }
 
-    pub fn len(&self) -> usize {
-        self.items
-    }
-
-    pub fn is_empty(&self) -> bool {
-        self.items == 0
-    }
-
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
         K: Borrow<Q>,
@@ -152,7 +484,7 @@ where
         self.get(key).is_some()
     }
 
-    pub fn entry(&mut self, key: K) -> Entry<K, V> {
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, A> {
         if self.buckets.is_empty() || self.items > 3 * self.buckets.len() / 4 {
             self.resize();
         }
@@ -163,7 +495,9 @@ where
             .position(|(k, _)| k == &key)
         {
             Some(index) => Entry::Occupied(OccupiedEntry {
-                element: &mut self.buckets[bucket_index][index],
+                map: self,
+                bucket_index,
+                index,
             }),
             None => Entry::Vacant(VacantEntry {
                 key,
@@ -173,46 +507,149 @@ where
         }
     }
 
+    // Grow the table up front so `additional` more entries go in without a
+    // resize on the way.
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(err) = self.try_reserve(additional) {
+            handle_error(err);
+        }
+    }
+
+    // Like `reserve`, but the map is left as it was if the memory can't be
+    // allocated.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let needed = self
+            .items
+            .checked_add(additional)
+            .ok_or(TryReserveErrorKind::CapacityOverflow)?;
+        let target_size = bucket_count(needed, self.buckets.len())?;
+        if target_size > self.buckets.len() {
+            self.try_rehash(target_size)?;
+        }
+        Ok(())
+    }
+
     fn resize(&mut self) {
         let target_size = match self.buckets.len() {
             0 => INITIAL_BUCKETS,
             n => n * 2,
         };
-        let mut new_buckets = Vec::with_capacity(target_size);
-        new_buckets.extend((0..target_size).map(|_| Vec::new()));
+        if let Err(err) = self.try_rehash(target_size) {
+            handle_error(err);
+        }
+    }
+
+    fn try_rehash(&mut self, target_size: usize) -> Result<(), TryReserveError> {
+        // Size every new bucket before moving anything, so a failed allocation
+        // leaves the map untouched.
+        let alloc = self.allocator();
+        let mut lens = Vec::new_in(alloc.clone());
+        lens.try_reserve_exact(target_size)?;
+        lens.resize(target_size, 0);
+        for (key, _) in self.iter() {
+            lens[bucket_index(key, target_size)] += 1;
+        }
+        let mut new_buckets = Vec::new_in(alloc.clone());
+        new_buckets.try_reserve_exact(target_size)?;
+        for len in lens {
+            let mut bucket = Vec::new_in(alloc.clone());
+            bucket.try_reserve_exact(len)?;
+            new_buckets.push(bucket);
+        }
+
         for (key, value) in self.buckets.iter_mut().flat_map(|bucket| bucket.drain(..)) {
-            let mut hasher = DefaultHasher::new();
-            key.hash(&mut hasher);
-            let bucket_index = (hasher.finish() % new_buckets.len() as u64) as usize;
-            new_buckets[bucket_index].push((key, value));
+            new_buckets[bucket_index(&key, target_size)].push((key, value));
         }
+        self.buckets = new_buckets;
+        Ok(())
+    }
+}
 
-        let _ = mem::replace(&mut self.buckets, new_buckets);
+fn bucket_index<Q: Hash + ?Sized>(key: &Q, buckets: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % buckets as u64) as usize
+}
+
+// The smallest table of at least `current` buckets, doubling from there, that
+// holds `needed` entries without going over the load factor.
+fn bucket_count(needed: usize, current: usize) -> Result<usize, TryReserveError> {
+    let mut size = current.max(INITIAL_BUCKETS);
+    loop {
+        let fits = size
+            .checked_mul(3)
+            .ok_or(TryReserveErrorKind::CapacityOverflow)?
+            / 4;
+        if needed <= fits {
+            return Ok(size);
+        }
+        size = size
+            .checked_mul(2)
+            .ok_or(TryReserveErrorKind::CapacityOverflow)?;
     }
 }
-impl<'a, K, V> IntoIterator for &'a HashMap<K, V> {
+impl<'a, K, V, A: Allocator + Clone> IntoIterator for &'a HashMap<K, V, A> {
     type Item = (&'a K, &'a V);
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type IntoIter = Iter<'a, K, V, A>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.buckets
-            .iter()
-            .flat_map(|bucket| bucket.iter().map(|(k, v)| (k, v)))
-            .collect::<Vec<_>>()
-            .into_iter()
+        self.iter()
+    }
+}
+
+impl<'a, K, V, A: Allocator + Clone> IntoIterator for &'a mut HashMap<K, V, A> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
     }
 }
 
-impl<K, V> IntoIterator for HashMap<K, V> {
+impl<K, V, A: Allocator> IntoIterator for HashMap<K, V, A> {
     type Item = (K, V);
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type IntoIter = Flatten<IntoBuckets<K, V, A>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.buckets
-            .into_iter()
-            .flat_map(|bucket| bucket.into_iter())
-            .collect::<Vec<_>>()
-            .into_iter()
+        self.buckets.into_iter().flatten()
+    }
+}
+
+impl<K, V, A> Extend<(K, V)> for HashMap<K, V, A>
+where
+    K: Hash + Eq,
+    A: Allocator + Clone,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Hash + Eq, V, S> From<std::collections::HashMap<K, V, S>> for HashMap<K, V> {
+    fn from(map: std::collections::HashMap<K, V, S>) -> Self {
+        let mut new = HashMap::with_capacity(map.len());
+        new.extend(map);
+        new
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S, A> From<HashMap<K, V, A>> for std::collections::HashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: core::hash::BuildHasher + Default,
+    A: Allocator,
+{
+    fn from(map: HashMap<K, V, A>) -> Self {
+        map.into_iter().collect()
     }
 }
 
@@ -225,15 +662,132 @@ where
         T: IntoIterator<Item = (K, V)>,
     {
         let mut map = HashMap::new();
-        for (k, v) in iter {
-            map.insert(k, v);
-        }
+        map.extend(iter);
         map
     }
 }
 
+impl<K: fmt::Debug, V: fmt::Debug, A: Allocator + Clone> fmt::Debug for HashMap<K, V, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, A> fmt::Display for HashMap<K, V, A>
+where
+    K: Format,
+    V: Format,
+    A: Allocator + Clone,
+{
+    /// Write the entries, in no particular order, as `{k: v, k: v}`, which
+    /// [`FromStr`] reads back.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        parse::fmt_map(f, self.iter())
+    }
+}
+
+impl<K, V, A> Format for HashMap<K, V, A>
+where
+    K: Format,
+    V: Format,
+    A: Allocator + Clone,
+{
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl<K: Hash + Eq + Parse, V: Parse> FromStr for HashMap<K, V> {
+    type Err = ParseError;
+
+    /// Parse a map like `{a: 1, b: 2}`, in the format of [`Parse`]. A key
+    /// that appears twice keeps its last value.
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        parse::from_str(s)
+    }
+}
+
+impl<K: Hash + Eq + Parse, V: Parse> Parse for HashMap<K, V> {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        let mut map = HashMap::new();
+        map.extend(parser.map()?);
+        Ok(map)
+    }
+}
+
+// Equal maps hold the same entries, whatever order their buckets keep them in.
+impl<K, V, A> PartialEq for HashMap<K, V, A>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+    A: Allocator + Clone,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, A: Allocator + Clone> Eq for HashMap<K, V, A> {}
+
+#[cfg(feature = "serde")]
+impl<K, V, A> serde::Serialize for HashMap<K, V, A>
+where
+    K: serde::Serialize,
+    V: serde::Serialize,
+    A: Allocator + Clone,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for HashMap<K, V>
+where
+    K: serde::Deserialize<'de> + Hash + Eq + Clone,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for HashMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Hash + Eq + Clone,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for HashMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Hash + Eq + Clone,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::string::{String, ToString};
+
     use super::*;
 
     #[test]
@@ -249,6 +803,18 @@ mod tests {
         assert_eq!(map.insert("foo", 42), None);
         assert_eq!(map.get("foo"), Some(&42));
         assert_eq!(map.get("bar"), None);
+        *map.get_mut("foo").unwrap() += 1;
+        assert_eq!(map.get("foo"), Some(&43));
+        assert_eq!(map.get_mut("bar"), None);
+    }
+
+    #[test]
+    fn empty() {
+        let mut map: HashMap<&str, i32> = HashMap::new();
+        assert_eq!(map.get("foo"), None);
+        assert_eq!(map.get_mut("foo"), None);
+        assert_eq!(map.remove("foo"), None);
+        assert!(!map.contains_key("foo"));
     }
 
     #[test]
@@ -289,6 +855,74 @@ mod tests {
         assert_eq!(map.entry("bar").or_insert(44), &44);
         assert_eq!(map.entry("bar").or_insert_with(|| 45), &44);
         assert_eq!(map.entry("baz").or_default(), &0);
+
+        *map.entry("foo").and_modify(|v| *v += 1).or_insert(0) += 10;
+        assert_eq!(map.get("foo"), Some(&53));
+        assert_eq!(map.entry("qux").or_insert_with_key(|k| k.len()), &3);
+        match map.entry("foo") {
+            Entry::Occupied(mut e) => {
+                assert_eq!(e.key(), &"foo");
+                assert_eq!(e.insert(1), 53);
+                assert_eq!(e.remove(), 1);
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+        match map.entry("foo") {
+            Entry::Occupied(_) => unreachable!(),
+            Entry::Vacant(e) => assert_eq!(e.into_key(), "foo"),
+        }
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("foo"), None);
+    }
+
+    #[test]
+    fn drain() {
+        let mut map: HashMap<_, _> = (0..100).map(|i| (i, i.to_string())).collect();
+        let mut drain = map.drain();
+        assert_eq!(drain.len(), 100);
+        drain.next();
+        assert_eq!(drain.len(), 99);
+        // Dropping or leaking the iterator early still empties the map.
+        drop(drain);
+        assert!(map.is_empty());
+        assert_eq!(map.get(&1), None);
+        map.insert(1, "one".to_string());
+        std::mem::forget(map.drain());
+        assert!(map.is_empty());
+
+        let mut map: HashMap<_, _> = (0..100).map(|i| (i, i)).collect();
+        let mut evens: Vec<_> = map
+            .drain_filter(|k, v| {
+                *v += 1;
+                k % 2 == 0
+            })
+            .collect();
+        evens.sort();
+        assert_eq!(
+            evens,
+            (0..100).step_by(2).map(|i| (i, i + 1)).collect::<Vec<_>>()
+        );
+        assert_eq!(map.len(), 50);
+        assert!((1..100).step_by(2).all(|i| map.get(&i) == Some(&(i + 1))));
+
+        assert_eq!(map.drain_filter(|_, _| true).take(10).count(), 10);
+        assert_eq!(map.len(), 40);
+    }
+
+    #[test]
+    fn reserve() {
+        let mut map = HashMap::try_with_capacity(100).unwrap();
+        let buckets = map.buckets.len();
+        map.extend((0..100).map(|i| (i, i)));
+        assert_eq!(map.buckets.len(), buckets);
+
+        map.try_reserve(1000).unwrap();
+        assert!(map.buckets.len() * 3 / 4 >= 1100);
+        assert!(map.try_reserve(usize::MAX).is_err());
+        assert!(map.try_reserve(usize::MAX / 2).is_err());
+        assert_eq!(map.len(), 100);
+        assert!((0..100).all(|i| map.get(&i) == Some(&i)));
+        assert!(HashMap::<u8, u8>::try_with_capacity(usize::MAX).is_err());
     }
 
     #[test]
@@ -297,4 +931,41 @@ mod tests {
         assert_eq!(map.get("foo"), Some(&42));
         assert_eq!(map.get("bar"), Some(&43));
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn std_hash_map() {
+        let std_map: std::collections::HashMap<_, _> = (0..100).map(|i| (i, i * i)).collect();
+        let map = HashMap::from(std_map.clone());
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.get(&9), Some(&81));
+        assert_eq!(std::collections::HashMap::from(map), std_map);
+    }
+
+    #[test]
+    fn parse() {
+        let map: HashMap<String, i32> = r#"{"a": 1, "b": -2, "a": 3}"#.parse().unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a"), Some(&3));
+        assert!(map.to_string().parse::<HashMap<String, i32>>().unwrap() == map);
+        let single: HashMap<u8, crate::my_vec::MyVec<u8>> = "{1: [2, 3]}".parse().unwrap();
+        assert_eq!(single.to_string(), "{1: [2, 3]}");
+        assert_eq!(HashMap::<u8, u8>::new().to_string(), "{}");
+        assert!("{a 1}".parse::<HashMap<String, i32>>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: HashMap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        let back: HashMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert!(back == map);
+        assert_eq!(
+            serde_json::from_str::<HashMap<i32, i32>>(r#"{"1":1,"1":2}"#)
+                .unwrap()
+                .get(&1),
+            Some(&2)
+        );
+    }
 }
@@ -0,0 +1,871 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{Hash, Hasher};
+use core::str::FromStr;
+use core::{fmt, mem, slice};
+
+use crate::compat::DefaultHasher;
+use crate::parse::{self, Format, Parse, ParseError, Parser};
+
+const INITIAL_BUCKETS: usize = 8;
+
+fn hash<Q: Hash + ?Sized>(key: &Q) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A hash map that remembers insertion order.
+///
+/// Entries live in a dense `Vec` in the order they were inserted, and a hash
+/// table of buckets maps each key to its position in that `Vec`. Iteration is
+/// therefore deterministic and as fast as iterating a slice, and entries can also
+/// be accessed by position with [`IndexMap::get_index`].
+///
+/// Removing an entry either swaps the last entry into its place in O(1)
+/// ([`IndexMap::swap_remove`]) or shifts every later entry down in O(n) to keep
+/// the order intact ([`IndexMap::shift_remove`]).
+///
+/// # Examples
+///
+/// ```
+/// use my_std::index_map::IndexMap;
+///
+/// let mut map = IndexMap::new();
+/// map.insert("b", 2);
+/// map.insert("a", 1);
+/// map.insert("c", 3);
+/// assert_eq!(map.get_index(0), Some((&"b", &2)));
+/// assert_eq!(map.get_index_of("c"), Some(2));
+///
+/// map.shift_remove("b");
+/// let keys: Vec<_> = map.keys().collect();
+/// assert_eq!(keys, vec![&"a", &"c"]);
+/// ```
+#[derive(Clone)]
+pub struct IndexMap<K, V> {
+    entries: Vec<(K, V)>,
+    // Each bucket holds the positions in `entries` of the keys hashing to it.
+    buckets: Vec<Vec<usize>>,
+}
+
+impl<K, V> Default for IndexMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> IndexMap<K, V> {
+    /// Create a new, empty map.
+    pub const fn new() -> Self {
+        IndexMap {
+            entries: Vec::new(),
+            buckets: Vec::new(),
+        }
+    }
+
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.buckets.iter_mut().for_each(Vec::clear);
+    }
+
+    /// Get the entry at position `index`.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|(k, v)| (k, v))
+    }
+
+    /// Get the entry at position `index` with a mutable reference to the value.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.entries.get_mut(index).map(|(k, v)| (&*k, v))
+    }
+
+    /// The first entry in insertion order.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    /// The last entry in insertion order.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.len().checked_sub(1).and_then(|i| self.get_index(i))
+    }
+
+    /// Iterate over the entries in index order, which is insertion order
+    /// unless entries were moved by [`IndexMap::swap_remove`].
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Iterate over the entries in index order with mutable references to the
+    /// values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+
+    /// Iterate over the keys in order.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over the values in order.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.entries.iter().map(|(_, v)| v)
+    }
+}
+
+impl<K, V> IndexMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn bucket<Q: Hash + ?Sized>(&self, key: &Q) -> usize {
+        (hash(key) % self.buckets.len() as u64) as usize
+    }
+
+    /// Get the position of a key in the map.
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        self.buckets[self.bucket(key)]
+            .iter()
+            .copied()
+            .find(|&i| self.entries[i].0.borrow() == key)
+    }
+
+    /// Insert a key-value pair into the map.
+    /// If the key was already present its value is replaced, its position is kept
+    /// and the old value is returned. Otherwise the entry is appended.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+
+    /// Like [`IndexMap::insert`], but also returns the position of the entry.
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        if let Some(index) = self.get_index_of(&key) {
+            let old = mem::replace(&mut self.entries[index].1, value);
+            return (index, Some(old));
+        }
+        (self.push(key, value), None)
+    }
+
+    // Append a key known to be missing, returning its position.
+    fn push(&mut self, key: K, value: V) -> usize {
+        if self.buckets.is_empty() || self.entries.len() > 3 * self.buckets.len() / 4 {
+            self.resize();
+        }
+        let index = self.entries.len();
+        let bucket = self.bucket(&key);
+        self.buckets[bucket].push(index);
+        self.entries.push((key, value));
+        index
+    }
+
+    /// Get the entry of a key, to read, insert or remove its value after a
+    /// single lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::index_map::IndexMap;
+    ///
+    /// let mut counts = IndexMap::new();
+    /// for word in ["b", "a", "b"] {
+    ///     counts.entry(word).and_modify(|n| *n += 1).or_insert(1);
+    /// }
+    /// assert_eq!(counts.get_index(0), Some((&"b", &2)));
+    /// assert_eq!(counts.get_index(1), Some((&"a", &1)));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.get_index_of(&key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+
+    /// Get a reference to the value of a key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_index_of(key).map(|i| &self.entries[i].1)
+    }
+
+    /// Get a mutable reference to the value of a key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_index_of(key).map(|i| &mut self.entries[i].1)
+    }
+
+    /// Return `true` if the map contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_index_of(key).is_some()
+    }
+
+    /// Remove a key by swapping the last entry into its position. O(1), but
+    /// changes the order of the map.
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.get_index_of(key)?;
+        self.swap_remove_index(index).map(|(_, v)| v)
+    }
+
+    /// Remove the entry at position `index` by swapping the last entry into its place.
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        self.unlink(index);
+        let last = self.entries.len() - 1;
+        if index != last {
+            // Point the moved entry's bucket at its new position.
+            let bucket = self.bucket(&self.entries[last].0);
+            for i in &mut self.buckets[bucket] {
+                if *i == last {
+                    *i = index;
+                }
+            }
+        }
+        Some(self.entries.swap_remove(index))
+    }
+
+    /// Remove a key by shifting every later entry down. O(n), but keeps the
+    /// order of the remaining entries.
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.get_index_of(key)?;
+        self.shift_remove_index(index).map(|(_, v)| v)
+    }
+
+    /// Remove the entry at position `index`, shifting every later entry down.
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        self.unlink(index);
+        for i in self.buckets.iter_mut().flatten() {
+            if *i > index {
+                *i -= 1;
+            }
+        }
+        Some(self.entries.remove(index))
+    }
+
+    /// Remove and return the last entry.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let last = self.entries.len().checked_sub(1)?;
+        self.swap_remove_index(last)
+    }
+
+    /// Keep only the entries for which `f` returns `true`, visiting them in order
+    /// and keeping their order.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let len = self.entries.len();
+        self.entries.retain_mut(|(k, v)| f(k, v));
+        if self.entries.len() < len {
+            self.rehash(self.buckets.len());
+        }
+    }
+
+    /// Return an iterator that removes and yields, in order, every entry for
+    /// which `filter` returns `true`. The entries kept keep their order.
+    ///
+    /// Entries the iterator didn't get to before being dropped are kept. If it
+    /// is leaked, the map is left with only the entries kept until then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::index_map::IndexMap;
+    ///
+    /// let mut map: IndexMap<_, _> = [("b", 2), ("a", 1), ("c", 3)].into_iter().collect();
+    /// let odd: Vec<_> = map.extract_if(|_, v| *v % 2 == 1).collect();
+    /// assert_eq!(odd, [("a", 1), ("c", 3)]);
+    /// assert_eq!(map.get_index_of("b"), Some(0));
+    /// ```
+    pub fn extract_if<F>(&mut self, filter: F) -> ExtractIf<'_, K, V, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let rest = mem::take(&mut self.entries).into_iter();
+        self.entries.reserve(rest.len());
+        self.buckets.iter_mut().for_each(Vec::clear);
+        ExtractIf {
+            map: self,
+            rest,
+            filter,
+        }
+    }
+
+    // Remove position `index` from its bucket.
+    fn unlink(&mut self, index: usize) {
+        let bucket = self.bucket(&self.entries[index].0);
+        let bucket = &mut self.buckets[bucket];
+        let pos = bucket.iter().position(|&i| i == index).unwrap();
+        bucket.swap_remove(pos);
+    }
+
+    fn resize(&mut self) {
+        let target_size = match self.buckets.len() {
+            0 => INITIAL_BUCKETS,
+            n => n * 2,
+        };
+        self.rehash(target_size);
+    }
+
+    // Rebuild the buckets from the entries, with `target_size` of them.
+    fn rehash(&mut self, target_size: usize) {
+        let mut buckets = vec![Vec::new(); target_size];
+        for (i, (k, _)) in self.entries.iter().enumerate() {
+            buckets[(hash(k) % target_size as u64) as usize].push(i);
+        }
+        self.buckets = buckets;
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for IndexMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Format, V: Format> fmt::Display for IndexMap<K, V> {
+    /// Write the entries in index order as `{k: v, k: v}`, which [`FromStr`]
+    /// reads back.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        parse::fmt_map(f, self.iter())
+    }
+}
+
+impl<K: Format, V: Format> Format for IndexMap<K, V> {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl<K: Hash + Eq + Parse, V: Parse> FromStr for IndexMap<K, V> {
+    type Err = ParseError;
+
+    /// Parse a map like `{a: 1, b: 2}`, in the format of [`Parse`], keeping
+    /// the entries in the order written. A key that appears twice keeps its
+    /// first position and last value.
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        parse::from_str(s)
+    }
+}
+
+impl<K: Hash + Eq + Parse, V: Parse> Parse for IndexMap<K, V> {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        let mut map = IndexMap::new();
+        for (key, value) in parser.map()? {
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for IndexMap<K, V> {
+    /// Two maps are equal if they hold the same entries in the same order.
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for IndexMap<K, V> {}
+
+/// An entry of an [`IndexMap`], returned by [`IndexMap::entry`].
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: Hash + Eq, V> Entry<'a, K, V> {
+    /// The key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    /// The position of the entry, or the one it would be inserted at.
+    pub fn index(&self) -> usize {
+        match self {
+            Entry::Occupied(e) => e.index(),
+            Entry::Vacant(e) => e.index(),
+        }
+    }
+
+    /// Get the value, appending `default` first if the key is missing.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Get the value, appending the result of `default` first if the key is missing.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// Like [`Entry::or_insert_with`], but `default` is passed the key.
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let value = default(&e.key);
+                e.insert(value)
+            }
+        }
+    }
+
+    /// Get the value, appending the default value first if the key is missing.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Apply `f` to the value if the key is present.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+/// An entry of an [`IndexMap`] whose key is present.
+pub struct OccupiedEntry<'a, K, V> {
+    map: &'a mut IndexMap<K, V>,
+    index: usize,
+}
+
+impl<'a, K: Hash + Eq, V> OccupiedEntry<'a, K, V> {
+    /// The key of the entry.
+    pub fn key(&self) -> &K {
+        &self.map.entries[self.index].0
+    }
+
+    /// The position of the entry.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get a reference to the value.
+    pub fn get(&self) -> &V {
+        &self.map.entries[self.index].1
+    }
+
+    /// Get a mutable reference to the value.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Turn the entry into a mutable reference to the value, borrowed from the map.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Replace the value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the entry like [`IndexMap::swap_remove`], returning its value.
+    pub fn swap_remove(self) -> V {
+        self.map.swap_remove_index(self.index).unwrap().1
+    }
+
+    /// Remove the entry like [`IndexMap::shift_remove`], returning its value.
+    pub fn shift_remove(self) -> V {
+        self.map.shift_remove_index(self.index).unwrap().1
+    }
+}
+
+/// An entry of an [`IndexMap`] whose key is missing.
+pub struct VacantEntry<'a, K, V> {
+    map: &'a mut IndexMap<K, V>,
+    key: K,
+}
+
+impl<'a, K: Hash + Eq, V> VacantEntry<'a, K, V> {
+    /// The key of the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Take back ownership of the key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// The position the entry would be inserted at, the end of the map.
+    pub fn index(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Append the key with `value`, returning a mutable reference to the value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let index = self.map.push(self.key, value);
+        &mut self.map.entries[index].1
+    }
+}
+
+/// An iterator removing the entries of an [`IndexMap`] that match a filter,
+/// returned by [`IndexMap::extract_if`].
+pub struct ExtractIf<'a, K: Hash + Eq, V, F> {
+    // Holds the entries kept so far.
+    map: &'a mut IndexMap<K, V>,
+    // The entries not looked at yet, taken out of the map.
+    rest: vec::IntoIter<(K, V)>,
+    filter: F,
+}
+
+impl<K: Hash + Eq, V, F> Iterator for ExtractIf<'_, K, V, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        for (k, mut v) in self.rest.by_ref() {
+            if (self.filter)(&k, &mut v) {
+                return Some((k, v));
+            }
+            self.map.push(k, v);
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.rest.len()))
+    }
+}
+
+impl<K: Hash + Eq, V, F> Drop for ExtractIf<'_, K, V, F> {
+    fn drop(&mut self) {
+        for (k, v) in self.rest.by_ref() {
+            self.map.push(k, v);
+        }
+    }
+}
+
+/// Iterator over the entries of an [`IndexMap`].
+pub struct Iter<'a, K, V> {
+    inner: slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, v)| (k, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// Mutable iterator over the entries of an [`IndexMap`].
+pub struct IterMut<'a, K, V> {
+    inner: slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (&*k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, v)| (&*k, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a IndexMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut IndexMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> IntoIterator for IndexMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = alloc::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for IndexMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = IndexMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> Extend<(K, V)> for IndexMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for IndexMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for IndexMap<K, V>
+where
+    K: serde::Deserialize<'de> + Hash + Eq,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for IndexMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Hash + Eq,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for IndexMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Hash + Eq,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use super::*;
+
+    #[test]
+    fn insert() {
+        let mut map = IndexMap::new();
+        assert_eq!(map.insert("foo", 1), None);
+        assert_eq!(map.insert_full("bar", 2), (1, None));
+        assert_eq!(map.insert_full("foo", 3), (0, Some(1)));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("foo"), Some(&3));
+        assert_eq!(map.get("baz"), None);
+        assert_eq!(map.first(), Some((&"foo", &3)));
+        assert_eq!(map.last(), Some((&"bar", &2)));
+    }
+
+    #[test]
+    fn order() {
+        let map: IndexMap<_, _> = (0..100).rev().map(|i| (i, i * 2)).collect();
+        let keys: Vec<_> = map.keys().copied().collect();
+        assert_eq!(keys, (0..100).rev().collect::<Vec<_>>());
+        for i in 0..100 {
+            assert_eq!(map.get_index_of(&i), Some(99 - i));
+        }
+    }
+
+    #[test]
+    fn swap_remove() {
+        let mut map: IndexMap<_, _> = (0..5).map(|i| (i, i)).collect();
+        assert_eq!(map.swap_remove(&1), Some(1));
+        assert_eq!(map.swap_remove(&1), None);
+        let keys: Vec<_> = map.keys().copied().collect();
+        assert_eq!(keys, vec![0, 4, 2, 3]);
+        assert_eq!(map.get_index_of(&4), Some(1));
+        assert_eq!(map.get(&4), Some(&4));
+        assert_eq!(map.pop(), Some((3, 3)));
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn entry() {
+        let mut map = IndexMap::new();
+        assert_eq!(map.entry("a").index(), 0);
+        *map.entry("a").or_insert(1) += 10;
+        assert_eq!(map.entry("b").or_insert_with_key(|k| k.len()), &1);
+        assert_eq!(map.entry("c").or_default(), &0);
+        map.entry("a").and_modify(|v| *v *= 2).or_insert(0);
+        assert_eq!(map.get("a"), Some(&22));
+
+        let Entry::Occupied(mut e) = map.entry("b") else {
+            unreachable!()
+        };
+        assert_eq!((e.key(), e.index(), e.get()), (&"b", 1, &1));
+        assert_eq!(e.insert(5), 1);
+        assert_eq!(e.shift_remove(), 5);
+        let keys: Vec<_> = map.keys().copied().collect();
+        assert_eq!(keys, vec!["a", "c"]);
+
+        let Entry::Vacant(e) = map.entry("d") else {
+            unreachable!()
+        };
+        assert_eq!(e.index(), 2);
+        *e.insert(4) += 1;
+        assert_eq!(map.get_index_of("d"), Some(2));
+        let Entry::Occupied(e) = map.entry("a") else {
+            unreachable!()
+        };
+        assert_eq!(e.swap_remove(), 22);
+        assert_eq!(map.get_index(0), Some((&"d", &5)));
+        assert_eq!(map.get_index_of("d"), Some(0));
+    }
+
+    #[test]
+    fn shift_remove() {
+        let mut map: IndexMap<_, _> = (0..5).map(|i| (i, i)).collect();
+        assert_eq!(map.shift_remove(&1), Some(1));
+        let keys: Vec<_> = map.keys().copied().collect();
+        assert_eq!(keys, vec![0, 2, 3, 4]);
+        for (pos, key) in keys.iter().enumerate() {
+            assert_eq!(map.get_index_of(key), Some(pos));
+        }
+        assert_eq!(map.shift_remove_index(10), None);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut map: IndexMap<_, _> = vec![("a", 1), ("b", 2)].into_iter().collect();
+        for (_, v) in map.iter_mut() {
+            *v *= 10;
+        }
+        *map.get_index_mut(0).unwrap().1 += 1;
+        assert_eq!(
+            map.iter().rev().collect::<Vec<_>>(),
+            vec![(&"b", &20), (&"a", &11)]
+        );
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.get("a"), None);
+    }
+
+    #[test]
+    fn parse() {
+        let map: IndexMap<String, u8> = r#"{"b": 1, "a": 2, "b": 3}"#.parse().unwrap();
+        assert_eq!(map.to_string(), r#"{"b": 3, "a": 2}"#);
+        let nested: IndexMap<char, IndexMap<char, u8>> =
+            "{'x': {'y': 1}, 'z': {}}".parse().unwrap();
+        assert_eq!(nested.to_string(), "{'x': {'y': 1}, 'z': {}}");
+        assert_eq!(
+            "{'a': 1,}"
+                .parse::<IndexMap<char, u8>>()
+                .unwrap()
+                .to_string(),
+            "{'a': 1}"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: IndexMap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"2":20,"1":10}"#);
+        let back: IndexMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            back.iter().collect::<Vec<_>>(),
+            map.iter().collect::<Vec<_>>()
+        );
+    }
+}
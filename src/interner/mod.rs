@@ -0,0 +1,300 @@
+#[cfg(feature = "proptest")]
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::{fmt, iter, slice};
+
+use crate::bump::Bump;
+use crate::hashmap::HashMap;
+
+/// A handle to a string stored in an [`Interner`].
+///
+/// Symbols are small and `Copy`, and comparing two of them is a single integer
+/// comparison, which is the point of interning. They are numbered from 0 in the
+/// order their strings were first interned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// The symbol with the given number.
+    ///
+    /// This is mostly useful to name the keywords given to
+    /// [`Interner::with_keywords`] as constants.
+    pub const fn new(index: u32) -> Self {
+        Symbol(index)
+    }
+
+    /// The number of this symbol.
+    pub const fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// A string interner.
+///
+/// Each distinct string is stored once and mapped to a [`Symbol`]. The strings are
+/// copied into a [`Bump`] allocator, so interning doesn't allocate once per string
+/// and the text of a symbol never moves. Looking a symbol up again with
+/// [`Interner::resolve`] is an index into a `Vec`.
+///
+/// Strings are never freed individually: they all go away with the interner.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::interner::{Interner, Symbol};
+///
+/// const FN: Symbol = Symbol::new(0);
+/// const LET: Symbol = Symbol::new(1);
+///
+/// let mut interner = Interner::with_keywords(&["fn", "let"]);
+/// assert_eq!(interner.intern("let"), LET);
+///
+/// let x = interner.intern("x");
+/// assert_eq!(interner.intern("x"), x);
+/// assert_ne!(x, FN);
+/// assert_eq!(interner.resolve(x), "x");
+/// assert_eq!(interner.get("y"), None);
+/// ```
+pub struct Interner {
+    // The `&'static str`s below point into `arena`. They are never handed out with
+    // that lifetime, only borrowed from the interner.
+    map: HashMap<&'static str, Symbol>,
+    strings: Vec<&'static str>,
+    arena: Bump,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interner {
+    /// Create a new, empty interner.
+    pub fn new() -> Self {
+        Interner {
+            map: HashMap::new(),
+            strings: Vec::new(),
+            arena: Bump::new(),
+        }
+    }
+
+    /// Create an interner holding `keywords`, so that `keywords[i]` is
+    /// `Symbol::new(i)`.
+    /// Panics if a keyword is repeated, as the numbering would no longer hold.
+    pub fn with_keywords(keywords: &[&str]) -> Self {
+        let mut interner = Self::new();
+        for (i, keyword) in keywords.iter().enumerate() {
+            assert_eq!(
+                interner.intern(keyword).as_u32() as usize,
+                i,
+                "keyword {keyword:?} should not be repeated"
+            );
+        }
+        interner
+    }
+
+    /// Number of distinct strings interned.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Return `true` if no string was interned.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Return the symbol of `s`, interning it first if needed.
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(&symbol) = self.map.get(s) {
+            return symbol;
+        }
+        let index = u32::try_from(self.strings.len()).expect("too many symbols");
+        let symbol = Symbol(index);
+        // SAFETY: the string lives in `arena`, which is never reset and outlives
+        // `map` and `strings`. Only references bound to `&self` are given out.
+        let s: &'static str = unsafe { &*(self.arena.alloc_str(s) as *const str) };
+        self.map.insert(s, symbol);
+        self.strings.push(s);
+        symbol
+    }
+
+    /// The symbol of `s`, if it was interned.
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.map.get(s).copied()
+    }
+
+    /// The string of `symbol`.
+    /// Panics if `symbol` doesn't come from this interner.
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        let len = self.strings.len();
+        match self.strings.get(symbol.0 as usize) {
+            Some(s) => s,
+            None => panic!("symbol (is {}) should be < len (is {len})", symbol.0),
+        }
+    }
+
+    /// Iterate over the symbols and their strings, in the order they were interned.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.strings.iter().enumerate(),
+        }
+    }
+}
+
+impl fmt::Debug for Interner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.strings).finish()
+    }
+}
+
+/// Iterator over the symbols of an [`Interner`] and their strings, in the order
+/// they were interned.
+pub struct Iter<'a> {
+    inner: iter::Enumerate<slice::Iter<'a, &'static str>>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (Symbol, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(i, s)| (Symbol(i as u32), *s))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> IntoIterator for &'a Interner {
+    type Item = (Symbol, &'a str);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> Extend<&'a str> for Interner {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.intern(s);
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Interner {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut interner = Interner::new();
+        interner.extend(iter);
+        interner
+    }
+}
+
+/// An interner serializes as its strings in the order they were interned, so
+/// every symbol resolves to the same string after a round trip.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Interner {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.strings.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Interner {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let strings: Vec<alloc::string::String> = serde::Deserialize::deserialize(deserializer)?;
+        let interner: Interner = strings.iter().map(|s| s.as_str()).collect();
+        // A repeated string would shift the symbols of the ones after it.
+        if interner.len() != strings.len() {
+            return Err(serde::de::Error::custom("repeated string in an interner"));
+        }
+        Ok(interner)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Interner {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<&'a str>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<&'a str>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Interner {
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <String as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((size, args): Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::collection::vec(proptest::arbitrary::any_with::<String>(args), size)
+            .prop_map(|strings| strings.iter().map(String::as_str).collect())
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, format, string::String, vec};
+
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let mut interner = Interner::new();
+        let words: Vec<String> = (0..1000).map(|i| format!("word{}", i % 300)).collect();
+        let symbols: Vec<_> = words.iter().map(|w| interner.intern(w)).collect();
+        assert_eq!(interner.len(), 300);
+        for (word, symbol) in words.iter().zip(&symbols) {
+            assert_eq!(interner.resolve(*symbol), word);
+            assert_eq!(interner.get(word), Some(*symbol));
+        }
+        assert_eq!(symbols[0], symbols[300]);
+        assert_eq!(interner.intern(""), Symbol(300));
+        assert_eq!(interner.resolve(Symbol(300)), "");
+    }
+
+    #[test]
+    fn strings_survive_a_move() {
+        let mut interner = Interner::new();
+        let a = interner.intern("alpha");
+        let boxed = Box::new(interner);
+        assert_eq!(boxed.resolve(a), "alpha");
+        let order: Vec<_> = boxed.iter().map(|(_, s)| s).collect();
+        assert_eq!(order, vec!["alpha"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "keyword \"if\" should not be repeated")]
+    fn repeated_keyword() {
+        Interner::with_keywords(&["if", "else", "if"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "symbol (is 3) should be < len (is 0)")]
+    fn unknown_symbol() {
+        Interner::new().resolve(Symbol::new(3));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut interner = Interner::new();
+        let b = interner.intern("b");
+        let a = interner.intern("a");
+        let json = serde_json::to_string(&interner).unwrap();
+        assert_eq!(json, r#"["b","a"]"#);
+        let back: Interner = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.resolve(b), "b");
+        assert_eq!(back.get("a"), Some(a));
+        assert!(serde_json::from_str::<Interner>(r#"["a","a"]"#).is_err());
+    }
+}
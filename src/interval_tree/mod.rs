@@ -0,0 +1,435 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::cmp;
+use core::ops::Range;
+
+/// Node of an interval tree.
+///
+/// Every node caches the largest `end` found in its subtree so that whole
+/// subtrees can be skipped when they cannot overlap a query.
+#[derive(Debug)]
+struct Node<K, V> {
+    range: Range<K>,
+    value: V,
+    max: K,
+    height: usize,
+    left: Option<Box<Node<K, V>>>,
+    right: Option<Box<Node<K, V>>>,
+}
+
+impl<K, V> Node<K, V>
+where
+    K: Ord + Clone,
+{
+    fn new(range: Range<K>, value: V) -> Self {
+        Node {
+            max: range.end.clone(),
+            range,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    // Recompute the cached height and max end from the children.
+    fn update(&mut self) {
+        let mut max = self.range.end.clone();
+        let mut height = 0;
+        for child in [&self.left, &self.right].into_iter().flatten() {
+            if child.max > max {
+                max = child.max.clone();
+            }
+            height = cmp::max(height, child.height);
+        }
+        self.max = max;
+        self.height = height + 1;
+    }
+
+    fn balance_factor(&self) -> isize {
+        height(&self.left) as isize - height(&self.right) as isize
+    }
+}
+
+fn height<K, V>(node: &Option<Box<Node<K, V>>>) -> usize {
+    node.as_ref().map_or(0, |node| node.height)
+}
+
+/// Interval tree.
+/// It stores half-open ranges together with a value and answers overlap queries in
+/// O(log n + k), where k is the number of reported intervals.
+/// The tree is ordered by range start and kept balanced with AVL rotations.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::interval_tree::IntervalTree;
+///
+/// let mut tree = IntervalTree::new();
+/// tree.insert(1..5, "a");
+/// tree.insert(4..8, "b");
+/// tree.insert(10..12, "c");
+///
+/// let values: Vec<_> = tree.find_overlapping(&(3..6)).into_iter().map(|(_, v)| *v).collect();
+/// assert_eq!(values, vec!["a", "b"]);
+/// assert_eq!(tree.find_containing(&11).len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct IntervalTree<K, V> {
+    root: Option<Box<Node<K, V>>>,
+    len: usize,
+}
+
+impl<K, V> Default for IntervalTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> IntervalTree<K, V> {
+    /// Create a new, empty interval tree.
+    pub const fn new() -> Self {
+        IntervalTree { root: None, len: 0 }
+    }
+
+    /// Number of intervals stored in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the tree holds no intervals.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<K, V> IntervalTree<K, V>
+where
+    K: Ord + Clone,
+{
+    /// Insert an interval with its associated value.
+    /// Duplicate and overlapping intervals are allowed.
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        let root = self.root.take();
+        self.root = Some(Self::insert_node(root, Node::new(range, value)));
+        self.len += 1;
+    }
+
+    fn insert_node(node: Option<Box<Node<K, V>>>, new_node: Node<K, V>) -> Box<Node<K, V>> {
+        let mut node = match node {
+            Some(node) => node,
+            None => return Box::new(new_node),
+        };
+        if new_node.range.start < node.range.start {
+            node.left = Some(Self::insert_node(node.left.take(), new_node));
+        } else {
+            node.right = Some(Self::insert_node(node.right.take(), new_node));
+        }
+        Self::rebalance(node)
+    }
+
+    fn rebalance(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        node.update();
+        let balance = node.balance_factor();
+        if balance > 1 {
+            // Left heavy.
+            if node.left.as_ref().unwrap().balance_factor() < 0 {
+                node.left = Some(Self::rotate_left(node.left.take().unwrap()));
+            }
+            Self::rotate_right(node)
+        } else if balance < -1 {
+            // Right heavy.
+            if node.right.as_ref().unwrap().balance_factor() > 0 {
+                node.right = Some(Self::rotate_right(node.right.take().unwrap()));
+            }
+            Self::rotate_left(node)
+        } else {
+            node
+        }
+    }
+
+    fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut right = node
+            .right
+            .take()
+            .expect("rotate left requires a right child");
+        node.right = right.left.take();
+        node.update();
+        right.left = Some(node);
+        right.update();
+        right
+    }
+
+    fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut left = node
+            .left
+            .take()
+            .expect("rotate right requires a left child");
+        node.left = left.right.take();
+        node.update();
+        left.right = Some(node);
+        left.update();
+        left
+    }
+
+    /// Find all intervals that overlap the given range, ordered by start.
+    ///
+    /// An empty range holds no key, so it overlaps nothing, even inside an
+    /// interval. Query a single key with
+    /// [`find_containing`](IntervalTree::find_containing).
+    pub fn find_overlapping(&self, range: &Range<K>) -> Vec<(&Range<K>, &V)> {
+        fn helper<'a, K: Ord, V>(
+            node: &'a Option<Box<Node<K, V>>>,
+            range: &Range<K>,
+            res: &mut Vec<(&'a Range<K>, &'a V)>,
+        ) {
+            let node = match node {
+                Some(node) => node,
+                None => return,
+            };
+            // Nothing in this subtree ends after the query starts.
+            if node.max <= range.start {
+                return;
+            }
+            helper(&node.left, range, res);
+            // Every interval to the right starts at or after this one.
+            if node.range.start < range.end {
+                if range.start < node.range.end {
+                    res.push((&node.range, &node.value));
+                }
+                helper(&node.right, range, res);
+            }
+        }
+
+        let mut res = Vec::new();
+        if !range.is_empty() {
+            helper(&self.root, range, &mut res);
+        }
+        res
+    }
+
+    /// Find all intervals that contain the given point, ordered by start.
+    pub fn find_containing(&self, point: &K) -> Vec<(&Range<K>, &V)> {
+        fn helper<'a, K: Ord, V>(
+            node: &'a Option<Box<Node<K, V>>>,
+            point: &K,
+            res: &mut Vec<(&'a Range<K>, &'a V)>,
+        ) {
+            let node = match node {
+                Some(node) => node,
+                None => return,
+            };
+            if node.max <= *point {
+                return;
+            }
+            helper(&node.left, point, res);
+            if node.range.start <= *point {
+                if node.range.contains(point) {
+                    res.push((&node.range, &node.value));
+                }
+                helper(&node.right, point, res);
+            }
+        }
+
+        let mut res = Vec::new();
+        helper(&self.root, point, &mut res);
+        res
+    }
+
+    /// Traverse the intervals in order of their start.
+    pub fn inorder<F>(&self, visit: F)
+    where
+        F: FnMut(&Range<K>, &V),
+    {
+        let mut visit = visit;
+        fn inorder_helper<K, V, F>(node: &Option<Box<Node<K, V>>>, visit: &mut F)
+        where
+            F: FnMut(&Range<K>, &V),
+        {
+            if let Some(node) = node {
+                inorder_helper(&node.left, visit);
+                visit(&node.range, &node.value);
+                inorder_helper(&node.right, visit);
+            }
+        }
+        inorder_helper(&self.root, &mut visit);
+    }
+}
+
+impl<K, V> Extend<(Range<K>, V)> for IntervalTree<K, V>
+where
+    K: Ord + Clone,
+{
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(Range<K>, V)> for IntervalTree<K, V>
+where
+    K: Ord + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut tree = IntervalTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+/// A tree serializes as a sequence of `(range, value)` pairs in order of their
+/// start.
+#[cfg(feature = "serde")]
+impl<K, V> serde::Serialize for IntervalTree<K, V>
+where
+    K: serde::Serialize + Ord + Clone,
+    V: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        let mut result = Ok(());
+        self.inorder(|range, value| {
+            if result.is_ok() {
+                result = seq.serialize_element(&(range, value));
+            }
+        });
+        result?;
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for IntervalTree<K, V>
+where
+    K: serde::Deserialize<'de> + Ord + Clone,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for IntervalTree<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord + Clone,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(Range<K>, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(Range<K>, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for IntervalTree<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Ord + Clone,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(Range<K>, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &(Range<K>, V), b: &(Range<K>, V)| {
+            a.0.start.cmp(&b.0.start)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn tree() -> IntervalTree<i32, &'static str> {
+        let mut tree: IntervalTree<_, _> = [(15..20, "a"), (10..30, "b"), (17..19, "c")]
+            .into_iter()
+            .collect();
+        tree.extend([(5..20, "d"), (12..15, "e"), (30..40, "f")]);
+        tree
+    }
+
+    #[test]
+    fn insert() {
+        let tree = tree();
+        assert_eq!(tree.len(), 6);
+        let mut res = Vec::new();
+        tree.inorder(|range, _| res.push(range.start));
+        assert_eq!(res, vec![5, 10, 12, 15, 17, 30]);
+    }
+
+    #[test]
+    fn find_overlapping() {
+        let tree = tree();
+        let res: Vec<_> = tree
+            .find_overlapping(&(14..16))
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect();
+        assert_eq!(res, vec!["d", "b", "e", "a"]);
+
+        // Ranges are half-open, so touching endpoints don't overlap.
+        let res: Vec<_> = tree
+            .find_overlapping(&(20..30))
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect();
+        assert_eq!(res, vec!["b"]);
+        assert!(tree.find_overlapping(&(40..50)).is_empty());
+    }
+
+    #[test]
+    fn empty_query_overlaps_nothing() {
+        let tree = tree();
+        assert!(tree.find_overlapping(&(18..18)).is_empty());
+        assert_eq!(tree.find_containing(&18).len(), 4);
+    }
+
+    #[test]
+    fn find_containing() {
+        let tree = tree();
+        let res: Vec<_> = tree
+            .find_containing(&18)
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect();
+        assert_eq!(res, vec!["d", "b", "a", "c"]);
+        assert!(tree.find_containing(&4).is_empty());
+        assert_eq!(tree.find_containing(&30).len(), 1);
+    }
+
+    #[test]
+    fn balanced() {
+        let mut tree = IntervalTree::new();
+        for i in 0..1024 {
+            tree.insert(i..i + 1, i);
+        }
+        assert_eq!(tree.len(), 1024);
+        assert!(height(&tree.root) <= 11);
+        assert_eq!(tree.find_containing(&512), vec![(&(512..513), &512)]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let tree: IntervalTree<i32, char> = [(5..7, 'b'), (1..6, 'a')].into_iter().collect();
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(
+            json,
+            r#"[[{"start":1,"end":6},"a"],[{"start":5,"end":7},"b"]]"#
+        );
+        let back: IntervalTree<i32, char> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.len(), 2);
+        assert_eq!(back.find_containing(&5).len(), 2);
+    }
+}
@@ -0,0 +1,1518 @@
+use alloc::vec::Vec;
+use alloc::{collections::VecDeque, vec};
+use core::{
+    cmp::Ordering,
+    fmt,
+    iter::{Fuse, FusedIterator, Peekable, Skip, StepBy},
+    mem,
+};
+
+use crate::leftist_heap::LeftistHeap;
+
+/// Adapters available on every [`Iterator`], so they work the same on the
+/// iterators of every collection in the crate.
+///
+/// Nightly Rust has an unstable `Iterator::intersperse`; calling
+/// [`intersperse`](IteratorExt::intersperse) as a method warns about the
+/// collision there, which `IteratorExt::intersperse(iter, sep)` avoids.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::iter::IteratorExt;
+/// use my_std::my_vec::MyVec;
+///
+/// let mut v = MyVec::new();
+/// for x in [1, 1, 2, 3, 3, 3, 4, 5] {
+///     v.push(x);
+/// }
+/// let deduped: Vec<_> = v.iter().copied().dedup().collect();
+/// assert_eq!(deduped, [1, 2, 3, 4, 5]);
+///
+/// let chunks: Vec<_> = deduped.iter().chunks(2).collect();
+/// assert_eq!(chunks, [vec![&1, &2], vec![&3, &4], vec![&5]]);
+///
+/// let sums: Vec<i32> = deduped.iter().windows(3).map(|w| w.into_iter().sum()).collect();
+/// assert_eq!(sums, [6, 9, 12]);
+///
+/// let odd: Vec<_> = (0..10).step_by_from(1, 2).collect();
+/// assert_eq!(odd, [1, 3, 5, 7, 9]);
+///
+/// let merged: Vec<_> = [vec![1, 4], vec![2, 3, 5]].into_iter().kmerge().collect();
+/// let parity: Vec<_> = merged.iter().group_by(|x| *x % 2).map(|(k, g)| (k, g.len())).collect();
+/// assert_eq!(parity, [(1, 1), (0, 1), (1, 1), (0, 1), (1, 1)]);
+///
+/// let grid: Vec<_> = (0..2).cartesian_product(['x', 'y']).collect();
+/// assert_eq!(grid, [(0, 'x'), (0, 'y'), (1, 'x'), (1, 'y')]);
+///
+/// let lowest: Vec<_> = [5, 3, 9, 1, 7].into_iter().k_smallest(2).collect();
+/// assert_eq!(lowest, [1, 3]);
+/// ```
+pub trait IteratorExt: Iterator + Sized {
+    /// Group the items into `Vec`s of `size`, the last one holding whatever
+    /// is left. Panics if `size` is zero.
+    fn chunks(self, size: usize) -> Chunks<Self> {
+        assert!(size > 0, "chunk size must be positive");
+        Chunks {
+            iter: self.fuse(),
+            size,
+        }
+    }
+
+    /// Yield every run of `size` consecutive items, as `Vec`s overlapping by
+    /// all but one item. Yields nothing if there are fewer than `size` items.
+    /// Panics if `size` is zero.
+    fn windows(self, size: usize) -> Windows<Self>
+    where
+        Self::Item: Clone,
+    {
+        assert!(size > 0, "window size must be positive");
+        Windows {
+            iter: self.fuse(),
+            window: VecDeque::with_capacity(size),
+            size,
+        }
+    }
+
+    /// Like [`windows`](IteratorExt::windows), lending each window as a slice
+    /// of a buffer the iterator reuses, so items are neither cloned nor
+    /// collected into a new `Vec` per window. Panics if `size` is zero.
+    fn lending_windows(self, size: usize) -> LendingWindows<Self> {
+        assert!(size > 0, "window size must be positive");
+        LendingWindows {
+            iter: self.fuse(),
+            buffer: Vec::with_capacity(2 * size),
+            start: 0,
+            size,
+        }
+    }
+
+    /// Drop items equal to the one yielded right before them, keeping the
+    /// first of each run.
+    fn dedup(self) -> Dedup<Self>
+    where
+        Self::Item: PartialEq,
+    {
+        Dedup {
+            iter: self.fuse(),
+            next: None,
+        }
+    }
+
+    /// Yield a clone of `separator` between every two items.
+    fn intersperse(self, separator: Self::Item) -> Intersperse<Self>
+    where
+        Self::Item: Clone,
+    {
+        Intersperse {
+            iter: self.peekable(),
+            separator,
+            needs_separator: false,
+        }
+    }
+
+    /// Yield the item at index `start`, then every `step`th item after it.
+    /// Panics if `step` is zero.
+    fn step_by_from(self, start: usize, step: usize) -> StepBy<Skip<Self>> {
+        self.skip(start).step_by(step)
+    }
+
+    /// Pair every item with every item of `other`, in order: all pairs with
+    /// the first item, then all pairs with the second, and so on. `other` is
+    /// cloned to go over it again for each item.
+    fn cartesian_product<J>(self, other: J) -> CartesianProduct<Self, J::IntoIter>
+    where
+        J: IntoIterator,
+        J::IntoIter: Clone,
+        Self::Item: Clone,
+    {
+        let other = other.into_iter();
+        CartesianProduct {
+            a: self,
+            a_item: None,
+            b: other.clone(),
+            b_start: other,
+        }
+    }
+
+    /// Group runs of consecutive items with equal keys, yielding each key with
+    /// the items of its run. Items with the same key that aren't next to each
+    /// other end up in separate groups, so sort by the key first to get one
+    /// group per key.
+    ///
+    /// Each group is collected when it's yielded, reading one item past it.
+    fn group_by<K, F>(self, key: F) -> GroupBy<Self, K, F>
+    where
+        K: PartialEq,
+        F: FnMut(&Self::Item) -> K,
+    {
+        GroupBy {
+            iter: self.fuse(),
+            key,
+            next: None,
+        }
+    }
+
+    /// Merge iterators each sorted in ascending order into one sorted
+    /// iterator. Items that compare equal come out in the order of their
+    /// iterators.
+    ///
+    /// The next item of each iterator waits in a [`LeftistHeap`], so each item
+    /// costs O(log k) for k iterators. Nothing is read before the first call
+    /// to `next`.
+    fn kmerge(self) -> KMerge<Self, <Self::Item as IntoIterator>::IntoIter>
+    where
+        Self::Item: IntoIterator,
+        <Self::Item as IntoIterator>::Item: Ord,
+    {
+        KMerge {
+            iters: Some(self),
+            heap: LeftistHeap::new(),
+        }
+    }
+
+    /// Collect the items and yield them in ascending order. The sort is
+    /// stable, so equal items keep the order they came in.
+    fn sorted(self) -> vec::IntoIter<Self::Item>
+    where
+        Self::Item: Ord,
+    {
+        self.sorted_by(Ord::cmp)
+    }
+
+    /// Like [`sorted`](IteratorExt::sorted), comparing items with `compare`.
+    fn sorted_by<F>(self, compare: F) -> vec::IntoIter<Self::Item>
+    where
+        F: FnMut(&Self::Item, &Self::Item) -> Ordering,
+    {
+        let mut items: Vec<_> = self.collect();
+        items.sort_by(compare);
+        items.into_iter()
+    }
+
+    /// Yield the `k` smallest items in ascending order.
+    ///
+    /// The items are read through a [`LeftistHeap`] that drops its largest
+    /// item whenever it holds more than `k`, so this takes O(n log k) time and
+    /// O(k) space instead of sorting all n items. Which of several equal items
+    /// is kept is unspecified.
+    fn k_smallest(self, k: usize) -> vec::IntoIter<Self::Item>
+    where
+        Self::Item: Ord,
+    {
+        let mut heap = LeftistHeap::new();
+        if k > 0 {
+            for item in self {
+                heap.push(item);
+                if heap.len() > k {
+                    heap.pop();
+                }
+            }
+        }
+        heap.into_sorted_vec().into_iter()
+    }
+
+    /// Allow looking any number of items ahead, see [`MultiPeekable`].
+    fn multi_peekable(self) -> MultiPeekable<Self> {
+        MultiPeekable {
+            iter: self.fuse(),
+            buffer: VecDeque::new(),
+            cursor: 0,
+        }
+    }
+}
+
+impl<I: Iterator> IteratorExt for I {}
+
+/// Iterator returned by [`IteratorExt::chunks`].
+#[derive(Debug, Clone)]
+pub struct Chunks<I> {
+    iter: Fuse<I>,
+    size: usize,
+}
+
+impl<I: Iterator> Iterator for Chunks<I> {
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Vec<I::Item>> {
+        let chunk: Vec<_> = self.iter.by_ref().take(self.size).collect();
+        (!chunk.is_empty()).then_some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.iter.size_hint();
+        (lo.div_ceil(self.size), hi.map(|hi| hi.div_ceil(self.size)))
+    }
+}
+
+impl<I: Iterator> FusedIterator for Chunks<I> {}
+
+/// Iterator returned by [`IteratorExt::windows`].
+pub struct Windows<I: Iterator> {
+    iter: Fuse<I>,
+    // The last window yielded, or the start of the first one.
+    window: VecDeque<I::Item>,
+    size: usize,
+}
+
+impl<I: Iterator> Iterator for Windows<I>
+where
+    I::Item: Clone,
+{
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Vec<I::Item>> {
+        if self.window.len() == self.size {
+            let next = self.iter.next()?;
+            self.window.pop_front();
+            self.window.push_back(next);
+        }
+        while self.window.len() < self.size {
+            self.window.push_back(self.iter.next()?);
+        }
+        Some(self.window.iter().cloned().collect())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Items already buffered that the next window reuses.
+        let kept = self.window.len().min(self.size - 1);
+        let windows = |left: usize| (kept + left + 1).saturating_sub(self.size);
+        let (lo, hi) = self.iter.size_hint();
+        (windows(lo), hi.map(windows))
+    }
+}
+
+impl<I: Iterator> FusedIterator for Windows<I> where I::Item: Clone {}
+
+impl<I: Iterator + Clone> Clone for Windows<I>
+where
+    I::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        Windows {
+            iter: self.iter.clone(),
+            window: self.window.clone(),
+            size: self.size,
+        }
+    }
+}
+
+impl<I: Iterator + fmt::Debug> fmt::Debug for Windows<I>
+where
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Windows")
+            .field("iter", &self.iter)
+            .field("window", &self.window)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+/// Iterator returned by [`IteratorExt::dedup`].
+pub struct Dedup<I: Iterator> {
+    iter: Fuse<I>,
+    // The first item of the next run, already taken from `iter`.
+    next: Option<I::Item>,
+}
+
+impl<I: Iterator> Iterator for Dedup<I>
+where
+    I::Item: PartialEq,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let item = self.next.take().or_else(|| self.iter.next())?;
+        self.next = self.iter.by_ref().find(|next| *next != item);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = usize::from(self.next.is_some());
+        let (lo, hi) = self.iter.size_hint();
+        (
+            usize::from(buffered + lo > 0),
+            hi.and_then(|hi| hi.checked_add(buffered)),
+        )
+    }
+}
+
+impl<I: Iterator> FusedIterator for Dedup<I> where I::Item: PartialEq {}
+
+impl<I: Iterator + Clone> Clone for Dedup<I>
+where
+    I::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        Dedup {
+            iter: self.iter.clone(),
+            next: self.next.clone(),
+        }
+    }
+}
+
+impl<I: Iterator + fmt::Debug> fmt::Debug for Dedup<I>
+where
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dedup")
+            .field("iter", &self.iter)
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+/// Iterator returned by [`IteratorExt::intersperse`].
+pub struct Intersperse<I: Iterator> {
+    iter: Peekable<I>,
+    separator: I::Item,
+    // An item was yielded, so a separator goes before the next one.
+    needs_separator: bool,
+}
+
+impl<I: Iterator> Iterator for Intersperse<I>
+where
+    I::Item: Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if self.needs_separator && self.iter.peek().is_some() {
+            self.needs_separator = false;
+            return Some(self.separator.clone());
+        }
+        let item = self.iter.next()?;
+        self.needs_separator = true;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.iter.size_hint();
+        // `n` items, with a separator before each but the first, unless one
+        // was already yielded.
+        let total = |n: usize| {
+            let separators = if self.needs_separator {
+                n
+            } else {
+                n.saturating_sub(1)
+            };
+            n.checked_add(separators)
+        };
+        (total(lo).unwrap_or(usize::MAX), hi.and_then(total))
+    }
+}
+
+impl<I: Iterator + Clone> Clone for Intersperse<I>
+where
+    I::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        Intersperse {
+            iter: self.iter.clone(),
+            separator: self.separator.clone(),
+            needs_separator: self.needs_separator,
+        }
+    }
+}
+
+impl<I: Iterator + fmt::Debug> fmt::Debug for Intersperse<I>
+where
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Intersperse")
+            .field("iter", &self.iter)
+            .field("separator", &self.separator)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Iterator returned by [`IteratorExt::cartesian_product`].
+#[derive(Debug, Clone)]
+pub struct CartesianProduct<I: Iterator, J> {
+    a: I,
+    // The item of `a` paired with the rest of `b`.
+    a_item: Option<I::Item>,
+    b: J,
+    b_start: J,
+}
+
+impl<I, J> Iterator for CartesianProduct<I, J>
+where
+    I: Iterator,
+    I::Item: Clone,
+    J: Iterator + Clone,
+{
+    type Item = (I::Item, J::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(a) = &self.a_item {
+                if let Some(b) = self.b.next() {
+                    return Some((a.clone(), b));
+                }
+            }
+            self.a_item = Some(self.a.next()?);
+            self.b = self.b_start.clone();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a_lo, a_hi) = self.a.size_hint();
+        let (b_lo, b_hi) = self.b_start.size_hint();
+        // What is left of the row of the current item of `a`.
+        let (row_lo, row_hi) = match self.a_item {
+            Some(_) => self.b.size_hint(),
+            None => (0, Some(0)),
+        };
+        let lo = a_lo.saturating_mul(b_lo).saturating_add(row_lo);
+        let hi = (|| a_hi?.checked_mul(b_hi?)?.checked_add(row_hi?))();
+        (lo, hi)
+    }
+}
+
+/// Iterator returned by [`IteratorExt::group_by`].
+pub struct GroupBy<I: Iterator, K, F> {
+    iter: Fuse<I>,
+    key: F,
+    // The first item of the next group, read while collecting the last one.
+    next: Option<(K, I::Item)>,
+}
+
+impl<I, K, F> Iterator for GroupBy<I, K, F>
+where
+    I: Iterator,
+    K: PartialEq,
+    F: FnMut(&I::Item) -> K,
+{
+    type Item = (K, Vec<I::Item>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, first) = match self.next.take() {
+            Some(next) => next,
+            None => {
+                let item = self.iter.next()?;
+                ((self.key)(&item), item)
+            }
+        };
+        let mut group = vec![first];
+        for item in self.iter.by_ref() {
+            let next_key = (self.key)(&item);
+            if next_key != key {
+                self.next = Some((next_key, item));
+                break;
+            }
+            group.push(item);
+        }
+        Some((key, group))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = usize::from(self.next.is_some());
+        let (lo, hi) = self.iter.size_hint();
+        (
+            usize::from(buffered + lo > 0),
+            hi.and_then(|hi| hi.checked_add(buffered)),
+        )
+    }
+}
+
+impl<I: Iterator, K, F> FusedIterator for GroupBy<I, K, F>
+where
+    K: PartialEq,
+    F: FnMut(&I::Item) -> K,
+{
+}
+
+impl<I: Iterator + fmt::Debug, K: fmt::Debug, F> fmt::Debug for GroupBy<I, K, F>
+where
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupBy")
+            .field("iter", &self.iter)
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+// An iterator being merged by `KMerge`, with the item it yielded last.
+struct Head<J: Iterator> {
+    item: J::Item,
+    // Position of the iterator among the merged ones, to break ties.
+    index: usize,
+    iter: J,
+}
+
+// Ordered so that the max-heap pops the smallest item, and among equal items,
+// the one from the first iterator.
+impl<J: Iterator> Ord for Head<J>
+where
+    J::Item: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .item
+            .cmp(&self.item)
+            .then(other.index.cmp(&self.index))
+    }
+}
+
+impl<J: Iterator> PartialOrd for Head<J>
+where
+    J::Item: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<J: Iterator> PartialEq for Head<J>
+where
+    J::Item: Ord,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<J: Iterator> Eq for Head<J> where J::Item: Ord {}
+
+/// Iterator returned by [`IteratorExt::kmerge`].
+pub struct KMerge<I, J: Iterator> {
+    // The iterators to merge, until the first call to `next` moves them into
+    // the heap.
+    iters: Option<I>,
+    heap: LeftistHeap<Head<J>>,
+}
+
+impl<I, J> Iterator for KMerge<I, J>
+where
+    I: Iterator,
+    I::Item: IntoIterator<IntoIter = J, Item = J::Item>,
+    J: Iterator,
+    J::Item: Ord,
+{
+    type Item = J::Item;
+
+    fn next(&mut self) -> Option<J::Item> {
+        if let Some(iters) = self.iters.take() {
+            for (index, iter) in iters.enumerate() {
+                let mut iter = iter.into_iter();
+                if let Some(item) = iter.next() {
+                    self.heap.push(Head { item, index, iter });
+                }
+            }
+        }
+        let mut head = self.heap.pop()?;
+        let item = match head.iter.next() {
+            Some(next) => {
+                let item = mem::replace(&mut head.item, next);
+                self.heap.push(head);
+                item
+            }
+            None => head.item,
+        };
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.iters.is_some() {
+            // Not counting unopened iterators.
+            return (0, None);
+        }
+        self.heap.iter().fold((0, Some(0)), |(lo, hi), head| {
+            let (head_lo, head_hi) = head.iter.size_hint();
+            (
+                lo.saturating_add(head_lo).saturating_add(1),
+                (|| hi?.checked_add(head_hi?)?.checked_add(1))(),
+            )
+        })
+    }
+}
+
+impl<I, J> FusedIterator for KMerge<I, J>
+where
+    I: Iterator,
+    I::Item: IntoIterator<IntoIter = J, Item = J::Item>,
+    J: Iterator,
+    J::Item: Ord,
+{
+}
+
+impl<I: fmt::Debug, J: Iterator> fmt::Debug for KMerge<I, J> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KMerge")
+            .field("iters", &self.iters)
+            .field("heads", &self.heap.len())
+            .finish()
+    }
+}
+
+/// An iterator that can look any number of items ahead, as returned by
+/// [`IteratorExt::multi_peekable`].
+///
+/// [`peek_nth`](MultiPeekable::peek_nth) looks at the item `n` places ahead.
+/// [`peek`](MultiPeekable::peek) walks ahead one item per call from a cursor,
+/// which [`reset_peek`](MultiPeekable::reset_peek) and every `next` move back
+/// to the next item. Items looked at are kept until `next` yields them, so a
+/// parser can try several token patterns at one position before committing.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::iter::IteratorExt;
+///
+/// let mut tokens = ["let", "x", "=", "1"].into_iter().multi_peekable();
+/// assert_eq!(tokens.peek_nth(2), Some(&"="));
+///
+/// assert_eq!(tokens.peek(), Some(&"let"));
+/// assert_eq!(tokens.peek(), Some(&"x"));
+/// tokens.reset_peek();
+/// assert_eq!(tokens.peek(), Some(&"let"));
+///
+/// assert_eq!(tokens.next(), Some("let"));
+/// assert_eq!(tokens.peek(), Some(&"x"));
+/// assert_eq!(tokens.peek_nth(5), None);
+/// ```
+pub struct MultiPeekable<I: Iterator> {
+    iter: Fuse<I>,
+    // Items looked at and not yielded yet.
+    buffer: VecDeque<I::Item>,
+    // Index in `buffer` of the item the next `peek` returns.
+    cursor: usize,
+}
+
+impl<I: Iterator> MultiPeekable<I> {
+    /// Look at the item `n` places ahead without consuming anything, `0`
+    /// being the next one. Doesn't move the [`peek`](MultiPeekable::peek)
+    /// cursor.
+    pub fn peek_nth(&mut self, n: usize) -> Option<&I::Item> {
+        while self.buffer.len() <= n {
+            self.buffer.push_back(self.iter.next()?);
+        }
+        self.buffer.get(n)
+    }
+
+    /// Like [`peek_nth`](MultiPeekable::peek_nth), with a mutable reference.
+    pub fn peek_nth_mut(&mut self, n: usize) -> Option<&mut I::Item> {
+        self.peek_nth(n)?;
+        self.buffer.get_mut(n)
+    }
+
+    /// Look at the item under the cursor and move the cursor one item
+    /// further. The first call after [`reset_peek`](MultiPeekable::reset_peek)
+    /// or `next` returns the next item.
+    pub fn peek(&mut self) -> Option<&I::Item> {
+        let n = self.cursor;
+        // Past the end, the cursor stops moving.
+        self.peek_nth(n)?;
+        self.cursor += 1;
+        self.buffer.get(n)
+    }
+
+    /// Move the [`peek`](MultiPeekable::peek) cursor back to the next item.
+    pub fn reset_peek(&mut self) {
+        self.cursor = 0;
+    }
+
+    /// Consume and return the next item if `f` accepts it.
+    pub fn next_if(&mut self, f: impl FnOnce(&I::Item) -> bool) -> Option<I::Item> {
+        if f(self.peek_nth(0)?) {
+            self.next()
+        } else {
+            None
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for MultiPeekable<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        self.cursor = 0;
+        self.buffer.pop_front().or_else(|| self.iter.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.iter.size_hint();
+        let buffered = self.buffer.len();
+        (
+            lo.saturating_add(buffered),
+            hi.and_then(|hi| hi.checked_add(buffered)),
+        )
+    }
+}
+
+impl<I: Iterator> FusedIterator for MultiPeekable<I> {}
+
+impl<I: Iterator + Clone> Clone for MultiPeekable<I>
+where
+    I::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        MultiPeekable {
+            iter: self.iter.clone(),
+            buffer: self.buffer.clone(),
+            cursor: self.cursor,
+        }
+    }
+}
+
+impl<I: Iterator + fmt::Debug> fmt::Debug for MultiPeekable<I>
+where
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiPeekable")
+            .field("iter", &self.iter)
+            .field("buffer", &self.buffer)
+            .field("cursor", &self.cursor)
+            .finish()
+    }
+}
+
+/// An iterator whose `next` may fail, like reading records from a file or a
+/// socket.
+///
+/// [`collect`](FallibleIterator::collect) builds any collection implementing
+/// [`FromIterator`], as every collection in the crate does, stopping at the
+/// first error. [`convert`] turns an iterator of `Result`s, such as
+/// [`BufRead::lines`](std::io::BufRead::lines), into a fallible iterator, and
+/// [`iterator`](FallibleIterator::iterator) goes the other way.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, BufRead, Cursor};
+/// use my_std::iter::{self, FallibleIterator};
+/// use my_std::skip_list::SkipMap;
+///
+/// let parse = |input: &str| {
+///     iter::convert(Cursor::new(input).lines())
+///         .filter(|line| Ok(!line.is_empty()))
+///         .map(|line| {
+///             let (key, value) = line.split_once('=').ok_or(io::ErrorKind::InvalidData)?;
+///             Ok((key.to_string(), value.to_string()))
+///         })
+///         .collect::<SkipMap<_, _>>()
+/// };
+///
+/// let config = parse("b=2\n\na=1\n").unwrap();
+/// assert_eq!(config.first_key_value(), Some((&"a".to_string(), &"1".to_string())));
+/// let err = parse("a=1\nnonsense\n").unwrap_err();
+/// assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+/// ```
+pub trait FallibleIterator {
+    /// The type of the items.
+    type Item;
+    /// The type of the errors.
+    type Error;
+
+    /// Advance and return the next item, `None` at the end, or an error.
+    fn next(&mut self) -> Result<Option<Self::Item>, Self::Error>;
+
+    /// Bounds on the number of items left, as [`Iterator::size_hint`]. An
+    /// error may still cut the iteration short.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+
+    /// Transform each item with `f`, which may fail too.
+    fn map<B, F>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> Result<B, Self::Error>,
+    {
+        Map { iter: self, f }
+    }
+
+    /// Keep the items `f` accepts. `f` may fail too.
+    fn filter<F>(self, f: F) -> Filter<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&Self::Item) -> Result<bool, Self::Error>,
+    {
+        Filter { iter: self, f }
+    }
+
+    /// Transform the errors with `f`.
+    fn map_err<E, F>(self, f: F) -> MapErr<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Self::Error) -> E,
+    {
+        MapErr { iter: self, f }
+    }
+
+    /// Fold every item into an accumulator, stopping at the first error.
+    fn fold<B, F>(mut self, init: B, mut f: F) -> Result<B, Self::Error>
+    where
+        Self: Sized,
+        F: FnMut(B, Self::Item) -> Result<B, Self::Error>,
+    {
+        let mut acc = init;
+        while let Some(item) = self.next()? {
+            acc = f(acc, item)?;
+        }
+        Ok(acc)
+    }
+
+    /// Call `f` on every item, stopping at the first error.
+    fn for_each<F>(self, mut f: F) -> Result<(), Self::Error>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> Result<(), Self::Error>,
+    {
+        self.fold((), |(), item| f(item))
+    }
+
+    /// Count the items, stopping at the first error.
+    fn count(self) -> Result<usize, Self::Error>
+    where
+        Self: Sized,
+    {
+        self.fold(0, |n, _| Ok(n + 1))
+    }
+
+    /// Build a collection from the items, or return the first error. The
+    /// items read before the error are dropped.
+    fn collect<B>(mut self) -> Result<B, Self::Error>
+    where
+        Self: Sized,
+        B: FromIterator<Self::Item>,
+    {
+        let mut error = None;
+        let collection = core::iter::from_fn(|| match self.next() {
+            Ok(item) => item,
+            Err(e) => {
+                error = Some(e);
+                None
+            }
+        })
+        .collect();
+        match error {
+            Some(e) => Err(e),
+            None => Ok(collection),
+        }
+    }
+
+    /// An [`Iterator`] over the results, ending after the first error.
+    fn iterator(self) -> IntoResults<Self>
+    where
+        Self: Sized,
+    {
+        IntoResults {
+            iter: self,
+            done: false,
+        }
+    }
+}
+
+impl<I: FallibleIterator + ?Sized> FallibleIterator for &mut I {
+    type Item = I::Item;
+    type Error = I::Error;
+
+    fn next(&mut self) -> Result<Option<I::Item>, I::Error> {
+        (**self).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (**self).size_hint()
+    }
+}
+
+/// Turn an iterator of `Result`s into a [`FallibleIterator`].
+pub fn convert<T, E, I>(iter: I) -> Convert<I::IntoIter>
+where
+    I: IntoIterator<Item = Result<T, E>>,
+{
+    Convert {
+        iter: iter.into_iter(),
+    }
+}
+
+/// Fallible iterator returned by [`convert`].
+#[derive(Debug, Clone)]
+pub struct Convert<I> {
+    iter: I,
+}
+
+impl<T, E, I: Iterator<Item = Result<T, E>>> FallibleIterator for Convert<I> {
+    type Item = T;
+    type Error = E;
+
+    fn next(&mut self) -> Result<Option<T>, E> {
+        self.iter.next().transpose()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Fallible iterator returned by [`FallibleIterator::map`].
+#[derive(Debug, Clone)]
+pub struct Map<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<B, I, F> FallibleIterator for Map<I, F>
+where
+    I: FallibleIterator,
+    F: FnMut(I::Item) -> Result<B, I::Error>,
+{
+    type Item = B;
+    type Error = I::Error;
+
+    fn next(&mut self) -> Result<Option<B>, I::Error> {
+        self.iter.next()?.map(&mut self.f).transpose()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Fallible iterator returned by [`FallibleIterator::filter`].
+#[derive(Debug, Clone)]
+pub struct Filter<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<I, F> FallibleIterator for Filter<I, F>
+where
+    I: FallibleIterator,
+    F: FnMut(&I::Item) -> Result<bool, I::Error>,
+{
+    type Item = I::Item;
+    type Error = I::Error;
+
+    fn next(&mut self) -> Result<Option<I::Item>, I::Error> {
+        while let Some(item) = self.iter.next()? {
+            if (self.f)(&item)? {
+                return Ok(Some(item));
+            }
+        }
+        Ok(None)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/// Fallible iterator returned by [`FallibleIterator::map_err`].
+#[derive(Debug, Clone)]
+pub struct MapErr<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<E, I, F> FallibleIterator for MapErr<I, F>
+where
+    I: FallibleIterator,
+    F: FnMut(I::Error) -> E,
+{
+    type Item = I::Item;
+    type Error = E;
+
+    fn next(&mut self) -> Result<Option<I::Item>, E> {
+        self.iter.next().map_err(&mut self.f)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Iterator returned by [`FallibleIterator::iterator`].
+#[derive(Debug, Clone)]
+pub struct IntoResults<I> {
+    iter: I,
+    // Set after an error, which ends the iteration.
+    done: bool,
+}
+
+impl<I: FallibleIterator> Iterator for IntoResults<I> {
+    type Item = Result<I::Item, I::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.iter.next().transpose();
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+        // An error may come at any point, and ends the iteration: in place
+        // of the first item, or after the last one.
+        let (lo, hi) = self.iter.size_hint();
+        (lo.min(1), hi.and_then(|hi| hi.checked_add(1)))
+    }
+}
+
+impl<I: FallibleIterator> FusedIterator for IntoResults<I> {}
+
+/// An iterator whose items may borrow from the iterator itself, so that each
+/// item must be dropped before asking for the next.
+///
+/// An [`Iterator`] can't do that, since all its items may be kept at once.
+/// That is what rules out overlapping mutable views, like
+/// [`windows_mut`], or handing out windows from a buffer the iterator reuses,
+/// like [`IteratorExt::lending_windows`].
+///
+/// # Examples
+///
+/// ```
+/// use my_std::iter::{self, LendingIterator};
+///
+/// // A running sum, computed in place over overlapping windows.
+/// let mut v = [1, 2, 3, 4];
+/// let mut windows = iter::windows_mut(&mut v, 2);
+/// while let Some(w) = windows.next() {
+///     w[1] += w[0];
+/// }
+/// assert_eq!(v, [1, 3, 6, 10]);
+/// ```
+///
+/// There is no `for_each` or `map`: a closure taking items of any lifetime
+/// would need `Self: 'static`, a limit of generic associated types today, so
+/// loop with `while let` instead.
+pub trait LendingIterator {
+    /// The type of the items, borrowing from the iterator for `'a`.
+    type Item<'a>
+    where
+        Self: 'a;
+
+    /// Advance and return the next item.
+    fn next(&mut self) -> Option<Self::Item<'_>>;
+
+    /// Bounds on the number of items left, as [`Iterator::size_hint`].
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+
+    /// Count the items.
+    fn count(mut self) -> usize
+    where
+        Self: Sized,
+    {
+        let mut n = 0;
+        while self.next().is_some() {
+            n += 1;
+        }
+        n
+    }
+}
+
+impl<I: LendingIterator + ?Sized> LendingIterator for &mut I {
+    type Item<'a>
+        = I::Item<'a>
+    where
+        Self: 'a;
+
+    fn next(&mut self) -> Option<I::Item<'_>> {
+        (**self).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (**self).size_hint()
+    }
+}
+
+/// Mutable views of every run of `size` consecutive elements of `slice`,
+/// overlapping by all but one element. Works on any contiguous collection,
+/// such as [`SmallVec`](crate::small_vec::SmallVec). Panics if `size` is
+/// zero.
+pub fn windows_mut<T>(slice: &mut [T], size: usize) -> WindowsMut<'_, T> {
+    assert!(size > 0, "window size must be positive");
+    WindowsMut {
+        slice,
+        size,
+        start: 0,
+    }
+}
+
+/// Lending iterator returned by [`windows_mut`].
+#[derive(Debug)]
+pub struct WindowsMut<'s, T> {
+    slice: &'s mut [T],
+    size: usize,
+    // Start of the next window.
+    start: usize,
+}
+
+impl<T> LendingIterator for WindowsMut<'_, T> {
+    type Item<'a>
+        = &'a mut [T]
+    where
+        Self: 'a;
+
+    fn next(&mut self) -> Option<&mut [T]> {
+        let start = self.start;
+        let window = self.slice.get_mut(start..start + self.size)?;
+        self.start += 1;
+        Some(window)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = (self.slice.len() + 1).saturating_sub(self.start + self.size);
+        (left, Some(left))
+    }
+}
+
+/// Lending iterator returned by [`IteratorExt::lending_windows`].
+pub struct LendingWindows<I: Iterator> {
+    iter: Fuse<I>,
+    // The current window is `buffer[start..]`, once it holds `size` items.
+    // Items before it are dropped in batches, so each item is moved once on
+    // average.
+    buffer: Vec<I::Item>,
+    start: usize,
+    size: usize,
+}
+
+impl<I: Iterator> LendingIterator for LendingWindows<I> {
+    type Item<'a>
+        = &'a [I::Item]
+    where
+        Self: 'a;
+
+    fn next(&mut self) -> Option<&[I::Item]> {
+        if self.buffer.len() - self.start == self.size {
+            let next = self.iter.next()?;
+            self.buffer.push(next);
+            self.start += 1;
+            if self.start >= self.size {
+                self.buffer.drain(..self.start);
+                self.start = 0;
+            }
+        }
+        while self.buffer.len() < self.size {
+            self.buffer.push(self.iter.next()?);
+        }
+        Some(&self.buffer[self.start..])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let kept = (self.buffer.len() - self.start).min(self.size - 1);
+        let windows = |left: usize| (kept + left + 1).saturating_sub(self.size);
+        let (lo, hi) = self.iter.size_hint();
+        (windows(lo), hi.map(windows))
+    }
+}
+
+impl<I: Iterator + fmt::Debug> fmt::Debug for LendingWindows<I>
+where
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LendingWindows")
+            .field("iter", &self.iter)
+            .field("window", &&self.buffer[self.start..])
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+    use std::cell::Cell;
+
+    // Checks that `size_hint` brackets the number of items left at every
+    // step, on fresh iterators from `make`.
+    fn check_size_hints<I: Iterator>(make: impl Fn() -> I) {
+        let total = make().count();
+        for taken in 0..=total {
+            let mut iter = make();
+            iter.by_ref().take(taken).for_each(drop);
+            let left = total - taken;
+            let (lo, hi) = iter.size_hint();
+            assert!(lo <= left && hi.is_none_or(|hi| left <= hi));
+        }
+    }
+
+    #[test]
+    fn chunks_and_windows() {
+        let chunks: Vec<_> = (1..=7).chunks(3).collect();
+        assert_eq!(chunks, [vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+        assert_eq!((0..0).chunks(3).next(), None);
+        check_size_hints(|| (1..=7).chunks(3));
+
+        let windows: Vec<_> = "abcd".chars().windows(2).collect();
+        assert_eq!(windows, [vec!['a', 'b'], vec!['b', 'c'], vec!['c', 'd']]);
+        assert_eq!((0..2).windows(3).next(), None);
+        check_size_hints(|| (0..10).windows(4));
+        check_size_hints(|| (0..3).windows(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be positive")]
+    fn empty_chunks() {
+        (0..3).chunks(0);
+    }
+
+    #[test]
+    fn dedup_keeps_the_first_of_each_run() {
+        let words = ["a", "a", "b", "a", "c", "c"];
+        let deduped: Vec<_> = words.into_iter().dedup().collect();
+        assert_eq!(deduped, ["a", "b", "a", "c"]);
+        check_size_hints(|| words.into_iter().dedup());
+    }
+
+    #[test]
+    fn intersperse_puts_separators_between_items() {
+        let joined: String = IteratorExt::intersperse(["x", "y", "z"].into_iter(), ", ").collect();
+        assert_eq!(joined, "x, y, z");
+        assert_eq!(IteratorExt::intersperse(0..1, 9).collect::<Vec<_>>(), [0]);
+        assert_eq!(IteratorExt::intersperse(0..0, 9).next(), None);
+        check_size_hints(|| IteratorExt::intersperse(0..5, 9));
+    }
+
+    #[test]
+    fn step_by_from_starts_at_an_offset() {
+        let picked: Vec<_> = (0..20).step_by_from(3, 5).collect();
+        assert_eq!(picked, [3, 8, 13, 18]);
+        assert_eq!((0..3).step_by_from(5, 1).next(), None);
+    }
+
+    #[test]
+    fn multi_peekable_looks_ahead() {
+        let mut iter = (1..=5).multi_peekable();
+        assert_eq!(iter.peek_nth(3), Some(&4));
+        assert_eq!(iter.size_hint(), (5, Some(5)));
+        assert_eq!(iter.peek(), Some(&1));
+        assert_eq!(iter.peek(), Some(&2));
+        *iter.peek_nth_mut(1).unwrap() = 20;
+        assert_eq!(iter.peek(), Some(&3));
+        iter.reset_peek();
+        assert_eq!(iter.peek(), Some(&1));
+        assert_eq!(iter.next_if(|&x| x > 1), None);
+        assert_eq!(iter.next_if(|&x| x == 1), Some(1));
+        // `next` moved the cursor back too.
+        assert_eq!(iter.peek(), Some(&20));
+        assert_eq!(iter.peek_nth(10), None);
+        check_size_hints(|| {
+            let mut iter = (1..=5).multi_peekable();
+            iter.peek_nth(2);
+            iter
+        });
+        assert_eq!(iter.collect::<Vec<_>>(), [20, 3, 4, 5]);
+    }
+
+    #[test]
+    fn multi_peekable_cursor_stops_at_the_end() {
+        let mut iter = "ab".chars().multi_peekable();
+        assert_eq!(iter.peek(), Some(&'a'));
+        assert_eq!(iter.peek(), Some(&'b'));
+        assert_eq!(iter.peek(), None);
+        assert_eq!(iter.peek(), None);
+        iter.reset_peek();
+        assert_eq!(iter.peek(), Some(&'a'));
+    }
+
+    #[test]
+    fn cartesian_product_is_lazy() {
+        let pulled = Cell::new(0);
+        let counted = (0..).inspect(|_| pulled.set(pulled.get() + 1));
+        let mut pairs = counted.cartesian_product(["a", "b"]);
+        assert_eq!(pulled.get(), 0);
+        assert_eq!(pairs.next(), Some((0, "a")));
+        assert_eq!(pairs.next(), Some((0, "b")));
+        assert_eq!(pulled.get(), 1);
+        assert_eq!(pairs.next(), Some((1, "a")));
+
+        let pairs: Vec<_> = (1..=2).cartesian_product(1..=3).collect();
+        assert_eq!(pairs, [(1, 1), (1, 2), (1, 3), (2, 1), (2, 2), (2, 3)]);
+        assert_eq!((0..3).cartesian_product(0..0).next(), None);
+        check_size_hints(|| (0..3).cartesian_product(0..4));
+        check_size_hints(|| (0..3).filter(|x| x % 2 == 0).cartesian_product(0..4));
+    }
+
+    #[test]
+    fn group_by_collects_runs() {
+        let words = ["apple", "avocado", "banana", "blueberry", "apricot"];
+        let pulled = Cell::new(0);
+        let mut groups = words
+            .into_iter()
+            .inspect(|_| pulled.set(pulled.get() + 1))
+            .group_by(|w| w.as_bytes()[0]);
+        assert_eq!(pulled.get(), 0);
+        assert_eq!(groups.next(), Some((b'a', vec!["apple", "avocado"])));
+        // One item past the group.
+        assert_eq!(pulled.get(), 3);
+        assert_eq!(groups.next(), Some((b'b', vec!["banana", "blueberry"])));
+        assert_eq!(groups.next(), Some((b'a', vec!["apricot"])));
+        assert_eq!(groups.next(), None);
+        check_size_hints(|| [1, 1, 2, 3, 3].into_iter().group_by(|&x| x));
+    }
+
+    #[test]
+    fn kmerge_merges_sorted_iterators() {
+        let merged: Vec<_> = [vec![1, 4, 7], vec![2, 5, 8], vec![], vec![3, 6, 9]]
+            .into_iter()
+            .kmerge()
+            .collect();
+        assert_eq!(merged, (1..=9).collect::<Vec<_>>());
+        check_size_hints(|| [vec![1, 3], vec![2]].into_iter().kmerge());
+
+        // Ordered by the first field only, to see which iterator ties come from.
+        #[derive(Debug, PartialEq, Eq)]
+        struct Keyed(u32, char);
+        impl Ord for Keyed {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+        impl PartialOrd for Keyed {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        let a = [Keyed(1, 'a'), Keyed(2, 'a')];
+        let b = [Keyed(1, 'b'), Keyed(2, 'b')];
+        let tags: String = [a, b].into_iter().kmerge().map(|k| k.1).collect();
+        assert_eq!(tags, "abab");
+    }
+
+    #[test]
+    fn sorted_adapters() {
+        let words = ["pear", "fig", "apple", "kiwi", "banana", "fig"];
+        let sorted: Vec<_> = words.iter().sorted().collect();
+        assert_eq!(
+            sorted,
+            [&"apple", &"banana", &"fig", &"fig", &"kiwi", &"pear"]
+        );
+        // Stable: words of the same length stay in input order.
+        let by_len: Vec<_> = words
+            .iter()
+            .sorted_by(|a, b| a.len().cmp(&b.len()))
+            .collect();
+        assert_eq!(
+            by_len,
+            [&"fig", &"fig", &"pear", &"kiwi", &"apple", &"banana"]
+        );
+
+        let scrambled = (0..1000).map(|i| i * 7919 % 1000);
+        assert_eq!(
+            scrambled.clone().k_smallest(5).collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4]
+        );
+        assert_eq!(scrambled.clone().k_smallest(2000).count(), 1000);
+        assert_eq!(scrambled.k_smallest(0).next(), None);
+        assert_eq!(
+            [3, 1, 3, 1].into_iter().k_smallest(3).collect::<Vec<_>>(),
+            [1, 1, 3]
+        );
+    }
+
+    #[test]
+    fn kmerge_is_lazy() {
+        let opened = Cell::new(0);
+        let iters = (0..3).map(|i| {
+            opened.set(opened.get() + 1);
+            i..i + 1
+        });
+        let mut merged = iters.kmerge();
+        assert_eq!(opened.get(), 0);
+        assert_eq!(merged.next(), Some(0));
+        assert_eq!(opened.get(), 3);
+    }
+
+    #[test]
+    fn fallible_collect_stops_at_the_first_error() {
+        let ok: Result<Vec<_>, &str> = convert([Ok(1), Ok(2), Ok(3)])
+            .map(|x| Ok(x * 10))
+            .filter(|x| Ok(*x > 10))
+            .collect();
+        assert_eq!(ok, Ok(vec![20, 30]));
+
+        let read = Cell::new(0);
+        let items = [Ok(1), Err("bad"), Ok(3)]
+            .into_iter()
+            .inspect(|_| read.set(read.get() + 1));
+        let failed: Result<crate::skip_list::SkipSet<_>, _> = convert(items).collect();
+        assert_eq!(failed.unwrap_err(), "bad");
+        // Nothing is read past the error.
+        assert_eq!(read.get(), 2);
+
+        let failed = convert([Ok(1), Err(7)]).map_err(|e| e * 2).count();
+        assert_eq!(failed, Err(14));
+        assert_eq!(
+            convert([Ok::<_, ()>(1), Ok(2)]).fold(0, |a, x| Ok(a + x)),
+            Ok(3)
+        );
+    }
+
+    #[test]
+    fn fallible_iterator_into_results() {
+        let mut source = convert([Ok(1), Err("bad"), Ok(3)]);
+        assert_eq!(FallibleIterator::next(&mut source), Ok(Some(1)));
+        let rest: Vec<_> = source.iterator().collect();
+        assert_eq!(rest, [Err("bad")]);
+        check_size_hints(|| convert([Ok::<_, ()>(1), Ok(2)]).iterator());
+        check_size_hints(|| convert([Ok(1), Err(()), Ok(2)]).iterator());
+    }
+
+    // Checks `size_hint` against the number of items left at every step.
+    fn check_lending_size_hints<L: LendingIterator>(make: impl Fn() -> L) {
+        let total = make().count();
+        for taken in 0..=total {
+            let mut iter = make();
+            for _ in 0..taken {
+                iter.next();
+            }
+            let (lo, hi) = iter.size_hint();
+            let left = total - taken;
+            assert!(lo <= left && hi.is_none_or(|hi| left <= hi));
+        }
+    }
+
+    #[test]
+    fn windows_mut_overlap() {
+        let mut v: crate::small_vec::SmallVec<u32, 4> = (1..=5).collect();
+        // Each window sees the previous window's writes.
+        let mut windows = windows_mut(&mut v, 3);
+        while let Some(w) = windows.next() {
+            w[2] = w[0] + w[1];
+        }
+        assert_eq!(*v, [1, 2, 3, 5, 8]);
+        assert_eq!(windows_mut(&mut [0; 2], 3).next(), None);
+        let mut data = [0; 5];
+        assert_eq!(windows_mut(&mut data, 5).count(), 1);
+        let total = 4;
+        let mut windows = windows_mut(&mut data, 2);
+        for left in (0..=total).rev() {
+            assert_eq!(windows.size_hint(), (left, Some(left)));
+            windows.next();
+        }
+    }
+
+    #[test]
+    fn lending_windows_move_each_item_once() {
+        // `String`s aren't `Copy`, and are only ever moved into the buffer.
+        let words = ["a", "b", "c", "d", "e"].map(String::from);
+        let mut joined = Vec::new();
+        let mut windows = words.into_iter().lending_windows(3);
+        while let Some(w) = windows.next() {
+            joined.push(w.concat());
+        }
+        assert_eq!(joined, ["abc", "bcd", "cde"]);
+        check_lending_size_hints(|| (0..10).lending_windows(4));
+        check_lending_size_hints(|| (0..2).lending_windows(4));
+    }
+}
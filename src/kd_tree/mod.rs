@@ -0,0 +1,469 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+
+use crate::spatial::SpatialIndex;
+
+type Link<T, const K: usize> = Option<Box<Node<T, K>>>;
+
+struct Node<T, const K: usize> {
+    point: [f64; K],
+    value: T,
+    left: Link<T, K>,
+    right: Link<T, K>,
+}
+
+fn distance2<const K: usize>(a: &[f64; K], b: &[f64; K]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// A k-d tree mapping points in `K`-dimensional space to values.
+///
+/// Each level of the tree splits space along one axis, cycling through the axes
+/// with depth. [`KdTree::build`] splits at the median of every subtree to get a
+/// balanced tree, while [`KdTree::insert`] adds points one by one without
+/// rebalancing. Searches skip every subtree whose half-space can't hold a better
+/// answer, so they take O(log n) on well-spread data.
+///
+/// Distances are Euclidean. Coordinates must not be NaN.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::kd_tree::KdTree;
+///
+/// let tree = KdTree::build(vec![
+///     ([0.0, 0.0], "origin"),
+///     ([5.0, 5.0], "center"),
+///     ([9.0, 1.0], "east"),
+/// ]);
+/// assert_eq!(tree.nearest(&[8.0, 0.0]), Some((&[9.0, 1.0], &"east")));
+///
+/// let two: Vec<_> = tree.k_nearest(&[1.0, 1.0], 2).into_iter().map(|(_, v)| *v).collect();
+/// assert_eq!(two, vec!["origin", "center"]);
+///
+/// let inside = tree.range(&[-1.0, -1.0], &[6.0, 6.0]);
+/// assert_eq!(inside.len(), 2);
+/// ```
+pub struct KdTree<T, const K: usize> {
+    root: Link<T, K>,
+    len: usize,
+}
+
+impl<T, const K: usize> Default for KdTree<T, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const K: usize> KdTree<T, K> {
+    /// Create a new, empty tree.
+    pub const fn new() -> Self {
+        KdTree { root: None, len: 0 }
+    }
+
+    /// Build a balanced tree from a set of points by splitting at the median.
+    /// O(n log n).
+    pub fn build(points: Vec<([f64; K], T)>) -> Self {
+        let len = points.len();
+        KdTree {
+            root: Self::build_node(points, 0),
+            len,
+        }
+    }
+
+    fn build_node(mut points: Vec<([f64; K], T)>, depth: usize) -> Link<T, K> {
+        if points.is_empty() {
+            return None;
+        }
+        let axis = depth % K;
+        let mid = points.len() / 2;
+        points.select_nth_unstable_by(mid, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+        let right = points.split_off(mid + 1);
+        let (point, value) = points.pop().unwrap();
+        Some(Box::new(Node {
+            point,
+            value,
+            left: Self::build_node(points, depth + 1),
+            right: Self::build_node(right, depth + 1),
+        }))
+    }
+
+    /// Number of points in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a point. Points that compare equal are all kept.
+    pub fn insert(&mut self, point: [f64; K], value: T) {
+        let mut link = &mut self.root;
+        let mut depth = 0;
+        while let Some(node) = link {
+            let axis = depth % K;
+            link = if point[axis] < node.point[axis] {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+            depth += 1;
+        }
+        *link = Some(Box::new(Node {
+            point,
+            value,
+            left: None,
+            right: None,
+        }));
+        self.len += 1;
+    }
+
+    /// The point closest to `query`.
+    pub fn nearest(&self, query: &[f64; K]) -> Option<(&[f64; K], &T)> {
+        self.k_nearest(query, 1).pop()
+    }
+
+    /// The `k` points closest to `query`, closest first.
+    pub fn k_nearest(&self, query: &[f64; K], k: usize) -> Vec<(&[f64; K], &T)> {
+        if k == 0 {
+            return Vec::new();
+        }
+        // Best candidates so far, sorted by distance.
+        let mut best: Vec<(f64, &Node<T, K>)> = Vec::with_capacity(k + 1);
+        Self::search(&self.root, query, k, 0, &mut best);
+        best.into_iter()
+            .map(|(_, node)| (&node.point, &node.value))
+            .collect()
+    }
+
+    fn search<'a>(
+        link: &'a Link<T, K>,
+        query: &[f64; K],
+        k: usize,
+        depth: usize,
+        best: &mut Vec<(f64, &'a Node<T, K>)>,
+    ) {
+        let Some(node) = link else {
+            return;
+        };
+        let d = distance2(query, &node.point);
+        if best.len() < k || d < best[best.len() - 1].0 {
+            let pos = best.partition_point(|(other, _)| *other <= d);
+            best.insert(pos, (d, node));
+            best.truncate(k);
+        }
+        let axis = depth % K;
+        let diff = query[axis] - node.point[axis];
+        let (near, far) = if diff < 0.0 {
+            (&node.left, &node.right)
+        } else {
+            (&node.right, &node.left)
+        };
+        Self::search(near, query, k, depth + 1, best);
+        // The far side can only hold closer points if the splitting plane is closer
+        // than the current worst candidate.
+        if best.len() < k || diff * diff < best[best.len() - 1].0 {
+            Self::search(far, query, k, depth + 1, best);
+        }
+    }
+
+    /// Every point inside the axis-aligned box from `min` to `max`, bounds included.
+    pub fn range(&self, min: &[f64; K], max: &[f64; K]) -> Vec<(&[f64; K], &T)> {
+        let mut res = Vec::new();
+        Self::range_node(&self.root, min, max, 0, &mut res);
+        res
+    }
+
+    fn range_node<'a>(
+        link: &'a Link<T, K>,
+        min: &[f64; K],
+        max: &[f64; K],
+        depth: usize,
+        res: &mut Vec<(&'a [f64; K], &'a T)>,
+    ) {
+        let Some(node) = link else {
+            return;
+        };
+        if (0..K).all(|i| min[i] <= node.point[i] && node.point[i] <= max[i]) {
+            res.push((&node.point, &node.value));
+        }
+        let axis = depth % K;
+        // A median split can leave points equal to the node on either side.
+        if min[axis] <= node.point[axis] {
+            Self::range_node(&node.left, min, max, depth + 1, res);
+        }
+        if node.point[axis] <= max[axis] {
+            Self::range_node(&node.right, min, max, depth + 1, res);
+        }
+    }
+
+    /// Iterate over every point in the tree, in no particular order.
+    pub fn iter(&self) -> Iter<'_, T, K> {
+        Iter {
+            stack: self.root.as_deref().into_iter().collect(),
+            remaining: self.len,
+        }
+    }
+}
+
+impl<T: fmt::Debug, const K: usize> fmt::Debug for KdTree<T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const K: usize> Extend<([f64; K], T)> for KdTree<T, K> {
+    /// Insert the points one by one, without rebalancing. An empty tree is
+    /// built balanced instead, like `collect` does.
+    fn extend<I: IntoIterator<Item = ([f64; K], T)>>(&mut self, iter: I) {
+        if self.is_empty() {
+            *self = iter.into_iter().collect();
+            return;
+        }
+        for (point, value) in iter {
+            self.insert(point, value);
+        }
+    }
+}
+
+impl<T, const K: usize> FromIterator<([f64; K], T)> for KdTree<T, K> {
+    fn from_iter<I: IntoIterator<Item = ([f64; K], T)>>(iter: I) -> Self {
+        KdTree::build(iter.into_iter().collect())
+    }
+}
+
+impl<T, const K: usize> SpatialIndex<T, K> for KdTree<T, K> {
+    fn insert_point(&mut self, point: [f64; K], value: T) {
+        self.insert(point, value);
+    }
+
+    fn nearest_to(&self, query: &[f64; K]) -> Option<&T> {
+        self.nearest(query).map(|(_, v)| v)
+    }
+
+    fn in_box(&self, min: &[f64; K], max: &[f64; K]) -> Vec<&T> {
+        self.range(min, max).into_iter().map(|(_, v)| v).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Iterator over the points of a [`KdTree`].
+pub struct Iter<'a, T, const K: usize> {
+    stack: Vec<&'a Node<T, K>>,
+    remaining: usize,
+}
+
+impl<'a, T, const K: usize> Iterator for Iter<'a, T, K> {
+    type Item = (&'a [f64; K], &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        self.remaining -= 1;
+        Some((&node.point, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, const K: usize> IntoIterator for &'a KdTree<T, K> {
+    type Item = (&'a [f64; K], &'a T);
+    type IntoIter = Iter<'a, T, K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A tree serializes as a sequence of `(point, value)` pairs in preorder, so
+/// inserting them back in that order rebuilds the same shape. Points are
+/// sequences of `K` coordinates.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, const K: usize> serde::Serialize for KdTree<T, K> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(|(point, value)| (&point[..], value)))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, const K: usize> serde::Deserialize<'de> for KdTree<T, K> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let points: Vec<(Vec<f64>, T)> = serde::Deserialize::deserialize(deserializer)?;
+        points
+            .into_iter()
+            .map(|(point, value)| match <[f64; K]>::try_from(point) {
+                Ok(point) => Ok((point, value)),
+                Err(point) => Err(serde::de::Error::custom(format_args!(
+                    "a point of {} coordinates in a tree of {K} dimensions",
+                    point.len()
+                ))),
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>, const K: usize> arbitrary::Arbitrary<'a> for KdTree<T, K> {
+    /// Points are inserted one at a time, so the tree can come out unbalanced.
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut tree = KdTree::new();
+        for _ in 0..u.arbitrary_len::<([i32; K], T)>()? {
+            let point = u.arbitrary::<[i32; K]>()?.map(crate::spatial::coord);
+            tree.insert(point, u.arbitrary()?);
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary + 'static, const K: usize> proptest::arbitrary::Arbitrary
+    for KdTree<T, K>
+{
+    type Parameters = (proptest::collection::SizeRange, T::Parameters);
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    /// Points are inserted one at a time, possibly sorted along the first
+    /// axis, which leaves the tree unbalanced.
+    fn arbitrary_with((size, args): Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        let point =
+            proptest::arbitrary::any::<[i32; K]>().prop_map(|p| p.map(crate::spatial::coord));
+        let points =
+            proptest::collection::vec((point, proptest::arbitrary::any_with::<T>(args)), size);
+        (points, 0..3u8)
+            .prop_map(|(points, order)| {
+                let mut tree = KdTree::new();
+                let by_first = |a: &([f64; K], T), b: &([f64; K], T)| a.0[0].total_cmp(&b.0[0]);
+                for (point, value) in crate::proptest_impl::sorted(points, order, by_first) {
+                    tree.insert(point, value);
+                }
+                tree
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn by_distance<const K: usize>(
+        query: &[f64; K],
+    ) -> impl Fn(&[f64; K], &[f64; K]) -> Ordering + '_ {
+        move |a, b| distance2(query, a).total_cmp(&distance2(query, b))
+    }
+
+    fn points(n: usize) -> Vec<[f64; 3]> {
+        let mut x = 0x2545_f491_u64;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            (x % 10_000) as f64 / 100.0
+        };
+        (0..n).map(|_| [next(), next(), next()]).collect()
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let pts = points(500);
+        let built: KdTree<_, 3> = pts
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(i, p)| (p, i))
+            .collect();
+        let mut inserted = KdTree::new();
+        for (i, p) in pts.iter().enumerate() {
+            inserted.insert(*p, i);
+        }
+        assert_eq!(built.len(), 500);
+        for query in points(50).iter().map(|p| [p[1], p[2], p[0]]) {
+            let expected = pts.iter().min_by(|a, b| by_distance(&query)(a, b)).unwrap();
+            assert_eq!(built.nearest(&query).unwrap().0, expected);
+            assert_eq!(inserted.nearest(&query).unwrap().0, expected);
+        }
+    }
+
+    #[test]
+    fn k_nearest_matches_brute_force() {
+        let pts = points(300);
+        let tree: KdTree<_, 3> = pts.iter().map(|p| (*p, ())).collect();
+        let query = [50.0, 50.0, 50.0];
+        let mut expected = pts.clone();
+        expected.sort_by(by_distance(&query));
+        let found: Vec<_> = tree
+            .k_nearest(&query, 10)
+            .into_iter()
+            .map(|(p, _)| *p)
+            .collect();
+        assert_eq!(found, expected[..10]);
+        assert_eq!(tree.k_nearest(&query, 1000).len(), 300);
+        assert!(tree.k_nearest(&query, 0).is_empty());
+    }
+
+    #[test]
+    fn range_matches_brute_force() {
+        let pts = points(400);
+        let tree: KdTree<_, 3> = pts.iter().map(|p| (*p, ())).collect();
+        let (min, max) = ([10.0, 20.0, 0.0], [60.0, 70.0, 50.0]);
+        let mut found: Vec<_> = tree
+            .range(&min, &max)
+            .into_iter()
+            .map(|(p, _)| *p)
+            .collect();
+        let mut expected: Vec<_> = pts
+            .into_iter()
+            .filter(|p| (0..3).all(|i| min[i] <= p[i] && p[i] <= max[i]))
+            .collect();
+        found.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn empty() {
+        let tree: KdTree<(), 2> = KdTree::new();
+        assert_eq!(tree.nearest(&[0.0, 0.0]), None);
+        assert!(tree.range(&[0.0, 0.0], &[1.0, 1.0]).is_empty());
+        assert_eq!(tree.iter().count(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let tree = KdTree::build(Vec::from([
+            ([0.0, 0.0], 'a'),
+            ([5.0, 5.0], 'b'),
+            ([9.0, 1.0], 'c'),
+        ]));
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(json, r#"[[[5.0,5.0],"b"],[[0.0,0.0],"a"],[[9.0,1.0],"c"]]"#);
+        let back: KdTree<char, 2> = serde_json::from_str(&json).unwrap();
+        assert!(back.iter().eq(tree.iter()));
+        assert!(serde_json::from_str::<KdTree<char, 2>>(r#"[[[1.0],"a"]]"#).is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes: [u8; 256] = core::array::from_fn(|i| (i * 37) as u8);
+        let tree = KdTree::<u8, 3>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert_eq!(tree.iter().count(), tree.len());
+        assert!(tree
+            .iter()
+            .all(|(point, _)| point.iter().all(|x| x.is_finite())));
+    }
+}
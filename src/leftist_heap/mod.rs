@@ -0,0 +1,538 @@
+use alloc::collections::{BinaryHeap, VecDeque};
+use alloc::{boxed::Box, vec::Vec};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::{fmt, mem};
+
+type Link<T> = Option<Box<Node<T>>>;
+
+/// Node of a leftist heap.
+struct Node<T> {
+    value: T,
+    // Length of the right spine of this subtree, which is never longer than the
+    // left one.
+    rank: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn new(value: T) -> Box<Self> {
+        Box::new(Node {
+            value,
+            rank: 1,
+            left: None,
+            right: None,
+        })
+    }
+}
+
+fn rank<T>(node: &Link<T>) -> usize {
+    node.as_ref().map_or(0, |node| node.rank)
+}
+
+// Merge two heaps along their right spines, which are O(log n) long.
+fn merge<T: Ord>(a: Link<T>, b: Link<T>) -> Link<T> {
+    let (mut a, b) = match (a, b) {
+        (None, node) | (node, None) => return node,
+        (Some(a), Some(b)) if a.value < b.value => (b, a),
+        (Some(a), Some(b)) => (a, b),
+    };
+    a.right = merge(a.right.take(), Some(b));
+    if rank(&a.left) < rank(&a.right) {
+        mem::swap(&mut a.left, &mut a.right);
+    }
+    a.rank = rank(&a.right) + 1;
+    Some(a)
+}
+
+/// A max-heap based on a leftist tree.
+///
+/// Every node's right spine is at most as long as its left one, so the right spine
+/// of the whole heap has O(log n) nodes. Two heaps are merged by walking their
+/// right spines, which makes [`LeftistHeap::meld`] O(log n) where a binary heap
+/// would need O(n). Pushing and popping are merges too.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::leftist_heap::LeftistHeap;
+///
+/// let mut a: LeftistHeap<_> = [3, 1, 4].into_iter().collect();
+/// let b: LeftistHeap<_> = [1, 5, 9].into_iter().collect();
+/// a.meld(b);
+/// assert_eq!(a.len(), 6);
+/// assert_eq!(a.pop(), Some(9));
+/// assert_eq!(a.peek(), Some(&5));
+/// assert_eq!(a.into_sorted_vec(), vec![1, 1, 3, 4, 5]);
+/// ```
+pub struct LeftistHeap<T> {
+    root: Link<T>,
+    len: usize,
+}
+
+impl<T> Default for LeftistHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LeftistHeap<T> {
+    /// Create a new, empty heap.
+    pub const fn new() -> Self {
+        LeftistHeap { root: None, len: 0 }
+    }
+
+    /// Number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The greatest element.
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.value)
+    }
+
+    /// Remove every element.
+    pub fn clear(&mut self) {
+        drop(mem::take(self));
+    }
+
+    /// Remove every element, returning them in no particular order.
+    ///
+    /// The heap is emptied right away, so it is empty even if the iterator is
+    /// leaked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::leftist_heap::LeftistHeap;
+    ///
+    /// let mut heap: LeftistHeap<_> = [3, 1, 2].into_iter().collect();
+    /// let mut drained: Vec<_> = heap.drain().collect();
+    /// drained.sort();
+    /// assert_eq!(drained, [1, 2, 3]);
+    /// assert!(heap.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain {
+            stack: self.root.take().into_iter().collect(),
+            remaining: mem::take(&mut self.len),
+            marker: PhantomData,
+        }
+    }
+
+    /// Iterate over the elements in no particular order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stack: self.root.as_deref().into_iter().collect(),
+            remaining: self.len,
+        }
+    }
+}
+
+impl<T: Ord> LeftistHeap<T> {
+    /// Add an element. O(log n).
+    pub fn push(&mut self, value: T) {
+        self.root = merge(self.root.take(), Some(Node::new(value)));
+        self.len += 1;
+    }
+
+    /// Remove and return the greatest element. O(log n).
+    pub fn pop(&mut self) -> Option<T> {
+        let mut root = self.root.take()?;
+        self.root = merge(root.left.take(), root.right.take());
+        self.len -= 1;
+        Some(root.value)
+    }
+
+    /// Move every element of `other` into this heap. O(log n).
+    pub fn meld(&mut self, mut other: LeftistHeap<T>) {
+        self.root = merge(self.root.take(), other.root.take());
+        self.len += mem::take(&mut other.len);
+    }
+
+    /// Keep only the elements for which `f` returns `true`, visiting them in
+    /// the order of [`LeftistHeap::iter`].
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.extract_if(|value| !f(value)).for_each(drop);
+    }
+
+    /// Return an iterator that removes and yields every element for which
+    /// `filter` returns `true`, in the order of [`LeftistHeap::iter`].
+    ///
+    /// The nodes are taken out of the heap up front and the kept elements are
+    /// pushed back as they are visited; dropping the iterator pushes back the
+    /// ones it didn't get to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::leftist_heap::LeftistHeap;
+    ///
+    /// let mut heap: LeftistHeap<_> = (1..=6).collect();
+    /// let mut even: Vec<_> = heap.extract_if(|x| x % 2 == 0).collect();
+    /// even.sort();
+    /// assert_eq!(even, [2, 4, 6]);
+    /// assert_eq!(heap.into_sorted_vec(), [1, 3, 5]);
+    /// ```
+    pub fn extract_if<F>(&mut self, filter: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.len = 0;
+        ExtractIf {
+            stack: self.root.take().into_iter().collect(),
+            heap: self,
+            filter,
+        }
+    }
+
+    /// Consume the heap and return its elements in ascending order.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut vec = Vec::with_capacity(self.len);
+        while let Some(value) = self.pop() {
+            vec.push(value);
+        }
+        vec.reverse();
+        vec
+    }
+}
+
+impl<T> Drop for LeftistHeap<T> {
+    fn drop(&mut self) {
+        // Left spines can be O(n) long, so free the nodes without recursing.
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: Ord + Clone> Clone for LeftistHeap<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LeftistHeap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord> Extend<T> for LeftistHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.meld(iter.into_iter().collect());
+    }
+}
+
+impl<T: Ord> From<BinaryHeap<T>> for LeftistHeap<T> {
+    fn from(heap: BinaryHeap<T>) -> Self {
+        heap.into_vec().into_iter().collect()
+    }
+}
+
+impl<T: Ord> From<LeftistHeap<T>> for BinaryHeap<T> {
+    /// Heapify the elements in O(n), rather than pushing them one by one.
+    fn from(mut heap: LeftistHeap<T>) -> Self {
+        BinaryHeap::from(heap.drain().collect::<Vec<_>>())
+    }
+}
+
+impl<T: Ord> FromIterator<T> for LeftistHeap<T> {
+    /// Build a heap in O(n) by merging one-element heaps in pairs, round after
+    /// round, where pushing the elements one by one would take O(n log n).
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heaps: VecDeque<_> = iter.into_iter().map(Node::new).collect();
+        let len = heaps.len();
+        while heaps.len() > 1 {
+            let (a, b) = (heaps.pop_front(), heaps.pop_front());
+            heaps.extend(merge(a, b));
+        }
+        LeftistHeap {
+            root: heaps.pop_front(),
+            len,
+        }
+    }
+}
+
+/// Iterator over the elements of a [`LeftistHeap`] in no particular order.
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        self.remaining -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LeftistHeap<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator removing every element of a [`LeftistHeap`] in no particular
+/// order, returned by [`LeftistHeap::drain`].
+pub struct Drain<'a, T> {
+    stack: Vec<Box<Node<T>>>,
+    remaining: usize,
+    marker: PhantomData<&'a mut LeftistHeap<T>>,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mut node = self.stack.pop()?;
+        self.stack.extend(node.right.take());
+        self.stack.extend(node.left.take());
+        self.remaining -= 1;
+        Some(node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> FusedIterator for Drain<'_, T> {}
+
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        // Free the nodes left without recursing, like the heap itself does.
+        self.for_each(drop);
+    }
+}
+
+/// An iterator removing the elements of a [`LeftistHeap`] that match a
+/// filter, returned by [`LeftistHeap::extract_if`].
+pub struct ExtractIf<'a, T: Ord, F> {
+    heap: &'a mut LeftistHeap<T>,
+    // Nodes not visited yet, detached from the heap.
+    stack: Vec<Box<Node<T>>>,
+    filter: F,
+}
+
+impl<T: Ord, F> Iterator for ExtractIf<'_, T, F>
+where
+    F: FnMut(&T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while let Some(mut node) = self.stack.pop() {
+            self.stack.extend(node.right.take());
+            self.stack.extend(node.left.take());
+            if (self.filter)(&node.value) {
+                return Some(node.value);
+            }
+            self.heap.push(node.value);
+        }
+        None
+    }
+}
+
+impl<T: Ord, F> Drop for ExtractIf<'_, T, F> {
+    fn drop(&mut self) {
+        while let Some(mut node) = self.stack.pop() {
+            self.stack.extend(node.right.take());
+            self.stack.extend(node.left.take());
+            self.heap.push(node.value);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for LeftistHeap<T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for LeftistHeap<T>
+where
+    T: serde::Deserialize<'de> + Ord,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for LeftistHeap<T>
+where
+    T: arbitrary::Arbitrary<'a> + Ord,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for LeftistHeap<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static + Ord,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, Ord::cmp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    // Check the heap order and the leftist property, and return the subtree size.
+    fn check<T: Ord>(node: &Link<T>) -> usize {
+        let Some(node) = node else {
+            return 0;
+        };
+        for child in [&node.left, &node.right].into_iter().flatten() {
+            assert!(child.value <= node.value);
+        }
+        assert!(rank(&node.left) >= rank(&node.right));
+        assert_eq!(node.rank, rank(&node.right) + 1);
+        1 + check(&node.left) + check(&node.right)
+    }
+
+    #[test]
+    fn heap_sort() {
+        let mut x = 0x9e37_79b9_u64;
+        let values: Vec<u64> = (0..1000)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x % 100
+            })
+            .collect();
+        let heap: LeftistHeap<_> = values.iter().copied().collect();
+        assert_eq!(check(&heap.root), 1000);
+        let mut expected = values;
+        expected.sort();
+        assert_eq!(heap.into_sorted_vec(), expected);
+    }
+
+    #[test]
+    fn extend() {
+        let mut heap: LeftistHeap<_> = (0..100).rev().collect();
+        assert_eq!(check(&heap.root), 100);
+        heap.extend((100..200).step_by(2));
+        assert_eq!(check(&heap.root), 150);
+        assert_eq!(heap.len(), 150);
+        assert_eq!(heap.pop(), Some(198));
+    }
+
+    #[test]
+    fn meld() {
+        let mut heaps: Vec<LeftistHeap<_>> = (0..50)
+            .map(|i| (0..20).map(|j| i * 20 + j).collect())
+            .collect();
+        while heaps.len() > 1 {
+            let other = heaps.pop().unwrap();
+            heaps[0].meld(other);
+        }
+        let mut heap = heaps.pop().unwrap();
+        assert_eq!(check(&heap.root), 1000);
+        assert_eq!(heap.len(), 1000);
+        for expected in (0..1000).rev() {
+            assert_eq!(heap.pop(), Some(expected));
+        }
+        assert_eq!(heap.pop(), None);
+        heap.meld(LeftistHeap::new());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn long_left_spine_drops() {
+        // Ascending pushes build a heap whose left spine holds every node.
+        let heap: LeftistHeap<_> = (0..100_000).collect();
+        assert_eq!(heap.iter().count(), 100_000);
+        drop(heap.clone());
+    }
+
+    #[test]
+    fn drain() {
+        let mut heap: LeftistHeap<_> = (0..100_000).collect();
+        let mut drain = heap.drain();
+        assert_eq!(drain.len(), 100_000);
+        let mut firsts: Vec<_> = drain.by_ref().take(1000).collect();
+        assert_eq!(drain.len(), 99_000);
+        // Dropping the rest doesn't recurse down the long left spine.
+        drop(drain);
+        assert!(heap.is_empty());
+        assert_eq!(heap.pop(), None);
+
+        heap.extend(0..10);
+        firsts.sort();
+        firsts.dedup();
+        assert_eq!(firsts.len(), 1000);
+        std::mem::forget(heap.drain());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn binary_heap() {
+        let mut heap = LeftistHeap::from(BinaryHeap::from(vec![3, 1, 4, 1, 5]));
+        check(&heap.root);
+        assert_eq!(heap.pop(), Some(5));
+        let heap = BinaryHeap::from(heap);
+        assert_eq!(heap.into_sorted_vec(), [1, 1, 3, 4]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let heap: LeftistHeap<i32> = [3, 1, 2].into_iter().collect();
+        let json = serde_json::to_string(&heap).unwrap();
+        let mut back: LeftistHeap<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.len(), 3);
+        assert_eq!(back.pop(), Some(3));
+        assert_eq!(back.pop(), Some(2));
+        assert_eq!(back.pop(), Some(1));
+    }
+}
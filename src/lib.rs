@@ -2,10 +2,98 @@
     allocator_api,
     try_reserve_kind,
     sized_type_properties,
-    dropck_eyepatch
+    dropck_eyepatch,
+    coerce_unsized,
+    unsize,
+    layout_for_ptr,
+    ptr_metadata
 )]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(feature = "std"), feature(core_intrinsics))]
+#![cfg_attr(not(feature = "std"), allow(internal_features))]
+
+extern crate alloc;
+// The test harness always links `std`, so tests may use it even without the
+// `std` feature.
+#[cfg(test)]
+extern crate std;
+
+pub mod arc;
+pub mod arena;
+pub mod array_vec;
+#[cfg(feature = "std")]
+pub mod atomic;
+pub mod avl;
+pub mod b_plus_tree;
+pub mod bi_map;
 pub mod binary_search_tree;
+pub mod bit_set;
+pub mod boxed;
+pub mod bump;
+pub mod cache;
+pub mod cell;
+#[cfg(feature = "std")]
+pub mod channel;
+pub mod codec;
+pub mod collection;
+mod compat;
+#[cfg(feature = "std")]
+pub mod concurrent;
+pub mod counter;
+pub mod cow;
+pub mod fenwick_tree;
+pub mod ffi;
+pub mod grid;
+pub mod hamt;
 pub mod hashmap;
+pub mod index_map;
+pub mod interner;
+pub mod interval_tree;
+pub mod iter;
+pub mod kd_tree;
+pub mod leftist_heap;
 pub mod linked_list;
+pub mod multi_map;
 pub mod my_vec;
+pub mod once;
+pub mod parse;
+pub mod persistent;
+#[cfg(feature = "proptest")]
+mod proptest_impl;
+pub mod quadtree;
+pub mod range_map;
+pub mod range_set;
 pub mod raw_vec;
+pub mod rc;
+pub mod rc_slice;
+pub mod red_black;
+pub mod ring;
+pub mod rtree;
+pub mod segment_tree;
+#[cfg(feature = "serde")]
+mod serde_impl;
+pub mod skip_list;
+pub mod sliding_window;
+pub mod slot_map;
+pub mod small_string;
+pub mod small_vec;
+pub mod sparse_table;
+pub mod spatial;
+pub mod splay;
+#[cfg(feature = "std")]
+pub mod spsc;
+pub mod string;
+pub mod suffix_array;
+pub mod suffix_automaton;
+#[cfg(feature = "std")]
+pub mod sync;
+pub mod thin_box;
+#[cfg(feature = "std")]
+pub mod thread_pool;
+pub mod tiny_map;
+pub mod treap;
+pub mod value;
+pub mod vec_map;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod weak_map;
@@ -1,23 +1,80 @@
-use std::{cell::RefCell, rc::Rc};
+use alloc::alloc::{Allocator, Global};
+use alloc::collections;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::parse::{self, Format, Parse, ParseError, Parser};
+
+type Link<T, A> = Option<Rc<RefCell<Node<T, A>>, A>>;
 
 /// A node in a singly linked list.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Node<T: Clone> {
+#[derive(Debug, Clone)]
+pub struct Node<T: Clone, A: Allocator = Global> {
     elem: T,
-    next: Option<Rc<RefCell<Node<T>>>>,
+    next: Link<T, A>,
+}
+
+impl<T: Clone + PartialEq, A: Allocator> PartialEq for Node<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.elem == other.elem && self.next == other.next
+    }
 }
 
+impl<T: Clone + Eq, A: Allocator> Eq for Node<T, A> {}
+
 /// A singly linked list with a reference-counted `Node` type.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct LinkedList<T: Clone> {
-    head: Option<Rc<RefCell<Node<T>>>>,
+///
+/// The nodes are allocated with `A`, the global allocator unless the list is
+/// created with [`LinkedList::new_in`].
+///
+/// # Examples
+///
+/// ```
+/// #![feature(allocator_api)]
+/// use my_std::bump::Bump;
+/// use my_std::linked_list::LinkedList;
+///
+/// let bump = Bump::new();
+/// let mut list = LinkedList::new_in(&bump);
+/// list.push(1);
+/// list.push(2);
+/// assert_eq!(list.pop(), Some(2));
+/// assert!(bump.allocated_bytes() > 0);
+/// ```
+#[derive(Debug)]
+pub struct LinkedList<T: Clone, A: Allocator = Global> {
+    head: Link<T, A>,
     len: usize,
+    alloc: A,
 }
 
 impl<T: Clone> LinkedList<T> {
     /// Create a new, empty `LinkedList`.
     pub fn new() -> LinkedList<T> {
-        LinkedList { head: None, len: 0 }
+        Self::new_in(Global)
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> LinkedList<T, A> {
+    /// Create a new, empty `LinkedList` whose nodes are allocated with `alloc`.
+    pub fn new_in(alloc: A) -> Self {
+        LinkedList {
+            head: None,
+            len: 0,
+            alloc,
+        }
+    }
+
+    /// The allocator the nodes are allocated with.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    fn node(&self, elem: T, next: Link<T, A>) -> Rc<RefCell<Node<T, A>>, A> {
+        Rc::new_in(RefCell::new(Node { elem, next }), self.alloc.clone())
     }
 
     /// Add an element to the front of the list.
@@ -32,7 +89,7 @@ impl<T: Clone> LinkedList<T> {
     /// assert_eq!(list.len(), 2);
     /// ```
     pub fn push(&mut self, elem: T) {
-        let new_node = Rc::new(RefCell::new(Node { elem, next: None }));
+        let new_node = self.node(elem, None);
 
         match self.head {
             Some(ref head) => {
@@ -118,10 +175,8 @@ impl<T: Clone> LinkedList<T> {
             current = next;
         }
 
-        let new_node = Rc::new(RefCell::new(Node {
-            elem,
-            next: current.borrow().next.clone(),
-        }));
+        let next = current.borrow().next.clone();
+        let new_node = self.node(elem, next);
 
         current.borrow_mut().next = Some(new_node);
         self.len += 1;
@@ -178,6 +233,132 @@ impl<T: Clone> LinkedList<T> {
     pub fn len(&self) -> usize {
         self.len
     }
+
+    /// Remove every element from the list.
+    pub fn clear(&mut self) {
+        // Unlink the nodes one by one, so a long list isn't dropped recursively.
+        let mut current = self.head.take();
+        while let Some(node) = current {
+            current = node.borrow_mut().next.take();
+        }
+        self.len = 0;
+    }
+}
+
+impl<T: Clone + PartialEq, A: Allocator> PartialEq for LinkedList<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.head == other.head
+    }
+}
+
+impl<T: Clone + Eq, A: Allocator> Eq for LinkedList<T, A> {}
+
+impl<T: Clone, A: Allocator + Clone> Clone for LinkedList<T, A> {
+    /// Copy every node, so the two lists can be changed independently.
+    fn clone(&self) -> Self {
+        let mut elems = Vec::with_capacity(self.len);
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            elems.push(node.borrow().elem.clone());
+            current = node.borrow().next.clone();
+        }
+        let mut list = LinkedList::new_in(self.alloc.clone());
+        for elem in elems.into_iter().rev() {
+            let next = list.head.take();
+            list.head = Some(list.node(elem, next));
+        }
+        list.len = self.len;
+        list
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Extend<T> for LinkedList<T, A> {
+    /// Push every element in order, like repeated calls to `push` would.
+    ///
+    /// The last node is looked up once, rather than on every `push`.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut tail = self.head.clone();
+        while let Some(next) = tail.as_ref().and_then(|node| node.borrow().next.clone()) {
+            tail = Some(next);
+        }
+        for elem in iter {
+            let node = self.node(elem, None);
+            match &tail {
+                Some(tail) => tail.borrow_mut().next = Some(node.clone()),
+                None => self.head = Some(node.clone()),
+            }
+            tail = Some(node);
+            self.len += 1;
+        }
+    }
+}
+
+impl<T: Clone> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Clone> From<collections::LinkedList<T>> for LinkedList<T> {
+    fn from(list: collections::LinkedList<T>) -> Self {
+        list.into_iter().collect()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> From<LinkedList<T, A>> for collections::LinkedList<T> {
+    /// Move the elements over in order, front to back.
+    fn from(mut list: LinkedList<T, A>) -> Self {
+        let mut std_list = collections::LinkedList::new();
+        let mut current = list.head.take();
+        while let Some(node) = current {
+            // The list is the only owner of its nodes, so this doesn't clone.
+            let node =
+                Rc::try_unwrap(node).map_or_else(|node| node.borrow().clone(), RefCell::into_inner);
+            std_list.push_back(node.elem);
+            current = node.next;
+        }
+        std_list
+    }
+}
+
+impl<T: Clone + Format, A: Allocator + Clone> fmt::Display for LinkedList<T, A> {
+    /// Write the elements front to back as `[a, b, c]`, which [`FromStr`]
+    /// reads back.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        let mut current = self.head.clone();
+        let mut separator = "";
+        while let Some(node) = current {
+            f.write_str(separator)?;
+            separator = ", ";
+            node.borrow().elem.format(f)?;
+            current = node.borrow().next.clone();
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T: Clone + Format, A: Allocator + Clone> Format for LinkedList<T, A> {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl<T: Clone + Parse> FromStr for LinkedList<T> {
+    type Err = ParseError;
+
+    /// Parse a list like `[1, 2, 3]`, in the format of [`Parse`].
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        parse::from_str(s)
+    }
+}
+
+impl<T: Clone + Parse> Parse for LinkedList<T> {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        Ok(parser.list()?.into_iter().collect())
+    }
 }
 
 impl<T: Clone> Default for LinkedList<T> {
@@ -186,8 +367,67 @@ impl<T: Clone> Default for LinkedList<T> {
     }
 }
 
+/// A list serializes front to back, the order `collect` rebuilds it in.
+#[cfg(feature = "serde")]
+impl<T, A> serde::Serialize for LinkedList<T, A>
+where
+    T: Clone + serde::Serialize,
+    A: Allocator + Clone,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            seq.serialize_element(&node.borrow().elem)?;
+            current = node.borrow().next.clone();
+        }
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Clone + serde::Deserialize<'de>> serde::Deserialize<'de> for LinkedList<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for LinkedList<T>
+where
+    T: arbitrary::Arbitrary<'a> + Clone,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for LinkedList<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static + Clone,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
+
     use super::*;
 
     #[test]
@@ -219,6 +459,17 @@ mod tests {
         assert_eq!(list.pop(), Some(1));
     }
 
+    #[test]
+    fn test_collect_extend() {
+        let mut list: LinkedList<_> = (1..=3).collect();
+        list.extend([4, 5]);
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.remove(0), 1);
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), Some(3));
+    }
+
     #[test]
     fn test_remove() {
         let mut list = LinkedList::new();
@@ -227,4 +478,36 @@ mod tests {
         assert_eq!(list.remove(1), 2);
         assert_eq!(list.remove(0), 1);
     }
+
+    #[test]
+    fn test_std_list() {
+        let list: collections::LinkedList<_> = (1..=4).map(|i| i.to_string()).collect();
+        let mut list = LinkedList::from(list);
+        assert_eq!(list.pop(), Some("4".to_string()));
+        let list = collections::LinkedList::from(list);
+        assert!(list.into_iter().eq(["1", "2", "3"]));
+        assert!(collections::LinkedList::from(LinkedList::<u8>::new()).is_empty());
+    }
+
+    #[test]
+    fn parse() {
+        let list: LinkedList<i32> = "[1, 2, 3]".parse().unwrap();
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.to_string(), "[1, 2, 3]");
+        assert_eq!(LinkedList::<u8>::new().to_string(), "[]");
+        let nested: LinkedList<LinkedList<char>> = "[['a'], ['b', 'c']]".parse().unwrap();
+        assert_eq!(nested.to_string(), "[['a'], ['b', 'c']]");
+        assert!("[1] [2]".parse::<LinkedList<i32>>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let list: LinkedList<i32> = [1, 2, 3].into_iter().collect();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let mut back: LinkedList<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.len(), 3);
+        assert_eq!(back.pop(), Some(3));
+    }
 }
@@ -0,0 +1,379 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::Hash;
+use core::slice;
+
+use crate::hashmap::{self, HashMap};
+
+/// A map from each key to any number of values, built on the crate's [`HashMap`].
+/// Values of a key keep their insertion order, and a key disappears once its last
+/// value is removed.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::multi_map::MultiMap;
+///
+/// let mut map = MultiMap::new();
+/// map.insert("fruit", "apple");
+/// map.insert("fruit", "pear");
+/// map.insert("vegetable", "leek");
+/// assert_eq!(map.get_all("fruit"), &["apple", "pear"]);
+/// assert_eq!(map.count("fruit"), 2);
+/// assert_eq!(map.len(), 3);
+///
+/// assert_eq!(map.remove_one("fruit"), Some("pear"));
+/// assert_eq!(map.get_all("meat"), &[] as &[&str]);
+/// ```
+pub struct MultiMap<K, V> {
+    map: HashMap<K, Vec<V>>,
+    // Total number of values across all keys.
+    len: usize,
+}
+
+impl<K, V> Default for MultiMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> MultiMap<K, V> {
+    /// Create a new, empty multimap.
+    pub fn new() -> Self {
+        MultiMap {
+            map: HashMap::new(),
+            len: 0,
+        }
+    }
+
+    /// Total number of values in the multimap.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the multimap holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over every key with all of its values.
+    pub fn iter_all(&self) -> impl Iterator<Item = (&K, &[V])> {
+        (&self.map)
+            .into_iter()
+            .map(|(k, values)| (k, values.as_slice()))
+    }
+
+    /// Iterate over every key-value pair, one item per value.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            keys: self.map.iter(),
+            current: None,
+            remaining: self.len,
+        }
+    }
+
+    /// Iterate over the distinct keys.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        (&self.map).into_iter().map(|(k, _)| k)
+    }
+}
+
+impl<K, V> MultiMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Number of distinct keys.
+    pub fn keys_len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Add a value to a key, after the values it already has.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map.entry(key).or_default().push(value);
+        self.len += 1;
+    }
+
+    /// Add every value of an iterator to a key.
+    pub fn insert_many<I>(&mut self, key: K, values: I)
+    where
+        I: IntoIterator<Item = V>,
+    {
+        let entry = self.map.entry(key).or_default();
+        let before = entry.len();
+        entry.extend(values);
+        self.len += entry.len() - before;
+    }
+
+    /// Get the first value of a key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).and_then(|values| values.first())
+    }
+
+    /// Get every value of a key, in insertion order.
+    /// The slice is empty if the key is absent.
+    pub fn get_all<Q>(&self, key: &Q) -> &[V]
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get mutable references to every value of a key.
+    pub fn get_all_mut<Q>(&mut self, key: &Q) -> &mut [V]
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get_mut(key).map_or(&mut [], Vec::as_mut_slice)
+    }
+
+    /// Number of values of a key.
+    pub fn count<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_all(key).len()
+    }
+
+    /// Return `true` if the key has at least one value.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Remove the most recently inserted value of a key.
+    pub fn remove_one<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let values = self.map.get_mut(key)?;
+        let value = values.pop();
+        if values.is_empty() {
+            self.map.remove(key);
+        }
+        self.len -= 1;
+        value
+    }
+
+    /// Remove a key and return all of its values.
+    /// The vector is empty if the key was absent.
+    pub fn remove_all<Q>(&mut self, key: &Q) -> Vec<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let values = self.map.remove(key).unwrap_or_default();
+        self.len -= values.len();
+        values
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for MultiMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_all()).finish()
+    }
+}
+
+/// Iterator over the key-value pairs of a [`MultiMap`].
+pub struct Iter<'a, K, V> {
+    keys: hashmap::Iter<'a, K, Vec<V>>,
+    current: Option<(&'a K, slice::Iter<'a, V>)>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, values)) = &mut self.current {
+                if let Some(value) = values.next() {
+                    self.remaining -= 1;
+                    return Some((key, value));
+                }
+            }
+            let (key, values) = self.keys.next()?;
+            self.current = Some((key, values.iter()));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a MultiMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for MultiMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = MultiMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> Extend<(K, V)> for MultiMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+/// A multimap serializes as a map from each key to the sequence of its values.
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for MultiMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter_all())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for MultiMap<K, V>
+where
+    K: serde::Deserialize<'de> + Hash + Eq,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries: Vec<(K, Vec<V>)> = crate::serde_impl::deserialize_map(deserializer)?;
+        let mut map = MultiMap::new();
+        // A key without values would still count towards `keys_len`.
+        for (key, values) in entries.into_iter().filter(|(_, values)| !values.is_empty()) {
+            map.insert_many(key, values);
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for MultiMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Hash + Eq,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for MultiMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Hash + Eq,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn insert() {
+        let mut map = MultiMap::new();
+        map.insert("foo", 1);
+        map.insert("foo", 2);
+        map.insert_many("bar", [3, 4, 5]);
+        assert_eq!(map.len(), 5);
+        assert_eq!(map.keys_len(), 2);
+        assert_eq!(map.get_all("foo"), &[1, 2]);
+        assert_eq!(map.get("bar"), Some(&3));
+        assert_eq!(map.count("bar"), 3);
+        assert_eq!(map.count("baz"), 0);
+    }
+
+    #[test]
+    fn get_all_mut() {
+        let mut map: MultiMap<_, _> = vec![("foo", 1), ("foo", 2)].into_iter().collect();
+        for v in map.get_all_mut("foo") {
+            *v *= 10;
+        }
+        assert_eq!(map.get_all("foo"), &[10, 20]);
+        assert!(map.get_all_mut("bar").is_empty());
+    }
+
+    #[test]
+    fn remove() {
+        let mut map = MultiMap::new();
+        assert_eq!(map.remove_one("foo"), None);
+        map.insert_many("foo", [1, 2]);
+        map.insert_many("bar", [3, 4]);
+        assert_eq!(map.remove_one("foo"), Some(2));
+        assert_eq!(map.remove_one("foo"), Some(1));
+        assert!(!map.contains_key("foo"));
+        assert_eq!(map.remove_all("bar"), vec![3, 4]);
+        assert_eq!(map.remove_all("bar"), Vec::<i32>::new());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn iter() {
+        let map: MultiMap<_, _> = vec![("foo", 1), ("bar", 2), ("foo", 3)]
+            .into_iter()
+            .collect();
+        let iter = map.iter();
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        let mut pairs: Vec<_> = iter.map(|(k, v)| (*k, *v)).collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![("bar", 2), ("foo", 1), ("foo", 3)]);
+        let mut keys: Vec<_> = map.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec![&"bar", &"foo"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: MultiMap<_, _> = [(1, 10), (2, 20), (1, 11)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        let back: MultiMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.get_all(&1), &[10, 11]);
+        assert_eq!(back.get_all(&2), &[20]);
+        assert_eq!(back.len(), 3);
+
+        let back: MultiMap<i32, i32> = serde_json::from_str(r#"{"1":[],"2":[20]}"#).unwrap();
+        assert_eq!(back.keys_len(), 1);
+    }
+}
@@ -1,9 +1,20 @@
-use std::{
-    alloc::{Allocator, Global},
-    cmp, fmt, ops, ptr, slice,
+use alloc::alloc::{Allocator, Global};
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+use core::{
+    cmp, fmt,
+    iter::FusedIterator,
+    mem::ManuallyDrop,
+    ops::{self, Bound, RangeBounds},
+    ptr, slice,
+    str::FromStr,
 };
 
-use crate::raw_vec::RawVec;
+use crate::{
+    codec::{self, Decode, DecodeError, Encode},
+    parse::{self, Format, Parse, ParseError, Parser},
+    raw_vec::RawVec,
+};
 
 /// A contiguous growable array.
 ///
@@ -41,6 +52,12 @@ impl<T> MyVec<T> {
     pub fn with_capacity(capacity: usize) -> Self {
         Self::with_capacity_in(capacity, Global)
     }
+
+    /// Constructs a new, empty `MyVec<T>` with the specified capacity,
+    /// returning an error if the memory can't be allocated.
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
 }
 
 impl Default for MyVec<u8> {
@@ -50,6 +67,27 @@ impl Default for MyVec<u8> {
 }
 
 impl<T, A: Allocator> MyVec<T, A> {
+    /// Constructs a new, empty `MyVec<T>` using a specified allocator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use my_std::bump::Bump;
+    /// use my_std::my_vec::MyVec;
+    ///
+    /// let bump = Bump::new();
+    /// let mut vec = MyVec::new_in(&bump);
+    /// vec.extend(0..100);
+    /// assert_eq!(vec.iter().sum::<i32>(), 4950);
+    /// ```
+    pub const fn new_in(alloc: A) -> Self {
+        MyVec {
+            buf: RawVec::new_in(alloc),
+            len: 0,
+        }
+    }
+
     /// Constructs a new, empty `MyVec<T>` with the specified capacity using a specified allocator.
     pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
         MyVec {
@@ -58,6 +96,15 @@ impl<T, A: Allocator> MyVec<T, A> {
         }
     }
 
+    /// Constructs a new, empty `MyVec<T>` with the specified capacity using a specified allocator,
+    /// returning an error if the memory can't be allocated.
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+        Ok(MyVec {
+            buf: RawVec::try_with_capacity_in(capacity, alloc)?,
+            len: 0,
+        })
+    }
+
     /// Appends an element to the back of a collection.
     ///
     /// # Examples
@@ -110,6 +157,28 @@ impl<T, A: Allocator> MyVec<T, A> {
         }
     }
 
+    /// Removes all elements, keeping the allocated capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::my_vec::MyVec;
+    ///
+    /// let mut vec = MyVec::new();
+    /// vec.push(1);
+    /// vec.clear();
+    /// assert!(vec.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        let elems = ptr::slice_from_raw_parts_mut(self.as_mut_ptr(), self.len);
+        unsafe {
+            // Set the length first, so a panicking destructor can't lead to
+            // the remaining elements being dropped twice.
+            self.len = 0;
+            ptr::drop_in_place(elems);
+        }
+    }
+
     /// Returns a raw pointer to the vector's buffer, or a dangling raw pointer
     /// valid for zero sized reads if the vector didn't allocate.
     pub fn as_ptr(&self) -> *const T {
@@ -136,6 +205,35 @@ impl<T, A: Allocator> MyVec<T, A> {
         self.buf.capacity()
     }
 
+    /// Returns a reference to the underlying allocator.
+    pub fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
+    /// Creates a `MyVec<T>` directly from a pointer, a length, a capacity and an allocator.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been allocated by `alloc` with the layout of a `[T; capacity]`,
+    /// or be dangling with a capacity of 0, and its first `length` elements must be
+    /// initialized. Ownership of the buffer moves to the vector.
+    pub unsafe fn from_raw_parts_in(ptr: *mut T, length: usize, capacity: usize, alloc: A) -> Self {
+        MyVec {
+            buf: RawVec::from_raw_parts_in(ptr, capacity, alloc),
+            len: length,
+        }
+    }
+
+    /// Decomposes a `MyVec<T>` into its pointer, length, capacity and allocator,
+    /// without freeing anything. [`MyVec::from_raw_parts_in`] puts it back together.
+    pub fn into_raw_parts_with_alloc(self) -> (*mut T, usize, usize, A) {
+        let mut me = ManuallyDrop::new(self);
+        let (ptr, len, capacity) = (me.as_mut_ptr(), me.len, me.capacity());
+        // SAFETY: `me` is never dropped, so the allocator is moved out only once.
+        let alloc = unsafe { ptr::read(me.allocator()) };
+        (ptr, len, capacity, alloc)
+    }
+
     /// Forces the length of the vector to `new_len`.
     ///
     /// # Safety
@@ -153,6 +251,35 @@ impl<T, A: Allocator> MyVec<T, A> {
         self.buf.reserve(self.len, additional);
     }
 
+    /// Reserves the minimum capacity for exactly `additional` more elements
+    /// to be inserted in the given `MyVec<T>`.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.buf.reserve_exact(self.len, additional);
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements,
+    /// returning an error instead of panicking or aborting if it can't.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::my_vec::MyVec;
+    ///
+    /// let mut vec: MyVec<u64> = MyVec::new();
+    /// assert!(vec.try_reserve(10).is_ok());
+    /// assert!(vec.capacity() >= 10);
+    /// assert!(vec.try_reserve(usize::MAX).is_err());
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.buf.try_reserve(self.len, additional)
+    }
+
+    /// Tries to reserve the minimum capacity for exactly `additional` more
+    /// elements, returning an error instead of panicking or aborting if it can't.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.buf.try_reserve_exact(self.len, additional)
+    }
+
     /// Inserts an element at position `index` within the vector,
     /// shifting all elements after it to the right.
     ///
@@ -171,6 +298,9 @@ impl<T, A: Allocator> MyVec<T, A> {
     /// ```
     pub fn insert(&mut self, index: usize, element: T) {
         let len = self.len();
+        if len == self.buf.capacity() {
+            self.buf.reserve_for_push(len);
+        }
 
         unsafe {
             // infallible
@@ -234,6 +364,133 @@ impl<T, A: Allocator> MyVec<T, A> {
     }
 }
 
+impl<T, A: Allocator> MyVec<T, A> {
+    /// Removes the elements in `range` and returns an iterator over them.
+    ///
+    /// The elements after `range` are moved back into place when the iterator
+    /// is dropped. If it is leaked instead, the vector is left holding only the
+    /// elements before `range`: nothing drained is ever seen in it again.
+    ///
+    /// Panics if `range` is out of order or out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::my_vec::MyVec;
+    ///
+    /// let mut v = MyVec::new();
+    /// for x in 0..6 {
+    ///     v.push(x);
+    /// }
+    /// let drained: Vec<_> = v.drain(1..4).collect();
+    /// assert_eq!(drained, [1, 2, 3]);
+    /// assert_eq!(*v, [0, 4, 5]);
+    /// ```
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A> {
+        let len = self.len;
+        let ops::Range { start, end } = to_range(range, len);
+        // Forget the drained elements and everything after them up front, so
+        // a leaked iterator can't leave them behind.
+        self.len = start;
+        Drain {
+            vec: self,
+            front: start,
+            back: end,
+            tail_start: end,
+            tail_len: len - end,
+        }
+    }
+
+    /// Returns an iterator that removes and yields every element for which
+    /// `filter` returns `true`, passing it a mutable reference so the elements
+    /// kept can be changed on the way.
+    ///
+    /// Elements the iterator didn't get to before being dropped are kept. If it
+    /// is leaked, the vector is left empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::my_vec::MyVec;
+    ///
+    /// let mut v = MyVec::new();
+    /// for x in 1..=6 {
+    ///     v.push(x);
+    /// }
+    /// let evens: Vec<_> = v.drain_filter(|x| *x % 2 == 0).collect();
+    /// assert_eq!(evens, [2, 4, 6]);
+    /// assert_eq!(*v, [1, 3, 5]);
+    /// ```
+    pub fn drain_filter<F>(&mut self, filter: F) -> DrainFilter<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let old_len = self.len;
+        self.len = 0;
+        DrainFilter {
+            vec: self,
+            index: 0,
+            removed: 0,
+            old_len,
+            filter,
+        }
+    }
+
+    /// Same as [`drain_filter`](MyVec::drain_filter), under the name the
+    /// standard library settled on.
+    pub fn extract_if<F>(&mut self, filter: F) -> DrainFilter<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.drain_filter(filter)
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, visiting them in
+    /// order and keeping their order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::my_vec::MyVec;
+    ///
+    /// let mut v = MyVec::new();
+    /// for x in 1..=6 {
+    ///     v.push(x);
+    /// }
+    /// v.retain(|x| x % 3 != 0);
+    /// assert_eq!(*v, [1, 2, 4, 5]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.drain_filter(|x| !f(x)).for_each(drop);
+    }
+}
+
+// Turn `range` into bounds within `0..len`.
+fn to_range<R: RangeBounds<usize>>(range: R, len: usize) -> ops::Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e + 1,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "range start (is {start}) should be <= range end (is {end})"
+    );
+    assert!(
+        end <= len,
+        "range end (is {end}) should be <= len (is {len})"
+    );
+    start..end
+}
+
 impl<T, A: Allocator> MyVec<T, A> {
     /// Use bubble sort to sort the vector.
     /// This is a simple sorting algorithm that repeatedly steps through the list,
@@ -433,14 +690,377 @@ impl<T, A: Allocator> ops::Deref for MyVec<T, A> {
     }
 }
 
+impl<T, A: Allocator> ops::DerefMut for MyVec<T, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+}
+
+unsafe impl<#[may_dangle] T, A: Allocator> Drop for MyVec<T, A> {
+    fn drop(&mut self) {
+        // `RawVec` frees the buffer, the elements are ours to drop.
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.as_mut_ptr(), self.len));
+        }
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for MyVec<T, A> {
+    fn clone(&self) -> Self {
+        let mut vec = MyVec::with_capacity_in(self.len, self.allocator().clone());
+        for value in self.iter() {
+            vec.push(value.clone());
+        }
+        vec
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a MyVec<T, A> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a mut MyVec<T, A> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for MyVec<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        // Allocate once for the elements the iterator is sure to yield.
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for MyVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = MyVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, A: Allocator> From<Vec<T, A>> for MyVec<T, A> {
+    /// Take over the buffer of a `Vec`, without copying the elements.
+    fn from(vec: Vec<T, A>) -> Self {
+        let (ptr, len, capacity, alloc) = vec.into_raw_parts_with_alloc();
+        // SAFETY: the parts come from a `Vec`, which lays its buffer out the same way.
+        unsafe { MyVec::from_raw_parts_in(ptr, len, capacity, alloc) }
+    }
+}
+
+impl<T, A: Allocator> From<MyVec<T, A>> for Vec<T, A> {
+    /// Hand the buffer over to a `Vec`, without copying the elements.
+    fn from(vec: MyVec<T, A>) -> Self {
+        let (ptr, len, capacity, alloc) = vec.into_raw_parts_with_alloc();
+        // SAFETY: as above, the other way around.
+        unsafe { Vec::from_raw_parts_in(ptr, len, capacity, alloc) }
+    }
+}
+
+impl<T: Encode, A: Allocator> MyVec<T, A> {
+    /// Encode the vector in the [`codec`] format: a version
+    /// byte, the length and then each element.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        codec::to_bytes(self)
+    }
+}
+
+impl<T: Decode> MyVec<T> {
+    /// Decode a vector written by [`MyVec::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        codec::from_bytes(bytes)
+    }
+}
+
+impl<T: Encode, A: Allocator> Encode for MyVec<T, A> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (**self).encode(out);
+    }
+}
+
+impl<T: Decode> Decode for MyVec<T> {
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        Vec::decode(input).map(MyVec::from)
+    }
+}
+
 impl<T: fmt::Debug, A: Allocator> fmt::Debug for MyVec<T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::Debug::fmt(&**self, f)
     }
 }
 
+impl<T: Format, A: Allocator> fmt::Display for MyVec<T, A> {
+    /// Write the elements as `[a, b, c]`, which [`FromStr`] reads back.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        parse::fmt_list(f, self.iter())
+    }
+}
+
+impl<T: Format, A: Allocator> Format for MyVec<T, A> {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl<T: Parse> FromStr for MyVec<T> {
+    type Err = ParseError;
+
+    /// Parse a list like `[1, 2, 3]`, in the format of [`Parse`].
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        parse::from_str(s)
+    }
+}
+
+impl<T: Parse> Parse for MyVec<T> {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        parser.list().map(MyVec::from)
+    }
+}
+
+/// An iterator removing a range of elements from a [`MyVec`], returned by
+/// [`MyVec::drain`].
+pub struct Drain<'a, T, A: Allocator = Global> {
+    // Its length covers only the elements before the drained range.
+    vec: &'a mut MyVec<T, A>,
+    // Drained elements not yielded yet.
+    front: usize,
+    back: usize,
+    // The elements after the drained range.
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<T, A: Allocator> Iterator for Drain<'_, T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        unsafe { Some(ptr::read(self.vec.as_ptr().add(self.front - 1))) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T, A: Allocator> DoubleEndedIterator for Drain<'_, T, A> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        unsafe { Some(ptr::read(self.vec.as_ptr().add(self.back))) }
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for Drain<'_, T, A> {}
+
+impl<T, A: Allocator> FusedIterator for Drain<'_, T, A> {}
+
+impl<T, A: Allocator> Drop for Drain<'_, T, A> {
+    fn drop(&mut self) {
+        unsafe {
+            let p = self.vec.as_mut_ptr();
+            // If one of these destructors panics the tail is leaked, which is
+            // safe since the vector's length already excludes it.
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                p.add(self.front),
+                self.back - self.front,
+            ));
+            let start = self.vec.len;
+            ptr::copy(p.add(self.tail_start), p.add(start), self.tail_len);
+            self.vec.len = start + self.tail_len;
+        }
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for Drain<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rest = unsafe {
+            slice::from_raw_parts(self.vec.as_ptr().add(self.front), self.back - self.front)
+        };
+        f.debug_tuple("Drain").field(&rest).finish()
+    }
+}
+
+/// An iterator removing the elements of a [`MyVec`] that match a filter,
+/// returned by [`MyVec::drain_filter`].
+pub struct DrainFilter<'a, T, F, A: Allocator = Global> {
+    // Its length is zero until the iterator is dropped.
+    vec: &'a mut MyVec<T, A>,
+    // Next element to look at.
+    index: usize,
+    // Number of elements removed so far, which is how far back the kept ones
+    // are moved.
+    removed: usize,
+    old_len: usize,
+    filter: F,
+}
+
+impl<T, F, A> Iterator for DrainFilter<'_, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+    A: Allocator,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        unsafe {
+            while self.index < self.old_len {
+                let cur = self.vec.as_mut_ptr().add(self.index);
+                // Only count the element as looked at once `filter` returned,
+                // so a panic in it leaves it to be moved back on drop.
+                let remove = (self.filter)(&mut *cur);
+                self.index += 1;
+                if remove {
+                    self.removed += 1;
+                    return Some(ptr::read(cur));
+                }
+                if self.removed > 0 {
+                    ptr::copy_nonoverlapping(cur, cur.sub(self.removed), 1);
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.old_len - self.index))
+    }
+}
+
+impl<T, F, A: Allocator> Drop for DrainFilter<'_, T, F, A> {
+    fn drop(&mut self) {
+        unsafe {
+            let p = self.vec.as_mut_ptr();
+            if self.removed > 0 {
+                ptr::copy(
+                    p.add(self.index),
+                    p.add(self.index - self.removed),
+                    self.old_len - self.index,
+                );
+            }
+            self.vec.len = self.old_len - self.removed;
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, A: Allocator> serde::Serialize for MyVec<T, A>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for MyVec<T>
+where
+    T: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+/// A vector archives as an [`ArchivedVec`](rkyv::vec::ArchivedVec), like a
+/// `Vec`, so the archive derefs to a slice of the archived elements.
+#[cfg(feature = "rkyv")]
+impl<T: rkyv::Archive, A: Allocator> rkyv::Archive for MyVec<T, A> {
+    type Archived = rkyv::vec::ArchivedVec<T::Archived>;
+    type Resolver = rkyv::vec::VecResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: rkyv::Place<Self::Archived>) {
+        rkyv::vec::ArchivedVec::resolve_from_slice(&self[..], resolver, out);
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T, A, S> rkyv::Serialize<S> for MyVec<T, A>
+where
+    T: rkyv::Serialize<S>,
+    A: Allocator,
+    S: rkyv::rancor::Fallible + rkyv::ser::Allocator + rkyv::ser::Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        rkyv::vec::ArchivedVec::serialize_from_slice(&self[..], serializer)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T, D> rkyv::Deserialize<MyVec<T>, D> for rkyv::vec::ArchivedVec<T::Archived>
+where
+    T: rkyv::Archive,
+    T::Archived: rkyv::Deserialize<T, D>,
+    D: rkyv::rancor::Fallible + ?Sized,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<MyVec<T>, D::Error> {
+        self.iter()
+            .map(|value| value.deserialize(deserializer))
+            .collect()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for MyVec<T>
+where
+    T: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for MyVec<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::{
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec,
+    };
+
     use super::*;
 
     #[test]
@@ -455,6 +1075,96 @@ mod tests {
         assert_eq!(v.pop(), None);
     }
 
+    #[test]
+    fn test_collect_extend() {
+        let mut v: MyVec<_> = (0..5).collect();
+        assert_eq!(v.capacity(), 5);
+        v.extend((5..8).filter(|x| x % 2 == 1));
+        assert_eq!(*v, [0, 1, 2, 3, 4, 5, 7]);
+    }
+
+    #[test]
+    fn test_insert_grows() {
+        let mut v = MyVec::new();
+        for i in 0..10 {
+            v.insert(0, i);
+        }
+        assert_eq!(*v, [9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+        v.clear();
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn test_try_reserve() {
+        let mut v: MyVec<u32> = MyVec::try_with_capacity(2).unwrap();
+        v.push(1);
+        v.try_reserve_exact(3).unwrap();
+        assert_eq!(v.capacity(), 4);
+        v.reserve_exact(3);
+        assert_eq!(v.capacity(), 4);
+        v.try_reserve(4).unwrap();
+        assert_eq!(v.capacity(), 8);
+        assert!(v.try_reserve(usize::MAX).is_err());
+        assert!(v.try_reserve_exact(isize::MAX as usize / 4).is_err());
+        assert!(MyVec::<u32>::try_with_capacity(usize::MAX).is_err());
+        assert_eq!(*v, [1]);
+        assert_eq!(v.capacity(), 8);
+    }
+
+    #[test]
+    fn test_drain() {
+        let make = || {
+            let mut v = MyVec::new();
+            for i in 0..8 {
+                v.push(i.to_string());
+            }
+            v
+        };
+        let mut v = make();
+        let mut drain = v.drain(2..6);
+        assert_eq!(drain.len(), 4);
+        assert_eq!(drain.next().as_deref(), Some("2"));
+        assert_eq!(drain.next_back().as_deref(), Some("5"));
+        drop(drain);
+        assert_eq!(*v, ["0", "1", "6", "7"]);
+        assert_eq!(v.drain(..).count(), 4);
+        assert!(v.is_empty());
+
+        // A leaked iterator still removes the drained elements.
+        let mut v = make();
+        std::mem::forget(v.drain(5..));
+        assert_eq!(*v, ["0", "1", "2", "3", "4"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "should be <= len")]
+    fn test_drain_out_of_bounds() {
+        let mut v = MyVec::new();
+        v.push(1);
+        v.drain(..2);
+    }
+
+    #[test]
+    fn test_drain_filter() {
+        let mut v = MyVec::new();
+        for i in 0..10 {
+            v.push(Box::new(i));
+        }
+        let mut odd = v.drain_filter(|x| {
+            **x *= 10;
+            **x % 20 != 0
+        });
+        assert_eq!(odd.next().as_deref(), Some(&10));
+        assert_eq!(odd.next().as_deref(), Some(&30));
+        // The rest is kept, and only what was looked at was changed.
+        drop(odd);
+        let kept: Vec<_> = v.iter().map(|x| **x).collect();
+        assert_eq!(kept, [0, 20, 4, 5, 6, 7, 8, 9]);
+
+        std::mem::forget(v.drain_filter(|_| false));
+        assert!(v.is_empty());
+    }
+
     #[test]
     fn test_bubble_sort() {
         let mut v = MyVec::new();
@@ -490,4 +1200,86 @@ mod tests {
         assert_eq!(v[1], 2);
         assert_eq!(v[2], 3);
     }
+
+    #[test]
+    fn test_std_vec() {
+        let mut vec = Vec::with_capacity(10);
+        vec.extend([1, 2, 3]);
+        let ptr = vec.as_ptr();
+        let mut v = MyVec::from(vec);
+        assert_eq!((v.as_ptr(), v.capacity()), (ptr, 10));
+        v.push(4);
+        let vec = Vec::from(v);
+        assert_eq!((vec.as_ptr(), vec.capacity()), (ptr, 10));
+        assert_eq!(vec, [1, 2, 3, 4]);
+
+        let units = MyVec::from(vec![(); 5]);
+        assert_eq!(Vec::from(units).len(), 5);
+        assert!(Vec::from(MyVec::<String>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let v: MyVec<i32> = [3, -1, 300].into_iter().collect();
+        let bytes = v.to_bytes();
+        assert_eq!(bytes, [codec::VERSION, 3, 6, 1, 0xd8, 0x04]);
+        assert_eq!(*MyVec::<i32>::from_bytes(&bytes).unwrap(), *v);
+
+        let nested: MyVec<MyVec<u8>> = [MyVec::new(), v.iter().map(|&x| x as u8).collect()]
+            .into_iter()
+            .collect();
+        let decoded = MyVec::<MyVec<u8>>::from_bytes(&nested.to_bytes()).unwrap();
+        assert!(decoded.iter().map(|v| &**v).eq(nested.iter().map(|v| &**v)));
+        assert_eq!(
+            MyVec::<u8>::from_bytes(&bytes[..4]).unwrap_err(),
+            DecodeError::UnexpectedEnd
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let v: MyVec<MyVec<i32>> = "[[1, -2], []]".parse().unwrap();
+        assert_eq!(v.to_string(), "[[1, -2], []]");
+        assert_eq!(format!("{:03}", v[0]), "[001, -02]");
+        assert!("[1,".parse::<MyVec<i32>>().is_err());
+        let strings: MyVec<String> = r#"["a, b", "", " x "]"#.parse().unwrap();
+        assert_eq!(*strings, ["a, b", "", " x "]);
+        let reparsed: MyVec<String> = strings.to_string().parse().unwrap();
+        assert_eq!(*reparsed, *strings);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let values: MyVec<i32> = [1, 2, 3].into_iter().collect();
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let back: MyVec<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(*back, *values);
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn rkyv_round_trip() {
+        use rkyv::{rancor::Error, vec::ArchivedVec, Archived};
+
+        let vec: MyVec<u32> = (0..100).collect();
+        let bytes = rkyv::to_bytes::<Error>(&vec).unwrap();
+        let archived = rkyv::access::<ArchivedVec<Archived<u32>>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 100);
+        assert_eq!(archived[42], 42);
+        let back: MyVec<u32> = rkyv::deserialize::<_, Error>(archived).unwrap();
+        assert_eq!(*back, *vec);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let vec = MyVec::<u8>::arbitrary(&mut Unstructured::new(&[1, 5, 1, 7, 0, 9])).unwrap();
+        assert_eq!(vec[..], [5, 7]);
+        let vec = MyVec::<u8>::arbitrary_take_rest(Unstructured::new(&[1, 5, 0, 7])).unwrap();
+        assert_eq!(vec[..], [5]);
+    }
 }
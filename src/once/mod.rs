@@ -0,0 +1,549 @@
+use core::{
+    cell::UnsafeCell,
+    convert::Infallible,
+    fmt,
+    mem::{self, MaybeUninit},
+    ops::Deref,
+    panic::{RefUnwindSafe, UnwindSafe},
+    sync::atomic::{AtomicU8, Ordering::*},
+};
+
+use crate::compat;
+
+/// A cell that can be written to only once, for a single thread.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::once::OnceCell;
+///
+/// let cell = OnceCell::new();
+/// assert_eq!(cell.get(), None);
+/// assert_eq!(cell.get_or_init(|| 1), &1);
+/// // Later initializers are ignored.
+/// assert_eq!(cell.get_or_init(|| 2), &1);
+/// assert_eq!(cell.set(3), Err(3));
+/// ```
+pub struct OnceCell<T> {
+    // Never written again once it is `Some`, which keeps the references
+    // handed out by `get` valid.
+    inner: UnsafeCell<Option<T>>,
+}
+
+impl<T> OnceCell<T> {
+    /// Create a new, empty cell.
+    pub const fn new() -> Self {
+        OnceCell {
+            inner: UnsafeCell::new(None),
+        }
+    }
+
+    /// The value, if the cell has been written to.
+    pub fn get(&self) -> Option<&T> {
+        unsafe { &*self.inner.get() }.as_ref()
+    }
+
+    /// A mutable reference to the value, if the cell has been written to.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.inner.get_mut().as_mut()
+    }
+
+    /// Write `value` to the cell, or hand it back if the cell is already full.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self.get().is_some() {
+            return Err(value);
+        }
+        // The cell is empty, so no reference to its contents exists.
+        unsafe { *self.inner.get() = Some(value) };
+        Ok(())
+    }
+
+    /// The value, initializing the cell with `f` first if it is empty.
+    /// Panics if `f` initializes the cell itself.
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        match self.get_or_try_init(|| Ok::<T, Infallible>(f())) {
+            Ok(value) => value,
+        }
+    }
+
+    /// The value, initializing the cell with `f` first if it is empty. If `f`
+    /// fails, the cell stays empty and the error is returned.
+    /// Panics if `f` initializes the cell itself.
+    pub fn get_or_try_init<E, F: FnOnce() -> Result<T, E>>(&self, f: F) -> Result<&T, E> {
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+        let value = f()?;
+        assert!(self.set(value).is_ok(), "reentrant init");
+        Ok(self.get().unwrap())
+    }
+
+    /// Take the value out, leaving the cell empty.
+    pub fn take(&mut self) -> Option<T> {
+        self.inner.get_mut().take()
+    }
+
+    /// Consume the cell and return the value, if any.
+    pub fn into_inner(self) -> Option<T> {
+        self.inner.into_inner()
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for OnceCell<T> {
+    fn clone(&self) -> Self {
+        OnceCell {
+            inner: UnsafeCell::new(self.get().cloned()),
+        }
+    }
+}
+
+impl<T> From<T> for OnceCell<T> {
+    fn from(value: T) -> Self {
+        OnceCell {
+            inner: UnsafeCell::new(Some(value)),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OnceCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            Some(value) => f.debug_tuple("OnceCell").field(value).finish(),
+            None => f.write_str("OnceCell(<uninit>)"),
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for OnceCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: Eq> Eq for OnceCell<T> {}
+
+// States of a `OnceLock`.
+const INCOMPLETE: u8 = 0;
+const RUNNING: u8 = 1;
+const COMPLETE: u8 = 2;
+
+/// A thread-safe cell that can be written to only once.
+///
+/// When several threads race to initialize the cell, one of them runs its
+/// initializer while the others wait for it to finish, then all of them see
+/// the same value. If the initializer panics, the cell stays empty and the next
+/// caller runs its own.
+///
+/// # Examples
+///
+/// A lookup table built the first time it is needed:
+///
+/// ```
+/// use std::collections::HashMap;
+/// use my_std::once::OnceLock;
+///
+/// static KEYWORDS: OnceLock<HashMap<&str, u32>> = OnceLock::new();
+///
+/// fn keyword_id(word: &str) -> Option<u32> {
+///     let table = KEYWORDS.get_or_init(|| {
+///         ["fn", "let", "match"].into_iter().zip(0..).collect()
+///     });
+///     table.get(word).copied()
+/// }
+///
+/// let handle = std::thread::spawn(|| keyword_id("let"));
+/// assert_eq!(keyword_id("match"), Some(2));
+/// assert_eq!(handle.join().unwrap(), Some(1));
+/// ```
+pub struct OnceLock<T> {
+    state: AtomicU8,
+    // Initialized once `state` is `COMPLETE`, and never written again.
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+// A `&OnceLock<T>` gives `&T` to every thread and lets any of them store the
+// `T`, which may then be dropped by another one.
+unsafe impl<T: Sync + Send> Sync for OnceLock<T> {}
+unsafe impl<T: Send> Send for OnceLock<T> {}
+
+impl<T: RefUnwindSafe + UnwindSafe> RefUnwindSafe for OnceLock<T> {}
+impl<T: UnwindSafe> UnwindSafe for OnceLock<T> {}
+
+// Puts a `OnceLock` back to `INCOMPLETE` if its initializer panics or fails.
+struct ResetOnDrop<'a> {
+    state: &'a AtomicU8,
+}
+
+impl Drop for ResetOnDrop<'_> {
+    fn drop(&mut self) {
+        self.state.store(INCOMPLETE, Release);
+    }
+}
+
+impl<T> OnceLock<T> {
+    /// Create a new, empty cell.
+    pub const fn new() -> Self {
+        OnceLock {
+            state: AtomicU8::new(INCOMPLETE),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// The value, if the cell has been written to. Never blocks.
+    pub fn get(&self) -> Option<&T> {
+        // `Acquire` pairs with the `Release` store of `COMPLETE`, so the value
+        // written before it is visible.
+        if self.state.load(Acquire) == COMPLETE {
+            Some(unsafe { (*self.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// A mutable reference to the value, if the cell has been written to.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if *self.state.get_mut() == COMPLETE {
+            Some(unsafe { self.value.get_mut().assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    /// Write `value` to the cell, or hand it back if the cell is already full.
+    /// Blocks while another thread is initializing the cell.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.get_or_init(|| value.take().unwrap());
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    /// The value, initializing the cell with `f` first if it is empty. Blocks
+    /// while another thread is initializing the cell.
+    ///
+    /// Calling it from `f` on the same cell never returns.
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        match self.get_or_try_init(|| Ok::<T, Infallible>(f())) {
+            Ok(value) => value,
+        }
+    }
+
+    /// The value, initializing the cell with `f` first if it is empty. If `f`
+    /// fails, the cell stays empty and the error is returned. Blocks while
+    /// another thread is initializing the cell.
+    pub fn get_or_try_init<E, F: FnOnce() -> Result<T, E>>(&self, f: F) -> Result<&T, E> {
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+        loop {
+            match self
+                .state
+                .compare_exchange(INCOMPLETE, RUNNING, Acquire, Acquire)
+            {
+                Ok(_) => {
+                    let reset = ResetOnDrop { state: &self.state };
+                    let value = f()?;
+                    // Only the thread that moved the state to `RUNNING` writes.
+                    unsafe { (*self.value.get()).write(value) };
+                    mem::forget(reset);
+                    self.state.store(COMPLETE, Release);
+                    return Ok(self.get().unwrap());
+                }
+                Err(COMPLETE) => return Ok(self.get().unwrap()),
+                // Initializers are expected to be short, so wait by yielding.
+                Err(_) => compat::yield_now(),
+            }
+        }
+    }
+
+    /// Take the value out, leaving the cell empty.
+    pub fn take(&mut self) -> Option<T> {
+        if *self.state.get_mut() != COMPLETE {
+            return None;
+        }
+        *self.state.get_mut() = INCOMPLETE;
+        Some(unsafe { self.value.get_mut().assume_init_read() })
+    }
+
+    /// Consume the cell and return the value, if any.
+    pub fn into_inner(mut self) -> Option<T> {
+        self.take()
+    }
+}
+
+impl<T> Drop for OnceLock<T> {
+    fn drop(&mut self) {
+        drop(self.take());
+    }
+}
+
+impl<T> Default for OnceLock<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for OnceLock<T> {
+    fn clone(&self) -> Self {
+        let cell = OnceLock::new();
+        if let Some(value) = self.get() {
+            let _ = cell.set(value.clone());
+        }
+        cell
+    }
+}
+
+impl<T> From<T> for OnceLock<T> {
+    fn from(value: T) -> Self {
+        let cell = OnceLock::new();
+        let _ = cell.set(value);
+        cell
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OnceLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            Some(value) => f.debug_tuple("OnceLock").field(value).finish(),
+            None => f.write_str("OnceLock(<uninit>)"),
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for OnceLock<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: Eq> Eq for OnceLock<T> {}
+
+/// A value computed on first access, for a single thread.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::once::LazyCell;
+///
+/// let squares = LazyCell::new(|| (0..10).map(|i| i * i).collect::<Vec<_>>());
+/// assert_eq!(squares[3], 9);
+/// ```
+pub struct LazyCell<T, F = fn() -> T> {
+    cell: OnceCell<T>,
+    // Taken by the first access.
+    init: UnsafeCell<Option<F>>,
+}
+
+impl<T, F: FnOnce() -> T> LazyCell<T, F> {
+    /// Create a new lazy value computed by `f`.
+    pub const fn new(f: F) -> Self {
+        LazyCell {
+            cell: OnceCell::new(),
+            init: UnsafeCell::new(Some(f)),
+        }
+    }
+
+    /// The value, computing it first if needed.
+    /// Panics if a previous computation panicked.
+    pub fn force(this: &Self) -> &T {
+        this.cell.get_or_init(|| {
+            // Only ever called once, as the cell is full afterwards.
+            let init = unsafe { (*this.init.get()).take() };
+            match init {
+                Some(f) => f(),
+                None => panic!("LazyCell instance has previously been poisoned"),
+            }
+        })
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for LazyCell<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        LazyCell::force(self)
+    }
+}
+
+impl<T: Default> Default for LazyCell<T> {
+    fn default() -> Self {
+        LazyCell::new(T::default)
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for LazyCell<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cell.get() {
+            Some(value) => f.debug_tuple("LazyCell").field(value).finish(),
+            None => f.write_str("LazyCell(<uninit>)"),
+        }
+    }
+}
+
+/// A thread-safe value computed on first access, typically in a `static`.
+///
+/// Built on [`OnceLock`]: the first thread to access the value computes it and
+/// the others wait for it.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::once::LazyLock;
+///
+/// static PRIMES: LazyLock<Vec<u32>> = LazyLock::new(|| {
+///     (2..100).filter(|n| (2..*n).all(|d| n % d != 0)).collect()
+/// });
+///
+/// assert_eq!(PRIMES.len(), 25);
+/// assert!(std::thread::spawn(|| PRIMES.contains(&97)).join().unwrap());
+/// ```
+pub struct LazyLock<T, F = fn() -> T> {
+    cell: OnceLock<T>,
+    // Only touched by the thread initializing `cell`.
+    init: UnsafeCell<Option<F>>,
+}
+
+unsafe impl<T: Sync + Send, F: Send> Sync for LazyLock<T, F> {}
+
+impl<T, F: FnOnce() -> T> LazyLock<T, F> {
+    /// Create a new lazy value computed by `f`.
+    pub const fn new(f: F) -> Self {
+        LazyLock {
+            cell: OnceLock::new(),
+            init: UnsafeCell::new(Some(f)),
+        }
+    }
+
+    /// The value, computing it first if needed.
+    /// Panics if a previous computation panicked.
+    pub fn force(this: &Self) -> &T {
+        this.cell.get_or_init(|| {
+            // `OnceLock` runs a single initializer at a time, and none after
+            // one succeeded.
+            let init = unsafe { (*this.init.get()).take() };
+            match init {
+                Some(f) => f(),
+                None => panic!("LazyLock instance has previously been poisoned"),
+            }
+        })
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for LazyLock<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        LazyLock::force(self)
+    }
+}
+
+impl<T: Default> Default for LazyLock<T> {
+    fn default() -> Self {
+        LazyLock::new(T::default)
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for LazyLock<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cell.get() {
+            Some(value) => f.debug_tuple("LazyLock").field(value).finish(),
+            None => f.write_str("LazyLock(<uninit>)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String};
+
+    use super::*;
+    use std::panic;
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    #[test]
+    fn once_cell() {
+        let mut cell = OnceCell::new();
+        assert_eq!(cell.get_or_try_init(|| Err("no")), Err("no"));
+        assert_eq!(cell.get(), None);
+        assert_eq!(cell.set(1), Ok(()));
+        assert_eq!(format!("{cell:?}"), "OnceCell(1)");
+        *cell.get_mut().unwrap() += 1;
+        assert_eq!(cell.take(), Some(2));
+        assert_eq!(cell.into_inner(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "reentrant init")]
+    fn once_cell_reentrant_init() {
+        let cell = OnceCell::new();
+        cell.get_or_init(|| {
+            cell.set(1).unwrap();
+            2
+        });
+    }
+
+    #[test]
+    fn once_lock_runs_one_initializer() {
+        let cell = OnceLock::new();
+        let runs = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for i in 0..8 {
+                let (cell, runs) = (&cell, &runs);
+                s.spawn(move || {
+                    let value = cell.get_or_init(|| {
+                        runs.fetch_add(1, SeqCst);
+                        thread::yield_now();
+                        i
+                    });
+                    assert_eq!(cell.get(), Some(value));
+                });
+            }
+        });
+        assert_eq!(runs.load(SeqCst), 1);
+        assert!(cell.set(100).is_err());
+    }
+
+    #[test]
+    fn once_lock_retries_after_panic() {
+        let cell = OnceLock::new();
+        let result = panic::catch_unwind(|| {
+            cell.get_or_init(|| panic!("boom"));
+        });
+        assert!(result.is_err());
+        assert_eq!(cell.get(), None);
+        assert_eq!(cell.get_or_init(|| String::from("ok")), "ok");
+        assert_eq!(cell.into_inner().as_deref(), Some("ok"));
+    }
+
+    #[test]
+    fn lazy_values_are_computed_once() {
+        let runs = AtomicUsize::new(0);
+        let lazy = LazyCell::new(|| {
+            runs.fetch_add(1, SeqCst);
+            5
+        });
+        assert_eq!(format!("{lazy:?}"), "LazyCell(<uninit>)");
+        assert_eq!(*lazy + *lazy, 10);
+        assert_eq!(runs.load(SeqCst), 1);
+
+        let lazy = LazyLock::new(|| {
+            runs.fetch_add(1, SeqCst);
+            String::from("shared")
+        });
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| assert_eq!(lazy.as_str(), "shared"));
+            }
+        });
+        assert_eq!(runs.load(SeqCst), 2);
+    }
+}
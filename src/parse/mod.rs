@@ -0,0 +1,486 @@
+use alloc::{string::String, vec::Vec};
+use core::{error, fmt, str::FromStr};
+
+use crate::string::MyString;
+
+/// A value that can be read by a [`Parser`].
+///
+/// The collections that implement `FromStr` with this module do it through
+/// `Parse`, so they can be nested: `"[[1], [2, 3]]"` parses as a vector of
+/// vectors. Lists are written `[a, b, c]` and maps `{k: v, k: v}`, with
+/// whitespace allowed between items and an optional trailing comma. Strings
+/// and chars are quoted and escaped like Rust literals, `"a, b"` and `'c'`, so
+/// any of them round-trips through `Display`. Anything else is a scalar: the
+/// run of text up to the next `[`, `]`, `{`, `}`, `,` or `:`, trimmed and
+/// handed to the type's own `FromStr`.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::{index_map::IndexMap, linked_list::LinkedList, my_vec::MyVec};
+///
+/// let list: LinkedList<i32> = "[1, 2, 3]".parse().unwrap();
+/// assert_eq!(list.to_string(), "[1, 2, 3]");
+///
+/// let map: IndexMap<String, MyVec<f64>> = r#"{"a": [1.5], "b": []}"#.parse().unwrap();
+/// assert_eq!(**map.get("a").unwrap(), [1.5]);
+/// assert_eq!(map.to_string(), r#"{"a": [1.5], "b": []}"#);
+///
+/// let err = "[1, x]".parse::<MyVec<u8>>().unwrap_err();
+/// assert_eq!(err.to_string(), "expected u8 at byte 4");
+/// ```
+pub trait Parse: Sized {
+    /// Read a value at the parser's position and move past it.
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError>;
+}
+
+/// A value that can be written in the format of [`Parse`].
+///
+/// The collections' `Display` impls write their items with it, so that
+/// strings in them come out quoted.
+pub trait Format {
+    /// Write the value, passing the formatter's flags on to numbers.
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+/// Parse all of `s` as a `T`, failing if anything but whitespace follows it.
+pub fn from_str<T: Parse>(s: &str) -> Result<T, ParseError> {
+    let mut parser = Parser::new(s);
+    let value = T::parse(&mut parser)?;
+    parser.finish()?;
+    Ok(value)
+}
+
+/// The error returned when text doesn't parse, saying what was expected
+/// where.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    position: usize,
+    expected: Expected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expected {
+    Char(char),
+    CommaOr(char),
+    Value(&'static str),
+    End,
+}
+
+impl ParseError {
+    /// Byte offset in the input where parsing failed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected ")?;
+        match self.expected {
+            Expected::Char(c) => write!(f, "`{c}`")?,
+            Expected::CommaOr(c) => write!(f, "`,` or `{c}`")?,
+            Expected::Value(name) => f.write_str(name)?,
+            Expected::End => f.write_str("end of input")?,
+        }
+        write!(f, " at byte {}", self.position)
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// A recursive-descent parser over the text format of [`Parse`].
+#[derive(Debug, Clone)]
+pub struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Start parsing at the beginning of `input`.
+    pub fn new(input: &'a str) -> Self {
+        Parser { input, position: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, expected: Expected) -> ParseError {
+        ParseError {
+            position: self.position,
+            expected,
+        }
+    }
+
+    /// Skip whitespace, then the character `c` if it comes next. Returns
+    /// whether it did.
+    pub fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(c);
+        if found {
+            self.position += c.len_utf8();
+        }
+        found
+    }
+
+    /// Skip whitespace and the character `c`, which must come next.
+    pub fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(self.error(Expected::Char(c))),
+        }
+    }
+
+    /// Skip a scalar and parse it with `T`'s `FromStr`. `name` describes a
+    /// `T` in the error if there is no scalar or it doesn't parse.
+    pub fn scalar<T: FromStr>(&mut self, name: &'static str) -> Result<T, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(['[', ']', '{', '}', ',', ':'])
+            .unwrap_or(rest.len());
+        let token = rest[..len].trim_end();
+        match token.parse() {
+            Ok(value) if !token.is_empty() => {
+                self.position += len;
+                Ok(value)
+            }
+            _ => Err(self.error(Expected::Value(name))),
+        }
+    }
+
+    /// Skip a literal between two `quote`s, with the escapes of Rust string
+    /// literals, and return what it stands for.
+    pub fn quoted(&mut self, quote: char) -> Result<String, ParseError> {
+        self.expect(quote)?;
+        let mut value = String::new();
+        loop {
+            let rest = self.rest();
+            let c = match rest.chars().next() {
+                Some(c) if c == quote => {
+                    self.position += c.len_utf8();
+                    return Ok(value);
+                }
+                Some('\\') => {
+                    let (c, len) = unescape(&rest[1..])
+                        .ok_or_else(|| self.error(Expected::Value("escape sequence")))?;
+                    self.position += len;
+                    c
+                }
+                Some(c) => {
+                    self.position += c.len_utf8();
+                    c
+                }
+                None => return Err(self.error(Expected::Char(quote))),
+            };
+            value.push(c);
+        }
+    }
+
+    // Parse `item` repeatedly, separated by commas, up to `close`.
+    fn items(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        while !self.eat(close) {
+            item(self)?;
+            if !self.eat(',') {
+                return match self.eat(close) {
+                    true => Ok(()),
+                    false => Err(self.error(Expected::CommaOr(close))),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a list, `[a, b, c]`, of `T`s.
+    pub fn list<T: Parse>(&mut self) -> Result<Vec<T>, ParseError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.items(']', |p| {
+            items.push(T::parse(p)?);
+            Ok(())
+        })?;
+        Ok(items)
+    }
+
+    /// Parse a map, `{k: v, k: v}`, of `K`s to `V`s, as its entries in order.
+    pub fn map<K: Parse, V: Parse>(&mut self) -> Result<Vec<(K, V)>, ParseError> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.items('}', |p| {
+            let key = K::parse(p)?;
+            p.expect(':')?;
+            entries.push((key, V::parse(p)?));
+            Ok(())
+        })?;
+        Ok(entries)
+    }
+
+    /// Check that only whitespace is left.
+    pub fn finish(mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        match self.rest().is_empty() {
+            true => Ok(()),
+            false => Err(self.error(Expected::End)),
+        }
+    }
+}
+
+// The character an escape sequence stands for, and the length of the
+// sequence including its backslash. `s` starts after the backslash.
+fn unescape(s: &str) -> Option<(char, usize)> {
+    let c = match s.chars().next()? {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        '0' => '\0',
+        c @ ('\\' | '\'' | '"') => c,
+        'u' => {
+            let hex = s.strip_prefix("u{")?;
+            let end = hex.find('}')?;
+            let code = u32::from_str_radix(&hex[..end], 16).ok()?;
+            return Some((char::from_u32(code)?, end + 4));
+        }
+        _ => return None,
+    };
+    Some((c, 2))
+}
+
+macro_rules! impl_parse {
+    ($($t:ty)*) => {$(
+        impl Parse for $t {
+            fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+                parser.scalar(stringify!($t))
+            }
+        }
+
+        impl Format for $t {
+            fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(self, f)
+            }
+        }
+    )*};
+}
+
+impl_parse!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize f32 f64 bool);
+
+impl Parse for char {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        parser.skip_whitespace();
+        let start = parser.position;
+        let s = parser.quoted('\'')?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(ParseError {
+                position: start,
+                expected: Expected::Value("char"),
+            }),
+        }
+    }
+}
+
+impl Parse for String {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        parser.quoted('"')
+    }
+}
+
+impl Parse for MyString {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        parser.quoted('"').map(MyString::from)
+    }
+}
+
+// `Debug` quotes and escapes them just the way `Parser::quoted` reads them.
+impl Format for char {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl Format for str {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl Format for String {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().format(f)
+    }
+}
+
+impl Format for MyString {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().format(f)
+    }
+}
+
+impl<T: Format + ?Sized> Format for &T {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).format(f)
+    }
+}
+
+impl<T: Parse> Parse for Vec<T> {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        parser.list()
+    }
+}
+
+impl<T: Format> Format for Vec<T> {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_list(f, self)
+    }
+}
+
+/// Write `items` as a list in the format of [`Parse`], passing the
+/// formatter's flags on to each item.
+pub(crate) fn fmt_list<T: Format>(
+    f: &mut fmt::Formatter<'_>,
+    items: impl IntoIterator<Item = T>,
+) -> fmt::Result {
+    f.write_str("[")?;
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        item.format(f)?;
+    }
+    f.write_str("]")
+}
+
+/// Write `entries` as a map in the format of [`Parse`], passing the
+/// formatter's flags on to each key and value.
+pub(crate) fn fmt_map<K: Format, V: Format>(
+    f: &mut fmt::Formatter<'_>,
+    entries: impl IntoIterator<Item = (K, V)>,
+) -> fmt::Result {
+    f.write_str("{")?;
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        key.format(f)?;
+        f.write_str(": ")?;
+        value.format(f)?;
+    }
+    f.write_str("}")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::ToString, vec};
+
+    use super::*;
+
+    #[test]
+    fn scalars_and_lists() {
+        assert_eq!(from_str::<i64>(" -42 "), Ok(-42));
+        assert_eq!(from_str::<bool>("true"), Ok(true));
+        assert_eq!(
+            from_str::<String>(r#" "hello world" "#),
+            Ok("hello world".into())
+        );
+        assert_eq!(from_str::<Vec<u8>>("[]"), Ok(vec![]));
+        assert_eq!(from_str::<Vec<u8>>("[ 1 ,2,\n3, ]"), Ok(vec![1, 2, 3]));
+        assert_eq!(
+            from_str::<Vec<Vec<char>>>("[['a'], [], ['b', 'c']]"),
+            Ok(vec![vec!['a'], vec![], vec!['b', 'c']])
+        );
+        let mut parser = Parser::new(r#"{"x": [1.5], "y": []}"#);
+        let map: Vec<(String, Vec<f32>)> = parser.map().unwrap();
+        assert_eq!(map, [("x".into(), vec![1.5]), ("y".into(), vec![])]);
+        assert!(parser.finish().is_ok());
+    }
+
+    #[test]
+    fn errors() {
+        let err = |s| from_str::<Vec<u32>>(s).unwrap_err().to_string();
+        assert_eq!(err(""), "expected `[` at byte 0");
+        assert_eq!(err("[1 2]"), "expected u32 at byte 1");
+        assert_eq!(err("[1, -2]"), "expected u32 at byte 4");
+        assert_eq!(err("[1, , 2]"), "expected u32 at byte 4");
+        assert_eq!(err("[1, 2"), "expected `,` or `]` at byte 5");
+        assert_eq!(err("[1] 2"), "expected end of input at byte 4");
+        let mut parser = Parser::new(r#"{"a" 1}"#);
+        assert_eq!(parser.map::<String, u8>().unwrap_err().position(), 5);
+
+        let err = |s| from_str::<Vec<String>>(s).unwrap_err().to_string();
+        assert_eq!(err("[a]"), "expected `\"` at byte 1");
+        assert_eq!(err(r#"["a]"#), "expected `\"` at byte 4");
+        assert_eq!(err(r#"["\q"]"#), "expected escape sequence at byte 2");
+        assert_eq!(
+            err(r#"["\u{110000}"]"#),
+            "expected escape sequence at byte 2"
+        );
+        let err = from_str::<char>(" 'ab'").unwrap_err().to_string();
+        assert_eq!(err, "expected char at byte 1");
+    }
+
+    #[test]
+    fn quoted_strings() {
+        let strings: Vec<String> = [
+            "a, b",
+            "",
+            " x ",
+            "{k: [v]}",
+            "\"'\\",
+            "tab\tnew\nline\0",
+            "\u{7f}é",
+        ]
+        .map(String::from)
+        .into();
+        let text = format!("{}", List(&strings));
+        assert_eq!(from_str::<Vec<String>>(&text), Ok(strings));
+        assert_eq!(
+            from_str::<Vec<MyString>>(r#"["a, b", ""]"#),
+            Ok(vec!["a, b".into(), "".into()])
+        );
+        assert_eq!(from_str::<String>(r#"" x ""#), Ok(" x ".into()));
+        assert_eq!(
+            from_str::<String>(r#""\u{1F600}\'""#),
+            Ok("\u{1F600}'".into())
+        );
+
+        let chars = vec![',', '\'', '"', ' ', '\n', ']'];
+        let text = format!("{}", List(&chars));
+        assert_eq!(text, r#"[',', '\'', '"', ' ', '\n', ']']"#);
+        assert_eq!(from_str::<Vec<char>>(&text), Ok(chars));
+    }
+
+    // Writes a vector in the format of `Parse`.
+    struct List<'a, T>(&'a Vec<T>);
+
+    impl<T: Format> fmt::Display for List<'_, T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.format(f)
+        }
+    }
+
+    #[test]
+    fn display() {
+        struct Map(Vec<(char, u8)>);
+        impl fmt::Display for Map {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt_map(f, self.0.iter().map(|(k, v)| (k, v)))
+            }
+        }
+        assert_eq!(List::<u8>(&vec![]).to_string(), "[]");
+        assert_eq!(format!("{:.2}", List(&vec![1.0, 2.5])), "[1.00, 2.50]");
+        let map = Map(vec![('a', 1), ('b', 2)]).to_string();
+        assert_eq!(map, "{'a': 1, 'b': 2}");
+        let mut parser = Parser::new(&map);
+        assert_eq!(parser.map::<char, u8>(), Ok(vec![('a', 1), ('b', 2)]));
+    }
+}
@@ -0,0 +1,537 @@
+use alloc::rc::Rc;
+use alloc::{vec, vec::Vec};
+use core::{fmt, mem, ops};
+
+const BITS: usize = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+#[derive(Clone)]
+enum Node<T> {
+    Branch(Vec<Rc<Node<T>>>),
+    Leaf(Vec<T>),
+}
+
+impl<T> Node<T> {
+    fn branch(&self) -> &[Rc<Node<T>>] {
+        match self {
+            Node::Branch(children) => children,
+            Node::Leaf(_) => unreachable!("expected a branch"),
+        }
+    }
+
+    fn leaf(&self) -> &[T] {
+        match self {
+            Node::Leaf(values) => values,
+            Node::Branch(_) => unreachable!("expected a leaf"),
+        }
+    }
+}
+
+// Wrap `node` in single-child branches until it sits `level` bits below the new top.
+fn new_path<T>(level: usize, node: Rc<Node<T>>) -> Rc<Node<T>> {
+    if level == 0 {
+        node
+    } else {
+        Rc::new(Node::Branch(vec![new_path(level - BITS, node)]))
+    }
+}
+
+/// An immutable vector with structural sharing.
+///
+/// Elements are stored in a 32-way trie of reference-counted nodes, with the
+/// last (up to) 32 elements kept in a separate tail. Cloning a vector is O(1)
+/// and gives an independent snapshot: [`Vector::push_back`] and [`Vector::update`]
+/// return a new vector that shares every node but the O(log n) path they touch.
+///
+/// For building a vector out of many elements, [`Vector::transient`] returns a
+/// [`Transient`] that mutates nodes in place as long as it owns them alone,
+/// instead of copying a path for every single operation.
+///
+/// The nodes are not relaxed, so concatenation and splitting are not supported.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::persistent::Vector;
+///
+/// let v1: Vector<i32> = (0..100).collect();
+/// let v2 = v1.push_back(100);
+/// let v3 = v2.update(0, -1);
+///
+/// assert_eq!(v1.len(), 100);
+/// assert_eq!(v2.len(), 101);
+/// assert_eq!(v2[0], 0);
+/// assert_eq!(v3[0], -1);
+/// assert_eq!(v3.get(100), Some(&100));
+/// ```
+pub struct Vector<T> {
+    len: usize,
+    // Number of bits to shift an index by to find its child in the root.
+    shift: usize,
+    root: Rc<Node<T>>,
+    tail: Rc<Vec<T>>,
+}
+
+impl<T> Clone for Vector<T> {
+    fn clone(&self) -> Self {
+        Vector {
+            len: self.len,
+            shift: self.shift,
+            root: Rc::clone(&self.root),
+            tail: Rc::clone(&self.tail),
+        }
+    }
+}
+
+impl<T> Default for Vector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Vector<T> {
+    /// Create a new, empty vector.
+    pub fn new() -> Self {
+        Vector {
+            len: 0,
+            shift: BITS,
+            root: Rc::new(Node::Branch(Vec::new())),
+            tail: Rc::new(Vec::new()),
+        }
+    }
+
+    /// Number of elements in the vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the vector is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Index of the first element stored in the tail.
+    fn tail_offset(&self) -> usize {
+        if self.len < WIDTH {
+            0
+        } else {
+            ((self.len - 1) >> BITS) << BITS
+        }
+    }
+
+    // The leaf holding `index`, which must be in bounds.
+    fn leaf_for(&self, index: usize) -> &[T] {
+        if index >= self.tail_offset() {
+            return &self.tail;
+        }
+        let mut node = &self.root;
+        let mut level = self.shift;
+        while level > 0 {
+            node = &node.branch()[(index >> level) & MASK];
+            level -= BITS;
+        }
+        node.leaf()
+    }
+
+    /// Get a reference to the element at `index`. O(log n).
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&self.leaf_for(index)[index & MASK])
+    }
+
+    /// The first element.
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// The last element. O(1).
+    pub fn last(&self) -> Option<&T> {
+        self.tail.last()
+    }
+
+    /// Iterate over the elements in order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            vec: self,
+            index: 0,
+            leaf: &[],
+        }
+    }
+
+    /// Return `true` if both vectors share the same nodes, so they are equal
+    /// without comparing elements.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && Rc::ptr_eq(&self.root, &other.root)
+            && Rc::ptr_eq(&self.tail, &other.tail)
+    }
+}
+
+impl<T: Clone> Vector<T> {
+    /// Return a new vector with `value` appended. O(log n).
+    pub fn push_back(&self, value: T) -> Self {
+        let mut vec = self.clone();
+        vec.push_mut(value);
+        vec
+    }
+
+    /// Return a new vector with the element at `index` replaced by `value`. O(log n).
+    /// Panics if `index >= len`.
+    pub fn update(&self, index: usize, value: T) -> Self {
+        let mut vec = self.clone();
+        vec.set_mut(index, value);
+        vec
+    }
+
+    /// Start a batch of in-place modifications on a snapshot of this vector.
+    pub fn transient(&self) -> Transient<T> {
+        Transient { vec: self.clone() }
+    }
+
+    // The mutating operations below copy a node only when it's shared with
+    // another vector, via `Rc::make_mut`.
+
+    fn push_mut(&mut self, value: T) {
+        if self.tail.len() < WIDTH {
+            Rc::make_mut(&mut self.tail).push(value);
+            self.len += 1;
+            return;
+        }
+        // The tail is full: move it into the trie and start a new one.
+        let tail = mem::replace(&mut self.tail, Rc::new(vec![value]));
+        let leaf = Rc::new(Node::Leaf(Rc::unwrap_or_clone(tail)));
+        if (self.len >> BITS) > (1 << self.shift) {
+            // The trie is full, grow a new root on top of it.
+            let path = new_path(self.shift, leaf);
+            let old_root = mem::replace(&mut self.root, Rc::new(Node::Branch(Vec::new())));
+            self.root = Rc::new(Node::Branch(vec![old_root, path]));
+            self.shift += BITS;
+        } else {
+            Self::push_leaf(&mut self.root, self.shift, self.len - 1, leaf);
+        }
+        self.len += 1;
+    }
+
+    // Insert `leaf` below `node` on the path to `index`.
+    fn push_leaf(node: &mut Rc<Node<T>>, level: usize, index: usize, leaf: Rc<Node<T>>) {
+        let Node::Branch(children) = Rc::make_mut(node) else {
+            unreachable!("expected a branch");
+        };
+        let sub = (index >> level) & MASK;
+        if level == BITS {
+            children.push(leaf);
+        } else if sub < children.len() {
+            Self::push_leaf(&mut children[sub], level - BITS, index, leaf);
+        } else {
+            children.push(new_path(level - BITS, leaf));
+        }
+    }
+
+    fn set_mut(&mut self, index: usize, value: T) {
+        let len = self.len;
+        if index >= len {
+            panic!("index out of bounds: the len is {len} but the index is {index}");
+        }
+        if index >= self.tail_offset() {
+            Rc::make_mut(&mut self.tail)[index & MASK] = value;
+            return;
+        }
+        let mut node = &mut self.root;
+        let mut level = self.shift;
+        loop {
+            match Rc::make_mut(node) {
+                Node::Branch(children) => {
+                    node = &mut children[(index >> level) & MASK];
+                    level -= BITS;
+                }
+                Node::Leaf(values) => {
+                    values[index & MASK] = value;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// A mutable builder for a [`Vector`].
+///
+/// A transient starts as a snapshot of a vector. The first write to a node copies
+/// it, and later writes to the same node happen in place, so bulk construction
+/// costs about as much as pushing to a `Vec`. Call [`Transient::persistent`] to
+/// turn it back into an immutable vector.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::persistent::Vector;
+///
+/// let empty = Vector::new();
+/// let mut t = empty.transient();
+/// for i in 0..1000 {
+///     t.push(i);
+/// }
+/// t.set(0, 42);
+/// let v = t.persistent();
+/// assert_eq!(v.len(), 1000);
+/// assert_eq!(v[0], 42);
+/// assert!(empty.is_empty());
+/// ```
+pub struct Transient<T> {
+    vec: Vector<T>,
+}
+
+impl<T: Clone> Transient<T> {
+    /// Number of elements.
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Return `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Get a reference to the element at `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.vec.get(index)
+    }
+
+    /// Append an element.
+    pub fn push(&mut self, value: T) {
+        self.vec.push_mut(value);
+    }
+
+    /// Replace the element at `index`.
+    /// Panics if `index >= len`.
+    pub fn set(&mut self, index: usize, value: T) {
+        self.vec.set_mut(index, value);
+    }
+
+    /// Finish the batch and return the resulting vector.
+    pub fn persistent(self) -> Vector<T> {
+        self.vec
+    }
+}
+
+impl<T: Clone> Extend<T> for Transient<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T> ops::Index<usize> for Vector<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+        self.get(index).unwrap_or_else(|| {
+            panic!("index out of bounds: the len is {len} but the index is {index}")
+        })
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Vector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Vector<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || (self.len == other.len && self.iter().eq(other.iter()))
+    }
+}
+
+impl<T: Eq> Eq for Vector<T> {}
+
+impl<T: Clone> FromIterator<T> for Vector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut t = Vector::new().transient();
+        t.extend(iter);
+        t.persistent()
+    }
+}
+
+/// Iterator over the elements of a [`Vector`].
+pub struct Iter<'a, T> {
+    vec: &'a Vector<T>,
+    index: usize,
+    // Rest of the current leaf, so only one lookup per leaf is needed.
+    leaf: &'a [T],
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.vec.len {
+            return None;
+        }
+        if self.leaf.is_empty() {
+            self.leaf = &self.vec.leaf_for(self.index)[self.index & MASK..];
+        }
+        let (first, rest) = self.leaf.split_first()?;
+        self.leaf = rest;
+        self.index += 1;
+        Some(first)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.vec.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Vector<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for Vector<T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for Vector<T>
+where
+    T: serde::Deserialize<'de> + Clone,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for Vector<T>
+where
+    T: arbitrary::Arbitrary<'a> + Clone,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for Vector<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static + Clone,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_back() {
+        let mut v = Vector::new();
+        // Enough elements for a trie three levels deep.
+        for i in 0..40_000 {
+            v = v.push_back(i);
+        }
+        assert_eq!(v.len(), 40_000);
+        for i in 0..40_000 {
+            assert_eq!(v[i], i);
+        }
+        assert_eq!(v.get(40_000), None);
+        assert_eq!(v.first(), Some(&0));
+        assert_eq!(v.last(), Some(&39_999));
+    }
+
+    #[test]
+    fn snapshots() {
+        let v1: Vector<_> = (0..1000).collect();
+        let v2 = v1.push_back(1000);
+        let v3 = v1.update(500, -1);
+        let v4 = v3.update(999, -2);
+        assert_eq!(v1.len(), 1000);
+        assert_eq!(v2.len(), 1001);
+        assert_eq!(v1[500], 500);
+        assert_eq!(v2[500], 500);
+        assert_eq!(v3[500], -1);
+        assert_eq!(v3[999], 999);
+        assert_eq!(v4[999], -2);
+        assert_ne!(v1, v3);
+        assert_eq!(v1, v1.clone());
+    }
+
+    #[test]
+    fn structural_sharing() {
+        let v1: Vector<_> = (0..100).collect();
+        let v2 = v1.update(0, 0);
+        // The path to index 0 was copied, the rest is shared.
+        assert!(!Rc::ptr_eq(&v1.root, &v2.root));
+        assert!(Rc::ptr_eq(&v1.tail, &v2.tail));
+        assert!(Rc::ptr_eq(&v1.root.branch()[1], &v2.root.branch()[1]));
+    }
+
+    #[test]
+    fn transient() {
+        let v1: Vector<_> = (0..10).collect();
+        let mut t = v1.transient();
+        t.extend(10..2000);
+        t.set(5, 0);
+        assert_eq!(t.get(1999), Some(&1999));
+        let v2 = t.persistent();
+        assert_eq!(v2.len(), 2000);
+        assert_eq!(v2[5], 0);
+        assert_eq!(v1.len(), 10);
+        assert_eq!(v1[5], 5);
+    }
+
+    #[test]
+    fn iter() {
+        let v: Vector<_> = (0..1500).collect();
+        assert!(v.iter().copied().eq(0..1500));
+        assert_eq!(v.iter().size_hint(), (1500, Some(1500)));
+        assert_eq!(Vector::<i32>::new().iter().next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds: the len is 3 but the index is 3")]
+    fn update_out_of_bounds() {
+        let v: Vector<_> = (0..3).collect();
+        v.update(3, 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let values: Vector<i32> = (0..40).collect();
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(
+            json,
+            serde_json::to_string(&(0..40).collect::<Vec<_>>()).unwrap()
+        );
+        let back: Vector<i32> = serde_json::from_str(&json).unwrap();
+        assert!(back.iter().eq(values.iter()));
+    }
+}
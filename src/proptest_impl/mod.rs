@@ -0,0 +1,83 @@
+//! Strategies shared by the collections' `proptest` impls, which build each
+//! collection from a vector of elements with `FromIterator`. Shrinking drops
+//! elements first and then simplifies the ones left.
+
+use alloc::vec::Vec;
+use core::{cmp::Ordering, fmt::Debug};
+
+use proptest::{
+    arbitrary::{any_with, Arbitrary},
+    collection::{vec, SizeRange},
+    strategy::{BoxedStrategy, Strategy},
+};
+
+/// Collect `A`s into `C`, in the order they were generated.
+pub(crate) fn collect<C, A>((size, args): (SizeRange, A::Parameters)) -> BoxedStrategy<C>
+where
+    C: FromIterator<A> + Debug + 'static,
+    A: Arbitrary + 'static,
+{
+    vec(any_with::<A>(args), size)
+        .prop_map(|elements| elements.into_iter().collect())
+        .boxed()
+}
+
+/// Like [`collect`], but the elements may also come sorted by `cmp`, in either
+/// direction. Sorted inserts turn a plain search tree into a path and keep a
+/// balanced one rebalancing along its edge. Shrinking goes back towards
+/// generation order.
+pub(crate) fn collect_ordered<C, A>(
+    (size, args): (SizeRange, A::Parameters),
+    cmp: fn(&A, &A) -> Ordering,
+) -> BoxedStrategy<C>
+where
+    C: FromIterator<A> + Debug + 'static,
+    A: Arbitrary + 'static,
+{
+    (vec(any_with::<A>(args), size), 0..3u8)
+        .prop_map(move |(elements, order)| sorted(elements, order, cmp).into_iter().collect())
+        .boxed()
+}
+
+/// Leave `elements` as they are for order 0, sort them ascending for order 1
+/// and descending for order 2.
+pub(crate) fn sorted<A>(mut elements: Vec<A>, order: u8, cmp: fn(&A, &A) -> Ordering) -> Vec<A> {
+    match order {
+        1 => elements.sort_by(cmp),
+        2 => elements.sort_by(|a, b| cmp(b, a)),
+        _ => {}
+    }
+    elements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::test_runner::{TestError, TestRunner};
+
+    #[test]
+    fn ordered_shapes() {
+        let strategy = collect_ordered::<Vec<u8>, u8>((SizeRange::from(16..32), ()), Ord::cmp);
+        let mut runner = TestRunner::deterministic();
+        let (mut ascending, mut descending) = (false, false);
+        for _ in 0..64 {
+            let elements = strategy.new_tree(&mut runner).unwrap().current();
+            ascending |= elements.is_sorted();
+            descending |= elements.iter().rev().is_sorted();
+        }
+        assert!(ascending && descending);
+    }
+
+    #[test]
+    fn shrinks_to_minimal() {
+        let strategy = collect::<Vec<u8>, u8>((SizeRange::default(), ()));
+        let result = TestRunner::deterministic().run(&strategy, |elements| {
+            proptest::prop_assert!(elements.len() < 3);
+            Ok(())
+        });
+        match result {
+            Err(TestError::Fail(_, elements)) => assert_eq!(elements, [0, 0, 0]),
+            result => panic!("expected a failure, got {result:?}"),
+        }
+    }
+}
@@ -0,0 +1,493 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{fmt, slice};
+
+pub use crate::spatial::Aabb;
+use crate::spatial::SpatialIndex;
+
+const DEFAULT_MAX_DEPTH: usize = 8;
+const DEFAULT_BUCKET_SIZE: usize = 8;
+
+// The four quadrants of a box, in the order NW, NE, SW, SE with y growing upward.
+fn quadrants(b: &Aabb) -> [Aabb; 4] {
+    let mid = [(b.min[0] + b.max[0]) / 2.0, (b.min[1] + b.max[1]) / 2.0];
+    [
+        Aabb::new([b.min[0], mid[1]], [mid[0], b.max[1]]),
+        Aabb::new(mid, b.max),
+        Aabb::new(b.min, mid),
+        Aabb::new([mid[0], b.min[1]], [b.max[0], mid[1]]),
+    ]
+}
+
+struct Node<T> {
+    bounds: Aabb,
+    // Items that don't fit entirely in one child stay at the node.
+    items: Vec<(Aabb, T)>,
+    children: Option<Box<[Node<T>; 4]>>,
+}
+
+impl<T> Node<T> {
+    fn new(bounds: Aabb) -> Self {
+        Node {
+            bounds,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+}
+
+/// A region quadtree over points and axis-aligned boxes in 2D.
+///
+/// A node holds up to `bucket_size` items before it splits into four quadrants
+/// and pushes down every item that fits entirely inside one of them. Items that
+/// straddle a split line stay at the node, so boxes of any size can be stored.
+/// Nodes stop splitting at `max_depth`. Items outside the root bounds are kept
+/// at the root, so they are still found, just without any speedup.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::quadtree::{Aabb, Quadtree};
+///
+/// let mut tree = Quadtree::new(Aabb::new([0.0, 0.0], [100.0, 100.0]));
+/// tree.insert(Aabb::point([10.0, 10.0]), "tree");
+/// tree.insert(Aabb::new([40.0, 40.0], [60.0, 50.0]), "house");
+///
+/// let found = tree.query(&Aabb::new([50.0, 45.0], [90.0, 90.0]));
+/// assert_eq!(found.len(), 1);
+/// assert_eq!(*found[0].1, "house");
+///
+/// assert_eq!(tree.remove(&Aabb::point([10.0, 10.0])), Some("tree"));
+/// assert_eq!(tree.len(), 1);
+/// ```
+pub struct Quadtree<T> {
+    root: Node<T>,
+    max_depth: usize,
+    bucket_size: usize,
+    len: usize,
+}
+
+impl<T> Quadtree<T> {
+    /// Create an empty quadtree covering `bounds` with the default maximum depth
+    /// and bucket size.
+    pub fn new(bounds: Aabb) -> Self {
+        Self::with_config(bounds, DEFAULT_MAX_DEPTH, DEFAULT_BUCKET_SIZE)
+    }
+
+    /// Create an empty quadtree covering `bounds` whose nodes split once they hold
+    /// more than `bucket_size` items, down to `max_depth` levels below the root.
+    /// Panics if `bucket_size` is 0.
+    pub fn with_config(bounds: Aabb, max_depth: usize, bucket_size: usize) -> Self {
+        assert!(bucket_size > 0, "bucket size should be > 0");
+        Quadtree {
+            root: Node::new(bounds),
+            max_depth,
+            bucket_size,
+            len: 0,
+        }
+    }
+
+    /// The bounds of the root node.
+    pub fn bounds(&self) -> Aabb {
+        self.root.bounds
+    }
+
+    /// Number of items in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove every item.
+    pub fn clear(&mut self) {
+        self.root = Node::new(self.root.bounds);
+        self.len = 0;
+    }
+
+    /// Insert an item covering `bounds`.
+    pub fn insert(&mut self, bounds: Aabb, value: T) {
+        let mut node = &mut self.root;
+        let mut depth = 0;
+        loop {
+            let child = node
+                .children
+                .as_ref()
+                .and_then(|children| children.iter().position(|c| c.bounds.contains(&bounds)));
+            match child {
+                Some(i) => {
+                    node = &mut node.children.as_mut().unwrap()[i];
+                    depth += 1;
+                }
+                None => break,
+            }
+        }
+        node.items.push((bounds, value));
+        if node.children.is_none() && node.items.len() > self.bucket_size && depth < self.max_depth
+        {
+            Self::split(node);
+        }
+        self.len += 1;
+    }
+
+    fn split(node: &mut Node<T>) {
+        let mut children = Box::new(quadrants(&node.bounds).map(Node::new));
+        let items = core::mem::take(&mut node.items);
+        for (bounds, value) in items {
+            match children.iter_mut().find(|c| c.bounds.contains(&bounds)) {
+                Some(child) => child.items.push((bounds, value)),
+                None => node.items.push((bounds, value)),
+            }
+        }
+        node.children = Some(children);
+    }
+
+    /// Remove one item whose bounds are exactly `bounds` and return its value.
+    pub fn remove(&mut self, bounds: &Aabb) -> Option<T> {
+        let mut node = &mut self.root;
+        loop {
+            if let Some(pos) = node.items.iter().position(|(b, _)| b == bounds) {
+                self.len -= 1;
+                return Some(node.items.swap_remove(pos).1);
+            }
+            node = node
+                .children
+                .as_mut()?
+                .iter_mut()
+                .find(|c| c.bounds.contains(bounds))?;
+        }
+    }
+
+    /// Every item intersecting `region`.
+    pub fn query(&self, region: &Aabb) -> Vec<(&Aabb, &T)> {
+        let mut res = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            res.extend(
+                node.items
+                    .iter()
+                    .filter(|(b, _)| b.intersects(region))
+                    .map(|(b, v)| (b, v)),
+            );
+            if let Some(children) = &node.children {
+                stack.extend(children.iter().filter(|c| c.bounds.intersects(region)));
+            }
+        }
+        res
+    }
+
+    /// The item closest to the point `query`.
+    pub fn nearest(&self, query: &[f64; 2]) -> Option<(&Aabb, &T)> {
+        let mut best: Option<(f64, &Aabb, &T)> = None;
+        Self::nearest_node(&self.root, query, &mut best);
+        best.map(|(_, b, v)| (b, v))
+    }
+
+    fn nearest_node<'a>(
+        node: &'a Node<T>,
+        query: &[f64; 2],
+        best: &mut Option<(f64, &'a Aabb, &'a T)>,
+    ) {
+        for (bounds, value) in &node.items {
+            let d = bounds.distance2(query);
+            if best.is_none_or(|(best_d, _, _)| d < best_d) {
+                *best = Some((d, bounds, value));
+            }
+        }
+        let Some(children) = &node.children else {
+            return;
+        };
+        // Visit the closest quadrants first to prune the others early.
+        let mut order: Vec<(f64, &Node<T>)> = children
+            .iter()
+            .map(|c| (c.bounds.distance2(query), c))
+            .collect();
+        order.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (d, child) in order {
+            if best.is_none_or(|(best_d, _, _)| d < best_d) {
+                Self::nearest_node(child, query, best);
+            }
+        }
+    }
+
+    /// Iterate over every item, in no particular order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stack: vec![&self.root],
+            items: [].iter(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Quadtree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the items of a [`Quadtree`], in no particular order.
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+    // Items left in the node last popped.
+    items: slice::Iter<'a, (Aabb, T)>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (&'a Aabb, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((b, v)) = self.items.next() {
+                return Some((b, v));
+            }
+            let node = self.stack.pop()?;
+            self.items = node.items.iter();
+            self.stack
+                .extend(node.children.iter().flat_map(|c| c.iter()));
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Quadtree<T> {
+    type Item = (&'a Aabb, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> SpatialIndex<T, 2> for Quadtree<T> {
+    fn insert_point(&mut self, point: [f64; 2], value: T) {
+        self.insert(Aabb::point(point), value);
+    }
+
+    fn nearest_to(&self, query: &[f64; 2]) -> Option<&T> {
+        self.nearest(query).map(|(_, v)| v)
+    }
+
+    fn in_box(&self, min: &[f64; 2], max: &[f64; 2]) -> Vec<&T> {
+        let region = Aabb::new(*min, *max);
+        // Boxes only partly inside the region are left out, like points outside it.
+        self.query(&region)
+            .into_iter()
+            .filter(|(b, _)| region.contains(b))
+            .map(|(_, v)| v)
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// A tree serializes as its configuration and a sequence of `(bounds, value)`
+/// pairs.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Quadtree<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Quadtree", 4)?;
+        state.serialize_field("bounds", &self.root.bounds)?;
+        state.serialize_field("max_depth", &self.max_depth)?;
+        state.serialize_field("bucket_size", &self.bucket_size)?;
+        state.serialize_field("items", &self.iter().collect::<Vec<_>>())?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Quadtree<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "Quadtree")]
+        struct Repr<T> {
+            bounds: Aabb,
+            max_depth: usize,
+            bucket_size: usize,
+            items: Vec<(Aabb, T)>,
+        }
+
+        let repr = Repr::deserialize(deserializer)?;
+        if repr.bucket_size == 0 {
+            return Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(0),
+                &"a bucket size > 0",
+            ));
+        }
+        let mut tree = Quadtree::with_config(repr.bounds, repr.max_depth, repr.bucket_size);
+        for (bounds, value) in repr.items {
+            tree.insert(bounds, value);
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Quadtree<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let bounds = u.arbitrary()?;
+        let max_depth = u.int_in_range(0..=8)?;
+        let bucket_size = u.int_in_range(1..=8)?;
+        let mut tree = Quadtree::with_config(bounds, max_depth, bucket_size);
+        for entry in u.arbitrary_iter::<(Aabb, T)>()? {
+            let (bounds, value) = entry?;
+            tree.insert(bounds, value);
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary + 'static> proptest::arbitrary::Arbitrary for Quadtree<T> {
+    type Parameters = (proptest::collection::SizeRange, T::Parameters);
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((size, args): Self::Parameters) -> Self::Strategy {
+        use proptest::{arbitrary::any_with, strategy::Strategy};
+
+        let items = proptest::collection::vec(any_with::<(Aabb, T)>(((), args)), size);
+        (any_with::<Aabb>(()), 0..=8usize, 1..=8usize, items)
+            .prop_map(|(bounds, max_depth, bucket_size, items)| {
+                let mut tree = Quadtree::with_config(bounds, max_depth, bucket_size);
+                for (bounds, value) in items {
+                    tree.insert(bounds, value);
+                }
+                tree
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(n: usize) -> Vec<[f64; 2]> {
+        let mut x = 0x9e37_79b9_u64;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            (x % 10_000) as f64 / 100.0
+        };
+        (0..n).map(|_| [next(), next()]).collect()
+    }
+
+    fn tree(pts: &[[f64; 2]]) -> Quadtree<usize> {
+        let mut tree = Quadtree::with_config(Aabb::new([0.0, 0.0], [100.0, 100.0]), 6, 4);
+        for (i, p) in pts.iter().enumerate() {
+            tree.insert(Aabb::point(*p), i);
+        }
+        tree
+    }
+
+    #[test]
+    fn query_matches_brute_force() {
+        let pts = points(1000);
+        let tree = tree(&pts);
+        assert_eq!(tree.len(), 1000);
+        assert!(tree.root.children.is_some());
+        let region = Aabb::new([20.0, 30.0], [45.0, 80.0]);
+        let mut found: Vec<_> = tree.query(&region).into_iter().map(|(_, v)| *v).collect();
+        found.sort();
+        let expected: Vec<_> = (0..pts.len())
+            .filter(|&i| region.contains(&Aabb::point(pts[i])))
+            .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let pts = points(500);
+        let tree = tree(&pts);
+        for q in points(30).iter().map(|p| [p[1], p[0]]) {
+            let d = |p: &[f64; 2]| Aabb::point(*p).distance2(&q);
+            let expected = pts.iter().map(d).min_by(f64::total_cmp).unwrap();
+            let (found, _) = tree.nearest(&q).unwrap();
+            assert_eq!(found.distance2(&q), expected);
+        }
+    }
+
+    #[test]
+    fn boxes() {
+        let mut tree = Quadtree::with_config(Aabb::new([0.0, 0.0], [8.0, 8.0]), 4, 1);
+        tree.insert(Aabb::new([1.0, 1.0], [2.0, 2.0]), "small");
+        tree.insert(Aabb::new([3.0, 3.0], [5.0, 5.0]), "center");
+        tree.insert(Aabb::new([6.0, 6.0], [7.0, 7.0]), "corner");
+        tree.insert(Aabb::new([-5.0, -5.0], [-4.0, -4.0]), "outside");
+        assert_eq!(tree.len(), 4);
+        let found = tree.query(&Aabb::point([4.0, 4.0]));
+        assert_eq!(found.len(), 1);
+        assert_eq!(*found[0].1, "center");
+        assert_eq!(tree.nearest(&[-3.0, -3.0]).unwrap().1, &"outside");
+        assert_eq!(
+            tree.remove(&Aabb::new([3.0, 3.0], [5.0, 5.0])),
+            Some("center")
+        );
+        assert_eq!(tree.remove(&Aabb::new([3.0, 3.0], [5.0, 5.0])), None);
+        assert_eq!(tree.iter().count(), 3);
+    }
+
+    #[test]
+    fn max_depth() {
+        // Identical points can't be separated, the depth limit stops the splitting.
+        let mut tree = Quadtree::with_config(Aabb::new([0.0, 0.0], [1.0, 1.0]), 3, 2);
+        for i in 0..100 {
+            tree.insert(Aabb::point([0.5, 0.5]), i);
+        }
+        assert_eq!(tree.query(&Aabb::point([0.5, 0.5])).len(), 100);
+        let mut depth = 0;
+        let mut node = &tree.root;
+        while let Some(children) = &node.children {
+            node = children.iter().max_by_key(|c| c.items.len()).unwrap();
+            depth += 1;
+        }
+        assert!(depth <= 3);
+    }
+
+    #[test]
+    fn spatial_index() {
+        let pts = points(200);
+        let mut tree = Quadtree::new(Aabb::new([0.0, 0.0], [100.0, 100.0]));
+        let mut kd = crate::kd_tree::KdTree::new();
+        for (i, p) in pts.iter().enumerate() {
+            tree.insert_point(*p, i);
+            kd.insert_point(*p, i);
+        }
+        let (min, max) = ([10.0, 10.0], [50.0, 40.0]);
+        let mut a: Vec<_> = tree.in_box(&min, &max).into_iter().copied().collect();
+        let mut b: Vec<_> = kd.in_box(&min, &max).into_iter().copied().collect();
+        a.sort();
+        b.sort();
+        assert_eq!(a, b);
+        let q = [33.0, 66.0];
+        assert_eq!(
+            pts[*tree.nearest_to(&q).unwrap()],
+            pts[*kd.nearest_to(&q).unwrap()]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut tree = Quadtree::with_config(Aabb::new([0.0, 0.0], [100.0, 100.0]), 4, 2);
+        for i in 0..10 {
+            tree.insert(Aabb::point([i as f64 * 10.0, 5.0]), i);
+        }
+        let json = serde_json::to_string(&tree).unwrap();
+        let back: Quadtree<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.len(), 10);
+        assert_eq!(back.bounds(), tree.bounds());
+        let mut found: Vec<_> = back
+            .query(&Aabb::new([15.0, 0.0], [35.0, 10.0]))
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect();
+        found.sort();
+        assert_eq!(found, [2, 3]);
+    }
+}
@@ -0,0 +1,415 @@
+use alloc::collections::{btree_map, BTreeMap};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+/// A map from non-overlapping half-open ranges of keys to values.
+///
+/// Inserting a range overwrites whatever the range covered before, splitting the
+/// ranges that stick out of it. Ranges that touch and hold equal values are
+/// merged, so the map always stores the fewest ranges describing its contents.
+/// This makes it a good fit for bookkeeping over large key spaces, such as the
+/// regions of an address space or blocks of IP addresses.
+///
+/// Ranges are kept in a [`BTreeMap`] keyed by their start, so lookups and
+/// inserts take O(log n) plus the number of ranges overwritten.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::range_map::RangeMap;
+///
+/// let mut map = RangeMap::new();
+/// map.insert(0..10, "free");
+/// map.insert(4..6, "used");
+/// assert_eq!(map.get(&5), Some(&"used"));
+/// assert_eq!(map.len(), 3);
+///
+/// // Freeing the middle merges everything back into a single range.
+/// map.insert(4..6, "free");
+/// assert_eq!(map.len(), 1);
+/// assert_eq!(map.get_key_value(&5), Some((&(0..10), &"free")));
+///
+/// map.remove(2..3);
+/// assert_eq!(map.gaps(&(0..12)), vec![2..3, 10..12]);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct RangeMap<K, V> {
+    // Keyed by range start. The ranges are non-empty, sorted and disjoint.
+    ranges: BTreeMap<K, (Range<K>, V)>,
+}
+
+impl<K, V> Default for RangeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> RangeMap<K, V> {
+    /// Create a new, empty map.
+    pub const fn new() -> Self {
+        RangeMap {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    /// Number of ranges in the map, after merging.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Return `true` if the map covers no key.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Remove every range.
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Iterate over the ranges and their values, ordered by start.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.ranges.values(),
+        }
+    }
+}
+
+impl<K, V> RangeMap<K, V>
+where
+    K: Ord + Clone,
+{
+    /// The value of the range containing `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    /// The range containing `key` and its value.
+    pub fn get_key_value(&self, key: &K) -> Option<(&Range<K>, &V)> {
+        let (_, (range, value)) = self.ranges.range(..=key).next_back()?;
+        range.contains(key).then_some((range, value))
+    }
+
+    /// Return `true` if a range contains `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get_key_value(key).is_some()
+    }
+
+    /// Every range overlapping `range`, ordered by start.
+    /// The ranges are returned whole, even if they stick out of `range`.
+    /// An empty `range` overlaps nothing.
+    pub fn overlapping(&self, range: &Range<K>) -> Vec<(&Range<K>, &V)> {
+        if range.is_empty() {
+            return Vec::new();
+        }
+        // The range starting before `range` may still reach into it.
+        let before = self
+            .ranges
+            .range(..&range.start)
+            .next_back()
+            .filter(|(_, (r, _))| r.end > range.start);
+        before
+            .into_iter()
+            .chain(self.ranges.range(&range.start..&range.end))
+            .map(|(_, (r, v))| (r, v))
+            .collect()
+    }
+
+    /// The parts of `outer` that no range covers, in order.
+    pub fn gaps(&self, outer: &Range<K>) -> Vec<Range<K>> {
+        let mut res = Vec::new();
+        let mut cursor = outer.start.clone();
+        for (range, _) in self.overlapping(outer) {
+            if cursor < range.start {
+                res.push(cursor.clone()..range.start.clone());
+            }
+            if range.end > cursor {
+                cursor = range.end.clone();
+            }
+        }
+        if cursor < outer.end {
+            res.push(cursor..outer.end.clone());
+        }
+        res
+    }
+
+    /// Remove every key in `range` from the map.
+    /// Ranges partly inside `range` are cut down to the part outside it.
+    pub fn remove(&mut self, range: Range<K>)
+    where
+        V: Clone,
+    {
+        if range.start >= range.end {
+            return;
+        }
+        // A range starting before `range` is cut at its start, and keeps its tail
+        // if it also ends after `range`.
+        let before = self.ranges.range_mut(..&range.start).next_back();
+        if let Some((_, (r, value))) = before {
+            if r.end > range.start {
+                let tail =
+                    (r.end > range.end).then(|| (range.end.clone()..r.end.clone(), value.clone()));
+                r.end = range.start.clone();
+                if let Some(tail) = tail {
+                    self.ranges.insert(tail.0.start.clone(), tail);
+                    return;
+                }
+            }
+        }
+        // Ranges starting inside `range` are dropped, except for the tail of the last.
+        let inside: Vec<K> = self
+            .ranges
+            .range(&range.start..&range.end)
+            .map(|(start, _)| start.clone())
+            .collect();
+        for start in inside {
+            let (r, value) = self.ranges.remove(&start).unwrap();
+            if r.end > range.end {
+                self.ranges
+                    .insert(range.end.clone(), (range.end.clone()..r.end, value));
+            }
+        }
+    }
+
+    /// Map every key in `range` to `value`, replacing what was there before.
+    /// Empty ranges are ignored.
+    pub fn insert(&mut self, range: Range<K>, value: V)
+    where
+        V: Clone + Eq,
+    {
+        if range.start >= range.end {
+            return;
+        }
+        self.remove(range.clone());
+        let Range { mut start, mut end } = range;
+        // Merge with the range ending where this one starts.
+        let prev = self
+            .ranges
+            .range(..&start)
+            .next_back()
+            .filter(|(_, (r, v))| r.end == start && *v == value)
+            .map(|(s, _)| s.clone());
+        if let Some(prev) = prev {
+            self.ranges.remove(&prev);
+            start = prev;
+        }
+        // And with the range starting where this one ends.
+        if let btree_map::Entry::Occupied(next) = self.ranges.entry(end.clone()) {
+            if next.get().1 == value {
+                end = next.remove().0.end;
+            }
+        }
+        self.ranges.insert(start.clone(), (start..end, value));
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for RangeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + Eq> Extend<(Range<K>, V)> for RangeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + Eq> FromIterator<(Range<K>, V)> for RangeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut map = RangeMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+/// Iterator over the ranges of a [`RangeMap`], ordered by start.
+pub struct Iter<'a, K, V> {
+    inner: btree_map::Values<'a, K, (Range<K>, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(r, v)| (r, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(r, v)| (r, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a RangeMap<K, V> {
+    type Item = (&'a Range<K>, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A map serializes as a sequence of `(range, value)` pairs in ascending order,
+/// since its keys are ranges.
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for RangeMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for RangeMap<K, V>
+where
+    K: serde::Deserialize<'de> + Ord + Clone,
+    V: serde::Deserialize<'de> + Clone + Eq,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for RangeMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord + Clone,
+    V: arbitrary::Arbitrary<'a> + Clone + Eq,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(Range<K>, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(Range<K>, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for RangeMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Ord + Clone,
+    V: proptest::arbitrary::Arbitrary + 'static + Clone + Eq,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(Range<K>, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &(Range<K>, V), b: &(Range<K>, V)| {
+            a.0.start.cmp(&b.0.start)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    const SIZE: usize = 64;
+
+    // Check that the ranges are non-empty, disjoint and fully merged.
+    fn check<V: Eq>(map: &RangeMap<usize, V>) {
+        for ((start, (r, _)), (_, (next, next_value))) in
+            map.ranges.iter().zip(map.ranges.iter().skip(1))
+        {
+            assert_eq!(*start, r.start);
+            assert!(r.start < r.end);
+            assert!(r.end <= next.start);
+            let value = &map.ranges[start].1;
+            assert!(
+                r.end < next.start || value != next_value,
+                "{r:?} and {next:?} should be merged"
+            );
+        }
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut x = 0x9e37_79b9_u64;
+        let mut next = move |m: usize| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x as usize % m
+        };
+        let mut map = RangeMap::new();
+        let mut model = [None; SIZE];
+        for _ in 0..2000 {
+            let (a, b) = (next(SIZE + 1), next(SIZE + 1));
+            let range = a.min(b)..a.max(b);
+            if next(4) == 0 {
+                map.remove(range.clone());
+                model[range].fill(None);
+            } else {
+                let value = next(3);
+                map.insert(range.clone(), value);
+                model[range].fill(Some(value));
+            }
+            check(&map);
+            for (key, expected) in model.iter().enumerate() {
+                assert_eq!(map.get(&key), expected.as_ref());
+            }
+        }
+        let gaps = map.gaps(&(0..SIZE));
+        for (key, expected) in model.iter().enumerate() {
+            assert_eq!(gaps.iter().any(|g| g.contains(&key)), expected.is_none());
+        }
+    }
+
+    #[test]
+    fn split_and_merge() {
+        let mut map = RangeMap::new();
+        map.insert(0..10, 'a');
+        map.insert(3..5, 'b');
+        let ranges: Vec<_> = map.iter().map(|(r, v)| (r.clone(), *v)).collect();
+        assert_eq!(ranges, vec![(0..3, 'a'), (3..5, 'b'), (5..10, 'a')]);
+
+        map.insert(10..12, 'a');
+        map.insert(12..14, 'c');
+        assert_eq!(map.get_key_value(&11), Some((&(5..12), &'a')));
+        assert_eq!(map.overlapping(&(4..13)).len(), 3);
+        assert!(map.overlapping(&(11..11)).is_empty());
+
+        map.remove(0..20);
+        assert!(map.is_empty());
+        map.insert(5..5, 'a');
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn gaps() {
+        let map: RangeMap<u32, ()> = [(2..4, ()), (6..8, ())].into_iter().collect();
+        assert_eq!(map.gaps(&(0..10)), vec![0..2, 4..6, 8..10]);
+        assert_eq!(map.gaps(&(3..7)), vec![4..6]);
+        assert!(map.gaps(&(2..4)).is_empty());
+        assert_eq!(RangeMap::<u32, ()>::new().gaps(&(1..2)), vec![1..2]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: RangeMap<i32, char> = [(5..7, 'b'), (1..3, 'a')].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(
+            json,
+            r#"[[{"start":1,"end":3},"a"],[{"start":5,"end":7},"b"]]"#
+        );
+        let back: RangeMap<i32, char> = serde_json::from_str(&json).unwrap();
+        assert!(back.iter().eq(map.iter()));
+    }
+}
@@ -0,0 +1,329 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use crate::{
+    codec::{self, Decode, DecodeError, Encode},
+    range_map::{self, RangeMap},
+};
+
+/// A set of values stored as disjoint half-open ranges.
+///
+/// Inserting a range merges it with every range it overlaps or touches, and
+/// removing one cuts the ranges sticking out of it, so the set always holds the
+/// fewest ranges covering its values. It is a thin layer over a
+/// [`RangeMap`] whose values are all `()`.
+///
+/// # Examples
+///
+/// Tracking which bytes of a download have arrived:
+///
+/// ```
+/// use my_std::range_set::RangeSet;
+///
+/// let mut received = RangeSet::new();
+/// received.insert(0..100);
+/// received.insert(200..300);
+/// received.insert(90..210);
+/// assert_eq!(received.len(), 1);
+/// assert!(received.contains(&250));
+///
+/// received.remove(50..60);
+/// assert_eq!(received.gaps(&(0..400)), vec![50..60, 300..400]);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct RangeSet<T> {
+    map: RangeMap<T, ()>,
+}
+
+impl<T> Default for RangeSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> RangeSet<T> {
+    /// Create a new, empty set.
+    pub const fn new() -> Self {
+        RangeSet {
+            map: RangeMap::new(),
+        }
+    }
+
+    /// Number of disjoint ranges in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the set holds no value.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Remove every range.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Iterate over the ranges in ascending order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.map.iter(),
+        }
+    }
+}
+
+impl<T: Ord + Clone> RangeSet<T> {
+    /// Add every value of `range`, merging it with the ranges it overlaps or
+    /// touches. Empty ranges are ignored.
+    pub fn insert(&mut self, range: Range<T>) {
+        self.map.insert(range, ());
+    }
+
+    /// Remove every value of `range`.
+    pub fn remove(&mut self, range: Range<T>) {
+        self.map.remove(range);
+    }
+
+    /// Return `true` if `value` is in the set.
+    pub fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    /// The range holding `value`.
+    pub fn get(&self, value: &T) -> Option<&Range<T>> {
+        self.map.get_key_value(value).map(|(range, _)| range)
+    }
+
+    /// The parts of `outer` that are not in the set, in order.
+    pub fn gaps(&self, outer: &Range<T>) -> Vec<Range<T>> {
+        self.map.gaps(outer)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RangeSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord + Clone> Extend<Range<T>> for RangeSet<T> {
+    fn extend<I: IntoIterator<Item = Range<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Encode> RangeSet<T> {
+    /// Encode the set in the [`codec`] format: a version byte,
+    /// the number of ranges and then each range's start and end.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        codec::to_bytes(self)
+    }
+}
+
+impl<T: Decode + Ord + Clone> RangeSet<T> {
+    /// Decode a set written by [`RangeSet::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        codec::from_bytes(bytes)
+    }
+}
+
+impl<T: Encode> Encode for RangeSet<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for range in self {
+            range.encode(out);
+        }
+    }
+}
+
+impl<T: Decode + Ord + Clone> Decode for RangeSet<T> {
+    /// Rejects ranges that are empty, out of order or touching, which an
+    /// encoded set never has.
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let ranges = Vec::<Range<T>>::decode(input)?;
+        let nonempty = ranges.iter().all(|r| r.start < r.end);
+        if !nonempty || !ranges.windows(2).all(|w| w[0].end < w[1].start) {
+            return Err(DecodeError::Invalid("range set"));
+        }
+        Ok(ranges.into_iter().collect())
+    }
+}
+
+/// Iterator over the ranges of a [`RangeSet`] in ascending order.
+pub struct Iter<'a, T> {
+    inner: range_map::Iter<'a, T, ()>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a Range<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(range, _)| range)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(range, _)| range)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a RangeSet<T> {
+    type Item = &'a Range<T>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for RangeSet<T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for RangeSet<T>
+where
+    T: serde::Deserialize<'de> + Ord + Clone,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for RangeSet<T>
+where
+    T: arbitrary::Arbitrary<'a> + Ord + Clone,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<Range<T>>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<Range<T>>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for RangeSet<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static + Ord + Clone,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <Range<T> as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &Range<T>, b: &Range<T>| {
+            a.start.cmp(&b.start)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn coalescing() {
+        let mut set: RangeSet<u32> = [5..7, 1..3, 3..4, 10..12].into_iter().collect();
+        let ranges: Vec<_> = set.iter().cloned().collect();
+        assert_eq!(ranges, vec![1..4, 5..7, 10..12]);
+
+        set.insert(2..11);
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.get(&6), Some(&(1..12)));
+
+        set.remove(4..6);
+        set.remove(0..2);
+        let ranges: Vec<_> = set.iter().cloned().collect();
+        assert_eq!(ranges, vec![2..4, 6..12]);
+        assert!(!set.contains(&5));
+        assert!(set.contains(&11));
+        assert!(!set.contains(&12));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut set = RangeSet::new();
+        let mut model = [false; 64];
+        let mut x = 0x1234_5678_u64;
+        for _ in 0..1000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let (a, b) = ((x % 65) as usize, ((x >> 8) % 65) as usize);
+            let range = a.min(b)..a.max(b);
+            if x.is_multiple_of(3) {
+                set.remove(range.clone());
+                model[range].fill(false);
+            } else {
+                set.insert(range.clone());
+                model[range].fill(true);
+            }
+            for (value, expected) in model.iter().enumerate() {
+                assert_eq!(set.contains(&value), *expected);
+            }
+            // Ranges never touch, otherwise they would have been merged.
+            let ranges: Vec<_> = set.iter().collect();
+            assert!(ranges.windows(2).all(|w| w[0].end < w[1].start));
+        }
+    }
+
+    #[test]
+    fn bytes() {
+        let set: RangeSet<i64> = [-5..0, 10..20, 30..31].into_iter().collect();
+        let bytes = set.to_bytes();
+        assert_eq!(bytes, [codec::VERSION, 3, 9, 0, 20, 40, 60, 62]);
+        assert_eq!(RangeSet::from_bytes(&bytes), Ok(set));
+        assert_eq!(
+            RangeSet::<u8>::from_bytes(&[codec::VERSION, 0]),
+            Ok(RangeSet::new())
+        );
+        for ranges in [[3..3, 5..6], [5..6, 1..2], [1..2, 2..3]] {
+            let bytes = codec::to_bytes(&ranges);
+            assert_eq!(
+                RangeSet::<u8>::from_bytes(&bytes),
+                Err(DecodeError::Invalid("range set"))
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let values: RangeSet<i32> = [5..7, 1..3, 2..4].into_iter().collect();
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[{\"start\":1,\"end\":4},{\"start\":5,\"end\":7}]");
+        let back: RangeSet<i32> = serde_json::from_str(&json).unwrap();
+        assert!(back.iter().eq(values.iter()));
+    }
+}
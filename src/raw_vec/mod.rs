@@ -1,8 +1,7 @@
-use std::{
-    alloc::{handle_alloc_error, Allocator, Global, Layout, LayoutError},
-    cmp,
-    collections::{TryReserveError, TryReserveErrorKind},
-    hint,
+use alloc::alloc::{handle_alloc_error, Allocator, Global, Layout, LayoutError};
+use alloc::collections::{TryReserveError, TryReserveErrorKind};
+use core::{
+    cmp, hint,
     mem::{self, SizedTypeProperties},
     ptr::NonNull,
 };
@@ -55,6 +54,13 @@ impl<T> RawVec<T, Global> {
     pub fn with_capacity_zeroed(capacity: usize) -> Self {
         Self::with_capacity_zeroed_in(capacity, Global)
     }
+
+    /// Like `with_capacity`, but returns an error instead of panicking or
+    /// aborting when the memory can't be allocated.
+    #[inline]
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
 }
 
 impl<T> Default for RawVec<T, Global> {
@@ -102,37 +108,62 @@ impl<T, A: Allocator> RawVec<T, A> {
         Self::allocate_in(capacity, AllocInit::Zeroed, alloc)
     }
 
+    /// Like `try_with_capacity`, but parameterized over the choice of
+    /// allocator for the returned `RawVec`.
+    #[inline]
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+        Self::try_allocate_in(capacity, AllocInit::Uninitialized, alloc)
+    }
+
     fn allocate_in(capacity: usize, init: AllocInit, alloc: A) -> Self {
+        match Self::try_allocate_in(capacity, init, alloc) {
+            Ok(raw_vec) => raw_vec,
+            Err(err) => handle_error(err),
+        }
+    }
+
+    fn try_allocate_in(
+        capacity: usize,
+        init: AllocInit,
+        alloc: A,
+    ) -> Result<Self, TryReserveError> {
         // Don't allocate here because `Drop` will not deallocate when `capacity` is 0.
         if T::IS_ZST || capacity == 0 {
-            Self::new_in(alloc)
-        } else {
-            // We avoid `unwrap_or_else` here because it bloats the amount of
-            // LLVM IR generated.
-            let layout = match Layout::array::<T>(capacity) {
-                Ok(layout) => layout,
-                Err(_) => capacity_overflow(),
-            };
-            match alloc_guard(layout.size()) {
-                Ok(_) => {}
-                Err(_) => capacity_overflow(),
-            }
-            let result = match init {
-                AllocInit::Uninitialized => alloc.allocate(layout),
-                AllocInit::Zeroed => alloc.allocate_zeroed(layout),
-            };
-            let ptr = match result {
-                Ok(ptr) => ptr,
-                Err(_) => handle_alloc_error(layout),
-            };
-            // Allocators currently return a `NonNull<[u8]>` whose length
-            // matches the size requested. If that ever changes, the capacity
-            // here should change to `ptr.len() / mem::size_of::<T>()`.
-            Self {
-                ptr: unsafe { NonNull::new_unchecked(ptr.cast().as_ptr()) },
-                cap: Cap(capacity),
-                alloc,
-            }
+            return Ok(Self::new_in(alloc));
+        }
+        let layout =
+            Layout::array::<T>(capacity).map_err(|_| TryReserveErrorKind::CapacityOverflow)?;
+        alloc_guard(layout.size())?;
+        let result = match init {
+            AllocInit::Uninitialized => alloc.allocate(layout),
+            AllocInit::Zeroed => alloc.allocate_zeroed(layout),
+        };
+        let ptr = result.map_err(|_| TryReserveErrorKind::AllocError {
+            layout,
+            non_exhaustive: (),
+        })?;
+        // Allocators currently return a `NonNull<[u8]>` whose length
+        // matches the size requested. If that ever changes, the capacity
+        // here should change to `ptr.len() / mem::size_of::<T>()`.
+        Ok(Self {
+            ptr: unsafe { NonNull::new_unchecked(ptr.cast().as_ptr()) },
+            cap: Cap(capacity),
+            alloc,
+        })
+    }
+
+    /// Reconstitutes a `RawVec` from a pointer, a capacity and an allocator.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been allocated by `alloc` with the layout of a `[T; capacity]`,
+    /// or be dangling with a capacity of 0. The capacity is ignored if `T` is a ZST.
+    pub unsafe fn from_raw_parts_in(ptr: *mut T, capacity: usize, alloc: A) -> Self {
+        let cap = if T::IS_ZST { Cap::ZERO } else { Cap(capacity) };
+        Self {
+            ptr: NonNull::new_unchecked(ptr),
+            cap,
+            alloc,
         }
     }
 
@@ -159,7 +190,7 @@ impl<T, A: Allocator> RawVec<T, A> {
         if T::IS_ZST || self.cap.0 == 0 {
             None
         } else {
-            const { assert!(mem::size_of::<T>() % mem::align_of::<T>() == 0) };
+            const { assert!(mem::size_of::<T>().is_multiple_of(mem::align_of::<T>())) };
             unsafe {
                 let align = mem::align_of::<T>();
                 let size = mem::size_of::<T>().unchecked_mul(self.cap.0);
@@ -191,6 +222,34 @@ impl<T, A: Allocator> RawVec<T, A> {
     pub fn reserve_for_push(&mut self, len: usize) {
         handle_reserve(self.grow_amortized(len, 1))
     }
+
+    /// Ensures that the buffer contains exactly enough space to hold `len + additional`
+    /// elements, without the headroom `reserve` leaves for later pushes.
+    pub fn reserve_exact(&mut self, len: usize, additional: usize) {
+        handle_reserve(self.try_reserve_exact(len, additional))
+    }
+
+    /// The same as `reserve`, but returns on errors instead of panicking or aborting.
+    pub fn try_reserve(&mut self, len: usize, additional: usize) -> Result<(), TryReserveError> {
+        if self.needs_to_grow(len, additional) {
+            self.grow_amortized(len, additional)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The same as `reserve_exact`, but returns on errors instead of panicking or aborting.
+    pub fn try_reserve_exact(
+        &mut self,
+        len: usize,
+        additional: usize,
+    ) -> Result<(), TryReserveError> {
+        if self.needs_to_grow(len, additional) {
+            self.grow_exact(len, additional)
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl<T, A: Allocator> RawVec<T, A> {
@@ -229,6 +288,24 @@ impl<T, A: Allocator> RawVec<T, A> {
 
         Ok(())
     }
+
+    fn grow_exact(&mut self, len: usize, additional: usize) -> Result<(), TryReserveError> {
+        if T::IS_ZST {
+            // See `grow_amortized`.
+            return Err(TryReserveErrorKind::CapacityOverflow.into());
+        }
+
+        let cap = len
+            .checked_add(additional)
+            .ok_or(TryReserveErrorKind::CapacityOverflow)?;
+        let new_layout = Layout::array::<T>(cap);
+
+        let ptr = finish_grow(new_layout, self.current_memory(), &mut self.alloc)?;
+        // SAFETY: as in `grow_amortized`.
+        unsafe { self.set_ptr_and_cap(ptr, cap) };
+
+        Ok(())
+    }
 }
 
 unsafe impl<#[may_dangle] T, A: Allocator> Drop for RawVec<T, A> {
@@ -287,11 +364,17 @@ fn alloc_guard(alloc_size: usize) -> Result<(), TryReserveError> {
     }
 }
 
-fn handle_reserve(result: Result<(), TryReserveError>) {
-    match result.map_err(|e| e.kind()) {
-        Err(TryReserveErrorKind::CapacityOverflow) => capacity_overflow(),
-        Err(TryReserveErrorKind::AllocError { layout, .. }) => handle_alloc_error(layout),
-        Ok(()) => { /* yay */ }
+pub(crate) fn handle_reserve(result: Result<(), TryReserveError>) {
+    if let Err(err) = result {
+        handle_error(err)
+    }
+}
+
+// Panic or abort the way the infallible APIs do for an error from a fallible one.
+pub(crate) fn handle_error(err: TryReserveError) -> ! {
+    match err.kind() {
+        TryReserveErrorKind::CapacityOverflow => capacity_overflow(),
+        TryReserveErrorKind::AllocError { layout, .. } => handle_alloc_error(layout),
     }
 }
 
@@ -311,4 +394,23 @@ mod tests {
         let raw_vec: RawVec<i64> = RawVec::with_capacity(3);
         assert_eq!(raw_vec.capacity(), 3);
     }
+
+    #[test]
+    fn test_try_reserve() {
+        let mut raw_vec: RawVec<i64> = RawVec::try_with_capacity(3).unwrap();
+        raw_vec.try_reserve_exact(3, 2).unwrap();
+        assert_eq!(raw_vec.capacity(), 5);
+        raw_vec.try_reserve(5, 1).unwrap();
+        assert_eq!(raw_vec.capacity(), 10);
+        let overflow = raw_vec.try_reserve(10, usize::MAX).unwrap_err();
+        assert_eq!(overflow.kind(), TryReserveErrorKind::CapacityOverflow);
+        assert!(RawVec::<i64>::try_with_capacity(usize::MAX / 4).is_err());
+        assert!(matches!(
+            raw_vec
+                .try_reserve_exact(0, isize::MAX as usize / 8)
+                .map_err(|e| e.kind()),
+            Err(TryReserveErrorKind::AllocError { .. })
+        ));
+        assert_eq!(raw_vec.capacity(), 10);
+    }
 }
@@ -0,0 +1,800 @@
+use ::alloc::alloc::{self, Layout};
+#[cfg(feature = "leak_check")]
+use ::alloc::{string::ToString, vec::Vec};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::{PhantomData, Unsize},
+    mem::{self, ManuallyDrop, MaybeUninit},
+    num::NonZeroUsize,
+    ops::{CoerceUnsized, Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+
+use crate::boxed::MyBox;
+use crate::cell::MyCell;
+use crate::compat;
+
+// The shared allocation: the counts live next to the value. All strong
+// pointers together hold one weak reference, so the allocation is freed when
+// the weak count drops to zero, which happens after the value has been dropped.
+//
+// `repr(C)` gives `RcInner<MaybeUninit<T>>` the layout of `RcInner<T>`, which
+// `MyRc::new_cyclic` relies on.
+#[repr(C)]
+struct RcInner<T: ?Sized> {
+    strong: MyCell<usize>,
+    weak: MyCell<usize>,
+    value: T,
+}
+
+impl<T: ?Sized> RcInner<T> {
+    fn inc(count: &MyCell<usize>) {
+        // Leaking clones in a loop could overflow the count and free the value
+        // while it is still in use.
+        let n = count
+            .get()
+            .checked_add(1)
+            .unwrap_or_else(|| compat::abort());
+        count.set(n);
+    }
+}
+
+/// A single-threaded reference-counted pointer.
+///
+/// Cloning a `MyRc` bumps a counter instead of copying the value, and the value
+/// is dropped along with the last clone. The counts are plain [`MyCell`]s, so
+/// `MyRc` is neither `Send` nor `Sync`. The shared allocation is made by
+/// [`MyBox`], and like it, `MyRc` can be unsized to `MyRc<[T]>` or
+/// `MyRc<dyn Trait>`.
+///
+/// A [`Weak`] pointer, made with [`MyRc::downgrade`], keeps the allocation but
+/// not the value alive, which breaks reference cycles: a tree whose children
+/// point back to their parent through `Weak` is freed like any other.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::rc::MyRc;
+///
+/// let a = MyRc::new(vec![1, 2, 3]);
+/// let mut b = a.clone();
+/// assert_eq!(MyRc::strong_count(&a), 2);
+///
+/// // `b` is shared, so writing to it clones the vector first.
+/// MyRc::make_mut(&mut b).push(4);
+/// assert_eq!(*a, vec![1, 2, 3]);
+/// assert_eq!(*b, vec![1, 2, 3, 4]);
+/// assert_eq!(MyRc::try_unwrap(a), Ok(vec![1, 2, 3]));
+/// ```
+pub struct MyRc<T: ?Sized> {
+    ptr: NonNull<RcInner<T>>,
+    _marker: PhantomData<RcInner<T>>,
+}
+
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<MyRc<U>> for MyRc<T> {}
+
+/// Live allocations of this thread, with where they were made, for
+/// [`assert_no_leaks`].
+#[cfg(feature = "leak_check")]
+mod registry {
+    use std::{cell::RefCell, collections::HashMap, panic::Location};
+
+    thread_local! {
+        static LIVE: RefCell<HashMap<usize, &'static Location<'static>>> =
+            RefCell::new(HashMap::new());
+    }
+
+    pub(super) fn track(addr: usize, location: &'static Location<'static>) {
+        // The registry is gone while the thread shuts down, so the values
+        // dropped then are not tracked.
+        let _ = LIVE.try_with(|live| live.borrow_mut().insert(addr, location));
+    }
+
+    pub(super) fn untrack(addr: usize) {
+        let _ = LIVE.try_with(|live| live.borrow_mut().remove(&addr));
+    }
+
+    pub(super) fn live() -> Vec<&'static Location<'static>> {
+        LIVE.with(|live| live.borrow().values().copied().collect())
+    }
+}
+
+// Record that the value at `ptr` was made by our caller.
+#[track_caller]
+fn track<T: ?Sized>(ptr: NonNull<RcInner<T>>) {
+    #[cfg(feature = "leak_check")]
+    registry::track(ptr.addr().get(), std::panic::Location::caller());
+    #[cfg(not(feature = "leak_check"))]
+    let _ = ptr;
+}
+
+// Record that the value at `ptr` is gone.
+fn untrack<T: ?Sized>(ptr: NonNull<RcInner<T>>) {
+    #[cfg(feature = "leak_check")]
+    registry::untrack(ptr.addr().get());
+    #[cfg(not(feature = "leak_check"))]
+    let _ = ptr;
+}
+
+/// Number of `MyRc` and [`UniqueRc`] values made by this thread that are still
+/// alive.
+#[cfg(feature = "leak_check")]
+pub fn live_count() -> usize {
+    registry::live().len()
+}
+
+/// Panic if a `MyRc` or [`UniqueRc`] value made by this thread is still alive,
+/// listing where each was made.
+///
+/// Called at the end of a test, once everything the test built is out of
+/// scope, it catches the values kept alive by reference cycles. Only the
+/// current thread is checked, so tests running in parallel don't interfere.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "leak_check")] {
+/// use my_std::cell::MyRefCell;
+/// use my_std::rc::{self, MyRc};
+///
+/// struct Node {
+///     next: MyRefCell<Option<MyRc<Node>>>,
+/// }
+///
+/// let a = MyRc::new(Node { next: MyRefCell::new(None) });
+/// let b = MyRc::new(Node { next: MyRefCell::new(Some(a.clone())) });
+/// *a.next.borrow_mut() = Some(b.clone()); // A cycle.
+/// drop((a, b));
+/// assert_eq!(rc::live_count(), 2);
+/// assert!(std::panic::catch_unwind(rc::assert_no_leaks).is_err());
+/// # }
+/// ```
+#[cfg(feature = "leak_check")]
+#[track_caller]
+pub fn assert_no_leaks() {
+    let live = registry::live();
+    if !live.is_empty() {
+        let sites: Vec<_> = live.iter().map(|l| l.to_string()).collect();
+        panic!(
+            "{} MyRc value(s) leaked, made at: {}",
+            live.len(),
+            sites.join(", ")
+        );
+    }
+}
+
+impl<T> MyRc<T> {
+    /// Move `value` into a new shared allocation.
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        let inner = MyBox::new(RcInner {
+            strong: MyCell::new(1),
+            weak: MyCell::new(1),
+            value,
+        });
+        let this = unsafe { Self::from_inner(MyBox::into_raw(inner)) };
+        track(this.ptr);
+        this
+    }
+
+    /// Build a value that holds a [`Weak`] pointer to itself.
+    ///
+    /// `f` receives the weak pointer before the value exists, so upgrading it
+    /// inside `f` returns `None`; it works as soon as `new_cyclic` returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::rc::{MyRc, Weak};
+    ///
+    /// struct Node {
+    ///     me: Weak<Node>,
+    /// }
+    ///
+    /// let node = MyRc::new_cyclic(|me| Node { me: me.clone() });
+    /// assert!(MyRc::ptr_eq(&node.me.upgrade().unwrap(), &node));
+    /// ```
+    #[track_caller]
+    pub fn new_cyclic<F>(f: F) -> Self
+    where
+        F: FnOnce(&Weak<T>) -> T,
+    {
+        // No strong pointer yet, and the weak reference of the strong pointers
+        // is the one handed to `f`.
+        let uninit = MyBox::new(RcInner {
+            strong: MyCell::new(0),
+            weak: MyCell::new(1),
+            value: MaybeUninit::<T>::uninit(),
+        });
+        let ptr = MyBox::into_raw(uninit).cast::<RcInner<T>>();
+        let weak = Weak {
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+        };
+        // If `f` panics, dropping `weak` frees the allocation.
+        let value = f(&weak);
+        unsafe {
+            ptr::write(&mut (*ptr).value, value);
+            (*ptr).strong.set(1);
+        }
+        mem::forget(weak);
+        let this = unsafe { Self::from_inner(ptr) };
+        track(this.ptr);
+        this
+    }
+
+    /// Return the value if `this` is its only pointer, or `this` itself
+    /// otherwise.
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if Self::strong_count(&this) != 1 {
+            return Err(this);
+        }
+        let this = ManuallyDrop::new(this);
+        this.inner().strong.set(0);
+        untrack(this.ptr);
+        unsafe {
+            let value = ptr::read(&this.inner().value);
+            // Give up the weak reference held by the strong pointers.
+            drop(Weak { ptr: this.ptr });
+            Ok(value)
+        }
+    }
+
+    /// Return the value if `this` is its only pointer, or a clone of it
+    /// otherwise.
+    pub fn unwrap_or_clone(this: Self) -> T
+    where
+        T: Clone,
+    {
+        Self::try_unwrap(this).unwrap_or_else(|this| (*this).clone())
+    }
+}
+
+impl<T: ?Sized> MyRc<T> {
+    unsafe fn from_inner(ptr: *mut RcInner<T>) -> Self {
+        MyRc {
+            ptr: NonNull::new_unchecked(ptr),
+            _marker: PhantomData,
+        }
+    }
+
+    fn inner(&self) -> &RcInner<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// Number of pointers to the value.
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().strong.get()
+    }
+
+    /// Number of [`Weak`] pointers to the value.
+    pub fn weak_count(this: &Self) -> usize {
+        this.inner().weak.get() - 1
+    }
+
+    /// Return `true` if both pointers point to the same allocation.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        ptr::addr_eq(this.ptr.as_ptr(), other.ptr.as_ptr())
+    }
+
+    /// Make a [`Weak`] pointer to the value.
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        RcInner::<T>::inc(&this.inner().weak);
+        Weak { ptr: this.ptr }
+    }
+
+    // Return `true` if no other strong or weak pointer exists.
+    fn is_unique(&self) -> bool {
+        Self::strong_count(self) == 1 && Self::weak_count(self) == 0
+    }
+
+    /// A mutable reference to the value if `this` is its only pointer, weak
+    /// pointers included.
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if this.is_unique() {
+            Some(unsafe { &mut this.ptr.as_mut().value })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Clone> MyRc<T> {
+    /// A mutable reference to the value, cloning it into a new allocation
+    /// first if any other pointer shares it (clone-on-write).
+    #[track_caller]
+    pub fn make_mut(this: &mut Self) -> &mut T {
+        if !this.is_unique() {
+            *this = MyRc::new((**this).clone());
+        }
+        unsafe { &mut this.ptr.as_mut().value }
+    }
+}
+
+impl<T: ?Sized> Clone for MyRc<T> {
+    fn clone(&self) -> Self {
+        RcInner::<T>::inc(&self.inner().strong);
+        MyRc {
+            ptr: self.ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+unsafe impl<#[may_dangle] T: ?Sized> Drop for MyRc<T> {
+    fn drop(&mut self) {
+        let strong = &self.inner().strong;
+        strong.set(strong.get() - 1);
+        if strong.get() == 0 {
+            untrack(self.ptr);
+            unsafe {
+                ptr::drop_in_place(&mut (*self.ptr.as_ptr()).value);
+                // Give up the weak reference held by the strong pointers.
+                drop(Weak { ptr: self.ptr });
+            }
+        }
+    }
+}
+
+/// A pointer to a [`MyRc`] allocation that doesn't keep the value alive.
+///
+/// The allocation itself stays around until the last `Weak` is dropped, so
+/// [`Weak::upgrade`] can always tell whether the value is still alive.
+pub struct Weak<T: ?Sized> {
+    // Dangling for weak pointers made by `Weak::new`, which have no allocation.
+    ptr: NonNull<RcInner<T>>,
+}
+
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Weak<U>> for Weak<T> {}
+
+impl<T> Weak<T> {
+    /// Make a weak pointer that never upgrades, without allocating.
+    pub const fn new() -> Self {
+        Weak {
+            ptr: NonNull::without_provenance(NonZeroUsize::MAX),
+        }
+    }
+}
+
+impl<T> Default for Weak<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized> Weak<T> {
+    fn inner(&self) -> Option<&RcInner<T>> {
+        if self.ptr.as_ptr().addr() == usize::MAX {
+            None
+        } else {
+            Some(unsafe { self.ptr.as_ref() })
+        }
+    }
+
+    /// Make a strong pointer to the value, or return `None` if it has already
+    /// been dropped.
+    pub fn upgrade(&self) -> Option<MyRc<T>> {
+        let inner = self.inner()?;
+        if inner.strong.get() == 0 {
+            return None;
+        }
+        RcInner::<T>::inc(&inner.strong);
+        Some(MyRc {
+            ptr: self.ptr,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Number of strong pointers to the value.
+    pub fn strong_count(&self) -> usize {
+        self.inner().map_or(0, |inner| inner.strong.get())
+    }
+
+    /// Return `true` if both pointers point to the same allocation, or were
+    /// both made by [`Weak::new`].
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        ptr::addr_eq(self.ptr.as_ptr(), other.ptr.as_ptr())
+    }
+}
+
+impl<T: ?Sized> Clone for Weak<T> {
+    fn clone(&self) -> Self {
+        if let Some(inner) = self.inner() {
+            RcInner::<T>::inc(&inner.weak);
+        }
+        Weak { ptr: self.ptr }
+    }
+}
+
+impl<T: ?Sized> Drop for Weak<T> {
+    fn drop(&mut self) {
+        let Some(inner) = self.inner() else {
+            return;
+        };
+        inner.weak.set(inner.weak.get() - 1);
+        if inner.weak.get() == 0 {
+            // The value is already gone, only the memory is left to free. It
+            // was allocated by `MyBox::new` with the global allocator.
+            unsafe {
+                let layout = Layout::for_value_raw(self.ptr.as_ptr());
+                alloc::dealloc(self.ptr.as_ptr().cast(), layout);
+            }
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Weak<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(Weak)")
+    }
+}
+
+impl<T: ?Sized> Deref for MyRc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for MyRc<T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for MyRc<T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: Default> Default for MyRc<T> {
+    fn default() -> Self {
+        MyRc::new(T::default())
+    }
+}
+
+impl<T> From<T> for MyRc<T> {
+    fn from(value: T) -> Self {
+        MyRc::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MyRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for MyRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> fmt::Pointer for MyRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&&**self, f)
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for MyRc<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for MyRc<T> {}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for MyRc<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: ?Sized + Ord> Ord for MyRc<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: ?Sized + Hash> Hash for MyRc<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+/// A [`MyRc`] that is still the only pointer to its value, so it can be
+/// mutated freely.
+///
+/// [`Weak`] pointers can be made from a `UniqueRc` and stored anywhere, but
+/// they only start upgrading once [`UniqueRc::into_rc`] turns it into a shared
+/// `MyRc`. That lets a structure wire up back-pointers to a node while it is
+/// still building the node, which [`MyRc::new_cyclic`] only allows inside a
+/// single closure.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::rc::{MyRc, UniqueRc, Weak};
+///
+/// struct Node {
+///     parent: Weak<Node>,
+///     children: Vec<MyRc<Node>>,
+/// }
+///
+/// let mut root = UniqueRc::new(Node { parent: Weak::new(), children: Vec::new() });
+/// for _ in 0..3 {
+///     let parent = UniqueRc::downgrade(&root);
+///     root.children.push(MyRc::new(Node { parent, children: Vec::new() }));
+/// }
+/// let root = UniqueRc::into_rc(root);
+/// let parent = root.children[2].parent.upgrade().unwrap();
+/// assert!(MyRc::ptr_eq(&parent, &root));
+/// ```
+pub struct UniqueRc<T: ?Sized> {
+    // The strong count stays at 0 until `into_rc`, so weak pointers can't
+    // upgrade.
+    ptr: NonNull<RcInner<T>>,
+    _marker: PhantomData<RcInner<T>>,
+}
+
+impl<T> UniqueRc<T> {
+    /// Move `value` into a new allocation.
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        let inner = MyBox::new(RcInner {
+            strong: MyCell::new(0),
+            weak: MyCell::new(1),
+            value,
+        });
+        let ptr = unsafe { NonNull::new_unchecked(MyBox::into_raw(inner)) };
+        track(ptr);
+        UniqueRc {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> UniqueRc<T> {
+    /// Make a [`Weak`] pointer to the value, which can be upgraded once
+    /// `this` has been turned into a [`MyRc`].
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        RcInner::<T>::inc(&unsafe { this.ptr.as_ref() }.weak);
+        Weak { ptr: this.ptr }
+    }
+
+    /// Share the value: weak pointers made so far can be upgraded from now on.
+    pub fn into_rc(this: Self) -> MyRc<T> {
+        let this = ManuallyDrop::new(this);
+        unsafe {
+            this.ptr.as_ref().strong.set(1);
+            MyRc::from_inner(this.ptr.as_ptr())
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for UniqueRc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &self.ptr.as_ref().value }
+    }
+}
+
+impl<T: ?Sized> DerefMut for UniqueRc<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Weak pointers can't reach the value while the strong count is 0.
+        unsafe { &mut self.ptr.as_mut().value }
+    }
+}
+
+unsafe impl<#[may_dangle] T: ?Sized> Drop for UniqueRc<T> {
+    fn drop(&mut self) {
+        untrack(self.ptr);
+        unsafe {
+            ptr::drop_in_place(&mut (*self.ptr.as_ptr()).value);
+            // Give up the weak reference the strong pointers would hold.
+            drop(Weak { ptr: self.ptr });
+        }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for UniqueRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use super::*;
+
+    struct Noisy<'a>(&'a MyCell<usize>);
+
+    impl Drop for Noisy<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn last_clone_drops_the_value() {
+        let drops = MyCell::new(0);
+        let a = MyRc::new(Noisy(&drops));
+        let b = a.clone();
+        let c = b.clone();
+        assert_eq!(MyRc::strong_count(&a), 3);
+        assert!(MyRc::ptr_eq(&a, &c));
+        drop(a);
+        drop(b);
+        assert_eq!(drops.get(), 0);
+        assert_eq!(MyRc::strong_count(&c), 1);
+        drop(c);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn unique_access() {
+        let mut a = MyRc::new(String::from("a"));
+        MyRc::get_mut(&mut a).unwrap().push('b');
+        let b = a.clone();
+        assert_eq!(MyRc::get_mut(&mut a), None);
+        let a = MyRc::try_unwrap(a).unwrap_err();
+        drop(b);
+        assert_eq!(MyRc::try_unwrap(a).as_deref(), Ok("ab"));
+    }
+
+    #[test]
+    fn make_mut_clones_only_when_shared() {
+        let mut a = MyRc::new(1);
+        let before = &*a as *const i32;
+        *MyRc::make_mut(&mut a) += 1;
+        assert_eq!(&*a as *const i32, before);
+
+        let b = a.clone();
+        *MyRc::make_mut(&mut a) += 1;
+        assert_eq!((*a, *b), (3, 2));
+        assert!(!MyRc::ptr_eq(&a, &b));
+        assert_eq!(MyRc::unwrap_or_clone(b), 2);
+    }
+
+    #[test]
+    fn weak_pointers() {
+        let drops = MyCell::new(0);
+        let a = MyRc::new(Noisy(&drops));
+        let weak = MyRc::downgrade(&a);
+        let weak2 = weak.clone();
+        assert_eq!((MyRc::strong_count(&a), MyRc::weak_count(&a)), (1, 2));
+        let b = weak.upgrade().unwrap();
+        drop(a);
+        assert_eq!(drops.get(), 0);
+        drop(b);
+        assert_eq!(drops.get(), 1);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(weak2.strong_count(), 0);
+        assert!(Weak::<u8>::new().upgrade().is_none());
+
+        let mut c = MyRc::new(5);
+        let weak = MyRc::downgrade(&c);
+        assert_eq!(MyRc::get_mut(&mut c), None);
+        // A weak pointer is enough to make `make_mut` clone.
+        *MyRc::make_mut(&mut c) += 1;
+        assert!(weak.upgrade().is_none());
+        assert_eq!(MyRc::try_unwrap(c), Ok(6));
+    }
+
+    #[test]
+    fn tree_with_parent_pointers() {
+        use crate::cell::MyRefCell;
+
+        struct TreeNode<'a> {
+            parent: Weak<TreeNode<'a>>,
+            children: MyRefCell<Vec<MyRc<TreeNode<'a>>>>,
+            _noisy: Noisy<'a>,
+        }
+
+        let drops = MyCell::new(0);
+        let root = MyRc::new_cyclic(|me| {
+            assert!(me.upgrade().is_none());
+            let child = MyRc::new(TreeNode {
+                parent: me.clone(),
+                children: MyRefCell::new(Vec::new()),
+                _noisy: Noisy(&drops),
+            });
+            TreeNode {
+                parent: Weak::new(),
+                children: MyRefCell::new(vec![child]),
+                _noisy: Noisy(&drops),
+            }
+        });
+        let child = root.children.borrow()[0].clone();
+        assert!(MyRc::ptr_eq(&child.parent.upgrade().unwrap(), &root));
+        assert!(root.parent.upgrade().is_none());
+        drop(root);
+        assert_eq!(drops.get(), 1);
+        assert!(child.parent.upgrade().is_none());
+        drop(child);
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn unique_rc_weak_pointers_wait_for_sharing() {
+        let drops = MyCell::new(0);
+        let mut unique = UniqueRc::new((0, Noisy(&drops)));
+        let weak = UniqueRc::downgrade(&unique);
+        assert!(weak.upgrade().is_none());
+        unique.0 += 1;
+
+        let shared = UniqueRc::into_rc(unique);
+        assert_eq!(weak.upgrade().map(|rc| rc.0), Some(1));
+        assert_eq!(MyRc::weak_count(&shared), 1);
+        drop(shared);
+        assert_eq!(drops.get(), 1);
+
+        // Dropped without ever being shared.
+        let unique = UniqueRc::new(Noisy(&drops));
+        let weak = UniqueRc::downgrade(&unique);
+        drop(unique);
+        assert_eq!(drops.get(), 2);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "leak_check")]
+    fn leak_check_flags_cycles() {
+        use crate::cell::MyRefCell;
+
+        struct Node {
+            next: MyRefCell<Option<MyRc<Node>>>,
+        }
+
+        assert_no_leaks();
+        let a = MyRc::new(Node {
+            next: MyRefCell::new(None),
+        });
+        let b = MyRc::new(Node {
+            next: MyRefCell::new(Some(a.clone())),
+        });
+        assert_eq!(live_count(), 2);
+        *a.next.borrow_mut() = Some(b.clone());
+        drop(b);
+        // Breaking the cycle frees both nodes.
+        a.next.borrow_mut().take();
+        drop(a);
+        assert_no_leaks();
+
+        let c = MyRc::new(Node {
+            next: MyRefCell::new(None),
+        });
+        *c.next.borrow_mut() = Some(c.clone());
+        drop(c);
+        let err = std::panic::catch_unwind(assert_no_leaks).unwrap_err();
+        let message = err.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with("1 MyRc value(s) leaked, made at: src/rc/mod.rs:"));
+    }
+
+    #[test]
+    fn unsized_targets() {
+        let drops = MyCell::new(0);
+        let slice: MyRc<[Noisy]> = MyRc::new([Noisy(&drops), Noisy(&drops)]);
+        let other = slice.clone();
+        assert_eq!(other.len(), 2);
+        drop(slice);
+        drop(other);
+        assert_eq!(drops.get(), 2);
+
+        let shown: MyRc<dyn fmt::Display> = MyRc::new(5);
+        assert_eq!(shown.to_string(), "5");
+    }
+}
@@ -0,0 +1,354 @@
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Bound, Deref, Range, RangeBounds},
+};
+
+use crate::{my_vec::MyVec, rc::MyRc};
+
+/// A reference-counted view into a shared buffer.
+///
+/// An `RcSlice` is a [`MyRc`] of a whole [`MyVec`] plus the range of it that
+/// the handle sees. Subslicing and splitting only make new handles with
+/// narrower ranges, so a buffer can be cut into pieces that are passed around
+/// and dropped independently without ever copying the elements. The buffer is
+/// freed along with the last handle.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::rc_slice::RcSlice;
+///
+/// let packet: RcSlice<u8> = b"HDR:payload".iter().copied().collect();
+/// let (header, body) = packet.split_at(4);
+/// assert_eq!(&*header, b"HDR:");
+/// assert_eq!(&*body.slice(..3), b"pay");
+/// // All three handles share one buffer.
+/// assert!(header.same_buffer(&body));
+/// ```
+pub struct RcSlice<T> {
+    buf: MyRc<MyVec<T>>,
+    // Part of `buf` this handle sees.
+    start: usize,
+    end: usize,
+}
+
+// Turn `range` into bounds within `0..len`.
+fn to_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e + 1,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "range start (is {start}) should be <= range end (is {end})"
+    );
+    assert!(
+        end <= len,
+        "range end (is {end}) should be <= len (is {len})"
+    );
+    start..end
+}
+
+impl<T> RcSlice<T> {
+    /// Create an empty slice.
+    pub fn new() -> Self {
+        Self::from(MyVec::new())
+    }
+
+    /// A new handle to the part of this slice in `range`, sharing the buffer.
+    /// Panics if the range is out of bounds.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let range = to_range(range, self.len());
+        RcSlice {
+            buf: self.buf.clone(),
+            start: self.start + range.start,
+            end: self.start + range.end,
+        }
+    }
+
+    /// Split into the handles to `[..mid]` and `[mid..]`, sharing the buffer.
+    /// Panics if `mid > len`.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        assert!(
+            mid <= self.len(),
+            "split index (is {mid}) should be <= len (is {len})",
+            len = self.len()
+        );
+        (self.slice(..mid), self.slice(mid..))
+    }
+
+    /// Shrink this handle to `[..at]` and return a handle to `[at..]`.
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let (head, tail) = self.split_at(at);
+        *self = head;
+        tail
+    }
+
+    /// Shrink this handle to `[at..]` and return a handle to `[..at]`.
+    /// Panics if `at > len`.
+    pub fn split_to(&mut self, at: usize) -> Self {
+        let (head, tail) = self.split_at(at);
+        *self = tail;
+        head
+    }
+
+    /// Return `true` if both handles share the same buffer, whatever parts of
+    /// it they see.
+    pub fn same_buffer(&self, other: &Self) -> bool {
+        MyRc::ptr_eq(&self.buf, &other.buf)
+    }
+
+    /// Offset of this slice in the shared buffer.
+    pub fn offset(&self) -> usize {
+        self.start
+    }
+
+    /// Copy the elements into a new vector.
+    pub fn to_vec(&self) -> MyVec<T>
+    where
+        T: Clone,
+    {
+        let mut vec = MyVec::with_capacity(self.len());
+        for value in self.iter() {
+            vec.push(value.clone());
+        }
+        vec
+    }
+
+    /// Return the buffer without copying it if this handle is its only one
+    /// and sees all of it, or the handle itself otherwise.
+    pub fn try_into_vec(self) -> Result<MyVec<T>, Self> {
+        if self.start != 0 || self.end != self.buf.len() {
+            return Err(self);
+        }
+        let (start, end) = (self.start, self.end);
+        MyRc::try_unwrap(self.buf).map_err(|buf| RcSlice { buf, start, end })
+    }
+}
+
+impl<T> Deref for RcSlice<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.buf[self.start..self.end]
+    }
+}
+
+impl<T> AsRef<[T]> for RcSlice<T> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> Borrow<[T]> for RcSlice<T> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> Clone for RcSlice<T> {
+    /// Make a new handle to the same part of the buffer, without copying.
+    fn clone(&self) -> Self {
+        RcSlice {
+            buf: self.buf.clone(),
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+impl<T> Default for RcSlice<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<MyVec<T>> for RcSlice<T> {
+    /// Share `vec` without copying it.
+    fn from(vec: MyVec<T>) -> Self {
+        let end = vec.len();
+        RcSlice {
+            buf: MyRc::new(vec),
+            start: 0,
+            end,
+        }
+    }
+}
+
+impl<T: Clone> From<&[T]> for RcSlice<T> {
+    fn from(slice: &[T]) -> Self {
+        slice.iter().cloned().collect()
+    }
+}
+
+impl<T> FromIterator<T> for RcSlice<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = MyVec::new();
+        for value in iter {
+            vec.push(value);
+        }
+        Self::from(vec)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcSlice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for RcSlice<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: PartialEq> PartialEq<[T]> for RcSlice<T> {
+    fn eq(&self, other: &[T]) -> bool {
+        **self == *other
+    }
+}
+
+impl<T: Eq> Eq for RcSlice<T> {}
+
+impl<T: PartialOrd> PartialOrd for RcSlice<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord> Ord for RcSlice<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: Hash> Hash for RcSlice<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RcSlice<T> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for RcSlice<T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for RcSlice<T>
+where
+    T: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for RcSlice<T>
+where
+    T: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for RcSlice<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use super::*;
+
+    #[test]
+    fn slices_share_the_buffer() {
+        let whole: RcSlice<u32> = (0..10).collect();
+        let middle = whole.slice(2..8);
+        let inner = middle.slice(1..=2);
+        assert_eq!(&*inner, &[3, 4]);
+        assert_eq!(inner.offset(), 3);
+        assert!(inner.same_buffer(&whole));
+        assert_eq!(whole.slice(..).len(), 10);
+        assert!(middle.slice(6..).is_empty());
+
+        let mut rest = whole.clone();
+        let head = rest.split_to(3);
+        let tail = rest.split_off(4);
+        assert_eq!(
+            (&*head, &*rest, &*tail),
+            (&[0, 1, 2][..], &[3, 4, 5, 6][..], &[7, 8, 9][..])
+        );
+    }
+
+    #[test]
+    fn into_vec_only_when_unique_and_whole() {
+        let whole: RcSlice<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+        let part = whole.slice(1..);
+        let whole = whole.try_into_vec().unwrap_err();
+        let part = part.try_into_vec().unwrap_err();
+        assert_eq!(&*part.to_vec(), &["b"]);
+        drop(part);
+        assert_eq!(&*whole.try_into_vec().unwrap(), &["a", "b"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end (is 5) should be <= len (is 4)")]
+    fn slice_out_of_bounds() {
+        let slice: RcSlice<u8> = RcSlice::from(&[1, 2, 3, 4, 5][1..]);
+        slice.slice(1..5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let values: RcSlice<i32> = [1, 2, 3].into_iter().collect();
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let back: RcSlice<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(*back, *values);
+    }
+}
@@ -0,0 +1,783 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::mem;
+use core::ops::{Bound, RangeBounds};
+use core::ptr;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Color {
+    Red,
+    Black,
+}
+
+impl Color {
+    fn flip(self) -> Self {
+        match self {
+            Color::Red => Color::Black,
+            Color::Black => Color::Red,
+        }
+    }
+}
+
+/// Node of a red-black tree.
+/// The color of a node is the color of the link from its parent.
+struct Node<K, V> {
+    key: K,
+    value: V,
+    color: Color,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn is_red<K, V>(node: &Link<K, V>) -> bool {
+    node.as_ref().is_some_and(|node| node.color == Color::Red)
+}
+
+// `true` if the left child of the node exists and is red.
+fn is_left_red<K, V>(node: &Link<K, V>) -> bool {
+    node.as_ref().is_some_and(|node| is_red(&node.left))
+}
+
+/// An ordered map based on a red-black tree.
+/// Every node is colored red or black such that no red node has a red child and
+/// every path from the root to a leaf crosses the same number of black nodes,
+/// which keeps the height below 2 log2(n + 1).
+///
+/// This is the left-leaning variant, where red links always lean left. It is
+/// equivalent to a 2-3 tree and needs far fewer rebalancing cases than the
+/// classic formulation: a left rotation, a right rotation and a color flip.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::red_black::RedBlackMap;
+///
+/// let mut map = RedBlackMap::new();
+/// map.insert(3, "c");
+/// map.insert(1, "a");
+/// map.insert(2, "b");
+/// assert_eq!(map.get(&2), Some(&"b"));
+/// assert_eq!(map.remove(&1), Some("a"));
+/// map.validate();
+/// let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+/// assert_eq!(keys, vec![2, 3]);
+/// ```
+pub struct RedBlackMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K, V> Default for RedBlackMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> RedBlackMap<K, V> {
+    /// Create a new, empty map.
+    pub const fn new() -> Self {
+        RedBlackMap { root: None, len: 0 }
+    }
+
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Iterate over the entries in ascending key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            remaining: self.len,
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    /// Iterate over the entries in ascending key order, with mutable
+    /// references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut iter = IterMut {
+            stack: Vec::new(),
+            remaining: self.len,
+        };
+        iter.push_left(self.root.as_deref_mut());
+        iter
+    }
+}
+
+impl<K, V> RedBlackMap<K, V>
+where
+    K: Ord,
+{
+    /// Insert a key-value pair into the map.
+    /// Returns the old value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut old = None;
+        let mut root = Self::insert_node(self.root.take(), key, value, &mut old);
+        root.color = Color::Black;
+        self.root = Some(root);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    fn insert_node(node: Link<K, V>, key: K, value: V, old: &mut Option<V>) -> Box<Node<K, V>> {
+        let mut node = match node {
+            Some(node) => node,
+            None => {
+                return Box::new(Node {
+                    key,
+                    value,
+                    color: Color::Red,
+                    left: None,
+                    right: None,
+                })
+            }
+        };
+        match key.cmp(&node.key) {
+            Ordering::Less => {
+                node.left = Some(Self::insert_node(node.left.take(), key, value, old))
+            }
+            Ordering::Greater => {
+                node.right = Some(Self::insert_node(node.right.take(), key, value, old))
+            }
+            Ordering::Equal => *old = Some(mem::replace(&mut node.value, value)),
+        }
+        Self::balance(node)
+    }
+
+    /// Get a reference to the value of a key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => current = node.left.as_deref(),
+                Ordering::Greater => current = node.right.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+            }
+        }
+        None
+    }
+
+    /// Get a mutable reference to the value of a key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => current = node.left.as_deref_mut(),
+                Ordering::Greater => current = node.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+            }
+        }
+        None
+    }
+
+    /// Return `true` if the map contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key from the map.
+    /// Returns the value of the key if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // The top-down deletion below assumes the key is present.
+        if !self.contains_key(key) {
+            return None;
+        }
+        let mut root = self.root.take().unwrap();
+        if !is_red(&root.left) && !is_red(&root.right) {
+            root.color = Color::Red;
+        }
+        let mut removed = None;
+        self.root = Self::remove_node(root, key, &mut removed);
+        if let Some(root) = self.root.as_mut() {
+            root.color = Color::Black;
+        }
+        self.len -= 1;
+        removed.map(|(_, value)| value)
+    }
+
+    fn remove_node<Q>(
+        mut node: Box<Node<K, V>>,
+        key: &Q,
+        removed: &mut Option<(K, V)>,
+    ) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if key < node.key.borrow() {
+            if !is_red(&node.left) && !is_left_red(&node.left) {
+                node = Self::move_red_left(node);
+            }
+            node.left = Self::remove_node(node.left.take().unwrap(), key, removed);
+        } else {
+            if is_red(&node.left) {
+                node = Self::rotate_right(node);
+            }
+            if key == node.key.borrow() && node.right.is_none() {
+                let node = *node;
+                *removed = Some((node.key, node.value));
+                return None;
+            }
+            if !is_red(&node.right) && !is_left_red(&node.right) {
+                node = Self::move_red_right(node);
+            }
+            if key == node.key.borrow() {
+                // Replace the node with the minimum of its right subtree.
+                let (right, min) = Self::remove_min(node.right.take().unwrap());
+                node.right = right;
+                let min = *min;
+                let key = mem::replace(&mut node.key, min.key);
+                let value = mem::replace(&mut node.value, min.value);
+                *removed = Some((key, value));
+            } else {
+                node.right = Self::remove_node(node.right.take().unwrap(), key, removed);
+            }
+        }
+        Some(Self::balance(node))
+    }
+
+    // Detach the minimum node of a subtree, returning the rest of the subtree and the node.
+    fn remove_min(mut node: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+        if node.left.is_none() {
+            return (None, node);
+        }
+        if !is_red(&node.left) && !is_left_red(&node.left) {
+            node = Self::move_red_left(node);
+        }
+        let (left, min) = Self::remove_min(node.left.take().unwrap());
+        node.left = left;
+        (Some(Self::balance(node)), min)
+    }
+
+    /// Get the entry with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Get the entry with the largest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Iterate over the entries whose keys fall in `range`, in ascending key order.
+    /// Panics if the start of the range is greater than its end.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+            (start, end)
+        {
+            assert!(s <= e, "range start is greater than range end");
+        }
+        let mut iter = Range {
+            front: Vec::new(),
+            back: Vec::new(),
+        };
+        // Each stack keeps the nodes on the path to its end of the range that
+        // are still to be visited from that end.
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            let key = current.key.borrow();
+            node = match start {
+                Bound::Included(s) if key < s => current.right.as_deref(),
+                Bound::Excluded(s) if key <= s => current.right.as_deref(),
+                _ => {
+                    iter.front.push(current);
+                    current.left.as_deref()
+                }
+            };
+        }
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            let key = current.key.borrow();
+            node = match end {
+                Bound::Included(e) if key > e => current.left.as_deref(),
+                Bound::Excluded(e) if key >= e => current.left.as_deref(),
+                _ => {
+                    iter.back.push(current);
+                    current.right.as_deref()
+                }
+            };
+        }
+        // The ends cross when no key falls in the range.
+        match (iter.front.last(), iter.back.last()) {
+            (Some(first), Some(last)) if first.key <= last.key => {}
+            _ => {
+                iter.front.clear();
+                iter.back.clear();
+            }
+        }
+        iter
+    }
+
+    fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut right = node
+            .right
+            .take()
+            .expect("rotate left requires a right child");
+        node.right = right.left.take();
+        right.color = node.color;
+        node.color = Color::Red;
+        right.left = Some(node);
+        right
+    }
+
+    fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut left = node
+            .left
+            .take()
+            .expect("rotate right requires a left child");
+        node.left = left.right.take();
+        left.color = node.color;
+        node.color = Color::Red;
+        left.right = Some(node);
+        left
+    }
+
+    // Flip the colors of a node and its two children.
+    fn flip_colors(node: &mut Node<K, V>) {
+        node.color = node.color.flip();
+        for child in [&mut node.left, &mut node.right].into_iter().flatten() {
+            child.color = child.color.flip();
+        }
+    }
+
+    // Assuming the node is red and both its left child and left grandchild are black,
+    // make the left child or one of its children red.
+    fn move_red_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        Self::flip_colors(&mut node);
+        if is_left_red(&node.right) {
+            node.right = Some(Self::rotate_right(node.right.take().unwrap()));
+            node = Self::rotate_left(node);
+            Self::flip_colors(&mut node);
+        }
+        node
+    }
+
+    // Assuming the node is red and both its right child and right grandchild are black,
+    // make the right child or one of its children red.
+    fn move_red_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        Self::flip_colors(&mut node);
+        if is_left_red(&node.left) {
+            node = Self::rotate_right(node);
+            Self::flip_colors(&mut node);
+        }
+        node
+    }
+
+    // Restore the left-leaning red-black invariants on the way up.
+    fn balance(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        // Right-leaning red link.
+        if is_red(&node.right) && !is_red(&node.left) {
+            node = Self::rotate_left(node);
+        }
+        // Two red links in a row.
+        if is_red(&node.left) && is_left_red(&node.left) {
+            node = Self::rotate_right(node);
+        }
+        // Temporary 4-node.
+        if is_red(&node.left) && is_red(&node.right) {
+            Self::flip_colors(&mut node);
+        }
+        node
+    }
+
+    /// Assert that the tree satisfies the red-black invariants.
+    /// Panics if
+    /// - the keys are not in ascending order,
+    /// - the root is red,
+    /// - a red node has a red child or a red link leans right,
+    /// - two paths from the root to a leaf cross a different number of black nodes.
+    pub fn validate(&self) {
+        fn check<K: Ord, V>(node: &Link<K, V>, is_root: bool) -> usize {
+            let node = match node {
+                Some(node) => node,
+                None => return 1,
+            };
+            if is_root {
+                assert_eq!(node.color, Color::Black, "root must be black");
+            }
+            if let Some(left) = &node.left {
+                assert!(
+                    left.key < node.key,
+                    "left child must be less than its parent"
+                );
+            }
+            if let Some(right) = &node.right {
+                assert!(
+                    right.key > node.key,
+                    "right child must be greater than its parent"
+                );
+            }
+            assert!(!is_red(&node.right), "red links must lean left");
+            if node.color == Color::Red {
+                assert!(!is_red(&node.left), "red node must not have a red child");
+            }
+            let left = check(&node.left, false);
+            let right = check(&node.right, false);
+            assert_eq!(left, right, "black height must be equal on all paths");
+            left + usize::from(node.color == Color::Black)
+        }
+        check(&self.root, true);
+        assert_eq!(self.iter().count(), self.len, "stale length");
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for RedBlackMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the entries of a [`RedBlackMap`] in ascending key order.
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a RedBlackMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over a range of entries of a [`RedBlackMap`] in ascending key order.
+pub struct Range<'a, K, V> {
+    // Like the stack of `Iter`, for the front and back of the range. The range
+    // is used up once both ends reach the same node.
+    front: Vec<&'a Node<K, V>>,
+    back: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front.pop()?;
+        if ptr::eq(node, *self.back.last().unwrap()) {
+            self.front.clear();
+            self.back.clear();
+        } else {
+            let mut next = node.right.as_deref();
+            while let Some(current) = next {
+                self.front.push(current);
+                next = current.left.as_deref();
+            }
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back.pop()?;
+        if ptr::eq(node, *self.front.last().unwrap()) {
+            self.front.clear();
+            self.back.clear();
+        } else {
+            let mut next = node.left.as_deref();
+            while let Some(current) = next {
+                self.back.push(current);
+                next = current.right.as_deref();
+            }
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+// A node whose left subtree is done, split into its entry and the right
+// subtree still to visit.
+type Pending<'a, K, V> = (&'a K, &'a mut V, Option<&'a mut Node<K, V>>);
+
+/// Mutable iterator over the entries of a [`RedBlackMap`] in ascending key order.
+pub struct IterMut<'a, K, V> {
+    stack: Vec<Pending<'a, K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> IterMut<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a mut Node<K, V>>) {
+        while let Some(current) = node {
+            let Node {
+                key,
+                value,
+                left,
+                right,
+                ..
+            } = current;
+            self.stack.push((key, value, right.as_deref_mut()));
+            node = left.as_deref_mut();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value, right) = self.stack.pop()?;
+        self.push_left(right);
+        self.remaining -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut RedBlackMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for RedBlackMap<K, V>
+where
+    K: Ord,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for RedBlackMap<K, V>
+where
+    K: Ord,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = RedBlackMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for RedBlackMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for RedBlackMap<K, V>
+where
+    K: serde::Deserialize<'de> + Ord,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for RedBlackMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for RedBlackMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Ord,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &(K, V), b: &(K, V)| a.0.cmp(&b.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn insert() {
+        let mut map = RedBlackMap::new();
+        assert_eq!(map.insert("foo", 42), None);
+        assert_eq!(map.insert("foo", 43), Some(42));
+        assert_eq!(map.len(), 1);
+        map.validate();
+    }
+
+    #[test]
+    fn get() {
+        let mut map = RedBlackMap::new();
+        map.insert("foo", 42);
+        assert_eq!(map.get("foo"), Some(&42));
+        assert_eq!(map.get("bar"), None);
+        *map.get_mut("foo").unwrap() += 1;
+        assert_eq!(map.get("foo"), Some(&43));
+        assert!(map.contains_key("foo"));
+    }
+
+    #[test]
+    fn remove() {
+        let mut map = RedBlackMap::new();
+        for i in 0..200 {
+            // Scramble the insertion order.
+            let key = (i * 37) % 200;
+            map.insert(key, key * 10);
+            map.validate();
+        }
+        for i in (0..200).step_by(3) {
+            assert_eq!(map.remove(&i), Some(i * 10));
+            map.validate();
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 133);
+        for i in 0..200 {
+            assert_eq!(map.contains_key(&i), i % 3 != 0);
+        }
+        for i in 0..200 {
+            map.remove(&i);
+            map.validate();
+        }
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn iter() {
+        let map: RedBlackMap<_, _> = [5, 3, 7, 2, 4, 6, 8].into_iter().map(|k| (k, ())).collect();
+        let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec![2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(map.first_key_value(), Some((&2, &())));
+        assert_eq!(map.last_key_value(), Some((&8, &())));
+    }
+
+    #[test]
+    #[should_panic(expected = "red node must not have a red child")]
+    fn validate() {
+        let mut map = RedBlackMap::new();
+        map.insert(2, ());
+        map.insert(1, ());
+        map.insert(0, ());
+        map.validate();
+        // Break the invariants by hand: a red node with a red child.
+        let left = map.root.as_mut().unwrap().left.as_mut().unwrap();
+        left.color = Color::Red;
+        left.left = Some(Box::new(Node {
+            key: -1,
+            value: (),
+            color: Color::Red,
+            left: None,
+            right: None,
+        }));
+        map.validate();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: RedBlackMap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"1":10,"2":20}"#);
+        let back: RedBlackMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            back.iter().collect::<Vec<_>>(),
+            map.iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,726 @@
+use alloc::collections::VecDeque;
+use core::{
+    fmt,
+    iter::{Chain, FusedIterator},
+    mem::{self, MaybeUninit},
+    ops::{self, Bound, RangeBounds},
+    slice,
+};
+
+/// A fixed-capacity circular buffer backed by an inline `[MaybeUninit<T>; N]`.
+///
+/// Pushing to a full buffer overwrites the oldest element, so the buffer always
+/// holds the `N` most recent values. That makes it a good fit for bounded
+/// histories such as the last few samples of a metric.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::ring::CircularBuffer;
+///
+/// let mut buf: CircularBuffer<i32, 3> = CircularBuffer::new();
+/// buf.push(1);
+/// buf.push(2);
+/// buf.push(3);
+/// assert!(buf.is_full());
+/// assert_eq!(buf.push(4), Some(1));
+/// assert_eq!(buf.latest(), Some(&4));
+/// assert_eq!(buf.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+/// ```
+pub struct CircularBuffer<T, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    // Slot of the oldest element.
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> CircularBuffer<T, N> {
+    /// Constructs a new, empty buffer.
+    pub const fn new() -> Self {
+        CircularBuffer {
+            data: [const { MaybeUninit::uninit() }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the buffer.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the buffer contains no elements.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements the buffer can hold, always `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns `true` if the next push will overwrite the oldest element.
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    // Slot of the `index`-th oldest element.
+    fn slot(&self, index: usize) -> usize {
+        (self.head + index) % N
+    }
+
+    /// Appends an element as the newest one.
+    /// If the buffer is full, the oldest element is removed and returned.
+    pub fn push(&mut self, value: T) -> Option<T> {
+        if N == 0 {
+            return Some(value);
+        }
+        if self.is_full() {
+            let old = unsafe { self.data[self.head].assume_init_read() };
+            self.data[self.head].write(value);
+            self.head = self.slot(1);
+            return Some(old);
+        }
+        let slot = self.slot(self.len);
+        self.data[slot].write(value);
+        self.len += 1;
+        None
+    }
+
+    /// Removes the oldest element and returns it, or [`None`] if the buffer is empty.
+    pub fn pop_oldest(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = unsafe { self.data[self.head].assume_init_read() };
+        self.head = self.slot(1);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Removes the newest element and returns it, or [`None`] if the buffer is empty.
+    pub fn pop_latest(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.slot(self.len);
+        unsafe { Some(self.data[slot].assume_init_read()) }
+    }
+
+    /// Returns the `index`-th oldest element.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        unsafe { Some(self.data[self.slot(index)].assume_init_ref()) }
+    }
+
+    /// Returns the `index`-th oldest element mutably.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let slot = self.slot(index);
+        unsafe { Some(self.data[slot].assume_init_mut()) }
+    }
+
+    /// Returns the oldest element.
+    pub fn oldest(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns the most recently pushed element.
+    pub fn latest(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// Returns an iterator from the oldest to the newest element.
+    pub fn iter(&self) -> Iter<'_, T, N> {
+        Iter {
+            buf: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+
+    /// Returns an iterator from the oldest to the newest element, yielding
+    /// mutable references.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, N> {
+        // The elements wrap around the end of the array at most once.
+        let wrapped = (self.head + self.len).saturating_sub(N);
+        let (start, rest) = self.data.split_at_mut(self.head);
+        IterMut {
+            inner: rest[..self.len - wrapped]
+                .iter_mut()
+                .chain(&mut start[..wrapped]),
+        }
+    }
+
+    /// Removes the elements in `range`, counted from the oldest, and returns an
+    /// iterator over them, oldest first.
+    ///
+    /// The newer elements are moved back into place when the iterator is
+    /// dropped. If it is leaked instead, the buffer is left holding only the
+    /// elements older than `range`.
+    ///
+    /// Panics if `range` is out of order or out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::ring::CircularBuffer;
+    ///
+    /// let mut buf: CircularBuffer<_, 4> = (0..6).collect();
+    /// assert_eq!(buf.drain(1..3).collect::<Vec<_>>(), [3, 4]);
+    /// assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [2, 5]);
+    /// assert_eq!(buf.drain(..).count(), 2);
+    /// assert!(buf.is_empty());
+    /// ```
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, N> {
+        let len = self.len;
+        let ops::Range { start, end } = to_range(range, len);
+        self.len = start;
+        Drain {
+            buf: self,
+            front: start,
+            back: end,
+            tail_start: end,
+            tail_len: len - end,
+        }
+    }
+
+    /// Returns an iterator that removes and yields, oldest first, every element
+    /// for which `filter` returns `true`.
+    ///
+    /// Elements the iterator didn't get to before being dropped are kept. If it
+    /// is leaked, the buffer is left empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::ring::CircularBuffer;
+    ///
+    /// let mut buf: CircularBuffer<_, 4> = (0..6).collect();
+    /// let odd: Vec<_> = buf.extract_if(|x| *x % 2 == 1).collect();
+    /// assert_eq!(odd, [3, 5]);
+    /// assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [2, 4]);
+    /// ```
+    pub fn extract_if<F>(&mut self, filter: F) -> ExtractIf<'_, T, N, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let old_len = mem::take(&mut self.len);
+        ExtractIf {
+            buf: self,
+            index: 0,
+            removed: 0,
+            old_len,
+            filter,
+        }
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, visiting them
+    /// oldest first and keeping their order.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.extract_if(|x| !f(x)).for_each(drop);
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        while self.pop_oldest().is_some() {}
+        self.head = 0;
+    }
+}
+
+// Turn `range` into bounds within `0..len`.
+fn to_range<R: RangeBounds<usize>>(range: R, len: usize) -> ops::Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e + 1,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "range start (is {start}) should be <= range end (is {end})"
+    );
+    assert!(
+        end <= len,
+        "range end (is {end}) should be <= len (is {len})"
+    );
+    start..end
+}
+
+impl<T, const N: usize> Default for CircularBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for CircularBuffer<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> ops::Index<usize> for CircularBuffer<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+        self.get(index).unwrap_or_else(|| {
+            panic!("index out of bounds: the len is {len} but the index is {index}")
+        })
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for CircularBuffer<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for CircularBuffer<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for CircularBuffer<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for CircularBuffer<T, N> {}
+
+impl<T, const N: usize> Extend<T> for CircularBuffer<T, N> {
+    /// Pushes every element, keeping only the last `N`.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for CircularBuffer<T, N> {
+    /// Collects the last `N` elements of the iterator.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut buf = CircularBuffer::new();
+        buf.extend(iter);
+        buf
+    }
+}
+
+impl<T, const N: usize> From<CircularBuffer<T, N>> for VecDeque<T> {
+    /// Move the elements over, oldest first.
+    fn from(mut buf: CircularBuffer<T, N>) -> Self {
+        buf.drain(..).collect()
+    }
+}
+
+impl<T, const N: usize> TryFrom<VecDeque<T>> for CircularBuffer<T, N> {
+    type Error = VecDeque<T>;
+
+    /// Move the elements over, front first, or hand the deque back if they
+    /// don't all fit.
+    fn try_from(deque: VecDeque<T>) -> Result<Self, Self::Error> {
+        if deque.len() > N {
+            return Err(deque);
+        }
+        Ok(deque.into_iter().collect())
+    }
+}
+
+/// An iterator over the elements of a [`CircularBuffer`], oldest first.
+pub struct Iter<'a, T, const N: usize> {
+    buf: &'a CircularBuffer<T, N>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.buf.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for Iter<'_, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.buf.get(self.back)
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for Iter<'_, T, N> {}
+
+impl<'a, T, const N: usize> IntoIterator for &'a CircularBuffer<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Mutable iterator over the elements of a [`CircularBuffer`], from the oldest
+/// to the newest.
+pub struct IterMut<'a, T, const N: usize> {
+    // The initialized slots, in order.
+    inner: Chain<slice::IterMut<'a, MaybeUninit<T>>, slice::IterMut<'a, MaybeUninit<T>>>,
+}
+
+impl<'a, T, const N: usize> Iterator for IterMut<'a, T, N> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.inner
+            .next()
+            .map(|slot| unsafe { slot.assume_init_mut() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IterMut<'_, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|slot| unsafe { slot.assume_init_mut() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IterMut<'_, T, N> {}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut CircularBuffer<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator removing a range of elements from a [`CircularBuffer`], returned
+/// by [`CircularBuffer::drain`].
+pub struct Drain<'a, T, const N: usize> {
+    // Its length covers only the elements older than the drained range.
+    buf: &'a mut CircularBuffer<T, N>,
+    // Drained elements not yielded yet, counted from the oldest.
+    front: usize,
+    back: usize,
+    // The elements newer than the drained range.
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<T, const N: usize> Iterator for Drain<'_, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        let slot = self.buf.slot(self.front);
+        self.front += 1;
+        unsafe { Some(self.buf.data[slot].assume_init_read()) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for Drain<'_, T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        let slot = self.buf.slot(self.back);
+        unsafe { Some(self.buf.data[slot].assume_init_read()) }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for Drain<'_, T, N> {}
+
+impl<T, const N: usize> FusedIterator for Drain<'_, T, N> {}
+
+impl<T, const N: usize> Drop for Drain<'_, T, N> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+        // Close the gap, moving each newer element back as far as the
+        // drained ones reached.
+        let start = self.buf.len;
+        for i in 0..self.tail_len {
+            let (from, to) = (self.buf.slot(self.tail_start + i), self.buf.slot(start + i));
+            if from != to {
+                let value = unsafe { self.buf.data[from].assume_init_read() };
+                self.buf.data[to].write(value);
+            }
+        }
+        self.buf.len = start + self.tail_len;
+    }
+}
+
+/// An iterator removing the elements of a [`CircularBuffer`] that match a
+/// filter, returned by [`CircularBuffer::extract_if`].
+pub struct ExtractIf<'a, T, const N: usize, F> {
+    // Its length is zero until the iterator is dropped.
+    buf: &'a mut CircularBuffer<T, N>,
+    // Next element to look at, counted from the oldest.
+    index: usize,
+    // Number of elements removed so far, which is how far back the kept ones
+    // are moved.
+    removed: usize,
+    old_len: usize,
+    filter: F,
+}
+
+impl<T, const N: usize, F> Iterator for ExtractIf<'_, T, N, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.index < self.old_len {
+            let slot = self.buf.slot(self.index);
+            let remove = (self.filter)(unsafe { self.buf.data[slot].assume_init_mut() });
+            self.index += 1;
+            if remove {
+                self.removed += 1;
+                return Some(unsafe { self.buf.data[slot].assume_init_read() });
+            }
+            if self.removed > 0 {
+                let value = unsafe { self.buf.data[slot].assume_init_read() };
+                let to = self.buf.slot(self.index - 1 - self.removed);
+                self.buf.data[to].write(value);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.old_len - self.index))
+    }
+}
+
+impl<T, const N: usize, F> Drop for ExtractIf<'_, T, N, F> {
+    fn drop(&mut self) {
+        if self.removed > 0 {
+            for i in self.index..self.old_len {
+                let value = unsafe { self.buf.data[self.buf.slot(i)].assume_init_read() };
+                let to = self.buf.slot(i - self.removed);
+                self.buf.data[to].write(value);
+            }
+        }
+        self.buf.len = self.old_len - self.removed;
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, const N: usize> serde::Serialize for CircularBuffer<T, N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+/// Fails on a sequence of more than `N` elements, instead of keeping the last `N` like
+/// `collect`.
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, const N: usize> serde::Deserialize<'de>
+    for CircularBuffer<T, N>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_bounded_seq(deserializer, N)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T, const N: usize> arbitrary::Arbitrary<'a> for CircularBuffer<T, N>
+where
+    T: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T, const N: usize> proptest::arbitrary::Arbitrary for CircularBuffer<T, N>
+where
+    T: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn push_overwrites() {
+        let mut buf: CircularBuffer<i32, 3> = CircularBuffer::new();
+        assert_eq!(buf.latest(), None);
+        for i in 0..3 {
+            assert_eq!(buf.push(i), None);
+        }
+        for i in 3..10 {
+            assert_eq!(buf.push(i), Some(i - 3));
+            assert_eq!(buf.latest(), Some(&i));
+            assert_eq!(buf.oldest(), Some(&(i - 2)));
+        }
+        assert_eq!(buf.len(), 3);
+        assert_eq!(buf[0], 7);
+        assert_eq!(buf.get(3), None);
+    }
+
+    #[test]
+    fn pop() {
+        let mut buf: CircularBuffer<_, 4> = (0..6).collect();
+        assert_eq!(buf.pop_oldest(), Some(2));
+        assert_eq!(buf.pop_latest(), Some(5));
+        buf.push(6);
+        buf.push(7);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), vec![3, 4, 6, 7]);
+        *buf.get_mut(0).unwrap() = 30;
+        assert_eq!(
+            buf.iter().rev().copied().collect::<Vec<_>>(),
+            vec![7, 6, 4, 30]
+        );
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut buf: CircularBuffer<i32, 0> = CircularBuffer::new();
+        assert_eq!(buf.push(1), Some(1));
+        assert!(buf.is_empty());
+        assert!(buf.is_full());
+        assert_eq!(buf.latest(), None);
+    }
+
+    #[test]
+    fn drops_elements() {
+        let rc = Rc::new(());
+        {
+            let mut buf: CircularBuffer<Rc<()>, 2> = CircularBuffer::new();
+            for _ in 0..5 {
+                buf.push(rc.clone());
+            }
+            assert_eq!(Rc::strong_count(&rc), 3);
+        }
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn drain() {
+        let make = || {
+            (0..7)
+                .map(|i| i.to_string())
+                .collect::<CircularBuffer<_, 5>>()
+        };
+        let contents = |buf: &CircularBuffer<String, 5>| buf.iter().cloned().collect::<Vec<_>>();
+
+        // The buffer wrapped around, so the drained range does too.
+        let mut buf = make();
+        let mut drain = buf.drain(1..=3);
+        assert_eq!(drain.next_back().as_deref(), Some("5"));
+        assert_eq!(drain.len(), 2);
+        drop(drain);
+        assert_eq!(contents(&buf), ["2", "6"]);
+        buf.push("7".to_string());
+        assert_eq!(contents(&buf), ["2", "6", "7"]);
+        assert_eq!(buf.latest().map(String::as_str), Some("7"));
+
+        let mut buf = make();
+        assert_eq!(buf.drain(..2).collect::<Vec<_>>(), ["2", "3"]);
+        assert_eq!(contents(&buf), ["4", "5", "6"]);
+
+        // A leaked iterator still removes the drained elements.
+        let mut buf = make();
+        std::mem::forget(buf.drain(3..4));
+        assert_eq!(contents(&buf), ["2", "3", "4"]);
+    }
+
+    #[test]
+    fn moving_average() {
+        let mut window: CircularBuffer<f64, 4> = CircularBuffer::new();
+        let mut averages = Vec::new();
+        for sample in [1.0, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            window.push(sample);
+            averages.push(window.iter().sum::<f64>() / window.len() as f64);
+        }
+        assert_eq!(averages, vec![1.0, 1.5, 2.0, 2.5, 3.5, 4.5]);
+    }
+
+    #[test]
+    fn vec_deque() {
+        let mut buf: CircularBuffer<_, 3> = (1..=5).collect();
+        buf.pop_latest();
+        let deque = VecDeque::from(buf);
+        assert_eq!(deque, [3, 4]);
+        let buf = CircularBuffer::<_, 2>::try_from(deque).unwrap();
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [3, 4]);
+        let deque = VecDeque::from([1, 2, 3]);
+        assert_eq!(
+            CircularBuffer::<_, 2>::try_from(deque.clone()).unwrap_err(),
+            deque
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let values: CircularBuffer<i32, 3> = [1, 2, 3].into_iter().collect();
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let back: CircularBuffer<i32, 3> = serde_json::from_str(&json).unwrap();
+        assert!(back.iter().eq(values.iter()));
+        assert!(serde_json::from_str::<CircularBuffer<i32, 2>>(&json).is_err());
+    }
+}
@@ -0,0 +1,568 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{fmt, mem, slice};
+
+use crate::spatial::{Aabb, SpatialIndex};
+
+const MAX_ENTRIES: usize = 8;
+const MIN_ENTRIES: usize = 3;
+
+enum Node<T> {
+    Leaf(Vec<(Aabb, T)>),
+    // Each child is stored with the bounding box of everything below it.
+    Internal(Vec<(Aabb, Box<Node<T>>)>),
+}
+
+impl<T> Node<T> {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(entries) => entries.len(),
+            Node::Internal(children) => children.len(),
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        match self {
+            Node::Leaf(entries) => mbr(entries),
+            Node::Internal(children) => mbr(children),
+        }
+    }
+
+    // Move every item below this node into `out`.
+    fn drain_into(self, out: &mut Vec<(Aabb, T)>) {
+        match self {
+            Node::Leaf(entries) => out.extend(entries),
+            Node::Internal(children) => {
+                for (_, child) in children {
+                    child.drain_into(out);
+                }
+            }
+        }
+    }
+}
+
+// Minimum bounding rectangle of a non-empty list of entries.
+fn mbr<E>(entries: &[(Aabb, E)]) -> Aabb {
+    entries
+        .iter()
+        .map(|(b, _)| *b)
+        .reduce(|a, b| a.union(&b))
+        .expect("node should not be empty")
+}
+
+fn enlargement(b: &Aabb, with: &Aabb) -> f64 {
+    b.union(with).area() - b.area()
+}
+
+// Guttman's quadratic split: leave one group in `entries` and return the other.
+fn split<E>(entries: &mut Vec<(Aabb, E)>) -> Vec<(Aabb, E)> {
+    // Seeds: the pair that would waste the most area in the same node.
+    let mut seeds = (0, 1);
+    let mut worst = f64::NEG_INFINITY;
+    for i in 0..entries.len() {
+        for j in i + 1..entries.len() {
+            let (a, b) = (&entries[i].0, &entries[j].0);
+            let waste = a.union(b).area() - a.area() - b.area();
+            if waste > worst {
+                worst = waste;
+                seeds = (i, j);
+            }
+        }
+    }
+    let mut rest = mem::take(entries);
+    // Remove the later seed first so the index of the other stays valid.
+    let second = rest.swap_remove(seeds.1);
+    let first = rest.swap_remove(seeds.0);
+    let (mut box_a, mut box_b) = (first.0, second.0);
+    let mut group_a = vec![first];
+    let mut group_b = vec![second];
+
+    while !rest.is_empty() {
+        // If a group needs every remaining entry to reach the minimum, give them all.
+        if group_a.len() + rest.len() == MIN_ENTRIES {
+            group_a.append(&mut rest);
+            break;
+        }
+        if group_b.len() + rest.len() == MIN_ENTRIES {
+            group_b.append(&mut rest);
+            break;
+        }
+        // Assign the entry with the strongest preference for one group first.
+        let (index, _) = rest
+            .iter()
+            .enumerate()
+            .map(|(i, (b, _))| (i, (enlargement(&box_a, b) - enlargement(&box_b, b)).abs()))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        let entry = rest.swap_remove(index);
+        let (da, db) = (enlargement(&box_a, &entry.0), enlargement(&box_b, &entry.0));
+        let to_a = match da.total_cmp(&db) {
+            core::cmp::Ordering::Less => true,
+            core::cmp::Ordering::Greater => false,
+            core::cmp::Ordering::Equal => {
+                (box_a.area(), group_a.len()) <= (box_b.area(), group_b.len())
+            }
+        };
+        if to_a {
+            box_a = box_a.union(&entry.0);
+            group_a.push(entry);
+        } else {
+            box_b = box_b.union(&entry.0);
+            group_b.push(entry);
+        }
+    }
+    *entries = group_a;
+    group_b
+}
+
+/// An R-tree indexing items by their 2D bounding boxes.
+///
+/// Every node holds between 3 and 8 entries, each with the bounding box of
+/// everything below it, and all leaves are at the same depth. Unlike a
+/// [`Quadtree`](crate::quadtree::Quadtree), the tree adapts its nodes to the data
+/// instead of splitting space at fixed points, so it copes well with large or
+/// clustered rectangles such as the shapes of a map. Overfull nodes are split
+/// with Guttman's quadratic split.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::rtree::RTree;
+/// use my_std::spatial::Aabb;
+///
+/// let mut tree = RTree::new();
+/// tree.insert(Aabb::new([0.0, 0.0], [10.0, 5.0]), "park");
+/// tree.insert(Aabb::new([20.0, 20.0], [22.0, 23.0]), "museum");
+/// tree.insert(Aabb::point([4.0, 4.0]), "fountain");
+///
+/// let mut hits: Vec<_> = tree
+///     .search(&Aabb::new([3.0, 3.0], [5.0, 5.0]))
+///     .into_iter()
+///     .map(|(_, v)| *v)
+///     .collect();
+/// hits.sort();
+/// assert_eq!(hits, vec!["fountain", "park"]);
+///
+/// assert_eq!(tree.nearest(&[19.0, 19.0]).unwrap().1, &"museum");
+/// assert_eq!(tree.remove(&Aabb::point([4.0, 4.0])), Some("fountain"));
+/// ```
+pub struct RTree<T> {
+    root: Node<T>,
+    len: usize,
+}
+
+impl<T> Default for RTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> RTree<T> {
+    /// Create a new, empty tree.
+    pub const fn new() -> Self {
+        RTree {
+            root: Node::Leaf(Vec::new()),
+            len: 0,
+        }
+    }
+
+    /// Number of items in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert an item covering `bounds`.
+    pub fn insert(&mut self, bounds: Aabb, value: T) {
+        self.len += 1;
+        if let Some(sibling) = Self::insert_node(&mut self.root, bounds, value) {
+            // The root was split: grow the tree by one level.
+            let old = mem::replace(&mut self.root, Node::Internal(Vec::new()));
+            let children = vec![
+                (old.bounds(), Box::new(old)),
+                (sibling.bounds(), Box::new(sibling)),
+            ];
+            self.root = Node::Internal(children);
+        }
+    }
+
+    // Insert below `node` and return the new sibling if `node` had to be split.
+    fn insert_node(node: &mut Node<T>, bounds: Aabb, value: T) -> Option<Node<T>> {
+        match node {
+            Node::Leaf(entries) => {
+                entries.push((bounds, value));
+                (entries.len() > MAX_ENTRIES).then(|| Node::Leaf(split(entries)))
+            }
+            Node::Internal(children) => {
+                // Descend into the child whose box grows the least, then the smallest one.
+                let (index, _) = children
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (b, _))| (i, (enlargement(b, &bounds), b.area())))
+                    .min_by(|a, b| a.1 .0.total_cmp(&b.1 .0).then(a.1 .1.total_cmp(&b.1 .1)))
+                    .unwrap();
+                let (child_bounds, child) = &mut children[index];
+                *child_bounds = child_bounds.union(&bounds);
+                if let Some(sibling) = Self::insert_node(child, bounds, value) {
+                    *child_bounds = child.bounds();
+                    children.push((sibling.bounds(), Box::new(sibling)));
+                }
+                (children.len() > MAX_ENTRIES).then(|| Node::Internal(split(children)))
+            }
+        }
+    }
+
+    /// Remove one item whose bounds are exactly `bounds` and return its value.
+    pub fn remove(&mut self, bounds: &Aabb) -> Option<T> {
+        let mut orphans = Vec::new();
+        let value = Self::remove_node(&mut self.root, bounds, &mut orphans)?;
+        self.len -= 1;
+        // Shrink the tree while the root has a single child.
+        while let Node::Internal(children) = &mut self.root {
+            match children.len() {
+                0 => self.root = Node::Leaf(Vec::new()),
+                1 => self.root = *children.pop().unwrap().1,
+                _ => break,
+            }
+        }
+        // Items of underfull nodes are inserted again from the top.
+        for (b, v) in orphans {
+            self.len -= 1;
+            self.insert(b, v);
+        }
+        Some(value)
+    }
+
+    fn remove_node(node: &mut Node<T>, bounds: &Aabb, orphans: &mut Vec<(Aabb, T)>) -> Option<T> {
+        match node {
+            Node::Leaf(entries) => {
+                let pos = entries.iter().position(|(b, _)| b == bounds)?;
+                Some(entries.swap_remove(pos).1)
+            }
+            Node::Internal(children) => {
+                for i in 0..children.len() {
+                    if !children[i].0.contains(bounds) {
+                        continue;
+                    }
+                    let Some(value) = Self::remove_node(&mut children[i].1, bounds, orphans) else {
+                        continue;
+                    };
+                    if children[i].1.len() < MIN_ENTRIES {
+                        let (_, child) = children.swap_remove(i);
+                        child.drain_into(orphans);
+                    } else {
+                        children[i].0 = children[i].1.bounds();
+                    }
+                    return Some(value);
+                }
+                None
+            }
+        }
+    }
+
+    /// Every item whose bounds intersect `region`.
+    pub fn search(&self, region: &Aabb) -> Vec<(&Aabb, &T)> {
+        let mut res = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Leaf(entries) => res.extend(
+                    entries
+                        .iter()
+                        .filter(|(b, _)| b.intersects(region))
+                        .map(|(b, v)| (b, v)),
+                ),
+                Node::Internal(children) => stack.extend(
+                    children
+                        .iter()
+                        .filter(|(b, _)| b.intersects(region))
+                        .map(|(_, child)| &**child),
+                ),
+            }
+        }
+        res
+    }
+
+    /// The item whose bounds are closest to the point `query`.
+    pub fn nearest(&self, query: &[f64; 2]) -> Option<(&Aabb, &T)> {
+        let mut best = None;
+        Self::nearest_node(&self.root, query, &mut best);
+        best.map(|(_, b, v)| (b, v))
+    }
+
+    fn nearest_node<'a>(
+        node: &'a Node<T>,
+        query: &[f64; 2],
+        best: &mut Option<(f64, &'a Aabb, &'a T)>,
+    ) {
+        match node {
+            Node::Leaf(entries) => {
+                for (b, v) in entries {
+                    let d = b.distance2(query);
+                    if best.is_none_or(|(best_d, _, _)| d < best_d) {
+                        *best = Some((d, b, v));
+                    }
+                }
+            }
+            Node::Internal(children) => {
+                let mut order: Vec<_> = children
+                    .iter()
+                    .map(|(b, child)| (b.distance2(query), child))
+                    .collect();
+                order.sort_by(|a, b| a.0.total_cmp(&b.0));
+                for (d, child) in order {
+                    if best.is_none_or(|(best_d, _, _)| d < best_d) {
+                        Self::nearest_node(child, query, best);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Iterate over every item, in no particular order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stack: vec![&self.root],
+            items: [].iter(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the items of an [`RTree`], in no particular order.
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+    // Entries left in the leaf last popped.
+    items: slice::Iter<'a, (Aabb, T)>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (&'a Aabb, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((b, v)) = self.items.next() {
+                return Some((b, v));
+            }
+            match self.stack.pop()? {
+                Node::Leaf(entries) => self.items = entries.iter(),
+                Node::Internal(children) => self.stack.extend(children.iter().map(|(_, c)| &**c)),
+            }
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RTree<T> {
+    type Item = (&'a Aabb, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Extend<(Aabb, T)> for RTree<T> {
+    fn extend<I: IntoIterator<Item = (Aabb, T)>>(&mut self, iter: I) {
+        for (b, v) in iter {
+            self.insert(b, v);
+        }
+    }
+}
+
+impl<T> FromIterator<(Aabb, T)> for RTree<T> {
+    fn from_iter<I: IntoIterator<Item = (Aabb, T)>>(iter: I) -> Self {
+        let mut tree = RTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T> SpatialIndex<T, 2> for RTree<T> {
+    fn insert_point(&mut self, point: [f64; 2], value: T) {
+        self.insert(Aabb::point(point), value);
+    }
+
+    fn nearest_to(&self, query: &[f64; 2]) -> Option<&T> {
+        self.nearest(query).map(|(_, v)| v)
+    }
+
+    fn in_box(&self, min: &[f64; 2], max: &[f64; 2]) -> Vec<&T> {
+        let region = Aabb::new(*min, *max);
+        self.search(&region)
+            .into_iter()
+            .filter(|(b, _)| region.contains(b))
+            .map(|(_, v)| v)
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// A tree serializes as a sequence of `(bounds, value)` pairs.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for RTree<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for RTree<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for RTree<T>
+where
+    T: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(Aabb, T)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(Aabb, T)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for RTree<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(Aabb, T) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boxes(n: usize) -> Vec<Aabb> {
+        let mut x = 0x1234_5678_u64;
+        let mut next = move |m: u64| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            (x % m) as f64
+        };
+        (0..n)
+            .map(|_| {
+                let min = [next(1000), next(1000)];
+                Aabb::new(min, [min[0] + next(20), min[1] + next(20)])
+            })
+            .collect()
+    }
+
+    // Check node sizes, stored bounds and that every leaf is at the same depth.
+    fn check<T>(node: &Node<T>, is_root: bool) -> usize {
+        if !is_root {
+            assert!((MIN_ENTRIES..=MAX_ENTRIES).contains(&node.len()));
+        }
+        match node {
+            Node::Leaf(_) => 0,
+            Node::Internal(children) => {
+                let depths: Vec<_> = children
+                    .iter()
+                    .map(|(b, child)| {
+                        assert_eq!(*b, child.bounds());
+                        check(child, false)
+                    })
+                    .collect();
+                assert!(depths.windows(2).all(|w| w[0] == w[1]));
+                depths[0] + 1
+            }
+        }
+    }
+
+    #[test]
+    fn search_matches_brute_force() {
+        let items = boxes(2000);
+        let tree: RTree<_> = items.iter().copied().zip(0..).collect();
+        check(&tree.root, true);
+        assert_eq!(tree.len(), 2000);
+        for region in boxes(20)
+            .iter()
+            .map(|b| Aabb::new(b.min, [b.max[0] + 80.0, b.max[1] + 80.0]))
+        {
+            let mut found: Vec<usize> = tree.search(&region).into_iter().map(|(_, v)| *v).collect();
+            found.sort();
+            let expected: Vec<_> = (0..items.len())
+                .filter(|&i| items[i].intersects(&region))
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let items = boxes(1000);
+        let tree: RTree<_> = items.iter().copied().zip(0..).collect();
+        for q in boxes(30).iter().map(|b| [b.min[1], b.min[0]]) {
+            let expected = items
+                .iter()
+                .map(|b| b.distance2(&q))
+                .min_by(f64::total_cmp)
+                .unwrap();
+            assert_eq!(tree.nearest(&q).unwrap().0.distance2(&q), expected);
+        }
+        assert_eq!(RTree::<()>::new().nearest(&[0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn remove() {
+        let items = boxes(500);
+        let mut tree: RTree<_> = items.iter().copied().zip(0..).collect();
+        for (i, b) in items.iter().enumerate().step_by(2) {
+            assert!(tree.remove(b).is_some(), "item {i} should be found");
+        }
+        check(&tree.root, true);
+        assert_eq!(tree.len(), 250);
+        assert_eq!(tree.iter().count(), 250);
+        for (i, b) in items.iter().enumerate().skip(1).step_by(2) {
+            assert!(tree.remove(b).is_some(), "item {i} should be found");
+        }
+        assert!(tree.is_empty());
+        assert!(matches!(&tree.root, Node::Leaf(e) if e.is_empty()));
+        assert_eq!(tree.remove(&items[0]), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let tree: RTree<i32> = (0..20).map(|i| (Aabb::point([i as f64, 0.0]), i)).collect();
+        let json = serde_json::to_string(&tree).unwrap();
+        let back: RTree<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.len(), 20);
+        let mut found: Vec<_> = back
+            .search(&Aabb::new([2.0, 0.0], [4.0, 0.0]))
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect();
+        found.sort();
+        assert_eq!(found, [2, 3, 4]);
+
+        let one: RTree<i32> = [(Aabb::point([1.0, 2.0]), 7)].into_iter().collect();
+        let json = serde_json::to_string(&one).unwrap();
+        assert_eq!(json, r#"[[{"min":[1.0,2.0],"max":[1.0,2.0]},7]]"#);
+    }
+}
@@ -0,0 +1,410 @@
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+/// Segment tree.
+/// It stores a sequence and answers queries that fold a contiguous range with an
+/// associative `combine` function (sum, min, max or any other monoid) in O(log n),
+/// while still allowing single elements to be updated in O(log n).
+///
+/// `identity` must be the neutral element of `combine`, e.g. `0` for sums or
+/// `i32::MAX` for minimums. `combine` does not need to be commutative.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::segment_tree::SegmentTree;
+///
+/// let mut tree = SegmentTree::new(&[1, 2, 3, 4, 5], 0, |a, b| a + b);
+/// assert_eq!(tree.query(1..4), 9);
+/// tree.update(2, 10);
+/// assert_eq!(tree.query(1..4), 16);
+/// ```
+pub struct SegmentTree<T, F> {
+    // Leaves live in `tree[n..2n]`, the parent of node `i` is `i / 2`.
+    tree: Vec<T>,
+    len: usize,
+    identity: T,
+    combine: F,
+}
+
+impl<T, F> SegmentTree<T, F>
+where
+    T: Clone,
+    F: Fn(&T, &T) -> T,
+{
+    /// Build a segment tree over `data` in O(n).
+    pub fn new(data: &[T], identity: T, combine: F) -> Self {
+        let len = data.len();
+        let mut tree = vec![identity.clone(); len];
+        tree.extend_from_slice(data);
+        for i in (1..len).rev() {
+            tree[i] = combine(&tree[2 * i], &tree[2 * i + 1]);
+        }
+        SegmentTree {
+            tree,
+            len,
+            identity,
+            combine,
+        }
+    }
+
+    /// Number of elements in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the element at `index`.
+    /// Returns `None` if the index is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&self.tree[self.len + index])
+    }
+
+    /// Replace the element at `index` with `value`.
+    /// Panics if the index is out of bounds.
+    pub fn update(&mut self, index: usize, value: T) {
+        let len = self.len;
+        if index >= len {
+            panic!("update index (is {index}) should be < len (is {len})");
+        }
+        let mut i = index + len;
+        self.tree[i] = value;
+        while i > 1 {
+            i /= 2;
+            self.tree[i] = (self.combine)(&self.tree[2 * i], &self.tree[2 * i + 1]);
+        }
+    }
+
+    /// Fold the elements in `range` with `combine`.
+    /// Returns `identity` for an empty range.
+    /// Panics if the range is out of bounds.
+    pub fn query(&self, range: Range<usize>) -> T {
+        check_range(&range, self.len);
+        let mut left = self.identity.clone();
+        let mut right = self.identity.clone();
+        let (mut l, mut r) = (range.start + self.len, range.end + self.len);
+        // Accumulate from both ends separately so that the order of the
+        // operands is preserved for non-commutative `combine` functions.
+        while l < r {
+            if l & 1 == 1 {
+                left = (self.combine)(&left, &self.tree[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = (self.combine)(&self.tree[r], &right);
+            }
+            l /= 2;
+            r /= 2;
+        }
+        (self.combine)(&left, &right)
+    }
+}
+
+/// Segment tree with lazy propagation.
+/// In addition to range queries it supports applying an update to a whole range
+/// in O(log n). Pending updates are stored on inner nodes and only pushed down to
+/// the children when a later operation needs to look inside them.
+///
+/// * `combine` folds two adjacent values, as in [`SegmentTree`].
+/// * `apply(value, update, len)` applies `update` to an aggregated `value` that
+///   covers `len` elements.
+/// * `compose(old, new)` merges two pending updates into one that has the same
+///   effect as applying `old` and then `new`.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::segment_tree::LazySegmentTree;
+///
+/// // Range add, range sum.
+/// let mut tree = LazySegmentTree::new(
+///     &[1, 2, 3, 4, 5],
+///     0,
+///     |a, b| a + b,
+///     |value, add, len| value + add * len as i64,
+///     |old, new| old + new,
+/// );
+/// tree.update_range(1..4, 10);
+/// assert_eq!(tree.query(0..5), 45);
+/// assert_eq!(tree.query(3..5), 19);
+/// ```
+pub struct LazySegmentTree<T, U, F, G, H> {
+    tree: Vec<T>,
+    lazy: Vec<Option<U>>,
+    len: usize,
+    identity: T,
+    combine: F,
+    apply: G,
+    compose: H,
+}
+
+impl<T, U, F, G, H> LazySegmentTree<T, U, F, G, H>
+where
+    T: Clone,
+    U: Clone,
+    F: Fn(&T, &T) -> T,
+    G: Fn(&T, &U, usize) -> T,
+    H: Fn(&U, &U) -> U,
+{
+    /// Build a lazy segment tree over `data` in O(n).
+    pub fn new(data: &[T], identity: T, combine: F, apply: G, compose: H) -> Self {
+        let len = data.len();
+        let size = 4 * len.max(1);
+        let mut tree = LazySegmentTree {
+            tree: vec![identity.clone(); size],
+            lazy: vec![None; size],
+            len,
+            identity,
+            combine,
+            apply,
+            compose,
+        };
+        if len > 0 {
+            tree.build(1, 0, len, data);
+        }
+        tree
+    }
+
+    fn build(&mut self, node: usize, lo: usize, hi: usize, data: &[T]) {
+        if hi - lo == 1 {
+            self.tree[node] = data[lo].clone();
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        self.build(2 * node, lo, mid, data);
+        self.build(2 * node + 1, mid, hi, data);
+        self.tree[node] = (self.combine)(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    /// Number of elements in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Apply an update to the node covering `len` elements and remember it for its children.
+    fn apply_node(&mut self, node: usize, update: &U, len: usize) {
+        self.tree[node] = (self.apply)(&self.tree[node], update, len);
+        self.lazy[node] = Some(match self.lazy[node].take() {
+            Some(pending) => (self.compose)(&pending, update),
+            None => update.clone(),
+        });
+    }
+
+    // Push the pending update of a node down to its two children.
+    fn push(&mut self, node: usize, lo: usize, mid: usize, hi: usize) {
+        if let Some(update) = self.lazy[node].take() {
+            self.apply_node(2 * node, &update, mid - lo);
+            self.apply_node(2 * node + 1, &update, hi - mid);
+        }
+    }
+
+    /// Replace the element at `index` with `value`.
+    /// Panics if the index is out of bounds.
+    pub fn update(&mut self, index: usize, value: T) {
+        let len = self.len;
+        if index >= len {
+            panic!("update index (is {index}) should be < len (is {len})");
+        }
+        self.set_helper(1, 0, len, index, value);
+    }
+
+    fn set_helper(&mut self, node: usize, lo: usize, hi: usize, index: usize, value: T) {
+        if hi - lo == 1 {
+            self.tree[node] = value;
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        self.push(node, lo, mid, hi);
+        if index < mid {
+            self.set_helper(2 * node, lo, mid, index, value);
+        } else {
+            self.set_helper(2 * node + 1, mid, hi, index, value);
+        }
+        self.tree[node] = (self.combine)(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    /// Apply `update` to every element in `range`.
+    /// Panics if the range is out of bounds.
+    pub fn update_range(&mut self, range: Range<usize>, update: U) {
+        check_range(&range, self.len);
+        if range.is_empty() {
+            return;
+        }
+        self.update_helper(1, 0, self.len, &range, &update);
+    }
+
+    fn update_helper(
+        &mut self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        range: &Range<usize>,
+        update: &U,
+    ) {
+        if range.end <= lo || hi <= range.start {
+            return;
+        }
+        if range.start <= lo && hi <= range.end {
+            self.apply_node(node, update, hi - lo);
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        self.push(node, lo, mid, hi);
+        self.update_helper(2 * node, lo, mid, range, update);
+        self.update_helper(2 * node + 1, mid, hi, range, update);
+        self.tree[node] = (self.combine)(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    /// Fold the elements in `range` with `combine`.
+    /// Returns `identity` for an empty range.
+    /// Panics if the range is out of bounds.
+    pub fn query(&mut self, range: Range<usize>) -> T {
+        check_range(&range, self.len);
+        if range.is_empty() {
+            return self.identity.clone();
+        }
+        self.query_helper(1, 0, self.len, &range)
+    }
+
+    fn query_helper(&mut self, node: usize, lo: usize, hi: usize, range: &Range<usize>) -> T {
+        if range.end <= lo || hi <= range.start {
+            return self.identity.clone();
+        }
+        if range.start <= lo && hi <= range.end {
+            return self.tree[node].clone();
+        }
+        let mid = lo + (hi - lo) / 2;
+        self.push(node, lo, mid, hi);
+        let left = self.query_helper(2 * node, lo, mid, range);
+        let right = self.query_helper(2 * node + 1, mid, hi, range);
+        (self.combine)(&left, &right)
+    }
+}
+
+fn check_range(range: &Range<usize>, len: usize) {
+    if range.start > range.end {
+        panic!(
+            "range start (is {start}) should be <= range end (is {end})",
+            start = range.start,
+            end = range.end
+        );
+    }
+    if range.end > len {
+        panic!(
+            "range end (is {end}) should be <= len (is {len})",
+            end = range.end
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        format,
+        string::{String, ToString},
+    };
+
+    use super::*;
+
+    #[test]
+    fn query_sum() {
+        let tree = SegmentTree::new(&[5, 3, 8, 6, 1, 4, 2], 0, |a, b| a + b);
+        assert_eq!(tree.query(0..7), 29);
+        assert_eq!(tree.query(2..5), 15);
+        assert_eq!(tree.query(3..3), 0);
+        assert_eq!(tree.get(6), Some(&2));
+        assert_eq!(tree.get(7), None);
+    }
+
+    #[test]
+    fn query_min_max() {
+        let data = [5, 3, 8, 6, 1, 4, 2];
+        let min = SegmentTree::new(&data, i32::MAX, |a, b| *a.min(b));
+        let max = SegmentTree::new(&data, i32::MIN, |a, b| *a.max(b));
+        assert_eq!(min.query(0..3), 3);
+        assert_eq!(min.query(2..7), 1);
+        assert_eq!(max.query(3..7), 6);
+    }
+
+    #[test]
+    fn update() {
+        let mut tree = SegmentTree::new(&[1, 2, 3, 4], 0, |a, b| a + b);
+        tree.update(0, 10);
+        tree.update(3, 0);
+        assert_eq!(tree.query(0..4), 15);
+        assert_eq!(tree.query(0..1), 10);
+    }
+
+    #[test]
+    fn non_commutative() {
+        let data: Vec<String> = "abcdefg".chars().map(String::from).collect();
+        let mut tree = SegmentTree::new(&data, String::new(), |a, b| format!("{a}{b}"));
+        assert_eq!(tree.query(0..7), "abcdefg");
+        assert_eq!(tree.query(1..6), "bcdef");
+        tree.update(3, "X".to_string());
+        assert_eq!(tree.query(2..5), "cXe");
+    }
+
+    #[test]
+    #[should_panic(expected = "range end (is 5) should be <= len (is 4)")]
+    fn query_out_of_bounds() {
+        let tree = SegmentTree::new(&[1, 2, 3, 4], 0, |a, b| a + b);
+        tree.query(0..5);
+    }
+
+    #[test]
+    fn lazy_range_add() {
+        let data: Vec<i64> = (1..=10).collect();
+        let mut tree = LazySegmentTree::new(
+            &data,
+            0,
+            |a, b| a + b,
+            |value, add, len| value + add * len as i64,
+            |old, new| old + new,
+        );
+        let mut naive = data.clone();
+        for (range, add) in [(0..5, 3), (3..10, -2), (4..6, 7), (9..10, 1)] {
+            for x in &mut naive[range.clone()] {
+                *x += add;
+            }
+            tree.update_range(range, add);
+        }
+        for l in 0..10 {
+            for r in l..=10 {
+                assert_eq!(tree.query(l..r), naive[l..r].iter().sum::<i64>());
+            }
+        }
+    }
+
+    #[test]
+    fn lazy_range_assign_min() {
+        let mut tree = LazySegmentTree::new(
+            &[4, 7, 1, 9, 3],
+            i32::MAX,
+            |a, b| *a.min(b),
+            |_, value, _| *value,
+            |_, new| *new,
+        );
+        tree.update_range(0..3, 5);
+        assert_eq!(tree.query(0..3), 5);
+        assert_eq!(tree.query(0..5), 3);
+        tree.update(4, 8);
+        assert_eq!(tree.query(2..5), 5);
+        tree.update_range(1..5, 2);
+        assert_eq!(tree.query(0..1), 5);
+        assert_eq!(tree.query(0..5), 2);
+    }
+}
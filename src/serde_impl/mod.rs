@@ -0,0 +1,117 @@
+//! Deserializers shared by the collections' `serde` impls, which build each
+//! collection from its elements with `FromIterator`.
+
+use core::{fmt, iter, marker::PhantomData};
+
+use serde::de::{Deserialize, Deserializer, Error, MapAccess, SeqAccess, Visitor};
+
+/// Deserialize a sequence of `T`s and collect it into `C`.
+pub(crate) fn deserialize_seq<'de, D, C, T>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<T>,
+    T: Deserialize<'de>,
+{
+    deserializer.deserialize_seq(SeqVisitor {
+        max: None,
+        marker: PhantomData,
+    })
+}
+
+/// Deserialize a sequence of at most `max` `T`s and collect it into `C`, for
+/// the fixed-capacity collections. A longer sequence is an invalid length
+/// error.
+pub(crate) fn deserialize_bounded_seq<'de, D, C, T>(
+    deserializer: D,
+    max: usize,
+) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<T>,
+    T: Deserialize<'de>,
+{
+    deserializer.deserialize_seq(SeqVisitor {
+        max: Some(max),
+        marker: PhantomData,
+    })
+}
+
+struct SeqVisitor<C, T> {
+    max: Option<usize>,
+    marker: PhantomData<(C, T)>,
+}
+
+impl<'de, C: FromIterator<T>, T: Deserialize<'de>> Visitor<'de> for SeqVisitor<C, T> {
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) => write!(f, "a sequence of at most {max} elements"),
+            None => f.write_str("a sequence"),
+        }
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<C, A::Error> {
+        // Stop at the first error, and report it instead of what was collected.
+        let mut error = None;
+        let mut len = 0;
+        let collection = iter::from_fn(|| {
+            let element = match seq.next_element() {
+                Ok(element) => element?,
+                Err(e) => {
+                    error = Some(e);
+                    return None;
+                }
+            };
+            if self.max == Some(len) {
+                error = Some(A::Error::invalid_length(len + 1, &self));
+                return None;
+            }
+            len += 1;
+            Some(element)
+        })
+        .collect();
+        error.map_or(Ok(collection), Err)
+    }
+}
+
+/// Deserialize a map from `K` to `V` and collect its entries into `C`. Later
+/// entries win over earlier ones with the same key, if `C` keeps one value per
+/// key.
+pub(crate) fn deserialize_map<'de, D, C, K, V>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<(K, V)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    struct MapVisitor<C, K, V>(PhantomData<(C, K, V)>);
+
+    impl<'de, C, K, V> Visitor<'de> for MapVisitor<C, K, V>
+    where
+        C: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = C;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a map")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<C, A::Error> {
+            let mut error = None;
+            let collection = iter::from_fn(|| match map.next_entry() {
+                Ok(entry) => entry,
+                Err(e) => {
+                    error = Some(e);
+                    None
+                }
+            })
+            .collect();
+            error.map_or(Ok(collection), Err)
+        }
+    }
+
+    deserializer.deserialize_map(MapVisitor(PhantomData))
+}
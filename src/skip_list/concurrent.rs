@@ -0,0 +1,628 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::hash::{BuildHasher, RandomState};
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, AtomicUsize, Ordering::*};
+use std::{fmt, thread};
+
+use super::MAX_LEVEL;
+use crate::boxed::MyBox;
+use crate::concurrent::epoch::{self, Guard};
+use crate::sync::{SpinLock, SpinLockGuard};
+
+/// Node of a [`ConcurrentSkipMap`]: the layout of [`Node`](super::Node), with atomic links
+/// and the flags the lazy algorithm needs.
+struct SyncNode<K, V> {
+    key: K,
+    // From `MyBox::into_raw`. An insert of a present key swaps it.
+    value: AtomicPtr<V>,
+    next: Box<[AtomicPtr<SyncNode<K, V>>]>,
+    // Held to mark the node, and to change the links out of it.
+    lock: SpinLock<()>,
+    // Set by the removal that unlinks the node, and never cleared.
+    marked: AtomicBool,
+    // Set once the node is linked on every level, which is when its insert
+    // takes effect.
+    linked: AtomicBool,
+}
+
+// A node, or the head when null.
+type SyncLink<K, V> = *mut SyncNode<K, V>;
+
+impl<K, V> SyncNode<K, V> {
+    fn is_live(&self) -> bool {
+        self.linked.load(Acquire) && !self.marked.load(Acquire)
+    }
+}
+
+impl<K, V> Drop for SyncNode<K, V> {
+    fn drop(&mut self) {
+        drop(unsafe { MyBox::from_raw(*self.value.get_mut()) });
+    }
+}
+
+/// An ordered map based on a skip list, shared between threads.
+///
+/// The ordered counterpart to [`ShardedMap`](crate::concurrent::ShardedMap),
+/// built as the "lazy" skip list of Herlihy, Lev, Luchangco and Shavit.
+/// Lookups and iteration follow the links without taking any lock. An insert
+/// or a removal locks only the nodes right before its entry on each level,
+/// checks they still link where the search found them, and relinks them, so
+/// operations on keys far apart never wait for each other.
+///
+/// Since readers don't lock, a removed node is only freed through [`epoch`],
+/// once no reader can still be on it. Methods handing out references take a
+/// pinned [`Guard`], and the references live as long as it does.
+///
+/// Iteration is weakly consistent: it sees every entry present for the whole
+/// iteration and none removed before it started, and may or may not see the
+/// entries inserted or removed meanwhile.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::concurrent::epoch;
+/// use my_std::skip_list::ConcurrentSkipMap;
+///
+/// let map = ConcurrentSkipMap::new();
+/// thread::scope(|s| {
+///     for t in 0..4 {
+///         let map = &map;
+///         s.spawn(move || {
+///             for i in 0..25 {
+///                 map.insert(i * 4 + t, t);
+///             }
+///         });
+///     }
+/// });
+/// assert_eq!(map.len(), 100);
+/// assert!(map.remove(&42));
+///
+/// let guard = epoch::pin();
+/// assert_eq!(map.get(&5, &guard), Some(&1));
+/// let keys: Vec<_> = map.range(40..45, &guard).map(|(k, _)| *k).collect();
+/// assert_eq!(keys, [40, 41, 43, 44]);
+/// ```
+pub struct ConcurrentSkipMap<K, V> {
+    head: [AtomicPtr<SyncNode<K, V>>; MAX_LEVEL],
+    // The head's counterpart of `SyncNode::lock`.
+    head_lock: SpinLock<()>,
+    len: AtomicUsize,
+    // State of the xorshift generator used for node heights.
+    seed: AtomicU64,
+    marker: PhantomData<MyBox<SyncNode<K, V>>>,
+}
+
+// SAFETY: entries are shared between the threads using the map, and dropped on
+// whichever thread frees their node.
+unsafe impl<K: Send, V: Send> Send for ConcurrentSkipMap<K, V> {}
+unsafe impl<K: Send + Sync, V: Send + Sync> Sync for ConcurrentSkipMap<K, V> {}
+
+impl<K, V> Default for ConcurrentSkipMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ConcurrentSkipMap<K, V> {
+    /// Create a new, empty map.
+    pub fn new() -> Self {
+        ConcurrentSkipMap {
+            head: [const { AtomicPtr::new(ptr::null_mut()) }; MAX_LEVEL],
+            head_lock: SpinLock::new(()),
+            len: AtomicUsize::new(0),
+            seed: AtomicU64::new(RandomState::new().hash_one(0u64) | 1),
+            marker: PhantomData,
+        }
+    }
+
+    /// Number of entries in the map. Other threads may change it right after.
+    pub fn len(&self) -> usize {
+        self.len.load(Relaxed)
+    }
+
+    /// Return `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the entries in ascending key order.
+    pub fn iter<'g>(&'g self, _guard: &'g Guard) -> ConcurrentIter<'g, K, V> {
+        ConcurrentIter {
+            next: self.head[0].load(Acquire),
+            marker: PhantomData,
+        }
+    }
+
+    fn next(&self, node: SyncLink<K, V>, level: usize) -> &AtomicPtr<SyncNode<K, V>> {
+        match unsafe { node.as_ref() } {
+            None => &self.head[level],
+            Some(node) => &node.next[level],
+        }
+    }
+
+    fn lock(&self, node: SyncLink<K, V>) -> SpinLockGuard<'_, ()> {
+        match unsafe { node.as_ref() } {
+            None => self.head_lock.lock(),
+            Some(node) => node.lock.lock(),
+        }
+    }
+
+    // Lock the distinct nodes in `preds` on levels `0..height`, lowest level
+    // first, and check that none was removed and each still links to
+    // `succ(level)`. Returns `None` if one doesn't, and the search must be
+    // redone.
+    //
+    // Every operation locks nodes in descending key order, so they can't
+    // deadlock.
+    fn lock_preds(
+        &self,
+        preds: &[SyncLink<K, V>; MAX_LEVEL],
+        height: usize,
+        succ: impl Fn(usize) -> SyncLink<K, V>,
+    ) -> Option<Vec<SpinLockGuard<'_, ()>>> {
+        let mut locks = Vec::with_capacity(height);
+        let mut last = None;
+        for (level, &pred) in preds.iter().enumerate().take(height) {
+            if last != Some(pred) {
+                locks.push(self.lock(pred));
+                last = Some(pred);
+            }
+            let removed = unsafe { pred.as_ref() }.is_some_and(|p| p.marked.load(Acquire));
+            if removed || self.next(pred, level).load(Acquire) != succ(level) {
+                return None;
+            }
+        }
+        Some(locks)
+    }
+
+    fn random_height(&self) -> usize {
+        // Threads racing here may draw the same height, which only costs a
+        // little balance.
+        let mut x = self.seed.load(Relaxed);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed.store(x, Relaxed);
+        (x.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+}
+
+impl<K: Ord, V> ConcurrentSkipMap<K, V> {
+    // Fill `preds` and `succs` with the nodes right before and from `key` on
+    // every level. Returns the highest level a node with `key` is on, if any.
+    // The caller must be pinned.
+    fn find<Q>(
+        &self,
+        key: &Q,
+        preds: &mut [SyncLink<K, V>; MAX_LEVEL],
+        succs: &mut [SyncLink<K, V>; MAX_LEVEL],
+    ) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut found = None;
+        let mut pred = ptr::null_mut();
+        for level in (0..MAX_LEVEL).rev() {
+            let mut cur = self.next(pred, level).load(Acquire);
+            while let Some(node) = unsafe { cur.as_ref() } {
+                match node.key.borrow().cmp(key) {
+                    Ordering::Less => {
+                        pred = cur;
+                        cur = node.next[level].load(Acquire);
+                    }
+                    Ordering::Equal => {
+                        if found.is_none() {
+                            found = Some(level);
+                        }
+                        break;
+                    }
+                    Ordering::Greater => break,
+                }
+            }
+            preds[level] = pred;
+            succs[level] = cur;
+        }
+        found
+    }
+
+    // The first node on the bottom level for which `before` doesn't hold. The
+    // caller must be pinned.
+    fn first_after(&self, before: impl Fn(&K) -> bool) -> SyncLink<K, V> {
+        let mut pred = ptr::null_mut();
+        for level in (0..MAX_LEVEL).rev() {
+            let mut cur = self.next(pred, level).load(Acquire);
+            while let Some(node) = unsafe { cur.as_ref() } {
+                if !before(&node.key) {
+                    break;
+                }
+                pred = cur;
+                cur = node.next[level].load(Acquire);
+            }
+        }
+        self.next(pred, 0).load(Acquire)
+    }
+
+    /// Get a reference to the value of a key, valid as long as `guard`.
+    pub fn get<'g, Q>(&'g self, key: &Q, _guard: &'g Guard) -> Option<&'g V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut preds = [ptr::null_mut(); MAX_LEVEL];
+        let mut succs = [ptr::null_mut(); MAX_LEVEL];
+        let level = self.find(key, &mut preds, &mut succs)?;
+        let node = unsafe { &*succs[level] };
+        node.is_live()
+            .then(|| unsafe { &*node.value.load(Acquire) })
+    }
+
+    /// Return `true` if the map contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key, &epoch::pin()).is_some()
+    }
+
+    /// Iterate over the entries whose keys fall in `range`, in ascending key
+    /// order. Panics if the start of the range is greater than its end.
+    pub fn range<'g, Q, R>(&'g self, range: R, _guard: &'g Guard) -> ConcurrentRange<'g, K, V, Q, R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+            (range.start_bound(), range.end_bound())
+        {
+            assert!(s <= e, "range start is greater than range end");
+        }
+        let next = match range.start_bound() {
+            Bound::Included(s) => self.first_after(|k| k.borrow() < s),
+            Bound::Excluded(s) => self.first_after(|k| k.borrow() <= s),
+            Bound::Unbounded => self.head[0].load(Acquire),
+        };
+        ConcurrentRange {
+            iter: ConcurrentIter {
+                next,
+                marker: PhantomData,
+            },
+            range,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K: Ord + Send + 'static, V: Send + 'static> ConcurrentSkipMap<K, V> {
+    /// Insert a key-value pair into the map, replacing the value if the key is
+    /// present. Returns `true` if it wasn't.
+    ///
+    /// A replaced value is dropped once no reader can still see it.
+    pub fn insert(&self, key: K, value: V) -> bool {
+        let guard = epoch::pin();
+        let value = MyBox::into_raw(MyBox::new(value));
+        let mut preds = [ptr::null_mut(); MAX_LEVEL];
+        let mut succs = [ptr::null_mut(); MAX_LEVEL];
+        let height = self.random_height();
+        loop {
+            if let Some(level) = self.find(&key, &mut preds, &mut succs) {
+                let node = unsafe { &*succs[level] };
+                if !node.marked.load(Acquire) {
+                    // Let the insert that added it finish first.
+                    while !node.linked.load(Acquire) {
+                        thread::yield_now();
+                    }
+                    let _lock = node.lock.lock();
+                    if !node.marked.load(Relaxed) {
+                        let old = node.value.swap(value, AcqRel);
+                        unsafe { guard.defer_destroy(old) };
+                        return false;
+                    }
+                }
+                // It is being removed: wait for it to be unlinked.
+                thread::yield_now();
+                continue;
+            }
+            let Some(_locks) = self.lock_preds(&preds, height, |level| succs[level]) else {
+                thread::yield_now();
+                continue;
+            };
+            let node = MyBox::into_raw(MyBox::new(SyncNode {
+                key,
+                value: AtomicPtr::new(value),
+                next: succs[..height].iter().map(|&s| AtomicPtr::new(s)).collect(),
+                lock: SpinLock::new(()),
+                marked: AtomicBool::new(false),
+                linked: AtomicBool::new(false),
+            }));
+            for (level, &pred) in preds.iter().enumerate().take(height) {
+                self.next(pred, level).store(node, Release);
+            }
+            // Counted before it can be removed, so `len` never wraps around.
+            self.len.fetch_add(1, Relaxed);
+            unsafe { (*node).linked.store(true, Release) };
+            return true;
+        }
+    }
+
+    /// Remove a key from the map. Returns `true` if it was present.
+    ///
+    /// The entry is dropped once no reader can still see it.
+    pub fn remove<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let guard = epoch::pin();
+        let mut preds = [ptr::null_mut(); MAX_LEVEL];
+        let mut succs = [ptr::null_mut(); MAX_LEVEL];
+        let mut victim = None;
+        loop {
+            let found = self.find(key, &mut preds, &mut succs);
+            let (node, _) = match victim {
+                Some(ref victim) => victim,
+                None => {
+                    let Some(level) = found else { return false };
+                    let node = unsafe { &*succs[level] };
+                    // A node not linked on all its levels yet isn't in the map
+                    // yet, and a marked one is another removal's.
+                    if !node.is_live() || node.next.len() != level + 1 {
+                        return false;
+                    }
+                    let lock = node.lock.lock();
+                    if node.marked.load(Relaxed) {
+                        return false;
+                    }
+                    // The removal takes effect here: from now on, readers skip
+                    // the node and inserts wait for it to be unlinked.
+                    node.marked.store(true, Release);
+                    self.len.fetch_sub(1, Relaxed);
+                    &*victim.insert((node, lock))
+                }
+            };
+            let target = ptr::from_ref(*node).cast_mut();
+            let height = node.next.len();
+            let Some(locks) = self.lock_preds(&preds, height, |_| target) else {
+                thread::yield_now();
+                continue;
+            };
+            for level in (0..height).rev() {
+                let next = node.next[level].load(Acquire);
+                self.next(preds[level], level).store(next, Release);
+            }
+            drop(locks);
+            drop(victim);
+            unsafe { guard.defer_destroy(target) };
+            return true;
+        }
+    }
+}
+
+impl<K, V> Drop for ConcurrentSkipMap<K, V> {
+    fn drop(&mut self) {
+        let mut next = *self.head[0].get_mut();
+        while !next.is_null() {
+            let mut node = unsafe { MyBox::from_raw(next) };
+            next = *node.next[0].get_mut();
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for ConcurrentSkipMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter(&epoch::pin())).finish()
+    }
+}
+
+impl<K: Ord + Send + 'static, V: Send + 'static> Extend<(K, V)> for ConcurrentSkipMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord + Send + 'static, V: Send + 'static> FromIterator<(K, V)> for ConcurrentSkipMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = ConcurrentSkipMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+// The first node in the map from `node` on, following the bottom level.
+unsafe fn first_live<'g, K, V>(mut node: SyncLink<K, V>) -> Option<&'g SyncNode<K, V>> {
+    while let Some(n) = node.as_ref() {
+        if n.is_live() {
+            return Some(n);
+        }
+        node = n.next[0].load(Acquire);
+    }
+    None
+}
+
+/// Iterator over the entries of a [`ConcurrentSkipMap`] in ascending key order.
+pub struct ConcurrentIter<'g, K, V> {
+    next: SyncLink<K, V>,
+    marker: PhantomData<(&'g SyncNode<K, V>, &'g Guard)>,
+}
+
+impl<'g, K, V> ConcurrentIter<'g, K, V> {
+    fn next_node(&mut self) -> Option<&'g SyncNode<K, V>> {
+        let node = unsafe { first_live(self.next)? };
+        self.next = node.next[0].load(Acquire);
+        Some(node)
+    }
+}
+
+impl<'g, K, V> Iterator for ConcurrentIter<'g, K, V> {
+    type Item = (&'g K, &'g V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next_node()?;
+        Some((&node.key, unsafe { &*node.value.load(Acquire) }))
+    }
+}
+
+/// Iterator over a range of entries of a [`ConcurrentSkipMap`] in ascending
+/// key order.
+pub struct ConcurrentRange<'g, K, V, Q: ?Sized, R> {
+    iter: ConcurrentIter<'g, K, V>,
+    range: R,
+    marker: PhantomData<fn(&Q)>,
+}
+
+impl<'g, K, V, Q, R> Iterator for ConcurrentRange<'g, K, V, Q, R>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
+{
+    type Item = (&'g K, &'g V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.iter.next_node()?;
+        let key = node.key.borrow();
+        let within = match self.range.end_bound() {
+            Bound::Included(e) => key <= e,
+            Bound::Excluded(e) => key < e,
+            Bound::Unbounded => true,
+        };
+        if !within {
+            self.iter.next = ptr::null_mut();
+            return None;
+        }
+        Some((&node.key, unsafe { &*node.value.load(Acquire) }))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for ConcurrentSkipMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord + Send + 'static,
+    V: arbitrary::Arbitrary<'a> + Send + 'static,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for ConcurrentSkipMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Ord + Send,
+    V: proptest::arbitrary::Arbitrary + 'static + Send,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &(K, V), b: &(K, V)| a.0.cmp(&b.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use std::vec::Vec;
+
+    #[test]
+    fn concurrent_matches_btree_map() {
+        let map = ConcurrentSkipMap::new();
+        let mut model = BTreeMap::new();
+        let mut x = 0x2545_f491_u64;
+        for _ in 0..5000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let key = x % 500;
+            let guard = epoch::pin();
+            match x % 4 {
+                0 | 1 => assert_eq!(map.insert(key, x), model.insert(key, x).is_none()),
+                2 => assert_eq!(map.remove(&key), model.remove(&key).is_some()),
+                _ => assert_eq!(map.get(&key, &guard), model.get(&key)),
+            }
+            assert_eq!(map.len(), model.len());
+        }
+        let guard = epoch::pin();
+        assert!(map.iter(&guard).eq(model.iter()));
+        for (a, b) in [(0, 500), (10, 10), (100, 250), (499, 600)] {
+            assert!(map.range(a..b, &guard).eq(model.range(a..b)));
+            assert!(map.range(a..=b, &guard).eq(model.range(a..=b)));
+            assert!(map
+                .range((Bound::Excluded(a), Bound::Unbounded), &guard)
+                .eq(model.range((Bound::Excluded(a), Bound::Unbounded))));
+        }
+    }
+
+    #[test]
+    fn concurrent_writers_and_readers() {
+        const THREADS: usize = 4;
+        const KEYS: usize = 2_000;
+        let map = ConcurrentSkipMap::new();
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let map = &map;
+                s.spawn(move || {
+                    for i in (t..KEYS).step_by(THREADS) {
+                        assert!(map.insert(i, i));
+                    }
+                    // Every thread tries to remove every odd key; only one
+                    // succeeds for each.
+                    (1..KEYS).step_by(2).filter(|i| map.remove(i)).count()
+                });
+            }
+            s.spawn(|| {
+                for _ in 0..100 {
+                    let guard = epoch::pin();
+                    let keys: Vec<_> = map.range(100..1_000, &guard).map(|(k, _)| *k).collect();
+                    assert!(keys.windows(2).all(|w| w[0] < w[1]));
+                    assert!(keys.iter().all(|k| (100..1_000).contains(k)));
+                }
+            });
+        });
+        assert_eq!(map.len(), KEYS / 2);
+        let guard = epoch::pin();
+        assert!(map.iter(&guard).map(|(k, _)| *k).eq((0..KEYS).step_by(2)));
+        assert!(map.iter(&guard).all(|(k, v)| k == v));
+    }
+
+    #[test]
+    fn concurrent_map_frees_every_entry() {
+        let counter = Arc::new(());
+        let map = ConcurrentSkipMap::new();
+        for i in 0..100 {
+            map.insert(i, Arc::clone(&counter));
+        }
+        for i in 0..10 {
+            map.remove(&i);
+            map.insert(i + 50, Arc::clone(&counter));
+        }
+        drop(map);
+        // Removed and replaced entries are freed once the epoch moves on,
+        // which other tests may hold back for a moment.
+        for _ in 0..1_000 {
+            epoch::pin().collect();
+            if Arc::strong_count(&counter) == 1 {
+                return;
+            }
+            thread::yield_now();
+        }
+        panic!("entries never freed");
+    }
+}
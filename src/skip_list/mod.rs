@@ -0,0 +1,1053 @@
+use alloc::boxed::Box;
+use core::borrow::Borrow;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::{Bound, RangeBounds};
+use core::ptr::NonNull;
+use core::{fmt, mem};
+
+use crate::compat;
+
+#[cfg(feature = "std")]
+mod concurrent;
+
+#[cfg(feature = "std")]
+pub use concurrent::{ConcurrentIter, ConcurrentRange, ConcurrentSkipMap};
+
+// Enough levels for far more entries than fit in memory with a 1/2 promotion rate.
+const MAX_LEVEL: usize = 32;
+
+type Link<K, V> = Option<NonNull<Node<K, V>>>;
+
+/// Node of a skip list.
+///
+/// A node is an entry and its tower of forward links, one per level it takes part
+/// in, plus a backward link on the bottom level for reverse iteration. The
+/// layout has nothing specific to single-threaded use, so a concurrent list can
+/// reuse it with atomic links in place of `Link`.
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // The previous node on level 0, `None` for the first one.
+    prev: Link<K, V>,
+    next: Box<[Link<K, V>]>,
+}
+
+/// An ordered map based on a skip list.
+///
+/// Entries are kept in a sorted linked list, and every entry is also promoted to
+/// higher "express" lists with probability 1/2 per level. Searches start on the
+/// highest list and drop down a level whenever the next entry would overshoot,
+/// which gives O(log n) expected time for lookups, inserts and removals without
+/// any rebalancing.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::skip_list::SkipMap;
+///
+/// let mut map = SkipMap::new();
+/// map.insert(3, "c");
+/// map.insert(1, "a");
+/// map.insert(2, "b");
+/// assert_eq!(map.get(&2), Some(&"b"));
+///
+/// let keys: Vec<_> = map.range(2..).map(|(k, _)| *k).collect();
+/// assert_eq!(keys, vec![2, 3]);
+/// assert_eq!(map.pop_first(), Some((1, "a")));
+/// ```
+pub struct SkipMap<K, V> {
+    head: [Link<K, V>; MAX_LEVEL],
+    // The last node on level 0.
+    tail: Link<K, V>,
+    // Number of levels in use.
+    level: usize,
+    len: usize,
+    // State of the xorshift generator used for node heights.
+    seed: u64,
+    marker: PhantomData<Box<Node<K, V>>>,
+}
+
+// SAFETY: the map owns its nodes like a `Box` would.
+unsafe impl<K: Send, V: Send> Send for SkipMap<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for SkipMap<K, V> {}
+
+impl<K, V> Default for SkipMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> SkipMap<K, V> {
+    /// Create a new, empty map.
+    pub fn new() -> Self {
+        SkipMap {
+            head: [None; MAX_LEVEL],
+            tail: None,
+            level: 0,
+            len: 0,
+            // See `Treap::new` for the choice of seed.
+            seed: compat::random_seed() | 1,
+            marker: PhantomData,
+        }
+    }
+
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear(&mut self) {
+        while self.pop_first().is_some() {}
+    }
+
+    /// Iterate over the entries in ascending key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            next: self.head[0],
+            back: self.tail,
+            remaining: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Iterate over the entries in ascending key order, with mutable
+    /// references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            next: self.head[0],
+            back: self.tail,
+            remaining: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Iterate over the keys in ascending order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over the values in ascending key order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Get the entry with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    /// Get the entry with the largest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let node = unsafe { self.last()?.as_ref() };
+        Some((&node.key, &node.value))
+    }
+
+    /// Remove and return the entry with the smallest key. O(1) expected.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let first = self.head[0]?;
+        // The first node is preceded by the head on every level it takes part in.
+        Some(self.unlink(&[None; MAX_LEVEL], first))
+    }
+
+    /// Remove and return the entry with the largest key. O(log n) expected.
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let last = self.last()?;
+        let preds = self.descend(|node| node != last);
+        Some(self.unlink(&preds, last))
+    }
+
+    /// Keep only the entries for which `f` returns `true`, visiting them in
+    /// ascending key order.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.extract_if(|k, v| !f(k, v)).for_each(drop);
+    }
+
+    /// Return an iterator that removes and yields, in ascending key order,
+    /// every entry for which `filter` returns `true`.
+    ///
+    /// Each entry is unlinked as it is yielded, so the entries the iterator
+    /// didn't get to are kept, whether it is dropped or leaked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::skip_list::SkipMap;
+    ///
+    /// let mut map: SkipMap<_, _> = (0..10).map(|i| (i, i * i)).collect();
+    /// let odd: Vec<_> = map.extract_if(|_, v| *v % 2 == 1).collect();
+    /// assert_eq!(odd, [(1, 1), (3, 9), (5, 25), (7, 49), (9, 81)]);
+    /// assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 2, 4, 6, 8]);
+    /// ```
+    pub fn extract_if<F>(&mut self, filter: F) -> ExtractIf<'_, K, V, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf {
+            map: self,
+            preds: [None; MAX_LEVEL],
+            filter,
+        }
+    }
+
+    // Walk level 0 from the node after `preds[0]`, unlinking and returning the
+    // first entry `filter` matches. `preds` holds the last node kept on every
+    // level, so the walk can be resumed with it.
+    fn extract_next(
+        &mut self,
+        preds: &mut [Link<K, V>; MAX_LEVEL],
+        filter: &mut impl FnMut(&K, &mut V) -> bool,
+    ) -> Option<(K, V)> {
+        let mut cur = self.next(preds[0], 0);
+        while let Some(mut node) = cur {
+            let n = unsafe { node.as_mut() };
+            if filter(&n.key, &mut n.value) {
+                return Some(self.unlink(preds, node));
+            }
+            for pred in &mut preds[..n.next.len()] {
+                *pred = Some(node);
+            }
+            cur = n.next[0];
+        }
+        None
+    }
+
+    // The node following `node` on `level`, where `None` stands for the head.
+    fn next(&self, node: Link<K, V>, level: usize) -> Link<K, V> {
+        match node {
+            None => self.head[level],
+            Some(node) => unsafe { node.as_ref().next[level] },
+        }
+    }
+
+    fn set_next(&mut self, node: Link<K, V>, level: usize, to: Link<K, V>) {
+        match node {
+            None => self.head[level] = to,
+            Some(mut node) => unsafe { node.as_mut().next[level] = to },
+        }
+    }
+
+    // Set the node preceding `node` on level 0, where `None` stands for the tail.
+    fn set_prev(&mut self, node: Link<K, V>, to: Link<K, V>) {
+        match node {
+            None => self.tail = to,
+            Some(mut node) => unsafe { node.as_mut().prev = to },
+        }
+    }
+
+    // Walk down from the top level, moving right while `before` holds for the next
+    // node. Returns the last node visited on every level.
+    fn descend(&self, before: impl Fn(NonNull<Node<K, V>>) -> bool) -> [Link<K, V>; MAX_LEVEL] {
+        let mut preds = [None; MAX_LEVEL];
+        let mut cur = None;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.next(cur, level) {
+                if !before(next) {
+                    break;
+                }
+                cur = Some(next);
+            }
+            preds[level] = cur;
+        }
+        preds
+    }
+
+    fn last(&self) -> Link<K, V> {
+        self.tail
+    }
+
+    // Unlink `node`, given the node preceding it on each of its levels, and free it.
+    fn unlink(&mut self, preds: &[Link<K, V>; MAX_LEVEL], node: NonNull<Node<K, V>>) -> (K, V) {
+        let node = unsafe { Box::from_raw(node.as_ptr()) };
+        for (level, &next) in node.next.iter().enumerate() {
+            self.set_next(preds[level], level, next);
+        }
+        self.set_prev(node.next[0], node.prev);
+        while self.level > 0 && self.head[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+        (node.key, node.value)
+    }
+
+    fn random_height(&mut self) -> usize {
+        let mut x = self.seed;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed = x;
+        // Each trailing one bit promotes the node one level higher.
+        (x.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+}
+
+impl<K, V> SkipMap<K, V>
+where
+    K: Ord,
+{
+    // The first node whose key is not less than `key`, and its predecessors.
+    fn search<Q>(&self, key: &Q) -> ([Link<K, V>; MAX_LEVEL], Link<K, V>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let preds = self.descend(|node| unsafe { node.as_ref() }.key.borrow() < key);
+        let found = self
+            .next(preds[0], 0)
+            .filter(|node| unsafe { node.as_ref() }.key.borrow() == key);
+        (preds, found)
+    }
+
+    /// Insert a key-value pair into the map.
+    /// Returns the old value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (preds, found) = self.search(&key);
+        if let Some(mut node) = found {
+            return Some(mem::replace(unsafe { &mut node.as_mut().value }, value));
+        }
+        let height = self.random_height();
+        // Levels above the current top are preceded by the head, which `preds`
+        // already says with `None`.
+        self.level = self.level.max(height);
+        let node = Box::new(Node {
+            key,
+            value,
+            prev: preds[0],
+            next: (0..height)
+                .map(|level| self.next(preds[level], level))
+                .collect(),
+        });
+        let node = NonNull::from(Box::leak(node));
+        for (level, &pred) in preds.iter().enumerate().take(height) {
+            self.set_next(pred, level, Some(node));
+        }
+        self.set_prev(unsafe { node.as_ref() }.next[0], Some(node));
+        self.len += 1;
+        None
+    }
+
+    /// Get a reference to the value of a key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (_, found) = self.search(key);
+        found.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// Get a mutable reference to the value of a key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (_, found) = self.search(key);
+        found.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// Return `true` if the map contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).1.is_some()
+    }
+
+    /// Remove a key from the map, returning its value if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (preds, found) = self.search(key);
+        Some(self.unlink(&preds, found?).1)
+    }
+
+    /// Iterate over the entries whose keys fall in `range`, in ascending key order.
+    /// Panics if the start of the range is greater than its end.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+            (start, end)
+        {
+            assert!(s <= e, "range start is greater than range end");
+            if s == e && !matches!((start, end), (Bound::Included(_), Bound::Included(_))) {
+                return Range {
+                    next: None,
+                    back: None,
+                    marker: PhantomData,
+                };
+            }
+        }
+        // Both ends are found as the first node past a bound.
+        let first_after = |before: &dyn Fn(&Q) -> bool| {
+            let preds = self.descend(|node| before(unsafe { node.as_ref() }.key.borrow()));
+            self.next(preds[0], 0)
+        };
+        let next = match start {
+            Bound::Included(s) => first_after(&|k| k < s),
+            Bound::Excluded(s) => first_after(&|k| k <= s),
+            Bound::Unbounded => self.head[0],
+        };
+        let end = match end {
+            Bound::Included(e) => first_after(&|k| k <= e),
+            Bound::Excluded(e) => first_after(&|k| k < e),
+            Bound::Unbounded => None,
+        };
+        if next == end {
+            return Range {
+                next: None,
+                back: None,
+                marker: PhantomData,
+            };
+        }
+        // `next` comes before `end`, so the range holds at least one node.
+        let back = match end {
+            Some(end) => unsafe { end.as_ref() }.prev,
+            None => self.tail,
+        };
+        Range {
+            next,
+            back,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> Drop for SkipMap<K, V> {
+    fn drop(&mut self) {
+        let mut next = self.head[0];
+        while let Some(node) = next {
+            let node = unsafe { Box::from_raw(node.as_ptr()) };
+            next = node.next[0];
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SkipMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = SkipMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+/// Iterator over the entries of a [`SkipMap`] in ascending key order.
+pub struct Iter<'a, K, V> {
+    next: Link<K, V>,
+    back: Link<K, V>,
+    remaining: usize,
+    marker: PhantomData<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = unsafe { self.next?.as_ref() };
+        self.next = node.next[0];
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = unsafe { self.back?.as_ref() };
+        self.back = node.prev;
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a SkipMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Mutable iterator over the entries of a [`SkipMap`] in ascending key order.
+pub struct IterMut<'a, K, V> {
+    next: Link<K, V>,
+    back: Link<K, V>,
+    remaining: usize,
+    marker: PhantomData<&'a mut Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = unsafe { self.next?.as_mut() };
+        self.next = node.next[0];
+        self.remaining -= 1;
+        Some((&node.key, &mut node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = unsafe { self.back?.as_mut() };
+        self.back = node.prev;
+        self.remaining -= 1;
+        Some((&node.key, &mut node.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a mut SkipMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator removing the entries of a [`SkipMap`] that match a filter,
+/// returned by [`SkipMap::extract_if`].
+pub struct ExtractIf<'a, K, V, F> {
+    map: &'a mut SkipMap<K, V>,
+    preds: [Link<K, V>; MAX_LEVEL],
+    filter: F,
+}
+
+impl<K, V, F> Iterator for ExtractIf<'_, K, V, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.map.extract_next(&mut self.preds, &mut self.filter)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.map.len))
+    }
+}
+
+/// Iterator over a range of entries of a [`SkipMap`] in ascending key order.
+pub struct Range<'a, K, V> {
+    // The first and last nodes left in the range, both `None` once it is used up.
+    next: Link<K, V>,
+    back: Link<K, V>,
+    marker: PhantomData<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = unsafe { self.next?.as_ref() };
+        if self.next == self.back {
+            (self.next, self.back) = (None, None);
+        } else {
+            self.next = node.next[0];
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = unsafe { self.back?.as_ref() };
+        if self.next == self.back {
+            (self.next, self.back) = (None, None);
+        } else {
+            self.back = node.prev;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> FusedIterator for Range<'_, K, V> {}
+
+/// An ordered set based on a [`SkipMap`].
+///
+/// # Examples
+///
+/// ```
+/// use my_std::skip_list::SkipSet;
+///
+/// let mut set: SkipSet<_> = [5, 1, 4, 2, 3].into_iter().collect();
+/// assert!(set.contains(&4));
+/// assert!(!set.insert(4));
+///
+/// let middle: Vec<_> = set.range(2..=4).copied().collect();
+/// assert_eq!(middle, vec![2, 3, 4]);
+/// assert_eq!(set.pop_first(), Some(1));
+/// assert_eq!(set.pop_last(), Some(5));
+/// assert_eq!(set.len(), 3);
+/// ```
+pub struct SkipSet<T> {
+    map: SkipMap<T, ()>,
+}
+
+impl<T> Default for SkipSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SkipSet<T> {
+    /// Create a new, empty set.
+    pub fn new() -> Self {
+        SkipSet {
+            map: SkipMap::new(),
+        }
+    }
+
+    /// Number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Remove all elements from the set.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Iterate over the elements in ascending order.
+    pub fn iter(&self) -> SetIter<'_, T> {
+        SetIter {
+            inner: self.map.iter(),
+        }
+    }
+
+    /// The smallest element.
+    pub fn first(&self) -> Option<&T> {
+        self.map.first_key_value().map(|(k, _)| k)
+    }
+
+    /// The largest element.
+    pub fn last(&self) -> Option<&T> {
+        self.map.last_key_value().map(|(k, _)| k)
+    }
+
+    /// Remove and return the smallest element.
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.map.pop_first().map(|(k, _)| k)
+    }
+
+    /// Remove and return the largest element.
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.map.pop_last().map(|(k, _)| k)
+    }
+
+    /// Keep only the elements for which `f` returns `true`, visiting them in
+    /// ascending order.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.map.retain(|k, _| f(k));
+    }
+
+    /// Return an iterator that removes and yields, in ascending order, every
+    /// element for which `filter` returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::skip_list::SkipSet;
+    ///
+    /// let mut set: SkipSet<_> = (1..=6).collect();
+    /// let even: Vec<_> = set.extract_if(|x| x % 2 == 0).collect();
+    /// assert_eq!(even, [2, 4, 6]);
+    /// assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 3, 5]);
+    /// ```
+    pub fn extract_if<F>(&mut self, filter: F) -> SetExtractIf<'_, T, F>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        SetExtractIf {
+            map: &mut self.map,
+            preds: [None; MAX_LEVEL],
+            filter,
+        }
+    }
+}
+
+impl<T: Ord> SkipSet<T> {
+    /// Add an element. Returns `false` if it was already present.
+    pub fn insert(&mut self, value: T) -> bool {
+        self.map.insert(value, ()).is_none()
+    }
+
+    /// Return `true` if the set contains the element.
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Remove an element. Returns `true` if it was present.
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.remove(value).is_some()
+    }
+
+    /// Iterate over the elements that fall in `range`, in ascending order.
+    /// Panics if the start of the range is greater than its end.
+    pub fn range<Q, R>(&self, range: R) -> impl DoubleEndedIterator<Item = &T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        self.map.range(range).map(|(k, _)| k)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SkipSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord> Extend<T> for SkipSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SkipSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = SkipSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+/// An iterator removing the elements of a [`SkipSet`] that match a filter,
+/// returned by [`SkipSet::extract_if`].
+pub struct SetExtractIf<'a, T, F> {
+    map: &'a mut SkipMap<T, ()>,
+    preds: [Link<T, ()>; MAX_LEVEL],
+    filter: F,
+}
+
+impl<T, F> Iterator for SetExtractIf<'_, T, F>
+where
+    F: FnMut(&T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let filter = &mut self.filter;
+        let (value, ()) = self
+            .map
+            .extract_next(&mut self.preds, &mut |k, _| filter(k))?;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.map.len))
+    }
+}
+
+/// Iterator over the elements of a [`SkipSet`] in ascending order.
+pub struct SetIter<'a, T> {
+    inner: Iter<'a, T, ()>,
+}
+
+impl<'a, T> Iterator for SetIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for SetIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<T> ExactSizeIterator for SetIter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a SkipSet<T> {
+    type Item = &'a T;
+    type IntoIter = SetIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for SkipMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for SkipMap<K, V>
+where
+    K: serde::Deserialize<'de> + Ord,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for SkipSet<T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for SkipSet<T>
+where
+    T: serde::Deserialize<'de> + Ord,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for SkipMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for SkipSet<T>
+where
+    T: arbitrary::Arbitrary<'a> + Ord,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for SkipMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Ord,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &(K, V), b: &(K, V)| a.0.cmp(&b.0))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for SkipSet<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static + Ord,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, Ord::cmp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        format,
+        string::{String, ToString},
+    };
+
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+
+    #[test]
+    fn matches_btree_map() {
+        let mut map = SkipMap::new();
+        let mut model = BTreeMap::new();
+        let mut x = 0x2545_f491_u64;
+        for _ in 0..5000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let key = x % 500;
+            match x % 5 {
+                0 | 1 => assert_eq!(map.insert(key, x), model.insert(key, x)),
+                2 => assert_eq!(map.remove(&key), model.remove(&key)),
+                3 => assert_eq!(map.pop_last(), model.pop_last()),
+                _ => assert_eq!(map.get(&key), model.get(&key)),
+            }
+            assert_eq!(map.len(), model.len());
+        }
+        assert!(map.iter().eq(model.iter()));
+        assert!(map.iter().rev().eq(model.iter().rev()));
+        assert!(map.iter_mut().rev().eq(model.iter_mut().rev()));
+        assert_eq!(map.first_key_value(), model.first_key_value());
+        assert_eq!(map.last_key_value(), model.last_key_value());
+        for (a, b) in [(0, 500), (10, 10), (100, 250), (499, 600)] {
+            assert!(map.range(a..b).eq(model.range(a..b)));
+            assert!(map.range(a..=b).eq(model.range(a..=b)));
+            assert!(map.range(a..=b).rev().eq(model.range(a..=b).rev()));
+            assert!(map
+                .range((Bound::Excluded(a), Bound::Unbounded))
+                .eq(model.range((Bound::Excluded(a), Bound::Unbounded))));
+        }
+    }
+
+    #[test]
+    fn set() {
+        let mut set = SkipSet::new();
+        for word in ["pear", "apple", "fig", "apple"] {
+            set.insert(word.to_string());
+        }
+        assert_eq!(set.len(), 3);
+        assert!(set.contains("fig"));
+        assert_eq!(set.first().map(String::as_str), Some("apple"));
+        assert_eq!(set.last().map(String::as_str), Some("pear"));
+        assert!(set.remove("fig"));
+        assert!(!set.remove("fig"));
+        assert_eq!(format!("{set:?}"), r#"{"apple", "pear"}"#);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn inverted_range() {
+        let map: SkipMap<i32, ()> = SkipMap::new();
+        let (start, end) = (5, 1);
+        map.range(start..end);
+    }
+
+    #[test]
+    fn drops_every_entry() {
+        let counter = Rc::new(());
+        let mut map = SkipMap::new();
+        for i in 0..100 {
+            map.insert(i, Rc::clone(&counter));
+        }
+        map.remove(&50);
+        map.pop_first();
+        map.pop_last();
+        assert_eq!(Rc::strong_count(&counter), 98);
+        drop(map);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: SkipMap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"1":10,"2":20}"#);
+        let back: SkipMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert!(back.iter().eq(map.iter()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn set_serde_round_trip() {
+        let values: SkipSet<i32> = [3, 1, 2].into_iter().collect();
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let back: SkipSet<i32> = serde_json::from_str(&json).unwrap();
+        assert!(back.iter().eq(values.iter()));
+    }
+}
@@ -0,0 +1,275 @@
+use alloc::collections::{vec_deque, VecDeque};
+use core::fmt;
+
+/// A FIFO queue that knows its minimum and maximum at all times.
+///
+/// Besides the elements themselves, the queue keeps two deques of positions: the
+/// elements that could still become the minimum once everything older has been
+/// popped, in increasing order, and the same for the maximum. A new element
+/// evicts every candidate it beats, so each position is added and removed at
+/// most once and all operations are amortized O(1).
+///
+/// This is the standard tool for min or max over a sliding window: push each new
+/// element and pop the oldest once the window is full.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::sliding_window::MonotonicQueue;
+///
+/// // Maximum of every window of 3 readings.
+/// let readings = [1, 3, -1, -3, 5, 3, 6, 7];
+/// let mut window = MonotonicQueue::new();
+/// let mut maxima = Vec::new();
+/// for reading in readings {
+///     window.push(reading);
+///     if window.len() > 3 {
+///         window.pop_front();
+///     }
+///     if window.len() == 3 {
+///         maxima.push(*window.max().unwrap());
+///     }
+/// }
+/// assert_eq!(maxima, vec![3, 3, 5, 5, 6, 7]);
+/// ```
+#[derive(Clone)]
+pub struct MonotonicQueue<T> {
+    values: VecDeque<T>,
+    // Number of elements popped so far, so `values[pos - popped]` is the element
+    // pushed at position `pos`.
+    popped: usize,
+    // Positions of the minimum candidates, with increasing values.
+    mins: VecDeque<usize>,
+    // Positions of the maximum candidates, with decreasing values.
+    maxs: VecDeque<usize>,
+}
+
+impl<T> Default for MonotonicQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MonotonicQueue<T> {
+    /// Create a new, empty queue.
+    pub const fn new() -> Self {
+        MonotonicQueue {
+            values: VecDeque::new(),
+            popped: 0,
+            mins: VecDeque::new(),
+            maxs: VecDeque::new(),
+        }
+    }
+
+    /// Number of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Return `true` if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Remove every element.
+    pub fn clear(&mut self) {
+        self.popped += self.values.len();
+        self.values.clear();
+        self.mins.clear();
+        self.maxs.clear();
+    }
+
+    /// The oldest element.
+    pub fn front(&self) -> Option<&T> {
+        self.values.front()
+    }
+
+    /// The newest element.
+    pub fn back(&self) -> Option<&T> {
+        self.values.back()
+    }
+
+    /// Remove and return the oldest element.
+    pub fn pop_front(&mut self) -> Option<T> {
+        let value = self.values.pop_front()?;
+        // The oldest element can only be a candidate at the front of each deque.
+        if self.mins.front() == Some(&self.popped) {
+            self.mins.pop_front();
+        }
+        if self.maxs.front() == Some(&self.popped) {
+            self.maxs.pop_front();
+        }
+        self.popped += 1;
+        Some(value)
+    }
+
+    /// Iterate over the elements from oldest to newest.
+    pub fn iter(&self) -> vec_deque::Iter<'_, T> {
+        self.values.iter()
+    }
+
+    fn at(&self, pos: usize) -> &T {
+        &self.values[pos - self.popped]
+    }
+}
+
+impl<T: Ord> MonotonicQueue<T> {
+    /// Add an element at the back. Amortized O(1).
+    pub fn push(&mut self, value: T) {
+        let pos = self.popped + self.values.len();
+        // Older elements that are not smaller than the new one can never be the
+        // minimum again, and likewise for the maximum. On ties the newest wins,
+        // as it stays in the queue the longest.
+        while self.mins.back().is_some_and(|&p| *self.at(p) >= value) {
+            self.mins.pop_back();
+        }
+        while self.maxs.back().is_some_and(|&p| *self.at(p) <= value) {
+            self.maxs.pop_back();
+        }
+        self.mins.push_back(pos);
+        self.maxs.push_back(pos);
+        self.values.push_back(value);
+    }
+
+    /// The smallest element. O(1).
+    pub fn min(&self) -> Option<&T> {
+        self.mins.front().map(|&pos| self.at(pos))
+    }
+
+    /// The largest element. O(1).
+    pub fn max(&self) -> Option<&T> {
+        self.maxs.front().map(|&pos| self.at(pos))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MonotonicQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord> Extend<T> for MonotonicQueue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MonotonicQueue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = MonotonicQueue::new();
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<'a, T> IntoIterator for &'a MonotonicQueue<T> {
+    type Item = &'a T;
+    type IntoIter = vec_deque::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for MonotonicQueue<T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for MonotonicQueue<T>
+where
+    T: serde::Deserialize<'de> + Ord,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for MonotonicQueue<T>
+where
+    T: arbitrary::Arbitrary<'a> + Ord,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for MonotonicQueue<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static + Ord,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, Ord::cmp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+
+    #[test]
+    fn matches_brute_force() {
+        let mut queue = MonotonicQueue::new();
+        let mut model = VecDeque::new();
+        let mut x = 0x2545_f491_u64;
+        for _ in 0..2000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            if x.is_multiple_of(3) {
+                assert_eq!(queue.pop_front(), model.pop_front());
+            } else {
+                queue.push(x % 50);
+                model.push_back(x % 50);
+            }
+            assert_eq!(queue.min(), model.iter().min());
+            assert_eq!(queue.max(), model.iter().max());
+            assert_eq!(queue.len(), model.len());
+        }
+    }
+
+    #[test]
+    fn clear() {
+        let mut queue: MonotonicQueue<_> = [3, 1, 2].into_iter().collect();
+        queue.clear();
+        assert_eq!(queue.min(), None);
+        queue.push(5);
+        queue.push(4);
+        assert_eq!(queue.pop_front(), Some(5));
+        assert_eq!((queue.min(), queue.max()), (Some(&4), Some(&4)));
+        assert_eq!(format!("{queue:?}"), "[4]");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let values: MonotonicQueue<i32> = [3, 1, 2].into_iter().collect();
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[3,1,2]");
+        let back: MonotonicQueue<i32> = serde_json::from_str(&json).unwrap();
+        assert!(back.iter().eq(values.iter()));
+        assert_eq!((back.min(), back.max()), (Some(&1), Some(&3)));
+    }
+}
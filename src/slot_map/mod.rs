@@ -0,0 +1,676 @@
+use alloc::vec::Vec;
+use core::{fmt, iter, mem, ops, slice};
+
+/// A handle to a value stored in a [`SlotMap`].
+///
+/// A key pairs the index of a slot with the generation of the slot at the time
+/// of insertion. Removing a value bumps the generation of its slot, so old keys
+/// stop resolving even after the slot is reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Key {
+    index: u32,
+    generation: u32,
+}
+
+impl Key {
+    /// Index of the slot the key points to.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    /// Generation of the slot when the key was handed out.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+enum Slot<T> {
+    Occupied(T),
+    Vacant { next_free: Option<u32> },
+}
+
+struct Entry<T> {
+    generation: u32,
+    slot: Slot<T>,
+}
+
+/// A generational arena.
+/// Inserting a value returns a [`Key`] that stays valid until the value is
+/// removed, after which the key is rejected instead of aliasing whatever is
+/// stored in the slot next. Insert, remove and lookup are all O(1).
+///
+/// This gives node-based and entity-style data structures stable references
+/// without reference counting or lifetimes.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::slot_map::SlotMap;
+///
+/// let mut map = SlotMap::new();
+/// let foo = map.insert("foo");
+/// let bar = map.insert("bar");
+/// assert_eq!(map[foo], "foo");
+///
+/// assert_eq!(map.remove(foo), Some("foo"));
+/// let baz = map.insert("baz");
+/// // `baz` reuses the slot of `foo`, but the old key stays dead.
+/// assert_eq!(baz.index(), foo.index());
+/// assert_eq!(map.get(foo), None);
+/// assert_eq!(map.get(bar), Some(&"bar"));
+/// ```
+pub struct SlotMap<T> {
+    entries: Vec<Entry<T>>,
+    free_head: Option<u32>,
+    len: usize,
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SlotMap<T> {
+    /// Create a new, empty slot map.
+    pub const fn new() -> Self {
+        SlotMap {
+            entries: Vec::new(),
+            free_head: None,
+            len: 0,
+        }
+    }
+
+    /// Create a new, empty slot map with room for `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        SlotMap {
+            entries: Vec::with_capacity(capacity),
+            free_head: None,
+            len: 0,
+        }
+    }
+
+    /// Number of values in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the map holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a value and return its key.
+    pub fn insert(&mut self, value: T) -> Key {
+        self.insert_with_key(|_| value)
+    }
+
+    /// Insert the value returned by `f`, which is given the key the value will
+    /// be stored under. Useful for values that need to know their own key.
+    pub fn insert_with_key<F>(&mut self, f: F) -> Key
+    where
+        F: FnOnce(Key) -> T,
+    {
+        let key = match self.free_head {
+            Some(index) => {
+                let entry = &self.entries[index as usize];
+                Key {
+                    index,
+                    generation: entry.generation,
+                }
+            }
+            None => {
+                let index = u32::try_from(self.entries.len()).expect("slot map is full");
+                self.entries.push(Entry {
+                    generation: 0,
+                    slot: Slot::Vacant { next_free: None },
+                });
+                Key {
+                    index,
+                    generation: 0,
+                }
+            }
+        };
+        let value = f(key);
+        let entry = &mut self.entries[key.index as usize];
+        if let Slot::Vacant { next_free } = entry.slot {
+            if self.free_head == Some(key.index) {
+                self.free_head = next_free;
+            }
+        }
+        entry.slot = Slot::Occupied(value);
+        self.len += 1;
+        key
+    }
+
+    /// Remove the value of a key.
+    /// Returns the value if the key was still valid.
+    pub fn remove(&mut self, key: Key) -> Option<T> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let entry = &mut self.entries[key.index as usize];
+        let slot = mem::replace(
+            &mut entry.slot,
+            Slot::Vacant {
+                next_free: self.free_head,
+            },
+        );
+        // Invalidate every outstanding key to this slot.
+        entry.generation = entry.generation.wrapping_add(1);
+        self.free_head = Some(key.index);
+        self.len -= 1;
+        match slot {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant { .. } => unreachable!(),
+        }
+    }
+
+    /// Get a reference to the value of a key.
+    pub fn get(&self, key: Key) -> Option<&T> {
+        match self.entries.get(key.index as usize) {
+            Some(Entry {
+                generation,
+                slot: Slot::Occupied(value),
+            }) if *generation == key.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the value of a key.
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        match self.entries.get_mut(key.index as usize) {
+            Some(Entry {
+                generation,
+                slot: Slot::Occupied(value),
+            }) if *generation == key.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return `true` if the key still refers to a value.
+    pub fn contains_key(&self, key: Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove every value, invalidating all keys.
+    pub fn clear(&mut self) {
+        let keys: Vec<_> = self.keys().collect();
+        for key in keys {
+            self.remove(key);
+        }
+    }
+
+    /// Iterate over the keys and values in slot order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            entries: self.entries.iter().enumerate(),
+            remaining: self.len,
+        }
+    }
+
+    /// Iterate over the keys and mutable values in slot order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            entries: self.entries.iter_mut().enumerate(),
+            remaining: self.len,
+        }
+    }
+
+    /// Iterate over the keys in slot order.
+    pub fn keys(&self) -> impl Iterator<Item = Key> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Iterate over the values in slot order.
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl<T> ops::Index<Key> for SlotMap<T> {
+    type Output = T;
+
+    fn index(&self, key: Key) -> &T {
+        self.get(key).expect("invalid slot map key")
+    }
+}
+
+impl<T> ops::IndexMut<Key> for SlotMap<T> {
+    fn index_mut(&mut self, key: Key) -> &mut T {
+        self.get_mut(key).expect("invalid slot map key")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SlotMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the keys and values of a [`SlotMap`] in slot order.
+pub struct Iter<'a, T> {
+    entries: iter::Enumerate<slice::Iter<'a, Entry<T>>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (Key, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, value, generation) =
+            self.entries.find_map(|(index, entry)| match &entry.slot {
+                Slot::Occupied(value) => Some((index, value, entry.generation)),
+                Slot::Vacant { .. } => None,
+            })?;
+        self.remaining -= 1;
+        let key = Key {
+            index: index as u32,
+            generation,
+        };
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// Iterator over the keys and mutable values of a [`SlotMap`] in slot order.
+pub struct IterMut<'a, T> {
+    entries: iter::Enumerate<slice::IterMut<'a, Entry<T>>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (Key, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, value, generation) =
+            self.entries
+                .find_map(|(index, entry)| match &mut entry.slot {
+                    Slot::Occupied(value) => Some((index, value, entry.generation)),
+                    Slot::Vacant { .. } => None,
+                })?;
+        self.remaining -= 1;
+        let key = Key {
+            index: index as u32,
+            generation,
+        };
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SlotMap<T> {
+    type Item = (Key, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut SlotMap<T> {
+    type Item = (Key, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A map that associates extra data with the keys of a [`SlotMap`].
+///
+/// Values are stored densely, indexed by the slot index of the key, so lookups
+/// are a bounds check and a generation comparison. A value is only returned
+/// for the exact key it was inserted with; keys of later generations don't see it.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::slot_map::{SecondaryMap, SlotMap};
+///
+/// let mut entities = SlotMap::new();
+/// let player = entities.insert("player");
+/// let mut health = SecondaryMap::new();
+/// health.insert(player, 100);
+/// assert_eq!(health.get(player), Some(&100));
+///
+/// entities.remove(player);
+/// let monster = entities.insert("monster");
+/// assert_eq!(health.get(monster), None);
+/// ```
+pub struct SecondaryMap<V> {
+    slots: Vec<Option<(u32, V)>>,
+    len: usize,
+}
+
+impl<V> Default for SecondaryMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> SecondaryMap<V> {
+    /// Create a new, empty secondary map.
+    pub const fn new() -> Self {
+        SecondaryMap {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of values in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the map holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Associate a value with a key.
+    /// Returns the old value if the same key already had one. A value stored for
+    /// an older generation of the slot is dropped and not returned.
+    pub fn insert(&mut self, key: Key, value: V) -> Option<V> {
+        let index = key.index as usize;
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || None);
+        }
+        let old = self.slots[index].replace((key.generation, value));
+        match old {
+            Some((generation, value)) if generation == key.generation => Some(value),
+            Some(_) => None,
+            None => {
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    /// Get a reference to the value of a key.
+    pub fn get(&self, key: Key) -> Option<&V> {
+        match self.slots.get(key.index as usize) {
+            Some(Some((generation, value))) if *generation == key.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the value of a key.
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut V> {
+        match self.slots.get_mut(key.index as usize) {
+            Some(Some((generation, value))) if *generation == key.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return `true` if the map has a value for the key.
+    pub fn contains_key(&self, key: Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove the value of a key.
+    pub fn remove(&mut self, key: Key) -> Option<V> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        self.len -= 1;
+        self.slots[key.index as usize]
+            .take()
+            .map(|(_, value)| value)
+    }
+
+    /// Iterate over the keys and values in slot order.
+    pub fn iter(&self) -> SecondaryIter<'_, V> {
+        SecondaryIter {
+            slots: self.slots.iter().enumerate(),
+            remaining: self.len,
+        }
+    }
+
+    /// Iterate over the keys and mutable values in slot order.
+    pub fn iter_mut(&mut self) -> SecondaryIterMut<'_, V> {
+        SecondaryIterMut {
+            slots: self.slots.iter_mut().enumerate(),
+            remaining: self.len,
+        }
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for SecondaryMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the keys and values of a [`SecondaryMap`] in slot order.
+pub struct SecondaryIter<'a, V> {
+    slots: iter::Enumerate<slice::Iter<'a, Option<(u32, V)>>>,
+    remaining: usize,
+}
+
+impl<'a, V> Iterator for SecondaryIter<'a, V> {
+    type Item = (Key, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, value, generation) = self.slots.find_map(|(index, slot)| {
+            let (generation, value) = slot.as_ref()?;
+            Some((index, value, *generation))
+        })?;
+        self.remaining -= 1;
+        let key = Key {
+            index: index as u32,
+            generation,
+        };
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// Iterator over the keys and mutable values of a [`SecondaryMap`] in slot order.
+pub struct SecondaryIterMut<'a, V> {
+    slots: iter::Enumerate<slice::IterMut<'a, Option<(u32, V)>>>,
+    remaining: usize,
+}
+
+impl<'a, V> Iterator for SecondaryIterMut<'a, V> {
+    type Item = (Key, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, value, generation) = self.slots.find_map(|(index, slot)| {
+            let (generation, value) = slot.as_mut()?;
+            Some((index, value, *generation))
+        })?;
+        self.remaining -= 1;
+        let key = Key {
+            index: index as u32,
+            generation,
+        };
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, V> IntoIterator for &'a SecondaryMap<V> {
+    type Item = (Key, &'a V);
+    type IntoIter = SecondaryIter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a mut SecondaryMap<V> {
+    type Item = (Key, &'a mut V);
+    type IntoIter = SecondaryIterMut<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for SlotMap<T> {
+    /// Each insert may also remove an earlier key, so that slots get reused and
+    /// generations move on.
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut map = SlotMap::new();
+        let mut keys = Vec::new();
+        for entry in u.arbitrary_iter::<(T, Option<usize>)>()? {
+            let (value, remove) = entry?;
+            keys.push(map.insert(value));
+            if let Some(i) = remove {
+                map.remove(keys.swap_remove(i % keys.len()));
+            }
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary + 'static> proptest::arbitrary::Arbitrary for SlotMap<T> {
+    type Parameters = (proptest::collection::SizeRange, T::Parameters);
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    /// Each insert may also remove an earlier key, so that slots get reused and
+    /// generations move on.
+    fn arbitrary_with((size, args): Self::Parameters) -> Self::Strategy {
+        use proptest::{arbitrary::any_with, strategy::Strategy};
+
+        let entry = any_with::<(T, Option<usize>)>((args, Default::default()));
+        proptest::collection::vec(entry, size)
+            .prop_map(|entries| {
+                let mut map = SlotMap::new();
+                let mut keys = Vec::new();
+                for (value, remove) in entries {
+                    keys.push(map.insert(value));
+                    if let Some(i) = remove {
+                        map.remove(keys.swap_remove(i % keys.len()));
+                    }
+                }
+                map
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn insert_get() {
+        let mut map = SlotMap::new();
+        let a = map.insert(1);
+        let b = map.insert(2);
+        assert_ne!(a, b);
+        assert_eq!(map.get(a), Some(&1));
+        map[b] += 10;
+        assert_eq!(map.get(b), Some(&12));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn remove_invalidates() {
+        let mut map = SlotMap::new();
+        let a = map.insert("a");
+        assert_eq!(map.remove(a), Some("a"));
+        assert_eq!(map.remove(a), None);
+        assert!(!map.contains_key(a));
+
+        let b = map.insert("b");
+        assert_eq!(b.index(), a.index());
+        assert_eq!(b.generation(), a.generation() + 1);
+        assert_eq!(map.get(a), None);
+        assert_eq!(map.get(b), Some(&"b"));
+    }
+
+    #[test]
+    fn free_list() {
+        let mut map = SlotMap::new();
+        let keys: Vec<_> = (0..10).map(|i| map.insert(i)).collect();
+        for key in &keys[2..5] {
+            map.remove(*key);
+        }
+        // Freed slots are reused most recently freed first.
+        let reused: Vec<_> = (0..3).map(|i| map.insert(i).index()).collect();
+        assert_eq!(reused, vec![4, 3, 2]);
+        assert_eq!(map.insert(100).index(), 10);
+        assert_eq!(map.len(), 11);
+    }
+
+    #[test]
+    fn insert_with_key() {
+        let mut map = SlotMap::new();
+        let key = map.insert_with_key(|key| key);
+        assert_eq!(map[key], key);
+        map.remove(key);
+        let key = map.insert_with_key(|key| key);
+        assert_eq!(map[key], key);
+    }
+
+    #[test]
+    fn iter() {
+        let mut map = SlotMap::new();
+        let a = map.insert(1);
+        let b = map.insert(2);
+        let c = map.insert(3);
+        map.remove(b);
+        for (_, v) in map.iter_mut() {
+            *v *= 10;
+        }
+        let res: Vec<_> = map.iter().collect();
+        assert_eq!(res, vec![(a, &10), (c, &30)]);
+        map.clear();
+        assert!(map.is_empty());
+        assert!(!map.contains_key(a));
+    }
+
+    #[test]
+    fn secondary_map() {
+        let mut map = SlotMap::new();
+        let a = map.insert("a");
+        let b = map.insert("b");
+        let mut extra = SecondaryMap::new();
+        assert_eq!(extra.insert(b, 2), None);
+        assert_eq!(extra.insert(b, 3), Some(2));
+        assert_eq!(extra.get(a), None);
+        assert_eq!(extra.len(), 1);
+
+        map.remove(b);
+        let c = map.insert("c");
+        assert_eq!(extra.get(c), None);
+        assert_eq!(extra.insert(c, 4), None);
+        assert_eq!(extra.get(b), None);
+        assert_eq!(extra.remove(c), Some(4));
+        assert!(extra.is_empty());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_reuses_slots() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        // Insert 5 and remove it, then insert 6 into the same slot.
+        let mut bytes = [0; 22];
+        bytes[..11].copy_from_slice(&[1, 5, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        bytes[11..14].copy_from_slice(&[1, 6, 0]);
+        let map = SlotMap::<u8>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        let (key, value) = map.iter().next().unwrap();
+        assert_eq!((map.len(), *value), (1, 6));
+        assert_eq!((key.index(), key.generation()), (0, 1));
+    }
+}
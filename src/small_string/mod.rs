@@ -0,0 +1,446 @@
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::hash::{Hash, Hasher};
+use core::str::FromStr;
+use core::{fmt, ops, ptr, str};
+
+use crate::small_vec::SmallVec;
+
+/// Number of bytes a [`SmallString`] stores without allocating.
+pub const INLINE_CAPACITY: usize = 22;
+
+/// A UTF-8 string that stores up to [`INLINE_CAPACITY`] bytes inline and moves
+/// to the heap once it grows beyond that.
+///
+/// Identifiers, keys and other short strings never touch the allocator. The
+/// bytes live in a [`SmallVec`], and the string supports the most used parts of
+/// the [`String`] API. Everything else is available through `Deref<Target = str>`.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::small_string::SmallString;
+///
+/// let mut s = SmallString::from("hello");
+/// s.push_str(", world");
+/// assert!(!s.spilled());
+/// assert_eq!(s, "hello, world");
+/// assert!(s.starts_with("hello"));
+///
+/// s.push_str(" and everyone in it");
+/// assert!(s.spilled());
+/// assert_eq!(s.pop(), Some('t'));
+/// assert_eq!(String::from(s), "hello, world and everyone in i");
+/// ```
+#[derive(Default, Clone)]
+pub struct SmallString {
+    // Always valid UTF-8.
+    bytes: SmallVec<u8, INLINE_CAPACITY>,
+}
+
+impl SmallString {
+    /// Create a new, empty string using the inline storage.
+    pub const fn new() -> Self {
+        SmallString {
+            bytes: SmallVec::new(),
+        }
+    }
+
+    /// Create a new, empty string with room for at least `capacity` bytes.
+    /// Allocates only if `capacity` is greater than [`INLINE_CAPACITY`].
+    pub fn with_capacity(capacity: usize) -> Self {
+        SmallString {
+            bytes: SmallVec::with_capacity(capacity),
+        }
+    }
+
+    /// Length of the string in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Return `true` if the string is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Number of bytes the string can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
+    }
+
+    /// Return `true` if the string has been moved to the heap.
+    pub fn spilled(&self) -> bool {
+        self.bytes.spilled()
+    }
+
+    /// Reserve room for at least `additional` more bytes.
+    pub fn reserve(&mut self, additional: usize) {
+        self.bytes.reserve(additional);
+    }
+
+    /// Extract a string slice of the whole string.
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the bytes are always valid UTF-8.
+        unsafe { str::from_utf8_unchecked(&self.bytes) }
+    }
+
+    /// Extract a mutable string slice of the whole string.
+    pub fn as_mut_str(&mut self) -> &mut str {
+        // SAFETY: the bytes are always valid UTF-8, and `str` keeps them that way.
+        unsafe { str::from_utf8_unchecked_mut(&mut self.bytes) }
+    }
+
+    /// The bytes of the string.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Append a character to the end of the string.
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Append a string slice to the end of the string.
+    pub fn push_str(&mut self, s: &str) {
+        self.bytes.extend(s.bytes());
+    }
+
+    /// Remove the last character and return it, or [`None`] if the string is empty.
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.chars().next_back()?;
+        self.bytes.truncate(self.len() - ch.len_utf8());
+        Some(ch)
+    }
+
+    /// Shorten the string to `new_len` bytes.
+    /// Has no effect if `new_len` is greater than the current length.
+    /// Panics if `new_len` doesn't lie on a char boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len() {
+            assert!(
+                self.is_char_boundary(new_len),
+                "new_len (is {new_len}) should lie on a char boundary"
+            );
+            self.bytes.truncate(new_len);
+        }
+    }
+
+    /// Remove every character. The capacity is kept.
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+
+    /// Insert a character at byte position `index`.
+    /// Panics if `index` is greater than the length or doesn't lie on a char boundary.
+    pub fn insert(&mut self, index: usize, ch: char) {
+        self.insert_str(index, ch.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Insert a string slice at byte position `index`.
+    /// Panics if `index` is greater than the length or doesn't lie on a char boundary.
+    pub fn insert_str(&mut self, index: usize, s: &str) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+        assert!(
+            self.is_char_boundary(index),
+            "insertion index (is {index}) should lie on a char boundary"
+        );
+        self.bytes.reserve(s.len());
+        unsafe {
+            let p = self.bytes.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(s.len()), len - index);
+            ptr::copy_nonoverlapping(s.as_ptr(), p, s.len());
+            self.bytes.set_len(len + s.len());
+        }
+    }
+
+    /// Remove the character at byte position `index` and return it.
+    /// Panics if `index` is not less than the length or doesn't lie on a char boundary.
+    pub fn remove(&mut self, index: usize) -> char {
+        let len = self.len();
+        assert!(
+            index < len,
+            "removal index (is {index}) should be < len (is {len})"
+        );
+        // Slicing panics if `index` is inside a character.
+        let ch = self[index..].chars().next().unwrap();
+        let next = index + ch.len_utf8();
+        unsafe {
+            let p = self.bytes.as_mut_ptr();
+            ptr::copy(p.add(next), p.add(index), len - next);
+            self.bytes.set_len(len - (next - index));
+        }
+        ch
+    }
+}
+
+impl ops::Deref for SmallString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl ops::DerefMut for SmallString {
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl AsRef<str> for SmallString {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl AsRef<[u8]> for SmallString {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl Borrow<str> for SmallString {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl fmt::Debug for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Write for SmallString {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl PartialEq for SmallString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SmallString {}
+
+impl PartialEq<str> for SmallString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SmallString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for SmallString {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SmallString {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+// Hash like `str`, so that `Borrow<str>` lookups work in hash maps.
+impl Hash for SmallString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl From<&str> for SmallString {
+    fn from(s: &str) -> Self {
+        let mut string = SmallString::with_capacity(s.len());
+        string.push_str(s);
+        string
+    }
+}
+
+impl From<String> for SmallString {
+    fn from(s: String) -> Self {
+        SmallString::from(s.as_str())
+    }
+}
+
+impl From<char> for SmallString {
+    fn from(ch: char) -> Self {
+        SmallString::from(ch.encode_utf8(&mut [0; 4]) as &str)
+    }
+}
+
+impl From<SmallString> for String {
+    fn from(s: SmallString) -> Self {
+        String::from(s.as_str())
+    }
+}
+
+impl FromStr for SmallString {
+    type Err = core::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(SmallString::from(s))
+    }
+}
+
+impl Extend<char> for SmallString {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        for ch in iter {
+            self.push(ch);
+        }
+    }
+}
+
+impl<'a> Extend<&'a str> for SmallString {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.push_str(s);
+        }
+    }
+}
+
+impl FromIterator<char> for SmallString {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut s = SmallString::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<'a> FromIterator<&'a str> for SmallString {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut s = SmallString::new();
+        s.extend(iter);
+        s
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SmallString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SmallString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(SmallString::from)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SmallString {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&str>::arbitrary(u).map(SmallString::from)
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&str>::arbitrary_take_rest(u).map(SmallString::from)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for SmallString {
+    type Parameters = <String as proptest::arbitrary::Arbitrary>::Parameters;
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::arbitrary::any_with::<String>(args)
+            .prop_map(SmallString::from)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::ToString};
+
+    use super::*;
+    use std::collections::HashSet;
+    use std::fmt::Write;
+
+    #[test]
+    fn spill_boundary() {
+        let mut s = SmallString::new();
+        s.push_str(&"a".repeat(INLINE_CAPACITY));
+        assert!(!s.spilled());
+        s.push('b');
+        assert!(s.spilled());
+        assert_eq!(s.len(), INLINE_CAPACITY + 1);
+        assert!(s.ends_with("ab"));
+    }
+
+    #[test]
+    fn edit() {
+        let mut s = SmallString::from("héllo");
+        s.insert(0, '¡');
+        s.insert_str(s.len(), " wörld");
+        assert_eq!(s, "¡héllo wörld");
+        assert_eq!(s.remove(0), '¡');
+        assert_eq!(s.remove(1), 'é');
+        assert_eq!(s, "hllo wörld");
+        s.truncate(8);
+        assert_eq!(s, "hllo w\u{f6}");
+        assert_eq!(s.pop(), Some('ö'));
+        s.make_ascii_uppercase();
+        assert_eq!(s, "HLLO W");
+        s.clear();
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "new_len (is 2) should lie on a char boundary")]
+    fn truncate_inside_char() {
+        SmallString::from("hé").truncate(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 2) should lie on a char boundary")]
+    fn insert_inside_char() {
+        SmallString::from("hé").insert(2, 'x');
+    }
+
+    #[test]
+    fn traits() {
+        let mut s: SmallString = ["ab", "cd"].into_iter().collect();
+        write!(s, "-{}", 42).unwrap();
+        assert_eq!(s.to_string(), "abcd-42");
+        assert_eq!(format!("{s:?}"), "\"abcd-42\"");
+        let set: HashSet<SmallString> = ["x", "y"].into_iter().map(SmallString::from).collect();
+        assert!(set.contains("x"));
+        let (a, b) = (SmallString::from("a"), SmallString::from('b'));
+        assert!(a < b);
+        assert_eq!("ok".parse::<SmallString>().unwrap(), "ok");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let s = SmallString::from("héllo");
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, "\"héllo\"");
+        assert_eq!(serde_json::from_str::<SmallString>(&json).unwrap(), s);
+    }
+}
@@ -0,0 +1,446 @@
+use core::{cmp, fmt, mem::MaybeUninit, ops, ptr, slice};
+
+use crate::raw_vec::RawVec;
+
+enum Data<T, const N: usize> {
+    Inline([MaybeUninit<T>; N]),
+    Heap(RawVec<T>),
+}
+
+/// A vector that stores up to `N` elements inline and transparently spills to a
+/// heap allocation once it grows beyond that.
+///
+/// Short vectors never touch the allocator, which makes this a good fit for
+/// collections that are usually tiny but occasionally large.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::small_vec::SmallVec;
+///
+/// let mut vec: SmallVec<i32, 2> = SmallVec::new();
+/// vec.push(1);
+/// vec.push(2);
+/// assert!(!vec.spilled());
+/// vec.push(3);
+/// assert!(vec.spilled());
+/// assert_eq!(&*vec, &[1, 2, 3]);
+/// ```
+pub struct SmallVec<T, const N: usize> {
+    data: Data<T, N>,
+    len: usize,
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    /// Constructs a new, empty `SmallVec` using the inline storage.
+    pub const fn new() -> Self {
+        SmallVec {
+            data: Data::Inline([const { MaybeUninit::uninit() }; N]),
+            len: 0,
+        }
+    }
+
+    /// Constructs a new, empty `SmallVec` with at least the specified capacity.
+    /// Allocates only if `capacity` is greater than `N`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= N {
+            Self::new()
+        } else {
+            SmallVec {
+                data: Data::Heap(RawVec::with_capacity(capacity)),
+                len: 0,
+            }
+        }
+    }
+
+    /// Returns `true` if the elements have been moved to the heap.
+    pub fn spilled(&self) -> bool {
+        matches!(self.data, Data::Heap(_))
+    }
+
+    /// Returns the number of elements the vector can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        match &self.data {
+            Data::Inline(_) => N,
+            Data::Heap(buf) => buf.capacity(),
+        }
+    }
+
+    /// Returns a raw pointer to the vector's buffer.
+    pub fn as_ptr(&self) -> *const T {
+        match &self.data {
+            Data::Inline(array) => array.as_ptr() as *const T,
+            Data::Heap(buf) => buf.ptr(),
+        }
+    }
+
+    /// Returns an unsafe mutable pointer to the vector's buffer.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        match &mut self.data {
+            Data::Inline(array) => array.as_mut_ptr() as *mut T,
+            Data::Heap(buf) => buf.ptr(),
+        }
+    }
+
+    /// Forces the length of the vector to `new_len`.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be at most the capacity, and the elements up to `new_len`
+    /// must be initialized.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.capacity());
+        self.len = new_len;
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    /// Spills to the heap if the inline storage is too small.
+    pub fn reserve(&mut self, additional: usize) {
+        match &mut self.data {
+            Data::Heap(buf) => buf.reserve(self.len, additional),
+            Data::Inline(_) => {
+                if additional > N - self.len {
+                    let required = self.len.checked_add(additional).expect("capacity overflow");
+                    self.spill(cmp::max(required, 2 * N));
+                }
+            }
+        }
+    }
+
+    // Move the inline elements into a heap buffer of the given capacity.
+    fn spill(&mut self, capacity: usize) {
+        let buf = RawVec::with_capacity(capacity);
+        unsafe {
+            ptr::copy_nonoverlapping(self.as_ptr(), buf.ptr(), self.len);
+        }
+        // The inline array is `MaybeUninit`, so overwriting it drops nothing.
+        self.data = Data::Heap(buf);
+    }
+
+    /// Appends an element to the back of the vector, spilling to the heap if the
+    /// inline storage is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::small_vec::SmallVec;
+    ///
+    /// let mut vec: SmallVec<i32, 4> = SmallVec::new();
+    /// vec.push(1);
+    /// vec.push(2);
+    /// assert_eq!(vec[0], 1);
+    /// assert_eq!(vec[1], 2);
+    /// ```
+    pub fn push(&mut self, value: T) {
+        if self.len == self.capacity() {
+            self.reserve(1);
+        }
+        unsafe {
+            ptr::write(self.as_mut_ptr().add(self.len), value);
+        }
+        self.len += 1;
+    }
+
+    /// Removes the last element from the vector and returns it, or [`None`] if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            unsafe { Some(ptr::read(self.as_ptr().add(self.len))) }
+        }
+    }
+
+    /// Inserts an element at position `index` within the vector,
+    /// shifting all elements after it to the right.
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, element: T) {
+        let len = self.len;
+        if index > len {
+            panic!("insertion index (is {index}) should be <= len (is {len})");
+        }
+        if len == self.capacity() {
+            self.reserve(1);
+        }
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(1), len - index);
+            ptr::write(p, element);
+        }
+        self.len += 1;
+    }
+
+    /// Removes and returns the element at position `index` within the vector,
+    /// shifting all elements after it to the left.
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        if index >= len {
+            panic!("removal index (is {index}) should be < len (is {len})");
+        }
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            let ret = ptr::read(p);
+            ptr::copy(p.add(1), p, len - index - 1);
+            self.len -= 1;
+            ret
+        }
+    }
+
+    /// Shortens the vector, keeping the first `len` elements and dropping the rest.
+    /// Has no effect if `len` is greater than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail =
+            ptr::slice_from_raw_parts_mut(unsafe { self.as_mut_ptr().add(len) }, self.len - len);
+        // Update the length first so a panicking destructor can't cause a double drop.
+        self.len = len;
+        unsafe {
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    /// Removes all elements. The capacity is kept.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVec<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+        // A heap buffer is freed by `RawVec`'s own destructor.
+    }
+}
+
+impl<T, const N: usize> ops::Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+}
+
+impl<T, const N: usize> ops::DerefMut for SmallVec<T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallVec<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for SmallVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for SmallVec<T, N> {}
+
+impl<'a, T, const N: usize> IntoIterator for &'a SmallVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut SmallVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = SmallVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize> serde::Serialize for SmallVec<T, N>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, const N: usize> serde::Deserialize<'de> for SmallVec<T, N>
+where
+    T: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_seq(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T, const N: usize> arbitrary::Arbitrary<'a> for SmallVec<T, N>
+where
+    T: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<T>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<T>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T, const N: usize> proptest::arbitrary::Arbitrary for SmallVec<T, N>
+where
+    T: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn push_pop() {
+        let mut v: SmallVec<i32, 4> = SmallVec::new();
+        v.push(1);
+        v.push(2);
+        v.push(3);
+        assert_eq!(v.pop(), Some(3));
+        assert_eq!(v.pop(), Some(2));
+        assert_eq!(v.pop(), Some(1));
+        assert_eq!(v.pop(), None);
+    }
+
+    #[test]
+    fn spill_boundary() {
+        let mut v: SmallVec<i32, 3> = SmallVec::new();
+        for i in 0..3 {
+            v.push(i);
+        }
+        assert!(!v.spilled());
+        assert_eq!(v.capacity(), 3);
+        v.push(3);
+        assert!(v.spilled());
+        assert!(v.capacity() >= 4);
+        assert_eq!(&*v, &[0, 1, 2, 3]);
+        // Once spilled, the vector stays on the heap.
+        v.clear();
+        assert!(v.spilled());
+    }
+
+    #[test]
+    fn zero_inline_capacity() {
+        let mut v: SmallVec<String, 0> = SmallVec::new();
+        assert!(!v.spilled());
+        v.push("foo".to_string());
+        assert!(v.spilled());
+        assert_eq!(v[0], "foo");
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut v: SmallVec<i32, 4> = [1, 2, 4].into_iter().collect();
+        v.insert(2, 3);
+        assert!(!v.spilled());
+        v.insert(0, 0);
+        assert!(v.spilled());
+        assert_eq!(&*v, &[0, 1, 2, 3, 4]);
+        assert_eq!(v.remove(4), 4);
+        assert_eq!(v.remove(0), 0);
+        assert_eq!(&*v, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn with_capacity() {
+        let v: SmallVec<u8, 8> = SmallVec::with_capacity(8);
+        assert!(!v.spilled());
+        let v: SmallVec<u8, 8> = SmallVec::with_capacity(9);
+        assert!(v.spilled());
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn drops_elements() {
+        let rc = Rc::new(());
+        {
+            let mut v: SmallVec<Rc<()>, 2> = SmallVec::new();
+            v.push(rc.clone());
+            v.push(rc.clone());
+            assert_eq!(Rc::strong_count(&rc), 3);
+            v.push(rc.clone());
+            v.truncate(1);
+            assert_eq!(Rc::strong_count(&rc), 2);
+            v.push(rc.clone());
+        }
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn deref_mut() {
+        let mut v: SmallVec<i32, 4> = [3, 1, 2].into_iter().collect();
+        v.sort();
+        assert_eq!(&*v, &[1, 2, 3]);
+        assert_eq!(v.clone(), v);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let values: SmallVec<i32, 2> = [1, 2, 3].into_iter().collect();
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let back: SmallVec<i32, 2> = serde_json::from_str(&json).unwrap();
+        assert_eq!(*back, *values);
+    }
+}
@@ -0,0 +1,126 @@
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+/// Sparse table.
+/// It preprocesses an immutable sequence in O(n log n) time and space and then answers
+/// range queries in O(1), complementing the segment tree for read-only data.
+///
+/// `combine` must be associative and idempotent (`combine(a, a) == a`), like `min`,
+/// `max` or `gcd`, because a query combines two blocks that may overlap.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::sparse_table::SparseTable;
+///
+/// let table = SparseTable::new(&[5, 3, 8, 6, 1, 4], |a, b| *a.min(b));
+/// assert_eq!(table.query(0..3), Some(3));
+/// assert_eq!(table.query(2..6), Some(1));
+/// assert_eq!(table.query(2..2), None);
+/// ```
+pub struct SparseTable<T, F> {
+    // `table[k][i]` is the combination of the `2^k` elements starting at `i`.
+    table: Vec<Vec<T>>,
+    combine: F,
+}
+
+impl<T, F> SparseTable<T, F>
+where
+    T: Clone,
+    F: Fn(&T, &T) -> T,
+{
+    /// Build a sparse table over `data`.
+    pub fn new(data: &[T], combine: F) -> Self {
+        let mut table = vec![data.to_vec()];
+        let mut width = 1;
+        while 2 * width <= data.len() {
+            let prev = table.last().unwrap();
+            let level = (0..=data.len() - 2 * width)
+                .map(|i| combine(&prev[i], &prev[i + width]))
+                .collect();
+            table.push(level);
+            width *= 2;
+        }
+        SparseTable { table, combine }
+    }
+
+    /// Number of elements in the table.
+    pub fn len(&self) -> usize {
+        self.table[0].len()
+    }
+
+    /// Return `true` if the table holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.table[0].is_empty()
+    }
+
+    /// Combine all elements in `range`.
+    /// Returns `None` if the range is empty.
+    /// Panics if the range is out of bounds.
+    pub fn query(&self, range: Range<usize>) -> Option<T> {
+        let len = self.len();
+        if range.end > len {
+            panic!(
+                "range end (is {end}) should be <= len (is {len})",
+                end = range.end
+            );
+        }
+        if range.start >= range.end {
+            return None;
+        }
+        let level = (usize::BITS - 1 - (range.end - range.start).leading_zeros()) as usize;
+        let row = &self.table[level];
+        Some((self.combine)(
+            &row[range.start],
+            &row[range.end - (1 << level)],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gcd(a: &u64, b: &u64) -> u64 {
+        let (mut a, mut b) = (*a, *b);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    }
+
+    #[test]
+    fn query_min_max() {
+        let data = [9, 2, 7, 4, 4, 8, 1, 6, 3];
+        let min = SparseTable::new(&data, |a, b| *a.min(b));
+        let max = SparseTable::new(&data, |a, b| *a.max(b));
+        for l in 0..data.len() {
+            for r in l + 1..=data.len() {
+                assert_eq!(min.query(l..r), data[l..r].iter().min().copied());
+                assert_eq!(max.query(l..r), data[l..r].iter().max().copied());
+            }
+        }
+    }
+
+    #[test]
+    fn query_gcd() {
+        let table = SparseTable::new(&[12, 18, 24, 36, 7], gcd);
+        assert_eq!(table.query(0..4), Some(6));
+        assert_eq!(table.query(2..4), Some(12));
+        assert_eq!(table.query(0..5), Some(1));
+    }
+
+    #[test]
+    fn empty() {
+        let table = SparseTable::new(&[] as &[i32], |a, b| *a.min(b));
+        assert!(table.is_empty());
+        assert_eq!(table.query(0..0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end (is 4) should be <= len (is 3)")]
+    fn query_out_of_bounds() {
+        let table = SparseTable::new(&[1, 2, 3], |a, b| *a.min(b));
+        table.query(1..4);
+    }
+}
@@ -0,0 +1,138 @@
+use alloc::vec::Vec;
+
+/// Common queries of the spatial indexes in this crate, so code can be written
+/// once against a [`KdTree`](crate::kd_tree::KdTree), a
+/// [`Quadtree`](crate::quadtree::Quadtree) or an [`RTree`](crate::rtree::RTree).
+///
+/// Points have `K` coordinates and distances are Euclidean.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::kd_tree::KdTree;
+/// use my_std::quadtree::{Aabb, Quadtree};
+/// use my_std::spatial::SpatialIndex;
+///
+/// fn closest_city<S: SpatialIndex<&'static str, 2>>(mut index: S) -> Option<&'static str> {
+///     index.insert_point([0.0, 0.0], "Paris");
+///     index.insert_point([10.0, 10.0], "Berlin");
+///     index.nearest_to(&[8.0, 9.0]).copied()
+/// }
+///
+/// let bounds = Aabb::new([-100.0, -100.0], [100.0, 100.0]);
+/// assert_eq!(closest_city(KdTree::new()), Some("Berlin"));
+/// assert_eq!(closest_city(Quadtree::new(bounds)), Some("Berlin"));
+/// ```
+pub trait SpatialIndex<T, const K: usize> {
+    /// Add a value at a point.
+    fn insert_point(&mut self, point: [f64; K], value: T);
+
+    /// The value closest to `query`.
+    fn nearest_to(&self, query: &[f64; K]) -> Option<&T>;
+
+    /// Every value in the axis-aligned box from `min` to `max`, bounds included,
+    /// in no particular order.
+    fn in_box(&self, min: &[f64; K], max: &[f64; K]) -> Vec<&T>;
+
+    /// Number of values in the index.
+    fn len(&self) -> usize;
+
+    /// Return `true` if the index is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// An axis-aligned bounding box in 2D, bounds included.
+/// A point is a box whose `min` and `max` are equal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aabb {
+    pub min: [f64; 2],
+    pub max: [f64; 2],
+}
+
+impl Aabb {
+    /// Create a box from its two corners.
+    /// Panics if `min` is greater than `max` on an axis.
+    pub fn new(min: [f64; 2], max: [f64; 2]) -> Self {
+        assert!(
+            min[0] <= max[0] && min[1] <= max[1],
+            "min (is {min:?}) should be <= max (is {max:?})"
+        );
+        Aabb { min, max }
+    }
+
+    /// Create the box of a single point.
+    pub fn point(p: [f64; 2]) -> Self {
+        Aabb { min: p, max: p }
+    }
+
+    /// Return `true` if `other` lies entirely inside this box.
+    pub fn contains(&self, other: &Aabb) -> bool {
+        (0..2).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    /// Return `true` if the boxes share at least one point.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..2).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    /// Squared distance from a point to the closest point of the box, 0 if it's inside.
+    pub fn distance2(&self, p: &[f64; 2]) -> f64 {
+        (0..2)
+            .map(|i| {
+                let d = (self.min[i] - p[i]).max(p[i] - self.max[i]).max(0.0);
+                d * d
+            })
+            .sum()
+    }
+
+    /// Area of the box.
+    pub fn area(&self) -> f64 {
+        (self.max[0] - self.min[0]) * (self.max[1] - self.min[1])
+    }
+
+    /// The smallest box containing both boxes.
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: [self.min[0].min(other.min[0]), self.min[1].min(other.min[1])],
+            max: [self.max[0].max(other.max[0]), self.max[1].max(other.max[1])],
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Aabb {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let [x0, y0, x1, y1] = u.arbitrary::<[i32; 4]>()?.map(coord);
+        Ok(Aabb::new(
+            [x0.min(x1), y0.min(y1)],
+            [x0.max(x1), y0.max(y1)],
+        ))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Aabb {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::arbitrary::any::<[i32; 4]>()
+            .prop_map(|corners| {
+                let [x0, y0, x1, y1] = corners.map(coord);
+                Aabb::new([x0.min(x1), y0.min(y1)], [x0.max(x1), y0.max(y1)])
+            })
+            .boxed()
+    }
+}
+
+/// A coordinate for a generated box or point. These are multiples of 1/16 in
+/// the range of `i32`, so the areas and distances built from them stay finite.
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub(crate) fn coord(x: i32) -> f64 {
+    f64::from(x) / 16.0
+}
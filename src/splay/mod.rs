@@ -0,0 +1,692 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::mem;
+use core::ops::{Bound, RangeBounds};
+use core::ptr;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+/// Node of a splay tree.
+struct Node<K, V> {
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V) -> Self {
+        Node {
+            key,
+            value,
+            left: None,
+            right: None,
+        }
+    }
+}
+
+/// An ordered map based on a splay tree.
+/// Every access rotates the accessed key to the root, so recently used keys are
+/// cheap to reach again. Operations are amortized O(log n), and much faster than
+/// that on skewed access patterns where a small set of keys is hit repeatedly.
+///
+/// Because a lookup restructures the tree, [`SplayMap::get`] takes `&mut self`.
+/// Use [`SplayMap::get_no_splay`] for read-only lookups through a shared reference.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::splay::SplayMap;
+///
+/// let mut map = SplayMap::new();
+/// map.insert(3, "c");
+/// map.insert(1, "a");
+/// map.insert(2, "b");
+/// assert_eq!(map.get(&1), Some(&"a"));
+/// assert_eq!(map.get_no_splay(&2), Some(&"b"));
+/// assert_eq!(map.remove(&1), Some("a"));
+/// let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+/// assert_eq!(keys, vec![2, 3]);
+/// ```
+pub struct SplayMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K, V> Default for SplayMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> SplayMap<K, V> {
+    /// Create a new, empty map.
+    pub const fn new() -> Self {
+        SplayMap { root: None, len: 0 }
+    }
+
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Iterate over the entries in ascending key order.
+    /// Iteration does not splay.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            remaining: self.len,
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    /// Iterate over the entries in ascending key order, with mutable
+    /// references to the values.
+    /// Iteration does not splay.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut iter = IterMut {
+            stack: Vec::new(),
+            remaining: self.len,
+        };
+        iter.push_left(self.root.as_deref_mut());
+        iter
+    }
+}
+
+impl<K, V> SplayMap<K, V>
+where
+    K: Ord,
+{
+    /// Insert a key-value pair into the map and splay it to the root.
+    /// Returns the old value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut root = match self.root.take() {
+            Some(root) => Self::splay(root, &key),
+            None => {
+                self.root = Some(Box::new(Node::new(key, value)));
+                self.len += 1;
+                return None;
+            }
+        };
+        let mut node = Box::new(Node::new(key, value));
+        // The new node becomes the root and the old root one of its children.
+        match node.key.cmp(&root.key) {
+            Ordering::Equal => {
+                let old = mem::replace(&mut root.value, node.value);
+                self.root = Some(root);
+                return Some(old);
+            }
+            Ordering::Less => {
+                node.left = root.left.take();
+                node.right = Some(root);
+            }
+            Ordering::Greater => {
+                node.right = root.right.take();
+                node.left = Some(root);
+            }
+        }
+        self.root = Some(node);
+        self.len += 1;
+        None
+    }
+
+    /// Get a reference to the value of a key, splaying it to the root.
+    /// If the key is absent, the last node visited by the search is splayed instead.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Get a mutable reference to the value of a key, splaying it to the root.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let root = Self::splay(self.root.take()?, key);
+        let root = self.root.insert(root);
+        if key == root.key.borrow() {
+            Some(&mut root.value)
+        } else {
+            None
+        }
+    }
+
+    /// Get a reference to the value of a key without restructuring the tree.
+    pub fn get_no_splay<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => current = node.left.as_deref(),
+                Ordering::Greater => current = node.right.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+            }
+        }
+        None
+    }
+
+    /// Return `true` if the map contains the key.
+    /// This does not splay.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_no_splay(key).is_some()
+    }
+
+    /// Remove a key from the map.
+    /// Returns the value of the key if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut root = Self::splay(self.root.take()?, key);
+        if key != root.key.borrow() {
+            self.root = Some(root);
+            return None;
+        }
+        self.root = match root.left.take() {
+            None => root.right.take(),
+            Some(left) => {
+                // Every key on the left is smaller, so splaying brings the
+                // maximum up and leaves its right link free.
+                let mut left = Self::splay(left, key);
+                left.right = root.right.take();
+                Some(left)
+            }
+        };
+        self.len -= 1;
+        Some(root.value)
+    }
+
+    /// Get the entry with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Get the entry with the largest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Iterate over the entries whose keys fall in `range`, in ascending key order.
+    /// Panics if the start of the range is greater than its end.
+    /// Unlike lookups, iterating doesn't splay the tree.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+            (start, end)
+        {
+            assert!(s <= e, "range start is greater than range end");
+        }
+        let mut iter = Range {
+            front: Vec::new(),
+            back: Vec::new(),
+        };
+        // Each stack keeps the nodes on the path to its end of the range that
+        // are still to be visited from that end.
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            let key = current.key.borrow();
+            node = match start {
+                Bound::Included(s) if key < s => current.right.as_deref(),
+                Bound::Excluded(s) if key <= s => current.right.as_deref(),
+                _ => {
+                    iter.front.push(current);
+                    current.left.as_deref()
+                }
+            };
+        }
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            let key = current.key.borrow();
+            node = match end {
+                Bound::Included(e) if key > e => current.left.as_deref(),
+                Bound::Excluded(e) if key >= e => current.left.as_deref(),
+                _ => {
+                    iter.back.push(current);
+                    current.right.as_deref()
+                }
+            };
+        }
+        // The ends cross when no key falls in the range.
+        match (iter.front.last(), iter.back.last()) {
+            (Some(first), Some(last)) if first.key <= last.key => {}
+            _ => {
+                iter.front.clear();
+                iter.back.clear();
+            }
+        }
+        iter
+    }
+
+    // Bring the node with `key`, or the last node on its search path, to the root.
+    fn splay<Q>(mut node: Box<Node<K, V>>, key: &Q) -> Box<Node<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match key.cmp(node.key.borrow()) {
+            Ordering::Equal => node,
+            Ordering::Less => {
+                let mut left = match node.left.take() {
+                    Some(left) => left,
+                    None => return node,
+                };
+                match key.cmp(left.key.borrow()) {
+                    // Zig-zig.
+                    Ordering::Less => {
+                        if let Some(left_left) = left.left.take() {
+                            left.left = Some(Self::splay(left_left, key));
+                            node.left = Some(left);
+                            node = Self::rotate_right(node);
+                        } else {
+                            node.left = Some(left);
+                        }
+                    }
+                    // Zig-zag.
+                    Ordering::Greater => {
+                        if let Some(left_right) = left.right.take() {
+                            left.right = Some(Self::splay(left_right, key));
+                            left = Self::rotate_left(left);
+                        }
+                        node.left = Some(left);
+                    }
+                    Ordering::Equal => node.left = Some(left),
+                }
+                if node.left.is_some() {
+                    Self::rotate_right(node)
+                } else {
+                    node
+                }
+            }
+            Ordering::Greater => {
+                let mut right = match node.right.take() {
+                    Some(right) => right,
+                    None => return node,
+                };
+                match key.cmp(right.key.borrow()) {
+                    // Zig-zig.
+                    Ordering::Greater => {
+                        if let Some(right_right) = right.right.take() {
+                            right.right = Some(Self::splay(right_right, key));
+                            node.right = Some(right);
+                            node = Self::rotate_left(node);
+                        } else {
+                            node.right = Some(right);
+                        }
+                    }
+                    // Zig-zag.
+                    Ordering::Less => {
+                        if let Some(right_left) = right.left.take() {
+                            right.left = Some(Self::splay(right_left, key));
+                            right = Self::rotate_right(right);
+                        }
+                        node.right = Some(right);
+                    }
+                    Ordering::Equal => node.right = Some(right),
+                }
+                if node.right.is_some() {
+                    Self::rotate_left(node)
+                } else {
+                    node
+                }
+            }
+        }
+    }
+
+    fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut right = node
+            .right
+            .take()
+            .expect("rotate left requires a right child");
+        node.right = right.left.take();
+        right.left = Some(node);
+        right
+    }
+
+    fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut left = node
+            .left
+            .take()
+            .expect("rotate right requires a left child");
+        node.left = left.right.take();
+        left.right = Some(node);
+        left
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SplayMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the entries of a [`SplayMap`] in ascending key order.
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SplayMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over a range of entries of a [`SplayMap`] in ascending key order.
+pub struct Range<'a, K, V> {
+    // Like the stack of `Iter`, for the front and back of the range. The range
+    // is used up once both ends reach the same node.
+    front: Vec<&'a Node<K, V>>,
+    back: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front.pop()?;
+        if ptr::eq(node, *self.back.last().unwrap()) {
+            self.front.clear();
+            self.back.clear();
+        } else {
+            let mut next = node.right.as_deref();
+            while let Some(current) = next {
+                self.front.push(current);
+                next = current.left.as_deref();
+            }
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back.pop()?;
+        if ptr::eq(node, *self.front.last().unwrap()) {
+            self.front.clear();
+            self.back.clear();
+        } else {
+            let mut next = node.left.as_deref();
+            while let Some(current) = next {
+                self.back.push(current);
+                next = current.right.as_deref();
+            }
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+// A node whose left subtree is done, split into its entry and the right
+// subtree still to visit.
+type Pending<'a, K, V> = (&'a K, &'a mut V, Option<&'a mut Node<K, V>>);
+
+/// Mutable iterator over the entries of a [`SplayMap`] in ascending key order.
+pub struct IterMut<'a, K, V> {
+    stack: Vec<Pending<'a, K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> IterMut<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a mut Node<K, V>>) {
+        while let Some(current) = node {
+            let Node {
+                key,
+                value,
+                left,
+                right,
+                ..
+            } = current;
+            self.stack.push((key, value, right.as_deref_mut()));
+            node = left.as_deref_mut();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value, right) = self.stack.pop()?;
+        self.push_left(right);
+        self.remaining -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut SplayMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for SplayMap<K, V>
+where
+    K: Ord,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for SplayMap<K, V>
+where
+    K: Ord,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = SplayMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for SplayMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for SplayMap<K, V>
+where
+    K: serde::Deserialize<'de> + Ord,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for SplayMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for SplayMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Ord,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &(K, V), b: &(K, V)| a.0.cmp(&b.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root_key<K: Copy, V>(map: &SplayMap<K, V>) -> Option<K> {
+        map.root.as_ref().map(|root| root.key)
+    }
+
+    #[test]
+    fn insert() {
+        let mut map = SplayMap::new();
+        assert_eq!(map.insert("foo", 42), None);
+        assert_eq!(map.insert("foo", 43), Some(42));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn get_splays() {
+        let mut map: SplayMap<_, _> = (0..100).map(|i| (i, i * 10)).collect();
+        assert_eq!(map.get(&42), Some(&420));
+        assert_eq!(root_key(&map), Some(42));
+        *map.get_mut(&7).unwrap() += 1;
+        assert_eq!(root_key(&map), Some(7));
+        assert_eq!(map.get(&7), Some(&71));
+        assert_eq!(map.get(&1000), None);
+        assert_eq!(root_key(&map), Some(99));
+    }
+
+    #[test]
+    fn get_no_splay() {
+        let mut map: SplayMap<_, _> = (0..100).map(|i| (i, i * 10)).collect();
+        map.get(&50);
+        assert_eq!(map.get_no_splay(&3), Some(&30));
+        assert_eq!(map.get_no_splay(&100), None);
+        assert!(map.contains_key(&99));
+        assert_eq!(root_key(&map), Some(50));
+    }
+
+    #[test]
+    fn remove() {
+        let mut map: SplayMap<_, _> = (0..100).map(|i| ((i * 37) % 100, i)).collect();
+        for i in (0..100).step_by(3) {
+            assert!(map.remove(&i).is_some());
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 66);
+        for i in 0..100 {
+            assert_eq!(map.contains_key(&i), i % 3 != 0);
+        }
+        let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+        let expected: Vec<_> = (0..100).filter(|i| i % 3 != 0).collect();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn skewed_access() {
+        // Sequential inserts degenerate into a path, but splaying repairs it.
+        let mut map: SplayMap<_, _> = (0..1000).map(|i| (i, ())).collect();
+        for _ in 0..10 {
+            for i in [3, 5, 8] {
+                assert!(map.get(&i).is_some());
+            }
+        }
+        assert_eq!(root_key(&map), Some(8));
+        let left = map.root.as_ref().unwrap().left.as_ref().unwrap();
+        assert_eq!(left.key, 5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: SplayMap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"1":10,"2":20}"#);
+        let back: SplayMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            back.iter().collect::<Vec<_>>(),
+            map.iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    cell::{Cell, UnsafeCell},
+    fmt,
+    mem::MaybeUninit,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering::*},
+        mpsc::{TryRecvError, TrySendError},
+    },
+};
+
+use crate::arc::MyArc;
+
+// Keeps a value on a cache line of its own, so that the producer writing one
+// index doesn't keep evicting the line the consumer writes the other on.
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+struct Ring<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    // Counts of values popped and pushed so far. They only grow, wrapping
+    // around, and a value's slot is its count modulo the capacity.
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+    // Set by whichever end is dropped first.
+    disconnected: AtomicBool,
+}
+
+// Each slot is only ever accessed by one end at a time.
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+        self.slots[index % self.slots.len()].get()
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        let tail = *self.tail.0.get_mut();
+        let mut head = *self.head.0.get_mut();
+        while head != tail {
+            unsafe { (*self.slot(head)).assume_init_drop() };
+            head = head.wrapping_add(1);
+        }
+    }
+}
+
+/// Create a ring holding up to `capacity` values, returning its two ends.
+///
+/// The [`Producer`] pushes and the [`Consumer`] pops, each from its own
+/// thread. Neither ever waits for the other: both only load the other's index
+/// and store their own, with no lock or compare-and-swap loop, so every
+/// operation finishes in a bounded number of steps. That makes it fit for
+/// latency-sensitive pipelines such as audio, where a full or empty ring is
+/// handled by the caller rather than by blocking.
+///
+/// The two indices live on separate cache lines, and each end keeps a copy of
+/// the other's, only reloading it when the ring looks full or empty.
+///
+/// Panics if `capacity` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::mpsc::TryRecvError;
+/// use std::thread;
+/// use my_std::spsc;
+///
+/// let (mut tx, mut rx) = spsc::channel(4);
+/// thread::scope(|s| {
+///     s.spawn(move || {
+///         for sample in 0..100 {
+///             while tx.push(sample).is_err() {
+///                 std::hint::spin_loop();
+///             }
+///         }
+///     });
+///     let mut expected = 0;
+///     while expected < 100 {
+///         match rx.pop() {
+///             Ok(sample) => {
+///                 assert_eq!(sample, expected);
+///                 expected += 1;
+///             }
+///             Err(TryRecvError::Empty) => std::hint::spin_loop(),
+///             Err(TryRecvError::Disconnected) => unreachable!(),
+///         }
+///     }
+/// });
+/// ```
+pub fn channel<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    assert!(capacity > 0, "capacity must be positive");
+    let ring = MyArc::new(Ring {
+        slots: (0..capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect(),
+        head: CachePadded(AtomicUsize::new(0)),
+        tail: CachePadded(AtomicUsize::new(0)),
+        disconnected: AtomicBool::new(false),
+    });
+    let producer = Producer {
+        ring: ring.clone(),
+        tail: 0,
+        head: Cell::new(0),
+    };
+    let consumer = Consumer {
+        ring,
+        head: 0,
+        tail: Cell::new(0),
+    };
+    (producer, consumer)
+}
+
+/// The pushing end of an [`spsc::channel`](channel).
+pub struct Producer<T> {
+    ring: MyArc<Ring<T>>,
+    // Our own index, which only we write.
+    tail: usize,
+    // The consumer's index when we last loaded it.
+    head: Cell<usize>,
+}
+
+impl<T> Producer<T> {
+    /// Push `value`, or hand it back if the ring is full or the consumer is
+    /// gone.
+    pub fn push(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        if self.ring.disconnected.load(Relaxed) {
+            return Err(TrySendError::Disconnected(value));
+        }
+        if self.tail.wrapping_sub(self.head.get()) == self.capacity() {
+            // `Acquire` so the consumer is done with the slot we reuse.
+            self.head.set(self.ring.head.load(Acquire));
+            if self.tail.wrapping_sub(self.head.get()) == self.capacity() {
+                return Err(TrySendError::Full(value));
+            }
+        }
+        unsafe { (*self.ring.slot(self.tail)).write(value) };
+        self.tail = self.tail.wrapping_add(1);
+        // `Release` publishes the value to the consumer.
+        self.ring.tail.store(self.tail, Release);
+        Ok(())
+    }
+
+    /// Number of values in the ring. The consumer may pop some right after.
+    pub fn len(&self) -> usize {
+        self.head.set(self.ring.head.load(Acquire));
+        self.tail.wrapping_sub(self.head.get())
+    }
+
+    /// Return `true` if the ring holds no value.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return `true` if a push would fail for lack of room.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Most values the ring holds.
+    pub fn capacity(&self) -> usize {
+        self.ring.slots.len()
+    }
+
+    /// Return `true` if the consumer is gone.
+    pub fn is_abandoned(&self) -> bool {
+        self.ring.disconnected.load(Relaxed)
+    }
+}
+
+impl<T> Drop for Producer<T> {
+    fn drop(&mut self) {
+        self.ring.disconnected.store(true, Release);
+    }
+}
+
+impl<T> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Producer")
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The popping end of an [`spsc::channel`](channel).
+pub struct Consumer<T> {
+    ring: MyArc<Ring<T>>,
+    // Our own index, which only we write.
+    head: usize,
+    // The producer's index when we last loaded it.
+    tail: Cell<usize>,
+}
+
+impl<T> Consumer<T> {
+    /// Pop the oldest value. Fails with `Empty` if there is none yet, or with
+    /// `Disconnected` once the producer is gone and every value was popped.
+    pub fn pop(&mut self) -> Result<T, TryRecvError> {
+        if self.head == self.tail.get() {
+            // Read before the index, so a value pushed right before the
+            // producer was dropped isn't missed.
+            let disconnected = self.ring.disconnected.load(Acquire);
+            // `Acquire` pairs with the producer's `Release` store.
+            self.tail.set(self.ring.tail.load(Acquire));
+            if self.head == self.tail.get() {
+                return Err(if disconnected {
+                    TryRecvError::Disconnected
+                } else {
+                    TryRecvError::Empty
+                });
+            }
+        }
+        let value = unsafe { (*self.ring.slot(self.head)).assume_init_read() };
+        self.head = self.head.wrapping_add(1);
+        // `Release` hands the slot back to the producer.
+        self.ring.head.store(self.head, Release);
+        Ok(value)
+    }
+
+    /// Number of values in the ring. The producer may push more right after.
+    pub fn len(&self) -> usize {
+        self.tail.set(self.ring.tail.load(Acquire));
+        self.tail.get().wrapping_sub(self.head)
+    }
+
+    /// Return `true` if the ring holds no value.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Most values the ring holds.
+    pub fn capacity(&self) -> usize {
+        self.ring.slots.len()
+    }
+
+    /// Return `true` if the producer is gone.
+    pub fn is_abandoned(&self) -> bool {
+        self.ring.disconnected.load(Relaxed)
+    }
+}
+
+impl<T> Drop for Consumer<T> {
+    fn drop(&mut self) {
+        self.ring.disconnected.store(true, Release);
+    }
+}
+
+impl<T> Iterator for Consumer<T> {
+    type Item = T;
+
+    /// Pop the oldest value, if there is one now.
+    fn next(&mut self) -> Option<T> {
+        self.pop().ok()
+    }
+}
+
+impl<T> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Consumer")
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::Cell as StdCell, rc::Rc, thread};
+
+    #[test]
+    fn push_and_pop() {
+        let (mut tx, mut rx) = channel(2);
+        assert_eq!(rx.pop(), Err(TryRecvError::Empty));
+        tx.push(1).unwrap();
+        tx.push(2).unwrap();
+        assert!(tx.is_full());
+        assert!(matches!(tx.push(3), Err(TrySendError::Full(3))));
+        assert_eq!(rx.pop(), Ok(1));
+        // Wraps around the end of the slots.
+        tx.push(3).unwrap();
+        assert_eq!(rx.len(), 2);
+        assert_eq!(rx.by_ref().collect::<Vec<_>>(), [2, 3]);
+        assert!(tx.is_empty());
+
+        tx.push(4).unwrap();
+        drop(tx);
+        assert!(rx.is_abandoned());
+        // Values pushed before the producer left are still there.
+        assert_eq!(rx.pop(), Ok(4));
+        assert_eq!(rx.pop(), Err(TryRecvError::Disconnected));
+
+        let (mut tx, rx) = channel(1);
+        drop(rx);
+        assert!(matches!(tx.push(()), Err(TrySendError::Disconnected(()))));
+    }
+
+    #[test]
+    fn drops_values_left_in_the_ring() {
+        struct Noisy(Rc<StdCell<usize>>);
+
+        impl Drop for Noisy {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Rc::new(StdCell::new(0));
+        let (mut tx, mut rx) = channel(3);
+        for _ in 0..3 {
+            assert!(tx.push(Noisy(drops.clone())).is_ok());
+        }
+        drop(rx.pop());
+        drop((tx, rx));
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn keeps_order_across_threads() {
+        const N: u64 = 100_000;
+        let (mut tx, mut rx) = channel(16);
+        thread::scope(|s| {
+            s.spawn(move || {
+                for i in 0..N {
+                    let mut value = i;
+                    while let Err(TrySendError::Full(v)) = tx.push(value) {
+                        value = v;
+                        thread::yield_now();
+                    }
+                }
+            });
+            let mut expected = 0;
+            loop {
+                match rx.pop() {
+                    Ok(value) => {
+                        assert_eq!(value, expected);
+                        expected += 1;
+                    }
+                    Err(TryRecvError::Empty) => thread::yield_now(),
+                    Err(TryRecvError::Disconnected) => break,
+                }
+            }
+            assert_eq!(expected, N);
+        });
+    }
+}
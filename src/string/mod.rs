@@ -0,0 +1,637 @@
+use alloc::collections::TryReserveError;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::hash::{Hash, Hasher};
+use core::ops::{self, Bound, RangeBounds};
+use core::str::{FromStr, Utf8Error};
+use core::{error, fmt, ptr, slice, str};
+
+use crate::my_vec::MyVec;
+
+/// A growable UTF-8 string, built on [`MyVec<u8>`].
+///
+/// Every method keeps the bytes valid UTF-8: positions are byte offsets and the
+/// methods taking one panic if it falls inside a character, the same way
+/// [`String`] does.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::string::MyString;
+///
+/// let mut s = MyString::from("grüß");
+/// s.push_str(" dich");
+/// s.insert(0, '¡');
+/// assert_eq!(s, "¡grüß dich");
+/// assert_eq!(s.chars().count(), 10);
+///
+/// assert_eq!(s.slice(2..8), "grüß");
+/// assert_eq!(s.get(2..5), None); // `ü` starts at 4 and ends at 6
+/// assert_eq!(s.remove(0), '¡');
+/// assert_eq!(s.pop(), Some('h'));
+/// ```
+#[derive(Default)]
+pub struct MyString {
+    // Always valid UTF-8.
+    vec: MyVec<u8>,
+}
+
+/// The error returned by [`MyString::from_utf8`], holding the rejected bytes.
+#[derive(Debug)]
+pub struct FromUtf8Error {
+    bytes: MyVec<u8>,
+    error: Utf8Error,
+}
+
+impl FromUtf8Error {
+    /// The bytes that were passed to [`MyString::from_utf8`].
+    pub fn into_bytes(self) -> MyVec<u8> {
+        self.bytes
+    }
+
+    /// Details about where the bytes stop being valid UTF-8.
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+}
+
+impl fmt::Display for FromUtf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl error::Error for FromUtf8Error {}
+
+impl MyString {
+    /// Create a new, empty string. No memory is allocated.
+    pub const fn new() -> Self {
+        MyString { vec: MyVec::new() }
+    }
+
+    /// Create a new, empty string with room for `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        MyString {
+            vec: MyVec::with_capacity(capacity),
+        }
+    }
+
+    /// Create a new, empty string with room for `capacity` bytes, returning an
+    /// error if the memory can't be allocated.
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        Ok(MyString {
+            vec: MyVec::try_with_capacity(capacity)?,
+        })
+    }
+
+    /// Convert a vector of bytes to a string, checking that they are valid UTF-8.
+    pub fn from_utf8(vec: MyVec<u8>) -> Result<Self, FromUtf8Error> {
+        match str::from_utf8(&vec) {
+            Ok(_) => Ok(MyString { vec }),
+            Err(error) => Err(FromUtf8Error { bytes: vec, error }),
+        }
+    }
+
+    /// Convert the string into its vector of bytes.
+    pub fn into_bytes(self) -> MyVec<u8> {
+        self.vec
+    }
+
+    /// Length of the string in bytes.
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Return `true` if the string is empty.
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Number of bytes the string can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    /// Reserve room for at least `additional` more bytes.
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional);
+    }
+
+    /// Reserve room for exactly `additional` more bytes.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.vec.reserve_exact(additional);
+    }
+
+    /// Reserve room for at least `additional` more bytes, returning an error if
+    /// the memory can't be allocated.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.vec.try_reserve(additional)
+    }
+
+    /// Reserve room for exactly `additional` more bytes, returning an error if
+    /// the memory can't be allocated.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.vec.try_reserve_exact(additional)
+    }
+
+    /// Extract a string slice of the whole string.
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the bytes are always valid UTF-8.
+        unsafe { str::from_utf8_unchecked(&self.vec) }
+    }
+
+    /// Extract a mutable string slice of the whole string.
+    pub fn as_mut_str(&mut self) -> &mut str {
+        let len = self.len();
+        // SAFETY: the bytes are always valid UTF-8, and `str` keeps them that way.
+        unsafe {
+            str::from_utf8_unchecked_mut(slice::from_raw_parts_mut(self.vec.as_mut_ptr(), len))
+        }
+    }
+
+    /// The bytes of the string.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.vec
+    }
+
+    /// Append a character to the end of the string.
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Append a string slice to the end of the string.
+    pub fn push_str(&mut self, s: &str) {
+        let len = self.len();
+        self.insert_bytes(len, s.as_bytes());
+    }
+
+    /// Remove the last character and return it, or [`None`] if the string is empty.
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.chars().next_back()?;
+        let new_len = self.len() - ch.len_utf8();
+        unsafe {
+            self.vec.set_len(new_len);
+        }
+        Some(ch)
+    }
+
+    /// Shorten the string to `new_len` bytes.
+    /// Has no effect if `new_len` is greater than the current length.
+    /// Panics if `new_len` doesn't lie on a char boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len() {
+            assert!(
+                self.is_char_boundary(new_len),
+                "new_len (is {new_len}) should lie on a char boundary"
+            );
+            unsafe {
+                self.vec.set_len(new_len);
+            }
+        }
+    }
+
+    /// Remove every character. The capacity is kept.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Insert a character at byte position `index`.
+    /// Panics if `index` is greater than the length or doesn't lie on a char boundary.
+    pub fn insert(&mut self, index: usize, ch: char) {
+        self.insert_str(index, ch.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Insert a string slice at byte position `index`.
+    /// Panics if `index` is greater than the length or doesn't lie on a char boundary.
+    pub fn insert_str(&mut self, index: usize, s: &str) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+        assert!(
+            self.is_char_boundary(index),
+            "insertion index (is {index}) should lie on a char boundary"
+        );
+        self.insert_bytes(index, s.as_bytes());
+    }
+
+    // Insert `bytes` at `index`, which must be a char boundary.
+    fn insert_bytes(&mut self, index: usize, bytes: &[u8]) {
+        let len = self.len();
+        self.vec.reserve(bytes.len());
+        unsafe {
+            let p = self.vec.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(bytes.len()), len - index);
+            ptr::copy_nonoverlapping(bytes.as_ptr(), p, bytes.len());
+            self.vec.set_len(len + bytes.len());
+        }
+    }
+
+    /// Remove the character at byte position `index` and return it.
+    /// Panics if `index` is not less than the length or doesn't lie on a char boundary.
+    pub fn remove(&mut self, index: usize) -> char {
+        let len = self.len();
+        assert!(
+            index < len,
+            "removal index (is {index}) should be < len (is {len})"
+        );
+        assert!(
+            self.is_char_boundary(index),
+            "removal index (is {index}) should lie on a char boundary"
+        );
+        let ch = self.as_str()[index..].chars().next().unwrap();
+        let next = index + ch.len_utf8();
+        unsafe {
+            let p = self.vec.as_mut_ptr();
+            ptr::copy(p.add(next), p.add(index), len - next);
+            self.vec.set_len(len - ch.len_utf8());
+        }
+        ch
+    }
+
+    // Resolve a range of byte positions against the length of the string.
+    fn bounds(&self, range: impl RangeBounds<usize>) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => i + 1,
+            Bound::Excluded(&i) => i,
+            Bound::Unbounded => self.len(),
+        };
+        (start, end)
+    }
+
+    /// The substring between the byte positions of `range`, or [`None`] if they
+    /// are out of bounds or inside a character.
+    pub fn get(&self, range: impl RangeBounds<usize>) -> Option<&str> {
+        let (start, end) = self.bounds(range);
+        self.as_str().get(start..end)
+    }
+
+    /// The substring between the byte positions of `range`.
+    /// Panics if they are out of bounds, out of order or inside a character.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> &str {
+        let (start, end) = self.bounds(range);
+        let len = self.len();
+        assert!(
+            end <= len,
+            "range end (is {end}) should be <= len (is {len})"
+        );
+        assert!(
+            start <= end,
+            "range start (is {start}) should be <= range end (is {end})"
+        );
+        for index in [start, end] {
+            assert!(
+                self.is_char_boundary(index),
+                "byte index (is {index}) should lie on a char boundary"
+            );
+        }
+        &self.as_str()[start..end]
+    }
+}
+
+impl ops::Deref for MyString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl ops::DerefMut for MyString {
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl AsRef<str> for MyString {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl AsRef<[u8]> for MyString {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl Borrow<str> for MyString {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl Clone for MyString {
+    fn clone(&self) -> Self {
+        MyString::from(self.as_str())
+    }
+}
+
+impl fmt::Debug for MyString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for MyString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Write for MyString {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl PartialEq for MyString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for MyString {}
+
+impl PartialEq<str> for MyString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for MyString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for MyString {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MyString {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+// Hash like `str`, so that `Borrow<str>` lookups work in hash maps.
+impl Hash for MyString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl ops::Add<&str> for MyString {
+    type Output = MyString;
+
+    fn add(mut self, other: &str) -> MyString {
+        self.push_str(other);
+        self
+    }
+}
+
+impl ops::AddAssign<&str> for MyString {
+    fn add_assign(&mut self, other: &str) {
+        self.push_str(other);
+    }
+}
+
+impl From<&str> for MyString {
+    fn from(s: &str) -> Self {
+        let mut string = MyString::with_capacity(s.len());
+        string.push_str(s);
+        string
+    }
+}
+
+impl From<char> for MyString {
+    fn from(ch: char) -> Self {
+        MyString::from(ch.encode_utf8(&mut [0; 4]) as &str)
+    }
+}
+
+impl From<String> for MyString {
+    /// Take over the bytes of a `String`, without copying them.
+    fn from(s: String) -> Self {
+        MyString {
+            vec: MyVec::from(s.into_bytes()),
+        }
+    }
+}
+
+impl From<MyString> for String {
+    /// Hand the bytes over to a `String`, without copying them.
+    fn from(s: MyString) -> Self {
+        // SAFETY: the bytes are always valid UTF-8.
+        unsafe { String::from_utf8_unchecked(s.vec.into()) }
+    }
+}
+
+impl FromStr for MyString {
+    type Err = core::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(MyString::from(s))
+    }
+}
+
+impl Extend<char> for MyString {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for ch in iter {
+            self.push(ch);
+        }
+    }
+}
+
+impl<'a> Extend<&'a str> for MyString {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.push_str(s);
+        }
+    }
+}
+
+impl FromIterator<char> for MyString {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut s = MyString::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<'a> FromIterator<&'a str> for MyString {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut s = MyString::new();
+        s.extend(iter);
+        s
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for MyString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MyString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(MyString::from)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for MyString {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&str>::arbitrary(u).map(MyString::from)
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&str>::arbitrary_take_rest(u).map(MyString::from)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for MyString {
+    type Parameters = <String as proptest::arbitrary::Arbitrary>::Parameters;
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::arbitrary::any_with::<String>(args)
+            .prop_map(MyString::from)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::ToString};
+
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn push_pop() {
+        let mut s = MyString::new();
+        for ch in "añ€😀".chars() {
+            s.push(ch);
+        }
+        assert_eq!(s.len(), 1 + 2 + 3 + 4);
+        assert_eq!(s.pop(), Some('😀'));
+        assert_eq!(s.pop(), Some('€'));
+        s += "!";
+        assert_eq!(s, "añ!");
+        s.clear();
+        assert_eq!(s.pop(), None);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut s = MyString::from("ace");
+        s.insert(1, 'b');
+        s.insert_str(3, "d€");
+        assert_eq!(s, "abcd€e");
+        assert_eq!(s.remove(4), '€');
+        assert_eq!(s.remove(0), 'a');
+        assert_eq!(s, "bcde");
+        let mut long = MyString::new();
+        for i in 0..100 {
+            long.insert(0, char::from(b'a' + (i % 26) as u8));
+        }
+        assert_eq!(long.len(), 100);
+        assert!(long.starts_with("vu"));
+    }
+
+    #[test]
+    fn slicing() {
+        let s = MyString::from("añb");
+        assert_eq!(s.slice(..), "añb");
+        assert_eq!(s.slice(1..=2), "ñ");
+        assert_eq!(s.get(1..2), None);
+        assert_eq!(s.get(..10), None);
+        assert_eq!(s.get(3..), Some("b"));
+    }
+
+    #[test]
+    #[should_panic(expected = "byte index (is 2) should lie on a char boundary")]
+    fn slice_inside_char() {
+        MyString::from("añb").slice(0..2);
+    }
+
+    #[test]
+    #[should_panic(expected = "removal index (is 2) should lie on a char boundary")]
+    fn remove_inside_char() {
+        MyString::from("añb").remove(2);
+    }
+
+    #[test]
+    fn from_utf8() {
+        let mut bytes = MyVec::new();
+        bytes.push(b'h');
+        bytes.push(b'i');
+        let s = MyString::from_utf8(bytes).unwrap();
+        assert_eq!(s, "hi");
+
+        let mut bytes = s.into_bytes();
+        bytes.push(0xff);
+        let err = MyString::from_utf8(bytes).unwrap_err();
+        assert_eq!(err.utf8_error().valid_up_to(), 2);
+        assert_eq!(&*err.into_bytes(), b"hi\xff");
+    }
+
+    #[test]
+    fn try_reserve() {
+        let mut s = MyString::try_with_capacity(4).unwrap();
+        s.push_str("abc");
+        s.try_reserve_exact(5).unwrap();
+        assert_eq!(s.capacity(), 8);
+        assert!(s.try_reserve(usize::MAX).is_err());
+        assert!(s.try_reserve_exact(isize::MAX as usize).is_err());
+        assert_eq!(s, "abc");
+    }
+
+    #[test]
+    fn traits() {
+        let mut s: MyString = "hello".chars().collect();
+        write!(s, " {}", 1 + 1).unwrap();
+        let s = s + "!";
+        assert_eq!(s.to_string(), "hello 2!");
+        assert_eq!(format!("{s:?}"), "\"hello 2!\"");
+        assert_eq!(String::from(s.clone()), "hello 2!");
+        assert!(MyString::from('a') < s);
+    }
+
+    #[test]
+    fn std_string() {
+        let mut s = MyString::from(String::from("grüß"));
+        s.push('!');
+        let s = String::from(s);
+        assert_eq!(s, "grüß!");
+        assert_eq!(MyString::from(s.clone()), s.as_str());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let s = MyString::from("héllo");
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, "\"héllo\"");
+        assert_eq!(serde_json::from_str::<MyString>(&json).unwrap(), s);
+    }
+}
@@ -0,0 +1,512 @@
+use alloc::{vec, vec::Vec};
+use core::{mem, ops::Range};
+
+use crate::codec::{self, Decode, DecodeError, Encode};
+
+const NONE: usize = usize::MAX;
+
+// Suffix array of `s`, whose values are all `<= upper`, by induced sorting (SA-IS).
+// Runs in O(n + upper).
+fn sa_is(s: &[usize], upper: usize) -> Vec<usize> {
+    let n = s.len();
+    match n {
+        0 => return Vec::new(),
+        1 => return vec![0],
+        2 => return if s[0] < s[1] { vec![0, 1] } else { vec![1, 0] },
+        _ => {}
+    }
+
+    // `ls[i]` is true if suffix `i` is S-type (smaller than suffix `i + 1`).
+    // The last suffix is L-type as it's larger than the implicit empty suffix.
+    let mut ls = vec![false; n];
+    for i in (0..n - 1).rev() {
+        ls[i] = if s[i] == s[i + 1] {
+            ls[i + 1]
+        } else {
+            s[i] < s[i + 1]
+        };
+    }
+
+    // Start of the S-type and L-type part of every character's bucket.
+    let mut sum_l = vec![0; upper + 1];
+    let mut sum_s = vec![0; upper + 1];
+    for i in 0..n {
+        if ls[i] {
+            sum_l[s[i] + 1] += 1;
+        } else {
+            sum_s[s[i]] += 1;
+        }
+    }
+    for i in 0..=upper {
+        sum_s[i] += sum_l[i];
+        if i < upper {
+            sum_l[i + 1] += sum_s[i];
+        }
+    }
+
+    let mut sa = vec![NONE; n];
+    let induce = |sa: &mut Vec<usize>, lms: &[usize]| {
+        sa.fill(NONE);
+        let mut buf = sum_s.clone();
+        for &d in lms {
+            if d != n {
+                sa[buf[s[d]]] = d;
+                buf[s[d]] += 1;
+            }
+        }
+        buf.copy_from_slice(&sum_l);
+        sa[buf[s[n - 1]]] = n - 1;
+        buf[s[n - 1]] += 1;
+        for i in 0..n {
+            let v = sa[i];
+            if v != NONE && v >= 1 && !ls[v - 1] {
+                sa[buf[s[v - 1]]] = v - 1;
+                buf[s[v - 1]] += 1;
+            }
+        }
+        buf.copy_from_slice(&sum_l);
+        for i in (0..n).rev() {
+            let v = sa[i];
+            if v != NONE && v >= 1 && ls[v - 1] {
+                buf[s[v - 1] + 1] -= 1;
+                sa[buf[s[v - 1] + 1]] = v - 1;
+            }
+        }
+    };
+
+    // Leftmost S-type positions, and the rank of each among them.
+    let lms: Vec<usize> = (1..n).filter(|&i| !ls[i - 1] && ls[i]).collect();
+    let mut lms_map = vec![NONE; n + 1];
+    for (rank, &i) in lms.iter().enumerate() {
+        lms_map[i] = rank;
+    }
+    let m = lms.len();
+    induce(&mut sa, &lms);
+
+    if m > 0 {
+        // Name the LMS substrings in sorted order and sort them recursively.
+        let mut sorted_lms: Vec<usize> =
+            sa.iter().copied().filter(|&v| lms_map[v] != NONE).collect();
+        let mut rec_s = vec![0; m];
+        let mut rec_upper = 0;
+        for i in 1..m {
+            let (mut l, mut r) = (sorted_lms[i - 1], sorted_lms[i]);
+            let end_l = lms.get(lms_map[l] + 1).copied().unwrap_or(n);
+            let end_r = lms.get(lms_map[r] + 1).copied().unwrap_or(n);
+            let mut same = end_l - l == end_r - r;
+            if same {
+                while l < end_l && s[l] == s[r] {
+                    l += 1;
+                    r += 1;
+                }
+                if l == n || s[l] != s[r] {
+                    same = false;
+                }
+            }
+            if !same {
+                rec_upper += 1;
+            }
+            rec_s[lms_map[sorted_lms[i]]] = rec_upper;
+        }
+        let rec_sa = sa_is(&rec_s, rec_upper);
+        for (sorted, &i) in sorted_lms.iter_mut().zip(&rec_sa) {
+            *sorted = lms[i];
+        }
+        induce(&mut sa, &sorted_lms);
+    }
+    sa
+}
+
+// LCP array by Kasai's algorithm: `lcp[i]` is the length of the longest common
+// prefix of the suffixes `sa[i - 1]` and `sa[i]`, and `lcp[0]` is 0.
+fn kasai(text: &[u8], sa: &[usize]) -> Vec<usize> {
+    let n = text.len();
+    let mut rank = vec![0; n];
+    for (i, &p) in sa.iter().enumerate() {
+        rank[p] = i;
+    }
+    let mut lcp = vec![0; n];
+    let mut h: usize = 0;
+    for i in 0..n {
+        h = h.saturating_sub(1);
+        if rank[i] == 0 {
+            h = 0;
+            continue;
+        }
+        let j = sa[rank[i] - 1];
+        while i + h < n && j + h < n && text[i + h] == text[j + h] {
+            h += 1;
+        }
+        lcp[rank[i]] = h;
+    }
+    lcp
+}
+
+/// The suffix array and LCP array of a byte string.
+///
+/// The suffix array lists the starting positions of all suffixes of the text in
+/// lexicographic order. It is built in linear time with the SA-IS algorithm, and
+/// the LCP array with Kasai's algorithm. Once built, every occurrence of a pattern
+/// can be found with two binary searches in O(m log n).
+///
+/// # Examples
+///
+/// ```
+/// use my_std::suffix_array::SuffixArray;
+///
+/// let sa = SuffixArray::new(b"banana");
+/// assert_eq!(sa.suffix_array(), &[5, 3, 1, 0, 4, 2]);
+/// assert_eq!(sa.lcp(), &[0, 1, 3, 0, 0, 2]);
+/// assert_eq!(sa.find(b"ana"), vec![1, 3]);
+/// assert_eq!(sa.longest_repeated_substring(), b"ana");
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize))]
+pub struct SuffixArray {
+    text: Vec<u8>,
+    sa: Vec<usize>,
+    // Rebuilt from the other two when deserializing, like when decoding.
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    lcp: Vec<usize>,
+}
+
+// Range of `sa` whose suffixes start with `pattern`, where `position` reads a
+// suffix's start out of `sa`. A start past the end of the text reads as an
+// empty suffix, as an archived array may hold one.
+fn pattern_range<P>(
+    text: &[u8],
+    sa: &[P],
+    position: impl Fn(&P) -> usize,
+    pattern: &[u8],
+) -> Range<usize> {
+    let suffix = |p: &P| text.get(position(p)..).unwrap_or_default();
+    let start = sa.partition_point(|p| suffix(p) < pattern);
+    let len = sa[start..].partition_point(|p| suffix(p).starts_with(pattern));
+    start..start + len
+}
+
+impl SuffixArray {
+    /// Build the suffix array and LCP array of `text`.
+    pub fn new(text: impl AsRef<[u8]>) -> Self {
+        let text = text.as_ref().to_vec();
+        let s: Vec<usize> = text.iter().map(|&b| b as usize).collect();
+        let sa = sa_is(&s, u8::MAX as usize);
+        let lcp = kasai(&text, &sa);
+        SuffixArray { text, sa, lcp }
+    }
+
+    /// The indexed text.
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Length of the text.
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    /// Return `true` if the text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Starting positions of the suffixes of the text, in lexicographic order.
+    pub fn suffix_array(&self) -> &[usize] {
+        &self.sa
+    }
+
+    /// `lcp()[i]` is the length of the longest common prefix of the suffixes
+    /// starting at `suffix_array()[i - 1]` and `suffix_array()[i]`. The first
+    /// entry is 0.
+    pub fn lcp(&self) -> &[usize] {
+        &self.lcp
+    }
+
+    fn range(&self, pattern: &[u8]) -> Range<usize> {
+        pattern_range(&self.text, &self.sa, |&p| p, pattern)
+    }
+
+    /// Every position where `pattern` occurs in the text, in increasing order.
+    pub fn find(&self, pattern: &[u8]) -> Vec<usize> {
+        let mut positions = self.sa[self.range(pattern)].to_vec();
+        positions.sort_unstable();
+        positions
+    }
+
+    /// Number of occurrences of `pattern` in the text.
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.range(pattern).len()
+    }
+
+    /// Return `true` if `pattern` occurs in the text.
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        !self.range(pattern).is_empty()
+    }
+
+    /// The longest substring that occurs at least twice, the first one in
+    /// lexicographic order if there are several.
+    pub fn longest_repeated_substring(&self) -> &[u8] {
+        match self
+            .lcp
+            .iter()
+            .enumerate()
+            .max_by_key(|&(i, len)| (len, usize::MAX - i))
+        {
+            Some((i, &len)) => &self.text[self.sa[i]..self.sa[i] + len],
+            None => &[],
+        }
+    }
+}
+
+impl SuffixArray {
+    /// Encode the index in the [`codec`] format: a version
+    /// byte, the text and then the suffix array. The LCP array is rebuilt
+    /// when decoding, in linear time.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        codec::to_bytes(self)
+    }
+
+    /// Decode an index written by [`SuffixArray::to_bytes`], without sorting
+    /// the suffixes again.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        codec::from_bytes(bytes)
+    }
+}
+
+impl Encode for SuffixArray {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.text.encode(out);
+        self.sa.encode(out);
+    }
+}
+
+// Return `true` if `sa` holds each of `0..n` exactly once.
+fn is_permutation(sa: &[usize], n: usize) -> bool {
+    let mut seen = vec![false; n];
+    sa.len() == n
+        && sa
+            .iter()
+            .all(|&p| p < n && !mem::replace(&mut seen[p], true))
+}
+
+impl Decode for SuffixArray {
+    /// Only checks that the suffix array is a permutation of the text's
+    /// positions. If it is not sorted too, searches give wrong answers but do
+    /// not panic.
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let text = Vec::<u8>::decode(input)?;
+        let sa = Vec::<usize>::decode(input)?;
+        if !is_permutation(&sa, text.len()) {
+            return Err(DecodeError::Invalid("suffix array"));
+        }
+        let lcp = kasai(&text, &sa);
+        Ok(SuffixArray { text, sa, lcp })
+    }
+}
+
+/// The archive of a [`SuffixArray`] answers pattern queries in place, so a
+/// large prebuilt index can be memory-mapped and searched without
+/// deserializing. It holds the text and the suffix array, not the LCP array.
+#[cfg(feature = "rkyv")]
+impl ArchivedSuffixArray {
+    /// The indexed text.
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Length of the text.
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    /// Return `true` if the text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    fn range(&self, pattern: &[u8]) -> Range<usize> {
+        pattern_range(&self.text, &self.sa, |p| p.to_native() as usize, pattern)
+    }
+
+    /// Every position where `pattern` occurs in the text, in increasing order.
+    pub fn find(&self, pattern: &[u8]) -> Vec<usize> {
+        let mut positions: Vec<usize> = self.sa[self.range(pattern)]
+            .iter()
+            .map(|p| p.to_native() as usize)
+            .collect();
+        positions.sort_unstable();
+        positions
+    }
+
+    /// Number of occurrences of `pattern` in the text.
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.range(pattern).len()
+    }
+
+    /// Return `true` if `pattern` occurs in the text.
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        !self.range(pattern).is_empty()
+    }
+}
+
+/// Deserializing checks the suffix array like [`SuffixArray::from_bytes`] does,
+/// and rebuilds the LCP array in linear time.
+#[cfg(feature = "rkyv")]
+impl<D> rkyv::Deserialize<SuffixArray, D> for ArchivedSuffixArray
+where
+    D: rkyv::rancor::Fallible + ?Sized,
+    D::Error: rkyv::rancor::Source,
+{
+    fn deserialize(&self, _: &mut D) -> Result<SuffixArray, D::Error> {
+        let text = self.text.to_vec();
+        let sa: Vec<usize> = self.sa.iter().map(|p| p.to_native() as usize).collect();
+        if !is_permutation(&sa, text.len()) {
+            return Err(rkyv::rancor::Source::new(DecodeError::Invalid(
+                "suffix array",
+            )));
+        }
+        let lcp = kasai(&text, &sa);
+        Ok(SuffixArray { text, sa, lcp })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SuffixArray {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&[u8]>::arbitrary(u).map(SuffixArray::new)
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&[u8]>::arbitrary_take_rest(u).map(SuffixArray::new)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for SuffixArray {
+    type Parameters = proptest::collection::SizeRange;
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    /// Texts over `a`, `b` and `c`, so that substrings repeat.
+    fn arbitrary_with(size: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::collection::vec(b'a'..=b'c', size)
+            .prop_map(SuffixArray::new)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive(text: &[u8]) -> Vec<usize> {
+        let mut sa: Vec<usize> = (0..text.len()).collect();
+        sa.sort_by_key(|&i| &text[i..]);
+        sa
+    }
+
+    #[test]
+    fn matches_naive() {
+        let texts: [&[u8]; 7] = [
+            b"",
+            b"a",
+            b"ab",
+            b"aaaaaaa",
+            b"mississippi",
+            b"abracadabra",
+            b"the quick brown fox jumps over the lazy dog the end",
+        ];
+        for text in texts {
+            assert_eq!(SuffixArray::new(text).suffix_array(), naive(text));
+        }
+        // A pseudo-random text over a small alphabet recurses several times.
+        let mut x = 12345u32;
+        let text: Vec<u8> = (0..5000)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                b'a' + (x % 3) as u8
+            })
+            .collect();
+        assert_eq!(SuffixArray::new(&text).suffix_array(), naive(&text));
+    }
+
+    #[test]
+    fn lcp() {
+        let sa = SuffixArray::new(b"mississippi");
+        let text = sa.text();
+        for i in 1..sa.len() {
+            let (a, b) = (
+                &text[sa.suffix_array()[i - 1]..],
+                &text[sa.suffix_array()[i]..],
+            );
+            let expected = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+            assert_eq!(sa.lcp()[i], expected);
+        }
+        assert_eq!(sa.longest_repeated_substring(), b"issi");
+    }
+
+    #[test]
+    fn find() {
+        let sa = SuffixArray::new("abracadabra");
+        assert_eq!(sa.find(b"abra"), vec![0, 7]);
+        assert_eq!(sa.find(b"a"), vec![0, 3, 5, 7, 10]);
+        assert_eq!(sa.find(b"cad"), vec![4]);
+        assert_eq!(sa.find(b"zzz"), Vec::<usize>::new());
+        assert_eq!(sa.find(b"abracadabrax"), Vec::<usize>::new());
+        assert_eq!(sa.count(b"bra"), 2);
+        assert!(sa.contains(b"dab"));
+        assert!(!sa.contains(b"bad"));
+        assert_eq!(sa.count(b""), 11);
+    }
+
+    #[test]
+    fn empty() {
+        let sa = SuffixArray::new(b"");
+        assert!(sa.is_empty());
+        assert_eq!(sa.find(b"a"), Vec::<usize>::new());
+        assert_eq!(sa.longest_repeated_substring(), b"");
+    }
+
+    #[test]
+    fn bytes() {
+        let sa = SuffixArray::new(b"mississippi");
+        let decoded = SuffixArray::from_bytes(&sa.to_bytes()).unwrap();
+        assert_eq!(decoded.text(), sa.text());
+        assert_eq!(decoded.suffix_array(), sa.suffix_array());
+        assert_eq!(decoded.lcp(), sa.lcp());
+        assert_eq!(decoded.find(b"ssi"), [2, 5]);
+
+        let empty = SuffixArray::from_bytes(&SuffixArray::new(b"").to_bytes()).unwrap();
+        assert!(empty.is_empty());
+        // "ab" with suffix 0 listed twice.
+        assert_eq!(
+            SuffixArray::from_bytes(&[codec::VERSION, 2, b'a', b'b', 2, 0, 0]).unwrap_err(),
+            DecodeError::Invalid("suffix array")
+        );
+        assert_eq!(
+            SuffixArray::from_bytes(&[codec::VERSION, 1, b'a', 1, 1]).unwrap_err(),
+            DecodeError::Invalid("suffix array")
+        );
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn rkyv_round_trip() {
+        use rkyv::rancor::Error;
+
+        let sa = SuffixArray::new("mississippi");
+        let bytes = rkyv::to_bytes::<Error>(&sa).unwrap();
+        let archived = rkyv::access::<ArchivedSuffixArray, Error>(&bytes).unwrap();
+        assert_eq!(archived.text(), b"mississippi");
+        assert_eq!(archived.find(b"ss"), sa.find(b"ss"));
+        assert_eq!(archived.count(b"i"), 4);
+        assert!(archived.contains(b"sip"));
+        assert!(!archived.contains(b"spi"));
+        let back: SuffixArray = rkyv::deserialize::<_, Error>(archived).unwrap();
+        assert_eq!(back.suffix_array(), sa.suffix_array());
+        assert_eq!(back.lcp(), sa.lcp());
+    }
+}
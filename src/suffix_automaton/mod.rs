@@ -0,0 +1,253 @@
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+
+#[derive(Debug, Clone)]
+struct State {
+    // Length of the longest string in this state.
+    len: usize,
+    // Suffix link: the state of the longest suffix that belongs to another state.
+    // The initial state has none.
+    link: Option<usize>,
+    next: BTreeMap<u8, usize>,
+}
+
+/// The suffix automaton of a byte string.
+///
+/// It is the smallest automaton accepting every substring of the text, with at
+/// most `2n - 1` states. It is built online in O(n log σ) and answers substring
+/// queries in time proportional to the query alone.
+///
+/// It complements [`SuffixArray`](crate::suffix_array::SuffixArray), which is
+/// better at listing occurrences, while the automaton is better at questions
+/// about the set of distinct substrings.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::suffix_automaton::SuffixAutomaton;
+///
+/// let sam = SuffixAutomaton::new(b"abcbc");
+/// assert!(sam.contains(b"cbc"));
+/// assert!(!sam.contains(b"cc"));
+/// assert_eq!(sam.count_distinct_substrings(), 12);
+/// assert_eq!(sam.longest_common_substring(b"xxbcbyy"), b"bcb");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SuffixAutomaton {
+    states: Vec<State>,
+    // State of the whole text read so far.
+    last: usize,
+}
+
+impl Default for SuffixAutomaton {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl SuffixAutomaton {
+    /// Create the automaton of the empty string.
+    pub fn empty() -> Self {
+        SuffixAutomaton {
+            states: vec![State {
+                len: 0,
+                link: None,
+                next: BTreeMap::new(),
+            }],
+            last: 0,
+        }
+    }
+
+    /// Build the automaton of `text`.
+    pub fn new(text: impl AsRef<[u8]>) -> Self {
+        let mut sam = Self::empty();
+        for &c in text.as_ref() {
+            sam.push(c);
+        }
+        sam
+    }
+
+    /// Number of states of the automaton.
+    pub fn num_states(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Append a byte to the text.
+    pub fn push(&mut self, c: u8) {
+        let cur = self.states.len();
+        self.states.push(State {
+            len: self.states[self.last].len + 1,
+            link: None,
+            next: BTreeMap::new(),
+        });
+        // Walk the suffix links adding the new transition until a state already has one.
+        let mut p = Some(self.last);
+        while let Some(state) = p {
+            if self.states[state].next.contains_key(&c) {
+                break;
+            }
+            self.states[state].next.insert(c, cur);
+            p = self.states[state].link;
+        }
+        self.states[cur].link = Some(match p {
+            None => 0,
+            Some(p) => {
+                let q = self.states[p].next[&c];
+                if self.states[p].len + 1 == self.states[q].len {
+                    q
+                } else {
+                    // `q` holds strings of different lengths: split off the shorter ones.
+                    let clone = self.states.len();
+                    self.states.push(State {
+                        len: self.states[p].len + 1,
+                        link: self.states[q].link,
+                        next: self.states[q].next.clone(),
+                    });
+                    let mut p = Some(p);
+                    while let Some(state) = p {
+                        if self.states[state].next.get(&c) != Some(&q) {
+                            break;
+                        }
+                        self.states[state].next.insert(c, clone);
+                        p = self.states[state].link;
+                    }
+                    self.states[q].link = Some(clone);
+                    clone
+                }
+            }
+        });
+        self.last = cur;
+    }
+
+    /// Return `true` if `pattern` is a substring of the text.
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        let mut state = 0;
+        for c in pattern {
+            match self.states[state].next.get(c) {
+                Some(&next) => state = next,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Number of distinct non-empty substrings of the text.
+    pub fn count_distinct_substrings(&self) -> usize {
+        // Every state holds the strings whose length is in (len(link), len].
+        self.states
+            .iter()
+            .filter_map(|s| s.link.map(|link| s.len - self.states[link].len))
+            .sum()
+    }
+
+    /// The longest substring shared by the text and `other`, as a slice of `other`.
+    /// If there are several, the one ending first in `other` is returned.
+    pub fn longest_common_substring<'a>(&self, other: &'a [u8]) -> &'a [u8] {
+        let (mut state, mut len) = (0, 0);
+        let (mut best_len, mut best_end) = (0, 0);
+        for (i, c) in other.iter().enumerate() {
+            // Drop characters from the front of the match until it can be extended.
+            while state != 0 && !self.states[state].next.contains_key(c) {
+                state = self.states[state].link.unwrap();
+                len = self.states[state].len;
+            }
+            if let Some(&next) = self.states[state].next.get(c) {
+                state = next;
+                len += 1;
+            }
+            if len > best_len {
+                best_len = len;
+                best_end = i + 1;
+            }
+        }
+        &other[best_end - best_len..best_end]
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SuffixAutomaton {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&[u8]>::arbitrary(u).map(SuffixAutomaton::new)
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&[u8]>::arbitrary_take_rest(u).map(SuffixAutomaton::new)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for SuffixAutomaton {
+    type Parameters = proptest::collection::SizeRange;
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    /// Texts over `a`, `b` and `c`, so that substrings repeat.
+    fn arbitrary_with(size: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+
+        proptest::collection::vec(b'a'..=b'c', size)
+            .prop_map(SuffixAutomaton::new)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn substrings(text: &[u8]) -> HashSet<&[u8]> {
+        (0..text.len())
+            .flat_map(|i| (i + 1..=text.len()).map(move |j| &text[i..j]))
+            .collect()
+    }
+
+    #[test]
+    fn contains() {
+        let text = b"abracadabra";
+        let sam = SuffixAutomaton::new(text);
+        for sub in substrings(text) {
+            assert!(sam.contains(sub));
+        }
+        assert!(sam.contains(b""));
+        assert!(!sam.contains(b"abrab"));
+        assert!(!sam.contains(b"z"));
+        assert!(sam.num_states() < 2 * text.len());
+    }
+
+    #[test]
+    fn count_distinct_substrings() {
+        for text in [
+            &b""[..],
+            b"a",
+            b"aaaa",
+            b"banana",
+            b"mississippi",
+            b"abcabcabc",
+        ] {
+            let sam = SuffixAutomaton::new(text);
+            assert_eq!(sam.count_distinct_substrings(), substrings(text).len());
+        }
+    }
+
+    #[test]
+    fn online() {
+        let mut sam = SuffixAutomaton::empty();
+        for &c in b"abab" {
+            sam.push(c);
+        }
+        assert!(sam.contains(b"bab"));
+        assert_eq!(sam.count_distinct_substrings(), 7);
+    }
+
+    #[test]
+    fn longest_common_substring() {
+        let sam = SuffixAutomaton::new(b"the quick brown fox");
+        assert_eq!(sam.longest_common_substring(b"a brown dog"), b" brown ");
+        assert_eq!(sam.longest_common_substring(b"xyz"), b"x");
+        assert_eq!(sam.longest_common_substring(b"JKL"), b"");
+        assert_eq!(
+            SuffixAutomaton::new(b"").longest_common_substring(b"abc"),
+            b""
+        );
+    }
+}
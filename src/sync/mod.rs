@@ -0,0 +1,2429 @@
+use std::{
+    cell::UnsafeCell,
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    panic::{self, AssertUnwindSafe, RefUnwindSafe, UnwindSafe},
+    ptr::NonNull,
+    sync::{LockResult, PoisonError, TryLockError, TryLockResult},
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    arc::MyArc,
+    compat::{
+        self,
+        atomic::{AtomicBool, AtomicPtr, AtomicU8, AtomicUsize, Ordering::*},
+        const_unless_loom,
+        thread::{park, park_timeout},
+    },
+    concurrent::epoch,
+    thread_pool::ThreadPool,
+};
+
+// Exponential backoff for spin loops: each failed attempt spins twice as long
+// as the last, up to a limit, so contending threads stop hammering the cache
+// line they all want.
+pub(crate) struct Backoff {
+    step: u32,
+}
+
+impl Backoff {
+    const MAX_STEP: u32 = 6;
+
+    pub(crate) const fn new() -> Self {
+        Backoff { step: 0 }
+    }
+
+    pub(crate) fn spin(&mut self) {
+        // Under loom every spin is a point to switch threads at, so spinning
+        // more than once only multiplies the schedules to explore.
+        let spins = if cfg!(loom) { 1 } else { 1 << self.step };
+        for _ in 0..spins {
+            compat::spin_loop();
+        }
+        if self.step < Self::MAX_STEP {
+            self.step += 1;
+        }
+    }
+}
+
+/// A lock that busy-waits instead of parking the thread.
+///
+/// Waiting threads spin with exponential backoff until the lock is free, so a
+/// `SpinLock` never calls into the OS scheduler. That suits critical sections
+/// of a few instructions, and targets without threads to park. For anything
+/// longer, or when the holder may be descheduled, a [`Mutex`] wastes far less
+/// CPU. There is no poisoning: a panic while holding the lock just releases
+/// it.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::sync::SpinLock;
+///
+/// let hits = SpinLock::new([0u32; 4]);
+/// thread::scope(|s| {
+///     for i in 0..4 {
+///         let hits = &hits;
+///         s.spawn(move || hits.lock()[i] += 1);
+///     }
+/// });
+/// assert_eq!(hits.into_inner(), [1; 4]);
+/// ```
+pub struct SpinLock<T: ?Sized> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for SpinLock<T> {}
+unsafe impl<T: ?Sized + Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    const_unless_loom! {
+        /// Create a new, unlocked lock.
+        pub fn new(value: T) -> Self {
+            SpinLock {
+                locked: AtomicBool::new(false),
+                value: UnsafeCell::new(value),
+            }
+        }
+    }
+
+    /// Consume the lock and return the value.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> SpinLock<T> {
+    /// Spin until the lock is free, then take it.
+    ///
+    /// Locking it again from the thread that holds it never returns.
+    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+        let mut backoff = Backoff::new();
+        loop {
+            if let Some(guard) = self.try_lock() {
+                return guard;
+            }
+            // Wait for it to look free before trying again, reading the flag
+            // instead of writing it.
+            while self.locked.load(Relaxed) {
+                backoff.spin();
+            }
+        }
+    }
+
+    /// Take the lock if it is free, without spinning.
+    pub fn try_lock(&self) -> Option<SpinLockGuard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Acquire, Relaxed)
+            .ok()
+            .map(|_| SpinLockGuard {
+                lock: self,
+                _marker: PhantomData,
+            })
+    }
+
+    /// Return `true` if some thread holds the lock. By the time it returns,
+    /// that may have changed.
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Relaxed)
+    }
+
+    /// A mutable reference to the value. No locking is needed, since the
+    /// borrow proves no other thread can hold the lock.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: Default> Default for SpinLock<T> {
+    fn default() -> Self {
+        SpinLock::new(T::default())
+    }
+}
+
+impl<T> From<T> for SpinLock<T> {
+    fn from(value: T) -> Self {
+        SpinLock::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for SpinLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("SpinLock");
+        match self.try_lock() {
+            Some(guard) => d.field("data", &&*guard),
+            None => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish_non_exhaustive()
+    }
+}
+
+/// Access to the value of a locked [`SpinLock`]. The lock is released when the
+/// guard is dropped.
+#[must_use = "if unused the SpinLock will immediately unlock"]
+pub struct SpinLockGuard<'a, T: ?Sized> {
+    lock: &'a SpinLock<T>,
+    // Sharing or sending the guard shares or sends a `&mut T`.
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T: ?Sized> Drop for SpinLockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Release);
+    }
+}
+
+impl<T: ?Sized> Deref for SpinLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for SpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for SpinLockGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for SpinLockGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+// A thread blocked in a `WaitQueue`. It lives on the waiting thread's stack.
+struct Waiter {
+    thread: compat::thread::Thread,
+    woken: AtomicBool,
+}
+
+// How a wait on a `WaitQueue` ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Wait {
+    // The condition said not to wait.
+    Skipped,
+    Woken,
+    TimedOut,
+}
+
+// Threads parked until another thread wakes them, in arrival order.
+//
+// The queue itself is guarded by a spin lock, which is only ever held for a
+// push or a pop.
+pub(crate) struct WaitQueue {
+    waiters: SpinLock<VecDeque<NonNull<Waiter>>>,
+}
+
+// The `Waiter`s are only touched under the queue lock, or by the thread that
+// popped them.
+unsafe impl Send for WaitQueue {}
+unsafe impl Sync for WaitQueue {}
+
+impl WaitQueue {
+    const_unless_loom! {
+        pub(crate) fn new() -> Self {
+            WaitQueue {
+                waiters: SpinLock::new(VecDeque::new()),
+            }
+        }
+    }
+
+    fn with_queue<R>(&self, f: impl FnOnce(&mut VecDeque<NonNull<Waiter>>) -> R) -> R {
+        f(&mut self.waiters.lock())
+    }
+
+    // Park the current thread until it is woken, if `should_wait` returns
+    // `true`. `should_wait` runs under the queue lock, so a wake-up sent after
+    // it can't be missed. Return whether the thread waited.
+    pub(crate) fn wait_if(&self, should_wait: impl FnOnce() -> bool) -> bool {
+        self.wait(None, should_wait) != Wait::Skipped
+    }
+
+    // Like `wait_if`, but give up waiting at `deadline`.
+    pub(crate) fn wait_if_until(
+        &self,
+        deadline: Instant,
+        should_wait: impl FnOnce() -> bool,
+    ) -> Wait {
+        self.wait(Some(deadline), should_wait)
+    }
+
+    fn wait(&self, deadline: Option<Instant>, should_wait: impl FnOnce() -> bool) -> Wait {
+        let waiter = Waiter {
+            thread: compat::thread::current(),
+            woken: AtomicBool::new(false),
+        };
+        let ptr = NonNull::from(&waiter);
+        let queued = self.with_queue(|queue| {
+            let queued = should_wait();
+            if queued {
+                queue.push_back(ptr);
+            }
+            queued
+        });
+        if !queued {
+            return Wait::Skipped;
+        }
+        // `park` may return spuriously.
+        while !waiter.woken.load(Acquire) {
+            let Some(deadline) = deadline else {
+                park();
+                continue;
+            };
+            let now = Instant::now();
+            if now < deadline {
+                park_timeout(deadline - now);
+                continue;
+            }
+            let removed = self.with_queue(|queue| {
+                let index = queue.iter().position(|&w| w == ptr);
+                index.and_then(|i| queue.remove(i)).is_some()
+            });
+            if removed {
+                return Wait::TimedOut;
+            }
+            // A waker already popped us, and is about to set `woken`. We can't
+            // return before it does, since `waiter` lives in this frame.
+            while !waiter.woken.load(Acquire) {
+                compat::spin_loop();
+            }
+        }
+        Wait::Woken
+    }
+
+    // Wake the longest waiting thread, if any. Return whether there was one.
+    pub(crate) fn wake_one(&self) -> bool {
+        match self.with_queue(|queue| queue.pop_front()) {
+            Some(waiter) => {
+                unsafe { Self::wake(waiter) };
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Wake every waiting thread.
+    pub(crate) fn wake_all(&self) {
+        for waiter in self.with_queue(mem::take) {
+            unsafe { Self::wake(waiter) };
+        }
+    }
+
+    // `waiter` must have been popped from the queue.
+    unsafe fn wake(waiter: NonNull<Waiter>) {
+        // The waiter may return, and its stack frame go away, as soon as
+        // `woken` is set, so take the thread handle out first.
+        let thread = waiter.as_ref().thread.clone();
+        waiter.as_ref().woken.store(true, Release);
+        thread.unpark();
+    }
+}
+
+// A 32-bit atomic that threads can sleep on until another thread changes it
+// and wakes them.
+//
+// On Linux this is the futex system call: the kernel keeps the sleeping
+// threads, keyed by the word's address, and waking a word nobody sleeps on is
+// cheap. Elsewhere, and under loom, which can't follow a thread into the
+// kernel, threads park on a `WaitQueue` next to the word.
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+    not(loom)
+))]
+mod futex {
+    use std::{ffi::c_long, ops::Deref, ptr, sync::atomic::AtomicU32};
+
+    #[cfg(target_arch = "x86_64")]
+    const SYS_FUTEX: c_long = 202;
+    #[cfg(target_arch = "aarch64")]
+    const SYS_FUTEX: c_long = 98;
+    // The `_PRIVATE` operations: the word is never shared with another process.
+    const FUTEX_WAIT: c_long = 128;
+    const FUTEX_WAKE: c_long = 128 | 1;
+
+    extern "C" {
+        fn syscall(number: c_long, ...) -> c_long;
+    }
+
+    pub(crate) struct Futex {
+        word: AtomicU32,
+    }
+
+    impl Futex {
+        pub(crate) const fn new(value: u32) -> Self {
+            Futex {
+                word: AtomicU32::new(value),
+            }
+        }
+
+        // Sleep while the word is `expected`, until woken. The kernel checks
+        // the word and queues the thread atomically, so a wake-up sent after
+        // the word changed can't be missed. May return spuriously.
+        pub(crate) fn wait(&self, expected: u32) {
+            let null = ptr::null::<()>();
+            unsafe { syscall(SYS_FUTEX, self.word.as_ptr(), FUTEX_WAIT, expected, null) };
+        }
+
+        // Wake one thread sleeping in `wait`, if any.
+        pub(crate) fn wake_one(&self) {
+            unsafe { syscall(SYS_FUTEX, self.word.as_ptr(), FUTEX_WAKE, 1) };
+        }
+    }
+
+    impl Deref for Futex {
+        type Target = AtomicU32;
+
+        fn deref(&self) -> &AtomicU32 {
+            &self.word
+        }
+    }
+}
+
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+    not(loom)
+)))]
+mod futex {
+    use std::ops::Deref;
+
+    use super::WaitQueue;
+    use crate::compat::{
+        atomic::{AtomicU32, Ordering::Relaxed},
+        const_unless_loom,
+    };
+
+    pub(crate) struct Futex {
+        word: AtomicU32,
+        waiters: WaitQueue,
+    }
+
+    impl Futex {
+        const_unless_loom! {
+            pub(crate) fn new(value: u32) -> Self {
+                Futex {
+                    word: AtomicU32::new(value),
+                    waiters: WaitQueue::new(),
+                }
+            }
+        }
+
+        // Sleep while the word is `expected`, until woken. The check runs
+        // under the queue lock, which waking takes too, so a wake-up sent
+        // after the word changed can't be missed. May return spuriously.
+        pub(crate) fn wait(&self, expected: u32) {
+            self.waiters.wait_if(|| self.word.load(Relaxed) == expected);
+        }
+
+        // Wake one thread sleeping in `wait`, if any.
+        pub(crate) fn wake_one(&self) {
+            self.waiters.wake_one();
+        }
+    }
+
+    impl Deref for Futex {
+        type Target = AtomicU32;
+
+        fn deref(&self) -> &AtomicU32 {
+            &self.word
+        }
+    }
+}
+
+pub(crate) use futex::Futex;
+
+// `value`, as an error if the lock it came from is poisoned.
+fn poison_check<T>(poisoned: bool, value: T) -> LockResult<T> {
+    if poisoned {
+        Err(PoisonError::new(value))
+    } else {
+        Ok(value)
+    }
+}
+
+// States of a `Mutex`.
+const UNLOCKED: u32 = 0;
+const LOCKED: u32 = 1;
+// Locked, and other threads may be sleeping on it.
+const CONTENDED: u32 = 2;
+
+/// A mutual exclusion lock protecting a value shared between threads.
+///
+/// [`lock`](Mutex::lock) blocks until the lock is free. A thread that finds it
+/// taken spins briefly, in case it is about to be released, then sleeps until
+/// the holder wakes it on unlock: on a futex on Linux, so the kernel keeps the
+/// queue, and parked on a queue of its own elsewhere. Taking and releasing an
+/// uncontended lock is a single atomic operation each, and the holder only
+/// makes a system call to wake a thread it knows sleeps. The lock is released
+/// when the returned guard is dropped.
+///
+/// If a thread panics while holding the lock, the mutex is *poisoned*: the
+/// value may have been left half updated, so later `lock` calls return an
+/// `Err`. The error still carries the guard, for callers that can repair or
+/// ignore the damage.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::sync::Mutex;
+///
+/// let total = Mutex::new(0);
+/// thread::scope(|s| {
+///     for i in 1..=4 {
+///         let total = &total;
+///         s.spawn(move || *total.lock().unwrap() += i);
+///     }
+/// });
+/// assert_eq!(total.into_inner().unwrap(), 10);
+/// ```
+pub struct Mutex<T: ?Sized> {
+    state: Futex,
+    poisoned: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// Only one thread at a time reaches the value, so it only has to be `Send`.
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+// Poisoning already reports a value a panic may have broken.
+impl<T: ?Sized> UnwindSafe for Mutex<T> {}
+impl<T: ?Sized> RefUnwindSafe for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    const_unless_loom! {
+        /// Create a new, unlocked mutex.
+        pub fn new(value: T) -> Self {
+            Mutex {
+                state: Futex::new(UNLOCKED),
+                poisoned: AtomicBool::new(false),
+                value: UnsafeCell::new(value),
+            }
+        }
+    }
+
+    /// Consume the mutex and return the value, or an error holding it if the
+    /// mutex is poisoned.
+    pub fn into_inner(self) -> LockResult<T> {
+        let poisoned = self.is_poisoned();
+        poison_check(poisoned, self.value.into_inner())
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Block until the lock is free, then take it.
+    ///
+    /// Returns an error holding the guard if the mutex is poisoned. Locking a
+    /// mutex again from the thread that holds it never returns.
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        if self
+            .state
+            .compare_exchange(UNLOCKED, LOCKED, Acquire, Relaxed)
+            .is_err()
+        {
+            self.lock_contended();
+        }
+        MutexGuard::new(self)
+    }
+
+    /// Take the lock if it is free, without blocking.
+    ///
+    /// Fails with [`TryLockError::WouldBlock`] if the lock is taken, and with
+    /// [`TryLockError::Poisoned`] holding the guard if the mutex is poisoned.
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        if self
+            .state
+            .compare_exchange(UNLOCKED, LOCKED, Acquire, Relaxed)
+            .is_err()
+        {
+            return Err(TryLockError::WouldBlock);
+        }
+        Ok(MutexGuard::new(self)?)
+    }
+
+    #[cold]
+    fn lock_contended(&self) {
+        let mut state = self.spin();
+        if state == UNLOCKED {
+            match self
+                .state
+                .compare_exchange(UNLOCKED, LOCKED, Acquire, Relaxed)
+            {
+                Ok(_) => return,
+                Err(actual) => state = actual,
+            }
+        }
+        loop {
+            // Mark the lock as contended so the holder wakes a sleeper on
+            // unlock. Finding it unlocked means we took it, though as
+            // contended since other threads may still be sleeping.
+            if state != CONTENDED && self.state.swap(CONTENDED, Acquire) == UNLOCKED {
+                return;
+            }
+            self.state.wait(CONTENDED);
+            state = self.spin();
+        }
+    }
+
+    // Spin while the lock is held and nobody sleeps on it yet, as the holder
+    // may be about to release it. Sleeping and waking cost a system call each,
+    // far more than a short critical section.
+    fn spin(&self) -> u32 {
+        let mut backoff = Backoff::new();
+        for _ in 0..10 {
+            let state = self.state.load(Relaxed);
+            if state != LOCKED {
+                return state;
+            }
+            backoff.spin();
+        }
+        self.state.load(Relaxed)
+    }
+
+    fn unlock(&self) {
+        if self.state.swap(UNLOCKED, Release) == CONTENDED {
+            self.state.wake_one();
+        }
+    }
+
+    /// Return `true` if a thread panicked while holding the lock.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Relaxed)
+    }
+
+    /// Mark the value as sound again after a panic poisoned the mutex.
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Relaxed);
+    }
+
+    /// A mutable reference to the value. No locking is needed, since the
+    /// borrow proves no other thread can hold the lock.
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        let poisoned = self.is_poisoned();
+        poison_check(poisoned, self.value.get_mut())
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Mutex::new(T::default())
+    }
+}
+
+impl<T> From<T> for Mutex<T> {
+    fn from(value: T) -> Self {
+        Mutex::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Mutex");
+        match self.try_lock() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(TryLockError::Poisoned(err)) => d.field("data", &&**err.get_ref()),
+            Err(TryLockError::WouldBlock) => d.field("data", &format_args!("<locked>")),
+        };
+        d.field("poisoned", &self.is_poisoned())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Access to the value of a locked [`Mutex`]. The lock is released when the
+/// guard is dropped.
+#[must_use = "if unused the Mutex will immediately unlock"]
+pub struct MutexGuard<'a, T: ?Sized> {
+    lock: &'a Mutex<T>,
+    // Whether the thread was already panicking when it took the lock, in which
+    // case dropping the guard during that panic doesn't poison the mutex.
+    panicking: bool,
+    // The lock must be released by the thread that took it.
+    _not_send: PhantomData<*const ()>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
+
+impl<'a, T: ?Sized> MutexGuard<'a, T> {
+    fn new(lock: &'a Mutex<T>) -> LockResult<Self> {
+        let guard = MutexGuard {
+            lock,
+            panicking: thread::panicking(),
+            _not_send: PhantomData,
+        };
+        poison_check(lock.is_poisoned(), guard)
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        if !self.panicking && thread::panicking() {
+            self.lock.poisoned.store(true, Relaxed);
+        }
+        self.lock.unlock();
+    }
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+/// Whether a timed wait on a [`Condvar`] returned because it timed out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    /// Return `true` if the wait timed out.
+    pub fn timed_out(&self) -> bool {
+        self.0
+    }
+}
+
+/// A condition variable: a place for threads to sleep until the value behind a
+/// [`Mutex`] changes.
+///
+/// A waiting thread hands its guard to [`wait`](Condvar::wait), which unlocks
+/// the mutex and parks the thread as one step, so a notification sent by a
+/// thread that locked the mutex afterwards can't be missed. The mutex is
+/// locked again before `wait` returns.
+///
+/// Wake-ups don't guarantee the condition the thread waits for holds: another
+/// thread may have got there first. Check it in a loop, or use
+/// [`wait_while`](Condvar::wait_while), which does.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::sync::{Condvar, Mutex};
+///
+/// let ready = Mutex::new(false);
+/// let changed = Condvar::new();
+/// thread::scope(|s| {
+///     s.spawn(|| {
+///         *ready.lock().unwrap() = true;
+///         changed.notify_one();
+///     });
+///     let guard = changed
+///         .wait_while(ready.lock().unwrap(), |ready| !*ready)
+///         .unwrap();
+///     assert!(*guard);
+/// });
+/// ```
+pub struct Condvar {
+    waiters: WaitQueue,
+}
+
+impl Condvar {
+    const_unless_loom! {
+        /// Create a condition variable with no waiting thread.
+        pub fn new() -> Self {
+            Condvar {
+                waiters: WaitQueue::new(),
+            }
+        }
+    }
+
+    /// Unlock the mutex of `guard` and block until notified, then lock it
+    /// again. May also return without a notification.
+    ///
+    /// Returns an error holding the guard if the mutex is poisoned.
+    pub fn wait<'a, T: ?Sized>(&self, guard: MutexGuard<'a, T>) -> LockResult<MutexGuard<'a, T>> {
+        let mutex = guard.lock;
+        // Unlock under the queue lock instead, once we are queued.
+        mem::forget(guard);
+        self.waiters.wait_if(|| {
+            mutex.unlock();
+            true
+        });
+        mutex.lock()
+    }
+
+    /// Block while `condition` returns `true` for the value, waiting on the
+    /// condition variable between checks.
+    pub fn wait_while<'a, T: ?Sized, F>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        mut condition: F,
+    ) -> LockResult<MutexGuard<'a, T>>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        while condition(&mut *guard) {
+            guard = self.wait(guard)?;
+        }
+        Ok(guard)
+    }
+
+    /// Like [`wait`](Condvar::wait), but give up after `timeout`.
+    pub fn wait_timeout<'a, T: ?Sized>(
+        &self,
+        guard: MutexGuard<'a, T>,
+        timeout: Duration,
+    ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
+        let Some(deadline) = Instant::now().checked_add(timeout) else {
+            // Too far away to tell from forever.
+            return map_guard(self.wait(guard), |guard| (guard, WaitTimeoutResult(false)));
+        };
+        let mutex = guard.lock;
+        mem::forget(guard);
+        let wait = self.waiters.wait_if_until(deadline, || {
+            mutex.unlock();
+            true
+        });
+        let result = WaitTimeoutResult(wait == Wait::TimedOut);
+        map_guard(mutex.lock(), |guard| (guard, result))
+    }
+
+    /// Like [`wait_while`](Condvar::wait_while), but give up after `timeout`.
+    /// The result tells whether the condition still held then.
+    pub fn wait_timeout_while<'a, T: ?Sized, F>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        timeout: Duration,
+        mut condition: F,
+    ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let start = Instant::now();
+        while condition(&mut *guard) {
+            let Some(left) = timeout.checked_sub(start.elapsed()) else {
+                return Ok((guard, WaitTimeoutResult(true)));
+            };
+            guard = self.wait_timeout(guard, left)?.0;
+        }
+        Ok((guard, WaitTimeoutResult(false)))
+    }
+
+    /// Wake one thread waiting on the condition variable, if any.
+    pub fn notify_one(&self) {
+        self.waiters.wake_one();
+    }
+
+    /// Wake every thread waiting on the condition variable.
+    pub fn notify_all(&self) {
+        self.waiters.wake_all();
+    }
+}
+
+// Apply `f` to the guard in `result`, whether the mutex is poisoned or not.
+fn map_guard<G, U>(result: LockResult<G>, f: impl FnOnce(G) -> U) -> LockResult<U> {
+    match result {
+        Ok(guard) => Ok(f(guard)),
+        Err(err) => Err(PoisonError::new(f(err.into_inner()))),
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Condvar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Condvar").finish_non_exhaustive()
+    }
+}
+
+struct BarrierState {
+    // Threads waiting in the current generation.
+    count: usize,
+    generation: u64,
+}
+
+/// A rendezvous point where a group of threads waits until all of them have
+/// arrived.
+///
+/// Each call to [`wait`](Barrier::wait) blocks until `n` threads have made
+/// one, then releases them all together. The barrier then starts over, so the
+/// same one can separate every phase of a multi-step parallel algorithm.
+/// Exactly one thread of each group is told it is the leader, for work that
+/// must happen once per phase.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::thread;
+/// use my_std::sync::Barrier;
+///
+/// let barrier = Barrier::new(3);
+/// let leaders = AtomicUsize::new(0);
+/// thread::scope(|s| {
+///     for _ in 0..3 {
+///         s.spawn(|| {
+///             for _phase in 0..2 {
+///                 if barrier.wait().is_leader() {
+///                     leaders.fetch_add(1, Ordering::SeqCst);
+///                 }
+///             }
+///         });
+///     }
+/// });
+/// assert_eq!(leaders.load(Ordering::SeqCst), 2);
+/// ```
+pub struct Barrier {
+    state: Mutex<BarrierState>,
+    changed: Condvar,
+    n: usize,
+}
+
+/// What [`Barrier::wait`] returns: whether this thread is the group's leader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult(bool);
+
+impl BarrierWaitResult {
+    /// Return `true` for exactly one thread of each group released by the
+    /// barrier.
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+impl Barrier {
+    const_unless_loom! {
+        /// Create a barrier releasing groups of `n` threads. With `n` of 0 or 1,
+        /// `wait` never blocks.
+        pub fn new(n: usize) -> Self {
+            Barrier {
+                state: Mutex::new(BarrierState {
+                    count: 0,
+                    generation: 0,
+                }),
+                changed: Condvar::new(),
+                n,
+            }
+        }
+    }
+
+    /// Block until `n` threads are waiting, then release them all.
+    pub fn wait(&self) -> BarrierWaitResult {
+        // No user code runs under the lock, so it can't be poisoned.
+        let mut state = self.state.lock().unwrap();
+        state.count += 1;
+        if state.count < self.n {
+            let generation = state.generation;
+            // The generation changes once the group is complete, even if
+            // threads of the next one already arrived.
+            let _state = self
+                .changed
+                .wait_while(state, |state| state.generation == generation)
+                .unwrap();
+            BarrierWaitResult(false)
+        } else {
+            state.count = 0;
+            state.generation = state.generation.wrapping_add(1);
+            self.changed.notify_all();
+            BarrierWaitResult(true)
+        }
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Barrier")
+            .field("n", &self.n)
+            .finish_non_exhaustive()
+    }
+}
+
+// States of a `Once`.
+const ONCE_INCOMPLETE: u8 = 0;
+const ONCE_POISONED: u8 = 1;
+const ONCE_RUNNING: u8 = 2;
+const ONCE_COMPLETE: u8 = 3;
+
+/// A way to run a piece of initialization exactly once, for instance to fill
+/// in a global table.
+///
+/// The first [`call_once`](Once::call_once) runs its closure, and calls made
+/// meanwhile from other threads block until it is done. Once it has run, later
+/// calls return right away without running theirs.
+///
+/// If the closure panics, the `Once` is poisoned and later `call_once` calls
+/// panic too, since whatever it was setting up may be half done.
+/// [`call_once_force`](Once::call_once_force) runs its closure anyway, telling
+/// it about the poisoning so it can clean up.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::thread;
+/// use my_std::sync::Once;
+///
+/// static INIT: Once = Once::new();
+/// static RUNS: AtomicUsize = AtomicUsize::new(0);
+///
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| INIT.call_once(|| {
+///             RUNS.fetch_add(1, Ordering::SeqCst);
+///         }));
+///     }
+/// });
+/// assert!(INIT.is_completed());
+/// assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+/// ```
+pub struct Once {
+    state: AtomicU8,
+    // Threads waiting for the running closure to finish.
+    waiters: WaitQueue,
+}
+
+/// Passed to the closure of [`Once::call_once_force`].
+#[derive(Debug)]
+pub struct OnceState {
+    poisoned: bool,
+}
+
+impl OnceState {
+    /// Return `true` if an earlier closure panicked.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+}
+
+// Sets the state of a `Once` when its closure returns or panics, and wakes the
+// threads waiting for it.
+struct Finish<'a> {
+    once: &'a Once,
+    state: u8,
+}
+
+impl Drop for Finish<'_> {
+    fn drop(&mut self) {
+        self.once.state.store(self.state, Release);
+        self.once.waiters.wake_all();
+    }
+}
+
+impl Once {
+    const_unless_loom! {
+        /// Create a `Once` whose closure hasn't run yet.
+        pub fn new() -> Self {
+            Once {
+                state: AtomicU8::new(ONCE_INCOMPLETE),
+                waiters: WaitQueue::new(),
+            }
+        }
+    }
+
+    /// Run `f` if no closure has completed on this `Once` yet, blocking while
+    /// another thread runs one.
+    ///
+    /// Panics if an earlier closure panicked. Calling it from `f` on the same
+    /// `Once` never returns.
+    pub fn call_once<F: FnOnce()>(&self, f: F) {
+        // Fast path, without building the closure.
+        if self.is_completed() {
+            return;
+        }
+        let mut f = Some(f);
+        self.call(false, &mut |_| f.take().unwrap()());
+    }
+
+    /// Like [`call_once`](Once::call_once), but also runs `f` if an earlier
+    /// closure panicked, instead of panicking.
+    pub fn call_once_force<F: FnOnce(&OnceState)>(&self, f: F) {
+        if self.is_completed() {
+            return;
+        }
+        let mut f = Some(f);
+        self.call(true, &mut |state| f.take().unwrap()(state));
+    }
+
+    // Not generic, so the slow path is compiled only once.
+    fn call(&self, ignore_poison: bool, f: &mut dyn FnMut(&OnceState)) {
+        loop {
+            let state = self.state.load(Acquire);
+            match state {
+                ONCE_COMPLETE => return,
+                ONCE_POISONED if !ignore_poison => {
+                    panic!("Once instance has previously been poisoned")
+                }
+                ONCE_INCOMPLETE | ONCE_POISONED => {
+                    if self
+                        .state
+                        .compare_exchange(state, ONCE_RUNNING, Acquire, Acquire)
+                        .is_err()
+                    {
+                        continue;
+                    }
+                    // Poisons the `Once` if `f` panics.
+                    let mut finish = Finish {
+                        once: self,
+                        state: ONCE_POISONED,
+                    };
+                    f(&OnceState {
+                        poisoned: state == ONCE_POISONED,
+                    });
+                    finish.state = ONCE_COMPLETE;
+                    return;
+                }
+                _ => {
+                    self.waiters
+                        .wait_if(|| self.state.load(Acquire) == ONCE_RUNNING);
+                }
+            }
+        }
+    }
+
+    /// Return `true` if a closure has completed. Never blocks.
+    pub fn is_completed(&self) -> bool {
+        // `Acquire` so what the closure did is visible.
+        self.state.load(Acquire) == ONCE_COMPLETE
+    }
+}
+
+// Poisoning covers a panic in the closure.
+impl UnwindSafe for Once {}
+impl RefUnwindSafe for Once {}
+
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Once {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Once")
+            .field("completed", &self.is_completed())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A shared slot holding a [`MyArc`], which readers load without locking while
+/// writers swap in a new one.
+///
+/// Suits data read far more often than it changes, such as configuration or a
+/// routing table: a writer builds a new version, often cheaply from the old one
+/// with a persistent structure like [`HamtMap`](crate::hamt::HamtMap), and
+/// stores it, while readers keep using whichever version they loaded.
+///
+/// [`load`](AtomicArc::load) pins the thread with
+/// [`epoch`] just long enough to read the pointer and
+/// take a reference, so it never waits for a writer. Writers hand the reference
+/// they replaced to the epoch collector, which drops it once no load can still
+/// be taking a reference through it.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::arc::MyArc;
+/// use my_std::hamt::HamtMap;
+/// use my_std::sync::AtomicArc;
+///
+/// let routes = AtomicArc::new(MyArc::new(HamtMap::new().insert("/", "index")));
+/// thread::scope(|s| {
+///     s.spawn(|| {
+///         routes.rcu(|old| old.insert("/about", "about"));
+///     });
+///     // Readers see either version, never a half-updated one.
+///     let snapshot = routes.load();
+///     assert_eq!(snapshot.get("/"), Some(&"index"));
+/// });
+/// assert_eq!(routes.load().get("/about"), Some(&"about"));
+/// ```
+pub struct AtomicArc<T> {
+    // From `MyArc::into_raw`, owning one strong reference.
+    ptr: AtomicPtr<T>,
+    _marker: PhantomData<MyArc<T>>,
+}
+
+unsafe impl<T: Send + Sync> Send for AtomicArc<T> {}
+unsafe impl<T: Send + Sync> Sync for AtomicArc<T> {}
+
+impl<T> AtomicArc<T> {
+    /// Create a slot holding `value`.
+    pub fn new(value: MyArc<T>) -> Self {
+        AtomicArc {
+            ptr: AtomicPtr::new(MyArc::into_raw(value).cast_mut()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Consume the slot and return the value it holds.
+    pub fn into_inner(self) -> MyArc<T> {
+        let this = mem::ManuallyDrop::new(self);
+        unsafe { MyArc::from_raw(this.ptr.load(Relaxed)) }
+    }
+}
+
+impl<T: Send + Sync + 'static> AtomicArc<T> {
+    /// A reference to the value held now.
+    pub fn load(&self) -> MyArc<T> {
+        let _guard = epoch::pin();
+        let ptr = self.ptr.load(Acquire);
+        // The reference the slot owns isn't dropped while we are pinned, so
+        // the count can't drop to zero before ours is added.
+        let held = mem::ManuallyDrop::new(unsafe { MyArc::from_raw(ptr) });
+        MyArc::clone(&held)
+    }
+
+    /// Replace the value with `value`.
+    pub fn store(&self, value: MyArc<T>) {
+        drop(self.swap(value));
+    }
+
+    /// Replace the value with `value`, and return the old one.
+    pub fn swap(&self, value: MyArc<T>) -> MyArc<T> {
+        let guard = epoch::pin();
+        let old = self.ptr.swap(MyArc::into_raw(value).cast_mut(), AcqRel);
+        self.retire(&guard, old)
+    }
+
+    /// Replace the value with `f` applied to it, retrying if another thread
+    /// replaced it meanwhile, and return the value `f` was applied to.
+    ///
+    /// `f` may run several times, so it should have no side effects.
+    pub fn rcu<F: FnMut(&T) -> T>(&self, mut f: F) -> MyArc<T> {
+        loop {
+            let current = self.load();
+            let new = MyArc::into_raw(MyArc::new(f(&current))).cast_mut();
+            let guard = epoch::pin();
+            let expected = MyArc::as_ptr(&current).cast_mut();
+            match self.ptr.compare_exchange(expected, new, AcqRel, Acquire) {
+                Ok(old) => {
+                    // `old` is `current`, so hand back the reference we hold.
+                    drop(self.retire(&guard, old));
+                    return current;
+                }
+                Err(_) => drop(unsafe { MyArc::from_raw(new) }),
+            }
+        }
+    }
+
+    // Take over the reference `old` held by the slot: return a new one, and
+    // drop the slot's once no `load` can still be cloning through it.
+    fn retire(&self, guard: &epoch::Guard, old: *mut T) -> MyArc<T> {
+        let old = unsafe { MyArc::from_raw(old) };
+        let returned = MyArc::clone(&old);
+        guard.defer(move || drop(old));
+        returned
+    }
+}
+
+impl<T> Drop for AtomicArc<T> {
+    fn drop(&mut self) {
+        // No `load` can be running, since we have the slot to ourselves.
+        drop(unsafe { MyArc::from_raw(self.ptr.load(Relaxed)) });
+    }
+}
+
+impl<T> From<MyArc<T>> for AtomicArc<T> {
+    fn from(value: MyArc<T>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Default> Default for AtomicArc<T> {
+    fn default() -> Self {
+        Self::new(MyArc::default())
+    }
+}
+
+impl<T: fmt::Debug + Send + Sync + 'static> fmt::Debug for AtomicArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AtomicArc").field(&self.load()).finish()
+    }
+}
+
+// Reader count of a `RwLock` held for writing.
+const WRITE_LOCKED: usize = usize::MAX;
+
+/// A readers-writer lock: any number of threads may read the value at once,
+/// or a single thread may write it.
+///
+/// The lock prefers writers. Once a writer is waiting, new readers queue up
+/// behind it instead of joining the readers already inside, so a steady stream
+/// of readers can't keep a writer out forever. The flip side is that a thread
+/// taking a second read lock while it already holds one can deadlock if a
+/// writer arrives in between.
+///
+/// Like [`Mutex`], the lock is poisoned if a thread panics while writing.
+/// Panicking while reading can't leave the value half updated, so it doesn't
+/// poison the lock.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use my_std::sync::RwLock;
+///
+/// let config = RwLock::new(vec!["a"]);
+/// thread::scope(|s| {
+///     s.spawn(|| config.write().unwrap().push("b"));
+///     s.spawn(|| assert!(config.read().unwrap().contains(&"a")));
+/// });
+/// assert_eq!(*config.read().unwrap(), ["a", "b"]);
+/// ```
+pub struct RwLock<T: ?Sized> {
+    // Number of readers inside, or `WRITE_LOCKED`.
+    state: AtomicUsize,
+    // Writers blocked in `write`. Readers wait while it isn't zero.
+    writers_waiting: AtomicUsize,
+    poisoned: AtomicBool,
+    readers: WaitQueue,
+    writers: WaitQueue,
+    value: UnsafeCell<T>,
+}
+
+// Readers on several threads share `&T`, so `T` must be `Sync` as well.
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+impl<T: ?Sized> UnwindSafe for RwLock<T> {}
+impl<T: ?Sized> RefUnwindSafe for RwLock<T> {}
+
+impl<T> RwLock<T> {
+    const_unless_loom! {
+        /// Create a new, unlocked lock.
+        pub fn new(value: T) -> Self {
+            RwLock {
+                state: AtomicUsize::new(0),
+                writers_waiting: AtomicUsize::new(0),
+                poisoned: AtomicBool::new(false),
+                readers: WaitQueue::new(),
+                writers: WaitQueue::new(),
+                value: UnsafeCell::new(value),
+            }
+        }
+    }
+
+    /// Consume the lock and return the value, or an error holding it if the
+    /// lock is poisoned.
+    pub fn into_inner(self) -> LockResult<T> {
+        let poisoned = self.is_poisoned();
+        poison_check(poisoned, self.value.into_inner())
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Block until no writer holds or waits for the lock, then take it for
+    /// reading.
+    ///
+    /// Returns an error holding the guard if the lock is poisoned.
+    pub fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
+        while !self.try_add_reader() {
+            // Checked under the queue lock, so the wake-up of the writer that
+            // unlocks after it can't be missed.
+            self.readers.wait_if(|| {
+                self.state.load(SeqCst) == WRITE_LOCKED || self.writers_waiting.load(SeqCst) > 0
+            });
+        }
+        poison_check(self.is_poisoned(), RwLockReadGuard::new(self))
+    }
+
+    /// Take the lock for reading if no writer holds or waits for it, without
+    /// blocking.
+    pub fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, T>> {
+        if !self.try_add_reader() {
+            return Err(TryLockError::WouldBlock);
+        }
+        Ok(poison_check(
+            self.is_poisoned(),
+            RwLockReadGuard::new(self),
+        )?)
+    }
+
+    /// Block until no thread holds the lock, then take it for writing.
+    ///
+    /// Returns an error holding the guard if the lock is poisoned.
+    pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
+        if !self.try_lock_writer() {
+            self.writers_waiting.fetch_add(1, SeqCst);
+            while !self.try_lock_writer() {
+                self.writers.wait_if(|| self.state.load(SeqCst) != 0);
+            }
+            // Readers still wait for us to unlock, which wakes them.
+            self.writers_waiting.fetch_sub(1, SeqCst);
+        }
+        poison_check(self.is_poisoned(), RwLockWriteGuard::new(self))
+    }
+
+    /// Take the lock for writing if no thread holds it, without blocking.
+    pub fn try_write(&self) -> TryLockResult<RwLockWriteGuard<'_, T>> {
+        if !self.try_lock_writer() {
+            return Err(TryLockError::WouldBlock);
+        }
+        Ok(poison_check(
+            self.is_poisoned(),
+            RwLockWriteGuard::new(self),
+        )?)
+    }
+
+    fn try_add_reader(&self) -> bool {
+        let mut state = self.state.load(Relaxed);
+        loop {
+            if state == WRITE_LOCKED || self.writers_waiting.load(SeqCst) > 0 {
+                return false;
+            }
+            assert!(state < WRITE_LOCKED - 1, "too many readers");
+            match self
+                .state
+                .compare_exchange_weak(state, state + 1, Acquire, Relaxed)
+            {
+                Ok(_) => return true,
+                Err(actual) => state = actual,
+            }
+        }
+    }
+
+    fn try_lock_writer(&self) -> bool {
+        self.state
+            .compare_exchange(0, WRITE_LOCKED, Acquire, Relaxed)
+            .is_ok()
+    }
+
+    fn read_unlock(&self) {
+        // `SeqCst` on both sides: either a waiting writer sees the lock free,
+        // or we see it counted and wake it.
+        if self.state.fetch_sub(1, SeqCst) == 1 && self.writers_waiting.load(SeqCst) > 0 {
+            self.writers.wake_one();
+        }
+    }
+
+    fn write_unlock(&self) {
+        self.state.store(0, SeqCst);
+        if self.writers_waiting.load(SeqCst) > 0 {
+            self.writers.wake_one();
+        } else {
+            self.readers.wake_all();
+        }
+    }
+
+    /// Return `true` if a thread panicked while holding the lock for writing.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Relaxed)
+    }
+
+    /// Mark the value as sound again after a panic poisoned the lock.
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Relaxed);
+    }
+
+    /// A mutable reference to the value. No locking is needed, since the
+    /// borrow proves no other thread can hold the lock.
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        let poisoned = self.is_poisoned();
+        poison_check(poisoned, self.value.get_mut())
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        RwLock::new(T::default())
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    fn from(value: T) -> Self {
+        RwLock::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        match self.try_read() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(TryLockError::Poisoned(err)) => d.field("data", &&**err.get_ref()),
+            Err(TryLockError::WouldBlock) => d.field("data", &format_args!("<locked>")),
+        };
+        d.field("poisoned", &self.is_poisoned())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Shared access to the value of a [`RwLock`] locked for reading. The lock is
+/// released when the guard is dropped.
+#[must_use = "if unused the RwLock will immediately unlock"]
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+    _not_send: PhantomData<*const ()>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for RwLockReadGuard<'_, T> {}
+
+impl<'a, T: ?Sized> RwLockReadGuard<'a, T> {
+    fn new(lock: &'a RwLock<T>) -> Self {
+        RwLockReadGuard {
+            lock,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.read_unlock();
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+/// Exclusive access to the value of a [`RwLock`] locked for writing. The lock
+/// is released when the guard is dropped.
+#[must_use = "if unused the RwLock will immediately unlock"]
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+    // Whether the thread was already panicking when it took the lock.
+    panicking: bool,
+    _not_send: PhantomData<*const ()>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for RwLockWriteGuard<'_, T> {}
+
+impl<'a, T: ?Sized> RwLockWriteGuard<'a, T> {
+    fn new(lock: &'a RwLock<T>) -> Self {
+        RwLockWriteGuard {
+            lock,
+            panicking: thread::panicking(),
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        if !self.panicking && thread::panicking() {
+            self.lock.poisoned.store(true, Relaxed);
+        }
+        self.lock.write_unlock();
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+/// A counting semaphore: a pool of permits that threads take and give back.
+///
+/// [`acquire`](Semaphore::acquire) blocks until a permit is free and returns it
+/// as a guard, which puts it back when dropped. With the permit count set to
+/// the number of tasks allowed to run at once, it caps the concurrency of a
+/// section, for instance how many jobs may hold a connection.
+///
+/// Waiters are woken together when permits come back, and whichever gets them
+/// first wins, so the semaphore isn't fair: a thread asking for many permits
+/// can be overtaken by threads asking for fewer.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::thread;
+/// use my_std::sync::Semaphore;
+///
+/// let slots = Semaphore::new(2);
+/// let running = AtomicUsize::new(0);
+/// thread::scope(|s| {
+///     for _ in 0..8 {
+///         s.spawn(|| {
+///             let _permit = slots.acquire();
+///             let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+///             assert!(now <= 2);
+///             running.fetch_sub(1, Ordering::SeqCst);
+///         });
+///     }
+/// });
+/// assert_eq!(slots.available_permits(), 2);
+/// ```
+pub struct Semaphore {
+    permits: AtomicUsize,
+    waiters: WaitQueue,
+}
+
+impl Semaphore {
+    const_unless_loom! {
+        /// Create a semaphore with `permits` free permits.
+        pub fn new(permits: usize) -> Self {
+            Semaphore {
+                permits: AtomicUsize::new(permits),
+                waiters: WaitQueue::new(),
+            }
+        }
+    }
+
+    /// Block until a permit is free, then take it.
+    pub fn acquire(&self) -> SemaphorePermit<'_> {
+        self.acquire_many(1)
+    }
+
+    /// Block until `n` permits are free, then take them all at once.
+    ///
+    /// Asking for more permits than the semaphore will ever have never
+    /// returns.
+    pub fn acquire_many(&self, n: usize) -> SemaphorePermit<'_> {
+        loop {
+            if let Some(permit) = self.try_acquire_many(n) {
+                return permit;
+            }
+            // Checked under the queue lock, so permits given back after it
+            // are sure to wake us.
+            self.waiters.wait_if(|| self.permits.load(SeqCst) < n);
+        }
+    }
+
+    /// Take a permit if one is free, without blocking.
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.try_acquire_many(1)
+    }
+
+    /// Take `n` permits if they are all free, without blocking.
+    pub fn try_acquire_many(&self, n: usize) -> Option<SemaphorePermit<'_>> {
+        self.permits
+            .fetch_update(Acquire, Relaxed, |permits| permits.checked_sub(n))
+            .ok()
+            .map(|_| SemaphorePermit {
+                semaphore: self,
+                permits: n,
+            })
+    }
+
+    /// Add `n` free permits, waking the threads waiting for them.
+    ///
+    /// Panics if the count would overflow.
+    pub fn add_permits(&self, n: usize) {
+        self.permits
+            .fetch_update(SeqCst, Relaxed, |permits| permits.checked_add(n))
+            .expect("semaphore permit count overflow");
+        self.waiters.wake_all();
+    }
+
+    /// Number of free permits. By the time it returns, other threads may have
+    /// changed that.
+    pub fn available_permits(&self) -> usize {
+        self.permits.load(Relaxed)
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("permits", &self.available_permits())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Permits taken from a [`Semaphore`], given back when the guard is dropped.
+#[must_use = "if unused the permits are given back immediately"]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+}
+
+impl SemaphorePermit<'_> {
+    /// Number of permits the guard holds.
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Drop the guard without giving the permits back, shrinking the
+    /// semaphore for good.
+    pub fn forget(self) {
+        mem::forget(self);
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.semaphore.add_permits(self.permits);
+    }
+}
+
+impl fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SemaphorePermit")
+            .field("permits", &self.permits)
+            .finish_non_exhaustive()
+    }
+}
+
+// State shared by a scope and the threads and tasks it started.
+struct ScopeData {
+    // Threads and tasks not finished yet.
+    running: AtomicUsize,
+    // Set when one of them panicked and nobody joined it to see the panic.
+    panicked: AtomicBool,
+    // The scope's own thread, woken when `running` drops to zero.
+    done: WaitQueue,
+}
+
+impl ScopeData {
+    fn started(&self) {
+        self.running.fetch_add(1, Relaxed);
+    }
+
+    fn finished(&self, panicked: bool) {
+        if panicked {
+            self.panicked.store(true, Relaxed);
+        }
+        // `Release` so the scope sees what the thread did.
+        if self.running.fetch_sub(1, Release) == 1 {
+            self.done.wake_all();
+        }
+    }
+}
+
+/// Run `f` with a [`Scope`] to start threads and pool tasks that may borrow
+/// from the current stack frame, and wait for all of them before returning.
+///
+/// Since nothing started in the scope outlives it, closures passed to
+/// [`Scope::spawn`] and [`Scope::execute`] don't need to be `'static`: they
+/// can borrow local data, such as a shared [`Mutex`] or a structure from
+/// [`concurrent`](crate::concurrent), instead of wrapping it in an
+/// [`MyArc`].
+///
+/// If `f` panics, the panic is resumed once everything finished. Otherwise,
+/// if a thread or task panicked and wasn't joined through its handle, `scope`
+/// panics.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::sync::{self, Mutex};
+/// use my_std::thread_pool::ThreadPool;
+///
+/// let words = ["apple", "fig", "pear", "plum"];
+/// let lengths = Mutex::new(Vec::new());
+/// let pool = ThreadPool::new(2);
+/// let total = sync::scope(|s| {
+///     for word in &words {
+///         let lengths = &lengths;
+///         s.execute(&pool, move || lengths.lock().unwrap().push(word.len()));
+///     }
+///     let handle = s.spawn(|| words.iter().map(|w| w.len()).sum::<usize>());
+///     handle.join().unwrap()
+/// });
+/// let mut lengths = lengths.into_inner().unwrap();
+/// lengths.sort();
+/// assert_eq!(lengths, [3, 4, 4, 5]);
+/// assert_eq!(total, 16);
+/// ```
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        data: MyArc::new(ScopeData {
+            running: AtomicUsize::new(0),
+            panicked: AtomicBool::new(false),
+            done: WaitQueue::new(),
+        }),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+    // Whatever happened, borrows handed out must end before we return.
+    let data = &*scope.data;
+    while data.done.wait_if(|| data.running.load(Acquire) > 0) {}
+    match result {
+        Err(payload) => panic::resume_unwind(payload),
+        Ok(_) if data.panicked.load(Relaxed) => panic!("a scoped thread panicked"),
+        Ok(value) => value,
+    }
+}
+
+/// Starts threads and pool tasks that can borrow from outside the scope, as
+/// passed by [`scope`].
+///
+/// `'scope` is the lifetime of the scope itself, which everything started in
+/// it lives within, and `'env` that of the data they may borrow.
+pub struct Scope<'scope, 'env: 'scope> {
+    data: MyArc<ScopeData>,
+    // Invariant in both lifetimes, so neither can be shrunk or stretched.
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope> Scope<'scope, '_> {
+    /// Start a thread running `f`, joined at the latest when the scope ends.
+    ///
+    /// Panics if the thread can't be created.
+    pub fn spawn<F, T>(&'scope self, f: F) -> ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        let packet = MyArc::new(Packet {
+            scope: self.data.clone(),
+            result: UnsafeCell::new(None),
+        });
+        let their_packet = packet.clone();
+        self.data.started();
+        let body = move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(f));
+            unsafe { *their_packet.result.get() = Some(result) };
+        };
+        // SAFETY: the scope waits for the thread, which drops its packet last
+        // thing, before any borrow in `f` or `T` can end.
+        let native = unsafe { thread::Builder::new().spawn_unchecked(body) }
+            .expect("failed to spawn a scoped thread");
+        ScopedJoinHandle {
+            native,
+            packet,
+            scope: PhantomData,
+        }
+    }
+
+    /// Queue `task` on `pool`, to be finished at the latest when the scope
+    /// ends.
+    ///
+    /// Blocks while the pool's queue is full. A panicking task makes the
+    /// scope panic, and isn't counted by [`ThreadPool::panicked`]. Running a
+    /// scope from a task of the same pool may deadlock, if every worker ends
+    /// up waiting for tasks queued behind it.
+    pub fn execute<F>(&'scope self, pool: &ThreadPool, task: F)
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        let data = self.data.clone();
+        self.data.started();
+        let task: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(task));
+            data.finished(result.is_err());
+        });
+        // SAFETY: the scope waits for the task to finish before any borrow
+        // in it can end.
+        let task: Box<dyn FnOnce() + Send> = unsafe { mem::transmute(task) };
+        pool.execute(task);
+    }
+}
+
+impl fmt::Debug for Scope<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scope")
+            .field("running", &self.data.running.load(Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+// Where a scoped thread leaves its result for its handle.
+struct Packet<T> {
+    scope: MyArc<ScopeData>,
+    result: UnsafeCell<Option<thread::Result<T>>>,
+}
+
+// The thread writes the result before it exits, and the handle only reads it
+// after joining the thread.
+unsafe impl<T: Send> Sync for Packet<T> {}
+
+impl<T> Drop for Packet<T> {
+    fn drop(&mut self) {
+        // Drop the result here, while whatever it borrows still lives. A
+        // panic nobody joined to see makes the scope panic.
+        let unjoined_panic = matches!(self.result.get_mut().take(), Some(Err(_)));
+        self.scope.finished(unjoined_panic);
+    }
+}
+
+/// Owned permission to join a thread started with [`Scope::spawn`].
+pub struct ScopedJoinHandle<'scope, T> {
+    native: thread::JoinHandle<()>,
+    packet: MyArc<Packet<T>>,
+    scope: PhantomData<&'scope ()>,
+}
+
+impl<T> ScopedJoinHandle<'_, T> {
+    /// Wait for the thread to finish, returning its result, or the payload
+    /// of its panic.
+    pub fn join(self) -> thread::Result<T> {
+        // The body catches panics, so the thread itself never panics.
+        let _ = self.native.join();
+        unsafe { (*self.packet.result.get()).take() }.unwrap()
+    }
+
+    /// The handle of the thread.
+    pub fn thread(&self) -> &Thread {
+        self.native.thread()
+    }
+
+    /// Return `true` if the thread finished running.
+    pub fn is_finished(&self) -> bool {
+        self.native.is_finished()
+    }
+}
+
+impl<T> fmt::Debug for ScopedJoinHandle<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopedJoinHandle").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic;
+    use std::time::Duration;
+
+    #[test]
+    fn try_lock_and_get_mut() {
+        let mut lock = Mutex::new(vec![1]);
+        {
+            let mut guard = lock.try_lock().unwrap();
+            guard.push(2);
+            assert!(matches!(lock.try_lock(), Err(TryLockError::WouldBlock)));
+            assert_eq!(
+                format!("{lock:?}"),
+                "Mutex { data: <locked>, poisoned: false, .. }"
+            );
+        }
+        lock.get_mut().unwrap().push(3);
+        assert_eq!(
+            format!("{lock:?}"),
+            "Mutex { data: [1, 2, 3], poisoned: false, .. }"
+        );
+        assert_eq!(lock.into_inner().unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn unsized_values() {
+        let lock: &Mutex<[u32]> = &Mutex::new([1, 2, 3]);
+        lock.lock().unwrap()[1] = 5;
+        assert_eq!(lock.lock().unwrap().iter().sum::<u32>(), 9);
+    }
+
+    #[test]
+    fn poisoned_by_panic() {
+        let lock = Mutex::new(1);
+        let result = panic::catch_unwind(|| {
+            let mut guard = lock.lock().unwrap();
+            *guard = 2;
+            panic!("boom");
+        });
+        assert!(result.is_err());
+        assert!(lock.is_poisoned());
+        let err = lock.lock().unwrap_err();
+        assert_eq!(**err.get_ref(), 2);
+        drop(err);
+        assert!(matches!(lock.try_lock(), Err(TryLockError::Poisoned(_))));
+
+        lock.clear_poison();
+        *lock.lock().unwrap() = 3;
+        assert_eq!(lock.into_inner().unwrap(), 3);
+    }
+
+    #[test]
+    fn waiters_are_woken() {
+        let lock = Mutex::new(0);
+        thread::scope(|s| {
+            let guard = lock.lock().unwrap();
+            let waiter = s.spawn(|| *lock.lock().unwrap() += 1);
+            // Give the waiter time to park.
+            thread::sleep(Duration::from_millis(20));
+            drop(guard);
+            waiter.join().unwrap();
+        });
+        assert_eq!(lock.into_inner().unwrap(), 1);
+    }
+
+    #[test]
+    fn contended_increments() {
+        // Not atomic, so lost updates would show up as a wrong total.
+        let lock = Mutex::new((0u64, 0u64));
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..10_000 {
+                        let mut guard = lock.lock().unwrap();
+                        guard.0 += 1;
+                        guard.1 = guard.0 * 2;
+                    }
+                });
+            }
+        });
+        assert_eq!(lock.into_inner().unwrap(), (80_000, 160_000));
+    }
+
+    #[test]
+    fn futex_wait_and_wake() {
+        let futex = Futex::new(0);
+        // The word isn't the expected value, so this returns at once.
+        futex.wait(1);
+        thread::scope(|s| {
+            s.spawn(|| {
+                while futex.load(Acquire) == 0 {
+                    futex.wait(0);
+                }
+            });
+            thread::sleep(Duration::from_millis(10));
+            futex.store(1, Release);
+            futex.wake_one();
+        });
+    }
+
+    #[test]
+    fn rw_lock_readers_share() {
+        let mut lock = RwLock::new(1);
+        {
+            let a = lock.read().unwrap();
+            let b = lock.try_read().unwrap();
+            assert_eq!(*a + *b, 2);
+            assert!(matches!(lock.try_write(), Err(TryLockError::WouldBlock)));
+        }
+        {
+            let mut w = lock.try_write().unwrap();
+            *w += 1;
+            assert!(matches!(lock.try_read(), Err(TryLockError::WouldBlock)));
+            assert_eq!(
+                format!("{lock:?}"),
+                "RwLock { data: <locked>, poisoned: false, .. }"
+            );
+        }
+        *lock.get_mut().unwrap() += 1;
+        assert_eq!(lock.into_inner().unwrap(), 3);
+    }
+
+    #[test]
+    fn rw_lock_poisoned_by_writer_panic() {
+        let lock = RwLock::new(1);
+        let _ = panic::catch_unwind(|| {
+            let _r = lock.read().unwrap();
+            panic!("reader");
+        });
+        assert!(!lock.is_poisoned());
+        let _ = panic::catch_unwind(|| {
+            let _w = lock.write().unwrap();
+            panic!("writer");
+        });
+        assert!(lock.is_poisoned());
+        assert_eq!(*lock.read().unwrap_err().into_inner(), 1);
+        lock.clear_poison();
+        assert!(lock.write().is_ok());
+    }
+
+    #[test]
+    fn rw_lock_writer_is_not_starved() {
+        let lock = RwLock::new(false);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                // Readers overlap each other, so the lock is never free of them
+                // unless new ones wait for the writer.
+                s.spawn(|| {
+                    while !*lock.read().unwrap() {
+                        thread::yield_now();
+                    }
+                });
+            }
+            thread::sleep(Duration::from_millis(10));
+            *lock.write().unwrap() = true;
+        });
+    }
+
+    #[test]
+    fn rw_lock_mixed_stress() {
+        // Writers keep both halves equal, so readers would see a torn update.
+        let lock = RwLock::new((0u64, 0u64));
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..5_000 {
+                        let mut guard = lock.write().unwrap();
+                        guard.0 += 1;
+                        guard.1 += 1;
+                    }
+                });
+            }
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..5_000 {
+                        let guard = lock.read().unwrap();
+                        assert_eq!(guard.0, guard.1);
+                    }
+                });
+            }
+        });
+        assert_eq!(lock.into_inner().unwrap(), (20_000, 20_000));
+    }
+
+    #[test]
+    fn spin_lock() {
+        let mut lock = SpinLock::new(String::from("a"));
+        {
+            let mut guard = lock.lock();
+            guard.push('b');
+            assert!(lock.is_locked());
+            assert!(lock.try_lock().is_none());
+            assert_eq!(format!("{lock:?}"), "SpinLock { data: <locked>, .. }");
+        }
+        lock.get_mut().push('c');
+        assert_eq!(format!("{lock:?}"), r#"SpinLock { data: "abc", .. }"#);
+        let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _guard = lock.lock();
+            panic!("boom");
+        }));
+        assert!(!lock.is_locked());
+        assert_eq!(lock.into_inner(), "abc");
+    }
+
+    #[test]
+    fn spin_lock_contended_increments() {
+        let lock = SpinLock::new((0u64, 0u64));
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..10_000 {
+                        let mut guard = lock.lock();
+                        guard.0 += 1;
+                        guard.1 = guard.0 * 2;
+                    }
+                });
+            }
+        });
+        assert_eq!(lock.into_inner(), (80_000, 160_000));
+    }
+
+    #[test]
+    fn semaphore_permits() {
+        let sem = Semaphore::new(3);
+        let two = sem.try_acquire_many(2).unwrap();
+        assert_eq!(two.num_permits(), 2);
+        assert!(sem.try_acquire_many(2).is_none());
+        let one = sem.acquire();
+        assert!(sem.try_acquire().is_none());
+        drop(two);
+        assert_eq!(sem.available_permits(), 2);
+        one.forget();
+        assert_eq!(sem.available_permits(), 2);
+        sem.add_permits(1);
+        assert_eq!(format!("{sem:?}"), "Semaphore { permits: 3, .. }");
+    }
+
+    #[test]
+    fn semaphore_wakes_waiters() {
+        let sem = Semaphore::new(0);
+        thread::scope(|s| {
+            let many = s.spawn(|| sem.acquire_many(3).num_permits());
+            let one = s.spawn(|| sem.acquire().num_permits());
+            thread::sleep(Duration::from_millis(10));
+            sem.add_permits(4);
+            assert_eq!(many.join().unwrap() + one.join().unwrap(), 4);
+        });
+        assert_eq!(sem.available_permits(), 4);
+    }
+
+    #[test]
+    fn semaphore_bounds_concurrency() {
+        let sem = Semaphore::new(3);
+        let inside = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..200 {
+                        let _permit = sem.acquire();
+                        let now = inside.fetch_add(1, SeqCst) + 1;
+                        peak.fetch_max(now, SeqCst);
+                        thread::yield_now();
+                        inside.fetch_sub(1, SeqCst);
+                    }
+                });
+            }
+        });
+        assert!(peak.into_inner() <= 3);
+        assert_eq!(sem.available_permits(), 3);
+    }
+
+    #[test]
+    fn condvar_producer_consumer() {
+        let queue = Mutex::new(VecDeque::new());
+        let changed = Condvar::new();
+        let received = thread::scope(|s| {
+            let consumer = s.spawn(|| {
+                let mut received = Vec::new();
+                while received.last() != Some(&99) {
+                    let mut guard = changed
+                        .wait_while(queue.lock().unwrap(), |q| q.is_empty())
+                        .unwrap();
+                    received.extend(guard.drain(..));
+                }
+                received
+            });
+            for i in 0..100 {
+                queue.lock().unwrap().push_back(i);
+                changed.notify_all();
+            }
+            consumer.join().unwrap()
+        });
+        assert_eq!(received, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn condvar_timeouts() {
+        let lock = Mutex::new(0);
+        let changed = Condvar::new();
+        let (guard, result) = changed
+            .wait_timeout(lock.lock().unwrap(), Duration::from_millis(10))
+            .unwrap();
+        assert!(result.timed_out());
+        let (guard, result) = changed
+            .wait_timeout_while(guard, Duration::from_millis(10), |n| *n == 0)
+            .unwrap();
+        assert!(result.timed_out());
+        drop(guard);
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(10));
+                *lock.lock().unwrap() = 1;
+                changed.notify_one();
+            });
+            let (guard, result) = changed
+                .wait_timeout_while(lock.lock().unwrap(), Duration::from_secs(10), |n| *n == 0)
+                .unwrap();
+            assert!(!result.timed_out());
+            assert_eq!(*guard, 1);
+        });
+    }
+
+    #[test]
+    fn condvar_wait_keeps_poison() {
+        let lock = Mutex::new(0);
+        let changed = Condvar::new();
+        let _ = panic::catch_unwind(|| {
+            let _guard = lock.lock().unwrap();
+            panic!("boom");
+        });
+        let guard = lock.lock().unwrap_err().into_inner();
+        let err = changed
+            .wait_timeout(guard, Duration::from_millis(1))
+            .unwrap_err();
+        assert!(err.get_ref().1.timed_out());
+    }
+
+    #[test]
+    fn barrier_separates_phases() {
+        const THREADS: usize = 4;
+        let barrier = Barrier::new(THREADS);
+        let arrived = AtomicUsize::new(0);
+        let leaders = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(|| {
+                    for phase in 1..=50 {
+                        arrived.fetch_add(1, SeqCst);
+                        if barrier.wait().is_leader() {
+                            leaders.fetch_add(1, SeqCst);
+                        }
+                        // Nobody starts the next phase before all finished
+                        // this one.
+                        assert!(arrived.load(SeqCst) >= phase * THREADS);
+                        barrier.wait();
+                    }
+                });
+            }
+        });
+        assert_eq!(leaders.into_inner(), 50);
+        assert!(Barrier::new(1).wait().is_leader());
+        assert!(Barrier::new(0).wait().is_leader());
+    }
+
+    #[test]
+    fn once_runs_one_closure() {
+        let once = Once::new();
+        let runs = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    once.call_once(|| {
+                        thread::sleep(Duration::from_millis(5));
+                        runs.fetch_add(1, SeqCst);
+                    });
+                    // Nobody returns before the closure finished.
+                    assert_eq!(runs.load(SeqCst), 1);
+                });
+            }
+        });
+        assert!(once.is_completed());
+        once.call_once_force(|_| unreachable!());
+    }
+
+    #[test]
+    fn once_poisoned_by_panic() {
+        let once = Once::new();
+        assert!(panic::catch_unwind(|| once.call_once(|| panic!("boom"))).is_err());
+        assert!(!once.is_completed());
+        let err = panic::catch_unwind(|| once.call_once(|| {})).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<&str>(),
+            Some(&"Once instance has previously been poisoned")
+        );
+        let mut saw_poison = false;
+        once.call_once_force(|state| saw_poison = state.is_poisoned());
+        assert!(saw_poison);
+        assert!(once.is_completed());
+        once.call_once(|| unreachable!());
+    }
+
+    #[test]
+    fn atomic_arc_swaps_snapshots() {
+        let slot = AtomicArc::new(MyArc::new(vec![1]));
+        let before = slot.load();
+        assert_eq!(*slot.swap(MyArc::new(vec![2])), [1]);
+        // Snapshots taken earlier stay valid.
+        assert_eq!(*before, [1]);
+        slot.store(MyArc::new(vec![3]));
+        assert_eq!(*slot.rcu(|v| v.iter().map(|x| x * 10).collect()), [3]);
+        assert_eq!(format!("{slot:?}"), "AtomicArc([30])");
+        assert_eq!(*slot.into_inner(), [30]);
+    }
+
+    #[test]
+    fn atomic_arc_concurrent_rcu() {
+        let slot = AtomicArc::new(MyArc::new((0usize, 0usize)));
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..500 {
+                        slot.rcu(|&(a, b)| (a + 1, b + 2));
+                        let (a, b) = *slot.load();
+                        assert_eq!(b, 2 * a);
+                    }
+                });
+            }
+        });
+        assert_eq!(*slot.load(), (2_000, 4_000));
+    }
+
+    #[test]
+    fn scoped_threads_borrow_locals() {
+        let mut numbers: Vec<u64> = (1..=100).collect();
+        let evens = Mutex::new(0);
+        let sums: Vec<u64> = scope(|s| {
+            let handles: Vec<_> = numbers
+                .chunks(25)
+                .map(|chunk| {
+                    let evens = &evens;
+                    s.spawn(move || {
+                        *evens.lock().unwrap() += chunk.iter().filter(|n| *n % 2 == 0).count();
+                        chunk.iter().sum()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(sums.iter().sum::<u64>(), 5_050);
+        assert_eq!(evens.into_inner().unwrap(), 50);
+        // The borrows ended with the scope.
+        numbers.clear();
+    }
+
+    #[test]
+    fn scoped_pool_tasks_finish_before_the_scope_ends() {
+        let pool = ThreadPool::new(2);
+        let done = AtomicUsize::new(0);
+        scope(|s| {
+            for _ in 0..20 {
+                s.execute(&pool, || {
+                    thread::sleep(Duration::from_millis(1));
+                    done.fetch_add(1, Relaxed);
+                });
+            }
+        });
+        assert_eq!(done.load(Relaxed), 20);
+    }
+
+    #[test]
+    fn scope_reports_unjoined_panics() {
+        // A panic seen through `join` is the caller's to handle.
+        scope(|s| assert!(s.spawn(|| panic!("joined")).join().is_err()));
+
+        let result = panic::catch_unwind(|| {
+            scope(|s| {
+                s.spawn(|| panic!("not joined"));
+            })
+        });
+        assert!(result.is_err());
+
+        let pool = ThreadPool::new(1);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            scope(|s| s.execute(&pool, || panic!("task failed")));
+        }));
+        assert!(result.is_err());
+        assert_eq!(pool.panicked(), 0);
+    }
+}
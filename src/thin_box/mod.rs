@@ -0,0 +1,211 @@
+use ::alloc::alloc::{self, Layout};
+use core::{
+    fmt,
+    marker::{PhantomData, Unsize},
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull, Pointee},
+};
+
+/// An owned pointer to a heap value that is one machine word even for unsized
+/// values.
+///
+/// A `MyBox<dyn Trait>` or `MyBox<[T]>` is two words wide: the pointer plus a
+/// vtable pointer or length. A `ThinBox` stores that metadata in the
+/// allocation, right in front of the value, so the pointer itself is thin.
+/// Reaching the value costs one extra read, and in exchange every node holding
+/// a boxed trait object is a word smaller.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::Display;
+/// use std::mem::size_of;
+/// use my_std::thin_box::ThinBox;
+///
+/// let items: Vec<ThinBox<dyn Display>> =
+///     vec![ThinBox::new_unsize(1), ThinBox::new_unsize("two")];
+/// assert_eq!(items[1].to_string(), "two");
+/// assert_eq!(size_of::<ThinBox<dyn Display>>(), size_of::<usize>());
+///
+/// let slice: ThinBox<[u32]> = ThinBox::new_unsize([1, 2, 3]);
+/// assert_eq!(slice.iter().sum::<u32>(), 6);
+/// ```
+pub struct ThinBox<T: ?Sized> {
+    // Points at the value, with the metadata stored just before it.
+    ptr: NonNull<u8>,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for ThinBox<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for ThinBox<T> {}
+
+impl<T> ThinBox<T> {
+    /// Move `value` to the heap.
+    pub fn new(value: T) -> Self {
+        // Sized values have no metadata.
+        unsafe { Self::new_with_meta(value, ()) }
+    }
+}
+
+impl<Dyn: ?Sized> ThinBox<Dyn> {
+    /// Move `value` to the heap, as the unsized type `Dyn`, such as a trait
+    /// object it implements or a slice if it is an array.
+    pub fn new_unsize<T: Unsize<Dyn>>(value: T) -> Self {
+        let meta = ptr::metadata(&value as &Dyn);
+        unsafe { Self::new_with_meta(value, meta) }
+    }
+
+    // `meta` must be the metadata of `value` as a `Dyn`.
+    unsafe fn new_with_meta<T>(value: T, meta: <Dyn as Pointee>::Metadata) -> Self {
+        let (layout, offset) = Self::layout(Layout::new::<T>());
+        let ptr = if layout.size() == 0 {
+            // Nothing to store, but the pointer still has to be aligned.
+            NonNull::<u8>::without_provenance(layout.align().try_into().unwrap())
+        } else {
+            match NonNull::new(alloc::alloc(layout)) {
+                Some(ptr) => ptr.add(offset),
+                None => alloc::handle_alloc_error(layout),
+            }
+        };
+        ptr.cast::<<Dyn as Pointee>::Metadata>().sub(1).write(meta);
+        ptr.cast::<T>().write(value);
+        ThinBox {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    // Layout of the allocation for a value with `value_layout`, and the offset
+    // of the value in it.
+    fn layout(value_layout: Layout) -> (Layout, usize) {
+        let meta = Layout::new::<<Dyn as Pointee>::Metadata>();
+        // The value comes after the metadata, and its offset is a multiple of
+        // both alignments, so the metadata can sit right before it.
+        let (layout, offset) = meta.extend(value_layout).unwrap();
+        (layout.pad_to_align(), offset)
+    }
+
+    fn meta(&self) -> <Dyn as Pointee>::Metadata {
+        unsafe { self.ptr.cast::<<Dyn as Pointee>::Metadata>().sub(1).read() }
+    }
+
+    fn as_ptr(&self) -> *mut Dyn {
+        ptr::from_raw_parts_mut(self.ptr.as_ptr(), self.meta())
+    }
+}
+
+impl<T: ?Sized> Drop for ThinBox<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let value = self.as_ptr();
+            let (layout, offset) = Self::layout(Layout::for_value(&*value));
+            ptr::drop_in_place(value);
+            if layout.size() != 0 {
+                alloc::dealloc(self.ptr.as_ptr().sub(offset), layout);
+            }
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for ThinBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.as_ptr() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for ThinBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.as_ptr() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for ThinBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for ThinBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for ThinBox<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for ThinBox<T> {}
+
+#[cfg(test)]
+mod tests {
+    use ::alloc::{format, string::String, vec, vec::Vec};
+
+    use super::*;
+    use std::any::Any;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct Noisy(Rc<Cell<usize>>);
+
+    impl Drop for Noisy {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn trait_objects() {
+        let drops = Rc::new(Cell::new(0));
+        let mut values: Vec<ThinBox<dyn Any>> = vec![
+            ThinBox::new_unsize(1u8),
+            ThinBox::new_unsize(String::from("s")),
+            ThinBox::new_unsize(Noisy(drops.clone())),
+            ThinBox::new_unsize(()),
+        ];
+        values[1].downcast_mut::<String>().unwrap().push('!');
+        assert_eq!(values[0].downcast_ref::<u8>(), Some(&1));
+        assert_eq!(values[1].downcast_ref::<String>().unwrap(), "s!");
+        assert!(values[3].is::<()>());
+        drop(values);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn over_aligned_values() {
+        #[repr(align(64))]
+        #[derive(Debug, PartialEq)]
+        struct Aligned(u8);
+
+        let boxed: ThinBox<dyn fmt::Debug> = ThinBox::new_unsize(Aligned(7));
+        assert_eq!(format!("{boxed:?}"), "Aligned(7)");
+        let sized = ThinBox::new(Aligned(8));
+        assert_eq!(*sized, Aligned(8));
+        assert_eq!((&*sized as *const Aligned).addr() % 64, 0);
+    }
+
+    #[test]
+    fn one_word() {
+        use std::mem::size_of;
+        assert_eq!(size_of::<ThinBox<dyn Any>>(), size_of::<usize>());
+        assert_eq!(size_of::<ThinBox<[u8]>>(), size_of::<usize>());
+        assert_eq!(size_of::<Option<ThinBox<str>>>(), size_of::<usize>());
+    }
+
+    #[test]
+    fn slices() {
+        let drops = Rc::new(Cell::new(0));
+        let slice: ThinBox<[Noisy]> =
+            ThinBox::new_unsize([Noisy(drops.clone()), Noisy(drops.clone())]);
+        assert_eq!(slice.len(), 2);
+        drop(slice);
+        assert_eq!(drops.get(), 2);
+
+        let empty: ThinBox<[u64]> = ThinBox::new_unsize([]);
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::atomic::{AtomicUsize, Ordering::*},
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    arc::MyArc,
+    channel::{self, Sender},
+    sync::WaitQueue,
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+struct Shared {
+    // Tasks submitted and not finished yet, queued or running.
+    pending: AtomicUsize,
+    panicked: AtomicUsize,
+    // Threads in `join`, woken when `pending` drops to zero.
+    idle: WaitQueue,
+}
+
+/// A fixed number of worker threads running submitted tasks.
+///
+/// Tasks go through a bounded [`channel`] to whichever worker
+/// is free. When the queue is full, [`execute`](ThreadPool::execute) blocks
+/// until a worker takes a task, which keeps a fast producer from piling up
+/// unbounded work.
+///
+/// A task that panics doesn't take its worker down: the panic is caught,
+/// counted in [`panicked`](ThreadPool::panicked), and the worker moves on to
+/// the next task. Dropping the pool, or calling
+/// [`shutdown`](ThreadPool::shutdown), lets the workers finish every queued
+/// task, then waits for them to exit.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use my_std::arc::MyArc;
+/// use my_std::thread_pool::ThreadPool;
+///
+/// let pool = ThreadPool::new(4);
+/// let total = MyArc::new(AtomicUsize::new(0));
+/// for i in 1..=10 {
+///     let total = total.clone();
+///     pool.execute(move || {
+///         total.fetch_add(i, Ordering::Relaxed);
+///     });
+/// }
+/// pool.join();
+/// assert_eq!(total.load(Ordering::Relaxed), 55);
+/// ```
+pub struct ThreadPool {
+    // `None` once shutting down, which disconnects the workers.
+    sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    shared: MyArc<Shared>,
+}
+
+impl ThreadPool {
+    /// Start a pool of `threads` workers, with room for 64 queued tasks per
+    /// worker. Panics if `threads` is zero.
+    pub fn new(threads: usize) -> Self {
+        Self::with_queue_capacity(threads, threads * 64)
+    }
+
+    /// Start a pool of `threads` workers, with room for `capacity` queued
+    /// tasks. Panics if either is zero.
+    pub fn with_queue_capacity(threads: usize, capacity: usize) -> Self {
+        assert!(threads > 0, "a ThreadPool needs at least one thread");
+        let (sender, receiver) = channel::bounded::<Job>(capacity);
+        let shared = MyArc::new(Shared {
+            pending: AtomicUsize::new(0),
+            panicked: AtomicUsize::new(0),
+            idle: WaitQueue::new(),
+        });
+        let workers = (0..threads)
+            .map(|i| {
+                let receiver = receiver.clone();
+                let shared = shared.clone();
+                thread::Builder::new()
+                    .name(format!("pool-worker-{i}"))
+                    .spawn(move || {
+                        for job in receiver {
+                            if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                                shared.panicked.fetch_add(1, Relaxed);
+                            }
+                            // `Release` so `join` sees what the task did.
+                            if shared.pending.fetch_sub(1, Release) == 1 {
+                                shared.idle.wake_all();
+                            }
+                        }
+                    })
+                    .expect("failed to spawn a pool worker")
+            })
+            .collect();
+        ThreadPool {
+            sender: Some(sender),
+            workers,
+            shared,
+        }
+    }
+
+    /// Queue `task` to run on a worker, blocking while the queue is full.
+    pub fn execute<F: FnOnce() + Send + 'static>(&self, task: F) {
+        self.shared.pending.fetch_add(1, Relaxed);
+        // The workers only stop once the sender is gone.
+        let sender = self.sender.as_ref().unwrap();
+        sender
+            .send(Box::new(task))
+            .expect("pool workers exited early");
+    }
+
+    /// Block until every task submitted so far has finished.
+    pub fn join(&self) {
+        let shared = &*self.shared;
+        while shared.idle.wait_if(|| shared.pending.load(Acquire) > 0) {}
+    }
+
+    /// Number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Number of tasks not finished yet, queued or running.
+    pub fn pending(&self) -> usize {
+        self.shared.pending.load(Relaxed)
+    }
+
+    /// Number of tasks that panicked.
+    pub fn panicked(&self) -> usize {
+        self.shared.panicked.load(Relaxed)
+    }
+
+    /// Let the workers finish every queued task, then wait for them to exit.
+    /// Same as dropping the pool.
+    pub fn shutdown(self) {}
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            // Task panics are caught, so workers don't panic themselves.
+            let _ = worker.join();
+        }
+    }
+}
+
+impl fmt::Debug for ThreadPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThreadPool")
+            .field("threads", &self.threads())
+            .field("pending", &self.pending())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn runs_every_task() {
+        let pool = ThreadPool::with_queue_capacity(3, 2);
+        let done = MyArc::new(AtomicUsize::new(0));
+        for _ in 0..100 {
+            let done = done.clone();
+            pool.execute(move || {
+                done.fetch_add(1, Relaxed);
+            });
+        }
+        pool.join();
+        assert_eq!(done.load(Relaxed), 100);
+        assert_eq!(pool.pending(), 0);
+        assert_eq!(pool.threads(), 3);
+        // Joining an idle pool returns right away.
+        pool.join();
+    }
+
+    #[test]
+    fn panics_are_isolated() {
+        let pool = ThreadPool::new(1);
+        let done = MyArc::new(AtomicUsize::new(0));
+        pool.execute(|| panic!("task failed"));
+        let d = done.clone();
+        pool.execute(move || {
+            d.fetch_add(1, Relaxed);
+        });
+        pool.join();
+        assert_eq!(pool.panicked(), 1);
+        assert_eq!(done.load(Relaxed), 1);
+    }
+
+    #[test]
+    fn shutdown_drains_the_queue() {
+        let pool = ThreadPool::new(2);
+        let done = MyArc::new(AtomicUsize::new(0));
+        for _ in 0..10 {
+            let done = done.clone();
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(2));
+                done.fetch_add(1, Relaxed);
+            });
+        }
+        pool.shutdown();
+        assert_eq!(done.load(Relaxed), 10);
+    }
+}
@@ -0,0 +1,659 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::Hash;
+use core::{mem, slice};
+
+use crate::hashmap::HashMap;
+
+/// Number of entries above which a [`TinyMap`] should be promoted to a [`HashMap`].
+pub const PROMOTION_THRESHOLD: usize = 32;
+
+/// A map that stores its entries in a flat array and finds keys by linear scan.
+///
+/// For a handful of entries a linear scan over contiguous memory beats hashing:
+/// there is no hash to compute and no bucket indirection. Once the map grows past
+/// [`PROMOTION_THRESHOLD`] entries, convert it into the crate's [`HashMap`] with
+/// [`TinyMap::into_hash_map`].
+///
+/// Only `Eq` is required of the keys. Iteration follows insertion order until an
+/// entry is removed.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::tiny_map::TinyMap;
+///
+/// let mut map = TinyMap::new();
+/// map.insert("foo", 1);
+/// map.insert("bar", 2);
+/// assert_eq!(map.get("foo"), Some(&1));
+/// assert_eq!(map.remove("bar"), Some(2));
+///
+/// if map.should_promote() {
+///     let _map = map.into_hash_map();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TinyMap<K, V> {
+    entries: Vec<(K, V)>,
+}
+
+impl<K, V> Default for TinyMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Equal maps hold the same entries, whatever order removals left them in.
+impl<K: Eq, V: PartialEq> PartialEq for TinyMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for TinyMap<K, V> {}
+
+impl<K, V> TinyMap<K, V> {
+    /// Create a new, empty map.
+    pub const fn new() -> Self {
+        TinyMap {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Create a new, empty map with room for `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        TinyMap {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Return `true` if the map has grown large enough that a [`HashMap`] would be faster.
+    pub fn should_promote(&self) -> bool {
+        self.len() > PROMOTION_THRESHOLD
+    }
+
+    /// Keep only the entries for which `f` returns `true`, visiting them in
+    /// iteration order and keeping their order.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.entries.retain_mut(|(k, v)| f(k, v));
+    }
+
+    /// Return an iterator that removes and yields, in iteration order, every
+    /// entry for which `filter` returns `true`.
+    ///
+    /// Entries the iterator didn't get to before being dropped are kept. If it
+    /// is leaked, the map is left with only the entries kept until then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::tiny_map::TinyMap;
+    ///
+    /// let mut map: TinyMap<_, _> = [("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+    /// let odd: Vec<_> = map.extract_if(|_, v| *v % 2 == 1).collect();
+    /// assert_eq!(odd, [("a", 1), ("c", 3)]);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn extract_if<F>(&mut self, filter: F) -> ExtractIf<'_, K, V, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let rest = mem::take(&mut self.entries).into_iter();
+        self.entries.reserve(rest.len());
+        ExtractIf {
+            entries: &mut self.entries,
+            rest,
+            filter,
+        }
+    }
+
+    /// Iterate over the entries of the map in insertion order, except that
+    /// removing an entry moves the last one into its place.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Iterate over the entries of the map, in the same order as
+    /// [`TinyMap::iter`], with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+}
+
+impl<K, V> TinyMap<K, V>
+where
+    K: Eq,
+{
+    fn position<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.entries.iter().position(|(k, _)| k.borrow() == key)
+    }
+
+    /// Insert a key-value pair into the map.
+    /// Returns the old value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.position(&key) {
+            Some(index) => Some(mem::replace(&mut self.entries[index].1, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Get a reference to the value of a key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.position(key).map(|index| &self.entries[index].1)
+    }
+
+    /// Get a mutable reference to the value of a key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.position(key).map(|index| &mut self.entries[index].1)
+    }
+
+    /// Return `true` if the map contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.position(key).is_some()
+    }
+
+    /// Remove a key from the map.
+    /// Returns the value of the key if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        // swap_remove is O(1) because the order of the entries doesn't matter.
+        self.position(key)
+            .map(|index| self.entries.swap_remove(index).1)
+    }
+
+    /// Get the entry of a key, to read, insert or remove its value after a
+    /// single scan.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::tiny_map::TinyMap;
+    ///
+    /// let mut counts = TinyMap::new();
+    /// for c in "hello".chars() {
+    ///     *counts.entry(c).or_insert(0) += 1;
+    /// }
+    /// assert_eq!(counts.get(&'l'), Some(&2));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.position(&key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+
+    /// Convert the map into the crate's [`HashMap`], moving every entry.
+    pub fn into_hash_map(self) -> HashMap<K, V>
+    where
+        K: Hash,
+    {
+        let mut map = HashMap::new();
+        for (k, v) in self.entries {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+impl<K: Eq + Hash, V> From<TinyMap<K, V>> for HashMap<K, V> {
+    fn from(map: TinyMap<K, V>) -> Self {
+        map.into_hash_map()
+    }
+}
+
+/// An iterator removing the entries of a [`TinyMap`] that match a filter,
+/// returned by [`TinyMap::extract_if`].
+pub struct ExtractIf<'a, K, V, F> {
+    // The entries kept so far.
+    entries: &'a mut Vec<(K, V)>,
+    // The entries not looked at yet, taken out of the map.
+    rest: vec::IntoIter<(K, V)>,
+    filter: F,
+}
+
+impl<K, V, F> Iterator for ExtractIf<'_, K, V, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        for (k, mut v) in self.rest.by_ref() {
+            if (self.filter)(&k, &mut v) {
+                return Some((k, v));
+            }
+            self.entries.push((k, v));
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.rest.len()))
+    }
+}
+
+impl<K, V, F> Drop for ExtractIf<'_, K, V, F> {
+    fn drop(&mut self) {
+        self.entries.extend(self.rest.by_ref());
+    }
+}
+
+/// An entry of a [`TinyMap`], returned by [`TinyMap::entry`].
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// The key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    /// Get the value, inserting `default` first if the key is missing.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Get the value, inserting the result of `default` first if the key is missing.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// Like [`Entry::or_insert_with`], but `default` is passed the key.
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let value = default(&e.key);
+                e.insert(value)
+            }
+        }
+    }
+
+    /// Get the value, inserting the default value first if the key is missing.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Apply `f` to the value if the key is present.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+/// An entry of a [`TinyMap`] whose key is present.
+pub struct OccupiedEntry<'a, K, V> {
+    map: &'a mut TinyMap<K, V>,
+    index: usize,
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    /// The key of the entry.
+    pub fn key(&self) -> &K {
+        &self.map.entries[self.index].0
+    }
+
+    /// Get a reference to the value.
+    pub fn get(&self) -> &V {
+        &self.map.entries[self.index].1
+    }
+
+    /// Get a mutable reference to the value.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Turn the entry into a mutable reference to the value, borrowed from the map.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Replace the value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the entry from the map, returning the key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.entries.swap_remove(self.index)
+    }
+
+    /// Remove the entry from the map, returning the value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+/// An entry of a [`TinyMap`] whose key is missing.
+pub struct VacantEntry<'a, K, V> {
+    map: &'a mut TinyMap<K, V>,
+    key: K,
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    /// The key of the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Take back ownership of the key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Insert the key with `value`, returning a mutable reference to the value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.entries.push((self.key, value));
+        &mut self.map.entries.last_mut().unwrap().1
+    }
+}
+
+/// Iterator over the entries of a [`TinyMap`].
+pub struct Iter<'a, K, V> {
+    inner: slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Mutable iterator over the entries of a [`TinyMap`].
+pub struct IterMut<'a, K, V> {
+    inner: slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (&*k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a TinyMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut TinyMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> IntoIterator for TinyMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = alloc::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for TinyMap<K, V>
+where
+    K: Eq,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for TinyMap<K, V>
+where
+    K: Eq,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = TinyMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for TinyMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for TinyMap<K, V>
+where
+    K: serde::Deserialize<'de> + Eq,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for TinyMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Eq,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for TinyMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Eq,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert() {
+        let mut map = TinyMap::new();
+        assert_eq!(map.insert("foo", 42), None);
+        assert_eq!(map.insert("foo", 43), Some(42));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn eq_ignores_order() {
+        let mut a = TinyMap::new();
+        a.insert("x", 1);
+        a.insert("y", 2);
+        a.insert("z", 3);
+        a.remove("x");
+        let mut b = TinyMap::new();
+        b.insert("y", 2);
+        b.insert("z", 3);
+        assert!(a.iter().ne(b.iter()));
+        assert_eq!(a, b);
+        b.insert("z", 4);
+        assert_ne!(a, b);
+        b.remove("z");
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn get() {
+        let mut map = TinyMap::new();
+        map.insert("foo", 42);
+        assert_eq!(map.get("foo"), Some(&42));
+        assert_eq!(map.get("bar"), None);
+        *map.get_mut("foo").unwrap() += 1;
+        assert_eq!(map.get("foo"), Some(&43));
+    }
+
+    #[test]
+    fn remove() {
+        let mut map = TinyMap::new();
+        map.insert("foo", 42);
+        map.insert("bar", 43);
+        assert_eq!(map.remove("baz"), None);
+        assert_eq!(map.remove("foo"), Some(42));
+        assert_eq!(map.remove("foo"), None);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn entry() {
+        let mut map = TinyMap::new();
+        *map.entry("foo").or_insert(1) += 1;
+        map.entry("foo").and_modify(|v| *v *= 10).or_default();
+        assert_eq!(map.entry("bar").or_insert_with_key(|k| k.len()), &3);
+        assert_eq!(map.get("foo"), Some(&20));
+
+        match map.entry("foo") {
+            Entry::Occupied(mut e) => {
+                assert_eq!(e.key(), &"foo");
+                assert_eq!(e.get(), &20);
+                assert_eq!(e.insert(1), 20);
+                assert_eq!(e.remove_entry(), ("foo", 1));
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+        match map.entry("foo") {
+            Entry::Occupied(_) => unreachable!(),
+            Entry::Vacant(e) => assert_eq!(e.into_key(), "foo"),
+        }
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn iter() {
+        let mut map: TinyMap<_, _> = vec![("foo", 42), ("bar", 43)].into_iter().collect();
+        for (_, v) in map.iter_mut() {
+            *v += 1;
+        }
+        let mut iter = map.iter();
+        assert_eq!(iter.next(), Some((&"foo", &43)));
+        assert_eq!(iter.next(), Some((&"bar", &44)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn promote() {
+        let mut map = TinyMap::new();
+        for i in 0..PROMOTION_THRESHOLD {
+            map.insert(i, i * 2);
+        }
+        assert!(!map.should_promote());
+        map.insert(PROMOTION_THRESHOLD, 0);
+        assert!(map.should_promote());
+        let map: HashMap<_, _> = map.into();
+        assert_eq!(map.len(), PROMOTION_THRESHOLD + 1);
+        assert_eq!(map.get(&10), Some(&20));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: TinyMap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"2":20,"1":10}"#);
+        let back: TinyMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            back.iter().collect::<Vec<_>>(),
+            map.iter().collect::<Vec<_>>()
+        );
+    }
+}
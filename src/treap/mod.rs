@@ -0,0 +1,701 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::mem;
+use core::ops::{Bound, RangeBounds};
+use core::ptr;
+
+use crate::compat;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+/// Node of a treap.
+/// Nodes are ordered by key like a binary search tree and by priority like a
+/// max-heap.
+struct Node<K, V> {
+    key: K,
+    value: V,
+    priority: u64,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+fn size<K, V>(node: &Link<K, V>) -> usize {
+    node.as_ref().map_or(0, |node| node.size)
+}
+
+/// An ordered map based on a treap, a randomized balanced binary search tree.
+/// Every node gets a random priority and the tree is kept heap-ordered by
+/// priority, which gives an expected height of O(log n).
+///
+/// The [`Treap::split`] and [`Treap::merge`] primitives are public: they cut a
+/// map in two at a key and glue two maps back together in O(log n), which makes
+/// bulk operations on key ranges cheap.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::treap::Treap;
+///
+/// let mut map: Treap<_, _> = (0..10).map(|i| (i, i * 10)).collect();
+/// assert_eq!(map.get(&3), Some(&30));
+///
+/// let (low, high) = map.split(&5);
+/// assert_eq!(low.len(), 5);
+/// assert_eq!(high.first_key_value(), Some((&5, &50)));
+///
+/// let map = low.merge(high);
+/// assert_eq!(map.len(), 10);
+/// ```
+pub struct Treap<K, V> {
+    root: Link<K, V>,
+    // State of the xorshift generator used for node priorities.
+    seed: u64,
+}
+
+impl<K, V> Default for Treap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Treap<K, V> {
+    /// Create a new, empty treap.
+    pub fn new() -> Self {
+        // The seed must be non-zero for xorshift.
+        let seed = compat::random_seed() | 1;
+        Treap { root: None, seed }
+    }
+
+    /// Number of entries in the treap.
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Return `true` if the treap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Remove all entries from the treap.
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+
+    /// Iterate over the entries in ascending key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            remaining: self.len(),
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    /// Iterate over the entries in ascending key order, with mutable
+    /// references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut iter = IterMut {
+            stack: Vec::new(),
+            remaining: self.len(),
+        };
+        iter.push_left(self.root.as_deref_mut());
+        iter
+    }
+
+    fn next_priority(&mut self) -> u64 {
+        let mut x = self.seed;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed = x;
+        x
+    }
+
+    // Merge two subtrees where every key in `left` is less than every key in `right`.
+    fn merge_nodes(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+        match (left, right) {
+            (None, node) | (node, None) => node,
+            (Some(mut left), Some(mut right)) => {
+                if left.priority > right.priority {
+                    left.right = Self::merge_nodes(left.right.take(), Some(right));
+                    left.update_size();
+                    Some(left)
+                } else {
+                    right.left = Self::merge_nodes(Some(left), right.left.take());
+                    right.update_size();
+                    Some(right)
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> Treap<K, V>
+where
+    K: Ord,
+{
+    // Split a subtree into the keys less than `key` and the keys greater than or equal to it.
+    fn split_node<Q>(node: Link<K, V>, key: &Q) -> (Link<K, V>, Link<K, V>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = match node {
+            Some(node) => node,
+            None => return (None, None),
+        };
+        if node.key.borrow() < key {
+            let (left, right) = Self::split_node(node.right.take(), key);
+            node.right = left;
+            node.update_size();
+            (Some(node), right)
+        } else {
+            let (left, right) = Self::split_node(node.left.take(), key);
+            node.left = right;
+            node.update_size();
+            (left, Some(node))
+        }
+    }
+
+    /// Split the treap into two: the first holds every key less than `key`, the
+    /// second every key greater than or equal to `key`.
+    pub fn split<Q>(mut self, key: &Q) -> (Treap<K, V>, Treap<K, V>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (left, right) = Self::split_node(self.root.take(), key);
+        let mut high = Treap::new();
+        high.root = right;
+        self.root = left;
+        (self, high)
+    }
+
+    /// Concatenate two treaps.
+    /// Panics if the smallest key of `other` is not greater than the largest key of `self`.
+    pub fn merge(mut self, mut other: Treap<K, V>) -> Treap<K, V> {
+        if let (Some((last, _)), Some((first, _))) =
+            (self.last_key_value(), other.first_key_value())
+        {
+            if last >= first {
+                panic!("keys of the merged treap should be greater than the keys of this treap");
+            }
+        }
+        self.root = Self::merge_nodes(self.root.take(), other.root.take());
+        self
+    }
+
+    /// Insert a key-value pair into the treap.
+    /// Returns the old value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(mem::replace(old, value));
+        }
+        let node = Box::new(Node {
+            key,
+            value,
+            priority: self.next_priority(),
+            size: 1,
+            left: None,
+            right: None,
+        });
+        let (left, right) = Self::split_node(self.root.take(), &node.key);
+        let left = Self::merge_nodes(left, Some(node));
+        self.root = Self::merge_nodes(left, right);
+        None
+    }
+
+    /// Get a reference to the value of a key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => current = node.left.as_deref(),
+                Ordering::Greater => current = node.right.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+            }
+        }
+        None
+    }
+
+    /// Get a mutable reference to the value of a key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => current = node.left.as_deref_mut(),
+                Ordering::Greater => current = node.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+            }
+        }
+        None
+    }
+
+    /// Return `true` if the treap contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key from the treap.
+    /// Returns the value of the key if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut removed = None;
+        self.root = Self::remove_node(self.root.take(), key, &mut removed);
+        removed
+    }
+
+    fn remove_node<Q>(node: Link<K, V>, key: &Q, removed: &mut Option<V>) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = node?;
+        match key.cmp(node.key.borrow()) {
+            Ordering::Less => node.left = Self::remove_node(node.left.take(), key, removed),
+            Ordering::Greater => node.right = Self::remove_node(node.right.take(), key, removed),
+            Ordering::Equal => {
+                let node = *node;
+                *removed = Some(node.value);
+                return Self::merge_nodes(node.left, node.right);
+            }
+        }
+        node.update_size();
+        Some(node)
+    }
+
+    /// Get the entry with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Get the entry with the largest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Iterate over the entries whose keys fall in `range`, in ascending key order.
+    /// Panics if the start of the range is greater than its end.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+            (start, end)
+        {
+            assert!(s <= e, "range start is greater than range end");
+        }
+        let mut iter = Range {
+            front: Vec::new(),
+            back: Vec::new(),
+        };
+        // Each stack keeps the nodes on the path to its end of the range that
+        // are still to be visited from that end.
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            let key = current.key.borrow();
+            node = match start {
+                Bound::Included(s) if key < s => current.right.as_deref(),
+                Bound::Excluded(s) if key <= s => current.right.as_deref(),
+                _ => {
+                    iter.front.push(current);
+                    current.left.as_deref()
+                }
+            };
+        }
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            let key = current.key.borrow();
+            node = match end {
+                Bound::Included(e) if key > e => current.left.as_deref(),
+                Bound::Excluded(e) if key >= e => current.left.as_deref(),
+                _ => {
+                    iter.back.push(current);
+                    current.right.as_deref()
+                }
+            };
+        }
+        // The ends cross when no key falls in the range.
+        match (iter.front.last(), iter.back.last()) {
+            (Some(first), Some(last)) if first.key <= last.key => {}
+            _ => {
+                iter.front.clear();
+                iter.back.clear();
+            }
+        }
+        iter
+    }
+
+    /// Get the entry at position `index` in key order.
+    /// Runs in O(log n) thanks to the subtree sizes kept in every node.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        let mut index = index;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            let left = size(&node.left);
+            match index.cmp(&left) {
+                Ordering::Less => current = node.left.as_deref(),
+                Ordering::Equal => return Some((&node.key, &node.value)),
+                Ordering::Greater => {
+                    index -= left + 1;
+                    current = node.right.as_deref();
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Treap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the entries of a [`Treap`] in ascending key order.
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a Treap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over a range of entries of a [`Treap`] in ascending key order.
+pub struct Range<'a, K, V> {
+    // Like the stack of `Iter`, for the front and back of the range. The range
+    // is used up once both ends reach the same node.
+    front: Vec<&'a Node<K, V>>,
+    back: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front.pop()?;
+        if ptr::eq(node, *self.back.last().unwrap()) {
+            self.front.clear();
+            self.back.clear();
+        } else {
+            let mut next = node.right.as_deref();
+            while let Some(current) = next {
+                self.front.push(current);
+                next = current.left.as_deref();
+            }
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back.pop()?;
+        if ptr::eq(node, *self.front.last().unwrap()) {
+            self.front.clear();
+            self.back.clear();
+        } else {
+            let mut next = node.left.as_deref();
+            while let Some(current) = next {
+                self.back.push(current);
+                next = current.right.as_deref();
+            }
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+// A node whose left subtree is done, split into its entry and the right
+// subtree still to visit.
+type Pending<'a, K, V> = (&'a K, &'a mut V, Option<&'a mut Node<K, V>>);
+
+/// Mutable iterator over the entries of a [`Treap`] in ascending key order.
+pub struct IterMut<'a, K, V> {
+    stack: Vec<Pending<'a, K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> IterMut<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a mut Node<K, V>>) {
+        while let Some(current) = node {
+            let Node {
+                key,
+                value,
+                left,
+                right,
+                ..
+            } = current;
+            self.stack.push((key, value, right.as_deref_mut()));
+            node = left.as_deref_mut();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value, right) = self.stack.pop()?;
+        self.push_left(right);
+        self.remaining -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut Treap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for Treap<K, V>
+where
+    K: Ord,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Treap<K, V>
+where
+    K: Ord,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Treap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for Treap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for Treap<K, V>
+where
+    K: serde::Deserialize<'de> + Ord,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for Treap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for Treap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Ord,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &(K, V), b: &(K, V)| a.0.cmp(&b.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn keys<K: Copy, V>(treap: &Treap<K, V>) -> Vec<K> {
+        treap.iter().map(|(k, _)| *k).collect()
+    }
+
+    fn height<K, V>(node: &Link<K, V>) -> usize {
+        match node {
+            Some(node) => 1 + height(&node.left).max(height(&node.right)),
+            None => 0,
+        }
+    }
+
+    #[test]
+    fn insert() {
+        let mut map = Treap::new();
+        assert_eq!(map.insert("foo", 42), None);
+        assert_eq!(map.insert("foo", 43), Some(42));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("foo"), Some(&43));
+    }
+
+    #[test]
+    fn remove() {
+        let mut map: Treap<_, _> = (0..100).map(|i| (i, i * 10)).collect();
+        for i in (0..100).step_by(3) {
+            assert_eq!(map.remove(&i), Some(i * 10));
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 66);
+        let expected: Vec<_> = (0..100).filter(|i| i % 3 != 0).collect();
+        assert_eq!(keys(&map), expected);
+    }
+
+    #[test]
+    fn split() {
+        let map: Treap<_, _> = (0..20).map(|i| (i * 2, ())).collect();
+        let (low, high) = map.split(&11);
+        assert_eq!(keys(&low), vec![0, 2, 4, 6, 8, 10]);
+        assert_eq!(high.len(), 14);
+        assert_eq!(high.first_key_value(), Some((&12, &())));
+
+        let (low, high) = low.split(&0);
+        assert!(low.is_empty());
+        assert_eq!(high.len(), 6);
+    }
+
+    #[test]
+    fn merge() {
+        let low: Treap<_, _> = (0..50).map(|i| (i, ())).collect();
+        let high: Treap<_, _> = (50..100).map(|i| (i, ())).collect();
+        let mut map = low.merge(high);
+        assert_eq!(keys(&map), (0..100).collect::<Vec<_>>());
+        map.insert(100, ());
+        assert_eq!(map.get_index(100), Some((&100, &())));
+    }
+
+    #[test]
+    #[should_panic(expected = "keys of the merged treap should be greater")]
+    fn merge_overlapping() {
+        let low: Treap<_, _> = (0..10).map(|i| (i, ())).collect();
+        let high: Treap<_, _> = (5..15).map(|i| (i, ())).collect();
+        low.merge(high);
+    }
+
+    #[test]
+    fn range_removal() {
+        // Cut out the keys in 30..60 with two splits and a merge.
+        let map: Treap<_, _> = (0..100).map(|i| (i, ())).collect();
+        let (low, rest) = map.split(&30);
+        let (_, high) = rest.split(&60);
+        let map = low.merge(high);
+        assert_eq!(map.len(), 70);
+        assert!(!map.contains_key(&45));
+        assert_eq!(map.get_index(30), Some((&60, &())));
+    }
+
+    #[test]
+    fn balanced() {
+        let map: Treap<_, _> = (0..10_000).map(|i| (i, ())).collect();
+        // The expected height is about 3 log2(n); allow plenty of slack.
+        assert!(height(&map.root) < 80);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: Treap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"1":10,"2":20}"#);
+        let back: Treap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            back.iter().collect::<Vec<_>>(),
+            map.iter().collect::<Vec<_>>()
+        );
+    }
+}
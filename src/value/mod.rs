@@ -0,0 +1,651 @@
+use alloc::string::String;
+use core::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use crate::{index_map::IndexMap, my_vec::MyVec, string::MyString};
+
+/// A dynamically typed, JSON-like document.
+///
+/// Arrays are [`MyVec`]s and maps are [`IndexMap`]s from [`MyString`] keys,
+/// so maps remember the order their keys were inserted in and print in it.
+/// Indexing with a string looks up a map key and indexing with a `usize` an
+/// array element. A lookup that finds nothing gives [`Value::Null`], so
+/// chains like `value["a"][0]` never panic; [`Value::pointer`] tells missing
+/// and null apart. Indexing mutably with a string inserts the key, turning a
+/// null into an empty map first, so documents can be built up by assignment.
+/// `Display` writes JSON.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::value::Value;
+///
+/// let mut doc = Value::Null;
+/// doc["name"] = "widget".into();
+/// doc["sizes"] = [1, 2, 3].into_iter().map(Value::from).collect();
+/// doc["dims"]["w"] = 2.5.into();
+///
+/// assert_eq!(doc["sizes"][1], 2);
+/// assert_eq!(doc["missing"][7], Value::Null);
+/// assert_eq!(doc.pointer("/dims/w").and_then(Value::as_f64), Some(2.5));
+/// assert_eq!(
+///     doc.to_string(),
+///     r#"{"name":"widget","sizes":[1,2,3],"dims":{"w":2.5}}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub enum Value {
+    #[default]
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(MyString),
+    Array(MyVec<Value>),
+    Map(IndexMap<MyString, Value>),
+}
+
+const NULL: &Value = &Value::Null;
+
+impl Value {
+    // Name of the variant, for panic messages.
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Str(_) => "string",
+            Value::Array(_) => "array",
+            Value::Map(_) => "map",
+        }
+    }
+
+    /// Return `true` if the value is `Null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// The boolean, if the value is a `Bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// The integer, if the value is an `Int`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Int(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// The number, if the value is a `Float` or an `Int`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Float(x) => Some(x),
+            Value::Int(i) => Some(i as f64),
+            _ => None,
+        }
+    }
+
+    /// The string, if the value is a `Str`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The elements, if the value is an `Array`.
+    pub fn as_array(&self) -> Option<&MyVec<Value>> {
+        match self {
+            Value::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// The elements mutably, if the value is an `Array`.
+    pub fn as_array_mut(&mut self) -> Option<&mut MyVec<Value>> {
+        match self {
+            Value::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// The entries, if the value is a `Map`.
+    pub fn as_map(&self) -> Option<&IndexMap<MyString, Value>> {
+        match self {
+            Value::Map(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// The entries mutably, if the value is a `Map`.
+    pub fn as_map_mut(&mut self) -> Option<&mut IndexMap<MyString, Value>> {
+        match self {
+            Value::Map(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    // The value one step down `segment` of a path: a map key, or an array
+    // position written in decimal.
+    fn child(&self, segment: &str) -> Option<&Value> {
+        match self {
+            Value::Map(m) => m.get(segment),
+            Value::Array(a) => a.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        }
+    }
+
+    fn child_mut(&mut self, segment: &str) -> Option<&mut Value> {
+        match self {
+            Value::Map(m) => m.get_mut(segment),
+            Value::Array(a) => a.get_mut(segment.parse::<usize>().ok()?),
+            _ => None,
+        }
+    }
+
+    /// Look up a value by a JSON Pointer path like `/a/0/b`, or `None` if
+    /// there is nothing there. The empty path is the value itself. As in
+    /// JSON Pointer, `~1` in a segment stands for `/` and `~0` for `~`.
+    pub fn pointer(&self, path: &str) -> Option<&Value> {
+        segments(path)?.try_fold(self, |value, segment| value.child(&segment))
+    }
+
+    /// Like [`Value::pointer`], but returns a mutable reference.
+    pub fn pointer_mut(&mut self, path: &str) -> Option<&mut Value> {
+        segments(path)?.try_fold(self, |value, segment| value.child_mut(&segment))
+    }
+}
+
+// The unescaped segments of a JSON Pointer, or `None` if it is malformed.
+fn segments(path: &str) -> Option<impl Iterator<Item = String> + '_> {
+    if !path.is_empty() && !path.starts_with('/') {
+        return None;
+    }
+    Some(
+        path.split('/')
+            .skip(1)
+            .map(|s| s.replace("~1", "/").replace("~0", "~")),
+    )
+}
+
+impl Index<&str> for Value {
+    type Output = Value;
+
+    /// The value under `key`, or `Null` if there is none or this is not a
+    /// map.
+    fn index(&self, key: &str) -> &Value {
+        match self {
+            Value::Map(m) => m.get(key).unwrap_or(NULL),
+            _ => NULL,
+        }
+    }
+}
+
+impl IndexMut<&str> for Value {
+    /// The value under `key`, inserted as `Null` if there is none. A `Null`
+    /// becomes an empty map first. Panics if this is any other non-map.
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        if self.is_null() {
+            *self = Value::Map(IndexMap::new());
+        }
+        match self {
+            Value::Map(m) => m.entry(key.into()).or_insert(Value::Null),
+            other => panic!("cannot index into a {} with a string", other.kind()),
+        }
+    }
+}
+
+impl Index<usize> for Value {
+    type Output = Value;
+
+    /// The element at `index`, or `Null` if there is none or this is not an
+    /// array.
+    fn index(&self, index: usize) -> &Value {
+        match self {
+            Value::Array(a) => a.get(index).unwrap_or(NULL),
+            _ => NULL,
+        }
+    }
+}
+
+impl IndexMut<usize> for Value {
+    /// The element at `index`. Panics if this is not an array or the index is
+    /// out of bounds.
+    fn index_mut(&mut self, index: usize) -> &mut Value {
+        match self {
+            Value::Array(a) => {
+                let len = a.len();
+                a.get_mut(index).unwrap_or_else(|| {
+                    panic!("index out of bounds: the len is {len} but the index is {index}")
+                })
+            }
+            other => panic!("cannot index into a {} with a number", other.kind()),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    /// Values are equal if they are the same variant with equal contents.
+    /// Maps compare their entries regardless of order, and an `Int` never
+    /// equals a `Float`.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => a[..] == b[..],
+            (Value::Map(a), Value::Map(b)) => {
+                a.len() == b.len() && a.iter().all(|(k, v)| b.get(k) == Some(v))
+            }
+            _ => false,
+        }
+    }
+}
+
+macro_rules! impl_eq_scalar {
+    ($($t:ty => $variant:ident)*) => {$(
+        impl PartialEq<$t> for Value {
+            fn eq(&self, other: &$t) -> bool {
+                matches!(self, Value::$variant(v) if *v == *other)
+            }
+        }
+    )*};
+}
+
+impl_eq_scalar!(bool => Bool i64 => Int f64 => Float &str => Str);
+
+impl PartialEq<i32> for Value {
+    fn eq(&self, other: &i32) -> bool {
+        *self == i64::from(*other)
+    }
+}
+
+macro_rules! impl_from {
+    ($($t:ty => $variant:ident)*) => {$(
+        impl From<$t> for Value {
+            fn from(v: $t) -> Self {
+                Value::$variant(v.into())
+            }
+        }
+    )*};
+}
+
+impl_from! {
+    bool => Bool
+    i8 => Int i16 => Int i32 => Int i64 => Int u8 => Int u16 => Int u32 => Int
+    f32 => Float f64 => Float
+    &str => Str String => Str MyString => Str char => Str
+    MyVec<Value> => Array
+    IndexMap<MyString, Value> => Map
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    /// `Null` for `None`, otherwise the converted value.
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Value::Null, Into::into)
+    }
+}
+
+impl FromIterator<Value> for Value {
+    /// Collect an `Array`.
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        Value::Array(iter.into_iter().collect())
+    }
+}
+
+impl<K: Into<MyString>> FromIterator<(K, Value)> for Value {
+    /// Collect a `Map`. A key that appears twice keeps its last value.
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(iter: I) -> Self {
+        let mut map = IndexMap::new();
+        for (key, value) in iter {
+            map.insert(key.into(), value);
+        }
+        Value::Map(map)
+    }
+}
+
+// Write `s` as a JSON string literal.
+fn write_json_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Value {
+    /// Write the value as compact JSON. Floats that are not finite have no
+    /// JSON form and are written as `null`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Int(i) => write!(f, "{i}"),
+            Value::Float(x) if x.is_finite() => write!(f, "{x}"),
+            Value::Float(_) => f.write_str("null"),
+            Value::Str(s) => write_json_str(f, s),
+            Value::Array(a) => {
+                f.write_str("[")?;
+                for (i, v) in a.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                f.write_str("]")
+            }
+            Value::Map(m) => {
+                f.write_str("{")?;
+                for (i, (k, v)) in m.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_json_str(f, k)?;
+                    write!(f, ":{v}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// A value serializes as the matching serde data model type, so it converts
+/// to and from any self-describing format such as JSON.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Int(i) => serializer.serialize_i64(*i),
+            Value::Float(x) => serializer.serialize_f64(*x),
+            Value::Str(s) => serializer.serialize_str(s),
+            Value::Array(a) => serializer.collect_seq(a),
+            Value::Map(m) => serializer.collect_map(m.iter()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Value {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+        use serde::de::{Deserialize, Error, MapAccess, SeqAccess, Unexpected, Visitor};
+
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a JSON-like value")
+            }
+
+            fn visit_unit<E: Error>(self) -> Result<Value, E> {
+                Ok(Value::Null)
+            }
+
+            fn visit_none<E: Error>(self) -> Result<Value, E> {
+                Ok(Value::Null)
+            }
+
+            fn visit_some<D: serde::Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Value, D::Error> {
+                Value::deserialize(deserializer)
+            }
+
+            fn visit_bool<E: Error>(self, b: bool) -> Result<Value, E> {
+                Ok(Value::Bool(b))
+            }
+
+            fn visit_i64<E: Error>(self, i: i64) -> Result<Value, E> {
+                Ok(Value::Int(i))
+            }
+
+            fn visit_u64<E: Error>(self, u: u64) -> Result<Value, E> {
+                i64::try_from(u).map(Value::Int).map_err(|_| {
+                    E::invalid_value(Unexpected::Unsigned(u), &"an integer in the i64 range")
+                })
+            }
+
+            fn visit_f64<E: Error>(self, x: f64) -> Result<Value, E> {
+                Ok(Value::Float(x))
+            }
+
+            fn visit_str<E: Error>(self, s: &str) -> Result<Value, E> {
+                Ok(Value::Str(s.into()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Value, A::Error> {
+                MyVec::deserialize(SeqAccessDeserializer::new(seq)).map(Value::Array)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Value, A::Error> {
+                IndexMap::deserialize(MapAccessDeserializer::new(map)).map(Value::Map)
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Value {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        arbitrary_value(u, 8)
+    }
+}
+
+// A value whose arrays and maps nest at most `depth` levels.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_value(u: &mut arbitrary::Unstructured<'_>, depth: usize) -> arbitrary::Result<Value> {
+    let kinds = if depth == 0 { 5 } else { 7 };
+    Ok(match u.choose_index(kinds)? {
+        0 => Value::Null,
+        1 => Value::Bool(u.arbitrary()?),
+        2 => Value::Int(u.arbitrary()?),
+        3 => Value::Float(u.arbitrary()?),
+        4 => Value::Str(u.arbitrary()?),
+        5 => {
+            let len = u.arbitrary_len::<Value>()?;
+            let values = (0..len).map(|_| arbitrary_value(u, depth - 1));
+            Value::Array(values.collect::<arbitrary::Result<_>>()?)
+        }
+        _ => {
+            let len = u.arbitrary_len::<(MyString, Value)>()?;
+            let entries = (0..len).map(|_| Ok((u.arbitrary()?, arbitrary_value(u, depth - 1)?)));
+            Value::Map(entries.collect::<arbitrary::Result<_>>()?)
+        }
+    })
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Value {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    /// Arrays and maps nest at most 8 levels and shrink towards scalars.
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        use proptest::{
+            arbitrary::any, collection::vec, prop_oneof, strategy::Just, strategy::Strategy,
+        };
+
+        let leaf = prop_oneof![
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::Bool),
+            any::<i64>().prop_map(Value::Int),
+            any::<f64>().prop_map(Value::Float),
+            any::<MyString>().prop_map(Value::Str),
+        ];
+        leaf.prop_recursive(8, 64, 8, |inner| {
+            prop_oneof![
+                vec(inner.clone(), 0..8)
+                    .prop_map(|values| Value::Array(values.into_iter().collect())),
+                vec((any::<MyString>(), inner), 0..8)
+                    .prop_map(|entries| Value::Map(entries.into_iter().collect())),
+            ]
+        })
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    fn doc() -> Value {
+        let mut doc = Value::Null;
+        doc["a"] = [Value::from(1), "x".into(), Value::Null]
+            .into_iter()
+            .collect();
+        doc["a/b"]["~"] = true.into();
+        doc
+    }
+
+    #[test]
+    fn index() {
+        let mut doc = doc();
+        assert_eq!(doc["a"][0], 1);
+        assert_eq!(doc["a"][1], "x");
+        assert!(doc["a"][2].is_null());
+        assert!(doc["a"][3].is_null());
+        assert!(doc["b"]["c"][0].is_null());
+        assert!(doc[0].is_null());
+        doc["a"][0] = 2.5.into();
+        assert_eq!(doc["a"][0].as_f64(), Some(2.5));
+        assert_eq!(doc["a"].as_array().map(|a| a.len()), Some(3));
+        assert_eq!(doc.as_map().map(|m| m.len()), Some(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index into a bool with a string")]
+    fn index_mut_wrong_kind() {
+        let mut v = Value::from(true);
+        v["a"] = Value::Null;
+    }
+
+    #[test]
+    #[should_panic(expected = "the len is 3 but the index is 3")]
+    fn index_mut_out_of_bounds() {
+        doc()["a"][3] = Value::Null;
+    }
+
+    #[test]
+    fn pointer() {
+        let mut doc = doc();
+        assert_eq!(doc.pointer(""), Some(&doc));
+        assert_eq!(doc.pointer("/a/1"), Some(&Value::from("x")));
+        assert_eq!(doc.pointer("/a/2"), Some(&Value::Null));
+        assert_eq!(doc.pointer("/a/3"), None);
+        assert_eq!(doc.pointer("/a/x"), None);
+        assert_eq!(doc.pointer("a"), None);
+        assert_eq!(doc.pointer("/a~1b/~0"), Some(&Value::from(true)));
+        *doc.pointer_mut("/a/0").unwrap() = Value::from(Some("y"));
+        assert_eq!(doc["a"][0], "y");
+    }
+
+    #[test]
+    fn equality_and_conversions() {
+        let a: Value = [("x", Value::from(1)), ("y", Value::from(None::<i32>))]
+            .into_iter()
+            .collect();
+        let b: Value = [("y", Value::Null), ("x", Value::from(1u8))]
+            .into_iter()
+            .collect();
+        assert_eq!(a, b);
+        assert_ne!(Value::from(1), Value::from(1.0));
+        assert_ne!(Value::from(1), Value::from("1"));
+        assert_eq!(Value::from(1).as_f64(), Some(1.0));
+        assert_eq!(Value::from('c').as_str(), Some("c"));
+        assert_eq!(Value::from(false).as_bool(), Some(false));
+        assert_eq!(Value::from(7).as_i64(), Some(7));
+        assert_eq!(Value::default(), Value::Null);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(doc().to_string(), r#"{"a":[1,"x",null],"a/b":{"~":true}}"#);
+        let s = Value::from("quote \" slash \\ \n \u{1}");
+        assert_eq!(s.to_string(), r#""quote \" slash \\ \n \u0001""#);
+        let floats: Value = [f64::NAN, -0.5, 1e21]
+            .into_iter()
+            .map(Value::from)
+            .collect();
+        assert_eq!(floats.to_string(), "[null,-0.5,1000000000000000000000]");
+        assert_eq!(Value::from(IndexMap::new()).to_string(), "{}");
+        assert_eq!(Value::from(MyVec::new()).to_string(), "[]");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let doc = doc();
+        let json = serde_json::to_string(&doc).unwrap();
+        assert_eq!(json, doc.to_string());
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), doc);
+        assert_eq!(
+            serde_json::from_str::<Value>("[null, 1.5]").unwrap()[1],
+            1.5
+        );
+        assert!(serde_json::from_str::<Value>("18446744073709551615").is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_nests() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        fn depth(value: &Value) -> usize {
+            match value {
+                Value::Array(values) => 1 + values.iter().map(depth).max().unwrap_or(0),
+                Value::Map(entries) => 1 + entries.values().map(depth).max().unwrap_or(0),
+                _ => 0,
+            }
+        }
+
+        // A run of the same byte keeps picking the same kind, here arrays.
+        let bytes = [6; 1024];
+        let value = Value::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert_eq!(depth(&value), 8);
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn proptest_shrinks() {
+        use proptest::{
+            arbitrary::any,
+            test_runner::{TestError, TestRunner},
+        };
+
+        let result = TestRunner::deterministic().run(&any::<Value>(), |value| {
+            proptest::prop_assert!(!matches!(value, Value::Array(_)));
+            Ok(())
+        });
+        match result {
+            Err(TestError::Fail(_, value)) => assert_eq!(value, Value::Array(MyVec::new())),
+            result => panic!("expected a failure, got {result:?}"),
+        }
+    }
+}
@@ -0,0 +1,603 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::ops::{Bound, Index, RangeBounds};
+use core::{fmt, mem, slice};
+
+/// An ordered map backed by a sorted `Vec` of entries.
+///
+/// Lookups are a binary search, and iteration walks a slice, which beats tree
+/// maps on both speed and memory as long as the map is read far more often than
+/// it is changed: inserting or removing shifts every later entry, so it takes
+/// O(n). The best way to build one is all at once, with
+/// [`VecMap::from_sorted_iter`] or by collecting an iterator.
+///
+/// # Examples
+///
+/// ```
+/// use my_std::vec_map::VecMap;
+///
+/// let map: VecMap<_, _> = [(3, "c"), (1, "a"), (2, "b")].into_iter().collect();
+/// assert_eq!(map.get(&2), Some(&"b"));
+/// assert_eq!(map.as_slice(), &[(1, "a"), (2, "b"), (3, "c")]);
+///
+/// let tail: Vec<_> = map.range(2..).map(|(_, v)| *v).collect();
+/// assert_eq!(tail, vec!["b", "c"]);
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize))]
+pub struct VecMap<K, V> {
+    // Sorted by key, without duplicates.
+    entries: Vec<(K, V)>,
+}
+
+impl<K, V> Default for VecMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> VecMap<K, V> {
+    /// Create a new, empty map.
+    pub const fn new() -> Self {
+        VecMap {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Create a new, empty map with room for `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        VecMap {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The entries, sorted by key.
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.entries
+    }
+
+    /// Keep only the entries for which `f` returns `true`, visiting them in
+    /// ascending key order.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.entries.retain_mut(|(k, v)| f(k, v));
+    }
+
+    /// Return an iterator that removes and yields, in ascending key order, every
+    /// entry for which `filter` returns `true`.
+    ///
+    /// Entries the iterator didn't get to before being dropped are kept. If it
+    /// is leaked, the map is left with only the entries kept until then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_std::vec_map::VecMap;
+    ///
+    /// let mut map: VecMap<_, _> = (0..6).map(|i| (i, i * 10)).collect();
+    /// let small: Vec<_> = map.extract_if(|k, _| *k < 2).collect();
+    /// assert_eq!(small, [(0, 0), (1, 10)]);
+    /// assert_eq!(map.first_key_value(), Some((&2, &20)));
+    /// ```
+    pub fn extract_if<F>(&mut self, filter: F) -> ExtractIf<'_, K, V, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let rest = mem::take(&mut self.entries).into_iter();
+        self.entries.reserve(rest.len());
+        ExtractIf {
+            entries: &mut self.entries,
+            rest,
+            filter,
+        }
+    }
+
+    /// Iterate over the entries in ascending key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Iterate over the entries in ascending key order, with mutable values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+
+    /// Iterate over the keys in ascending order.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over the values in ascending key order.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    /// Get the entry with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.entries.first().map(|(k, v)| (k, v))
+    }
+
+    /// Get the entry with the largest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.entries.last().map(|(k, v)| (k, v))
+    }
+}
+
+impl<K: Ord, V> VecMap<K, V> {
+    /// Build a map from entries already sorted by key, in O(n).
+    /// Panics if the keys are not strictly ascending.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let entries: Vec<_> = iter.into_iter().collect();
+        assert!(
+            entries.windows(2).all(|w| w[0].0 < w[1].0),
+            "keys should be strictly ascending"
+        );
+        VecMap { entries }
+    }
+
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries.binary_search_by(|(k, _)| k.borrow().cmp(key))
+    }
+
+    /// Insert a key-value pair into the map. O(n).
+    /// Returns the old value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.search(&key) {
+            Ok(index) => Some(mem::replace(&mut self.entries[index].1, value)),
+            Err(index) => {
+                self.entries.insert(index, (key, value));
+                None
+            }
+        }
+    }
+
+    /// Get a reference to the value of a key. O(log n).
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    /// Get the entry of a key. O(log n).
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (k, v) = &self.entries[self.search(key).ok()?];
+        Some((k, v))
+    }
+
+    /// Get a mutable reference to the value of a key. O(log n).
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.search(key).ok()?;
+        Some(&mut self.entries[index].1)
+    }
+
+    /// Return `true` if the map contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).is_ok()
+    }
+
+    /// Remove a key from the map, returning its value if it was present. O(n).
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.search(key).ok()?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Iterate over the entries whose keys fall in `range`, in ascending key order.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(s) => self.entries.partition_point(|(k, _)| k.borrow() < s),
+            Bound::Excluded(s) => self.entries.partition_point(|(k, _)| k.borrow() <= s),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(e) => self.entries.partition_point(|(k, _)| k.borrow() <= e),
+            Bound::Excluded(e) => self.entries.partition_point(|(k, _)| k.borrow() < e),
+            Bound::Unbounded => self.entries.len(),
+        };
+        Iter {
+            inner: self.entries[start..end.max(start)].iter(),
+        }
+    }
+}
+
+impl<K, V, Q> Index<&Q> for VecMap<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    /// Panics if the key is not in the map.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key should be in the map")
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for VecMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for VecMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let old = mem::take(&mut self.entries);
+        // Later entries win, like repeated inserts, so they go first into the
+        // stable sort and the dedup keeps the first of each key.
+        let mut entries: Vec<_> = iter.into_iter().collect();
+        entries.reverse();
+        entries.extend(old);
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.dedup_by(|a, b| a.0 == b.0);
+        self.entries = entries;
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for VecMap<K, V> {
+    /// Build a map from entries in any order, in O(n log n).
+    /// If a key is repeated, the last entry wins.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = VecMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+/// An iterator removing the entries of a [`VecMap`] that match a filter,
+/// returned by [`VecMap::extract_if`].
+pub struct ExtractIf<'a, K, V, F> {
+    // The entries kept so far.
+    entries: &'a mut Vec<(K, V)>,
+    // The entries not looked at yet, taken out of the map.
+    rest: vec::IntoIter<(K, V)>,
+    filter: F,
+}
+
+impl<K, V, F> Iterator for ExtractIf<'_, K, V, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        for (k, mut v) in self.rest.by_ref() {
+            if (self.filter)(&k, &mut v) {
+                return Some((k, v));
+            }
+            self.entries.push((k, v));
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.rest.len()))
+    }
+}
+
+impl<K, V, F> Drop for ExtractIf<'_, K, V, F> {
+    fn drop(&mut self) {
+        self.entries.extend(self.rest.by_ref());
+    }
+}
+
+/// Iterator over the entries of a [`VecMap`] in ascending key order.
+pub struct Iter<'a, K, V> {
+    inner: slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, v)| (k, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// Mutable iterator over the entries of a [`VecMap`] in ascending key order.
+pub struct IterMut<'a, K, V> {
+    inner: slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (&*k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, v)| (&*k, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a VecMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut VecMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> IntoIterator for VecMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = alloc::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for VecMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for VecMap<K, V>
+where
+    K: serde::Deserialize<'de> + Ord,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_impl::deserialize_map(deserializer)
+    }
+}
+
+/// The archive of a [`VecMap`] keeps its entries sorted, so it answers lookups
+/// with a binary search in place, without deserializing.
+#[cfg(feature = "rkyv")]
+impl<K: rkyv::Archive, V: rkyv::Archive> ArchivedVecMap<K, V> {
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get a reference to the archived value of a key. O(log n).
+    pub fn get<Q: ?Sized>(&self, key: &Q) -> Option<&V::Archived>
+    where
+        K::Archived: PartialOrd<Q>,
+    {
+        // The keys are `Ord`, so their archives compare with every key and
+        // `partial_cmp` never gives `None`.
+        self.entries
+            .binary_search_by(|entry| {
+                entry
+                    .0
+                    .partial_cmp(key)
+                    .unwrap_or(core::cmp::Ordering::Less)
+            })
+            .ok()
+            .map(|i| &self.entries[i].1)
+    }
+
+    /// Iterate over the archived entries in ascending order of their keys.
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (&K::Archived, &V::Archived)> + ExactSizeIterator {
+        self.entries.iter().map(|entry| (&entry.0, &entry.1))
+    }
+}
+
+/// Deserializing sorts the entries again, so a map read back from a corrupt
+/// archive still keeps its keys in order.
+#[cfg(feature = "rkyv")]
+impl<K, V, D> rkyv::Deserialize<VecMap<K, V>, D> for ArchivedVecMap<K, V>
+where
+    K: rkyv::Archive + Ord,
+    V: rkyv::Archive,
+    K::Archived: rkyv::Deserialize<K, D>,
+    V::Archived: rkyv::Deserialize<V, D>,
+    D: rkyv::rancor::Fallible + ?Sized,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<VecMap<K, V>, D::Error> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                Ok((
+                    entry.0.deserialize(deserializer)?,
+                    entry.1.deserialize(deserializer)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for VecMap<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter::<(K, V)>()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter::<(K, V)>()?.collect()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<K, V> proptest::arbitrary::Arbitrary for VecMap<K, V>
+where
+    K: proptest::arbitrary::Arbitrary + 'static + Ord,
+    V: proptest::arbitrary::Arbitrary + 'static,
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <(K, V) as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::proptest_impl::collect_ordered(args, |a: &(K, V), b: &(K, V)| a.0.cmp(&b.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn matches_btree_map() {
+        let mut map = VecMap::new();
+        let mut model = BTreeMap::new();
+        for i in 0..500u32 {
+            let key = i.wrapping_mul(2_654_435_761) % 97;
+            if i % 3 == 2 {
+                assert_eq!(map.remove(&key), model.remove(&key));
+            } else {
+                assert_eq!(map.insert(key, i), model.insert(key, i));
+            }
+        }
+        assert!(map.iter().eq(model.iter()));
+        assert!(map.range(10..=40).eq(model.range(10..=40)));
+        assert!(map.range(50..).rev().eq(model.range(50..).rev()));
+        assert_eq!(map.first_key_value(), model.first_key_value());
+    }
+
+    #[test]
+    fn collect_keeps_last_value() {
+        let mut map: VecMap<_, _> = [(2, 'a'), (1, 'b'), (2, 'c')].into_iter().collect();
+        assert_eq!(map.as_slice(), &[(1, 'b'), (2, 'c')]);
+        map.extend([(1, 'd'), (3, 'e')]);
+        assert_eq!(map.as_slice(), &[(1, 'd'), (2, 'c'), (3, 'e')]);
+        for (_, v) in map.iter_mut() {
+            *v = v.to_ascii_uppercase();
+        }
+        assert_eq!(map[&3], 'E');
+    }
+
+    #[test]
+    fn from_sorted_iter() {
+        let map = VecMap::from_sorted_iter((0..5).map(|i| (i, i * i)));
+        assert_eq!(map.get(&4), Some(&16));
+        assert_eq!(map.len(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "keys should be strictly ascending")]
+    fn from_unsorted_iter() {
+        VecMap::from_sorted_iter([(1, ()), (1, ())]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map: VecMap<i32, i32> = [(2, 20), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"1":10,"2":20}"#);
+        let back: VecMap<i32, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            back.iter().collect::<Vec<_>>(),
+            map.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn rkyv_round_trip() {
+        use alloc::string::{String, ToString};
+        use rkyv::rancor::Error;
+
+        let map: VecMap<String, u32> = [("b", 2), ("a", 1), ("c", 3)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+        let archived = rkyv::access::<ArchivedVecMap<String, u32>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 3);
+        assert_eq!(archived.get("b").map(|v| v.to_native()), Some(2));
+        assert_eq!(archived.get("d"), None);
+        let keys: Vec<_> = archived.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["a", "b", "c"]);
+        let back: VecMap<String, u32> = rkyv::deserialize::<_, Error>(archived).unwrap();
+        assert!(back == map);
+    }
+}
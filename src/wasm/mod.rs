@@ -0,0 +1,339 @@
+use alloc::{collections, string::String, vec::Vec};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{cache::LruCache, hashmap::HashMap, linked_list::LinkedList, my_vec::MyVec};
+
+/// A [`LinkedList`] of JavaScript values, exported as `LinkedList`.
+#[wasm_bindgen(js_name = LinkedList)]
+#[derive(Default)]
+pub struct JsLinkedList(LinkedList<JsValue>);
+
+#[wasm_bindgen(js_class = LinkedList)]
+impl JsLinkedList {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.0.len()
+    }
+
+    #[wasm_bindgen(js_name = isEmpty)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn push(&mut self, value: JsValue) {
+        self.0.push(value);
+    }
+
+    /// Remove the last value pushed, or return `undefined` if the list is
+    /// empty.
+    pub fn pop(&mut self) -> JsValue {
+        self.0.pop().unwrap_or_default()
+    }
+
+    /// Insert `value` after the value at `index`.
+    /// Throws if `index` is out of bounds.
+    pub fn insert(&mut self, index: usize, value: JsValue) -> Result<(), JsError> {
+        check_index(index, self.0.len())?;
+        self.0.insert(index, value);
+        Ok(())
+    }
+
+    /// Throws if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Result<JsValue, JsError> {
+        check_index(index, self.0.len())?;
+        Ok(self.0.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// The values from the first pushed to the last.
+    #[wasm_bindgen(js_name = toArray)]
+    pub fn to_array(&self) -> Vec<JsValue> {
+        collections::LinkedList::from(self.0.clone())
+            .into_iter()
+            .collect()
+    }
+}
+
+/// A [`MyVec`] of JavaScript values, exported as `Vec`.
+#[wasm_bindgen(js_name = Vec)]
+pub struct JsVec(MyVec<JsValue>);
+
+#[wasm_bindgen(js_class = Vec)]
+impl JsVec {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        JsVec(MyVec::new())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.0.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    #[wasm_bindgen(js_name = isEmpty)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The value at `index`, or `undefined` if it is out of bounds.
+    pub fn get(&self, index: usize) -> JsValue {
+        self.0.get(index).cloned().unwrap_or_default()
+    }
+
+    /// Throws if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: JsValue) -> Result<(), JsError> {
+        check_index(index, self.0.len())?;
+        self.0[index] = value;
+        Ok(())
+    }
+
+    pub fn push(&mut self, value: JsValue) {
+        self.0.push(value);
+    }
+
+    /// Remove the last value, or return `undefined` if the vector is empty.
+    pub fn pop(&mut self) -> JsValue {
+        self.0.pop().unwrap_or_default()
+    }
+
+    /// Throws if `index` is greater than the length.
+    pub fn insert(&mut self, index: usize, value: JsValue) -> Result<(), JsError> {
+        check_index(index, self.0.len() + 1)?;
+        self.0.insert(index, value);
+        Ok(())
+    }
+
+    /// Throws if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Result<JsValue, JsError> {
+        check_index(index, self.0.len())?;
+        Ok(self.0.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    #[wasm_bindgen(js_name = toArray)]
+    pub fn to_array(&self) -> Vec<JsValue> {
+        self.0.to_vec()
+    }
+}
+
+impl Default for JsVec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`HashMap`] from strings to JavaScript values, exported as `HashMap`.
+/// Its methods follow those of a JavaScript `Map`, and throw if a key is not a
+/// string.
+#[wasm_bindgen(js_name = HashMap)]
+#[derive(Default)]
+pub struct JsHashMap(HashMap<String, JsValue>);
+
+#[wasm_bindgen(js_class = HashMap)]
+impl JsHashMap {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The value for `key`, or `undefined` if there is none.
+    pub fn get(&self, key: &JsValue) -> Result<JsValue, JsError> {
+        Ok(self.0.get(&string_key(key)?).cloned().unwrap_or_default())
+    }
+
+    /// Return the value `key` had before, or `undefined`.
+    pub fn set(&mut self, key: &JsValue, value: JsValue) -> Result<JsValue, JsError> {
+        Ok(self.0.insert(string_key(key)?, value).unwrap_or_default())
+    }
+
+    pub fn has(&self, key: &JsValue) -> Result<bool, JsError> {
+        Ok(self.0.contains_key(&string_key(key)?))
+    }
+
+    /// Return the removed value, or `undefined` if there was none.
+    pub fn delete(&mut self, key: &JsValue) -> Result<JsValue, JsError> {
+        Ok(self.0.remove(&string_key(key)?).unwrap_or_default())
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// The keys, in no particular order.
+    pub fn keys(&self) -> Vec<JsValue> {
+        self.0.iter().map(|(key, _)| key.into()).collect()
+    }
+
+    /// The values, in the same order as [`keys`](Self::keys).
+    pub fn values(&self) -> Vec<JsValue> {
+        self.0.iter().map(|(_, value)| value.clone()).collect()
+    }
+}
+
+/// An [`LruCache`] from strings to JavaScript values, exported as `LruCache`.
+/// Its methods throw if a key is not a string.
+#[wasm_bindgen(js_name = LruCache)]
+pub struct JsLruCache(LruCache<String, JsValue>);
+
+#[wasm_bindgen(js_class = LruCache)]
+impl JsLruCache {
+    /// Throws if `capacity` is zero.
+    #[wasm_bindgen(constructor)]
+    pub fn new(capacity: usize) -> Result<JsLruCache, JsError> {
+        if capacity == 0 {
+            return Err(JsError::new(
+                "an LruCache needs room for at least one entry",
+            ));
+        }
+        Ok(JsLruCache(LruCache::new(capacity)))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.0.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// The value for `key`, or `undefined` if there is none. A hit marks the
+    /// entry as the most recently used.
+    pub fn get(&mut self, key: &JsValue) -> Result<JsValue, JsError> {
+        Ok(self.0.get(&string_key(key)?).cloned().unwrap_or_default())
+    }
+
+    /// Like [`get`](Self::get), but leaves the order of the entries alone.
+    pub fn peek(&self, key: &JsValue) -> Result<JsValue, JsError> {
+        Ok(self.0.peek(&string_key(key)?).cloned().unwrap_or_default())
+    }
+
+    /// Insert or update an entry, evicting the least recently used one if the
+    /// cache is full. Return the value `key` had before, or `undefined`.
+    pub fn put(&mut self, key: &JsValue, value: JsValue) -> Result<JsValue, JsError> {
+        Ok(self.0.put(string_key(key)?, value).unwrap_or_default())
+    }
+
+    pub fn has(&self, key: &JsValue) -> Result<bool, JsError> {
+        Ok(self.0.contains_key(&string_key(key)?))
+    }
+
+    /// Return the removed value, or `undefined` if there was none.
+    pub fn delete(&mut self, key: &JsValue) -> Result<JsValue, JsError> {
+        Ok(self.0.remove(&string_key(key)?).unwrap_or_default())
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// The keys, from the most to the least recently used.
+    pub fn keys(&self) -> Vec<JsValue> {
+        self.0.iter().map(|(key, _)| key.into()).collect()
+    }
+}
+
+fn check_index(index: usize, len: usize) -> Result<(), JsError> {
+    if index < len {
+        Ok(())
+    } else {
+        Err(JsError::new(&alloc::format!(
+            "index (is {index}) should be < {len}"
+        )))
+    }
+}
+
+fn string_key(key: &JsValue) -> Result<String, JsError> {
+    key.as_string()
+        .ok_or_else(|| JsError::new("key should be a string"))
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    #[wasm_bindgen_test]
+    fn linked_list() {
+        let mut list = JsLinkedList::new();
+        list.push(1.into());
+        list.push(2.into());
+        list.insert(0, 3.into()).unwrap();
+        assert_eq!(list.length(), 3);
+        assert_eq!(list.to_array(), [JsValue::from(1), 3.into(), 2.into()]);
+        assert_eq!(list.pop(), 2);
+        assert!(list.remove(2).is_err());
+        assert_eq!(list.remove(0).unwrap(), 1);
+        list.clear();
+        assert!(list.is_empty());
+        assert!(list.pop().is_undefined());
+    }
+
+    #[wasm_bindgen_test]
+    fn vec() {
+        let mut vec = JsVec::new();
+        vec.push("a".into());
+        vec.insert(1, "b".into()).unwrap();
+        vec.set(0, "c".into()).unwrap();
+        assert_eq!(vec.to_array(), [JsValue::from("c"), "b".into()]);
+        assert!(vec.get(2).is_undefined());
+        assert!(vec.set(2, "d".into()).is_err());
+        assert!(vec.insert(3, "d".into()).is_err());
+        assert_eq!(vec.remove(0).unwrap(), "c");
+        assert_eq!(vec.pop(), "b");
+        assert!(vec.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn hash_map() {
+        let mut map = JsHashMap::new();
+        assert!(map.set(&"a".into(), 1.into()).unwrap().is_undefined());
+        assert_eq!(map.set(&"a".into(), 2.into()).unwrap(), 1);
+        assert_eq!(map.get(&"a".into()).unwrap(), 2);
+        assert!(map.has(&"a".into()).unwrap());
+        assert_eq!(map.keys(), [JsValue::from("a")]);
+        assert!(map.get(&1.into()).is_err());
+        assert_eq!(map.delete(&"a".into()).unwrap(), 2);
+        assert_eq!(map.size(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn lru_cache() {
+        assert!(JsLruCache::new(0).is_err());
+        let mut cache = JsLruCache::new(2).unwrap();
+        cache.put(&"a".into(), 1.into()).unwrap();
+        cache.put(&"b".into(), 2.into()).unwrap();
+        assert_eq!(cache.get(&"a".into()).unwrap(), 1);
+        cache.put(&"c".into(), 3.into()).unwrap();
+        assert_eq!(cache.keys(), [JsValue::from("c"), "a".into()]);
+        assert!(!cache.has(&"b".into()).unwrap());
+        assert_eq!(cache.peek(&"a".into()).unwrap(), 1);
+        assert!(cache.put(&1.into(), 1.into()).is_err());
+        assert_eq!(cache.delete(&"c".into()).unwrap(), 3);
+        assert_eq!((cache.size(), cache.capacity()), (1, 2));
+    }
+}
@@ -0,0 +1,243 @@
+use alloc::rc::{Rc, Weak};
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::Hash;
+
+use crate::hashmap::{self, HashMap};
+
+// Number of entries below which inserts never purge.
+const MIN_PURGE_AT: usize = 16;
+
+/// A hash map holding its values through [`Weak`] references.
+///
+/// The map never keeps a value alive: once the last [`Rc`] to a value is dropped,
+/// its entry is dead and lookups no longer see it. Dead entries are removed by
+/// [`WeakValueMap::purge`], which inserts also run whenever the map has doubled
+/// in size since the last purge, so the cost is amortized O(1) per insert.
+///
+/// A typical use is a canonicalizing cache, which hands out one shared copy of
+/// each value for as long as anyone uses it.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use my_std::weak_map::WeakValueMap;
+///
+/// let mut cache = WeakValueMap::new();
+/// let a = cache.get_or_insert_with("config", || vec![1, 2, 3]);
+/// let b = cache.get_or_insert_with("config", || unreachable!());
+/// assert!(Rc::ptr_eq(&a, &b));
+///
+/// drop(a);
+/// drop(b);
+/// assert_eq!(cache.get("config"), None);
+/// cache.purge();
+/// assert!(cache.is_empty());
+/// ```
+pub struct WeakValueMap<K, V> {
+    map: HashMap<K, Weak<V>>,
+    // Size at which the next insert purges dead entries.
+    purge_at: usize,
+}
+
+impl<K, V> Default for WeakValueMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> WeakValueMap<K, V> {
+    /// Create a new, empty map.
+    pub fn new() -> Self {
+        WeakValueMap {
+            map: HashMap::new(),
+            purge_at: MIN_PURGE_AT,
+        }
+    }
+
+    /// Number of entries, including dead ones that haven't been purged yet.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the map holds no entry, dead or alive.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Remove every entry.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Remove the entries whose value has been dropped.
+    pub fn purge(&mut self) {
+        self.map.retain(|_, value| value.strong_count() > 0);
+        self.purge_at = MIN_PURGE_AT.max(2 * self.map.len());
+    }
+
+    /// Iterate over the live entries, in no particular order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.map.iter(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> WeakValueMap<K, V> {
+    /// Insert a weak reference to `value` under `key`.
+    /// Returns the previous value if it was still alive.
+    pub fn insert(&mut self, key: K, value: &Rc<V>) -> Option<Rc<V>> {
+        if self.map.len() >= self.purge_at {
+            self.purge();
+        }
+        self.map
+            .insert(key, Rc::downgrade(value))
+            .and_then(|old| old.upgrade())
+    }
+
+    /// Get the value of a key, if it is still alive.
+    pub fn get<Q>(&self, key: &Q) -> Option<Rc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.map.get(key)?.upgrade()
+    }
+
+    /// Return `true` if the key has a live value.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.map
+            .get(key)
+            .is_some_and(|value| value.strong_count() > 0)
+    }
+
+    /// Get the value of a key, or create it with `f` and insert it if the key is
+    /// missing or its value is dead.
+    pub fn get_or_insert_with<F>(&mut self, key: K, f: F) -> Rc<V>
+    where
+        F: FnOnce() -> V,
+    {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = Rc::new(f());
+        self.insert(key, &value);
+        value
+    }
+
+    /// Remove a key from the map, returning its value if it was still alive.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Rc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.map.remove(key)?.upgrade()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for WeakValueMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the live entries of a [`WeakValueMap`], in no particular
+/// order.
+pub struct Iter<'a, K, V> {
+    inner: hashmap::Iter<'a, K, Weak<V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, Rc<V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .find_map(|(key, value)| Some((key, value.upgrade()?)))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a WeakValueMap<K, V> {
+    type Item = (&'a K, Rc<V>);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: Eq + Hash, V: 'a> Extend<(K, &'a Rc<V>)> for WeakValueMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, &'a Rc<V>)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K: Eq + Hash, V: 'a> FromIterator<(K, &'a Rc<V>)> for WeakValueMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, &'a Rc<V>)>>(iter: I) -> Self {
+        let mut map = WeakValueMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+
+    use super::*;
+
+    #[test]
+    fn values_are_not_kept_alive() {
+        let mut map = WeakValueMap::new();
+        let one = Rc::new(1);
+        let two = Rc::new(2);
+        map.insert("one", &one);
+        map.insert("two", &two);
+        assert_eq!(map.get("one").as_deref(), Some(&1));
+        assert_eq!(Rc::strong_count(&one), 1);
+
+        drop(one);
+        assert!(!map.contains_key("one"));
+        assert!(map.contains_key("two"));
+        assert_eq!(map.iter().count(), 1);
+        assert_eq!(map.len(), 2);
+        map.purge();
+        assert_eq!(map.len(), 1);
+
+        let new_two = Rc::new(22);
+        assert_eq!(map.insert("two", &new_two), Some(two));
+        assert_eq!(map.remove("two"), Some(new_two));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn inserts_purge_dead_entries() {
+        let mut map = WeakValueMap::new();
+        for i in 0..10_000 {
+            // Every value dies right away.
+            map.insert(i, &Rc::new(i));
+        }
+        assert!(map.len() <= 2 * MIN_PURGE_AT);
+
+        let kept: Vec<_> = (0..100).map(Rc::new).collect();
+        map.extend(kept.iter().map(|v| (**v, v)));
+        map.purge();
+        assert_eq!(map.len(), 100);
+    }
+
+    #[test]
+    fn dead_values_are_recreated() {
+        let mut map = WeakValueMap::new();
+        let first = map.get_or_insert_with(1, || String::from("a"));
+        drop(first);
+        let second = map.get_or_insert_with(1, || String::from("b"));
+        assert_eq!(*second, "b");
+    }
+}